@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use clap::ValueEnum;
+
+use crate::backend::{Backend, Target};
+use crate::codeowners::CodeOwners;
+
+/// How `kit affected --group-by` aggregates targets.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum GroupBy {
+    /// CODEOWNERS owner of the target's directory. Targets with several owners count once for each.
+    Owner,
+    /// Backend that reported the target.
+    Backend,
+    /// First directory component of the target's path.
+    TopDir,
+}
+
+const UNOWNED: &str = "(unowned)";
+
+/// Affected targets from every backend, paired with the name of the backend that reported them.
+pub fn collect<'a>(
+    backends: &[&'a dyn Backend],
+    repo_root: &Path,
+    base: &str,
+    changed_files: &[PathBuf],
+) -> Vec<(&'a str, Target)> {
+    backends
+        .iter()
+        .flat_map(|b| {
+            b.affected_targets(repo_root, base, changed_files)
+                .into_iter()
+                .map(move |t| (b.name(), t))
+        })
+        .collect()
+}
+
+/// The changed files in a target's directory or below it, as the changes that made it affected.
+/// Targets affected only through what they depend on have none.
+pub fn triggers(repo_root: &Path, target: &Target, changed_files: &[PathBuf]) -> Vec<PathBuf> {
+    let dir = target.dir.strip_prefix(repo_root).unwrap_or(&target.dir);
+    changed_files.iter().filter(|f| f.starts_with(dir)).cloned().collect()
+}
+
+/// Count targets per group, largest groups first.
+pub fn group(repo_root: &Path, targets: &[(&str, Target)], by: GroupBy) -> Result<Vec<(String, usize)>> {
+    let codeowners = match by {
+        GroupBy::Owner => CodeOwners::load(repo_root)?,
+        _ => None,
+    };
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for (backend, target) in targets {
+        let rel = target.dir.strip_prefix(repo_root).unwrap_or(&target.dir);
+        let keys: Vec<String> = match by {
+            GroupBy::Backend => vec![backend.to_string()],
+            GroupBy::TopDir => vec![
+                rel.components()
+                    .next()
+                    .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                    .unwrap_or_else(|| ".".to_string()),
+            ],
+            GroupBy::Owner => match codeowners.as_ref().and_then(|co| co.dir_owners(repo_root, rel)) {
+                Some(owners) if !owners.is_empty() => owners.to_vec(),
+                _ => vec![UNOWNED.to_string()],
+            },
+        };
+        for key in keys {
+            *counts.entry(key).or_default() += 1;
+        }
+    }
+    let mut groups: Vec<(String, usize)> = counts.into_iter().collect();
+    groups.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(groups)
+}
+
+#[cfg(test)]
+#[path = "affected_test.rs"]
+mod tests;
@@ -0,0 +1,72 @@
+use super::*;
+use tempfile::TempDir;
+
+fn target(root: &Path, dir: &str) -> Target {
+    Target {
+        label: format!("./{dir}/..."),
+        dir: root.join(dir),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn group_by_top_dir_and_backend() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    let targets = vec![
+        ("go", target(root, "pkg/db")),
+        ("go", target(root, "pkg/api")),
+        ("pnpm", target(root, "web/ui")),
+        ("go", target(root, "")),
+    ];
+    assert_eq!(
+        group(root, &targets, GroupBy::TopDir).unwrap(),
+        vec![("pkg".to_string(), 2), (".".to_string(), 1), ("web".to_string(), 1)]
+    );
+    assert_eq!(
+        group(root, &targets, GroupBy::Backend).unwrap(),
+        vec![("go".to_string(), 3), ("pnpm".to_string(), 1)]
+    );
+}
+
+#[test]
+fn group_by_owner() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    std::fs::write(
+        root.join("CODEOWNERS"),
+        "/pkg/ @org/backend\n/pkg/db/ @org/db @org/dba\n",
+    )
+    .unwrap();
+    let targets = vec![
+        ("go", target(root, "pkg/db")),
+        ("go", target(root, "pkg/api")),
+        ("go", target(root, "tools")),
+    ];
+    assert_eq!(
+        group(root, &targets, GroupBy::Owner).unwrap(),
+        vec![
+            ("(unowned)".to_string(), 1),
+            ("@org/backend".to_string(), 1),
+            ("@org/db".to_string(), 1),
+            ("@org/dba".to_string(), 1),
+        ]
+    );
+}
+
+#[test]
+fn triggers_are_the_changed_files_in_the_target() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    let changed = vec![
+        PathBuf::from("pkg/db/schema.sql"),
+        PathBuf::from("pkg/dbtools/main.go"),
+        PathBuf::from("README.md"),
+    ];
+    assert_eq!(
+        triggers(root, &target(root, "pkg/db"), &changed),
+        [PathBuf::from("pkg/db/schema.sql")]
+    );
+    assert_eq!(triggers(root, &target(root, ""), &changed).len(), 3);
+    assert!(triggers(root, &target(root, "web"), &changed).is_empty());
+}
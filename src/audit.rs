@@ -0,0 +1,90 @@
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::state;
+
+/// A command run on targets: the one `kit audit-affected` runs on every target, or the one `kit list`
+/// lists the targets of.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum AuditCommand {
+    Build,
+    Test,
+    Lint,
+}
+
+impl AuditCommand {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Build => "build",
+            Self::Test => "test",
+            Self::Lint => "lint",
+        }
+    }
+}
+
+/// A target of the full run and whether it passed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ran {
+    pub backend: String,
+    pub label: String,
+    pub passed: bool,
+}
+
+/// How the affected set compared with a full run at the same commit, as recorded in
+/// `.kit/history/audit.jsonl`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Audit {
+    pub commit: String,
+    pub base: String,
+    pub command: String,
+    /// Targets in the full run.
+    pub targets: usize,
+    /// Of those, the ones the changes affect.
+    pub affected: usize,
+    /// Failing targets the changes affect, which an affected-only run catches.
+    pub caught: Vec<String>,
+    /// Failing targets the changes don't affect, which an affected-only run misses.
+    pub missed: Vec<String>,
+}
+
+/// Compare the full run with the affected set, both as `backend label` pairs.
+pub fn compare(ran: &[Ran], affected: &BTreeSet<(String, String)>) -> (usize, Vec<String>, Vec<String>) {
+    let mut count = 0;
+    let mut caught = Vec::new();
+    let mut missed = Vec::new();
+    for target in ran {
+        let is_affected = affected.contains(&(target.backend.clone(), target.label.clone()));
+        count += usize::from(is_affected);
+        if target.passed {
+            continue;
+        }
+        let name = format!("{} {}", target.backend, target.label);
+        if is_affected {
+            caught.push(name);
+        } else {
+            missed.push(name);
+        }
+    }
+    (count, caught, missed)
+}
+
+/// Append an audit to the repo's audit history, so teams can see how often affected-only runs
+/// would have missed failures before relying on them.
+pub fn record(repo_root: &Path, audit: &Audit) -> Result<()> {
+    let path = state::dir(repo_root, "history")?.join("audit.jsonl");
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(audit)?).with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+#[path = "audit_test.rs"]
+mod tests;
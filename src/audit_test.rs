@@ -0,0 +1,29 @@
+use super::*;
+
+fn ran(label: &str, passed: bool) -> Ran {
+    Ran {
+        backend: "go".to_string(),
+        label: label.to_string(),
+        passed,
+    }
+}
+
+#[test]
+fn failures_outside_the_affected_set_are_missed() {
+    let full = [
+        ran("./api/...", false),
+        ran("./db/...", true),
+        ran("./billing/...", false),
+        ran("./web/...", true),
+    ];
+    let affected: BTreeSet<(String, String)> = [
+        ("go".to_string(), "./api/...".to_string()),
+        ("go".to_string(), "./db/...".to_string()),
+    ]
+    .into_iter()
+    .collect();
+    let (count, caught, missed) = compare(&full, &affected);
+    assert_eq!(count, 2);
+    assert_eq!(caught, ["go ./api/..."]);
+    assert_eq!(missed, ["go ./billing/..."]);
+}
@@ -1,41 +1,103 @@
-use std::collections::BTreeSet;
-use std::ffi::OsStr;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+use std::time::SystemTime;
 
 use anyhow::{Context, Result};
+use regex::Regex;
+use sha2::{Digest, Sha256};
 
-use super::{Backend, Target};
+use super::{Backend, GraphNode, Outdated, Target, TargetMeta, which_exists, written_since};
+use crate::coverage::Coverage;
+use crate::error::KitError;
+use crate::exec::Invocation;
+use crate::failures::Failure;
+use crate::report::{Skip, TestCache};
+use crate::{git, state};
+
+/// Pathspecs for the files that define the build graph, whose contents invalidate cached queries.
+const GRAPH_FILES: &[&str] = &[
+    ":(glob)**/BUILD",
+    ":(glob)**/BUILD.bazel",
+    ":(glob)**/*.bzl",
+    "MODULE.bazel",
+    "WORKSPACE",
+    "WORKSPACE.bazel",
+];
+
+/// The registry Bazel resolves modules from unless `.bazelrc` sets `--registry`.
+const BCR: &str = "https://bcr.bazel.build";
+
+static BAZEL_DEP: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)\bbazel_dep\((.*?)\)").unwrap());
+static DEP_NAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"\bname\s*=\s*"([^"]+)""#).unwrap());
+static DEP_VERSION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"\bversion\s*=\s*"([^"]+)""#).unwrap());
 
 pub struct BazelBackend;
 
 impl BazelBackend {
-    fn run<I, S>(cmd: &str, args: I, dir: &Path) -> Result<()>
-    where
-        I: IntoIterator<Item = S>,
-        S: AsRef<OsStr>,
-    {
-        let status = Command::new(cmd)
-            .args(args)
-            .current_dir(dir)
+    pub fn bazel_cmd() -> &'static str {
+        if which_exists("bazelisk") { "bazelisk" } else { "bazel" }
+    }
+
+    /// Returns true if the workspace defines the given target.
+    fn has_target(repo_root: &Path, label: &str) -> bool {
+        Command::new(Self::bazel_cmd())
+            .args(["query", label])
+            .current_dir(repo_root)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
             .status()
-            .with_context(|| format!("failed to run {cmd}"))?;
-        if !status.success() {
-            anyhow::bail!("{cmd} exited with {status}");
+            .is_ok_and(|s| s.success())
+    }
+
+    /// Output of `bazel query` with the given expression and flags.
+    fn query(repo_root: &Path, args: &[&str]) -> Result<String> {
+        let output = Command::new(Self::bazel_cmd())
+            .arg("query")
+            .args(args)
+            .current_dir(repo_root)
+            .output()
+            .context("failed to run bazel query")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("bazel query failed: {}", stderr.trim());
         }
-        Ok(())
+        String::from_utf8(output.stdout).context("invalid utf-8 from bazel query")
     }
 
-    fn bazel_cmd() -> &'static str {
-        if which_exists("bazelisk") { "bazelisk" } else { "bazel" }
+    /// Cache key for an rdeps query: the changed paths plus the contents of every build graph file.
+    fn query_key(repo_root: &Path, changed_files: &[PathBuf]) -> Result<String> {
+        let mut hasher = Sha256::new();
+        for file in changed_files {
+            hasher.update(file.to_string_lossy().as_bytes());
+            hasher.update([0]);
+        }
+        let pathspecs: Vec<String> = GRAPH_FILES.iter().map(|s| s.to_string()).collect();
+        for (path, blob) in git::blob_hashes(repo_root, &pathspecs)? {
+            hasher.update(path.to_string_lossy().as_bytes());
+            hasher.update(blob.as_bytes());
+        }
+        // The index doesn't see uncommitted edits, but edited graph files are among the changed files.
+        for file in changed_files.iter().filter(|f| is_graph_file(f)) {
+            hasher.update(std::fs::read(repo_root.join(file)).unwrap_or_default());
+        }
+        Ok(hasher.finalize().iter().map(|b| format!("{b:02x}")).collect())
     }
 
-    /// Use `bazel query` with `rdeps` to find all targets affected by the changed files.
+    /// Use `bazel query` with `rdeps` to find all targets affected by the changed files. Results are
+    /// cached under `.kit/bazel-query/`, keyed by [`Self::query_key`].
     fn query_rdeps(repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Target>> {
         if changed_files.is_empty() {
             return Ok(vec![]);
         }
 
+        let cache = state::dir(repo_root, "bazel-query")?.join(Self::query_key(repo_root, changed_files)?);
+        if let Ok(stdout) = std::fs::read_to_string(&cache) {
+            eprintln!("kit: using cached bazel query results");
+            return Ok(parse_label_kind(repo_root, &stdout));
+        }
+
         let file_labels: Vec<String> = changed_files
             .iter()
             .map(|f| f.to_string_lossy().replace('\\', "/"))
@@ -46,53 +108,157 @@ impl BazelBackend {
         let query = format!("rdeps(//..., set({set_expr}))");
 
         let output = Command::new(Self::bazel_cmd())
-            .args(["query", &query, "--keep_going", "--output=label"])
+            .args(["query", &query, "--keep_going", "--output=label_kind"])
             .current_dir(repo_root)
             .output()
             .context("failed to run bazel query")?;
 
         let stdout = String::from_utf8(output.stdout).context("invalid utf-8 from bazel query")?;
-
-        let targets: Vec<Target> = stdout
-            .lines()
-            .filter(|l| !l.is_empty())
-            .map(|label| {
-                let dir = label_to_dir(repo_root, label);
-                Target {
-                    label: label.to_string(),
-                    dir,
-                }
-            })
-            .collect();
-
-        Ok(targets)
+        // With --keep_going a failed query still prints partial results; only complete ones are cached.
+        if output.status.success() {
+            std::fs::write(&cache, &stdout).with_context(|| format!("failed to write {}", cache.display()))?;
+        }
+        Ok(parse_label_kind(repo_root, &stdout))
     }
 
-    /// Deduplicate targets to package-level wildcard patterns where possible.
+    /// Deduplicate targets to package-level wildcard patterns where possible. A package is a test
+    /// if any of its rules is, and has a language if all of its rules share one.
     fn deduplicate_to_packages(repo_root: &Path, targets: &[Target]) -> Vec<Target> {
-        let mut packages: BTreeSet<String> = BTreeSet::new();
+        let mut packages: BTreeMap<String, Vec<&TargetMeta>> = BTreeMap::new();
         for t in targets {
             if let Some(pkg) = t.label.split(':').next() {
-                packages.insert(format!("{pkg}:all"));
+                packages.entry(format!("{pkg}:all")).or_default().push(&t.meta);
             }
         }
         packages
             .into_iter()
-            .map(|label| {
+            .map(|(label, rules)| {
                 let rel = label.trim_start_matches("//").split(':').next().unwrap_or("");
                 let dir = repo_root.join(rel);
-                Target { label, dir }
+                let language = rules[0]
+                    .language
+                    .clone()
+                    .filter(|l| rules.iter().all(|r| r.language.as_ref() == Some(l)));
+                let meta = TargetMeta {
+                    kind: Some("package".to_string()),
+                    language,
+                    is_test: Some(rules.iter().any(|r| r.is_test == Some(true))),
+                    size: Some(rules.len()),
+                };
+                Target {
+                    label,
+                    dir,
+                    meta,
+                    files: Vec::new(),
+                }
             })
             .collect()
     }
 }
 
-fn which_exists(cmd: &str) -> bool {
-    Command::new("which")
-        .arg(cmd)
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+/// Targets from `bazel query --output=label_kind`. Each line is "<kind> rule <label>", or
+/// "source file <label>" for inputs, which are skipped.
+fn parse_label_kind(repo_root: &Path, stdout: &str) -> Vec<Target> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let (kind, label) = line.rsplit_once(' ')?;
+            let kind = kind.strip_suffix(" rule")?;
+            Some(Target {
+                label: label.to_string(),
+                dir: label_to_dir(repo_root, label),
+                meta: rule_meta(kind),
+                files: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+/// Whether a file is part of the build graph definition.
+fn is_graph_file(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    matches!(
+        name,
+        "BUILD" | "BUILD.bazel" | "MODULE.bazel" | "WORKSPACE" | "WORKSPACE.bazel"
+    ) || name.ends_with(".bzl")
+}
+
+/// Metadata implied by a rule kind such as `go_test` or `py_library`.
+fn rule_meta(kind: &str) -> TargetMeta {
+    let language = kind.split_once('_').map(|(prefix, _)| match prefix {
+        "cc" => "c++",
+        "py" => "python",
+        "ts" => "typescript",
+        "js" => "javascript",
+        "kt" => "kotlin",
+        "sh" => "shell",
+        other => other,
+    });
+    TargetMeta {
+        kind: Some(kind.to_string()),
+        language: language.map(String::from),
+        is_test: Some(kind.ends_with("_test")),
+        size: Some(1),
+    }
+}
+
+/// Rules in `--output=label_kind` output that no edge of the `--output=graph` dependency graph
+/// points at, other than binaries, tests, and images.
+fn bazel_orphans(rules: &str, graph: &str) -> Vec<String> {
+    let depended_on: BTreeSet<&str> = graph
+        .lines()
+        .filter_map(|line| line.split_once(" -> "))
+        .map(|(_, to)| to.trim().trim_end_matches(';').trim_matches('"'))
+        .collect();
+    rules
+        .lines()
+        .filter_map(|line| {
+            let (kind, label) = line.split_once(" rule ")?;
+            let entry_point = kind.ends_with("_binary")
+                || kind.ends_with("_test")
+                || kind.ends_with("_image")
+                || kind == "test_suite";
+            (!entry_point && !depended_on.contains(label.trim())).then(|| label.trim().to_string())
+        })
+        .collect()
+}
+
+/// Packages, labelled `//pkg:all` like targets, and the packages their rules and files depend on,
+/// from the `--output=graph` dependency graph. External repositories are left out.
+fn package_graph(graph: &str) -> Vec<GraphNode> {
+    let package = |label: &str| {
+        let label = label.trim().trim_end_matches(';').trim_matches('"');
+        label
+            .starts_with("//")
+            .then(|| label.split(':').next().unwrap_or(label).to_string())
+    };
+    let mut deps: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for line in graph.lines() {
+        match line.split_once(" -> ") {
+            Some((from, to)) => {
+                let (Some(from), to) = (package(from), package(to)) else {
+                    continue;
+                };
+                let entry = deps.entry(from.clone()).or_default();
+                if let Some(to) = to.filter(|to| *to != from) {
+                    entry.insert(format!("{to}:all"));
+                    deps.entry(to).or_default();
+                }
+            }
+            None => {
+                if let Some(node) = package(line) {
+                    deps.entry(node).or_default();
+                }
+            }
+        }
+    }
+    deps.into_iter()
+        .map(|(pkg, deps)| GraphNode {
+            label: format!("{pkg}:all"),
+            dir: PathBuf::from(pkg.trim_start_matches("//")),
+            deps,
+        })
+        .collect()
 }
 
 fn label_to_dir(repo_root: &Path, label: &str) -> PathBuf {
@@ -100,6 +266,86 @@ fn label_to_dir(repo_root: &Path, label: &str) -> PathBuf {
     repo_root.join(pkg)
 }
 
+/// The label of the `task` target in a target's package: `//pkg:e2e` for `//pkg:all`, and `//:e2e`
+/// for the whole repo (`//...:all`).
+fn task_label(label: &str, task: &str) -> String {
+    let pkg = label.split(':').next().unwrap_or("");
+    let pkg = pkg.strip_suffix("...").map_or(pkg, |p| p.trim_end_matches('/'));
+    let pkg = if pkg.is_empty() { "//" } else { pkg };
+    format!("{pkg}:{task}")
+}
+
+/// Failing test targets in `bazel test` output. Bazel does not relay where in the test a failure
+/// happened, so each points at the target's test log.
+fn test_failures(output: &str) -> Vec<Failure> {
+    let mut failures = Vec::new();
+    let mut lines = output.lines().peekable();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("FAIL: ") {
+            let (label, log) = rest.split_once(" (see ").unwrap_or((rest, ""));
+            failures.push(Failure {
+                test: label.trim().to_string(),
+                file: Some(PathBuf::from(log.trim_end_matches(')'))).filter(|l| !l.as_os_str().is_empty()),
+                line: None,
+                message: "FAILED".to_string(),
+            });
+            continue;
+        }
+        // Summary lines: "//pkg:test    FAILED in 0.4s", followed by the log path.
+        let mut fields = line.split_whitespace().filter(|f| *f != "(cached)");
+        let (Some(label), Some(status), Some("in")) = (fields.next(), fields.next(), fields.next()) else {
+            continue;
+        };
+        if !label.starts_with("//") || !(status == "FAILED" || status == "TIMEOUT") {
+            continue;
+        }
+        let log = lines
+            .next_if(|l| l.trim().ends_with("test.log"))
+            .map(|l| PathBuf::from(l.trim()));
+        failures.push(Failure {
+            test: label.to_string(),
+            file: log,
+            line: None,
+            message: status.to_string(),
+        });
+    }
+    failures
+}
+
+/// Cached results among the per-target lines of the `bazel test` summary, e.g.
+/// "//pkg:test   (cached) PASSED in 0.4s".
+fn test_cache(output: &str) -> Option<TestCache> {
+    const STATUSES: &[&str] = &[
+        "PASSED",
+        "FAILED",
+        "FLAKY",
+        "TIMEOUT",
+        "NO STATUS",
+        "INCOMPLETE",
+        "SKIPPED",
+    ];
+    let mut cache = TestCache { cached: 0, total: 0 };
+    for line in output.lines() {
+        let Some((label, rest)) = line.trim().split_once(char::is_whitespace) else {
+            continue;
+        };
+        if !(label.starts_with("//") || label.starts_with('@')) {
+            continue;
+        }
+        let rest = rest.trim_start();
+        let (cached, status) = match rest.strip_prefix("(cached)") {
+            Some(status) => (true, status.trim_start()),
+            None => (false, rest),
+        };
+        if STATUSES.iter().any(|s| status.starts_with(s)) {
+            cache.total += 1;
+            cache.cached += usize::from(cached);
+        }
+    }
+    (cache.total > 0).then_some(cache)
+}
+
 impl Backend for BazelBackend {
     fn name(&self) -> &str {
         "bazel"
@@ -109,7 +355,7 @@ impl Backend for BazelBackend {
         dir.join("WORKSPACE").exists() || dir.join("WORKSPACE.bazel").exists() || dir.join("MODULE.bazel").exists()
     }
 
-    fn affected_targets(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Vec<Target> {
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
         match Self::query_rdeps(repo_root, changed_files) {
             Ok(targets) => Self::deduplicate_to_packages(repo_root, &targets),
             Err(e) => {
@@ -147,79 +393,362 @@ impl Backend for BazelBackend {
         } else {
             format!("//{rel}:all")
         };
-        Target { label, dir }
+        let meta = TargetMeta {
+            kind: Some("package".to_string()),
+            ..TargetMeta::default()
+        };
+        Target {
+            label,
+            dir,
+            meta,
+            files: Vec::new(),
+        }
     }
 
-    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<()> {
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
         if targets.is_empty() {
-            return Ok(());
+            return Ok(vec![]);
         }
-        let labels: Vec<&str> = targets.iter().map(|t| t.label.as_str()).collect();
-        let mut args = vec!["build"];
-        args.extend(&labels);
-        Self::run(Self::bazel_cmd(), &args, repo_root)
+        let labels = targets.iter().map(|t| t.label.as_str());
+        Ok(vec![
+            Invocation::new(Self::bazel_cmd(), repo_root).arg("build").args(labels),
+        ])
     }
 
-    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<()> {
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
         if targets.is_empty() {
-            return Ok(());
+            return Ok(vec![]);
         }
-        let labels: Vec<&str> = targets.iter().map(|t| t.label.as_str()).collect();
-        let mut args: Vec<&str> = vec!["test"];
-        args.extend(&labels);
-        Self::run(Self::bazel_cmd(), &args, repo_root)
+        let labels = targets.iter().map(|t| t.label.as_str());
+        Ok(vec![
+            Invocation::new(Self::bazel_cmd(), repo_root).arg("test").args(labels),
+        ])
     }
 
-    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<()> {
+    fn check(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
         if targets.is_empty() {
-            return Ok(());
-        }
-        if which_exists("buildifier") {
-            let labels: Vec<&str> = targets.iter().map(|t| t.label.as_str()).collect();
-            let mut args = vec!["run", "//:buildifier", "--"];
-            args.extend(&labels);
-            Self::run(Self::bazel_cmd(), &args, repo_root).or_else(|_| {
-                eprintln!("kit: //:buildifier target not found, running buildifier directly");
-                let dirs: Vec<&str> = targets.iter().map(|t| t.dir.to_str().unwrap_or(".")).collect();
-                let mut fallback_args = vec!["-lint=warn", "-r"];
-                fallback_args.extend(&dirs);
-                Self::run("buildifier", &fallback_args, repo_root)
-            })
+            return Ok(vec![]);
+        }
+        // Loads and analyzes the targets, which catches missing deps and visibility errors, without
+        // running any action.
+        let labels = targets.iter().map(|t| t.label.as_str());
+        Ok(vec![
+            Invocation::new(Self::bazel_cmd(), repo_root)
+                .args(["build", "--nobuild"])
+                .args(labels),
+        ])
+    }
+
+    fn task(&self, repo_root: &Path, task: &str, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        // `bazel run` takes one target, so each affected package defining the task runs on its own.
+        let labels: BTreeSet<String> = targets.iter().map(|t| task_label(&t.label, task)).collect();
+        let plan: Vec<Invocation> = labels
+            .into_iter()
+            .filter(|label| Self::has_target(repo_root, label))
+            .map(|label| Invocation::new(Self::bazel_cmd(), repo_root).args(["run", &label]))
+            .collect();
+        if plan.is_empty() {
+            return Err(Skip::NotConfigured {
+                detail: format!("no :{task} target in the affected packages"),
+            }
+            .into());
+        }
+        Ok(plan)
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        if !which_exists("buildifier") {
+            return Err(Skip::ToolMissing {
+                tool: "buildifier".to_string(),
+            }
+            .into());
+        }
+        if Self::has_target(repo_root, "//:buildifier") {
+            let labels = targets.iter().map(|t| t.label.as_str());
+            Ok(vec![
+                Invocation::new(Self::bazel_cmd(), repo_root)
+                    .args(["run", "//:buildifier", "--"])
+                    .args(labels),
+            ])
         } else {
-            eprintln!("kit: buildifier not found, skipping lint");
-            Ok(())
+            eprintln!("kit: //:buildifier target not found, running buildifier directly");
+            let dirs = targets.iter().map(|t| t.dir.to_string_lossy().into_owned());
+            Ok(vec![
+                Invocation::new("buildifier", repo_root)
+                    .args(["-lint=warn", "-r"])
+                    .args(dirs),
+            ])
         }
     }
 
-    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<()> {
-        let build_files: Vec<PathBuf> = changed_files
+    fn fix(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let dirs: Vec<String> = targets.iter().map(|t| t.dir.to_string_lossy().into_owned()).collect();
+        let args = ["-mode=fix", "-lint=fix", "-r"];
+        if Self::has_target(repo_root, "//:buildifier") {
+            return Ok(vec![
+                Invocation::new(Self::bazel_cmd(), repo_root)
+                    .args(["run", "//:buildifier", "--"])
+                    .args(args)
+                    .args(dirs),
+            ]);
+        }
+        if !which_exists("buildifier") {
+            return Err(Skip::ToolMissing {
+                tool: "buildifier".to_string(),
+            }
+            .into());
+        }
+        Ok(vec![Invocation::new("buildifier", repo_root).args(args).args(dirs)])
+    }
+
+    fn docs(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        if !["BUILD", "BUILD.bazel"]
             .iter()
-            .filter(|f| {
-                let name = f.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                name == "BUILD"
-                    || name == "BUILD.bazel"
-                    || name == "WORKSPACE"
-                    || name == "WORKSPACE.bazel"
-                    || name == "MODULE.bazel"
-                    || name.ends_with(".bzl")
+            .any(|f| repo_root.join("docs").join(f).is_file())
+        {
+            return Err(Skip::NotConfigured {
+                detail: "no //docs package".to_string(),
+            }
+            .into());
+        }
+        // Only the doc targets built from an affected target.
+        let labels: Vec<&str> = targets.iter().map(|t| t.label.as_str()).collect();
+        let expression = format!("rdeps(//docs/..., set({}))", labels.join(" "));
+        let docs: Vec<String> = Self::query(repo_root, &[&expression])?
+            .lines()
+            .map(String::from)
+            .collect();
+        if docs.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![
+            Invocation::new(Self::bazel_cmd(), repo_root).arg("build").args(docs),
+        ])
+    }
+
+    fn generate(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        // Gazelle regenerates BUILD files; it is only available through the target the repo defines.
+        if !Self::has_target(repo_root, "//:gazelle") {
+            return Err(Skip::NotConfigured {
+                detail: "no //:gazelle target".to_string(),
+            }
+            .into());
+        }
+        let dirs: BTreeSet<String> = targets
+            .iter()
+            .map(|t| {
+                let dir = t.dir.strip_prefix(repo_root).unwrap_or(&t.dir).to_string_lossy();
+                if dir.is_empty() {
+                    ".".to_string()
+                } else {
+                    dir.replace('\\', "/")
+                }
             })
-            .map(|f| repo_root.join(f))
-            .filter(|f| f.exists())
             .collect();
+        Ok(vec![
+            Invocation::new(Self::bazel_cmd(), repo_root)
+                .args(["run", "//:gazelle", "--"])
+                .args(dirs),
+        ])
+    }
 
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let build_files = buildifier_files(repo_root, changed_files)?;
         if build_files.is_empty() {
-            return Ok(());
+            return Ok(vec![]);
         }
+        Ok(vec![
+            Invocation::new("buildifier", repo_root)
+                .arg("-mode=fix")
+                .args(build_files),
+        ])
+    }
 
-        if !which_exists("buildifier") {
-            eprintln!("kit: buildifier not found, skipping format");
-            return Ok(());
+    fn fmt_check(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let build_files = buildifier_files(repo_root, changed_files)?;
+        if build_files.is_empty() {
+            return Ok(vec![]);
         }
+        Ok(vec![
+            Invocation::new("buildifier", repo_root)
+                .args(["-mode=check", "-lint=off"])
+                .args(build_files),
+        ])
+    }
+
+    fn coverage(&self, repo_root: &Path, targets: &[Target], _out_dir: &Path) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let labels = targets.iter().map(|t| t.label.as_str());
+        Ok(vec![
+            Invocation::new(Self::bazel_cmd(), repo_root)
+                .args(["coverage", "--combined_report=lcov"])
+                .args(labels),
+        ])
+    }
+
+    fn read_coverage(&self, repo_root: &Path, _out_dir: &Path, since: SystemTime) -> Result<Coverage> {
+        let report = repo_root.join("bazel-out/_coverage/_coverage_report.dat");
+        if !written_since(&report, since) {
+            return Ok(Coverage::default());
+        }
+        let text = std::fs::read_to_string(&report).with_context(|| format!("failed to read {}", report.display()))?;
+        Ok(Coverage::parse_lcov(&text, repo_root, repo_root))
+    }
+
+    fn no_cache_args(&self) -> Option<Vec<String>> {
+        Some(vec!["--nocache_test_results".to_string()])
+    }
 
-        let mut args: Vec<&OsStr> = vec![OsStr::new("-mode=fix")];
-        args.extend(build_files.iter().map(|f| f.as_os_str()));
-        Self::run("buildifier", args, repo_root)
+    fn test_cache(&self, output: &str) -> Option<TestCache> {
+        test_cache(output)
     }
+
+    fn graph(&self, repo_root: &Path) -> Result<Option<Vec<GraphNode>>> {
+        let graph = Self::query(repo_root, &["deps(//..., 1)", "--output=graph", "--nograph:factored"])?;
+        Ok(Some(package_graph(&graph)))
+    }
+
+    fn orphans(&self, repo_root: &Path) -> Result<Option<Vec<String>>> {
+        let rules = Self::query(repo_root, &["kind(rule, //...)", "--output=label_kind"])?;
+        let graph = Self::query(repo_root, &["deps(//..., 1)", "--output=graph", "--nograph:factored"])?;
+        Ok(Some(bazel_orphans(&rules, &graph)))
+    }
+
+    fn outdated(&self, repo_root: &Path) -> Result<Option<Vec<Outdated>>> {
+        // Bazel has no outdated command, so each bazel_dep is compared with its registry metadata.
+        let Ok(module) = std::fs::read_to_string(repo_root.join("MODULE.bazel")) else {
+            return Ok(None);
+        };
+        let registry = registry(&std::fs::read_to_string(repo_root.join(".bazelrc")).unwrap_or_default());
+        let mut outdated = Vec::new();
+        for (name, current) in bazel_deps(&module) {
+            let url = format!("{}/modules/{name}/metadata.json", registry.trim_end_matches('/'));
+            let output = Command::new("curl")
+                .args(["-fsSL", &url])
+                .output()
+                .map_err(|e| KitError::spawn("curl", e))?;
+            let latest = output
+                .status
+                .success()
+                .then(|| latest_version(&String::from_utf8_lossy(&output.stdout)))
+                .flatten();
+            match latest {
+                Some(latest) if latest != current => outdated.push(Outdated {
+                    name,
+                    current,
+                    latest,
+                    dir: PathBuf::new(),
+                }),
+                Some(_) => {}
+                None => eprintln!("kit: no registry metadata for Bazel module {name} at {url}"),
+            }
+        }
+        Ok(Some(outdated))
+    }
+
+    fn parse_failures(&self, _repo_root: &Path, output: &str) -> Vec<Failure> {
+        test_failures(output)
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new(Self::bazel_cmd(), repo_root).arg("--version")]
+    }
+
+    fn extra_tools(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new("buildifier", repo_root).arg("--version")]
+    }
+
+    fn smoke(&self, repo_root: &Path) -> Vec<Invocation> {
+        // Starts the Bazel server and loads the workspace setup, which is where most breakage shows.
+        vec![Invocation::new(Self::bazel_cmd(), repo_root).args(["info", "workspace"])]
+    }
+
+    fn misconfigurations(&self, repo_root: &Path) -> Vec<String> {
+        if repo_root.join(".bazelversion").is_file() {
+            return vec![];
+        }
+        vec!["no .bazelversion, so every machine builds with whichever Bazel it has installed".to_string()]
+    }
+}
+
+/// The changed BUILD, WORKSPACE, MODULE.bazel, and .bzl files that still exist, as absolute paths,
+/// or a [`Skip`] when there are some but buildifier isn't installed.
+fn buildifier_files(repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<String>> {
+    let build_files: Vec<String> = changed_files
+        .iter()
+        .filter(|f| {
+            let name = f.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            name == "BUILD"
+                || name == "BUILD.bazel"
+                || name == "WORKSPACE"
+                || name == "WORKSPACE.bazel"
+                || name == "MODULE.bazel"
+                || name.ends_with(".bzl")
+        })
+        .map(|f| repo_root.join(f))
+        .filter(|f| f.exists())
+        .map(|f| f.to_string_lossy().into_owned())
+        .collect();
+    if !build_files.is_empty() && !which_exists("buildifier") {
+        return Err(Skip::ToolMissing {
+            tool: "buildifier".to_string(),
+        }
+        .into());
+    }
+    Ok(build_files)
+}
+
+/// The name and version of each `bazel_dep` in a MODULE.bazel, skipping those without a version,
+/// which an override pins instead.
+fn bazel_deps(module: &str) -> Vec<(String, String)> {
+    BAZEL_DEP
+        .captures_iter(module)
+        .filter_map(|c| {
+            let name = DEP_NAME.captures(&c[1])?[1].to_string();
+            let version = DEP_VERSION.captures(&c[1])?[1].to_string();
+            Some((name, version))
+        })
+        .collect()
+}
+
+/// The first `--registry` a `.bazelrc` sets, or the Bazel Central Registry.
+fn registry(bazelrc: &str) -> String {
+    bazelrc
+        .split_whitespace()
+        .find_map(|word| word.strip_prefix("--registry="))
+        .unwrap_or(BCR)
+        .to_string()
+}
+
+/// The newest version in a registry's `metadata.json` that isn't yanked. Versions are listed oldest
+/// first.
+fn latest_version(metadata: &str) -> Option<String> {
+    let metadata: serde_json::Value = serde_json::from_str(metadata).ok()?;
+    let yanked = metadata["yanked_versions"].as_object();
+    metadata["versions"]
+        .as_array()?
+        .iter()
+        .rev()
+        .filter_map(|v| v.as_str())
+        .find(|v| yanked.is_none_or(|y| !y.contains_key(*v)))
+        .map(String::from)
 }
 
 #[cfg(test)]
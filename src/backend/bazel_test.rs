@@ -9,10 +9,12 @@ fn deduplicate_to_packages_collapses_targets() {
         Target {
             label: "//pkg/foo:bar".to_string(),
             dir: root.join("pkg/foo"),
+            ..Default::default()
         },
         Target {
             label: "//pkg/foo:baz".to_string(),
             dir: root.join("pkg/foo"),
+            ..Default::default()
         },
     ];
     let deduped = BazelBackend::deduplicate_to_packages(root, &targets);
@@ -29,10 +31,12 @@ fn deduplicate_to_packages_distinct_packages() {
         Target {
             label: "//pkg/foo:bar".to_string(),
             dir: root.join("pkg/foo"),
+            ..Default::default()
         },
         Target {
             label: "//pkg/bar:baz".to_string(),
             dir: root.join("pkg/bar"),
+            ..Default::default()
         },
     ];
     let deduped = BazelBackend::deduplicate_to_packages(root, &targets);
@@ -42,6 +46,34 @@ fn deduplicate_to_packages_distinct_packages() {
     assert!(labels.contains(&"//pkg/foo:all"));
 }
 
+#[test]
+fn deduplicate_to_packages_combines_rule_metadata() {
+    let root = Path::new("/repo");
+    let rule = |label: &str, kind: &str| Target {
+        label: label.to_string(),
+        dir: label_to_dir(root, label),
+        meta: rule_meta(kind),
+        files: Vec::new(),
+    };
+    let targets = vec![
+        rule("//pkg/foo:lib", "go_library"),
+        rule("//pkg/foo:lib_test", "go_test"),
+        rule("//pkg/bar:bin", "py_binary"),
+        rule("//pkg/bar:gen", "genrule"),
+    ];
+    let deduped = BazelBackend::deduplicate_to_packages(root, &targets);
+    let bar = &deduped[0].meta;
+    assert_eq!(
+        (bar.language.as_deref(), bar.is_test, bar.size),
+        (None, Some(false), Some(2))
+    );
+    let foo = &deduped[1].meta;
+    assert_eq!(
+        (foo.language.as_deref(), foo.is_test, foo.size),
+        (Some("go"), Some(true), Some(2))
+    );
+}
+
 #[test]
 fn label_to_dir_strips_prefix() {
     let tmp = TempDir::new().unwrap();
@@ -49,3 +81,134 @@ fn label_to_dir_strips_prefix() {
     assert_eq!(label_to_dir(root, "//pkg/foo:bar"), root.join("pkg/foo"));
     assert_eq!(label_to_dir(root, "//:something"), root.join(""));
 }
+
+#[test]
+fn parse_label_kind_skips_source_files() {
+    let root = Path::new("/repo");
+    let stdout = "go_test rule //pkg/foo:foo_test\nsource file //pkg/foo:foo.go\ngenrule rule //:gen\n";
+    let targets = parse_label_kind(root, stdout);
+    let labels: Vec<&str> = targets.iter().map(|t| t.label.as_str()).collect();
+    assert_eq!(labels, ["//pkg/foo:foo_test", "//:gen"]);
+    assert_eq!(targets[0].meta.is_test, Some(true));
+    assert!(is_graph_file(Path::new("tools/defs.bzl")));
+    assert!(!is_graph_file(Path::new("pkg/foo/foo.go")));
+}
+
+#[test]
+fn test_failures_point_at_test_logs() {
+    let output = "\
+FAIL: //pkg/a:a_test (see /cache/testlogs/pkg/a/a_test/test.log)
+INFO: Build completed, 2 tests FAILED, 3 total actions
+//pkg/a:a_test                                                           FAILED in 0.4s
+  /cache/testlogs/pkg/a/a_test/test.log
+//pkg/b:b_test                                                          TIMEOUT in 60.0s
+  /cache/testlogs/pkg/b/b_test/test.log
+//pkg/c:c_test                                                           PASSED in 0.1s
+";
+    let failure = |test: &str, log: &str, message: &str| Failure {
+        test: test.to_string(),
+        file: Some(PathBuf::from(log)),
+        line: None,
+        message: message.to_string(),
+    };
+    assert_eq!(
+        test_failures(output),
+        [
+            failure("//pkg/a:a_test", "/cache/testlogs/pkg/a/a_test/test.log", "FAILED"),
+            failure("//pkg/a:a_test", "/cache/testlogs/pkg/a/a_test/test.log", "FAILED"),
+            failure("//pkg/b:b_test", "/cache/testlogs/pkg/b/b_test/test.log", "TIMEOUT"),
+        ]
+    );
+}
+
+#[test]
+fn test_cache_counts_cached_summary_lines() {
+    let output = "\
+INFO: Build completed successfully, 4 total actions
+//pkg/a:a_test                                                  (cached) PASSED in 0.4s
+//pkg/b:b_test                                                           PASSED in 1.2s
+@dep//c:c_test                                                  (cached) PASSED in 0.1s
+//pkg/d:d_test                                                           FAILED in 0.3s
+  /cache/testlogs/pkg/d/d_test/test.log
+
+Executed 2 out of 4 tests: 3 tests pass and 1 fails locally.
+";
+    assert_eq!(test_cache(output), Some(TestCache { cached: 2, total: 4 }));
+    assert_eq!(test_cache("INFO: Build completed successfully\n"), None);
+    assert_eq!(test_failures(output).len(), 1);
+}
+
+#[test]
+fn bazel_orphans_have_no_incoming_edges() {
+    let rules = "\
+go_binary rule //cmd/app:app
+go_library rule //pkg/api:api
+go_library rule //pkg/dead:dead
+go_test rule //pkg/api:api_test
+filegroup rule //docs:docs
+";
+    let graph = r#"digraph mygraph {
+  node [shape=box];
+  "//cmd/app:app"
+  "//cmd/app:app" -> "//pkg/api:api"
+  "//pkg/api:api_test" -> "//pkg/api:api"
+  "//pkg/dead:dead" -> "//pkg/dead:dead.go"
+}"#;
+    assert_eq!(bazel_orphans(rules, graph), ["//pkg/dead:dead", "//docs:docs"]);
+}
+
+#[test]
+fn task_labels_are_in_the_targets_package() {
+    assert_eq!(task_label("//services/api:all", "e2e"), "//services/api:e2e");
+    assert_eq!(task_label("//services/api:server", "e2e"), "//services/api:e2e");
+    assert_eq!(task_label("//...:all", "deploy-preview"), "//:deploy-preview");
+    assert_eq!(task_label("//tools/...:all", "e2e"), "//tools:e2e");
+}
+
+#[test]
+fn package_graph_collapses_rules_to_packages() {
+    let graph = r#"digraph mygraph {
+  node [shape=box];
+  "//cmd/app:app"
+  "//cmd/app:app" -> "//pkg/api:api"
+  "//cmd/app:app" -> "//cmd/app:main.go"
+  "//pkg/api:api" -> "@com_github_google_uuid//:uuid"
+  "//pkg/api:api_test" -> "//pkg/api:api"
+  "//:gazelle"
+}"#;
+    let nodes = package_graph(graph);
+    let labels: Vec<&str> = nodes.iter().map(|n| n.label.as_str()).collect();
+    assert_eq!(labels, ["//:all", "//cmd/app:all", "//pkg/api:all"]);
+    assert_eq!(nodes[1].dir, Path::new("cmd/app"));
+    assert_eq!(nodes[1].deps.iter().collect::<Vec<_>>(), ["//pkg/api:all"]);
+    assert!(nodes[2].deps.is_empty());
+}
+
+#[test]
+fn outdated_reads_bazel_deps_and_registry_metadata() {
+    let module = r#"
+module(name = "app", version = "1.0")
+
+bazel_dep(name = "rules_go", version = "0.46.0")
+bazel_dep(
+    name = "gazelle",
+    version = "0.35.0",
+    dev_dependency = True,
+)
+bazel_dep(name = "local_lib")
+"#;
+    assert_eq!(
+        bazel_deps(module),
+        [
+            ("rules_go".to_string(), "0.46.0".to_string()),
+            ("gazelle".to_string(), "0.35.0".to_string()),
+        ]
+    );
+    let metadata = r#"{"versions": ["0.45.0", "0.46.0", "0.47.0"], "yanked_versions": {"0.47.0": "broken"}}"#;
+    assert_eq!(latest_version(metadata).as_deref(), Some("0.46.0"));
+    assert_eq!(
+        registry("common --registry=https://bcr.example.com\n"),
+        "https://bcr.example.com"
+    );
+    assert_eq!(registry(""), BCR);
+}
@@ -0,0 +1,248 @@
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, OnceLock};
+
+use anyhow::Result;
+use regex::Regex;
+
+use super::{Backend, Target, TargetMeta};
+use crate::exec::Invocation;
+use crate::report::Skip;
+use crate::{git, paths};
+
+/// An entry of a list in buf.yaml or buf.work.yaml: "  - proto" or "  - path: proto".
+static LIST_ENTRY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^\s*-\s*(?:path:\s*)?["']?([^"'#\s]+)["']?\s*(?:#.*)?$"#).unwrap());
+
+/// Files that configure a module or workspace; a change to one affects every module it covers.
+const CONFIG_FILES: &[&str] = &["buf.yaml", "buf.lock", "buf.work.yaml"];
+
+/// The merge base [`Backend::affected_targets`] diffed against, which `buf breaking` compares the
+/// affected modules with.
+static MERGE_BASE: OnceLock<String> = OnceLock::new();
+
+pub struct BufBackend;
+
+impl BufBackend {
+    /// Directories of buf modules, relative to the repo root: those listed by a buf.work.yaml
+    /// (`directories`) or a v2 buf.yaml (`modules`), and those with a v1 buf.yaml of their own.
+    fn modules(repo_root: &Path) -> Result<Vec<PathBuf>> {
+        let mut modules = Vec::new();
+        for config in ["buf.work.yaml", "buf.yaml"] {
+            for file in git::find_files(repo_root, config)? {
+                let dir = file.parent().unwrap_or(Path::new("")).to_path_buf();
+                let text = std::fs::read_to_string(repo_root.join(&file)).unwrap_or_default();
+                let listed = listed_dirs(&text, if config == "buf.yaml" { "modules" } else { "directories" });
+                if listed.is_empty() && config == "buf.yaml" {
+                    modules.push(dir);
+                } else {
+                    modules.extend(listed.iter().filter_map(|d| paths::normalize(&dir.join(d))));
+                }
+            }
+        }
+        modules.sort();
+        modules.dedup();
+        Ok(modules)
+    }
+
+    /// `buf <command>` on each module.
+    fn each(repo_root: &Path, command: &str, targets: &[Target]) -> Vec<Invocation> {
+        targets
+            .iter()
+            .map(|t| Invocation::new("buf", repo_root).args([command, &t.label]))
+            .collect()
+    }
+}
+
+impl Backend for BufBackend {
+    fn name(&self) -> &str {
+        "buf"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join("buf.yaml").exists() || dir.join("buf.work.yaml").exists()
+    }
+
+    fn affected_targets(&self, repo_root: &Path, base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let modules = match Self::modules(repo_root) {
+            Ok(modules) => modules,
+            Err(e) => {
+                eprintln!("kit: failed to find buf modules ({e:#})");
+                return vec![];
+            }
+        };
+        let affected = affected_modules(&modules, changed_files);
+        if !affected.is_empty() {
+            match git::merge_base(repo_root, base) {
+                Ok(merge_base) => {
+                    let _ = MERGE_BASE.set(merge_base);
+                }
+                Err(e) => eprintln!("kit: not checking for breaking protobuf changes ({e:#})"),
+            }
+        }
+        affected.into_iter().map(|m| module_target(repo_root, m)).collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_path_buf();
+        let modules = Self::modules(repo_root).unwrap_or_default();
+        let module = modules
+            .into_iter()
+            .filter(|m| rel.starts_with(m))
+            .max_by_key(|m| m.components().count());
+        module_target(repo_root, &module.unwrap_or(rel))
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Ok(Self::each(repo_root, "build", targets))
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let Some(merge_base) = MERGE_BASE.get() else {
+            return Err(Skip::NotConfigured {
+                detail: "breaking changes are only checked for modules affected by the branch".to_string(),
+            }
+            .into());
+        };
+        Ok(targets
+            .iter()
+            .map(|t| {
+                Invocation::new("buf", repo_root).args([
+                    "breaking".to_string(),
+                    t.label.clone(),
+                    "--against".to_string(),
+                    against(merge_base, &t.label),
+                ])
+            })
+            .collect())
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Ok(Self::each(repo_root, "lint", targets))
+    }
+
+    fn generate(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        // A template at the repo root generates from each module named as the input; otherwise each
+        // module generates with its own.
+        if repo_root.join("buf.gen.yaml").is_file() {
+            return Ok(Self::each(repo_root, "generate", targets));
+        }
+        let plan: Vec<Invocation> = targets
+            .iter()
+            .filter(|t| t.dir.join("buf.gen.yaml").is_file())
+            .map(|t| Invocation::new("buf", &t.dir).arg("generate"))
+            .collect();
+        if plan.is_empty() {
+            return Err(Skip::NotConfigured {
+                detail: "no buf.gen.yaml at the repo root or in the affected modules".to_string(),
+            }
+            .into());
+        }
+        Ok(plan)
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        Ok(changed_files
+            .iter()
+            .filter(|f| f.extension().is_some_and(|e| e == "proto") && repo_root.join(f).is_file())
+            .map(|f| Invocation::new("buf", repo_root).args(["format", "-w", &f.to_string_lossy().replace('\\', "/")]))
+            .collect())
+    }
+
+    fn fmt_check(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        // `buf format -d` prints the diff it would apply; `--exit-code` fails when there is one.
+        Ok(changed_files
+            .iter()
+            .filter(|f| f.extension().is_some_and(|e| e == "proto") && repo_root.join(f).is_file())
+            .map(|f| {
+                Invocation::new("buf", repo_root).args([
+                    "format",
+                    "-d",
+                    "--exit-code",
+                    &f.to_string_lossy().replace('\\', "/"),
+                ])
+            })
+            .collect())
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new("buf", repo_root).arg("--version")]
+    }
+}
+
+fn module_target(repo_root: &Path, module: &Path) -> Target {
+    let rel = module.to_string_lossy().replace('\\', "/");
+    let meta = TargetMeta {
+        kind: Some("module".to_string()),
+        language: Some("protobuf".to_string()),
+        is_test: None,
+        size: None,
+    };
+    Target {
+        label: if rel.is_empty() { ".".to_string() } else { rel },
+        dir: repo_root.join(module),
+        meta,
+        files: Vec::new(),
+    }
+}
+
+/// The entries of the top-level `key:` list in a buf config, e.g. `directories:` in buf.work.yaml.
+/// Lists nested in an entry's settings (a module's `excludes`) are indented further and ignored.
+fn listed_dirs(text: &str, key: &str) -> Vec<String> {
+    let mut dirs = Vec::new();
+    let mut in_list = false;
+    let mut indent = None;
+    for line in text.lines() {
+        if !line.starts_with([' ', '\t', '-']) && !line.trim().is_empty() {
+            in_list = line.trim_end() == format!("{key}:");
+            indent = None;
+            continue;
+        }
+        let Some(c) = LIST_ENTRY.captures(line).filter(|_| in_list) else {
+            continue;
+        };
+        let depth = line.len() - line.trim_start().len();
+        if *indent.get_or_insert(depth) == depth {
+            dirs.push(c[1].to_string());
+        }
+    }
+    dirs
+}
+
+/// Modules containing a changed `.proto` file, or covered by a changed buf config file (the
+/// module's own, or a workspace's above it).
+fn affected_modules<'a>(modules: &'a [PathBuf], changed_files: &[PathBuf]) -> Vec<&'a PathBuf> {
+    let mut affected: Vec<&PathBuf> = Vec::new();
+    for file in changed_files {
+        let name = file.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let dir = file.parent().unwrap_or(Path::new(""));
+        if CONFIG_FILES.contains(&name) {
+            affected.extend(modules.iter().filter(|m| m.starts_with(dir)));
+        } else if file.extension().is_some_and(|e| e == "proto")
+            && let Some(m) = modules
+                .iter()
+                .filter(|m| file.starts_with(m))
+                .max_by_key(|m| m.components().count())
+        {
+            affected.push(m);
+        }
+    }
+    affected.sort();
+    affected.dedup();
+    affected
+}
+
+/// The `--against` input for `buf breaking`: the module as it was at the merge base.
+fn against(merge_base: &str, module: &str) -> String {
+    if module == "." {
+        format!(".git#ref={merge_base}")
+    } else {
+        format!(".git#ref={merge_base},subdir={module}")
+    }
+}
+
+#[cfg(test)]
+#[path = "buf_test.rs"]
+mod tests;
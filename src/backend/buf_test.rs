@@ -0,0 +1,109 @@
+use super::*;
+
+const WORKSPACE: &str = "version: v1
+directories:
+  - proto
+  - 'vendor/googleapis' # third party
+";
+
+const V2: &str = "version: v2
+modules:
+  - path: proto/public
+    name: buf.build/acme/public
+    excludes:
+      - proto/public/internal
+  - path: proto/private
+lint:
+  use:
+    - STANDARD
+";
+
+fn dirs(dirs: &[&str]) -> Vec<PathBuf> {
+    dirs.iter().map(PathBuf::from).collect()
+}
+
+#[test]
+fn workspace_directories_are_listed() {
+    assert_eq!(listed_dirs(WORKSPACE, "directories"), ["proto", "vendor/googleapis"]);
+}
+
+#[test]
+fn v2_module_paths_are_listed_without_nested_settings() {
+    assert_eq!(listed_dirs(V2, "modules"), ["proto/public", "proto/private"]);
+    assert!(listed_dirs("version: v1\nlint:\n  use:\n    - DEFAULT\n", "modules").is_empty());
+}
+
+#[test]
+fn changed_protos_map_to_the_deepest_module() {
+    let modules = dirs(&["proto", "proto/vendor", "services/api/proto"]);
+    let changed = dirs(&[
+        "proto/vendor/x/v1/x.proto",
+        "services/api/main.go",
+        "proto/acme/v1/user.proto",
+    ]);
+    assert_eq!(
+        affected_modules(&modules, &changed),
+        [&PathBuf::from("proto"), &PathBuf::from("proto/vendor")]
+    );
+}
+
+#[test]
+fn config_changes_affect_every_module_below_them() {
+    let modules = dirs(&["proto", "services/api/proto"]);
+    assert_eq!(affected_modules(&modules, &dirs(&["buf.work.yaml"])).len(), 2);
+    assert_eq!(
+        affected_modules(&modules, &dirs(&["services/api/proto/buf.lock"])),
+        [&PathBuf::from("services/api/proto")]
+    );
+}
+
+#[test]
+fn breaking_compares_with_the_module_at_the_merge_base() {
+    assert_eq!(against("abc123", "proto"), ".git#ref=abc123,subdir=proto");
+    assert_eq!(against("abc123", "."), ".git#ref=abc123");
+}
+
+#[test]
+fn fmt_formats_changed_protos_in_place() {
+    let repo = tempfile::tempdir().unwrap();
+    std::fs::create_dir(repo.path().join("proto")).unwrap();
+    std::fs::write(repo.path().join("proto/user.proto"), "syntax = \"proto3\";\n").unwrap();
+    let plan = BufBackend
+        .fmt(
+            repo.path(),
+            &dirs(&["proto/user.proto", "proto/gone.proto", "README.md"]),
+        )
+        .unwrap();
+    assert_eq!(plan.len(), 1);
+    assert_eq!(plan[0].args, ["format", "-w", "proto/user.proto"]);
+}
+
+#[test]
+fn fmt_check_diffs_changed_protos_without_writing() {
+    let repo = tempfile::tempdir().unwrap();
+    std::fs::create_dir(repo.path().join("proto")).unwrap();
+    std::fs::write(repo.path().join("proto/user.proto"), "syntax = \"proto3\";\n").unwrap();
+    let plan = BufBackend
+        .fmt_check(repo.path(), &dirs(&["proto/user.proto", "proto/gone.proto"]))
+        .unwrap();
+    assert_eq!(plan.len(), 1);
+    assert_eq!(plan[0].args, ["format", "-d", "--exit-code", "proto/user.proto"]);
+}
+
+#[test]
+fn generate_uses_the_root_template_or_each_modules_own() {
+    let repo = tempfile::tempdir().unwrap();
+    let root = repo.path();
+    std::fs::create_dir_all(root.join("proto")).unwrap();
+    let targets = vec![BufBackend.resolve_target(root, root.join("proto"))];
+    assert!(BufBackend.generate(root, &targets).is_err());
+    std::fs::write(root.join("proto/buf.gen.yaml"), "version: v2\n").unwrap();
+    let plan = BufBackend.generate(root, &targets).unwrap();
+    assert_eq!(
+        (plan[0].args.as_slice(), plan[0].dir.as_path()),
+        (&["generate".to_string()][..], root.join("proto").as_path())
+    );
+    std::fs::write(root.join("buf.gen.yaml"), "version: v2\n").unwrap();
+    let plan = BufBackend.generate(root, &targets).unwrap();
+    assert_eq!(plan[0].args, ["generate", "proto"]);
+}
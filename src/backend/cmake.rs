@@ -0,0 +1,238 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use regex::Regex;
+
+use super::{Backend, Target, TargetMeta};
+use crate::exec::Invocation;
+use crate::failures::Failure;
+use crate::report::Skip;
+use crate::{git, state};
+
+/// A failed test in ctest's summary: "  2 - parser_test (Failed)".
+static CTEST_FAILED: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*\d+ - (\S+) \((.+)\)").unwrap());
+
+/// Extensions of the C and C++ files clang-tidy and clang-format handle.
+const SOURCES: &[&str] = &["c", "cc", "cpp", "cxx"];
+const HEADERS: &[&str] = &["h", "hh", "hpp", "hxx"];
+
+pub struct CmakeBackend;
+
+impl CmakeBackend {
+    /// Directories with a CMakeLists.txt, relative to the repo root.
+    fn dirs(repo_root: &Path) -> Result<Vec<PathBuf>> {
+        Ok(git::find_files(repo_root, "CMakeLists.txt")?
+            .into_iter()
+            .map(|f| f.parent().map(Path::to_path_buf).unwrap_or_default())
+            .collect())
+    }
+
+    /// The build directory: `build/` if it is already configured, otherwise `.kit/cmake`.
+    fn build_dir(repo_root: &Path) -> Result<PathBuf> {
+        let existing = repo_root.join("build");
+        if existing.join("CMakeCache.txt").is_file() {
+            return Ok(existing);
+        }
+        state::dir(repo_root, "cmake")
+    }
+
+    /// Configure the build directory, exporting compile_commands.json for clang-tidy. Reconfiguring
+    /// an up-to-date build directory is quick, so every plan starts with it.
+    fn configure(repo_root: &Path, build: &Path) -> Invocation {
+        Invocation::new("cmake", repo_root).args([
+            "-S".to_string(),
+            ".".to_string(),
+            "-B".to_string(),
+            build.to_string_lossy().into_owned(),
+            "-DCMAKE_EXPORT_COMPILE_COMMANDS=ON".to_string(),
+        ])
+    }
+}
+
+impl Backend for CmakeBackend {
+    fn name(&self) -> &str {
+        "cmake"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join("CMakeLists.txt").exists()
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let dirs = match Self::dirs(repo_root) {
+            Ok(dirs) => dirs,
+            Err(e) => {
+                eprintln!("kit: failed to find CMakeLists.txt files ({e:#})");
+                return vec![];
+            }
+        };
+        affected_dirs(&dirs, changed_files)
+            .into_iter()
+            .map(|dir| self.resolve_target(repo_root, repo_root.join(dir)))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_string_lossy();
+        let rel = rel.replace('\\', "/");
+        let label = if rel.is_empty() { ".".to_string() } else { rel };
+        let meta = TargetMeta {
+            kind: Some("directory".to_string()),
+            language: Some("cpp".to_string()),
+            is_test: None,
+            size: None,
+        };
+        Target {
+            label,
+            dir,
+            meta,
+            files: Vec::new(),
+        }
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        // CMake targets are named in the CMakeLists.txt files, not by directory, so the whole tree
+        // builds; the build tool skips what is up to date.
+        let build = Self::build_dir(repo_root)?;
+        Ok(vec![
+            Self::configure(repo_root, &build),
+            Invocation::new("cmake", repo_root).args(["--build".to_string(), build.to_string_lossy().into_owned()]),
+        ])
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let build = Self::build_dir(repo_root)?;
+        let mut plan = self.build(repo_root, targets)?;
+        // Each directory of the build tree runs the tests its CMakeLists.txt (and those below it)
+        // added, so ctest runs once per affected directory, or once for everything.
+        let labels: Vec<&str> = if targets.iter().any(|t| t.label == ".") {
+            vec!["."]
+        } else {
+            targets.iter().map(|t| t.label.as_str()).collect()
+        };
+        for label in labels {
+            let dir = if label == "." { build.clone() } else { build.join(label) };
+            plan.push(Invocation::new("ctest", repo_root).args([
+                "--test-dir".to_string(),
+                dir.to_string_lossy().into_owned(),
+                "--output-on-failure".to_string(),
+            ]));
+        }
+        Ok(plan)
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        if !repo_root.join(".clang-tidy").is_file() {
+            return Err(Skip::NotConfigured {
+                detail: "no .clang-tidy at the repo root".to_string(),
+            }
+            .into());
+        }
+        let mut sources = Vec::new();
+        for ext in SOURCES {
+            sources.extend(git::find_files(repo_root, &format!("*.{ext}"))?);
+        }
+        let files = files_in(&sources, targets, repo_root);
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        let build = Self::build_dir(repo_root)?;
+        Ok(vec![
+            Self::configure(repo_root, &build),
+            Invocation::new("clang-tidy", repo_root)
+                .args(["-p".to_string(), build.to_string_lossy().into_owned()])
+                .args(files),
+        ])
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        if !repo_root.join(".clang-format").is_file() {
+            return Err(Skip::NotConfigured {
+                detail: "no .clang-format at the repo root".to_string(),
+            }
+            .into());
+        }
+        let files: Vec<String> = changed_files
+            .iter()
+            .filter(|f| is_c_or_cpp(f) && repo_root.join(f).exists())
+            .map(|f| f.to_string_lossy().replace('\\', "/"))
+            .collect();
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![Invocation::new("clang-format", repo_root).arg("-i").args(files)])
+    }
+
+    fn parse_failures(&self, _repo_root: &Path, output: &str) -> Vec<Failure> {
+        ctest_failures(output)
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new("cmake", repo_root).arg("--version")]
+    }
+}
+
+fn is_c_or_cpp(file: &Path) -> bool {
+    file.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| SOURCES.contains(&e) || HEADERS.contains(&e))
+}
+
+/// Directories containing a changed C/C++ file or CMake script, mapped to the deepest directory
+/// with a CMakeLists.txt. Dependencies between directories are not followed: a header change tests
+/// the directory the header is in.
+fn affected_dirs(dirs: &[PathBuf], changed_files: &[PathBuf]) -> BTreeSet<PathBuf> {
+    changed_files
+        .iter()
+        .filter(|f| {
+            let name = f.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            is_c_or_cpp(f) || name == "CMakeLists.txt" || name == "CMakePresets.json" || name.ends_with(".cmake")
+        })
+        .filter_map(|f| {
+            dirs.iter()
+                .filter(|d| f.starts_with(d))
+                .max_by_key(|d| d.components().count())
+        })
+        .cloned()
+        .collect()
+}
+
+/// The files among `sources` inside any of the targets' directories.
+fn files_in(sources: &[PathBuf], targets: &[Target], repo_root: &Path) -> Vec<String> {
+    sources
+        .iter()
+        .filter(|f| targets.iter().any(|t| repo_root.join(f).starts_with(&t.dir)))
+        .map(|f| f.to_string_lossy().replace('\\', "/"))
+        .collect()
+}
+
+/// Failed tests in the "The following tests FAILED:" list ctest prints at the end.
+fn ctest_failures(output: &str) -> Vec<Failure> {
+    output
+        .lines()
+        .skip_while(|line| !line.starts_with("The following tests FAILED:"))
+        .skip(1)
+        .map_while(|line| CTEST_FAILED.captures(line))
+        .map(|c| Failure {
+            test: c[1].to_string(),
+            file: None,
+            line: None,
+            message: c[2].to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "cmake_test.rs"]
+mod tests;
@@ -0,0 +1,66 @@
+use super::*;
+
+#[test]
+fn affected_dirs_map_files_to_the_deepest_cmake_directory() {
+    let dirs = [PathBuf::new(), PathBuf::from("lib"), PathBuf::from("lib/parser")];
+    let changed = [
+        PathBuf::from("lib/parser/parse.cpp"),
+        PathBuf::from("lib/util.h"),
+        PathBuf::from("cmake/warnings.cmake"),
+        PathBuf::from("lib/README.md"),
+    ];
+    assert_eq!(
+        affected_dirs(&dirs, &changed).into_iter().collect::<Vec<_>>(),
+        [PathBuf::new(), PathBuf::from("lib"), PathBuf::from("lib/parser")]
+    );
+    assert!(affected_dirs(&dirs, &[PathBuf::from("docs/index.md")]).is_empty());
+}
+
+#[test]
+fn test_runs_ctest_in_each_affected_build_directory() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let build = root.join(".kit/cmake");
+    let targets = [
+        CmakeBackend.resolve_target(root, root.join("lib")),
+        CmakeBackend.resolve_target(root, root.join("app")),
+    ];
+    let plan = CmakeBackend.test(root, &targets).unwrap();
+    let build_arg = build.to_string_lossy().into_owned();
+    assert_eq!(plan[0].args[..4], ["-S", ".", "-B", build_arg.as_str()]);
+    assert_eq!(plan[1].args, ["--build", build_arg.as_str()]);
+    assert_eq!(
+        plan[2..],
+        [
+            Invocation::new("ctest", root).args([
+                "--test-dir",
+                &build.join("lib").to_string_lossy(),
+                "--output-on-failure"
+            ]),
+            Invocation::new("ctest", root).args([
+                "--test-dir",
+                &build.join("app").to_string_lossy(),
+                "--output-on-failure"
+            ]),
+        ]
+    );
+}
+
+#[test]
+fn ctest_failures_read_the_summary() {
+    let output = "\
+50% tests passed, 2 tests failed out of 4
+
+Total Test time (real) =   0.02 sec
+
+The following tests FAILED:
+\t  2 - parser_test (Failed)
+\t  4 - slow_test (Timeout)
+Errors while running CTest
+";
+    let failures = ctest_failures(output);
+    assert_eq!(failures.len(), 2);
+    assert_eq!(failures[0].test, "parser_test");
+    assert_eq!(failures[1].message, "Timeout");
+    assert!(ctest_failures("100% tests passed, 0 tests failed out of 4\n").is_empty());
+}
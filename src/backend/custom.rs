@@ -0,0 +1,243 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use super::{Backend, Target, TargetMeta};
+use crate::config::BackendConfig;
+use crate::exec::Invocation;
+use crate::git;
+use crate::hooks::shell_quote;
+use crate::report::Skip;
+
+/// A backend whose commands come from a `[[backends]]` entry in kit.toml.
+pub struct CustomBackend {
+    config: BackendConfig,
+}
+
+impl CustomBackend {
+    pub fn new(config: BackendConfig) -> Self {
+        Self { config }
+    }
+
+    /// Directories holding the marker file, relative to the repo root.
+    fn projects(&self, repo_root: &Path) -> Result<Vec<PathBuf>> {
+        let depth = Path::new(&self.config.detect).components().count();
+        let dirs: BTreeSet<PathBuf> = git::find_files(repo_root, &self.config.detect)?
+            .iter()
+            .filter_map(|file| file.ancestors().nth(depth).map(Path::to_path_buf))
+            .collect();
+        Ok(dirs.into_iter().collect())
+    }
+
+    /// The command template for `command` with its placeholders filled in, run with `sh -c`, or a
+    /// [`Skip`] when kit.toml has no template for it.
+    fn run(
+        &self,
+        repo_root: &Path,
+        command: &str,
+        template: Option<&String>,
+        targets: &[String],
+        files: &[PathBuf],
+    ) -> Result<Vec<Invocation>> {
+        let Some(template) = template else {
+            return Err(Skip::NotConfigured {
+                detail: format!("no {command} command for the {} backend in kit.toml", self.config.name),
+            }
+            .into());
+        };
+        let command = render(template, targets, files);
+        Ok(vec![Invocation::new("sh", repo_root).args(["-c", &command])])
+    }
+
+    /// Each target's files: those named on the command line or changed on the branch, or else
+    /// every input file in it.
+    fn files(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let mut whole = Vec::new();
+        for target in targets {
+            if target.files.is_empty() {
+                whole.push(relative(repo_root, &target.dir));
+            } else {
+                files.extend(target.files.iter().cloned());
+            }
+        }
+        if !whole.is_empty() {
+            let matcher = inputs(repo_root, &self.config)?;
+            files.extend(
+                git::find_files(repo_root, "*")?
+                    .into_iter()
+                    .filter(|f| whole.iter().any(|dir| f.starts_with(dir)) && matches(repo_root, &matcher, f)),
+            );
+        }
+        Ok(files)
+    }
+
+    fn plan(
+        &self,
+        repo_root: &Path,
+        command: &str,
+        template: Option<&String>,
+        targets: &[Target],
+    ) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let labels: Vec<String> = targets.iter().map(|t| t.label.clone()).collect();
+        let files = if template.is_some_and(|t| t.contains("{files}")) {
+            self.files(repo_root, targets)?
+        } else {
+            vec![]
+        };
+        self.run(repo_root, command, template, &labels, &files)
+    }
+}
+
+impl Backend for CustomBackend {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        git::find_files(dir, &self.config.detect).is_ok_and(|files| !files.is_empty())
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let found = self
+            .projects(repo_root)
+            .and_then(|projects| Ok((projects, inputs(repo_root, &self.config)?)));
+        let (projects, matcher) = match found {
+            Ok(found) => found,
+            Err(e) => {
+                eprintln!("kit: failed to find {} projects ({e:#})", self.config.name);
+                return vec![];
+            }
+        };
+        let changed: Vec<PathBuf> = changed_files
+            .iter()
+            .filter(|f| matches(repo_root, &matcher, f))
+            .cloned()
+            .collect();
+        affected_projects(&projects, &changed)
+            .into_iter()
+            .map(|(dir, files)| Target {
+                files,
+                ..self.resolve_target(repo_root, repo_root.join(dir))
+            })
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = relative(repo_root, &dir).to_string_lossy().replace('\\', "/");
+        Target {
+            label: if rel.is_empty() { ".".to_string() } else { rel },
+            dir,
+            meta: TargetMeta {
+                kind: Some("project".to_string()),
+                ..TargetMeta::default()
+            },
+            files: Vec::new(),
+        }
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        self.plan(repo_root, "build", self.config.build.as_ref(), targets)
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        self.plan(repo_root, "test", self.config.test.as_ref(), targets)
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        self.plan(repo_root, "lint", self.config.lint.as_ref(), targets)
+    }
+
+    fn check(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        self.plan(repo_root, "check", self.config.check.as_ref(), targets)
+    }
+
+    fn fix(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        self.plan(repo_root, "fix", self.config.fix.as_ref(), targets)
+    }
+
+    fn docs(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        self.plan(repo_root, "docs", self.config.docs.as_ref(), targets)
+    }
+
+    fn generate(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        self.plan(repo_root, "generate", self.config.generate.as_ref(), targets)
+    }
+
+    fn task(&self, repo_root: &Path, task: &str, targets: &[Target]) -> Result<Vec<Invocation>> {
+        self.plan(repo_root, task, self.config.tasks.get(task), targets)
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let matcher = inputs(repo_root, &self.config)?;
+        let files: Vec<PathBuf> = changed_files
+            .iter()
+            .filter(|f| repo_root.join(f).is_file() && matches(repo_root, &matcher, f))
+            .cloned()
+            .collect();
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        self.run(repo_root, "fmt", self.config.fmt.as_ref(), &[], &files)
+    }
+}
+
+fn relative(repo_root: &Path, dir: &Path) -> PathBuf {
+    dir.strip_prefix(repo_root).unwrap_or(dir).to_path_buf()
+}
+
+/// A matcher for the backend's inputs, anchored at the repo root. It matches nothing when there are
+/// no inputs; see [`matches`].
+fn inputs(repo_root: &Path, config: &BackendConfig) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(repo_root);
+    for input in &config.inputs {
+        let input = input.trim_start_matches("./").trim_end_matches('/');
+        builder
+            .add_line(None, &format!("/{input}"))
+            .with_context(|| format!("invalid input `{input}` for the {} backend", config.name))?;
+    }
+    Ok(builder.build()?)
+}
+
+/// Whether a file is one of the inputs; any file is when there are none.
+fn matches(repo_root: &Path, matcher: &Gitignore, file: &Path) -> bool {
+    matcher.is_empty()
+        || matcher
+            .matched_path_or_any_parents(repo_root.join(file), false)
+            .is_ignore()
+}
+
+/// Projects containing a changed file, mapped to the deepest project, with the changed files in
+/// each.
+fn affected_projects(projects: &[PathBuf], changed_files: &[PathBuf]) -> BTreeMap<PathBuf, Vec<PathBuf>> {
+    let mut affected: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+    for file in changed_files {
+        if let Some(project) = projects
+            .iter()
+            .filter(|p| file.starts_with(p))
+            .max_by_key(|p| p.components().count())
+        {
+            affected.entry(project.clone()).or_default().push(file.clone());
+        }
+    }
+    affected
+}
+
+/// A command template with `{targets}` and `{files}` replaced by shell-quoted, space-separated
+/// lists.
+fn render(template: &str, targets: &[String], files: &[PathBuf]) -> String {
+    let quoted = |items: Vec<String>| items.iter().map(|i| shell_quote(i)).collect::<Vec<_>>().join(" ");
+    template.replace("{targets}", &quoted(targets.to_vec())).replace(
+        "{files}",
+        &quoted(files.iter().map(|f| f.to_string_lossy().replace('\\', "/")).collect()),
+    )
+}
+
+#[cfg(test)]
+#[path = "custom_test.rs"]
+mod tests;
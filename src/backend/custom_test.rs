@@ -0,0 +1,65 @@
+use super::*;
+
+#[test]
+fn changed_files_map_to_the_deepest_project() {
+    let projects = [PathBuf::from(""), PathBuf::from("games/racer")];
+    let affected = affected_projects(
+        &projects,
+        &[
+            PathBuf::from("games/racer/Content/Car.uasset"),
+            PathBuf::from("games/racer/Source/Car.cpp"),
+            PathBuf::from("Tools/validate.py"),
+        ],
+    );
+    assert_eq!(
+        affected.into_iter().collect::<Vec<_>>(),
+        [
+            (PathBuf::from(""), vec![PathBuf::from("Tools/validate.py")]),
+            (
+                PathBuf::from("games/racer"),
+                vec![
+                    PathBuf::from("games/racer/Content/Car.uasset"),
+                    PathBuf::from("games/racer/Source/Car.cpp")
+                ]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn placeholders_are_shell_quoted_lists() {
+    let command = render(
+        "validate-assets --project {targets} -- {files}",
+        &["games/racer".to_string()],
+        &[PathBuf::from("games/racer/Content/Main Menu.umap")],
+    );
+    assert_eq!(
+        command,
+        "validate-assets --project 'games/racer' -- 'games/racer/Content/Main Menu.umap'"
+    );
+}
+
+#[test]
+fn any_file_is_an_input_when_none_are_listed() {
+    let repo = tempfile::tempdir().unwrap();
+    let mut config = BackendConfig {
+        name: "unity".to_string(),
+        detect: "ProjectSettings/ProjectVersion.txt".to_string(),
+        inputs: vec![],
+        build: None,
+        test: None,
+        lint: None,
+        check: None,
+        fix: None,
+        generate: None,
+        docs: None,
+        fmt: None,
+        tasks: BTreeMap::new(),
+    };
+    let matcher = inputs(repo.path(), &config).unwrap();
+    assert!(matches(repo.path(), &matcher, Path::new("Assets/readme.txt")));
+    config.inputs = vec!["Assets/**/*.prefab".to_string()];
+    let matcher = inputs(repo.path(), &config).unwrap();
+    assert!(matches(repo.path(), &matcher, Path::new("Assets/Enemies/Bat.prefab")));
+    assert!(!matches(repo.path(), &matcher, Path::new("Assets/readme.txt")));
+}
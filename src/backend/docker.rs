@@ -0,0 +1,265 @@
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use regex::Regex;
+
+use super::{Backend, Target, TargetMeta};
+use crate::config::Config;
+use crate::exec::Invocation;
+use crate::report::Skip;
+use crate::{git, paths};
+
+/// The start of a target block in a bake file: `target "api" {`.
+static BAKE_TARGET: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"(?m)^\s*target\s+"([\w.-]+)"\s*\{"#).unwrap());
+static BAKE_CONTEXT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"(?m)^\s*context\s*=\s*"([^"]*)""#).unwrap());
+static BAKE_DOCKERFILE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?m)^\s*dockerfile\s*=\s*"([^"]*)""#).unwrap());
+
+/// Names Dockerfiles go by: `Dockerfile`, `Dockerfile.worker`, or `worker.Dockerfile`.
+const PATTERNS: &[&str] = &["Dockerfile", "Dockerfile.*", "*.Dockerfile"];
+
+/// Whether a file name matches [`PATTERNS`].
+fn is_dockerfile_name(name: &str) -> bool {
+    name == "Dockerfile"
+        || (name.starts_with("Dockerfile.") && name != "Dockerfile.dockerignore")
+        || name.ends_with(".Dockerfile")
+}
+
+/// Bake file `docker buildx bake` reads by default.
+const BAKE_FILE: &str = "docker-bake.hcl";
+
+/// A target in a bake file, with the Dockerfile it builds.
+#[derive(Debug, Clone, PartialEq)]
+struct BakeTarget {
+    name: String,
+    /// Dockerfile relative to the repo root.
+    dockerfile: PathBuf,
+}
+
+pub struct DockerBackend;
+
+impl DockerBackend {
+    /// Dockerfiles in the repo, relative to the repo root.
+    fn dockerfiles(repo_root: &Path) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for pattern in PATTERNS {
+            files.extend(git::find_files(repo_root, pattern)?);
+        }
+        files.retain(|f| f.extension().is_none_or(|e| e != "dockerignore"));
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+
+    /// `docker build` of one Dockerfile, tagged for [`Backend::test`] to refer to.
+    fn docker_build(repo_root: &Path, target: &Target) -> Invocation {
+        let dockerfile = repo_root.join(&target.label);
+        Invocation::new("docker", repo_root).args([
+            "build".to_string(),
+            "--file".to_string(),
+            dockerfile.to_string_lossy().into_owned(),
+            "--tag".to_string(),
+            image_tag(Path::new(&target.label)),
+            target.dir.to_string_lossy().into_owned(),
+        ])
+    }
+}
+
+impl Backend for DockerBackend {
+    fn name(&self) -> &str {
+        "docker"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join(BAKE_FILE).is_file()
+            || std::fs::read_dir(dir).is_ok_and(|entries| {
+                entries.flatten().any(|e| {
+                    let name = e.file_name().to_string_lossy().into_owned();
+                    e.path().is_file() && is_dockerfile_name(&name)
+                })
+            })
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let dockerfiles = match Self::dockerfiles(repo_root) {
+            Ok(files) => files,
+            Err(e) => {
+                eprintln!("kit: failed to find Dockerfiles ({e:#})");
+                return vec![];
+            }
+        };
+        affected_dockerfiles(&dockerfiles, changed_files)
+            .into_iter()
+            .map(|f| dockerfile_target(repo_root, f))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir);
+        let dockerfile = Self::dockerfiles(repo_root)
+            .unwrap_or_default()
+            .into_iter()
+            .find(|f| f.parent() == Some(rel));
+        dockerfile_target(repo_root, &dockerfile.unwrap_or_else(|| rel.join("Dockerfile")))
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let bake = std::fs::read_to_string(repo_root.join(BAKE_FILE))
+            .map(|text| bake_targets(&text))
+            .unwrap_or_default();
+        let mut baked = Vec::new();
+        let mut plan = Vec::new();
+        for target in targets {
+            match bake.iter().find(|b| b.dockerfile == Path::new(&target.label)) {
+                Some(b) => baked.push(b.name.clone()),
+                None => plan.push(Self::docker_build(repo_root, target)),
+            }
+        }
+        if !baked.is_empty() {
+            plan.insert(
+                0,
+                Invocation::new("docker", repo_root)
+                    .args(["buildx", "bake"])
+                    .args(baked),
+            );
+        }
+        Ok(plan)
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let Some(command) = Config::load(repo_root)?.image.test else {
+            return Err(Skip::NotConfigured {
+                detail: "no [image] test command in kit.toml".to_string(),
+            }
+            .into());
+        };
+        let mut plan = Vec::new();
+        for target in targets {
+            let image = image_tag(Path::new(&target.label));
+            plan.push(Self::docker_build(repo_root, target));
+            plan.push(Invocation::new("sh", repo_root).args(["-c".to_string(), command.replace("{image}", &image)]));
+        }
+        Ok(plan)
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![
+            Invocation::new("hadolint", repo_root).args(targets.iter().map(|t| t.label.clone())),
+        ])
+    }
+
+    fn fmt(&self, _repo_root: &Path, _changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        Ok(vec![])
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new("docker", repo_root).arg("--version")]
+    }
+
+    fn smoke(&self, repo_root: &Path) -> Vec<Invocation> {
+        // Fails when the daemon is not running or not reachable, unlike `docker --version`.
+        vec![Invocation::new("docker", repo_root).args(["info", "--format", "{{.ServerVersion}}"])]
+    }
+}
+
+fn dockerfile_target(repo_root: &Path, dockerfile: &Path) -> Target {
+    let meta = TargetMeta {
+        kind: Some("image".to_string()),
+        language: Some("dockerfile".to_string()),
+        is_test: None,
+        size: None,
+    };
+    Target {
+        label: dockerfile.to_string_lossy().replace('\\', "/"),
+        dir: repo_root.join(dockerfile.parent().unwrap_or(Path::new(""))),
+        meta,
+        files: Vec::new(),
+    }
+}
+
+/// Dockerfiles whose build context, the directory they are in, holds a changed file.
+fn affected_dockerfiles<'a>(dockerfiles: &'a [PathBuf], changed_files: &[PathBuf]) -> Vec<&'a PathBuf> {
+    dockerfiles
+        .iter()
+        .filter(|d| {
+            let context = d.parent().unwrap_or(Path::new(""));
+            changed_files.iter().any(|f| f.starts_with(context))
+        })
+        .collect()
+}
+
+/// The local tag images are built with: `kit/<dir>` for a `Dockerfile`, plus `-<variant>` for
+/// `Dockerfile.<variant>` or `<variant>.Dockerfile`.
+fn image_tag(dockerfile: &Path) -> String {
+    let dir = dockerfile
+        .parent()
+        .unwrap_or(Path::new(""))
+        .to_string_lossy()
+        .replace('\\', "/");
+    let name = dockerfile.file_name().and_then(|n| n.to_str()).unwrap_or("Dockerfile");
+    let variant = name
+        .strip_prefix("Dockerfile.")
+        .or_else(|| name.strip_suffix(".Dockerfile"))
+        .map(|v| format!("-{v}"))
+        .unwrap_or_default();
+    let repo = if dir.is_empty() { "root".to_string() } else { dir };
+    let tag: String = format!("kit/{repo}{variant}")
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "/._-".contains(c) {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("{tag}:dev")
+}
+
+/// Targets in a bake file and the Dockerfile each builds: `dockerfile` (default `Dockerfile`)
+/// inside `context` (default the repo root).
+fn bake_targets(text: &str) -> Vec<BakeTarget> {
+    let starts: Vec<(usize, String)> = BAKE_TARGET
+        .captures_iter(text)
+        .map(|c| (c.get(0).unwrap().end(), c[1].to_string()))
+        .collect();
+    starts
+        .iter()
+        .filter_map(|(start, name)| {
+            let body = block(&text[*start..]);
+            let context = BAKE_CONTEXT
+                .captures(body)
+                .map_or(".".to_string(), |c| c[1].to_string());
+            let dockerfile = BAKE_DOCKERFILE
+                .captures(body)
+                .map_or("Dockerfile".to_string(), |c| c[1].to_string());
+            Some(BakeTarget {
+                name: name.clone(),
+                dockerfile: paths::normalize(&Path::new(&context).join(dockerfile))?,
+            })
+        })
+        .collect()
+}
+
+/// The text up to the brace closing a block whose opening brace was just before `text`.
+fn block(text: &str) -> &str {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return &text[..i],
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+#[path = "docker_test.rs"]
+mod tests;
@@ -0,0 +1,131 @@
+use super::*;
+
+const BAKE: &str = r#"
+group "default" {
+  targets = ["api", "worker"]
+}
+
+target "api" {
+  context = "services/api"
+  args = {
+    VERSION = "1.0"
+  }
+}
+
+target "worker" {
+  context    = "services/api"
+  dockerfile = "Dockerfile.worker"
+}
+
+target "base" {}
+"#;
+
+#[test]
+fn dockerfiles_are_affected_by_changes_in_their_build_context() {
+    let dockerfiles = vec![
+        PathBuf::from("services/api/Dockerfile"),
+        PathBuf::from("services/web/Dockerfile"),
+        PathBuf::from("tools/ci.Dockerfile"),
+    ];
+    let changed = vec![PathBuf::from("services/api/src/main.rs"), PathBuf::from("README.md")];
+    assert_eq!(
+        affected_dockerfiles(&dockerfiles, &changed),
+        vec![&PathBuf::from("services/api/Dockerfile")]
+    );
+}
+
+#[test]
+fn a_dockerfile_at_the_root_is_affected_by_any_change() {
+    let dockerfiles = vec![PathBuf::from("Dockerfile")];
+    let changed = vec![PathBuf::from("docs/index.md")];
+    assert_eq!(affected_dockerfiles(&dockerfiles, &changed).len(), 1);
+}
+
+#[test]
+fn image_tags_name_the_directory_and_variant() {
+    assert_eq!(image_tag(Path::new("services/api/Dockerfile")), "kit/services/api:dev");
+    assert_eq!(
+        image_tag(Path::new("services/api/Dockerfile.worker")),
+        "kit/services/api-worker:dev"
+    );
+    assert_eq!(image_tag(Path::new("tools/CI.Dockerfile")), "kit/tools-ci:dev");
+    assert_eq!(image_tag(Path::new("Dockerfile")), "kit/root:dev");
+    assert_eq!(image_tag(Path::new("My App/Dockerfile")), "kit/my-app:dev");
+}
+
+#[test]
+fn bake_targets_resolve_dockerfiles_inside_their_context() {
+    assert_eq!(
+        bake_targets(BAKE),
+        vec![
+            BakeTarget {
+                name: "api".to_string(),
+                dockerfile: PathBuf::from("services/api/Dockerfile"),
+            },
+            BakeTarget {
+                name: "worker".to_string(),
+                dockerfile: PathBuf::from("services/api/Dockerfile.worker"),
+            },
+            BakeTarget {
+                name: "base".to_string(),
+                dockerfile: PathBuf::from("Dockerfile"),
+            },
+        ]
+    );
+}
+
+#[test]
+fn build_bakes_targets_the_bake_file_defines() {
+    let repo = tempfile::tempdir().unwrap();
+    std::fs::write(repo.path().join(BAKE_FILE), BAKE).unwrap();
+    let targets = vec![
+        dockerfile_target(repo.path(), Path::new("services/api/Dockerfile.worker")),
+        dockerfile_target(repo.path(), Path::new("services/web/Dockerfile")),
+    ];
+    let plan = DockerBackend.build(repo.path(), &targets).unwrap();
+    assert_eq!(plan.len(), 2);
+    assert_eq!(plan[0].args, ["buildx", "bake", "worker"]);
+    assert_eq!(plan[1].args[0], "build");
+    assert!(plan[1].args.contains(&"kit/services/web:dev".to_string()));
+    assert_eq!(
+        plan[1].args.last().map(PathBuf::from),
+        Some(repo.path().join("services/web"))
+    );
+}
+
+#[test]
+fn test_without_a_command_is_skipped() {
+    let repo = tempfile::tempdir().unwrap();
+    let targets = vec![dockerfile_target(repo.path(), Path::new("Dockerfile"))];
+    let err = DockerBackend.test(repo.path(), &targets).unwrap_err();
+    assert!(matches!(Skip::find(&err), Some(Skip::NotConfigured { .. })));
+}
+
+#[test]
+fn test_runs_the_configured_command_on_each_image() {
+    let repo = tempfile::tempdir().unwrap();
+    std::fs::write(
+        repo.path().join("kit.toml"),
+        "[image]\ntest = \"docker run --rm {image} --version\"\n",
+    )
+    .unwrap();
+    let targets = vec![dockerfile_target(repo.path(), Path::new("app/Dockerfile"))];
+    let plan = DockerBackend.test(repo.path(), &targets).unwrap();
+    assert_eq!(plan.len(), 2);
+    assert_eq!(plan[1].program, "sh");
+    assert_eq!(plan[1].args, ["-c", "docker run --rm kit/app:dev --version"]);
+}
+
+#[test]
+fn detection_needs_a_dockerfile_or_bake_file_at_the_root() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("services")).unwrap();
+    std::fs::write(dir.path().join("services/Dockerfile"), "").unwrap();
+    std::fs::write(dir.path().join("Dockerfile.dockerignore"), "").unwrap();
+    assert!(!DockerBackend.detect(dir.path()));
+    std::fs::write(dir.path().join(BAKE_FILE), "").unwrap();
+    assert!(DockerBackend.detect(dir.path()));
+    std::fs::remove_file(dir.path().join(BAKE_FILE)).unwrap();
+    std::fs::write(dir.path().join("worker.Dockerfile"), "").unwrap();
+    assert!(DockerBackend.detect(dir.path()));
+}
@@ -0,0 +1,305 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use regex::Regex;
+
+use super::maven::normalize;
+use super::{Backend, Target, TargetMeta};
+use crate::exec::Invocation;
+use crate::failures::Failure;
+use crate::git;
+
+static PROJECT_REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"<ProjectReference\s[^>]*?Include\s*=\s*"([^"]+)""#).unwrap());
+/// A failed test in `dotnet test` output: "  Failed Shop.Tests.CartTests.Adds [12 ms]".
+static FAILED: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*Failed (\S+) \[").unwrap());
+/// A stack frame with a location: "at Shop.Tests.CartTests.Adds() in /repo/tests/CartTests.cs:line 12".
+static FRAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*at .+ in (.+):line (\d+)").unwrap());
+
+/// MSBuild project file extensions and the language of each.
+const PROJECTS: &[(&str, &str)] = &[("csproj", "csharp"), ("fsproj", "fsharp"), ("vbproj", "vb")];
+/// Files that configure every project in their directory and below it.
+const SHARED: &[&str] = &[
+    "Directory.Build.props",
+    "Directory.Build.targets",
+    "Directory.Packages.props",
+    "global.json",
+    "NuGet.config",
+    "nuget.config",
+];
+
+/// A project in the repo.
+#[derive(Debug, Clone, PartialEq)]
+struct Project {
+    /// Project file, relative to the repo root.
+    file: PathBuf,
+    /// Project files it references, relative to the repo root.
+    references: Vec<PathBuf>,
+}
+
+pub struct DotnetBackend;
+
+impl DotnetBackend {
+    /// Every project file in the repo with its project references.
+    fn projects(repo_root: &Path) -> Result<Vec<Project>> {
+        let mut projects = Vec::new();
+        for (ext, _) in PROJECTS {
+            for file in git::find_files(repo_root, &format!("*.{ext}"))? {
+                let text = std::fs::read_to_string(repo_root.join(&file)).unwrap_or_default();
+                let dir = file.parent().unwrap_or(Path::new(""));
+                let references = parse_references(&text)
+                    .into_iter()
+                    .map(|r| normalize(&dir.join(r)))
+                    .collect();
+                projects.push(Project { file, references });
+            }
+        }
+        Ok(projects)
+    }
+
+    /// `dotnet <verb> <project>` for each target, followed by `args`.
+    fn per_target(repo_root: &Path, verb: &str, targets: &[&Target], args: &[&str]) -> Vec<Invocation> {
+        targets
+            .iter()
+            .map(|t| {
+                Invocation::new("dotnet", repo_root)
+                    .args([verb, t.label.as_str()])
+                    .args(args.iter().copied())
+            })
+            .collect()
+    }
+
+    /// `dotnet format <project> --include <files>` for the changed source files of each project, with
+    /// `extra` arguments.
+    fn format(repo_root: &Path, changed_files: &[PathBuf], extra: &[&str]) -> Result<Vec<Invocation>> {
+        let projects = Self::projects(repo_root)?;
+        let sources: Vec<PathBuf> = changed_files
+            .iter()
+            .filter(|f| f.extension().is_some_and(|e| e == "cs" || e == "fs" || e == "vb"))
+            .filter(|f| repo_root.join(f).is_file())
+            .cloned()
+            .collect();
+        let mut by_project: BTreeMap<&Path, Vec<String>> = BTreeMap::new();
+        for file in &sources {
+            if let Some(project) = owning_project(&projects, file) {
+                by_project
+                    .entry(project)
+                    .or_default()
+                    .push(file.to_string_lossy().replace('\\', "/"));
+            }
+        }
+        Ok(by_project
+            .into_iter()
+            .map(|(project, files)| {
+                Invocation::new("dotnet", repo_root)
+                    .args(["format".to_string(), project.to_string_lossy().replace('\\', "/")])
+                    .arg("--include")
+                    .args(files)
+                    .args(extra.iter().copied())
+            })
+            .collect())
+    }
+}
+
+impl Backend for DotnetBackend {
+    fn name(&self) -> &str {
+        "dotnet"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        std::fs::read_dir(dir).is_ok_and(|entries| {
+            entries
+                .flatten()
+                .any(|e| project_language(&e.path()).is_some() || e.path().extension().is_some_and(|e| e == "sln"))
+        })
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let projects = match Self::projects(repo_root) {
+            Ok(projects) => projects,
+            Err(e) => {
+                eprintln!("kit: failed to find .NET projects ({e:#})");
+                return vec![];
+            }
+        };
+        affected_projects(&projects, changed_files)
+            .into_iter()
+            .map(|file| project_target(repo_root, &file))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let project_files: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|e| e.path())
+                    .filter(|p| project_language(p).is_some())
+                    .collect()
+            })
+            .unwrap_or_default();
+        if let [file] = project_files.as_slice() {
+            return project_target(repo_root, file.strip_prefix(repo_root).unwrap_or(file));
+        }
+        // With a solution or several projects, dotnet finds what to run in the directory itself.
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_string_lossy();
+        let rel = rel.replace('\\', "/");
+        Target {
+            label: if rel.is_empty() { ".".to_string() } else { rel },
+            dir,
+            meta: TargetMeta::default(),
+            files: Vec::new(),
+        }
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let targets: Vec<&Target> = targets.iter().collect();
+        Ok(Self::per_target(repo_root, "build", &targets, &[]))
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        // Library projects are affected through the test projects that reference them; testing them
+        // directly finds no tests.
+        let targets: Vec<&Target> = targets.iter().filter(|t| t.meta.is_test != Some(false)).collect();
+        Ok(Self::per_target(repo_root, "test", &targets, &[]))
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let targets: Vec<&Target> = targets.iter().collect();
+        Ok(Self::per_target(
+            repo_root,
+            "format",
+            &targets,
+            &["--verify-no-changes"],
+        ))
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        Self::format(repo_root, changed_files, &[])
+    }
+
+    fn fmt_check(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        Self::format(repo_root, changed_files, &["--verify-no-changes"])
+    }
+
+    fn parse_failures(&self, repo_root: &Path, output: &str) -> Vec<Failure> {
+        test_failures(repo_root, output)
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new("dotnet", repo_root).arg("--version")]
+    }
+}
+
+/// The language of a project file, or None if `path` isn't one.
+fn project_language(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?;
+    PROJECTS.iter().find(|(e, _)| *e == ext).map(|(_, language)| *language)
+}
+
+/// A target for a project file relative to the repo root. Projects using the test SDK are tests.
+fn project_target(repo_root: &Path, file: &Path) -> Target {
+    let text = std::fs::read_to_string(repo_root.join(file)).unwrap_or_default();
+    let is_test = text.contains("Microsoft.NET.Test.Sdk") || text.contains("<IsTestProject>true</IsTestProject>");
+    let meta = TargetMeta {
+        kind: Some("project".to_string()),
+        language: project_language(file).map(String::from),
+        is_test: Some(is_test),
+        size: None,
+    };
+    Target {
+        label: file.to_string_lossy().replace('\\', "/"),
+        dir: repo_root.join(file.parent().unwrap_or(Path::new(""))),
+        meta,
+        files: Vec::new(),
+    }
+}
+
+/// Project paths in `<ProjectReference Include="...">` entries, with Windows separators turned
+/// into `/`.
+fn parse_references(project: &str) -> Vec<PathBuf> {
+    PROJECT_REFERENCE
+        .captures_iter(project)
+        .map(|c| PathBuf::from(c[1].replace('\\', "/")))
+        .collect()
+}
+
+/// The project whose directory most closely contains `file`.
+fn owning_project<'a>(projects: &'a [Project], file: &Path) -> Option<&'a Path> {
+    projects
+        .iter()
+        .map(|p| p.file.as_path())
+        .filter(|p| file.starts_with(p.parent().unwrap_or(Path::new(""))))
+        .max_by_key(|p| p.components().count())
+}
+
+/// Project files affected by the changes: the project owning each changed file, every project
+/// below a changed shared file such as `Directory.Build.props`, and everything that references
+/// an affected project, directly or not.
+fn affected_projects(projects: &[Project], changed_files: &[PathBuf]) -> BTreeSet<PathBuf> {
+    let mut affected = BTreeSet::new();
+    for file in changed_files {
+        let name = file.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if SHARED.contains(&name) {
+            let dir = file.parent().unwrap_or(Path::new(""));
+            affected.extend(
+                projects
+                    .iter()
+                    .filter(|p| p.file.starts_with(dir))
+                    .map(|p| p.file.clone()),
+            );
+        } else if let Some(project) = owning_project(projects, file) {
+            affected.insert(project.to_path_buf());
+        }
+    }
+    loop {
+        let dependents: Vec<PathBuf> = projects
+            .iter()
+            .filter(|p| !affected.contains(&p.file) && p.references.iter().any(|r| affected.contains(r)))
+            .map(|p| p.file.clone())
+            .collect();
+        if dependents.is_empty() {
+            return affected;
+        }
+        affected.extend(dependents);
+    }
+}
+
+/// Failed tests in `dotnet test` output, with the message under "Error Message:" and the first
+/// stack frame that has a location.
+fn test_failures(repo_root: &Path, output: &str) -> Vec<Failure> {
+    let mut failures: Vec<Failure> = Vec::new();
+    let mut lines = output.lines();
+    while let Some(line) = lines.next() {
+        if let Some(c) = FAILED.captures(line) {
+            failures.push(Failure {
+                test: c[1].to_string(),
+                file: None,
+                line: None,
+                message: "failed".to_string(),
+            });
+            continue;
+        }
+        let Some(failure) = failures.last_mut() else {
+            continue;
+        };
+        if line.trim() == "Error Message:" {
+            if let Some(message) = lines.by_ref().map(str::trim).find(|l| !l.is_empty()) {
+                failure.message = message.to_string();
+            }
+        } else if failure.file.is_none()
+            && let Some(c) = FRAME.captures(line)
+        {
+            let file = PathBuf::from(&c[1]);
+            failure.file = Some(file.strip_prefix(repo_root).map(Path::to_path_buf).unwrap_or(file));
+            failure.line = c[2].parse().ok();
+        }
+    }
+    failures
+}
+
+#[cfg(test)]
+#[path = "dotnet_test.rs"]
+mod tests;
@@ -0,0 +1,93 @@
+use super::*;
+
+fn project(file: &str, references: &[&str]) -> Project {
+    Project {
+        file: PathBuf::from(file),
+        references: references.iter().map(PathBuf::from).collect(),
+    }
+}
+
+#[test]
+fn references_are_read_with_forward_slashes() {
+    let csproj = r#"<Project Sdk="Microsoft.NET.Sdk">
+  <ItemGroup>
+    <ProjectReference Include="..\Shop\Shop.csproj" />
+    <ProjectReference Condition="'$(X)' == ''" Include="../Util/Util.fsproj" />
+  </ItemGroup>
+</Project>"#;
+    assert_eq!(
+        parse_references(csproj),
+        [
+            PathBuf::from("../Shop/Shop.csproj"),
+            PathBuf::from("../Util/Util.fsproj")
+        ]
+    );
+}
+
+#[test]
+fn affected_projects_include_dependents_and_shared_files() {
+    let projects = [
+        project("src/Util/Util.csproj", &[]),
+        project("src/Shop/Shop.csproj", &["src/Util/Util.csproj"]),
+        project("tests/Shop.Tests/Shop.Tests.csproj", &["src/Shop/Shop.csproj"]),
+        project("tools/Gen/Gen.csproj", &[]),
+    ];
+    let affected = affected_projects(&projects, &[PathBuf::from("src/Util/Strings.cs")]);
+    assert_eq!(
+        affected.into_iter().collect::<Vec<_>>(),
+        [
+            PathBuf::from("src/Shop/Shop.csproj"),
+            PathBuf::from("src/Util/Util.csproj"),
+            PathBuf::from("tests/Shop.Tests/Shop.Tests.csproj"),
+        ]
+    );
+    let affected = affected_projects(&projects, &[PathBuf::from("tools/Directory.Build.props")]);
+    assert_eq!(
+        affected.into_iter().collect::<Vec<_>>(),
+        [PathBuf::from("tools/Gen/Gen.csproj")]
+    );
+    assert!(affected_projects(&projects, &[PathBuf::from("README.md")]).is_empty());
+}
+
+#[test]
+fn test_only_runs_test_projects() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    std::fs::create_dir_all(root.join("src/Shop")).unwrap();
+    std::fs::create_dir_all(root.join("tests")).unwrap();
+    std::fs::write(root.join("src/Shop/Shop.csproj"), "<Project />").unwrap();
+    std::fs::write(
+        root.join("tests/Shop.Tests.csproj"),
+        r#"<PackageReference Include="Microsoft.NET.Test.Sdk" />"#,
+    )
+    .unwrap();
+    let targets = [
+        DotnetBackend.resolve_target(root, root.join("src/Shop")),
+        DotnetBackend.resolve_target(root, root.join("tests")),
+    ];
+    assert_eq!(targets[0].label, "src/Shop/Shop.csproj");
+    assert_eq!(
+        DotnetBackend.test(root, &targets).unwrap(),
+        [Invocation::new("dotnet", root).args(["test", "tests/Shop.Tests.csproj"])]
+    );
+}
+
+#[test]
+fn test_failures_read_message_and_location() {
+    let output = "\
+  Failed Shop.Tests.CartTests.Adds [12 ms]
+  Error Message:
+   Assert.Equal() Failure
+Expected: 3
+  Stack Trace:
+     at Shop.Tests.CartTests.Adds() in /repo/tests/CartTests.cs:line 12
+  Failed Shop.Tests.CartTests.Removes [1 ms]
+Passed!  - Failed: 2, Passed: 10
+";
+    let failures = test_failures(Path::new("/repo"), output);
+    assert_eq!(failures.len(), 2);
+    assert_eq!(failures[0].test, "Shop.Tests.CartTests.Adds");
+    assert_eq!(failures[0].message, "Assert.Equal() Failure");
+    assert_eq!(failures[0].location().as_deref(), Some("tests/CartTests.cs:12"));
+    assert_eq!(failures[1].location(), None);
+}
@@ -0,0 +1,265 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use regex::Regex;
+
+use super::{Backend, Target, TargetMeta};
+use crate::exec::Invocation;
+use crate::git;
+
+/// A library's `(name foo)` or `(public_name foo.bar)` field.
+static NAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\((?:public_)?name\s+([^\s()]+)\s*\)").unwrap());
+/// A `(libraries a b c)` field, up to the first nested form such as `(select ...)`.
+static LIBRARIES: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\(libraries\s+([^()]*)").unwrap());
+/// Stanzas that define tests: `(test ...)`, `(tests ...)`, or a rule attached to `runtest`.
+static TESTS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?m)^\s*\(tests?\b|\(alias\s+runtest\s*\)").unwrap());
+
+/// Files at the repo root that configure every directory in the project.
+const PROJECT_FILES: &[&str] = &["dune-project", "dune-workspace"];
+/// Extensions of the files that belong to a directory's build.
+const SOURCES: &[&str] = &["ml", "mli", "mll", "mly", "opam"];
+
+/// A directory with a `dune` file.
+#[derive(Debug, Clone, PartialEq)]
+struct DuneDir {
+    /// Directory relative to the repo root.
+    dir: PathBuf,
+    /// Names (and public names) of the libraries it defines.
+    libraries: BTreeSet<String>,
+    /// Names of the libraries its stanzas use.
+    depends: BTreeSet<String>,
+    has_tests: bool,
+}
+
+pub struct DuneBackend;
+
+impl DuneBackend {
+    fn dirs(repo_root: &Path) -> Result<Vec<DuneDir>> {
+        Ok(git::find_files(repo_root, "dune")?
+            .into_iter()
+            .map(|file| {
+                let text = std::fs::read_to_string(repo_root.join(&file)).unwrap_or_default();
+                parse_dune(file.parent().unwrap_or(Path::new("")), &text)
+            })
+            .collect())
+    }
+
+    /// `@<dir>/<alias>` for each target, or `@<alias>` alone for the whole project if the root is
+    /// among them.
+    fn aliases(targets: &[&Target], alias: &str) -> Vec<String> {
+        if targets.iter().any(|t| t.label == ".") {
+            return vec![format!("@{alias}")];
+        }
+        targets.iter().map(|t| format!("@{}/{alias}", t.label)).collect()
+    }
+}
+
+impl Backend for DuneBackend {
+    fn name(&self) -> &str {
+        "dune"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join("dune-project").is_file()
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let dirs = match Self::dirs(repo_root) {
+            Ok(dirs) => dirs,
+            Err(e) => {
+                eprintln!("kit: failed to find dune directories ({e:#})");
+                return vec![];
+            }
+        };
+        affected_dirs(&dirs, changed_files)
+            .into_iter()
+            .filter_map(|dir| dirs.iter().find(|d| d.dir == dir))
+            .map(|d| dir_target(repo_root, d))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_path_buf();
+        let known = Self::dirs(repo_root)
+            .unwrap_or_default()
+            .into_iter()
+            .find(|d| d.dir == rel);
+        if let Some(known) = known {
+            return dir_target(repo_root, &known);
+        }
+        // dune takes any directory, building or testing everything below it.
+        Target {
+            label: label(&rel),
+            dir,
+            meta: TargetMeta {
+                kind: Some("directory".to_string()),
+                language: Some("ocaml".to_string()),
+                ..TargetMeta::default()
+            },
+            files: Vec::new(),
+        }
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let targets: Vec<&Target> = targets.iter().collect();
+        Ok(vec![
+            Invocation::new("dune", repo_root)
+                .arg("build")
+                .args(Self::aliases(&targets, "default")),
+        ])
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let targets: Vec<&Target> = targets.iter().filter(|t| t.meta.is_test != Some(false)).collect();
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let invocation = Invocation::new("dune", repo_root).arg("runtest");
+        if targets.iter().any(|t| t.label == ".") {
+            return Ok(vec![invocation]);
+        }
+        Ok(vec![invocation.args(targets.iter().map(|t| t.label.clone()))])
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        // The fmt alias diffs each file against its formatted version without promoting it, so it
+        // fails on unformatted code; OCaml sources are only checked with an .ocamlformat.
+        let targets: Vec<&Target> = targets.iter().collect();
+        Ok(vec![
+            Invocation::new("dune", repo_root)
+                .arg("build")
+                .args(Self::aliases(&targets, "fmt")),
+        ])
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let changed: Vec<&PathBuf> = changed_files.iter().filter(|f| repo_root.join(f).is_file()).collect();
+        let sources: Vec<String> = changed
+            .iter()
+            .filter(|f| f.extension().is_some_and(|e| e == "ml" || e == "mli"))
+            .map(|f| f.to_string_lossy().replace('\\', "/"))
+            .collect();
+        let dune_files = changed.iter().any(|f| {
+            let name = f.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            name == "dune" || PROJECT_FILES.contains(&name)
+        });
+        let mut plan = Vec::new();
+        if !sources.is_empty() && repo_root.join(".ocamlformat").is_file() {
+            plan.push(Invocation::new("ocamlformat", repo_root).arg("--inplace").args(sources));
+        }
+        // ocamlformat doesn't format dune files; `dune fmt` formats them along with everything else.
+        if dune_files {
+            plan.push(Invocation::new("dune", repo_root).arg("fmt"));
+        }
+        Ok(plan)
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![
+            Invocation::new("dune", repo_root).arg("--version"),
+            Invocation::new("ocamlc", repo_root).arg("-version"),
+        ]
+    }
+}
+
+fn label(rel: &Path) -> String {
+    let rel = rel.to_string_lossy().replace('\\', "/");
+    if rel.is_empty() { ".".to_string() } else { rel }
+}
+
+fn dir_target(repo_root: &Path, dir: &DuneDir) -> Target {
+    let meta = TargetMeta {
+        kind: Some("directory".to_string()),
+        language: Some("ocaml".to_string()),
+        is_test: Some(dir.has_tests),
+        size: None,
+    };
+    Target {
+        label: label(&dir.dir),
+        dir: repo_root.join(&dir.dir),
+        meta,
+        files: Vec::new(),
+    }
+}
+
+/// A directory from its `dune` file. Stanzas start at the beginning of a line, as `dune fmt`
+/// writes them.
+fn parse_dune(dir: &Path, text: &str) -> DuneDir {
+    let text: String = text
+        .lines()
+        .map(|l| l.split(';').next().unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut libraries = BTreeSet::new();
+    let mut depends = BTreeSet::new();
+    for stanza in text.split("\n(").map(|s| s.trim_start_matches('(')) {
+        if stanza.starts_with("library") {
+            libraries.extend(NAME.captures_iter(stanza).map(|c| c[1].to_string()));
+        }
+        for fields in LIBRARIES.captures_iter(stanza) {
+            depends.extend(fields[1].split_whitespace().map(String::from));
+        }
+    }
+    DuneDir {
+        dir: dir.to_path_buf(),
+        depends: depends.difference(&libraries).cloned().collect(),
+        libraries,
+        has_tests: TESTS.is_match(&text),
+    }
+}
+
+/// Directories affected by the changes: the deepest one containing each changed source or dune
+/// file, every directory when a project file at the root changed, and those using a library
+/// defined in an affected one.
+fn affected_dirs(dirs: &[DuneDir], changed_files: &[PathBuf]) -> BTreeSet<PathBuf> {
+    let mut affected = BTreeSet::new();
+    for file in changed_files {
+        let name = file.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if PROJECT_FILES.contains(&name) && file.parent().is_none_or(|p| p.as_os_str().is_empty()) {
+            return dirs.iter().map(|d| d.dir.clone()).collect();
+        }
+        let is_source = name == "dune"
+            || file
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| SOURCES.contains(&e));
+        if !is_source {
+            continue;
+        }
+        if let Some(dir) = dirs
+            .iter()
+            .filter(|d| file.starts_with(&d.dir))
+            .max_by_key(|d| d.dir.components().count())
+        {
+            affected.insert(dir.dir.clone());
+        }
+    }
+    loop {
+        let used: BTreeSet<&String> = dirs
+            .iter()
+            .filter(|d| affected.contains(&d.dir))
+            .flat_map(|d| &d.libraries)
+            .collect();
+        let dependents: Vec<PathBuf> = dirs
+            .iter()
+            .filter(|d| !affected.contains(&d.dir) && d.depends.iter().any(|l| used.contains(l)))
+            .map(|d| d.dir.clone())
+            .collect();
+        if dependents.is_empty() {
+            return affected;
+        }
+        affected.extend(dependents);
+    }
+}
+
+#[cfg(test)]
+#[path = "dune_test.rs"]
+mod tests;
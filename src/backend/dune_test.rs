@@ -0,0 +1,53 @@
+use super::*;
+
+const CORE_DUNE: &str = "\
+(library
+ (name core)
+ (public_name shop.core) ; the name other packages use
+ (libraries fmt yojson))
+";
+
+const CORE_TEST_DUNE: &str = "\
+(test
+ (name test_core)
+ (libraries core alcotest))
+";
+
+const API_DUNE: &str = "\
+(executable
+ (name main)
+ (libraries shop.core cmdliner))
+";
+
+#[test]
+fn libraries_and_their_users_are_parsed_from_dune_files() {
+    let core = parse_dune(Path::new("src/core"), CORE_DUNE);
+    assert_eq!(core.libraries.iter().collect::<Vec<_>>(), ["core", "shop.core"]);
+    assert_eq!(core.depends.iter().collect::<Vec<_>>(), ["fmt", "yojson"]);
+    assert!(!core.has_tests);
+    let api = parse_dune(Path::new("bin"), API_DUNE);
+    assert!(api.libraries.is_empty());
+    assert_eq!(api.depends.iter().collect::<Vec<_>>(), ["cmdliner", "shop.core"]);
+    assert!(parse_dune(Path::new("test"), CORE_TEST_DUNE).has_tests);
+}
+
+#[test]
+fn affected_dirs_include_library_users() {
+    let dirs = [
+        parse_dune(Path::new("src/core"), CORE_DUNE),
+        parse_dune(Path::new("test"), CORE_TEST_DUNE),
+        parse_dune(Path::new("bin"), API_DUNE),
+    ];
+    let affected = affected_dirs(&dirs, &[PathBuf::from("src/core/cart.ml")]);
+    assert_eq!(
+        affected.into_iter().collect::<Vec<_>>(),
+        [PathBuf::from("bin"), PathBuf::from("src/core"), PathBuf::from("test")]
+    );
+    assert_eq!(
+        affected_dirs(&dirs, &[PathBuf::from("bin/main.ml"), PathBuf::from("README.md")])
+            .into_iter()
+            .collect::<Vec<_>>(),
+        [PathBuf::from("bin")]
+    );
+    assert_eq!(affected_dirs(&dirs, &[PathBuf::from("dune-project")]).len(), 3);
+}
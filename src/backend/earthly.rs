@@ -0,0 +1,196 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use regex::Regex;
+
+use super::{Backend, Target, TargetMeta};
+use crate::exec::Invocation;
+use crate::report::Skip;
+use crate::{git, paths};
+
+/// A target definition: an unindented `build:` line.
+static TARGET: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?m)^([a-z][A-Za-z0-9.-]*):\s*$").unwrap());
+/// A reference to a target or artifact of another Earthfile in the repo: `./libs/auth+build`,
+/// `../base+image`, or `./proto+gen/api.pb.go`.
+static REFERENCE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?:^|[\s=])(\.\.?(?:/[^\s+]*)?)\+[A-Za-z]").unwrap());
+
+/// A directory with an Earthfile.
+#[derive(Debug, Clone, PartialEq)]
+struct Earthfile {
+    /// Directory relative to the repo root.
+    dir: PathBuf,
+    /// Names of the targets it defines.
+    targets: BTreeSet<String>,
+    /// Directories of the Earthfiles in the repo it refers to.
+    local: BTreeSet<PathBuf>,
+}
+
+pub struct EarthlyBackend;
+
+impl EarthlyBackend {
+    fn earthfiles(repo_root: &Path) -> Result<Vec<Earthfile>> {
+        Ok(git::find_files(repo_root, "Earthfile")?
+            .into_iter()
+            .map(|file| {
+                let text = std::fs::read_to_string(repo_root.join(&file)).unwrap_or_default();
+                parse_earthfile(file.parent().unwrap_or(Path::new("")), &text)
+            })
+            .collect())
+    }
+
+    /// `earthly ./<dir>+<target>` for each target's Earthfile defining the target, or a [`Skip`] when
+    /// none does.
+    fn run(repo_root: &Path, target: &str, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let earthfiles = Self::earthfiles(repo_root)?;
+        let plan: Vec<Invocation> = targets
+            .iter()
+            .filter_map(|t| earthfiles.iter().find(|e| repo_root.join(&e.dir) == t.dir))
+            .filter(|e| e.targets.contains(target))
+            .map(|e| Invocation::new("earthly", repo_root).arg(reference(&e.dir, target)))
+            .collect();
+        if plan.is_empty() {
+            return Err(Skip::NotConfigured {
+                detail: format!("no +{target} target in the affected Earthfiles"),
+            }
+            .into());
+        }
+        Ok(plan)
+    }
+}
+
+impl Backend for EarthlyBackend {
+    fn name(&self) -> &str {
+        "earthly"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join("Earthfile").is_file()
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let earthfiles = match Self::earthfiles(repo_root) {
+            Ok(earthfiles) => earthfiles,
+            Err(e) => {
+                eprintln!("kit: failed to find Earthfiles ({e:#})");
+                return vec![];
+            }
+        };
+        affected_earthfiles(&earthfiles, changed_files)
+            .into_iter()
+            .map(|dir| self.resolve_target(repo_root, repo_root.join(dir)))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_string_lossy();
+        let rel = rel.replace('\\', "/");
+        let text = std::fs::read_to_string(dir.join("Earthfile")).unwrap_or_default();
+        let meta = TargetMeta {
+            kind: Some("earthfile".to_string()),
+            language: None,
+            is_test: Some(parse_earthfile(Path::new(""), &text).targets.contains("test")),
+            size: None,
+        };
+        Target {
+            label: if rel.is_empty() { ".".to_string() } else { rel },
+            dir,
+            meta,
+            files: Vec::new(),
+        }
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Self::run(repo_root, "build", targets)
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Self::run(repo_root, "test", targets)
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Self::run(repo_root, "lint", targets)
+    }
+
+    fn task(&self, repo_root: &Path, task: &str, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Self::run(repo_root, task, targets)
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        // Only the Earthfiles around the changed files: a +fmt target formats its own directory, so
+        // those referring to it have nothing new to format.
+        let earthfiles = Self::earthfiles(repo_root)?;
+        let owners: Vec<Target> = changed_files
+            .iter()
+            .filter_map(|f| owning_earthfile(&earthfiles, f))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|dir| self.resolve_target(repo_root, repo_root.join(dir)))
+            .collect();
+        Self::run(repo_root, "fmt", &owners)
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new("earthly", repo_root).arg("--version")]
+    }
+}
+
+/// `+build` for the Earthfile at the root, `./services/api+build` for others.
+fn reference(dir: &Path, target: &str) -> String {
+    if dir.as_os_str().is_empty() {
+        format!("+{target}")
+    } else {
+        format!("./{}+{target}", dir.to_string_lossy().replace('\\', "/"))
+    }
+}
+
+fn parse_earthfile(dir: &Path, text: &str) -> Earthfile {
+    let local = text
+        .lines()
+        .filter(|l| !l.trim_start().starts_with('#'))
+        .flat_map(|l| REFERENCE.captures_iter(l))
+        .filter_map(|c| paths::normalize(&dir.join(&c[1])))
+        .filter(|d| d != dir)
+        .collect();
+    Earthfile {
+        dir: dir.to_path_buf(),
+        targets: TARGET.captures_iter(text).map(|c| c[1].to_string()).collect(),
+        local,
+    }
+}
+
+fn owning_earthfile(earthfiles: &[Earthfile], file: &Path) -> Option<PathBuf> {
+    earthfiles
+        .iter()
+        .filter(|e| file.starts_with(&e.dir))
+        .max_by_key(|e| e.dir.components().count())
+        .map(|e| e.dir.clone())
+}
+
+/// Earthfiles whose directory holds a changed file, mapped to the deepest one, plus those referring
+/// to an affected Earthfile, directly or not.
+fn affected_earthfiles(earthfiles: &[Earthfile], changed_files: &[PathBuf]) -> BTreeSet<PathBuf> {
+    let mut affected: BTreeSet<PathBuf> = changed_files
+        .iter()
+        .filter_map(|f| owning_earthfile(earthfiles, f))
+        .collect();
+    loop {
+        let dependents: Vec<PathBuf> = earthfiles
+            .iter()
+            .filter(|e| !affected.contains(&e.dir) && e.local.iter().any(|d| affected.contains(d)))
+            .map(|e| e.dir.clone())
+            .collect();
+        if dependents.is_empty() {
+            return affected;
+        }
+        affected.extend(dependents);
+    }
+}
+
+#[cfg(test)]
+#[path = "earthly_test.rs"]
+mod tests;
@@ -0,0 +1,68 @@
+use super::*;
+
+const API: &str = "\
+VERSION 0.8
+FROM golang:1.22
+
+deps:
+    COPY ../../libs/auth+src/auth ./auth
+    COPY go.mod go.sum ./
+
+build:
+    FROM +deps
+    BUILD ../../proto+gen
+    RUN go build ./...
+
+test:
+    FROM +deps
+    # BUILD ../../tools+lint is not a reference
+    RUN go test ./...
+";
+
+#[test]
+fn earthfiles_list_targets_and_references() {
+    let api = parse_earthfile(Path::new("services/api"), API);
+    assert_eq!(api.targets.iter().collect::<Vec<_>>(), ["build", "deps", "test"]);
+    assert_eq!(
+        api.local.iter().collect::<Vec<_>>(),
+        [Path::new("libs/auth"), Path::new("proto")]
+    );
+    assert_eq!(reference(Path::new("services/api"), "build"), "./services/api+build");
+    assert_eq!(reference(Path::new(""), "lint"), "+lint");
+}
+
+#[test]
+fn affected_earthfiles_include_those_referring_to_them() {
+    let earthfiles = [
+        parse_earthfile(Path::new(""), "VERSION 0.8\nall:\n    BUILD ./services/api+build\n"),
+        parse_earthfile(Path::new("services/api"), API),
+        parse_earthfile(Path::new("libs/auth"), "src:\n    SAVE ARTIFACT auth\n"),
+        parse_earthfile(Path::new("proto"), "gen:\n    RUN buf generate\n"),
+    ];
+    assert_eq!(
+        affected_earthfiles(&earthfiles, &[PathBuf::from("libs/auth/token.go")])
+            .into_iter()
+            .collect::<Vec<_>>(),
+        [
+            PathBuf::from(""),
+            PathBuf::from("libs/auth"),
+            PathBuf::from("services/api")
+        ]
+    );
+    assert_eq!(
+        affected_earthfiles(&earthfiles, &[PathBuf::from("docs/index.md")])
+            .into_iter()
+            .collect::<Vec<_>>(),
+        [PathBuf::from("")]
+    );
+}
+
+#[test]
+fn detection_needs_an_earthfile_at_the_root() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("api")).unwrap();
+    std::fs::write(dir.path().join("api/Earthfile"), API).unwrap();
+    assert!(!EarthlyBackend.detect(dir.path()));
+    std::fs::write(dir.path().join("Earthfile"), API).unwrap();
+    assert!(EarthlyBackend.detect(dir.path()));
+}
@@ -0,0 +1,174 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use super::{Backend, Target, TargetMeta};
+use crate::exec::Invocation;
+use crate::{git, paths};
+
+/// A directory with a gleam.toml.
+#[derive(Debug, Clone, PartialEq)]
+struct Project {
+    /// Directory relative to the repo root.
+    dir: PathBuf,
+    /// Directories of the projects in the repo it depends on through a `path` dependency.
+    local: BTreeSet<PathBuf>,
+}
+
+pub struct GleamBackend;
+
+impl GleamBackend {
+    fn projects(repo_root: &Path) -> Result<Vec<Project>> {
+        Ok(git::find_files(repo_root, "gleam.toml")?
+            .into_iter()
+            .map(|file| {
+                let text = std::fs::read_to_string(repo_root.join(&file)).unwrap_or_default();
+                parse_project(file.parent().unwrap_or(Path::new("")), &text)
+            })
+            .collect())
+    }
+
+    /// `gleam <command>` in each target's project.
+    fn each(targets: &[Target], command: &str) -> Vec<Invocation> {
+        targets
+            .iter()
+            .map(|t| Invocation::new("gleam", &t.dir).arg(command))
+            .collect()
+    }
+}
+
+impl Backend for GleamBackend {
+    fn name(&self) -> &str {
+        "gleam"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join("gleam.toml").is_file()
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let projects = match Self::projects(repo_root) {
+            Ok(projects) => projects,
+            Err(e) => {
+                eprintln!("kit: failed to find Gleam projects ({e:#})");
+                return vec![];
+            }
+        };
+        affected_projects(&projects, changed_files)
+            .into_iter()
+            .map(|dir| self.resolve_target(repo_root, repo_root.join(dir)))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_string_lossy();
+        let rel = rel.replace('\\', "/");
+        let meta = TargetMeta {
+            kind: Some("project".to_string()),
+            language: Some("gleam".to_string()),
+            is_test: Some(dir.join("test").is_dir()),
+            size: None,
+        };
+        Target {
+            label: if rel.is_empty() { ".".to_string() } else { rel },
+            dir,
+            meta,
+            files: Vec::new(),
+        }
+    }
+
+    fn build(&self, _repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Ok(Self::each(targets, "build"))
+    }
+
+    fn test(&self, _repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Ok(Self::each(targets, "test"))
+    }
+
+    fn lint(&self, _repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        // `gleam check` type-checks without code generation; Gleam has no separate linter.
+        Ok(Self::each(targets, "check"))
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let files = gleam_files(repo_root, changed_files);
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![Invocation::new("gleam", repo_root).arg("format").args(files)])
+    }
+
+    fn fmt_check(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let files = gleam_files(repo_root, changed_files);
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![
+            Invocation::new("gleam", repo_root)
+                .args(["format", "--check"])
+                .args(files),
+        ])
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new("gleam", repo_root).arg("--version")]
+    }
+}
+
+/// A project from its gleam.toml, with the local projects in its `dependencies` and
+/// `dev-dependencies` (`shared = { path = "../shared" }`).
+fn parse_project(dir: &Path, gleam_toml: &str) -> Project {
+    let manifest: toml::Table = toml::from_str(gleam_toml).unwrap_or_default();
+    let local = ["dependencies", "dev-dependencies"]
+        .iter()
+        .filter_map(|key| manifest.get(*key).and_then(|d| d.as_table()))
+        .flat_map(|deps| deps.values())
+        .filter_map(|dep| dep.get("path").and_then(|p| p.as_str()))
+        .filter_map(|path| paths::normalize(&dir.join(path)))
+        .collect();
+    Project {
+        dir: dir.to_path_buf(),
+        local,
+    }
+}
+
+/// Projects containing a changed file, outside their `build/` output, plus the projects that depend
+/// on them through a path dependency, directly or not.
+fn affected_projects(projects: &[Project], changed_files: &[PathBuf]) -> BTreeSet<PathBuf> {
+    let mut affected: BTreeSet<PathBuf> = changed_files
+        .iter()
+        .filter_map(|f| {
+            projects
+                .iter()
+                .filter(|p| f.starts_with(&p.dir))
+                .max_by_key(|p| p.dir.components().count())
+                .filter(|p| !f.starts_with(p.dir.join("build")))
+        })
+        .map(|p| p.dir.clone())
+        .collect();
+    loop {
+        let dependents: Vec<PathBuf> = projects
+            .iter()
+            .filter(|p| !affected.contains(&p.dir) && p.local.iter().any(|d| affected.contains(d)))
+            .map(|p| p.dir.clone())
+            .collect();
+        if dependents.is_empty() {
+            return affected;
+        }
+        affected.extend(dependents);
+    }
+}
+
+/// The changed `.gleam` files that still exist.
+fn gleam_files(repo_root: &Path, changed_files: &[PathBuf]) -> Vec<String> {
+    changed_files
+        .iter()
+        .filter(|f| f.extension().is_some_and(|e| e == "gleam") && repo_root.join(f).is_file())
+        .map(|f| f.to_string_lossy().replace('\\', "/"))
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "gleam_test.rs"]
+mod tests;
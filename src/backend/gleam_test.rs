@@ -0,0 +1,51 @@
+use super::*;
+
+#[test]
+fn path_dependencies_are_read_from_gleam_toml() {
+    let project = parse_project(
+        Path::new("apps/web"),
+        r#"name = "web"
+version = "1.0.0"
+
+[dependencies]
+gleam_stdlib = ">= 0.34.0 and < 2.0.0"
+shared = { path = "../../libs/shared" }
+
+[dev-dependencies]
+gleeunit = ">= 1.0.0 and < 2.0.0"
+fixtures = { path = "../fixtures" }
+"#,
+    );
+    assert_eq!(
+        project.local.into_iter().collect::<Vec<_>>(),
+        [PathBuf::from("apps/fixtures"), PathBuf::from("libs/shared")]
+    );
+}
+
+#[test]
+fn dependents_of_changed_projects_are_affected() {
+    let projects = [
+        parse_project(Path::new("libs/shared"), "name = \"shared\"\n"),
+        parse_project(
+            Path::new("apps/web"),
+            "name = \"web\"\n[dependencies]\nshared = { path = \"../../libs/shared\" }\n",
+        ),
+        parse_project(Path::new("apps/cli"), "name = \"cli\"\n"),
+    ];
+    let affected = affected_projects(&projects, &[PathBuf::from("libs/shared/src/shared.gleam")]);
+    assert_eq!(
+        affected.into_iter().collect::<Vec<_>>(),
+        [PathBuf::from("apps/web"), PathBuf::from("libs/shared")]
+    );
+    assert!(affected_projects(&projects, &[PathBuf::from("apps/cli/build/dev/erlang/cli.beam")]).is_empty());
+}
+
+#[test]
+fn detection_needs_gleam_toml_at_the_root() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("vendor")).unwrap();
+    std::fs::write(dir.path().join("vendor/gleam.toml"), "name = \"lib\"\n").unwrap();
+    assert!(!GleamBackend.detect(dir.path()));
+    std::fs::write(dir.path().join("gleam.toml"), "name = \"app\"\n").unwrap();
+    assert!(GleamBackend.detect(dir.path()));
+}
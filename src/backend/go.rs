@@ -1,29 +1,160 @@
-use std::collections::BTreeSet;
-use std::ffi::OsStr;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::SystemTime;
 
 use anyhow::{Context, Result};
+use ignore::WalkBuilder;
+use serde::Deserialize;
 
-use super::{Backend, Target};
+use super::{Backend, GraphNode, Outdated, Target, TargetMeta};
+use crate::coverage::{self, Coverage};
+use crate::error::KitError;
+use crate::exec::Invocation;
+use crate::failures::Failure;
+use crate::git;
+use crate::report::{Skip, TestCache};
+use crate::security::{self, Severity, Vulnerability};
+
+/// `go list` template for [`go_orphans`]: one tab-separated line per package.
+const LIST_FORMAT: &str = "{{.ImportPath}}\t{{.Name}}\t{{len .GoFiles}}\t{{join .Imports \" \"}}\t{{join .TestImports \" \"}}\t{{join .XTestImports \" \"}}";
+
+/// Suffix of the coverage profiles `go test` writes into the coverage scratch directory, one per module.
+const COVERPROFILE: &str = ".coverprofile";
+
+/// Shell script for `kit fmt --check`: runs `gofmt -l` on its arguments and fails if it listed any.
+const GOFMT_CHECK: &str = r#"out=$(gofmt -l "$@") || exit 1; [ -z "$out" ] || { echo "$out"; exit 1; }"#;
 
 pub struct GoBackend;
 
 impl GoBackend {
-    fn run<I, S>(cmd: &str, args: I, dir: &Path) -> Result<()>
-    where
-        I: IntoIterator<Item = S>,
-        S: AsRef<OsStr>,
-    {
-        let status = Command::new(cmd)
-            .args(args)
-            .current_dir(dir)
-            .status()
-            .with_context(|| format!("failed to run {cmd}"))?;
-        if !status.success() {
-            anyhow::bail!("{cmd} exited with {status}");
+    /// Count the Go files under a package directory and note whether any are tests.
+    fn package_meta(dir: &Path) -> TargetMeta {
+        let mut files = 0;
+        let mut tests = false;
+        for entry in WalkBuilder::new(dir).build().flatten() {
+            let name = entry.file_name().to_string_lossy();
+            if name.ends_with(".go") {
+                files += 1;
+                tests |= name.ends_with("_test.go");
+            }
+        }
+        TargetMeta {
+            kind: Some("package".to_string()),
+            language: Some("go".to_string()),
+            is_test: Some(tests),
+            size: Some(files),
         }
-        Ok(())
+    }
+
+    /// Module paths whose versions differ in a changed go.mod or go.sum since `base`, or None if
+    /// the file changed in some other way (e.g. the `go` directive or a `replace`).
+    fn changed_dependencies(repo_root: &Path, base: &str, file: &Path) -> Option<BTreeSet<String>> {
+        let merge_base = git::merge_base(repo_root, base).ok()?;
+        let old = git::show(repo_root, &merge_base, file).ok()??;
+        let new = std::fs::read_to_string(repo_root.join(file)).ok()?;
+        let is_sum = file.file_name().is_some_and(|n| n == "go.sum");
+        changed_modules(&old, &new, is_sum)
+    }
+
+    /// Packages under `module_dir` that import one of `changed`, a module that depends on it, or
+    /// another such package. None if the module graph is unavailable.
+    fn packages_using(module_dir: &Path, changed: &BTreeSet<String>) -> Option<BTreeSet<PathBuf>> {
+        let go_mod = std::fs::read_to_string(module_dir.join("go.mod")).ok()?;
+        let module = go_mod.lines().find_map(|l| l.trim().strip_prefix("module "))?.trim();
+        let output = Command::new("go")
+            .args(["mod", "graph"])
+            .current_dir(module_dir)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let mut modules = dependents(&String::from_utf8_lossy(&output.stdout), changed);
+        modules.remove(module);
+
+        // Import path of each package in the module -> the paths it imports.
+        let mut imports: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        let walk = WalkBuilder::new(module_dir)
+            .filter_entry(|e| e.depth() == 0 || !e.path().join("go.mod").exists())
+            .build();
+        for entry in walk.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "go") {
+                continue;
+            }
+            let rel = path
+                .parent()?
+                .strip_prefix(module_dir)
+                .ok()?
+                .to_string_lossy()
+                .replace('\\', "/");
+            let package = if rel.is_empty() {
+                module.to_string()
+            } else {
+                format!("{module}/{rel}")
+            };
+            let source = std::fs::read_to_string(path).unwrap_or_default();
+            imports.entry(package).or_default().extend(go_imports(&source));
+        }
+
+        let within = |import: &str, m: &str| import == m || import.strip_prefix(m).is_some_and(|r| r.starts_with('/'));
+        let mut affected: BTreeSet<&str> = imports
+            .iter()
+            .filter(|(_, i)| i.iter().any(|i| modules.iter().any(|m| within(i, m))))
+            .map(|(p, _)| p.as_str())
+            .collect();
+        loop {
+            let next: Vec<&str> = imports
+                .iter()
+                .filter(|(p, i)| !affected.contains(p.as_str()) && i.iter().any(|i| affected.contains(i.as_str())))
+                .map(|(p, _)| p.as_str())
+                .collect();
+            if next.is_empty() {
+                break;
+            }
+            affected.extend(next);
+        }
+        Some(
+            affected
+                .into_iter()
+                .map(|p| match p.strip_prefix(module).and_then(|r| r.strip_prefix('/')) {
+                    Some(rel) => module_dir.join(rel),
+                    None => module_dir.to_path_buf(),
+                })
+                .collect(),
+        )
+    }
+
+    /// Module paths in the repo and their directories relative to the repo root.
+    /// The [`LIST_FORMAT`] listing of every package in every module of the repo.
+    fn list(repo_root: &Path) -> Result<String> {
+        let mut listing = String::new();
+        for (_, dir) in Self::modules(repo_root)? {
+            let output = Command::new("go")
+                .args(["list", "-e", "-f", LIST_FORMAT, "./..."])
+                .current_dir(repo_root.join(&dir))
+                .output()
+                .map_err(|e| KitError::spawn("go", e))?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                anyhow::bail!("go list failed in {}: {}", dir.display(), stderr.trim());
+            }
+            listing.push_str(&String::from_utf8(output.stdout).context("invalid utf-8 from go list")?);
+        }
+        Ok(listing)
+    }
+
+    pub fn modules(repo_root: &Path) -> Result<Vec<(String, PathBuf)>> {
+        let mut modules = Vec::new();
+        for go_mod in git::find_files(repo_root, "go.mod")? {
+            let text = std::fs::read_to_string(repo_root.join(&go_mod)).unwrap_or_default();
+            if let Some(module) = text.lines().find_map(|l| l.trim().strip_prefix("module ")) {
+                let dir = go_mod.parent().unwrap_or(Path::new("")).to_path_buf();
+                modules.push((module.trim().trim_matches('"').to_string(), dir));
+            }
+        }
+        Ok(modules)
     }
 }
 
@@ -36,26 +167,45 @@ impl Backend for GoBackend {
         dir.join("go.mod").exists() || dir.join("go.work").exists()
     }
 
-    fn affected_targets(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Vec<Target> {
+    fn affected_targets(&self, repo_root: &Path, base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
         let mut packages: BTreeSet<PathBuf> = BTreeSet::new();
+        // Module directory -> dependencies changed in its go.mod/go.sum, None if the whole module is affected.
+        let mut dep_changes: BTreeMap<PathBuf, Option<BTreeSet<String>>> = BTreeMap::new();
 
         for file in changed_files {
-            let is_dep_file = file
-                .file_name()
-                .is_some_and(|name| name == "go.mod" || name == "go.sum" || name == "go.work" || name == "go.work.sum");
+            let name = file.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            let is_dep_file = matches!(name, "go.mod" | "go.sum" | "go.work" | "go.work.sum");
 
             if is_dep_file {
                 let dir = file
                     .parent()
                     .map(|p| repo_root.join(p))
                     .unwrap_or_else(|| repo_root.to_path_buf());
-                packages.insert(dir);
-            } else if file.extension().is_some_and(|ext| ext == "go") {
-                if let Some(parent) = file.parent() {
-                    let dir = repo_root.join(parent);
-                    if dir.exists() {
-                        packages.insert(dir);
-                    }
+                let deps = match name {
+                    "go.mod" | "go.sum" => Self::changed_dependencies(repo_root, base, file),
+                    _ => None,
+                };
+                let entry = dep_changes.entry(dir).or_insert_with(|| Some(BTreeSet::new()));
+                match (entry.as_mut(), deps) {
+                    (Some(all), Some(deps)) => all.extend(deps),
+                    _ => *entry = None,
+                }
+            } else if file.extension().is_some_and(|ext| ext == "go")
+                && let Some(parent) = file.parent()
+            {
+                let dir = repo_root.join(parent);
+                if dir.exists() {
+                    packages.insert(dir);
+                }
+            }
+        }
+
+        // A dependency bump only affects the packages that (transitively) import the dependency.
+        for (dir, deps) in dep_changes {
+            match deps.and_then(|deps| Self::packages_using(&dir, &deps)) {
+                Some(users) => packages.extend(users),
+                None => {
+                    packages.insert(dir);
                 }
             }
         }
@@ -74,55 +224,656 @@ impl Backend for GoBackend {
         } else {
             format!("./{rel}/...")
         };
-        Target { label, dir }
+        let meta = Self::package_meta(&dir);
+        Target {
+            label,
+            dir,
+            meta,
+            files: Vec::new(),
+        }
     }
 
-    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<()> {
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
         if targets.is_empty() {
-            return Ok(());
+            return Ok(vec![]);
         }
-        let labels: Vec<&str> = targets.iter().map(|t| t.label.as_str()).collect();
-        let mut args = vec!["build"];
-        args.extend(&labels);
-        Self::run("go", &args, repo_root)
+        let labels = targets.iter().map(|t| t.label.as_str());
+        Ok(vec![Invocation::new("go", repo_root).arg("build").args(labels)])
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let (narrowed, whole): (Vec<&Target>, Vec<&Target>) = targets.iter().partition(|t| !t.files.is_empty());
+        let mut plan = Vec::new();
+        if !whole.is_empty() {
+            let labels = whole.iter().map(|t| t.label.as_str());
+            plan.push(Invocation::new("go", repo_root).arg("test").args(labels));
+        }
+        // Test files run only their own tests, in their own package. Naming a non-test file, or a
+        // test file without tests, runs the whole package.
+        for target in narrowed {
+            let package = target.label.trim_end_matches("/...");
+            let mut invocation = Invocation::new("go", repo_root).args(["test", package]);
+            if target.files.iter().all(|f| f.to_string_lossy().ends_with("_test.go")) {
+                let mut names = BTreeSet::new();
+                for file in &target.files {
+                    let path = repo_root.join(file);
+                    let source =
+                        std::fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+                    names.extend(test_functions(&source));
+                }
+                if !names.is_empty() {
+                    let names: Vec<String> = names.into_iter().collect();
+                    invocation = invocation.args(["-run".to_string(), format!("^({})$", names.join("|"))]);
+                }
+            }
+            plan.push(invocation);
+        }
+        Ok(plan)
     }
 
-    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<()> {
+    fn check(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
         if targets.is_empty() {
-            return Ok(());
+            return Ok(vec![]);
         }
+        // `go build` discards what it builds when the output is the null device; `go vet` then
+        // type-checks the test files too.
         let labels: Vec<&str> = targets.iter().map(|t| t.label.as_str()).collect();
-        let mut args = vec!["test"];
-        args.extend(&labels);
-        Self::run("go", &args, repo_root)
+        Ok(vec![
+            Invocation::new("go", repo_root)
+                .args(["build", "-o", "/dev/null"])
+                .args(labels.iter().copied()),
+            Invocation::new("go", repo_root).arg("vet").args(labels),
+        ])
     }
 
-    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<()> {
+    fn task(&self, repo_root: &Path, task: &str, targets: &[Target]) -> Result<Vec<Invocation>> {
         if targets.is_empty() {
-            return Ok(());
+            return Ok(vec![]);
         }
-        let dirs: Vec<&str> = targets.iter().map(|t| t.label.as_str()).collect();
-        let mut args = vec!["run"];
-        args.extend(&dirs);
-        Self::run("golangci-lint", &args, repo_root).context("failed to run golangci-lint — is it installed?")
+        // The task's main package runs once, with the packages it is for as arguments.
+        let mut packages: BTreeMap<PathBuf, Vec<&str>> = BTreeMap::new();
+        for target in targets {
+            if let Some(package) = task_package(repo_root, &target.dir, task) {
+                packages.entry(package).or_default().push(&target.label);
+            }
+        }
+        if packages.is_empty() {
+            return Err(Skip::NotConfigured {
+                detail: format!("no cmd/{task} package above the affected packages"),
+            }
+            .into());
+        }
+        Ok(packages
+            .into_iter()
+            .map(|(package, labels)| {
+                let package = format!("./{}", package.to_string_lossy().replace('\\', "/"));
+                Invocation::new("go", repo_root).args(["run", &package]).args(labels)
+            })
+            .collect())
     }
 
-    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<()> {
-        let go_files: Vec<PathBuf> = changed_files
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let labels = targets.iter().map(|t| t.label.as_str());
+        Ok(vec![
+            Invocation::new("golangci-lint", repo_root).arg("run").args(labels),
+        ])
+    }
+
+    fn fix(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let labels = targets.iter().map(|t| t.label.as_str());
+        Ok(vec![
+            Invocation::new("golangci-lint", repo_root)
+                .args(["run", "--fix"])
+                .args(labels),
+        ])
+    }
+
+    fn docs(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        // `go doc` takes one package at a time, without the `/...` pattern.
+        Ok(targets
+            .iter()
+            .map(|t| {
+                let package = t.label.trim_end_matches("/...");
+                Invocation::new("go", repo_root).args(["doc", "-all", package])
+            })
+            .collect())
+    }
+
+    fn generate(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let labels = targets.iter().map(|t| t.label.as_str());
+        Ok(vec![Invocation::new("go", repo_root).arg("generate").args(labels)])
+    }
+
+    fn tidy(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let modules: Vec<PathBuf> = Self::modules(repo_root)?.into_iter().map(|(_, dir)| dir).collect();
+        let affected: BTreeSet<PathBuf> = targets
             .iter()
-            .filter(|f| f.extension().is_some_and(|ext| ext == "go"))
-            .map(|f| repo_root.join(f))
-            .filter(|f| f.exists())
+            .map(|t| module_of(&modules, t.dir.strip_prefix(repo_root).unwrap_or(&t.dir)))
             .collect();
+        Ok(affected
+            .iter()
+            .map(|dir| Invocation::new("go", &repo_root.join(dir)).args(["mod", "tidy"]))
+            .collect())
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let go_files = go_files(repo_root, changed_files);
+        if go_files.is_empty() {
+            return Ok(vec![]);
+        }
+
+        Ok(vec![Invocation::new("gofmt", repo_root).arg("-w").args(go_files)])
+    }
 
+    fn fmt_check(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let go_files = go_files(repo_root, changed_files);
         if go_files.is_empty() {
-            return Ok(());
+            return Ok(vec![]);
         }
+        // `gofmt -l` lists the files it would change but exits 0 either way.
+        Ok(vec![
+            Invocation::new("sh", repo_root)
+                .args(["-c", GOFMT_CHECK, "gofmt"])
+                .args(go_files),
+        ])
+    }
+
+    fn coverage(&self, repo_root: &Path, targets: &[Target], out_dir: &Path) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        // `go test` only covers packages of the module it runs in, so each module gets its own run
+        // and profile; read_coverage merges them.
+        let modules: Vec<PathBuf> = Self::modules(repo_root)?.into_iter().map(|(_, dir)| dir).collect();
+        let mut by_module: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+        for target in targets {
+            let rel = target.dir.strip_prefix(repo_root).unwrap_or(&target.dir);
+            let module = module_of(&modules, rel);
+            let package = rel
+                .strip_prefix(&module)
+                .unwrap_or(rel)
+                .to_string_lossy()
+                .replace('\\', "/");
+            let label = if package.is_empty() {
+                "./...".to_string()
+            } else {
+                format!("./{package}/...")
+            };
+            by_module.entry(module).or_default().push(label);
+        }
+        Ok(by_module
+            .into_iter()
+            .enumerate()
+            .map(|(i, (module, labels))| {
+                let profile = out_dir.join(format!("go-{i}{COVERPROFILE}"));
+                Invocation::new("go", &repo_root.join(module))
+                    .arg("test")
+                    .arg(format!("-coverprofile={}", profile.display()))
+                    .args(labels)
+            })
+            .collect())
+    }
+
+    fn read_coverage(&self, repo_root: &Path, out_dir: &Path, since: SystemTime) -> Result<Coverage> {
+        let modules = Self::modules(repo_root)?;
+        let mut cov = Coverage::default();
+        let entries = std::fs::read_dir(out_dir).with_context(|| format!("failed to read {}", out_dir.display()))?;
+        for profile in entries.filter_map(|e| Some(e.ok()?.path())) {
+            let name = profile.file_name().unwrap_or_default().to_string_lossy();
+            if !(name.starts_with("go-") && name.ends_with(COVERPROFILE) && super::written_since(&profile, since)) {
+                continue;
+            }
+            let text =
+                std::fs::read_to_string(&profile).with_context(|| format!("failed to read {}", profile.display()))?;
+            cov.merge(Coverage::parse_go(&text, &modules));
+        }
+        Ok(cov)
+    }
 
-        let mut args: Vec<&OsStr> = vec![OsStr::new("-w")];
-        args.extend(go_files.iter().map(|f| f.as_os_str()));
-        Self::run("gofmt", args, repo_root)
+    fn shuffle_args(&self, seed: u64) -> Option<Vec<String>> {
+        Some(vec![format!("-shuffle={seed}")])
     }
+
+    fn no_cache_args(&self) -> Option<Vec<String>> {
+        Some(vec!["-count=1".to_string()])
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new("go", repo_root).arg("version")]
+    }
+
+    fn extra_tools(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new("golangci-lint", repo_root).arg("--version")]
+    }
+
+    fn smoke(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![
+            Invocation::new("go", repo_root).args(["env", "GOFLAGS", "GOWORK"]),
+            Invocation::new("go", repo_root).args(["list", "-m"]),
+        ]
+    }
+
+    fn misconfigurations(&self, repo_root: &Path) -> Vec<String> {
+        let goflags = std::env::var("GOFLAGS").unwrap_or_default();
+        goflags_conflicts(
+            &goflags,
+            repo_root.join("vendor/modules.txt").is_file(),
+            repo_root.join("go.work").is_file(),
+        )
+    }
+
+    fn test_cache(&self, output: &str) -> Option<TestCache> {
+        test_cache(output)
+    }
+
+    fn parse_failures(&self, repo_root: &Path, output: &str) -> Vec<Failure> {
+        test_failures(output, &Self::modules(repo_root).unwrap_or_default())
+    }
+
+    fn graph(&self, repo_root: &Path) -> Result<Option<Vec<GraphNode>>> {
+        let modules = Self::modules(repo_root)?;
+        Ok(Some(go_graph(&Self::list(repo_root)?, &modules)))
+    }
+
+    fn orphans(&self, repo_root: &Path) -> Result<Option<Vec<String>>> {
+        Ok(Some(go_orphans(&Self::list(repo_root)?)))
+    }
+
+    fn outdated(&self, repo_root: &Path) -> Result<Option<Vec<Outdated>>> {
+        let mut outdated = Vec::new();
+        for (_, dir) in Self::modules(repo_root)? {
+            let output = Command::new("go")
+                .args(["list", "-u", "-m", "-json", "all"])
+                .current_dir(repo_root.join(&dir))
+                .output()
+                .map_err(|e| KitError::spawn("go", e))?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                anyhow::bail!("go list -u failed in {}: {}", dir.display(), stderr.trim());
+            }
+            outdated.extend(go_outdated(&String::from_utf8_lossy(&output.stdout), &dir)?);
+        }
+        Ok(Some(outdated))
+    }
+
+    fn audit(&self, repo_root: &Path, targets: &[Target]) -> Result<Option<Vec<Vulnerability>>> {
+        let mut args = vec!["-json".to_string()];
+        args.extend(targets.iter().map(|t| t.label.clone()));
+        let output = security::scanner_output("govulncheck", &args, repo_root)?;
+        Ok(Some(govulncheck_findings(&output)?))
+    }
+}
+
+/// A message in `govulncheck -json` output: an advisory, a finding, or progress.
+#[derive(Deserialize)]
+struct VulncheckMessage {
+    osv: Option<VulncheckAdvisory>,
+    finding: Option<VulncheckFinding>,
+}
+
+#[derive(Deserialize)]
+struct VulncheckAdvisory {
+    id: String,
+    #[serde(default)]
+    summary: String,
+}
+
+#[derive(Deserialize)]
+struct VulncheckFinding {
+    osv: String,
+    fixed_version: Option<String>,
+    #[serde(default)]
+    trace: Vec<VulncheckFrame>,
+}
+
+#[derive(Deserialize)]
+struct VulncheckFrame {
+    module: String,
+    #[serde(default)]
+    version: String,
+    function: Option<String>,
+}
+
+/// The vulnerabilities `govulncheck -json` found the code calls into, once per advisory and
+/// module. Findings for vulnerable modules or packages the code imports without reaching the
+/// vulnerable function are left out, as govulncheck's own summary does. The Go vulnerability
+/// database doesn't rate severity.
+fn govulncheck_findings(output: &str) -> Result<Vec<Vulnerability>> {
+    let mut summaries = BTreeMap::new();
+    let mut findings = Vec::new();
+    for message in serde_json::Deserializer::from_str(output).into_iter::<VulncheckMessage>() {
+        let message = message.context("invalid JSON from govulncheck")?;
+        if let Some(advisory) = message.osv {
+            summaries.insert(advisory.id, advisory.summary);
+        }
+        if let Some(finding) = message.finding
+            && let Some(frame) = finding.trace.first()
+            && frame.function.is_some()
+        {
+            findings.push((
+                finding.osv.clone(),
+                frame.module.clone(),
+                frame.version.clone(),
+                finding.fixed_version,
+            ));
+        }
+    }
+    let mut vulnerabilities: Vec<Vulnerability> = Vec::new();
+    for (id, module, version, fixed) in findings {
+        if vulnerabilities.iter().any(|v| v.id == id && v.package == module) {
+            continue;
+        }
+        vulnerabilities.push(Vulnerability {
+            summary: summaries.get(&id).cloned().unwrap_or_default(),
+            id,
+            package: module,
+            version,
+            severity: Severity::Unknown,
+            fixed,
+            dir: PathBuf::new(),
+        });
+    }
+    Ok(vulnerabilities)
+}
+
+/// A module in `go list -m -json` output.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListedModule {
+    path: String,
+    #[serde(default)]
+    version: String,
+    #[serde(default)]
+    main: bool,
+    #[serde(default)]
+    indirect: bool,
+    update: Option<ModuleUpdate>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ModuleUpdate {
+    version: String,
+}
+
+/// The modules the module in `dir` requires directly that have an update, from the
+/// concatenated JSON objects `go list -u -m -json all` prints.
+fn go_outdated(listing: &str, dir: &Path) -> Result<Vec<Outdated>> {
+    let mut outdated = Vec::new();
+    for module in serde_json::Deserializer::from_str(listing).into_iter::<ListedModule>() {
+        let module = module.context("invalid JSON from go list")?;
+        if module.main || module.indirect {
+            continue;
+        }
+        if let Some(update) = module.update {
+            outdated.push(Outdated {
+                name: module.path,
+                current: module.version,
+                latest: update.version,
+                dir: dir.to_path_buf(),
+            });
+        }
+    }
+    Ok(outdated)
+}
+
+/// The repo-relative directory of the main package for `task`: `cmd/<task>` in the target's
+/// directory or the nearest directory above it, up to the repo root.
+fn task_package(repo_root: &Path, dir: &Path, task: &str) -> Option<PathBuf> {
+    let dir = dir.strip_prefix(repo_root).unwrap_or(dir);
+    dir.ancestors()
+        .map(|d| d.join("cmd").join(task))
+        .find(|package| repo_root.join(package).is_dir())
+}
+
+/// The packages in a [`LIST_FORMAT`] listing, labelled `./dir` by their directory, and the packages
+/// of the repo they import outside their tests.
+fn go_graph(listing: &str, modules: &[(String, PathBuf)]) -> Vec<GraphNode> {
+    let packages: Vec<Vec<&str>> = listing.lines().map(|line| line.split('\t').collect()).collect();
+    let dirs: BTreeMap<&str, PathBuf> = packages
+        .iter()
+        .filter_map(|f| {
+            // A module's root package has the module path itself.
+            let root = modules
+                .iter()
+                .find(|(module, _)| module == f[0])
+                .map(|(_, dir)| dir.clone());
+            Some((f[0], root.or_else(|| coverage::go_import_to_path(f[0], modules))?))
+        })
+        .collect();
+    let label = |dir: &Path| match dir.to_string_lossy().replace('\\', "/") {
+        dir if dir.is_empty() => ".".to_string(),
+        dir => format!("./{dir}"),
+    };
+    packages
+        .iter()
+        .filter_map(|fields| {
+            let dir = dirs.get(fields[0])?;
+            let deps = fields
+                .get(3)
+                .into_iter()
+                .flat_map(|imports| imports.split_whitespace())
+                .filter(|i| *i != fields[0])
+                .filter_map(|i| dirs.get(i).map(|d| label(d)))
+                .collect();
+            Some(GraphNode {
+                label: label(dir),
+                dir: dir.clone(),
+                deps,
+            })
+        })
+        .collect()
+}
+
+/// Import paths of the packages in a [`LIST_FORMAT`] listing that no other package imports, not
+/// even from its tests. Commands and test-only packages are entry points; a package's own tests
+/// don't keep it alive.
+fn go_orphans(listing: &str) -> Vec<String> {
+    let packages: Vec<Vec<&str>> = listing.lines().map(|line| line.split('\t').collect()).collect();
+    let mut imported: BTreeSet<&str> = BTreeSet::new();
+    for fields in &packages {
+        let own = fields[0];
+        let imports = fields.iter().skip(3).flat_map(|f| f.split_whitespace());
+        imported.extend(imports.filter(|i| *i != own));
+    }
+    packages
+        .iter()
+        .filter(|f| f.len() == 6 && f[1] != "main" && f[2] != "0" && !imported.contains(f[0]))
+        .map(|f| f[0].to_string())
+        .collect()
+}
+
+/// The deepest of `modules` holding `dir`, both relative to the repo root, or the root when none
+/// does.
+fn module_of(modules: &[PathBuf], dir: &Path) -> PathBuf {
+    modules
+        .iter()
+        .filter(|m| dir.starts_with(m))
+        .max_by_key(|m| m.components().count())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Module paths on lines that differ between two versions of a go.mod (or go.sum, if `is_sum`).
+/// None if a go.mod line other than a requirement changed.
+fn changed_modules(old: &str, new: &str, is_sum: bool) -> Option<BTreeSet<String>> {
+    let old_lines: BTreeSet<&str> = old.lines().map(str::trim).collect();
+    let new_lines: BTreeSet<&str> = new.lines().map(str::trim).collect();
+    let mut modules = BTreeSet::new();
+    for line in old_lines.symmetric_difference(&new_lines) {
+        let line = line.split("//").next().unwrap_or("").trim();
+        let line = line.strip_prefix("require ").unwrap_or(line).trim();
+        if line.is_empty() || line == "require (" || line == ")" {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let (module, version) = (fields.next()?, fields.next()?);
+        if !is_sum && (!version.starts_with('v') || fields.next().is_some()) {
+            return None;
+        }
+        modules.insert(module.to_string());
+    }
+    Some(modules)
+}
+
+/// `changed` plus every module that depends on one of them, according to `go mod graph` output.
+fn dependents<'a>(graph: &'a str, changed: &BTreeSet<String>) -> BTreeSet<String> {
+    let mut parents: BTreeMap<&'a str, Vec<&'a str>> = BTreeMap::new();
+    for line in graph.lines() {
+        if let Some((from, to)) = line.split_once(' ') {
+            let path = |s: &'a str| s.split('@').next().unwrap_or(s);
+            parents.entry(path(to)).or_default().push(path(from));
+        }
+    }
+    let mut seen: BTreeSet<String> = changed.clone();
+    let mut queue: Vec<String> = changed.iter().cloned().collect();
+    while let Some(module) = queue.pop() {
+        for parent in parents.get(module.as_str()).into_iter().flatten() {
+            if seen.insert(parent.to_string()) {
+                queue.push(parent.to_string());
+            }
+        }
+    }
+    seen
+}
+
+/// Failing tests in `go test` output. Test files are reported by base name, so they are resolved
+/// through the package's import path on the `FAIL\t<package>` line that ends its output. Parents of
+/// failing subtests are left out unless they failed on their own account.
+fn test_failures(output: &str, modules: &[(String, PathBuf)]) -> Vec<Failure> {
+    let mut failures = Vec::new();
+    let mut pending: Vec<Failure> = Vec::new();
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if let Some(rest) = trimmed.strip_prefix("--- FAIL: ") {
+            pending.push(Failure {
+                test: rest.split_whitespace().next().unwrap_or(rest).to_string(),
+                file: None,
+                line: None,
+                message: String::new(),
+            });
+        } else if let Some(package) = line.strip_prefix("FAIL\t") {
+            let package = package.split_whitespace().next().unwrap_or(package);
+            for mut f in pending.drain(..) {
+                if let Some(file) = &f.file {
+                    let name = format!("{package}/{}", file.display());
+                    f.file = coverage::go_import_to_path(&name, modules).or(f.file);
+                }
+                failures.push(f);
+            }
+        } else if let Some(f) = pending.last_mut().filter(|f| f.file.is_none())
+            && let Some((location, message)) = trimmed.split_once(": ")
+            && let Some((file, n)) = location.rsplit_once(':')
+            && file.ends_with(".go")
+            && let Ok(n) = n.parse()
+        {
+            f.file = Some(PathBuf::from(file));
+            f.line = Some(n);
+            f.message = message.to_string();
+        }
+    }
+    failures.append(&mut pending);
+    let names: Vec<String> = failures.iter().map(|f| f.test.clone()).collect();
+    failures.retain(|f| f.file.is_some() || !names.iter().any(|n| n.starts_with(&format!("{}/", f.test))));
+    failures
+}
+
+/// Packages whose `go test` result was cached (`ok  <package>  (cached)`), out of all the packages
+/// that reported a result.
+fn test_cache(output: &str) -> Option<TestCache> {
+    let mut cache = TestCache { cached: 0, total: 0 };
+    for line in output.lines() {
+        let mut fields = line.split('\t');
+        let (Some(status), Some(_), Some(result)) = (fields.next(), fields.next(), fields.next()) else {
+            continue;
+        };
+        if status.trim_end() == "ok" || status == "FAIL" {
+            cache.total += 1;
+            cache.cached += usize::from(result.trim() == "(cached)");
+        }
+    }
+    (cache.total > 0).then_some(cache)
+}
+
+/// Names of the tests, examples, and fuzz tests declared in a Go test file.
+fn test_functions(source: &str) -> Vec<String> {
+    source
+        .lines()
+        .filter_map(|line| {
+            let name = line.strip_prefix("func ")?.split('(').next()?.trim();
+            let is_test = ["Test", "Example", "Fuzz"].iter().any(|p| name.starts_with(p));
+            (is_test && !name.contains(char::is_whitespace)).then(|| name.to_string())
+        })
+        .collect()
+}
+
+/// Import paths in a Go source file.
+fn go_imports(source: &str) -> BTreeSet<String> {
+    let mut imports = BTreeSet::new();
+    let mut in_block = false;
+    for line in source.lines().map(str::trim) {
+        let spec = if in_block {
+            if line.starts_with(')') {
+                in_block = false;
+                continue;
+            }
+            line
+        } else if let Some(rest) = line.strip_prefix("import") {
+            let rest = rest.trim();
+            if rest.starts_with('(') {
+                in_block = true;
+                continue;
+            }
+            rest
+        } else {
+            continue;
+        };
+        if let Some(path) = spec.split('"').nth(1) {
+            imports.insert(path.to_string());
+        }
+    }
+    imports
+}
+
+/// Settings in GOFLAGS that break the go commands kit runs in this repo.
+fn goflags_conflicts(goflags: &str, vendored: bool, workspace: bool) -> Vec<String> {
+    let mut problems = Vec::new();
+    for flag in goflags.split_whitespace() {
+        let flag = flag.trim_start_matches('-');
+        match flag.split_once('=') {
+            Some(("mod", "vendor")) if !vendored => {
+                problems.push("GOFLAGS sets -mod=vendor but the repo has no vendor/modules.txt".to_string())
+            }
+            Some(("mod", "mod")) if workspace => {
+                problems.push("GOFLAGS sets -mod=mod, which go rejects in a go.work workspace".to_string())
+            }
+            Some(("modfile", file)) => {
+                problems.push(format!("GOFLAGS sets -modfile={file}, so go ignores the repo's go.mod"))
+            }
+            _ => {}
+        }
+    }
+    problems
+}
+
+/// The changed Go files that still exist, as absolute paths.
+fn go_files(repo_root: &Path, changed_files: &[PathBuf]) -> Vec<String> {
+    changed_files
+        .iter()
+        .filter(|f| f.extension().is_some_and(|ext| ext == "go"))
+        .map(|f| repo_root.join(f))
+        .filter(|f| f.exists())
+        .map(|f| f.to_string_lossy().into_owned())
+        .collect()
 }
 
 #[cfg(test)]
@@ -14,7 +14,7 @@ fn affected_targets_go_files() {
     std::fs::create_dir_all(root.join("pkg/bar")).unwrap();
 
     let changed = vec![PathBuf::from("pkg/foo/main.go"), PathBuf::from("pkg/bar/util.go")];
-    let targets = backend().affected_targets(root, &changed);
+    let targets = backend().affected_targets(root, "main", &changed);
     let labels: Vec<&str> = targets.iter().map(|t| t.label.as_str()).collect();
     assert_eq!(labels, vec!["./pkg/bar/...", "./pkg/foo/..."]);
 }
@@ -26,7 +26,7 @@ fn affected_targets_ignores_non_go_files() {
     std::fs::create_dir_all(root.join("pkg")).unwrap();
 
     let changed = vec![PathBuf::from("pkg/readme.md"), PathBuf::from("pkg/data.json")];
-    let targets = backend().affected_targets(root, &changed);
+    let targets = backend().affected_targets(root, "main", &changed);
     assert!(targets.is_empty());
 }
 
@@ -36,7 +36,7 @@ fn affected_targets_go_mod_and_go_sum() {
     let root = tmp.path();
 
     let changed = vec![PathBuf::from("go.mod"), PathBuf::from("go.sum")];
-    let targets = backend().affected_targets(root, &changed);
+    let targets = backend().affected_targets(root, "main", &changed);
     assert_eq!(targets.len(), 1);
     assert_eq!(targets[0].label, "./...");
 }
@@ -48,7 +48,314 @@ fn affected_targets_nested_go_mod() {
     std::fs::create_dir_all(root.join("sub")).unwrap();
 
     let changed = vec![PathBuf::from("sub/go.mod")];
-    let targets = backend().affected_targets(root, &changed);
+    let targets = backend().affected_targets(root, "main", &changed);
     assert_eq!(targets.len(), 1);
     assert_eq!(targets[0].label, "./sub/...");
 }
+
+#[test]
+fn test_plans_one_go_test_invocation() {
+    let root = Path::new("/repo");
+    let targets = vec![
+        backend().resolve_target(root, root.join("pkg/foo")),
+        backend().resolve_target(root, root.join("pkg/bar")),
+    ];
+    let plan = backend().test(root, &targets).unwrap();
+    assert_eq!(
+        plan,
+        vec![Invocation::new("go", root).args(["test", "./pkg/foo/...", "./pkg/bar/..."])]
+    );
+    assert!(backend().test(root, &[]).unwrap().is_empty());
+}
+
+#[test]
+fn module_of_picks_the_deepest_module_containing_the_dir() {
+    let modules = vec![PathBuf::new(), PathBuf::from("tools"), PathBuf::from("tools/gen")];
+    assert_eq!(
+        module_of(&modules, Path::new("tools/gen/cmd")),
+        PathBuf::from("tools/gen")
+    );
+    assert_eq!(module_of(&modules, Path::new("tools/lint")), PathBuf::from("tools"));
+    assert_eq!(module_of(&modules, Path::new("pkg/foo")), PathBuf::new());
+    assert_eq!(module_of(&[PathBuf::from("svc")], Path::new("other")), PathBuf::new());
+}
+
+#[test]
+fn fix_runs_golangci_lint_with_fixes_on_the_targets() {
+    let root = Path::new("/repo");
+    let targets = vec![backend().resolve_target(root, root.join("pkg/foo"))];
+    assert_eq!(
+        backend().fix(root, &targets).unwrap(),
+        vec![Invocation::new("golangci-lint", root).args(["run", "--fix", "./pkg/foo/..."])]
+    );
+}
+
+#[test]
+fn fmt_check_lists_unformatted_files_and_fails() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    std::fs::write(root.join("a.go"), "package a").unwrap();
+    let plan = backend()
+        .fmt_check(
+            root,
+            &[
+                PathBuf::from("a.go"),
+                PathBuf::from("gone.go"),
+                PathBuf::from("README.md"),
+            ],
+        )
+        .unwrap();
+    let file = root.join("a.go").to_string_lossy().into_owned();
+    assert_eq!(
+        plan,
+        vec![Invocation::new("sh", root).args(["-c", GOFMT_CHECK, "gofmt", &file])]
+    );
+    assert!(
+        backend()
+            .fmt_check(root, &[PathBuf::from("README.md")])
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn docs_print_each_package_with_go_doc() {
+    let root = Path::new("/repo");
+    let targets = vec![
+        backend().resolve_target(root, root.join("pkg/foo")),
+        backend().resolve_target(root, root.join("pkg/bar")),
+    ];
+    assert_eq!(
+        backend().docs(root, &targets).unwrap(),
+        vec![
+            Invocation::new("go", root).args(["doc", "-all", "./pkg/foo"]),
+            Invocation::new("go", root).args(["doc", "-all", "./pkg/bar"]),
+        ]
+    );
+}
+
+#[test]
+fn resolve_target_counts_go_files_and_tests() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    std::fs::create_dir_all(root.join("pkg/sub")).unwrap();
+    std::fs::write(root.join("pkg/a.go"), "package pkg").unwrap();
+    std::fs::write(root.join("pkg/sub/b_test.go"), "package sub").unwrap();
+    std::fs::write(root.join("pkg/README.md"), "").unwrap();
+
+    let meta = backend().resolve_target(root, root.join("pkg")).meta;
+    assert_eq!(meta.language.as_deref(), Some("go"));
+    assert_eq!(meta.is_test, Some(true));
+    assert_eq!(meta.size, Some(2));
+}
+
+#[test]
+fn changed_modules_reads_require_and_sum_lines() {
+    let old = "module example.com/m\n\ngo 1.22\n\nrequire (\n\tgithub.com/a/x v1.0.0\n\tgithub.com/b/y v1.2.0 // indirect\n)\n";
+    let bumped = old.replace("x v1.0.0", "x v1.1.0");
+    let modules = changed_modules(old, &bumped, false).unwrap();
+    assert_eq!(modules.into_iter().collect::<Vec<_>>(), ["github.com/a/x"]);
+
+    let added = old.replace(")\n", "\tgithub.com/c/z v0.1.0\n)\n");
+    assert_eq!(changed_modules(old, &added, false).unwrap().len(), 1);
+    assert!(changed_modules(old, &old.replace("go 1.22", "go 1.23"), false).is_none());
+
+    let sum_old = "github.com/a/x v1.0.0 h1:abc=\ngithub.com/a/x v1.0.0/go.mod h1:def=\n";
+    let sum_new = "github.com/a/x v1.1.0 h1:ghi=\ngithub.com/a/x v1.1.0/go.mod h1:jkl=\n";
+    let modules = changed_modules(sum_old, sum_new, true).unwrap();
+    assert_eq!(modules.into_iter().collect::<Vec<_>>(), ["github.com/a/x"]);
+}
+
+#[test]
+fn dependents_follow_the_module_graph_upwards() {
+    let graph = "example.com/m github.com/a/x@v1.0.0\n\
+                 example.com/m github.com/c/z@v0.1.0\n\
+                 github.com/a/x@v1.0.0 github.com/b/y@v1.2.0\n";
+    let changed = BTreeSet::from(["github.com/b/y".to_string()]);
+    let modules = dependents(graph, &changed);
+    assert!(modules.contains("github.com/a/x"));
+    assert!(modules.contains("github.com/b/y"));
+    assert!(!modules.contains("github.com/c/z"));
+}
+
+#[test]
+fn go_imports_reads_single_and_grouped_imports() {
+    let source =
+        "package p\n\nimport \"fmt\"\nimport (\n\tlog \"github.com/a/x/log\"\n\t_ \"embed\"\n)\n\nfunc f() {}\n";
+    let imports: Vec<String> = go_imports(source).into_iter().collect();
+    assert_eq!(imports, ["embed", "fmt", "github.com/a/x/log"]);
+}
+
+#[test]
+fn test_runs_only_the_tests_in_named_files() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    std::fs::create_dir_all(root.join("pkg")).unwrap();
+    let source = "package pkg\n\nfunc TestA(t *testing.T) {}\n\nfunc helper() {}\n\nfunc ExampleB() {}\n\nfunc (s *suite) TestMethod() {}\n";
+    std::fs::write(root.join("pkg/a_test.go"), source).unwrap();
+    std::fs::write(root.join("pkg/a.go"), "package pkg").unwrap();
+
+    let mut target = backend().resolve_target(root, root.join("pkg"));
+    target.files = vec![PathBuf::from("pkg/a_test.go")];
+    assert_eq!(
+        backend().test(root, &[target.clone()]).unwrap(),
+        [Invocation::new("go", root).args(["test", "./pkg", "-run", "^(ExampleB|TestA)$"])]
+    );
+
+    target.files.push(PathBuf::from("pkg/a.go"));
+    assert_eq!(
+        backend().test(root, &[target]).unwrap(),
+        [Invocation::new("go", root).args(["test", "./pkg"])]
+    );
+}
+
+#[test]
+fn test_failures_resolves_files_through_the_package() {
+    let output = "\
+--- FAIL: TestA (0.00s)
+    a_test.go:8: want 1, got 2
+--- FAIL: TestB (0.00s)
+    --- FAIL: TestB/empty (0.00s)
+        b_test.go:12: boom
+FAIL
+FAIL\texample.com/m/pkg/a\t0.002s
+ok  \texample.com/m/pkg/b\t0.001s
+";
+    let modules = vec![("example.com/m".to_string(), PathBuf::from("svc"))];
+    assert_eq!(
+        test_failures(output, &modules),
+        [
+            Failure {
+                test: "TestA".to_string(),
+                file: Some(PathBuf::from("svc/pkg/a/a_test.go")),
+                line: Some(8),
+                message: "want 1, got 2".to_string(),
+            },
+            Failure {
+                test: "TestB/empty".to_string(),
+                file: Some(PathBuf::from("svc/pkg/a/b_test.go")),
+                line: Some(12),
+                message: "boom".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_cache_counts_cached_packages() {
+    let output = "\
+ok  \texample.com/m/pkg/a\t(cached)
+ok  \texample.com/m/pkg/b\t0.011s
+?   \texample.com/m/cmd\t[no test files]
+--- FAIL: TestC (0.00s)
+FAIL
+FAIL\texample.com/m/pkg/c\t0.002s
+";
+    assert_eq!(test_cache(output), Some(TestCache { cached: 1, total: 3 }));
+}
+
+#[test]
+fn go_orphans_are_unimported_libraries() {
+    let listing = "\
+example.com/m/cmd/app\tmain\t1\texample.com/m/pkg/api fmt\t\t
+example.com/m/pkg/api\tapi\t2\tnet/http\t\t
+example.com/m/pkg/dead\tdead\t1\t\ttesting\texample.com/m/pkg/dead
+example.com/m/pkg/testutil\ttestutil\t1\t\t\t
+example.com/m/pkg/store\tstore\t1\t\texample.com/m/pkg/testutil\t
+example.com/m/e2e\te2e\t0\t\t\texample.com/m/pkg/api
+";
+    assert_eq!(
+        go_orphans(listing),
+        ["example.com/m/pkg/dead", "example.com/m/pkg/store"]
+    );
+}
+
+#[test]
+fn go_graph_links_packages_of_the_repo() {
+    let listing = "\
+example.com/m\tm\t1\texample.com/m/pkg/api\t\t
+example.com/m/cmd/app\tmain\t1\texample.com/m/pkg/api fmt\t\t
+example.com/m/pkg/api\tapi\t2\tnet/http example.com/tools/gen\ttesting example.com/m/cmd/app\t
+";
+    let modules = [
+        ("example.com/m".to_string(), PathBuf::new()),
+        ("example.com/tools".to_string(), PathBuf::from("tools")),
+    ];
+    let nodes = go_graph(listing, &modules);
+    let labels: Vec<&str> = nodes.iter().map(|n| n.label.as_str()).collect();
+    assert_eq!(labels, [".", "./cmd/app", "./pkg/api"]);
+    assert_eq!(nodes[1].dir, Path::new("cmd/app"));
+    assert_eq!(nodes[1].deps.iter().collect::<Vec<_>>(), ["./pkg/api"]);
+    assert!(nodes[2].deps.is_empty());
+}
+
+#[test]
+fn goflags_conflicting_with_the_repo_are_reported() {
+    assert!(goflags_conflicts("-mod=vendor -count=1", true, false).is_empty());
+    assert_eq!(
+        goflags_conflicts("-mod=vendor", false, false),
+        ["GOFLAGS sets -mod=vendor but the repo has no vendor/modules.txt"]
+    );
+    assert_eq!(goflags_conflicts("-mod=mod", false, true).len(), 1);
+    assert!(goflags_conflicts("-mod=mod", false, false).is_empty());
+    assert_eq!(
+        goflags_conflicts("-modfile=go.test.mod", false, false),
+        ["GOFLAGS sets -modfile=go.test.mod, so go ignores the repo's go.mod"]
+    );
+}
+
+#[test]
+fn task_packages_are_the_nearest_cmd_directory() {
+    let repo = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(repo.path().join("cmd/e2e")).unwrap();
+    std::fs::create_dir_all(repo.path().join("services/api/cmd/e2e")).unwrap();
+    std::fs::create_dir_all(repo.path().join("services/api/handlers")).unwrap();
+    let package = |dir: &str, task: &str| task_package(repo.path(), &repo.path().join(dir), task);
+    assert_eq!(
+        package("services/api/handlers", "e2e"),
+        Some(PathBuf::from("services/api/cmd/e2e"))
+    );
+    assert_eq!(package("pkg/auth", "e2e"), Some(PathBuf::from("cmd/e2e")));
+    assert_eq!(package("services/api", "seed"), None);
+}
+
+#[test]
+fn outdated_lists_direct_requirements_with_updates() {
+    let listing = r#"{"Path": "example.com/app", "Main": true, "Dir": "/repo"}
+{"Path": "golang.org/x/net", "Version": "v0.20.0", "Update": {"Path": "golang.org/x/net", "Version": "v0.24.0"}}
+{"Path": "golang.org/x/text", "Version": "v0.14.0", "Indirect": true, "Update": {"Path": "golang.org/x/text", "Version": "v0.15.0"}}
+{"Path": "github.com/google/uuid", "Version": "v1.6.0"}
+"#;
+    assert_eq!(
+        go_outdated(listing, Path::new("tools")).unwrap(),
+        [Outdated {
+            name: "golang.org/x/net".to_string(),
+            current: "v0.20.0".to_string(),
+            latest: "v0.24.0".to_string(),
+            dir: PathBuf::from("tools"),
+        }]
+    );
+}
+
+#[test]
+fn govulncheck_reports_called_vulnerabilities_once_per_module() {
+    let output = r#"{"config": {"protocol_version": "v1.0.0"}}
+{"osv": {"id": "GO-2024-2687", "summary": "HTTP/2 CONTINUATION flood in net/http"}}
+{"osv": {"id": "GO-2023-1988", "summary": "Improper rendering of text nodes"}}
+{"finding": {"osv": "GO-2024-2687", "fixed_version": "v0.23.0", "trace": [{"module": "golang.org/x/net", "version": "v0.17.0", "package": "golang.org/x/net/http2", "function": "Read"}]}}
+{"finding": {"osv": "GO-2024-2687", "fixed_version": "v0.23.0", "trace": [{"module": "golang.org/x/net", "version": "v0.17.0", "package": "golang.org/x/net/http2", "function": "Write"}]}}
+{"finding": {"osv": "GO-2023-1988", "fixed_version": "v0.13.0", "trace": [{"module": "golang.org/x/net", "version": "v0.17.0", "package": "golang.org/x/net/html"}]}}
+"#;
+    assert_eq!(
+        govulncheck_findings(output).unwrap(),
+        [Vulnerability {
+            id: "GO-2024-2687".to_string(),
+            package: "golang.org/x/net".to_string(),
+            version: "v0.17.0".to_string(),
+            severity: Severity::Unknown,
+            summary: "HTTP/2 CONTINUATION flood in net/http".to_string(),
+            fixed: Some("v0.23.0".to_string()),
+            dir: PathBuf::new(),
+        }]
+    );
+}
@@ -0,0 +1,330 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use regex::Regex;
+
+use super::{Backend, Target, TargetMeta};
+use crate::config::Config;
+use crate::exec::Invocation;
+use crate::report::Skip;
+
+/// A quoted project path in `include ":app", ":core:data"` or `include(":app")`.
+static INCLUDED: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"["'](:?[\w.-]+(?::[\w.-]+)*)["']"#).unwrap());
+/// An `include` statement, up to the end of the line.
+static INCLUDE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?m)^\s*include\b(.*)$").unwrap());
+/// A dependency on another project: `project(":core:data")` or `project(path: ':core')`.
+static PROJECT_DEPENDENCY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"project\(\s*(?:path\s*[:=]\s*)?["'](:[^"']*)["']"#).unwrap());
+/// The Android Gradle plugin, applied by id (`com.android.library`), through a version catalog
+/// alias (`libs.plugins.android.application`), or by a convention plugin named after it.
+static ANDROID: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"com\.android\.(application|library|dynamic-feature)|android[.-](application|library)\b").unwrap()
+});
+
+const SETTINGS_FILES: &[&str] = &["settings.gradle", "settings.gradle.kts"];
+const BUILD_FILES: &[&str] = &["build.gradle", "build.gradle.kts"];
+/// Files and directories at the repo root that configure every project in the build.
+const SHARED: &[&str] = &[
+    "settings.gradle",
+    "settings.gradle.kts",
+    "build.gradle",
+    "build.gradle.kts",
+    "gradle.properties",
+    "gradle",
+    "buildSrc",
+    "build-logic",
+];
+
+const DEFAULT_VARIANT: &str = "debug";
+
+/// A project in the Gradle build.
+#[derive(Debug, Clone, PartialEq)]
+struct Project {
+    /// Gradle project path, `:` for the root project (e.g. `:core:data`).
+    path: String,
+    /// Directory relative to the repo root.
+    dir: PathBuf,
+    /// Paths of the projects it depends on.
+    depends: BTreeSet<String>,
+    /// Whether it applies the Android Gradle plugin, so its tasks are per build variant.
+    android: bool,
+}
+
+pub struct GradleBackend;
+
+impl GradleBackend {
+    /// `./gradlew` when the repo has the wrapper, otherwise `gradle`.
+    fn gradle(repo_root: &Path) -> String {
+        let wrapper = repo_root.join("gradlew");
+        if wrapper.is_file() {
+            wrapper.to_string_lossy().into_owned()
+        } else {
+            "gradle".to_string()
+        }
+    }
+
+    /// The root project and the projects `include`d in the settings file, each in the directory
+    /// its path names.
+    fn projects(repo_root: &Path) -> Vec<Project> {
+        let settings = SETTINGS_FILES
+            .iter()
+            .find_map(|name| std::fs::read_to_string(repo_root.join(name)).ok())
+            .unwrap_or_default();
+        let mut paths = vec![":".to_string()];
+        paths.extend(included(&settings));
+        paths
+            .into_iter()
+            .map(|path| {
+                let dir: PathBuf = path.split(':').filter(|p| !p.is_empty()).collect();
+                let build = BUILD_FILES
+                    .iter()
+                    .find_map(|name| std::fs::read_to_string(repo_root.join(&dir).join(name)).ok())
+                    .unwrap_or_default();
+                parse_project(&path, dir, &build)
+            })
+            .collect()
+    }
+
+    fn variant(repo_root: &Path) -> String {
+        Config::load(repo_root)
+            .ok()
+            .and_then(|c| c.gradle.variant)
+            .unwrap_or_else(|| DEFAULT_VARIANT.to_string())
+    }
+
+    /// One Gradle invocation running a task in each target's project, from `task(project)`.
+    fn run(repo_root: &Path, targets: &[Target], task: impl Fn(&Project) -> String) -> Vec<Invocation> {
+        if targets.is_empty() {
+            return vec![];
+        }
+        let projects = Self::projects(repo_root);
+        let tasks: Vec<String> = targets
+            .iter()
+            .filter_map(|t| projects.iter().find(|p| p.path == t.label))
+            .map(|p| {
+                // `:task` runs the root project's task alone; unqualified, it would run everywhere.
+                let path = p.path.trim_end_matches(':');
+                format!("{path}:{}", task(p))
+            })
+            .collect();
+        if tasks.is_empty() {
+            return vec![];
+        }
+        vec![Invocation::new(Self::gradle(repo_root), repo_root).args(tasks)]
+    }
+
+    /// Any of the build files, for checking which plugins the build applies.
+    fn build_files(repo_root: &Path) -> String {
+        Self::projects(repo_root)
+            .iter()
+            .flat_map(|p| BUILD_FILES.iter().map(move |name| repo_root.join(&p.dir).join(name)))
+            .chain(SETTINGS_FILES.iter().map(|name| repo_root.join(name)))
+            .filter_map(|path| std::fs::read_to_string(path).ok())
+            .collect()
+    }
+}
+
+impl Backend for GradleBackend {
+    fn name(&self) -> &str {
+        "gradle"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        SETTINGS_FILES
+            .iter()
+            .chain(BUILD_FILES)
+            .any(|name| dir.join(name).is_file())
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let projects = Self::projects(repo_root);
+        affected_projects(&projects, changed_files)
+            .into_iter()
+            .filter_map(|path| projects.iter().find(|p| p.path == path))
+            .map(|p| project_target(repo_root, p))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_path_buf();
+        let projects = Self::projects(repo_root);
+        let project = projects
+            .iter()
+            .filter(|p| rel.starts_with(&p.dir))
+            .max_by_key(|p| p.dir.components().count())
+            .expect("the root project contains every directory");
+        project_target(repo_root, project)
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let variant = capitalize(&Self::variant(repo_root));
+        Ok(Self::run(repo_root, targets, |p| {
+            if p.android {
+                format!("assemble{variant}")
+            } else {
+                "assemble".to_string()
+            }
+        }))
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let variant = capitalize(&Self::variant(repo_root));
+        let targets: Vec<Target> = targets
+            .iter()
+            .filter(|t| t.meta.is_test != Some(false))
+            .cloned()
+            .collect();
+        Ok(Self::run(repo_root, &targets, |p| {
+            if p.android {
+                format!("test{variant}UnitTest")
+            } else {
+                "test".to_string()
+            }
+        }))
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let variant = capitalize(&Self::variant(repo_root));
+        let plan = Self::run(repo_root, targets, |p| {
+            if p.android {
+                format!("lint{variant}")
+            } else {
+                "check".to_string()
+            }
+        });
+        // `check` depends on the tests, which `kit test` runs.
+        Ok(plan.into_iter().map(|i| i.args(["-x", "test"])).collect())
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let build = Self::build_files(repo_root);
+        let task = if build.contains("spotless") {
+            "spotlessApply"
+        } else if build.contains("ktlint") {
+            "ktlintFormat"
+        } else {
+            return Err(Skip::NotConfigured {
+                detail: "neither spotless nor ktlint is applied in the Gradle build".to_string(),
+            }
+            .into());
+        };
+        let targets = self.affected_targets(repo_root, "", changed_files);
+        Ok(Self::run(repo_root, &targets, |_| task.to_string()))
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new(Self::gradle(repo_root), repo_root).arg("--version")]
+    }
+}
+
+fn project_target(repo_root: &Path, project: &Project) -> Target {
+    let dir = repo_root.join(&project.dir);
+    let meta = TargetMeta {
+        kind: Some(if project.android { "android" } else { "project" }.to_string()),
+        language: None,
+        is_test: Some(dir.join("src").join("test").is_dir()),
+        size: None,
+    };
+    Target {
+        label: project.path.clone(),
+        dir,
+        meta,
+        files: Vec::new(),
+    }
+}
+
+/// `debug` as `Debug` and `freeDebug` as `FreeDebug`, as variant names appear in task names.
+fn capitalize(variant: &str) -> String {
+    let mut chars = variant.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Project paths `include`d in a settings file, with the leading `:` Gradle allows leaving out.
+fn included(settings: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+    for include in INCLUDE.captures_iter(settings) {
+        for path in INCLUDED.captures_iter(&include[1]) {
+            let path = &path[1];
+            let path = if path.starts_with(':') {
+                path.to_string()
+            } else {
+                format!(":{path}")
+            };
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    paths
+}
+
+fn parse_project(path: &str, dir: PathBuf, build: &str) -> Project {
+    Project {
+        path: path.to_string(),
+        dir,
+        depends: PROJECT_DEPENDENCY
+            .captures_iter(build)
+            .map(|c| c[1].to_string())
+            .filter(|d| d != path)
+            .collect(),
+        android: ANDROID.is_match(build),
+    }
+}
+
+/// Paths of the projects affected by the changes: every project when build configuration shared
+/// by all of them changed, otherwise the deepest project containing each changed file (outside its
+/// `build/` output) and the projects that depend on an affected one, directly or not. The root
+/// project of a multi-project build usually builds nothing itself, so it is only affected by
+/// changes to its own `src/`.
+fn affected_projects(projects: &[Project], changed_files: &[PathBuf]) -> BTreeSet<String> {
+    let multi = projects.len() > 1;
+    let mut affected = BTreeSet::new();
+    for file in changed_files {
+        let first = file
+            .components()
+            .next()
+            .and_then(|c| c.as_os_str().to_str())
+            .unwrap_or("");
+        if SHARED.contains(&first) {
+            return projects
+                .iter()
+                .filter(|p| !(multi && p.path == ":"))
+                .map(|p| p.path.clone())
+                .collect();
+        }
+        if file.extension().is_some_and(|e| e == "md") {
+            continue;
+        }
+        let Some(project) = projects
+            .iter()
+            .filter(|p| file.starts_with(&p.dir))
+            .max_by_key(|p| p.dir.components().count())
+        else {
+            continue;
+        };
+        let outside_root_sources = multi && project.path == ":" && !file.starts_with("src");
+        if !outside_root_sources && !file.starts_with(project.dir.join("build")) {
+            affected.insert(project.path.clone());
+        }
+    }
+    loop {
+        let dependents: Vec<String> = projects
+            .iter()
+            .filter(|p| !affected.contains(&p.path) && p.depends.iter().any(|d| affected.contains(d)))
+            .map(|p| p.path.clone())
+            .collect();
+        if dependents.is_empty() {
+            return affected;
+        }
+        affected.extend(dependents);
+    }
+}
+
+#[cfg(test)]
+#[path = "gradle_test.rs"]
+mod tests;
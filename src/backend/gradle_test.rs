@@ -0,0 +1,80 @@
+use super::*;
+
+const SETTINGS: &str = r#"
+rootProject.name = "shop"
+include(":app")
+include ':core:data', "feature-cart"
+"#;
+
+const APP: &str = r#"
+plugins {
+    alias(libs.plugins.android.application)
+}
+dependencies {
+    implementation(project(":core:data"))
+    implementation(project(path = ":feature-cart"))
+}
+"#;
+
+const CART: &str = r#"
+apply plugin: 'com.android.library'
+dependencies {
+    implementation project(':core:data')
+}
+"#;
+
+const DATA: &str = r#"
+plugins { id("org.jetbrains.kotlin.jvm") }
+"#;
+
+fn projects() -> Vec<Project> {
+    vec![
+        parse_project(":", PathBuf::new(), ""),
+        parse_project(":app", PathBuf::from("app"), APP),
+        parse_project(":core:data", PathBuf::from("core/data"), DATA),
+        parse_project(":feature-cart", PathBuf::from("feature-cart"), CART),
+    ]
+}
+
+#[test]
+fn settings_include_projects() {
+    assert_eq!(included(SETTINGS), [":app", ":core:data", ":feature-cart"]);
+}
+
+#[test]
+fn projects_record_dependencies_and_the_android_plugin() {
+    let projects = projects();
+    assert_eq!(
+        projects[1].depends.iter().collect::<Vec<_>>(),
+        [":core:data", ":feature-cart"]
+    );
+    assert!(projects[1].android);
+    assert!(projects[3].android);
+    assert!(!projects[2].android);
+    assert_eq!(capitalize("freeDebug"), "FreeDebug");
+}
+
+#[test]
+fn affected_projects_include_dependents() {
+    let projects = projects();
+    let affected = |files: &[&str]| {
+        let files: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+        affected_projects(&projects, &files).into_iter().collect::<Vec<_>>()
+    };
+    assert_eq!(
+        affected(&["core/data/src/main/kotlin/Repo.kt"]),
+        [":app", ":core:data", ":feature-cart"]
+    );
+    assert_eq!(
+        affected(&["feature-cart/src/main/res/values/strings.xml"]),
+        [":app", ":feature-cart"]
+    );
+    assert_eq!(
+        affected(&["app/build/outputs/app.apk", "README.md", "docs/setup.txt"]),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        affected(&["gradle/libs.versions.toml"]),
+        [":app", ":core:data", ":feature-cart"]
+    );
+}
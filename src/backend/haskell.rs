@@ -0,0 +1,315 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use regex::Regex;
+
+use super::{Backend, Target, TargetMeta};
+use crate::exec::Invocation;
+use crate::git;
+
+static NAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?mi)^name\s*:\s*(\S+)").unwrap());
+/// A field line in a .cabal file (`build-depends:`) or an unindented stanza header (`library`).
+static FIELD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\s*[A-Za-z][\w-]*\s*:|[A-Za-z])").unwrap());
+
+/// Files that configure every package in the project.
+const PROJECT_FILES: &[&str] = &[
+    "stack.yaml",
+    "cabal.project",
+    "cabal.project.local",
+    "cabal.project.freeze",
+];
+/// Extensions of the files that belong to a package's build.
+const SOURCES: &[&str] = &["hs", "lhs", "hsc", "cabal"];
+
+/// A package in the project: a directory with a .cabal file or an hpack package.yaml.
+#[derive(Debug, Clone, PartialEq)]
+struct Package {
+    name: String,
+    /// Directory relative to the repo root.
+    dir: PathBuf,
+    /// Names of the packages it depends on.
+    depends: BTreeSet<String>,
+    has_tests: bool,
+}
+
+pub struct HaskellBackend;
+
+impl HaskellBackend {
+    /// `stack` for Stack projects, otherwise `cabal`.
+    fn tool(repo_root: &Path) -> &'static str {
+        if repo_root.join("stack.yaml").exists() {
+            "stack"
+        } else {
+            "cabal"
+        }
+    }
+
+    fn packages(repo_root: &Path) -> Result<Vec<Package>> {
+        let mut packages: BTreeMap<PathBuf, Package> = BTreeMap::new();
+        // hpack generates the .cabal file from package.yaml, so a package.yaml wins.
+        for pattern in ["*.cabal", "package.yaml"] {
+            for file in git::find_files(repo_root, pattern)? {
+                let text = std::fs::read_to_string(repo_root.join(&file)).unwrap_or_default();
+                let dir = file.parent().unwrap_or(Path::new("")).to_path_buf();
+                if let Some(package) = parse_package(&dir, &text, pattern == "package.yaml") {
+                    packages.insert(dir, package);
+                }
+            }
+        }
+        Ok(packages.into_values().collect())
+    }
+
+    /// `<tool> <command>` on the targets' packages, or on the whole project if the root is among
+    /// them.
+    fn invocation(repo_root: &Path, command: &str, targets: &[&Target]) -> Vec<Invocation> {
+        if targets.is_empty() {
+            return vec![];
+        }
+        let invocation = Invocation::new(Self::tool(repo_root), repo_root).arg(command);
+        if targets.iter().any(|t| t.label == ".") {
+            let all = if Self::tool(repo_root) == "cabal" {
+                vec!["all"]
+            } else {
+                vec![]
+            };
+            return vec![invocation.args(all)];
+        }
+        vec![invocation.args(targets.iter().map(|t| t.label.clone()))]
+    }
+}
+
+impl Backend for HaskellBackend {
+    fn name(&self) -> &str {
+        "haskell"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join("stack.yaml").exists()
+            || dir.join("cabal.project").exists()
+            || std::fs::read_dir(dir).is_ok_and(|entries| {
+                entries
+                    .flatten()
+                    .any(|e| e.path().extension().is_some_and(|e| e == "cabal"))
+            })
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let packages = match Self::packages(repo_root) {
+            Ok(packages) => packages,
+            Err(e) => {
+                eprintln!("kit: failed to find Haskell packages ({e:#})");
+                return vec![];
+            }
+        };
+        affected_packages(&packages, changed_files)
+            .into_iter()
+            .filter_map(|name| packages.iter().find(|p| p.name == name))
+            .map(|p| package_target(repo_root, p))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_path_buf();
+        let package = Self::packages(repo_root)
+            .unwrap_or_default()
+            .into_iter()
+            .find(|p| p.dir == rel);
+        if let Some(package) = package {
+            return package_target(repo_root, &package);
+        }
+        // Stack and cabal both take a directory as a target for the packages in it.
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        Target {
+            label: if rel.is_empty() {
+                ".".to_string()
+            } else {
+                format!("./{rel}")
+            },
+            dir,
+            meta: TargetMeta::default(),
+            files: Vec::new(),
+        }
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let targets: Vec<&Target> = targets.iter().collect();
+        Ok(Self::invocation(repo_root, "build", &targets))
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let targets: Vec<&Target> = targets.iter().filter(|t| t.meta.is_test != Some(false)).collect();
+        Ok(Self::invocation(repo_root, "test", &targets))
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let dirs = targets.iter().map(|t| {
+            let rel = t.dir.strip_prefix(repo_root).unwrap_or(&t.dir).to_string_lossy();
+            if rel.is_empty() {
+                ".".to_string()
+            } else {
+                rel.replace('\\', "/")
+            }
+        });
+        Ok(vec![Invocation::new("hlint", repo_root).args(dirs)])
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let files: Vec<String> = changed_files
+            .iter()
+            .filter(|f| f.extension().is_some_and(|e| e == "hs") && repo_root.join(f).is_file())
+            .map(|f| f.to_string_lossy().replace('\\', "/"))
+            .collect();
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        // fourmolu is ormolu with configuration; a fourmolu.yaml means the repo uses it.
+        let formatter = if repo_root.join("fourmolu.yaml").exists() {
+            "fourmolu"
+        } else {
+            "ormolu"
+        };
+        Ok(vec![
+            Invocation::new(formatter, repo_root)
+                .args(["--mode", "inplace"])
+                .args(files),
+        ])
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new(Self::tool(repo_root), repo_root).arg("--version")]
+    }
+}
+
+fn package_target(repo_root: &Path, package: &Package) -> Target {
+    let meta = TargetMeta {
+        kind: Some("package".to_string()),
+        language: Some("haskell".to_string()),
+        is_test: Some(package.has_tests),
+        size: None,
+    };
+    Target {
+        label: package.name.clone(),
+        dir: repo_root.join(&package.dir),
+        meta,
+        files: Vec::new(),
+    }
+}
+
+/// A package from its .cabal file or, with `hpack`, its package.yaml.
+fn parse_package(dir: &Path, text: &str, hpack: bool) -> Option<Package> {
+    let name = NAME.captures(text)?[1].to_string();
+    let (depends, has_tests) = if hpack {
+        (hpack_dependencies(text), text.lines().any(|l| l.starts_with("tests:")))
+    } else {
+        (
+            cabal_dependencies(text),
+            text.lines().any(|l| l.trim_start().starts_with("test-suite ")),
+        )
+    };
+    Some(Package {
+        depends: depends.into_iter().filter(|d| *d != name).collect(),
+        name,
+        dir: dir.to_path_buf(),
+        has_tests,
+    })
+}
+
+/// Package names in every `build-depends` field of a .cabal file, which may continue over
+/// several lines.
+fn cabal_dependencies(text: &str) -> BTreeSet<String> {
+    let mut depends = BTreeSet::new();
+    let mut in_field = false;
+    for line in text.lines() {
+        let line = line.split("--").next().unwrap_or("");
+        let value = match line.trim_start().to_lowercase().strip_prefix("build-depends") {
+            Some(rest) if rest.trim_start().starts_with(':') => {
+                in_field = true;
+                line.split_once(':').map(|(_, v)| v).unwrap_or("")
+            }
+            _ if FIELD.is_match(line) => {
+                in_field = false;
+                continue;
+            }
+            _ if in_field => line,
+            _ => continue,
+        };
+        depends.extend(
+            value
+                .split(',')
+                .filter_map(|dep| dep.split_whitespace().next())
+                .map(|dep| dep.split(['>', '<', '=', '^', '{']).next().unwrap_or(dep).to_string())
+                .filter(|dep| !dep.is_empty()),
+        );
+    }
+    depends
+}
+
+/// Package names in the `dependencies` lists of an hpack package.yaml: "- text >= 2".
+fn hpack_dependencies(text: &str) -> BTreeSet<String> {
+    let mut depends = BTreeSet::new();
+    let mut indent = None;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        let depth = line.len() - trimmed.len();
+        if trimmed.starts_with("dependencies:") {
+            indent = Some(depth);
+            continue;
+        }
+        match indent {
+            Some(field) if depth > field || (depth == field && trimmed.starts_with('-')) => {
+                if let Some(dep) = trimmed.strip_prefix('-').and_then(|d| d.split_whitespace().next()) {
+                    depends.insert(dep.to_string());
+                }
+            }
+            _ => indent = None,
+        }
+    }
+    depends
+}
+
+/// Names of the packages affected by the changes: the package owning each changed source, every
+/// package when a project file changed, and the local packages that depend on an affected one.
+fn affected_packages(packages: &[Package], changed_files: &[PathBuf]) -> BTreeSet<String> {
+    let mut affected = BTreeSet::new();
+    for file in changed_files {
+        let name = file.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if PROJECT_FILES.contains(&name) && file.parent().is_none_or(|p| p.as_os_str().is_empty()) {
+            return packages.iter().map(|p| p.name.clone()).collect();
+        }
+        let is_source = name == "package.yaml"
+            || file
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| SOURCES.contains(&e));
+        if !is_source {
+            continue;
+        }
+        if let Some(package) = packages
+            .iter()
+            .filter(|p| file.starts_with(&p.dir))
+            .max_by_key(|p| p.dir.components().count())
+        {
+            affected.insert(package.name.clone());
+        }
+    }
+    loop {
+        let dependents: Vec<String> = packages
+            .iter()
+            .filter(|p| !affected.contains(&p.name) && p.depends.iter().any(|d| affected.contains(d)))
+            .map(|p| p.name.clone())
+            .collect();
+        if dependents.is_empty() {
+            return affected;
+        }
+        affected.extend(dependents);
+    }
+}
+
+#[cfg(test)]
+#[path = "haskell_test.rs"]
+mod tests;
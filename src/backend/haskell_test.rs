@@ -0,0 +1,84 @@
+use super::*;
+
+const CORE_CABAL: &str = "\
+cabal-version: 2.4
+name:          core
+version:       0.1.0
+
+library
+  exposed-modules: Core
+  build-depends:
+      base >=4.14 && <5
+    , text ^>=2.0 -- strings
+    , containers
+  hs-source-dirs: src
+
+test-suite core-test
+  type: exitcode-stdio-1.0
+  build-depends: base, core, hspec
+";
+
+const API_YAML: &str = "\
+name: api
+dependencies:
+  - base >= 4.14
+  - core
+library:
+  source-dirs: src
+executables:
+  api:
+    main: Main.hs
+";
+
+#[test]
+fn packages_are_parsed_from_cabal_and_hpack_files() {
+    let core = parse_package(Path::new("core"), CORE_CABAL, false).unwrap();
+    assert_eq!(core.name, "core");
+    assert_eq!(
+        core.depends.iter().collect::<Vec<_>>(),
+        ["base", "containers", "hspec", "text"]
+    );
+    assert!(core.has_tests);
+    let api = parse_package(Path::new("services/api"), API_YAML, true).unwrap();
+    assert_eq!(api.depends.iter().collect::<Vec<_>>(), ["base", "core"]);
+    assert!(!api.has_tests);
+}
+
+#[test]
+fn affected_packages_include_local_dependents() {
+    let packages = [
+        parse_package(Path::new("core"), CORE_CABAL, false).unwrap(),
+        parse_package(Path::new("services/api"), API_YAML, true).unwrap(),
+    ];
+    let affected = affected_packages(&packages, &[PathBuf::from("core/src/Core.hs")]);
+    assert_eq!(affected.into_iter().collect::<Vec<_>>(), ["api", "core"]);
+    let affected = affected_packages(&packages, &[PathBuf::from("services/api/app/Main.hs")]);
+    assert_eq!(affected.into_iter().collect::<Vec<_>>(), ["api"]);
+    assert!(affected_packages(&packages, &[PathBuf::from("core/README.md")]).is_empty());
+    assert_eq!(affected_packages(&packages, &[PathBuf::from("stack.yaml")]).len(), 2);
+}
+
+#[test]
+fn stack_projects_build_named_packages() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    std::fs::write(root.join("stack.yaml"), "resolver: lts-22.0\n").unwrap();
+    let target = |label: &str, is_test| Target {
+        label: label.to_string(),
+        dir: root.join(label),
+        meta: TargetMeta {
+            is_test: Some(is_test),
+            ..TargetMeta::default()
+        },
+        files: Vec::new(),
+    };
+    let targets = [target("core", true), target("api", false)];
+    assert_eq!(
+        HaskellBackend.test(root, &targets).unwrap(),
+        [Invocation::new("stack", root).args(["test", "core"])]
+    );
+    assert_eq!(
+        HaskellBackend.build(root, &targets).unwrap(),
+        [Invocation::new("stack", root).args(["build", "core", "api"])]
+    );
+}
@@ -0,0 +1,246 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use regex::Regex;
+
+use super::{Backend, Target, TargetMeta};
+use crate::exec::Invocation;
+use crate::hooks::shell_quote;
+use crate::{git, paths, state};
+
+/// A dependency on a chart in the repo: `repository: file://../common`.
+static LOCAL_DEPENDENCY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?m)^\s*-?\s*repository:\s*["']?file://([^"'\s]+)"#).unwrap());
+static DEPENDENCIES: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?m)^dependencies:").unwrap());
+
+/// A directory with a Chart.yaml.
+#[derive(Debug, Clone, PartialEq)]
+struct Chart {
+    /// Directory relative to the repo root.
+    dir: PathBuf,
+    /// Whether Chart.yaml declares dependencies, which `helm dependency build` fetches into `charts/`.
+    has_dependencies: bool,
+    /// Directories of the charts in the repo it depends on.
+    local: BTreeSet<PathBuf>,
+}
+
+pub struct HelmBackend;
+
+impl HelmBackend {
+    fn charts(repo_root: &Path) -> Result<Vec<Chart>> {
+        Ok(git::find_files(repo_root, "Chart.yaml")?
+            .into_iter()
+            .map(|file| {
+                let text = std::fs::read_to_string(repo_root.join(&file)).unwrap_or_default();
+                parse_chart(file.parent().unwrap_or(Path::new("")), &text)
+            })
+            .collect())
+    }
+
+    /// `helm dependency build` for the charts that declare dependencies, which lint and template
+    /// need in `charts/`.
+    fn dependencies(repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let charts = Self::charts(repo_root)?;
+        Ok(targets
+            .iter()
+            .filter(|t| {
+                charts
+                    .iter()
+                    .any(|c| repo_root.join(&c.dir) == t.dir && c.has_dependencies)
+            })
+            .map(|t| Invocation::new("helm", repo_root).args(["dependency", "build", &t.label]))
+            .collect())
+    }
+}
+
+impl Backend for HelmBackend {
+    fn name(&self) -> &str {
+        "helm"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        // A chart at the root, chart-testing's config, or charts in `charts/` as chart-releaser lays
+        // them out.
+        dir.join("Chart.yaml").is_file()
+            || dir.join("ct.yaml").is_file()
+            || std::fs::read_dir(dir.join("charts"))
+                .is_ok_and(|entries| entries.flatten().any(|e| e.path().join("Chart.yaml").is_file()))
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let charts = match Self::charts(repo_root) {
+            Ok(charts) => charts,
+            Err(e) => {
+                eprintln!("kit: failed to find Helm charts ({e:#})");
+                return vec![];
+            }
+        };
+        affected_charts(&charts, changed_files)
+            .iter()
+            .map(|dir| chart_target(repo_root, dir))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_path_buf();
+        chart_target(repo_root, &rel)
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Self::dependencies(repo_root, targets)
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let mut plan = Self::dependencies(repo_root, targets)?;
+        let out = state::dir(repo_root, "helm")?;
+        for target in targets {
+            for values in ci_values(&target.dir) {
+                let name = match &values {
+                    Some(v) => format!(
+                        "{}-{}",
+                        target.label,
+                        v.file_stem().unwrap_or_default().to_string_lossy()
+                    ),
+                    None => target.label.clone(),
+                };
+                let manifest = out.join(format!("{}.yaml", name.replace(['/', '.'], "_")));
+                plan.push(Invocation::new("sh", repo_root).args([
+                    "-c".to_string(),
+                    template_script(&target.label, values.as_deref(), &manifest),
+                ]));
+            }
+        }
+        Ok(plan)
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut plan = Self::dependencies(repo_root, targets)?;
+        plan.push(
+            Invocation::new("helm", repo_root)
+                .arg("lint")
+                .args(targets.iter().map(|t| t.label.clone())),
+        );
+        Ok(plan)
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let charts = Self::charts(repo_root)?;
+        let dirs: Vec<PathBuf> = charts.iter().map(|c| c.dir.clone()).collect();
+        let touched: BTreeSet<&PathBuf> = changed_files.iter().filter_map(|f| owning_chart(&dirs, f)).collect();
+        Ok(touched
+            .into_iter()
+            .filter(|dir| uses_helm_docs(&repo_root.join(dir)))
+            .map(|dir| Invocation::new("helm-docs", repo_root).args(["--chart-search-root".to_string(), label(dir)]))
+            .collect())
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new("helm", repo_root).args(["version", "--short"])]
+    }
+}
+
+fn label(dir: &Path) -> String {
+    let rel = dir.to_string_lossy().replace('\\', "/");
+    if rel.is_empty() { ".".to_string() } else { rel }
+}
+
+fn chart_target(repo_root: &Path, dir: &Path) -> Target {
+    let meta = TargetMeta {
+        kind: Some("chart".to_string()),
+        language: Some("helm".to_string()),
+        is_test: None,
+        size: None,
+    };
+    Target {
+        label: label(dir),
+        dir: repo_root.join(dir),
+        meta,
+        files: Vec::new(),
+    }
+}
+
+fn parse_chart(dir: &Path, chart_yaml: &str) -> Chart {
+    Chart {
+        dir: dir.to_path_buf(),
+        has_dependencies: DEPENDENCIES.is_match(chart_yaml),
+        local: LOCAL_DEPENDENCY
+            .captures_iter(chart_yaml)
+            .filter_map(|c| paths::normalize(&dir.join(&c[1])))
+            .collect(),
+    }
+}
+
+/// The deepest chart directory containing `file`.
+fn owning_chart<'a>(dirs: &'a [PathBuf], file: &Path) -> Option<&'a PathBuf> {
+    dirs.iter()
+        .filter(|d| file.starts_with(d))
+        .max_by_key(|d| d.components().count())
+}
+
+/// Charts containing a changed file, plus the charts that depend on them through a `file://`
+/// repository, directly or not.
+fn affected_charts(charts: &[Chart], changed_files: &[PathBuf]) -> BTreeSet<PathBuf> {
+    let dirs: Vec<PathBuf> = charts.iter().map(|c| c.dir.clone()).collect();
+    let mut affected: BTreeSet<PathBuf> = changed_files
+        .iter()
+        .filter_map(|f| owning_chart(&dirs, f))
+        .cloned()
+        .collect();
+    loop {
+        let dependents: Vec<PathBuf> = charts
+            .iter()
+            .filter(|c| !affected.contains(&c.dir) && c.local.iter().any(|d| affected.contains(d)))
+            .map(|c| c.dir.clone())
+            .collect();
+        if dependents.is_empty() {
+            return affected;
+        }
+        affected.extend(dependents);
+    }
+}
+
+/// The values files to render a chart with: each `ci/*-values.yaml` (the chart-testing
+/// convention), or just the chart's defaults (None) when there are none.
+fn ci_values(chart: &Path) -> Vec<Option<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(chart.join("ci"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.to_string_lossy().ends_with("-values.yaml"))
+        .collect();
+    files.sort();
+    if files.is_empty() {
+        return vec![None];
+    }
+    files.into_iter().map(Some).collect()
+}
+
+/// Render a chart to `manifest` and validate the manifests with kubeconform. The output goes through
+/// a file rather than a pipe so a failing `helm template` fails the step.
+fn template_script(chart: &str, values: Option<&Path>, manifest: &Path) -> String {
+    let values = values
+        .map(|v| format!(" --values {}", shell_quote(&v.to_string_lossy())))
+        .unwrap_or_default();
+    let manifest = shell_quote(&manifest.to_string_lossy());
+    format!(
+        "helm template kit {}{values} > {manifest} && kubeconform -strict -summary {manifest}",
+        shell_quote(chart)
+    )
+}
+
+/// Whether a chart's README is generated by helm-docs: from its own template, or with the marker
+/// line helm-docs' default template writes.
+fn uses_helm_docs(chart: &Path) -> bool {
+    chart.join("README.md.gotmpl").is_file()
+        || std::fs::read_to_string(chart.join("README.md")).is_ok_and(|readme| readme.contains("helm-docs"))
+}
+
+#[cfg(test)]
+#[path = "helm_test.rs"]
+mod tests;
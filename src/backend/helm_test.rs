@@ -0,0 +1,79 @@
+use super::*;
+
+fn chart(dir: &str, local: &[&str]) -> Chart {
+    Chart {
+        dir: PathBuf::from(dir),
+        has_dependencies: !local.is_empty(),
+        local: local.iter().map(PathBuf::from).collect(),
+    }
+}
+
+#[test]
+fn charts_depending_on_an_affected_chart_are_affected() {
+    let charts = vec![
+        chart("charts/common", &[]),
+        chart("charts/api", &["charts/common"]),
+        chart("charts/platform", &["charts/api"]),
+        chart("charts/web", &[]),
+    ];
+    let changed = vec![
+        PathBuf::from("charts/common/templates/_helpers.tpl"),
+        PathBuf::from("src/main.go"),
+    ];
+    let affected: Vec<PathBuf> = affected_charts(&charts, &changed).into_iter().collect();
+    assert_eq!(
+        affected,
+        ["charts/api", "charts/common", "charts/platform"].map(PathBuf::from)
+    );
+}
+
+#[test]
+fn local_dependencies_are_read_from_chart_yaml() {
+    let text = "apiVersion: v2\nname: api\ndependencies:\n  - name: common\n    repository: \"file://../common\"\n  - name: redis\n    repository: https://charts.bitnami.com/bitnami\n";
+    assert_eq!(
+        parse_chart(Path::new("charts/api"), text),
+        chart("charts/api", &["charts/common"])
+    );
+    assert!(!parse_chart(Path::new("charts/web"), "apiVersion: v2\nname: web\n").has_dependencies);
+}
+
+#[test]
+fn template_failures_are_not_hidden_by_a_pipe() {
+    assert_eq!(
+        template_script(
+            "charts/api",
+            Some(Path::new("charts/api/ci/ha-values.yaml")),
+            Path::new("/out/api.yaml")
+        ),
+        "helm template kit 'charts/api' --values 'charts/api/ci/ha-values.yaml' > '/out/api.yaml' \
+         && kubeconform -strict -summary '/out/api.yaml'"
+    );
+}
+
+#[test]
+fn charts_render_once_per_ci_values_file() {
+    let chart = tempfile::tempdir().unwrap();
+    assert_eq!(ci_values(chart.path()), [None]);
+    std::fs::create_dir(chart.path().join("ci")).unwrap();
+    for file in ["ha-values.yaml", "default-values.yaml", "notes.txt"] {
+        std::fs::write(chart.path().join("ci").join(file), "").unwrap();
+    }
+    assert_eq!(
+        ci_values(chart.path()),
+        [
+            Some(chart.path().join("ci/default-values.yaml")),
+            Some(chart.path().join("ci/ha-values.yaml")),
+        ]
+    );
+}
+
+#[test]
+fn detection_needs_a_chart_at_the_root_or_in_charts() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("deploy/app")).unwrap();
+    std::fs::write(dir.path().join("deploy/app/Chart.yaml"), "name: app\n").unwrap();
+    assert!(!HelmBackend.detect(dir.path()));
+    std::fs::create_dir_all(dir.path().join("charts/api")).unwrap();
+    std::fs::write(dir.path().join("charts/api/Chart.yaml"), "name: api\n").unwrap();
+    assert!(HelmBackend.detect(dir.path()));
+}
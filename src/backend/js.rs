@@ -1,9 +1,35 @@
-use std::ffi::OsStr;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::LazyLock;
+use std::time::SystemTime;
 
 use anyhow::{Context, Result};
+use ignore::gitignore::GitignoreBuilder;
+use regex::Regex;
 
-use super::{Backend, Target};
+use super::{Backend, GraphNode, Outdated, Target, TargetMeta, pnpm_lock, written_since};
+use crate::coverage::Coverage;
+use crate::error::KitError;
+use crate::exec::Invocation;
+use crate::failures::Failure;
+use crate::git;
+use crate::report::Skip;
+use crate::security::{self, Severity, Vulnerability};
+
+/// Names of ESLint config files, flat and legacy.
+const ESLINT_CONFIGS: &[&str] = &["eslint.config.*", ".eslintrc*"];
+
+/// Names of TypeDoc config files.
+const TYPEDOC_CONFIGS: &[&str] = &["typedoc.json", "typedoc.jsonc"];
+
+/// Names of Prettier config files.
+const PRETTIER_CONFIGS: &[&str] = &[".prettierrc*", "prettier.config.*"];
+
+static FAIL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*FAIL\s+(\S+)(?:\s+>\s+(.+))?").unwrap());
+static JEST_TEST: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*● (.+)$").unwrap());
+static STACK_LOCATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:\(|❯ |at )([^\s()]+):(\d+):\d+\)?\s*$").unwrap());
 
 enum Orchestrator {
     Nx,
@@ -31,22 +57,6 @@ impl Orchestrator {
     }
 }
 
-fn run<I, S>(cmd: &str, args: I, dir: &Path) -> Result<()>
-where
-    I: IntoIterator<Item = S>,
-    S: AsRef<OsStr>,
-{
-    let status = std::process::Command::new(cmd)
-        .args(args)
-        .current_dir(dir)
-        .status()
-        .with_context(|| format!("failed to run {cmd}"))?;
-    if !status.success() {
-        anyhow::bail!("{cmd} exited with {status}");
-    }
-    Ok(())
-}
-
 pub struct JsBackend {
     /// Backend name (e.g. "pnpm", "yarn").
     name: &'static str,
@@ -54,27 +64,114 @@ pub struct JsBackend {
     lock_files: &'static [&'static str],
     /// Command used to install and run scripts.
     cmd: &'static str,
+    /// Arguments that install the locked dependencies.
+    install: &'static [&'static str],
+    /// Whether scripts run as `<cmd> run <script> -- <args>` rather than `<cmd> <script> <args>`, and
+    /// changes map to npm `workspaces` that scripts are filtered to with `--workspace`. pnpm maps
+    /// them to the packages of pnpm-workspace.yaml, filtered to with `--filter`.
+    npm: bool,
 }
 
 pub const PNPM: JsBackend = JsBackend {
     name: "pnpm",
     lock_files: &["pnpm-workspace.yaml", "pnpm-lock.yaml"],
     cmd: "pnpm",
+    install: &["install"],
+    npm: false,
 };
 
 pub const YARN: JsBackend = JsBackend {
     name: "yarn",
     lock_files: &["yarn.lock"],
     cmd: "yarn",
+    install: &["install"],
+    npm: false,
+};
+
+pub const NPM: JsBackend = JsBackend {
+    name: "npm",
+    lock_files: &["package-lock.json"],
+    cmd: "npm",
+    install: &["ci"],
+    npm: true,
 };
 
 impl JsBackend {
-    fn run_script(&self, orch: &Orchestrator, repo_root: &Path, target: &str) -> Result<()> {
+    fn run_script(&self, orch: &Orchestrator, repo_root: &Path, targets: &[Target], target: &str) -> Invocation {
         match orch {
-            Orchestrator::Nx => run("nx", ["affected", &format!("--target={target}")], repo_root),
-            Orchestrator::Turbo => run("turbo", ["run", target, "--filter=...[origin/main]"], repo_root),
-            Orchestrator::Plain => run(self.cmd, [target], repo_root),
+            Orchestrator::Nx => {
+                Invocation::new("nx", repo_root).args(["affected".to_string(), format!("--target={target}")])
+            }
+            Orchestrator::Turbo => {
+                Invocation::new("turbo", repo_root).args(["run", target, "--filter=...[origin/main]"])
+            }
+            Orchestrator::Plain => self.script(repo_root, target, self.workspace_flags(targets), vec![]),
+        }
+    }
+
+    /// Run the test script with `--coverage`, which Jest and Vitest both accept.
+    fn run_coverage(&self, orch: &Orchestrator, repo_root: &Path, targets: &[Target]) -> Invocation {
+        match orch {
+            Orchestrator::Nx => Invocation::new("nx", repo_root).args(["affected", "--target=test", "--coverage"]),
+            Orchestrator::Turbo => Invocation::new("turbo", repo_root).args([
+                "run",
+                "test",
+                "--filter=...[origin/main]",
+                "--",
+                "--coverage",
+            ]),
+            Orchestrator::Plain => self.script(
+                repo_root,
+                "test",
+                self.workspace_flags(targets),
+                vec!["--coverage".to_string()],
+            ),
+        }
+    }
+
+    /// A package script run in `dir`, with options for the package manager and arguments for the
+    /// script.
+    fn script(&self, dir: &Path, script: &str, flags: Vec<String>, args: Vec<String>) -> Invocation {
+        if !self.npm {
+            // pnpm passes options after the script name on to the script.
+            return Invocation::new(self.cmd, dir).args(flags).arg(script).args(args);
+        }
+        let invocation = Invocation::new(self.cmd, dir).args(["run", script]).args(flags);
+        if args.is_empty() {
+            invocation
+        } else {
+            invocation.arg("--").args(args)
+        }
+    }
+
+    /// A binary installed in the package's or the workspace's `node_modules`, run in `dir`.
+    fn exec(&self, dir: &Path, bin: &str) -> Invocation {
+        match self.name {
+            "npm" => Invocation::new(self.cmd, dir).args(["exec", "--", bin]),
+            // Yarn runs binaries as if they were scripts.
+            "yarn" => Invocation::new(self.cmd, dir).arg(bin),
+            _ => Invocation::new(self.cmd, dir).args(["exec", bin]),
+        }
+    }
+
+    /// `--workspace` options limiting an npm script to the targets' workspaces, or `--filter` options
+    /// limiting a pnpm one. Empty when the whole repo is a target.
+    fn workspace_flags(&self, targets: &[Target]) -> Vec<String> {
+        if targets.is_empty() || targets.iter().any(|t| t.label == ".") {
+            return vec![];
         }
+        if self.name == "pnpm" {
+            return targets.iter().map(|t| format!("--filter={}", t.label)).collect();
+        }
+        if !self.npm {
+            return vec![];
+        }
+        let mut flags: Vec<String> = targets
+            .iter()
+            .map(|t| format!("--workspace={}", t.label.trim_start_matches("./")))
+            .collect();
+        flags.push("--if-present".to_string());
+        flags
     }
 
     fn orch(&self, repo_root: &Path) -> Orchestrator {
@@ -93,11 +190,37 @@ impl Backend for JsBackend {
         self.lock_files.iter().any(|f| dir.join(f).exists())
     }
 
-    fn affected_targets(&self, repo_root: &Path, _changed_files: &[PathBuf]) -> Vec<Target> {
-        vec![Target {
-            label: ".".to_string(),
-            dir: repo_root.to_path_buf(),
-        }]
+    fn affected_targets(&self, repo_root: &Path, base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let whole = || vec![self.resolve_target(repo_root, repo_root.to_path_buf())];
+        if changed_files.is_empty() {
+            return whole();
+        }
+        let members = match self.name {
+            "npm" => npm_workspaces(repo_root),
+            "pnpm" => pnpm_workspaces(repo_root),
+            _ => return whole(),
+        };
+        let members = match members {
+            Ok(members) => members,
+            Err(e) => {
+                eprintln!("kit: failed to read {} workspaces ({e:#})", self.name);
+                return whole();
+            }
+        };
+        // A pnpm-lock.yaml change affects the packages whose resolved dependencies changed.
+        let lock = Path::new("pnpm-lock.yaml");
+        let mut changed: Vec<PathBuf> = changed_files.to_vec();
+        if self.name == "pnpm" && changed.iter().any(|f| f == lock) {
+            let Some(importers) = pnpm_lock_changes(repo_root, base) else {
+                return whole();
+            };
+            changed.retain(|f| f != lock);
+            changed.extend(importers);
+        }
+        affected_workspaces(&members, &changed)
+            .into_iter()
+            .map(|dir| self.resolve_target(repo_root, repo_root.join(dir)))
+            .collect()
     }
 
     fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
@@ -108,39 +231,670 @@ impl Backend for JsBackend {
         } else {
             format!("./{rel}")
         };
-        Target { label, dir }
+        let meta = TargetMeta {
+            kind: Some("workspace".to_string()),
+            language: Some("javascript".to_string()),
+            ..TargetMeta::default()
+        };
+        Target {
+            label,
+            dir,
+            meta,
+            files: Vec::new(),
+        }
     }
 
-    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<()> {
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
         if targets.is_empty() {
-            return Ok(());
+            return Ok(vec![]);
         }
         let orch = self.orch(repo_root);
-        run(self.cmd, ["install"], repo_root)?;
-        self.run_script(&orch, repo_root, "build")
+        Ok(vec![
+            Invocation::new(self.cmd, repo_root).args(self.install.iter().copied()),
+            self.run_script(&orch, repo_root, targets, "build"),
+        ])
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        if targets.iter().any(|t| t.files.is_empty()) {
+            let orch = self.orch(repo_root);
+            return Ok(vec![self.run_script(&orch, repo_root, targets, "test")]);
+        }
+        // Only files were named: pass them to the test script of the package that owns them, which
+        // Jest and Vitest take as path filters.
+        let mut packages: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+        for file in targets.iter().flat_map(|t| &t.files) {
+            let package = file
+                .ancestors()
+                .skip(1)
+                .find(|dir| repo_root.join(dir).join("package.json").is_file())
+                .unwrap_or(Path::new(""));
+            let rel = file.strip_prefix(package).unwrap_or(file);
+            packages
+                .entry(package.to_path_buf())
+                .or_default()
+                .push(rel.to_string_lossy().replace('\\', "/"));
+        }
+        Ok(packages
+            .into_iter()
+            .map(|(package, files)| self.script(&repo_root.join(package), "test", vec![], files))
+            .collect())
     }
 
-    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<()> {
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
         if targets.is_empty() {
-            return Ok(());
+            return Ok(vec![]);
         }
         let orch = self.orch(repo_root);
-        self.run_script(&orch, repo_root, "test")
+        Ok(vec![self.run_script(&orch, repo_root, targets, "lint")])
     }
 
-    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<()> {
+    fn check(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
         if targets.is_empty() {
-            return Ok(());
+            return Ok(vec![]);
+        }
+        let dirs: Vec<PathBuf> = targets
+            .iter()
+            .map(|t| t.dir.strip_prefix(repo_root).unwrap_or(&t.dir).to_path_buf())
+            .collect();
+        let projects = tsc_projects(&git::find_files(repo_root, "tsconfig.json")?, &dirs);
+        if projects.is_empty() {
+            return Err(Skip::NotConfigured {
+                detail: "no tsconfig.json in the affected packages".to_string(),
+            }
+            .into());
+        }
+        Ok(projects
+            .into_iter()
+            .map(|dir| self.exec(&repo_root.join(dir), "tsc").arg("--noEmit"))
+            .collect())
+    }
+
+    fn fix(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let configured = ESLINT_CONFIGS
+            .iter()
+            .any(|name| git::find_files(repo_root, name).is_ok_and(|files| !files.is_empty()));
+        if !configured {
+            return Err(Skip::NotConfigured {
+                detail: "no ESLint config in the repo".to_string(),
+            }
+            .into());
+        }
+        let dirs = targets.iter().map(|t| t.dir.to_string_lossy().into_owned());
+        Ok(vec![self.exec(repo_root, "eslint").arg("--fix").args(dirs)])
+    }
+
+    fn docs(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        // TypeDoc runs in each affected package with its own config, or once for the repo's.
+        let plan: Vec<Invocation> = targets
+            .iter()
+            .filter(|t| t.label != "." && TYPEDOC_CONFIGS.iter().any(|c| t.dir.join(c).is_file()))
+            .map(|t| self.exec(&t.dir, "typedoc"))
+            .collect();
+        if !plan.is_empty() {
+            return Ok(plan);
+        }
+        if TYPEDOC_CONFIGS.iter().any(|c| repo_root.join(c).is_file()) {
+            return Ok(vec![self.exec(repo_root, "typedoc")]);
+        }
+        Err(Skip::NotConfigured {
+            detail: "no typedoc.json in the affected packages or at the repo root".to_string(),
+        }
+        .into())
+    }
+
+    fn task(&self, repo_root: &Path, task: &str, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
         }
         let orch = self.orch(repo_root);
-        self.run_script(&orch, repo_root, "lint")
+        Ok(vec![self.run_script(&orch, repo_root, targets, task)])
     }
 
-    fn fmt(&self, repo_root: &Path, _changed_files: &[PathBuf]) -> Result<()> {
+    fn fmt(&self, repo_root: &Path, _changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
         let orch = self.orch(repo_root);
-        match orch {
-            Orchestrator::Nx => run("nx", ["format:write"], repo_root),
-            _ => self.run_script(&orch, repo_root, "format"),
+        Ok(vec![match orch {
+            Orchestrator::Nx => Invocation::new("nx", repo_root).arg("format:write"),
+            _ => self.run_script(&orch, repo_root, &[], "format"),
+        }])
+    }
+
+    fn fmt_check(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        if let Orchestrator::Nx = self.orch(repo_root) {
+            return Ok(vec![Invocation::new("nx", repo_root).arg("format:check")]);
+        }
+        let configured = PRETTIER_CONFIGS
+            .iter()
+            .any(|name| git::find_files(repo_root, name).is_ok_and(|files| !files.is_empty()));
+        if !configured {
+            return Err(Skip::NotConfigured {
+                detail: "no Prettier config in the repo".to_string(),
+            }
+            .into());
+        }
+        let files: Vec<String> = changed_files
+            .iter()
+            .filter(|f| repo_root.join(f).is_file())
+            .map(|f| f.to_string_lossy().replace('\\', "/"))
+            .collect();
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        // Prettier lists the files it would change and exits 1; unknown file types are skipped.
+        Ok(vec![
+            self.exec(repo_root, "prettier")
+                .args(["--check", "--ignore-unknown"])
+                .args(files),
+        ])
+    }
+
+    fn coverage(&self, repo_root: &Path, targets: &[Target], _out_dir: &Path) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let orch = self.orch(repo_root);
+        Ok(vec![self.run_coverage(&orch, repo_root, targets)])
+    }
+
+    fn read_coverage(&self, repo_root: &Path, _out_dir: &Path, since: SystemTime) -> Result<Coverage> {
+        // Test runners write coverage/lcov.info next to the package.json they ran in.
+        let mut cov = Coverage::default();
+        for manifest in git::find_files(repo_root, "package.json")? {
+            let dir = repo_root.join(manifest.parent().unwrap_or(Path::new("")));
+            let lcov = dir.join("coverage/lcov.info");
+            if written_since(&lcov, since) {
+                let text =
+                    std::fs::read_to_string(&lcov).with_context(|| format!("failed to read {}", lcov.display()))?;
+                cov.merge(Coverage::parse_lcov(&text, &dir, repo_root));
+            }
+        }
+        Ok(cov)
+    }
+
+    fn parse_failures(&self, _repo_root: &Path, output: &str) -> Vec<Failure> {
+        test_failures(output)
+    }
+
+    fn graph(&self, repo_root: &Path) -> Result<Option<Vec<GraphNode>>> {
+        Ok(Some(workspace_nodes(&workspace_manifests(repo_root)?)))
+    }
+
+    fn orphans(&self, repo_root: &Path) -> Result<Option<Vec<String>>> {
+        let root = read_manifest(repo_root, Path::new(""))?;
+        Ok(Some(js_orphans(&root, &workspace_manifests(repo_root)?)))
+    }
+
+    fn tidy(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        // The lock file is shared by the workspace, so it is deduplicated once for every package.
+        if self.name == "yarn" && !repo_root.join(".yarnrc.yml").is_file() {
+            return Err(Skip::NotConfigured {
+                detail: "classic Yarn has no dedupe command".to_string(),
+            }
+            .into());
+        }
+        Ok(vec![Invocation::new(self.cmd, repo_root).arg("dedupe")])
+    }
+
+    fn outdated(&self, repo_root: &Path) -> Result<Option<Vec<Outdated>>> {
+        // Yarn Berry has no outdated command, and classic Yarn's JSON is a table of strings.
+        let args: &[&str] = match self.name {
+            "npm" => &["outdated", "--json"],
+            "pnpm" => &["outdated", "-r", "--format", "json"],
+            _ => return Ok(None),
+        };
+        let output = Command::new(self.cmd)
+            .args(args)
+            .current_dir(repo_root)
+            .output()
+            .map_err(|e| KitError::spawn(self.cmd, e))?;
+        // Both exit 1 when anything is outdated, so only missing output is a failure.
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !output.status.success() && stdout.trim().is_empty() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("{} outdated failed: {}", self.cmd, stderr.trim());
+        }
+        Ok(Some(js_outdated(repo_root, &stdout)?))
+    }
+
+    fn audit(&self, repo_root: &Path, targets: &[Target]) -> Result<Option<Vec<Vulnerability>>> {
+        // Yarn's audit output differs between versions; OSV-Scanner reads yarn.lock instead.
+        let mut args = vec!["audit".to_string(), "--json".to_string()];
+        match self.name {
+            "npm" => args.extend(self.workspace_flags(targets)),
+            "pnpm" => {}
+            _ => return Ok(None),
+        }
+        let output = security::scanner_output(self.cmd, &args, repo_root)?;
+        Ok(Some(js_audit(&output)?))
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![
+            Invocation::new("node", repo_root).arg("--version"),
+            Invocation::new(self.cmd, repo_root).arg("--version"),
+        ]
+    }
+
+    fn extra_tools(&self, repo_root: &Path) -> Vec<Invocation> {
+        Orchestrator::detect(repo_root)
+            .name()
+            .map(|name| Invocation::new(name, repo_root).arg("--version"))
+            .into_iter()
+            .collect()
+    }
+
+    fn misconfigurations(&self, repo_root: &Path) -> Vec<String> {
+        let manager = read_manifest(repo_root, Path::new(""))
+            .ok()
+            .and_then(|root| root.get("packageManager")?.as_str().map(String::from));
+        package_manager_conflict(self.name, manager.as_deref())
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Dependency sections of a package.json that count as a use of another workspace package.
+const DEPENDENCY_FIELDS: &[&str] = &[
+    "dependencies",
+    "devDependencies",
+    "peerDependencies",
+    "optionalDependencies",
+];
+
+/// Scripts that mark a package as an application rather than a library.
+const APP_SCRIPTS: &[&str] = &["start", "dev", "serve"];
+
+/// A root `packageManager` field naming another package manager than the one the lock file is for,
+/// which Corepack enforces when scripts run.
+fn package_manager_conflict(backend: &str, package_manager: Option<&str>) -> Option<String> {
+    let manager = package_manager?;
+    let name = manager.split('@').next().unwrap_or(manager);
+    (name != backend).then(|| format!("package.json sets packageManager to {manager} but the lock file is {backend}'s"))
+}
+
+/// The installed packages with newer releases in `npm outdated --json` or `pnpm outdated --format
+/// json` output: an object by package name of its `current` and `latest` versions, and with pnpm
+/// `-r` the workspace packages depending on it. npm reports for the root package. Packages that
+/// aren't installed have no current version and are left out.
+fn js_outdated(repo_root: &Path, json: &str) -> Result<Vec<Outdated>> {
+    if json.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    let report: BTreeMap<String, serde_json::Value> =
+        serde_json::from_str(json).context("invalid JSON from the outdated command")?;
+    let mut outdated = Vec::new();
+    for (name, entries) in report {
+        // npm lists a package once per workspace depending on it, all reported for the root.
+        let entries = match entries {
+            serde_json::Value::Array(entries) => entries,
+            entry => vec![entry],
+        };
+        for entry in entries {
+            let (Some(current), Some(latest)) = (entry["current"].as_str(), entry["latest"].as_str()) else {
+                continue;
+            };
+            let dependents: Vec<PathBuf> = entry["dependentPackages"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|d| d["location"].as_str())
+                .map(|location| {
+                    Path::new(location)
+                        .strip_prefix(repo_root)
+                        .unwrap_or(Path::new(""))
+                        .to_path_buf()
+                })
+                .collect();
+            let dirs = if dependents.is_empty() {
+                vec![PathBuf::new()]
+            } else {
+                dependents
+            };
+            for dir in dirs {
+                outdated.push(Outdated {
+                    name: name.clone(),
+                    current: current.to_string(),
+                    latest: latest.to_string(),
+                    dir,
+                });
+            }
+        }
+    }
+    outdated.dedup();
+    Ok(outdated)
+}
+
+/// The advisories in `npm audit --json` (report version 2, by vulnerable package) or `pnpm audit
+/// --json` (by advisory) output. npm names the affected range rather than the installed version, and
+/// lists the packages that depend on a vulnerable one as well, which are left out.
+fn js_audit(json: &str) -> Result<Vec<Vulnerability>> {
+    let report: serde_json::Value = serde_json::from_str(json).context("invalid JSON from audit")?;
+    // npm reports a failure to reach the registry as JSON too.
+    if let Some(summary) = report["error"]["summary"].as_str() {
+        anyhow::bail!("audit failed: {summary}");
+    }
+    let mut found = Vec::new();
+    for (name, entry) in report["vulnerabilities"].as_object().into_iter().flatten() {
+        let fixed = entry["fixAvailable"]
+            .as_object()
+            .filter(|fix| fix.get("name").and_then(|n| n.as_str()) == Some(name))
+            .and_then(|fix| fix.get("version")?.as_str().map(String::from));
+        for via in entry["via"].as_array().into_iter().flatten() {
+            if via["name"].as_str() != Some(name) {
+                continue;
+            }
+            let url = via["url"].as_str().unwrap_or_default();
+            found.push(Vulnerability {
+                id: match url.rsplit('/').next() {
+                    Some(id) if !id.is_empty() => id.to_string(),
+                    _ => via["source"].to_string(),
+                },
+                package: name.clone(),
+                version: via["range"].as_str().unwrap_or_default().to_string(),
+                severity: Severity::parse(via["severity"].as_str().unwrap_or_default()),
+                summary: via["title"].as_str().unwrap_or_default().to_string(),
+                fixed: fixed.clone(),
+                dir: PathBuf::new(),
+            });
+        }
+    }
+    for (key, advisory) in report["advisories"].as_object().into_iter().flatten() {
+        let patched = advisory["patched_versions"].as_str().unwrap_or_default();
+        found.push(Vulnerability {
+            id: advisory["github_advisory_id"].as_str().unwrap_or(key).to_string(),
+            package: advisory["module_name"].as_str().unwrap_or_default().to_string(),
+            version: advisory["findings"][0]["version"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            severity: Severity::parse(advisory["severity"].as_str().unwrap_or_default()),
+            summary: advisory["title"].as_str().unwrap_or_default().to_string(),
+            // pnpm writes `<0.0.0` when no version is patched.
+            fixed: (!patched.is_empty() && patched != "<0.0.0").then(|| patched.to_string()),
+            dir: PathBuf::new(),
+        });
+    }
+    Ok(found)
+}
+
+fn read_manifest(repo_root: &Path, dir: &Path) -> Result<serde_json::Value> {
+    let path = repo_root.join(dir).join("package.json");
+    let text = std::fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_str(&text).with_context(|| format!("invalid {}", path.display()))
+}
+
+/// The package.json of every workspace package, by directory: those of the root package.json's
+/// `workspaces`, or else of pnpm-workspace.yaml. Without either, every nested package counts.
+fn workspace_manifests(repo_root: &Path) -> Result<Vec<(PathBuf, serde_json::Value)>> {
+    let mut members = npm_workspaces(repo_root)?;
+    if members.is_empty() {
+        members = pnpm_workspaces(repo_root)?;
+    }
+    if members.is_empty() {
+        members = git::find_files(repo_root, "package.json")?
+            .into_iter()
+            .filter_map(|m| m.parent().filter(|d| !d.as_os_str().is_empty()).map(Path::to_path_buf))
+            .collect();
+    }
+    let mut manifests = Vec::new();
+    for dir in members {
+        let manifest = read_manifest(repo_root, &dir)?;
+        manifests.push((dir, manifest));
+    }
+    Ok(manifests)
+}
+
+/// Workspace package names and the names of the workspace packages each depends on, in any
+/// dependency section.
+pub fn workspace_graph(repo_root: &Path) -> Result<BTreeMap<String, BTreeSet<String>>> {
+    Ok(workspace_nodes(&workspace_manifests(repo_root)?)
+        .into_iter()
+        .map(|node| (node.label, node.deps))
+        .collect())
+}
+
+/// Named workspace packages, by name, with the workspace packages each depends on in any
+/// dependency section.
+fn workspace_nodes(manifests: &[(PathBuf, serde_json::Value)]) -> Vec<GraphNode> {
+    let name = |manifest: &serde_json::Value| manifest.get("name").and_then(|n| n.as_str()).map(String::from);
+    let names: BTreeSet<String> = manifests.iter().filter_map(|(_, m)| name(m)).collect();
+    manifests
+        .iter()
+        .filter_map(|(dir, manifest)| {
+            let deps = DEPENDENCY_FIELDS
+                .iter()
+                .filter_map(|field| manifest.get(field)?.as_object())
+                .flat_map(|deps| deps.keys())
+                .filter(|d| names.contains(*d))
+                .cloned()
+                .collect();
+            Some(GraphNode {
+                label: name(manifest)?,
+                dir: dir.clone(),
+                deps,
+            })
+        })
+        .collect()
+}
+
+/// Directories of the npm workspaces declared in the root package.json, relative to the repo root.
+fn npm_workspaces(repo_root: &Path) -> Result<Vec<PathBuf>> {
+    let path = repo_root.join("package.json");
+    let text = std::fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    let root: serde_json::Value = serde_json::from_str(&text).with_context(|| format!("invalid {}", path.display()))?;
+    workspace_members(repo_root, &root, &git::find_files(repo_root, "package.json")?)
+}
+
+/// Directories of `manifests` matched by the root package.json's `workspaces` globs, given either
+/// as a list or as `{ "packages": [...] }`.
+fn workspace_members(repo_root: &Path, root: &serde_json::Value, manifests: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let patterns = root.get("workspaces").map(|w| w.get("packages").unwrap_or(w));
+    let patterns: Vec<&str> = patterns
+        .and_then(|p| p.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|p| p.as_str())
+        .collect();
+    members_matching(repo_root, &patterns, manifests)
+}
+
+/// Directories of the pnpm workspace packages listed in pnpm-workspace.yaml, relative to the repo
+/// root. Empty without the file.
+fn pnpm_workspaces(repo_root: &Path) -> Result<Vec<PathBuf>> {
+    let path = repo_root.join("pnpm-workspace.yaml");
+    if !path.is_file() {
+        return Ok(vec![]);
+    }
+    let text = std::fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    let patterns = pnpm_patterns(&text);
+    let patterns: Vec<&str> = patterns.iter().map(String::as_str).collect();
+    members_matching(repo_root, &patterns, &git::find_files(repo_root, "package.json")?)
+}
+
+/// The entries of the top-level `packages:` list of a pnpm-workspace.yaml, `!` exclusions included.
+fn pnpm_patterns(text: &str) -> Vec<String> {
+    let mut patterns = Vec::new();
+    let mut in_list = false;
+    for line in text.lines() {
+        if !line.starts_with([' ', '\t', '-']) && !line.trim().is_empty() {
+            in_list = line.trim_end() == "packages:";
+        } else if in_list && let Some(entry) = line.trim().strip_prefix('-') {
+            let entry = entry.split(" #").next().unwrap_or(entry).trim();
+            patterns.push(entry.trim_matches(['\'', '"']).to_string());
+        }
+    }
+    patterns
+}
+
+/// Importer directories a pnpm-lock.yaml change since `base` affects, or None if the change can't
+/// be narrowed.
+fn pnpm_lock_changes(repo_root: &Path, base: &str) -> Option<BTreeSet<PathBuf>> {
+    let file = Path::new("pnpm-lock.yaml");
+    let merge_base = git::merge_base(repo_root, base).ok()?;
+    let old = git::show(repo_root, &merge_base, file).ok()??;
+    let new = std::fs::read_to_string(repo_root.join(file)).ok()?;
+    pnpm_lock::affected_importers(&old, &new)
+}
+
+/// Directories of `manifests` matched by workspace globs, leaving out those matched by a later `!`
+/// pattern.
+fn members_matching(repo_root: &Path, patterns: &[&str], manifests: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut builder = GitignoreBuilder::new(repo_root);
+    for pattern in patterns {
+        let (negated, pattern) = pattern.strip_prefix('!').map_or((false, *pattern), |p| (true, p));
+        let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+        let line = if negated {
+            format!("!/{pattern}")
+        } else {
+            format!("/{pattern}")
+        };
+        builder
+            .add_line(None, &line)
+            .with_context(|| format!("invalid workspace pattern `{pattern}`"))?;
+    }
+    let globs = builder.build()?;
+    Ok(manifests
+        .iter()
+        .filter_map(|m| m.parent().filter(|d| !d.as_os_str().is_empty()))
+        .filter(|d| globs.matched(repo_root.join(d), true).is_ignore())
+        .map(Path::to_path_buf)
+        .collect())
+}
+
+/// Directories of the TypeScript projects in the target directories: those with a tsconfig.json
+/// in or below a target directory, outermost first, leaving out projects nested in another one.
+fn tsc_projects(tsconfigs: &[PathBuf], dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut projects: Vec<PathBuf> = tsconfigs
+        .iter()
+        .filter_map(|t| t.parent().map(Path::to_path_buf))
+        .filter(|p| dirs.iter().any(|d| p.starts_with(d)))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    projects.sort_by_key(|p| p.components().count());
+    let mut outermost: Vec<PathBuf> = Vec::new();
+    for project in projects {
+        if !outermost.iter().any(|o| project.starts_with(o)) {
+            outermost.push(project);
         }
     }
+    outermost
 }
+
+/// Workspace packages, as `./dir`, that neither the root package.json nor another workspace
+/// package depends on, and that are not entry points: packages with a `bin`, applications with a
+/// start, dev, or serve script, and packages published from the repo (not `"private": true`).
+fn js_orphans(root: &serde_json::Value, members: &[(PathBuf, serde_json::Value)]) -> Vec<String> {
+    let dependencies = |manifest: &serde_json::Value| -> Vec<String> {
+        DEPENDENCY_FIELDS
+            .iter()
+            .filter_map(|field| manifest.get(field)?.as_object())
+            .flat_map(|deps| deps.keys().cloned())
+            .collect()
+    };
+    let mut used: BTreeSet<String> = dependencies(root).into_iter().collect();
+    for (_, manifest) in members {
+        let own = manifest.get("name").and_then(|n| n.as_str());
+        used.extend(dependencies(manifest).into_iter().filter(|d| Some(d.as_str()) != own));
+    }
+    members
+        .iter()
+        .filter(|(_, manifest)| {
+            let name = manifest.get("name").and_then(|n| n.as_str()).unwrap_or_default();
+            let scripts = manifest.get("scripts").and_then(|s| s.as_object());
+            let entry_point = manifest.get("bin").is_some()
+                || manifest.get("private").and_then(|p| p.as_bool()) != Some(true)
+                || scripts.is_some_and(|s| APP_SCRIPTS.iter().any(|a| s.contains_key(*a)));
+            !entry_point && !used.contains(name)
+        })
+        .map(|(dir, _)| format!("./{}", dir.to_string_lossy().replace('\\', "/")))
+        .collect()
+}
+
+/// The deepest workspace containing each changed file. Files in no workspace, such as the lockfile
+/// and the root package.json, affect the whole repo (an empty path), which then is the only target.
+fn affected_workspaces(members: &[PathBuf], changed_files: &[PathBuf]) -> BTreeSet<PathBuf> {
+    let affected: BTreeSet<PathBuf> = changed_files
+        .iter()
+        .map(|f| {
+            members
+                .iter()
+                .filter(|m| f.starts_with(m))
+                .max_by_key(|m| m.components().count())
+                .cloned()
+                .unwrap_or_default()
+        })
+        .collect();
+    if affected.contains(Path::new("")) {
+        return BTreeSet::from([PathBuf::new()]);
+    }
+    affected
+}
+
+/// Failing tests in Jest or Vitest output. Jest names a failing file on a `FAIL` line and each
+/// failing test on a `●` line; Vitest puts both on one `FAIL file > suite > test` line. The message
+/// is the first line after the test's name and the line number comes from the first stack frame in
+/// the test file. Paths are as the runner prints them, relative to the package it ran in.
+fn test_failures(output: &str) -> Vec<Failure> {
+    let mut failures: Vec<Failure> = Vec::new();
+    let mut file: Option<String> = None;
+    // Whether the last failure is still waiting for its message and line.
+    let mut open = false;
+    for line in output.lines() {
+        if let Some(c) = FAIL.captures(line) {
+            file = Some(c[1].to_string());
+            open = c.get(2).is_some();
+            if let Some(test) = c.get(2) {
+                failures.push(Failure {
+                    test: test.as_str().trim().to_string(),
+                    file: Some(PathBuf::from(&c[1])),
+                    line: None,
+                    message: String::new(),
+                });
+            }
+        } else if let Some(c) = JEST_TEST.captures(line)
+            && let Some(file) = &file
+        {
+            open = !c[1].starts_with("Console");
+            if open {
+                failures.push(Failure {
+                    test: c[1].trim().to_string(),
+                    file: Some(PathBuf::from(file)),
+                    line: None,
+                    message: String::new(),
+                });
+            }
+        } else if open && let Some(f) = failures.last_mut() {
+            if let Some(c) = STACK_LOCATION.captures(line) {
+                let path = &c[1];
+                let in_test_file = f
+                    .file
+                    .as_ref()
+                    .is_some_and(|file| Path::new(path).ends_with(file) || file.ends_with(path));
+                if in_test_file {
+                    f.line = c[2].parse().ok();
+                    open = false;
+                }
+            } else if f.message.is_empty() && !line.trim().is_empty() {
+                f.message = line.trim().to_string();
+            }
+        }
+    }
+    failures
+}
+
+#[cfg(test)]
+#[path = "js_test.rs"]
+mod tests;
@@ -0,0 +1,303 @@
+use super::*;
+
+fn failure(test: &str, file: &str, line: Option<u32>, message: &str) -> Failure {
+    Failure {
+        test: test.to_string(),
+        file: Some(PathBuf::from(file)),
+        line,
+        message: message.to_string(),
+    }
+}
+
+#[test]
+fn test_failures_reads_jest_output() {
+    let output = "\
+ PASS  src/ok.test.js
+ FAIL  src/sum.test.js (1.2 s)
+  ● sum › adds numbers
+
+    expect(received).toBe(expected) // Object.is equality
+
+    Expected: 4
+    Received: 3
+
+      3 | test('adds', () => {
+    > 4 |   expect(sum(1, 2)).toBe(4);
+        |                     ^
+
+      at Object.<anonymous> (src/sum.test.js:4:21)
+
+  ● Console
+
+    console.log
+      hello
+";
+    assert_eq!(
+        test_failures(output),
+        [failure(
+            "sum › adds numbers",
+            "src/sum.test.js",
+            Some(4),
+            "expect(received).toBe(expected) // Object.is equality"
+        )]
+    );
+}
+
+#[test]
+fn test_failures_reads_vitest_output() {
+    let output = "\
+ ❯ src/sum.test.ts (2 tests | 1 failed) 5ms
+   × sum > adds numbers
+
+⎯⎯⎯⎯⎯⎯⎯ Failed Tests 1 ⎯⎯⎯⎯⎯⎯⎯
+
+ FAIL  src/sum.test.ts > sum > adds numbers
+AssertionError: expected 3 to be 4 // Object.is equality
+ ❯ src/sum.test.ts:5:23
+      3| describe('sum', () => {
+";
+    assert_eq!(
+        test_failures(output),
+        [failure(
+            "sum > adds numbers",
+            "src/sum.test.ts",
+            Some(5),
+            "AssertionError: expected 3 to be 4 // Object.is equality"
+        )]
+    );
+}
+
+#[test]
+fn workspace_members_accept_lists_and_package_objects() {
+    let repo = Path::new("/repo");
+    let manifests = [
+        PathBuf::from("package.json"),
+        PathBuf::from("packages/api/package.json"),
+        PathBuf::from("packages/api/node_modules/dep/package.json"),
+        PathBuf::from("tools/package.json"),
+    ];
+    let list: serde_json::Value = serde_json::json!({ "workspaces": ["packages/*"] });
+    assert_eq!(
+        workspace_members(repo, &list, &manifests).unwrap(),
+        [PathBuf::from("packages/api")]
+    );
+    let object: serde_json::Value = serde_json::json!({ "workspaces": { "packages": ["tools"] } });
+    assert_eq!(
+        workspace_members(repo, &object, &manifests).unwrap(),
+        [PathBuf::from("tools")]
+    );
+}
+
+#[test]
+fn affected_workspaces_fall_back_to_the_whole_repo() {
+    let members = [PathBuf::from("packages/api"), PathBuf::from("packages/web")];
+    let changed = [PathBuf::from("packages/api/src/index.js")];
+    assert_eq!(
+        affected_workspaces(&members, &changed).into_iter().collect::<Vec<_>>(),
+        [PathBuf::from("packages/api")]
+    );
+    let changed = [
+        PathBuf::from("packages/api/src/index.js"),
+        PathBuf::from("package-lock.json"),
+    ];
+    assert_eq!(
+        affected_workspaces(&members, &changed).into_iter().collect::<Vec<_>>(),
+        [PathBuf::new()]
+    );
+}
+
+#[test]
+fn npm_scripts_are_filtered_to_workspaces() {
+    let repo = Path::new("/repo");
+    let targets = [
+        NPM.resolve_target(repo, repo.join("packages/api")),
+        NPM.resolve_target(repo, repo.join("packages/web")),
+    ];
+    assert_eq!(
+        NPM.test(repo, &targets).unwrap(),
+        [Invocation::new("npm", repo).args([
+            "run",
+            "test",
+            "--workspace=packages/api",
+            "--workspace=packages/web",
+            "--if-present"
+        ])]
+    );
+    assert_eq!(
+        NPM.build(repo, &[NPM.resolve_target(repo, repo.to_path_buf())])
+            .unwrap(),
+        [
+            Invocation::new("npm", repo).arg("ci"),
+            Invocation::new("npm", repo).args(["run", "build"])
+        ]
+    );
+    assert_eq!(
+        NPM.coverage(repo, &targets[..1], Path::new("/tmp")).unwrap(),
+        [Invocation::new("npm", repo).args([
+            "run",
+            "test",
+            "--workspace=packages/api",
+            "--if-present",
+            "--",
+            "--coverage"
+        ])]
+    );
+}
+
+#[test]
+fn pnpm_scripts_are_filtered_to_workspace_packages() {
+    let repo = Path::new("/repo");
+    let targets = [PNPM.resolve_target(repo, repo.join("packages/api"))];
+    assert_eq!(
+        PNPM.test(repo, &targets).unwrap(),
+        [Invocation::new("pnpm", repo).args(["--filter=./packages/api", "test"])]
+    );
+    assert_eq!(
+        PNPM.test(repo, &[PNPM.resolve_target(repo, repo.to_path_buf())])
+            .unwrap(),
+        [Invocation::new("pnpm", repo).arg("test")]
+    );
+}
+
+#[test]
+fn pnpm_workspace_patterns_keep_exclusions() {
+    let text = "packages:\n  - 'packages/*'\n  - \"apps/**\" # apps\n  - '!**/test/**'\ncatalog:\n  react: ^18\n";
+    assert_eq!(pnpm_patterns(text), ["packages/*", "apps/**", "!**/test/**"]);
+    let repo = Path::new("/repo");
+    let manifests = [
+        PathBuf::from("packages/api/package.json"),
+        PathBuf::from("packages/api/test/fixture/package.json"),
+        PathBuf::from("apps/web/package.json"),
+        PathBuf::from("tools/package.json"),
+    ];
+    assert_eq!(
+        members_matching(repo, &["packages/**", "apps/*", "!**/test/**"], &manifests).unwrap(),
+        [PathBuf::from("packages/api"), PathBuf::from("apps/web")]
+    );
+}
+
+#[test]
+fn js_orphans_are_unused_private_libraries() {
+    let member = |dir: &str, manifest: &str| (PathBuf::from(dir), serde_json::from_str(manifest).unwrap());
+    let root = serde_json::json!({ "devDependencies": { "@acme/lint-config": "*" } });
+    let members = [
+        member(
+            "apps/web",
+            r#"{"name": "@acme/web", "private": true, "scripts": {"dev": "next"}, "dependencies": {"@acme/ui": "*"}}"#,
+        ),
+        member("packages/ui", r#"{"name": "@acme/ui", "private": true}"#),
+        member(
+            "packages/lint-config",
+            r#"{"name": "@acme/lint-config", "private": true}"#,
+        ),
+        member(
+            "packages/cli",
+            r#"{"name": "@acme/cli", "private": true, "bin": "cli.js"}"#,
+        ),
+        member("packages/sdk", r#"{"name": "@acme/sdk"}"#),
+        member(
+            "packages/old",
+            r#"{"name": "@acme/old", "private": true, "devDependencies": {"@acme/old": "*"}}"#,
+        ),
+    ];
+    assert_eq!(js_orphans(&root, &members), ["./packages/old"]);
+}
+
+#[test]
+fn package_manager_field_must_match_the_lock_file() {
+    assert_eq!(package_manager_conflict("pnpm", Some("pnpm@9.1.0")), None);
+    assert_eq!(package_manager_conflict("pnpm", None), None);
+    assert_eq!(
+        package_manager_conflict("npm", Some("yarn@4.2.2")),
+        Some("package.json sets packageManager to yarn@4.2.2 but the lock file is npm's".to_string())
+    );
+}
+
+#[test]
+fn tsc_projects_are_the_outermost_in_the_targets() {
+    let tsconfigs: Vec<PathBuf> = [
+        "tsconfig.json",
+        "packages/ui/tsconfig.json",
+        "packages/ui/e2e/tsconfig.json",
+        "packages/api/tsconfig.json",
+    ]
+    .iter()
+    .map(PathBuf::from)
+    .collect();
+    assert_eq!(
+        tsc_projects(
+            &tsconfigs,
+            &[PathBuf::from("packages/ui"), PathBuf::from("packages/db")]
+        ),
+        [PathBuf::from("packages/ui")]
+    );
+    assert_eq!(tsc_projects(&tsconfigs, &[PathBuf::new()]), [PathBuf::new()]);
+}
+
+#[test]
+fn outdated_reads_npm_and_pnpm_reports() {
+    let root = Path::new("/repo");
+    let npm = r#"{
+        "react": {"current": "18.2.0", "wanted": "18.3.1", "latest": "19.0.0", "dependent": "app"},
+        "left-pad": {"wanted": "1.3.0", "latest": "1.3.0", "dependent": "app"}
+    }"#;
+    let found = js_outdated(root, npm).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!((found[0].name.as_str(), found[0].latest.as_str()), ("react", "19.0.0"));
+    assert_eq!(found[0].dir, PathBuf::new());
+
+    let pnpm = r#"{
+        "typescript": {
+            "current": "5.3.3", "latest": "5.6.2", "wanted": "5.3.3",
+            "dependentPackages": [{"name": "@acme/ui", "location": "/repo/packages/ui"}, {"name": "app", "location": "/repo"}]
+        }
+    }"#;
+    let dirs: Vec<PathBuf> = js_outdated(root, pnpm).unwrap().into_iter().map(|o| o.dir).collect();
+    assert_eq!(dirs, [PathBuf::from("packages/ui"), PathBuf::new()]);
+    assert!(js_outdated(root, "").unwrap().is_empty());
+}
+
+#[test]
+fn audit_reads_npm_and_pnpm_reports() {
+    let npm = r#"{"auditReportVersion": 2, "vulnerabilities": {
+        "minimist": {
+            "name": "minimist", "severity": "critical", "range": "<=0.2.3",
+            "via": [{"source": 1096, "name": "minimist", "title": "Prototype Pollution", "url": "https://github.com/advisories/GHSA-xvch-5gv4-984h", "severity": "critical", "range": "<0.2.4"}],
+            "fixAvailable": true
+        },
+        "mkdirp": {"name": "mkdirp", "severity": "critical", "via": ["minimist"], "fixAvailable": true}
+    }}"#;
+    let found = js_audit(npm).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(
+        (found[0].id.as_str(), found[0].severity, found[0].version.as_str()),
+        ("GHSA-xvch-5gv4-984h", Severity::Critical, "<0.2.4")
+    );
+
+    let pnpm = r#"{"advisories": {"1096": {
+        "github_advisory_id": "GHSA-xvch-5gv4-984h", "module_name": "minimist", "severity": "high",
+        "title": "Prototype Pollution", "patched_versions": ">=0.2.4", "findings": [{"version": "0.0.8", "paths": ["mkdirp>minimist"]}]
+    }}}"#;
+    let found = js_audit(pnpm).unwrap();
+    assert_eq!(found[0].version, "0.0.8");
+    assert_eq!(found[0].fixed.as_deref(), Some(">=0.2.4"));
+}
+
+#[test]
+fn docs_run_typedoc_in_packages_with_a_config() {
+    let repo = tempfile::tempdir().unwrap();
+    let root = repo.path();
+    for dir in ["packages/api", "packages/web"] {
+        std::fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    std::fs::write(root.join("packages/api/typedoc.json"), "{}").unwrap();
+    let targets = vec![
+        PNPM.resolve_target(root, root.join("packages/api")),
+        PNPM.resolve_target(root, root.join("packages/web")),
+    ];
+    assert_eq!(
+        PNPM.docs(root, &targets).unwrap(),
+        vec![Invocation::new("pnpm", &root.join("packages/api")).args(["exec", "typedoc"])]
+    );
+    assert!(PNPM.docs(root, &targets[1..]).is_err());
+}
@@ -0,0 +1,190 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use super::{Backend, Target, TargetMeta};
+use crate::exec::Invocation;
+use crate::git;
+
+/// Runs JuliaFormatter on the paths passed as arguments. `format` returns whether everything was
+/// already formatted.
+const FORMAT: &str = "using JuliaFormatter; format(ARGS)";
+/// Like [`FORMAT`] without rewriting anything, failing when a file isn't formatted.
+const CHECK_FORMAT: &str = "using JuliaFormatter; exit(format(ARGS; overwrite = false) ? 0 : 1)";
+
+/// Files whose change affects the package they are in.
+const PROJECT_FILES: &[&str] = &["Project.toml", "Manifest.toml"];
+
+/// A directory with a Project.toml naming a package.
+#[derive(Debug, Clone, PartialEq)]
+struct Package {
+    name: String,
+    /// Directory relative to the repo root.
+    dir: PathBuf,
+    /// Names of the packages in its `[deps]` and `[extras]`.
+    depends: BTreeSet<String>,
+}
+
+pub struct JuliaBackend;
+
+impl JuliaBackend {
+    fn packages(repo_root: &Path) -> Result<Vec<Package>> {
+        Ok(git::find_files(repo_root, "Project.toml")?
+            .into_iter()
+            .filter_map(|file| {
+                let text = std::fs::read_to_string(repo_root.join(&file)).ok()?;
+                parse_package(file.parent().unwrap_or(Path::new("")), &text)
+            })
+            .collect())
+    }
+
+    /// `julia --project -e 'using Pkg; Pkg.<function>()'` in each target's package.
+    fn pkg(targets: &[&Target], function: &str) -> Vec<Invocation> {
+        targets
+            .iter()
+            .map(|t| {
+                Invocation::new("julia", &t.dir).args(["--project", "-e", &format!("using Pkg; Pkg.{function}()")])
+            })
+            .collect()
+    }
+}
+
+impl Backend for JuliaBackend {
+    fn name(&self) -> &str {
+        "julia"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join("Project.toml").is_file() || dir.join("Manifest.toml").is_file()
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let packages = match Self::packages(repo_root) {
+            Ok(packages) => packages,
+            Err(e) => {
+                eprintln!("kit: failed to find Julia packages ({e:#})");
+                return vec![];
+            }
+        };
+        affected_packages(&packages, changed_files)
+            .into_iter()
+            .map(|dir| self.resolve_target(repo_root, repo_root.join(dir)))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_string_lossy();
+        let rel = rel.replace('\\', "/");
+        let meta = TargetMeta {
+            kind: Some("package".to_string()),
+            language: Some("julia".to_string()),
+            is_test: Some(dir.join("test").join("runtests.jl").is_file()),
+            size: None,
+        };
+        Target {
+            label: if rel.is_empty() { ".".to_string() } else { rel },
+            dir,
+            meta,
+            files: Vec::new(),
+        }
+    }
+
+    fn build(&self, _repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        // Pkg.build runs the packages' deps/build.jl scripts, after instantiating the environment.
+        let targets: Vec<&Target> = targets.iter().collect();
+        Ok(Self::pkg(&targets, "build"))
+    }
+
+    fn test(&self, _repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let targets: Vec<&Target> = targets.iter().filter(|t| t.meta.is_test != Some(false)).collect();
+        Ok(Self::pkg(&targets, "test"))
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        // Julia has no standard linter; lint checks that JuliaFormatter would leave the code as is.
+        let dirs = targets.iter().map(|t| t.label.clone());
+        Ok(vec![
+            Invocation::new("julia", repo_root)
+                .args(["-e", CHECK_FORMAT])
+                .args(dirs),
+        ])
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let files: Vec<String> = changed_files
+            .iter()
+            .filter(|f| f.extension().is_some_and(|e| e == "jl") && repo_root.join(f).is_file())
+            .map(|f| f.to_string_lossy().replace('\\', "/"))
+            .collect();
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![
+            Invocation::new("julia", repo_root).args(["-e", FORMAT]).args(files),
+        ])
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new("julia", repo_root).arg("--version")]
+    }
+}
+
+/// A package from its Project.toml, or None for an environment without a `name`, such as a
+/// docs/ or test/ project.
+fn parse_package(dir: &Path, project_toml: &str) -> Option<Package> {
+    let manifest: toml::Table = toml::from_str(project_toml).ok()?;
+    let name = manifest.get("name")?.as_str()?.to_string();
+    let depends = ["deps", "extras"]
+        .iter()
+        .filter_map(|key| manifest.get(*key).and_then(|d| d.as_table()))
+        .flat_map(|deps| deps.keys().cloned())
+        .collect();
+    Some(Package {
+        name,
+        dir: dir.to_path_buf(),
+        depends,
+    })
+}
+
+/// Directories of the packages containing a changed Julia or project file, mapped to the deepest
+/// package, and of the packages that depend on them, directly or not.
+fn affected_packages(packages: &[Package], changed_files: &[PathBuf]) -> BTreeSet<PathBuf> {
+    let mut affected: BTreeSet<PathBuf> = changed_files
+        .iter()
+        .filter(|f| {
+            let name = f.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            name.ends_with(".jl") || PROJECT_FILES.contains(&name)
+        })
+        .filter_map(|f| {
+            packages
+                .iter()
+                .filter(|p| f.starts_with(&p.dir))
+                .max_by_key(|p| p.dir.components().count())
+        })
+        .map(|p| p.dir.clone())
+        .collect();
+    loop {
+        let names: BTreeSet<&String> = packages
+            .iter()
+            .filter(|p| affected.contains(&p.dir))
+            .map(|p| &p.name)
+            .collect();
+        let dependents: Vec<PathBuf> = packages
+            .iter()
+            .filter(|p| !affected.contains(&p.dir) && p.depends.iter().any(|d| names.contains(d)))
+            .map(|p| p.dir.clone())
+            .collect();
+        if dependents.is_empty() {
+            return affected;
+        }
+        affected.extend(dependents);
+    }
+}
+
+#[cfg(test)]
+#[path = "julia_test.rs"]
+mod tests;
@@ -0,0 +1,58 @@
+use super::*;
+
+const CORE: &str = r#"name = "Core"
+uuid = "6a9d8a3e-5c1b-4f7e-9d1a-0b2c3d4e5f60"
+version = "0.1.0"
+
+[deps]
+LinearAlgebra = "37e2e46d-f89d-539d-b4ee-838fcccc9c8e"
+
+[extras]
+Test = "8dfed614-e22c-5e08-85e1-65c5234f0b40"
+"#;
+
+const SOLVERS: &str = r#"name = "Solvers"
+uuid = "0f1e2d3c-4b5a-6978-8a9b-acbdcedf0123"
+
+[deps]
+Core = "6a9d8a3e-5c1b-4f7e-9d1a-0b2c3d4e5f60"
+"#;
+
+#[test]
+fn packages_are_parsed_from_project_toml() {
+    let core = parse_package(Path::new("packages/Core"), CORE).unwrap();
+    assert_eq!(core.name, "Core");
+    assert_eq!(core.depends.iter().collect::<Vec<_>>(), ["LinearAlgebra", "Test"]);
+    assert!(parse_package(Path::new("docs"), "[deps]\nDocumenter = \"e30172f5\"\n").is_none());
+}
+
+#[test]
+fn affected_packages_include_dependents() {
+    let packages = [
+        parse_package(Path::new("packages/Core"), CORE).unwrap(),
+        parse_package(Path::new("packages/Solvers"), SOLVERS).unwrap(),
+    ];
+    assert_eq!(
+        affected_packages(&packages, &[PathBuf::from("packages/Core/src/Core.jl")])
+            .into_iter()
+            .collect::<Vec<_>>(),
+        [PathBuf::from("packages/Core"), PathBuf::from("packages/Solvers")]
+    );
+    assert_eq!(
+        affected_packages(&packages, &[PathBuf::from("packages/Solvers/Project.toml")])
+            .into_iter()
+            .collect::<Vec<_>>(),
+        [PathBuf::from("packages/Solvers")]
+    );
+    assert!(affected_packages(&packages, &[PathBuf::from("packages/Core/README.md")]).is_empty());
+}
+
+#[test]
+fn detection_needs_a_project_at_the_root() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("bench")).unwrap();
+    std::fs::write(dir.path().join("bench/Project.toml"), CORE).unwrap();
+    assert!(!JuliaBackend.detect(dir.path()));
+    std::fs::write(dir.path().join("Project.toml"), CORE).unwrap();
+    assert!(JuliaBackend.detect(dir.path()));
+}
@@ -0,0 +1,145 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use regex::Regex;
+
+use super::{Backend, Target, TargetMeta};
+use crate::exec::Invocation;
+use crate::report::Skip;
+
+/// A recipe header: `build *dirs:` or `@lint:`, but not an assignment (`version := "1"`).
+static RECIPE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^@?([A-Za-z_][A-Za-z0-9_-]*)([^:]*?)\s*:(?:[^=]|$)").unwrap());
+
+/// Names just looks for, in its order.
+const FILE_NAMES: &[&str] = &["justfile", "Justfile", ".justfile"];
+
+/// A recipe in the justfile.
+#[derive(Debug, Clone, PartialEq)]
+struct Recipe {
+    name: String,
+    /// Whether it takes parameters, so the affected directories can be passed to it.
+    takes_args: bool,
+}
+
+pub struct JustBackend;
+
+impl JustBackend {
+    fn recipes(repo_root: &Path) -> Vec<Recipe> {
+        FILE_NAMES
+            .iter()
+            .find_map(|name| std::fs::read_to_string(repo_root.join(name)).ok())
+            .map(|text| parse_recipes(&text))
+            .unwrap_or_default()
+    }
+
+    /// `just <recipe> <args>`, or a [`Skip`] when the justfile has no such recipe.
+    fn recipe(repo_root: &Path, name: &str, args: Vec<String>) -> Result<Vec<Invocation>> {
+        let Some(recipe) = Self::recipes(repo_root).into_iter().find(|r| r.name == name) else {
+            return Err(Skip::NotConfigured {
+                detail: format!("no {name} recipe in the justfile"),
+            }
+            .into());
+        };
+        let invocation = Invocation::new("just", repo_root).arg(name);
+        Ok(vec![if recipe.takes_args {
+            invocation.args(args)
+        } else {
+            invocation
+        }])
+    }
+}
+
+impl Backend for JustBackend {
+    fn name(&self) -> &str {
+        "just"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        FILE_NAMES.iter().any(|name| dir.join(name).is_file())
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        affected_dirs(changed_files)
+            .into_iter()
+            .map(|dir| self.resolve_target(repo_root, repo_root.join(dir)))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_string_lossy();
+        let rel = rel.replace('\\', "/");
+        let meta = TargetMeta {
+            kind: Some("directory".to_string()),
+            ..TargetMeta::default()
+        };
+        Target {
+            label: if rel.is_empty() { ".".to_string() } else { rel },
+            dir,
+            meta,
+            files: Vec::new(),
+        }
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Self::recipe(repo_root, "build", labels(targets))
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Self::recipe(repo_root, "test", labels(targets))
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Self::recipe(repo_root, "lint", labels(targets))
+    }
+
+    fn task(&self, repo_root: &Path, task: &str, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Self::recipe(repo_root, task, labels(targets))
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let files: Vec<String> = changed_files
+            .iter()
+            .filter(|f| repo_root.join(f).is_file())
+            .map(|f| f.to_string_lossy().replace('\\', "/"))
+            .collect();
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        Self::recipe(repo_root, "fmt", files)
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new("just", repo_root).arg("--version")]
+    }
+}
+
+fn labels(targets: &[Target]) -> Vec<String> {
+    targets.iter().map(|t| t.label.clone()).collect()
+}
+
+/// Recipes defined in a justfile, in order.
+fn parse_recipes(justfile: &str) -> Vec<Recipe> {
+    justfile
+        .lines()
+        .filter_map(|line| RECIPE.captures(line))
+        .map(|c| Recipe {
+            name: c[1].to_string(),
+            takes_args: !c[2].trim().is_empty(),
+        })
+        .collect()
+}
+
+/// The directories holding changed files.
+fn affected_dirs(changed_files: &[PathBuf]) -> BTreeSet<PathBuf> {
+    changed_files
+        .iter()
+        .map(|f| f.parent().unwrap_or(Path::new("")).to_path_buf())
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "just_test.rs"]
+mod tests;
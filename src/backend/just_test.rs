@@ -0,0 +1,49 @@
+use super::*;
+
+const JUSTFILE: &str = r#"set shell := ["bash", "-cu"]
+version := "1.2.0"
+alias t := test
+
+# Build the given directories.
+build *dirs:
+    ./scripts/build.sh {{dirs}}
+
+[private]
+@lint:
+    ./scripts/lint.sh
+
+test dirs='.': build
+    go test {{dirs}}
+"#;
+
+#[test]
+fn recipes_and_whether_they_take_arguments_are_parsed() {
+    let recipes = parse_recipes(JUSTFILE);
+    let names: Vec<(&str, bool)> = recipes.iter().map(|r| (r.name.as_str(), r.takes_args)).collect();
+    assert_eq!(names, [("build", true), ("lint", false), ("test", true)]);
+}
+
+#[test]
+fn affected_directories_are_passed_to_recipes_that_take_arguments() {
+    let repo = tempfile::tempdir().unwrap();
+    std::fs::write(repo.path().join("justfile"), JUSTFILE).unwrap();
+    let changed = vec![
+        PathBuf::from("services/api/main.go"),
+        PathBuf::from("services/api/handler.go"),
+        PathBuf::from("README.md"),
+    ];
+    let targets = JustBackend.affected_targets(repo.path(), "main", &changed);
+    let plan = JustBackend.build(repo.path(), &targets).unwrap();
+    assert_eq!(plan[0].args, ["build", ".", "services/api"]);
+    let plan = JustBackend.lint(repo.path(), &targets).unwrap();
+    assert_eq!(plan[0].args, ["lint"]);
+}
+
+#[test]
+fn missing_recipes_skip_the_step() {
+    let repo = tempfile::tempdir().unwrap();
+    std::fs::write(repo.path().join("justfile"), JUSTFILE).unwrap();
+    std::fs::write(repo.path().join("a.go"), "").unwrap();
+    let err = JustBackend.fmt(repo.path(), &[PathBuf::from("a.go")]).unwrap_err();
+    assert!(matches!(Skip::find(&err), Some(Skip::NotConfigured { .. })));
+}
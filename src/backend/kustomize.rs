@@ -0,0 +1,266 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use regex::Regex;
+
+use super::{Backend, Target, TargetMeta, which_exists};
+use crate::exec::Invocation;
+use crate::hooks::shell_quote;
+use crate::{git, paths, state};
+
+/// An entry of a list: "- ../../base" or "- path: patch.yaml".
+static LIST_ENTRY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^\s*-\s*(?:path:\s*)?["']?([^"'#\s]+)["']?\s*(?:#.*)?$"#).unwrap());
+
+/// Names kustomize reads a kustomization from.
+const FILE_NAMES: &[&str] = &["kustomization.yaml", "kustomization.yml", "Kustomization"];
+
+/// Fields listing the files and directories a kustomization is built from.
+const REFERENCES: &[&str] = &[
+    "resources",
+    "bases",
+    "components",
+    "patches",
+    "patchesStrategicMerge",
+    "crds",
+];
+
+/// A directory with a kustomization file.
+#[derive(Debug, Clone, PartialEq)]
+struct Kustomization {
+    /// Directory relative to the repo root.
+    dir: PathBuf,
+    /// Local files and directories it references, relative to the repo root.
+    references: BTreeSet<PathBuf>,
+    /// Whether it is a `kind: Component`, which only builds as part of another kustomization.
+    component: bool,
+}
+
+pub struct KustomizeBackend;
+
+impl KustomizeBackend {
+    fn kustomizations(repo_root: &Path) -> Result<Vec<Kustomization>> {
+        let mut found = Vec::new();
+        for file in git::find_files_named(repo_root, FILE_NAMES)? {
+            let text = std::fs::read_to_string(repo_root.join(&file)).unwrap_or_default();
+            found.push(parse_kustomization(file.parent().unwrap_or(Path::new("")), &text));
+        }
+        found.sort_by(|a, b| a.dir.cmp(&b.dir));
+        found.dedup_by(|a, b| a.dir == b.dir);
+        Ok(found)
+    }
+
+    /// `kustomize build`, or `kubectl kustomize` when only kubectl is installed.
+    fn build_command() -> &'static str {
+        if !which_exists("kustomize") && which_exists("kubectl") {
+            "kubectl kustomize"
+        } else {
+            "kustomize build"
+        }
+    }
+}
+
+impl Backend for KustomizeBackend {
+    fn name(&self) -> &str {
+        "kustomize"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        // A kustomization at the root, or the base of the usual base-and-overlays layout.
+        FILE_NAMES
+            .iter()
+            .any(|name| dir.join(name).is_file() || dir.join("base").join(name).is_file())
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let kustomizations = match Self::kustomizations(repo_root) {
+            Ok(found) => found,
+            Err(e) => {
+                eprintln!("kit: failed to find kustomizations ({e:#})");
+                return vec![];
+            }
+        };
+        affected_kustomizations(&kustomizations, changed_files)
+            .iter()
+            .filter_map(|dir| kustomizations.iter().find(|k| &k.dir == dir))
+            .map(|k| kustomization_target(repo_root, &kustomizations, k))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_path_buf();
+        let kustomizations = Self::kustomizations(repo_root).unwrap_or_default();
+        match kustomizations.iter().find(|k| k.dir == rel) {
+            Some(k) => kustomization_target(repo_root, &kustomizations, k),
+            None => Target {
+                label: label(&rel),
+                dir,
+                meta: TargetMeta::default(),
+                files: Vec::new(),
+            },
+        }
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let build = Self::build_command();
+        Ok(overlays(targets)
+            .map(|t| {
+                Invocation::new("sh", repo_root).args([
+                    "-c".to_string(),
+                    format!("{build} {} >/dev/null", shell_quote(&t.label)),
+                ])
+            })
+            .collect())
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let build = Self::build_command();
+        let out = state::dir(repo_root, "kustomize")?;
+        Ok(overlays(targets)
+            .map(|t| {
+                let manifest = out.join(format!("{}.yaml", t.label.replace(['/', '.'], "_")));
+                Invocation::new("sh", repo_root).args(["-c".to_string(), validate_script(build, &t.label, &manifest)])
+            })
+            .collect())
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![
+            Invocation::new("yamllint", repo_root)
+                .arg("--strict")
+                .args(targets.iter().map(|t| t.label.clone())),
+        ])
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let kustomizations = Self::kustomizations(repo_root)?;
+        let files: Vec<String> = changed_files
+            .iter()
+            .filter(|f| f.extension().is_some_and(|e| e == "yaml" || e == "yml") && repo_root.join(f).is_file())
+            .filter(|f| {
+                kustomizations
+                    .iter()
+                    .any(|k| f.starts_with(&k.dir) || k.references.contains(*f))
+            })
+            .map(|f| f.to_string_lossy().replace('\\', "/"))
+            .collect();
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![Invocation::new("yamlfmt", repo_root).args(files)])
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new("kustomize", repo_root).arg("version")]
+    }
+}
+
+fn label(dir: &Path) -> String {
+    let rel = dir.to_string_lossy().replace('\\', "/");
+    if rel.is_empty() { ".".to_string() } else { rel }
+}
+
+/// Targets for overlays: kustomizations no other kustomization builds on, which are what gets
+/// applied to a cluster.
+fn overlays(targets: &[Target]) -> impl Iterator<Item = &Target> {
+    targets.iter().filter(|t| t.meta.kind.as_deref() == Some("overlay"))
+}
+
+fn kustomization_target(repo_root: &Path, all: &[Kustomization], kustomization: &Kustomization) -> Target {
+    let referenced = all.iter().any(|k| k.references.contains(&kustomization.dir));
+    let kind = if kustomization.component {
+        "component"
+    } else if referenced {
+        "base"
+    } else {
+        "overlay"
+    };
+    let meta = TargetMeta {
+        kind: Some(kind.to_string()),
+        language: Some("yaml".to_string()),
+        is_test: None,
+        size: None,
+    };
+    Target {
+        label: label(&kustomization.dir),
+        dir: repo_root.join(&kustomization.dir),
+        meta,
+        files: Vec::new(),
+    }
+}
+
+fn parse_kustomization(dir: &Path, text: &str) -> Kustomization {
+    let mut references = BTreeSet::new();
+    let mut in_list = false;
+    let mut indent = None;
+    for line in text.lines() {
+        if !line.starts_with([' ', '\t', '-']) && !line.trim().is_empty() {
+            let key = line.trim_end().strip_suffix(':').unwrap_or("");
+            in_list = REFERENCES.contains(&key);
+            indent = None;
+            continue;
+        }
+        let Some(c) = LIST_ENTRY.captures(line).filter(|_| in_list) else {
+            continue;
+        };
+        let depth = line.len() - line.trim_start().len();
+        if *indent.get_or_insert(depth) != depth || c[1].contains("://") || c[1].starts_with("github.com/") {
+            continue;
+        }
+        references.extend(paths::normalize(&dir.join(&c[1])));
+    }
+    Kustomization {
+        dir: dir.to_path_buf(),
+        references,
+        component: text.lines().any(|l| l.trim() == "kind: Component"),
+    }
+}
+
+/// Kustomizations containing or referencing a changed file, plus every kustomization that builds on
+/// them, directly or not.
+fn affected_kustomizations(kustomizations: &[Kustomization], changed_files: &[PathBuf]) -> BTreeSet<PathBuf> {
+    let mut affected: BTreeSet<PathBuf> = BTreeSet::new();
+    for file in changed_files {
+        let owner = kustomizations
+            .iter()
+            .filter(|k| file.starts_with(&k.dir))
+            .max_by_key(|k| k.dir.components().count());
+        affected.extend(owner.map(|k| k.dir.clone()));
+        affected.extend(
+            kustomizations
+                .iter()
+                .filter(|k| k.references.contains(file))
+                .map(|k| k.dir.clone()),
+        );
+    }
+    loop {
+        let dependents: Vec<PathBuf> = kustomizations
+            .iter()
+            .filter(|k| !affected.contains(&k.dir) && k.references.iter().any(|r| affected.contains(r)))
+            .map(|k| k.dir.clone())
+            .collect();
+        if dependents.is_empty() {
+            return affected;
+        }
+        affected.extend(dependents);
+    }
+}
+
+/// Build an overlay to `manifest` and validate it with kubeconform. The output goes through a file
+/// rather than a pipe so a failing build fails the step.
+fn validate_script(build: &str, overlay: &str, manifest: &Path) -> String {
+    let manifest = shell_quote(&manifest.to_string_lossy());
+    format!(
+        "{build} {} > {manifest} && kubeconform -strict -summary {manifest}",
+        shell_quote(overlay)
+    )
+}
+
+#[cfg(test)]
+#[path = "kustomize_test.rs"]
+mod tests;
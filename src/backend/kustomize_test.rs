@@ -0,0 +1,98 @@
+use super::*;
+
+const OVERLAY: &str = "apiVersion: kustomize.config.k8s.io/v1beta1
+kind: Kustomization
+resources:
+  - ../../base
+  - ingress.yaml
+  - https://github.com/acme/platform//monitoring?ref=v1.2.0
+components:
+  - ../../components/tracing
+patches:
+  - path: replicas.yaml
+    target:
+      kind: Deployment
+images:
+  - name: api
+    newTag: v2
+";
+
+fn kustomization(dir: &str, references: &[&str]) -> Kustomization {
+    Kustomization {
+        dir: PathBuf::from(dir),
+        references: references.iter().map(PathBuf::from).collect(),
+        component: false,
+    }
+}
+
+fn all() -> Vec<Kustomization> {
+    vec![
+        kustomization("deploy/base", &["deploy/base/deployment.yaml"]),
+        kustomization("deploy/overlays/prod", &["deploy/base"]),
+        kustomization("deploy/overlays/staging", &["deploy/base", "shared/limits.yaml"]),
+        kustomization("deploy/overlays/dev", &[]),
+    ]
+}
+
+#[test]
+fn local_references_are_resolved_from_the_kustomization() {
+    let parsed = parse_kustomization(Path::new("deploy/overlays/prod"), OVERLAY);
+    let references: Vec<&str> = parsed.references.iter().map(|p| p.to_str().unwrap()).collect();
+    assert_eq!(
+        references,
+        [
+            "deploy/base",
+            "deploy/components/tracing",
+            "deploy/overlays/prod/ingress.yaml",
+            "deploy/overlays/prod/replicas.yaml",
+        ]
+    );
+    assert!(!parsed.component);
+    assert!(parse_kustomization(Path::new("c"), "kind: Component\n").component);
+}
+
+#[test]
+fn base_changes_affect_the_overlays_built_on_it() {
+    let changed = vec![PathBuf::from("deploy/base/deployment.yaml")];
+    let affected: Vec<PathBuf> = affected_kustomizations(&all(), &changed).into_iter().collect();
+    assert_eq!(
+        affected,
+        ["deploy/base", "deploy/overlays/prod", "deploy/overlays/staging"].map(PathBuf::from)
+    );
+}
+
+#[test]
+fn files_referenced_from_outside_the_tree_affect_their_kustomization() {
+    let changed = vec![PathBuf::from("shared/limits.yaml"), PathBuf::from("README.md")];
+    let affected: Vec<PathBuf> = affected_kustomizations(&all(), &changed).into_iter().collect();
+    assert_eq!(affected, [PathBuf::from("deploy/overlays/staging")]);
+}
+
+#[test]
+fn only_overlays_are_built() {
+    let repo = Path::new("/repo");
+    let all = all();
+    let targets: Vec<Target> = all.iter().map(|k| kustomization_target(repo, &all, k)).collect();
+    let kinds: Vec<&str> = targets.iter().map(|t| t.meta.kind.as_deref().unwrap()).collect();
+    assert_eq!(kinds, ["base", "overlay", "overlay", "overlay"]);
+    assert_eq!(overlays(&targets).count(), 3);
+}
+
+#[test]
+fn build_failures_are_not_hidden_by_a_pipe() {
+    assert_eq!(
+        validate_script("kustomize build", "deploy/overlays/prod", Path::new("/out/prod.yaml")),
+        "kustomize build 'deploy/overlays/prod' > '/out/prod.yaml' && kubeconform -strict -summary '/out/prod.yaml'"
+    );
+}
+
+#[test]
+fn detection_needs_a_kustomization_at_the_root_or_in_base() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("deploy/prod")).unwrap();
+    std::fs::write(dir.path().join("deploy/prod/kustomization.yaml"), OVERLAY).unwrap();
+    assert!(!KustomizeBackend.detect(dir.path()));
+    std::fs::create_dir(dir.path().join("base")).unwrap();
+    std::fs::write(dir.path().join("base/kustomization.yml"), OVERLAY).unwrap();
+    assert!(KustomizeBackend.detect(dir.path()));
+}
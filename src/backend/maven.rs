@@ -0,0 +1,229 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use regex::Regex;
+
+use super::{Backend, Target, TargetMeta};
+use crate::exec::Invocation;
+use crate::report::Skip;
+
+static MODULE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<module>\s*([^<]+?)\s*</module>").unwrap());
+static COMMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+
+pub struct MavenBackend;
+
+impl MavenBackend {
+    /// The Maven wrapper if the repo has one, otherwise `mvn` from PATH.
+    fn mvn(repo_root: &Path) -> String {
+        let wrapper = repo_root.join("mvnw");
+        if wrapper.is_file() {
+            wrapper.to_string_lossy().into_owned()
+        } else {
+            "mvn".to_string()
+        }
+    }
+
+    /// Directories of every module in the reactor, relative to the repo root, found by following
+    /// `<module>` entries from the root pom. The root itself is included as an empty path.
+    fn modules(repo_root: &Path) -> Vec<PathBuf> {
+        let mut modules = vec![PathBuf::new()];
+        let mut i = 0;
+        while i < modules.len() {
+            let pom = repo_root.join(&modules[i]).join("pom.xml");
+            let text = std::fs::read_to_string(pom).unwrap_or_default();
+            for module in parse_modules(&text) {
+                let dir = normalize(&modules[i].join(module));
+                if !modules.contains(&dir) {
+                    modules.push(dir);
+                }
+            }
+            i += 1;
+        }
+        modules
+    }
+
+    /// Whether the root pom configures a plugin, e.g. "spotless-maven-plugin".
+    fn has_plugin(repo_root: &Path, artifact: &str) -> bool {
+        std::fs::read_to_string(repo_root.join("pom.xml")).is_ok_and(|pom| pom.contains(artifact))
+    }
+
+    /// `mvn` running `goals` on the targets' modules, or on the whole reactor if the root module is
+    /// among them. `also_make` adds the modules they depend on.
+    fn invocation(repo_root: &Path, targets: &[Target], also_make: bool, goals: &[&str]) -> Invocation {
+        let mut invocation = Invocation::new(Self::mvn(repo_root), repo_root).arg("-B");
+        if !targets.iter().any(|t| t.label == ".") {
+            let modules: Vec<&str> = targets.iter().map(|t| t.label.as_str()).collect();
+            invocation = invocation.args(["-pl".to_string(), modules.join(",")]);
+            if also_make {
+                invocation = invocation.arg("-am");
+            }
+        }
+        invocation.args(goals.iter().copied())
+    }
+}
+
+impl Backend for MavenBackend {
+    fn name(&self) -> &str {
+        "maven"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join("pom.xml").exists()
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let modules = Self::modules(repo_root);
+        let mut affected: BTreeSet<&Path> = BTreeSet::new();
+        for file in changed_files {
+            if let Some(module) = owning_module(&modules, file) {
+                affected.insert(module);
+            }
+        }
+        affected
+            .into_iter()
+            .map(|m| self.resolve_target(repo_root, repo_root.join(m)))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_string_lossy();
+        let rel = rel.replace('\\', "/");
+        let label = if rel.is_empty() { ".".to_string() } else { rel };
+        let meta = TargetMeta {
+            kind: Some("module".to_string()),
+            language: Some("java".to_string()),
+            is_test: Some(dir.join("src/test").is_dir()),
+            size: None,
+        };
+        Target {
+            label,
+            dir,
+            meta,
+            files: Vec::new(),
+        }
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![Self::invocation(
+            repo_root,
+            targets,
+            true,
+            &["package", "-DskipTests"],
+        )])
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut invocation = Self::invocation(repo_root, targets, true, &["test"]);
+        // Named test classes run alone; modules built only as dependencies have none of them.
+        let files: Vec<&PathBuf> = targets.iter().flat_map(|t| &t.files).collect();
+        if !files.is_empty() && targets.iter().all(|t| !t.files.is_empty()) {
+            let classes: Vec<&str> = files
+                .iter()
+                .filter(|f| f.extension().is_some_and(|e| e == "java"))
+                .filter_map(|f| f.file_stem()?.to_str())
+                .collect();
+            if classes.len() == files.len() {
+                invocation = invocation.args([
+                    format!("-Dtest={}", classes.join(",")),
+                    "-Dsurefire.failIfNoSpecifiedTests=false".to_string(),
+                ]);
+            }
+        }
+        Ok(vec![invocation])
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut goals = Vec::new();
+        if Self::has_plugin(repo_root, "spotless-maven-plugin") {
+            goals.push("spotless:check");
+        }
+        if Self::has_plugin(repo_root, "maven-checkstyle-plugin") {
+            goals.push("checkstyle:check");
+        }
+        if goals.is_empty() {
+            return Err(Skip::NotConfigured {
+                detail: "neither spotless nor checkstyle is configured in pom.xml".to_string(),
+            }
+            .into());
+        }
+        Ok(vec![Self::invocation(repo_root, targets, false, &goals)])
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        if !Self::has_plugin(repo_root, "spotless-maven-plugin") {
+            return Err(Skip::NotConfigured {
+                detail: "spotless is not configured in pom.xml".to_string(),
+            }
+            .into());
+        }
+        let targets = self.affected_targets(repo_root, "", changed_files);
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![Self::invocation(repo_root, &targets, false, &["spotless:apply"])])
+    }
+
+    fn shuffle_args(&self, seed: u64) -> Option<Vec<String>> {
+        // Surefire shuffles the order of test classes, not of the tests within a class.
+        Some(vec![
+            "-Dsurefire.runOrder=random".to_string(),
+            format!("-Dsurefire.runOrder.random.seed={seed}"),
+        ])
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new(Self::mvn(repo_root), repo_root).arg("--version")]
+    }
+}
+
+/// Module paths listed in a pom, ignoring commented-out entries.
+fn parse_modules(pom: &str) -> Vec<String> {
+    let pom = COMMENT.replace_all(pom, "");
+    MODULE.captures_iter(&pom).map(|c| c[1].to_string()).collect()
+}
+
+/// The deepest module containing a changed file. Files owned by the root module only count if they
+/// are part of the build (the pom, `.mvn/`, or sources), so a README change builds nothing.
+fn owning_module<'a>(modules: &'a [PathBuf], file: &Path) -> Option<&'a Path> {
+    let module = modules
+        .iter()
+        .filter(|m| file.starts_with(m))
+        .max_by_key(|m| m.components().count())?;
+    if module.as_os_str().is_empty() {
+        let part_of_build = file == Path::new("pom.xml") || file.starts_with(".mvn") || file.starts_with("src");
+        if !part_of_build {
+            return None;
+        }
+    }
+    Some(module)
+}
+
+/// Resolve `.` and `..` in a relative path without touching the filesystem.
+pub(super) fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+#[path = "maven_test.rs"]
+mod tests;
@@ -0,0 +1,67 @@
+use super::*;
+use tempfile::TempDir;
+
+fn write(root: &Path, file: &str, text: &str) {
+    let path = root.join(file);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+fn pom(modules: &[&str]) -> String {
+    let modules: String = modules.iter().map(|m| format!("<module>{m}</module>")).collect();
+    format!("<project><modules>{modules}<!-- <module>old</module> --></modules></project>")
+}
+
+#[test]
+fn affected_targets_map_files_to_the_deepest_module() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    write(root, "pom.xml", &pom(&["core", "services"]));
+    write(root, "services/pom.xml", &pom(&["api", "../tools"]));
+    let changed = [
+        PathBuf::from("services/api/src/main/java/Api.java"),
+        PathBuf::from("tools/src/Tool.java"),
+        PathBuf::from("core/README.md"),
+        PathBuf::from("README.md"),
+    ];
+    let targets = MavenBackend.affected_targets(root, "main", &changed);
+    let labels: Vec<&str> = targets.iter().map(|t| t.label.as_str()).collect();
+    assert_eq!(labels, ["core", "services/api", "tools"]);
+
+    let targets = MavenBackend.affected_targets(root, "main", &[PathBuf::from("pom.xml")]);
+    assert_eq!(targets[0].label, ".");
+}
+
+#[test]
+fn build_and_test_use_the_project_list() {
+    let root = Path::new("/repo");
+    let targets = [
+        MavenBackend.resolve_target(root, root.join("core")),
+        MavenBackend.resolve_target(root, root.join("services/api")),
+    ];
+    assert_eq!(
+        MavenBackend.test(root, &targets).unwrap(),
+        [Invocation::new("mvn", root).args(["-B", "-pl", "core,services/api", "-am", "test"])]
+    );
+
+    let mut narrowed = targets[0].clone();
+    narrowed.files = vec![PathBuf::from("core/src/test/java/CoreTest.java")];
+    assert_eq!(
+        MavenBackend.test(root, &[narrowed]).unwrap(),
+        [Invocation::new("mvn", root).args([
+            "-B",
+            "-pl",
+            "core",
+            "-am",
+            "test",
+            "-Dtest=CoreTest",
+            "-Dsurefire.failIfNoSpecifiedTests=false"
+        ])]
+    );
+
+    let whole = MavenBackend.resolve_target(root, root.to_path_buf());
+    assert_eq!(
+        MavenBackend.build(root, &[whole]).unwrap(),
+        [Invocation::new("mvn", root).args(["-B", "package", "-DskipTests"])]
+    );
+}
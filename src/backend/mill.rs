@@ -0,0 +1,345 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use regex::Regex;
+
+use super::{Backend, Target, TargetMeta};
+use crate::exec::Invocation;
+use crate::report::Skip;
+
+/// A module definition: `object core extends ScalaModule with ScalafmtModule`, up to its body.
+static OBJECT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\bobject\s+(`[^`]+`|\w+)\s+extends\s+([^{}\n]*(?:\n\s*with\b[^{}\n]*)*)").unwrap());
+/// The modules a module depends on: `def moduleDeps = Seq(core, util.jvm)`.
+static MODULE_DEPS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"moduleDeps\s*=[^\n]*?Seq\(([^)]*)\)").unwrap());
+/// Test modules nested in the module they test: `object test extends ScalaTests`.
+static TESTS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"Tests\b|TestModule").unwrap());
+static COMMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?m)//.*$").unwrap());
+
+/// Build files, with Mill 0.12's name first.
+const BUILD_FILES: &[&str] = &["build.mill", "build.sc"];
+const SCALA_EXTENSIONS: &[&str] = &["scala", "sc", "mill"];
+
+/// A module of the Mill build, other than test modules.
+#[derive(Debug, Clone, PartialEq)]
+struct Module {
+    /// Module path, as in `core.jvm.compile`.
+    name: String,
+    /// Directory relative to the repo root, which Mill derives from the module path.
+    dir: PathBuf,
+    /// Names of the modules it depends on.
+    depends: BTreeSet<String>,
+    /// Whether it has a test module.
+    tests: bool,
+    /// Whether it mixes in `ScalafmtModule`, so it has a `checkFormat` command.
+    scalafmt: bool,
+}
+
+pub struct MillBackend;
+
+impl MillBackend {
+    /// `./mill` when the repo has the bootstrap script, otherwise `mill`.
+    fn mill(repo_root: &Path) -> String {
+        let script = repo_root.join("mill");
+        if script.is_file() {
+            script.to_string_lossy().into_owned()
+        } else {
+            "mill".to_string()
+        }
+    }
+
+    fn modules(repo_root: &Path) -> Vec<Module> {
+        let build = BUILD_FILES
+            .iter()
+            .find_map(|name| std::fs::read_to_string(repo_root.join(name)).ok())
+            .unwrap_or_default();
+        parse_modules(&build)
+    }
+
+    /// One Mill invocation running `task` in each target's module and its submodules.
+    fn run(repo_root: &Path, targets: &[Target], task: &str) -> Vec<Invocation> {
+        if targets.is_empty() {
+            return vec![];
+        }
+        let modules = Self::modules(repo_root);
+        let names: Vec<&str> = targets.iter().map(|t| t.label.as_str()).collect();
+        let everything = names.contains(&".") || modules.iter().all(|m| names.contains(&m.name.as_str()));
+        vec![Invocation::new(Self::mill(repo_root), repo_root).arg(selector(&names, everything, task))]
+    }
+}
+
+impl Backend for MillBackend {
+    fn name(&self) -> &str {
+        "mill"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        BUILD_FILES.iter().any(|name| dir.join(name).is_file())
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let modules = Self::modules(repo_root);
+        affected_modules(&modules, changed_files)
+            .into_iter()
+            .filter_map(|name| modules.iter().find(|m| m.name == name))
+            .map(|m| module_target(repo_root, m))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_path_buf();
+        let modules = Self::modules(repo_root);
+        match owning_module(&modules, &rel) {
+            Some(module) => module_target(repo_root, module),
+            // Outside every module, such as the repo root: the whole build.
+            None => Target {
+                label: ".".to_string(),
+                dir,
+                meta: TargetMeta {
+                    kind: Some("module".to_string()),
+                    ..TargetMeta::default()
+                },
+                files: Vec::new(),
+            },
+        }
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Ok(Self::run(repo_root, targets, "compile"))
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let targets: Vec<Target> = targets
+            .iter()
+            .filter(|t| t.meta.is_test != Some(false))
+            .cloned()
+            .collect();
+        Ok(Self::run(repo_root, &targets, "test"))
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let modules = Self::modules(repo_root);
+        let formatted: Vec<Target> = targets
+            .iter()
+            .filter(|t| t.label == "." || modules.iter().any(|m| m.name == t.label && m.scalafmt))
+            .cloned()
+            .collect();
+        if formatted.is_empty() {
+            return Err(Skip::NotConfigured {
+                detail: "no affected Mill module extends ScalafmtModule".to_string(),
+            }
+            .into());
+        }
+        Ok(Self::run(repo_root, &formatted, "checkFormat"))
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let files: Vec<String> = changed_files
+            .iter()
+            .filter(|f| {
+                f.extension().is_some_and(|e| SCALA_EXTENSIONS.iter().any(|x| e == *x)) && repo_root.join(f).is_file()
+            })
+            .map(|f| f.to_string_lossy().replace('\\', "/"))
+            .collect();
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        if !repo_root.join(".scalafmt.conf").is_file() {
+            return Err(Skip::NotConfigured {
+                detail: "no .scalafmt.conf at the repo root".to_string(),
+            }
+            .into());
+        }
+        Ok(vec![
+            Invocation::new("scalafmt", repo_root)
+                .arg("--non-interactive")
+                .args(files),
+        ])
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new(Self::mill(repo_root), repo_root).arg("--version")]
+    }
+
+    fn smoke(&self, repo_root: &Path) -> Vec<Invocation> {
+        // Compiles the build file, which is where most breakage shows.
+        vec![Invocation::new(Self::mill(repo_root), repo_root).args(["resolve", "_"])]
+    }
+}
+
+fn module_target(repo_root: &Path, module: &Module) -> Target {
+    Target {
+        label: module.name.clone(),
+        dir: repo_root.join(&module.dir),
+        meta: TargetMeta {
+            kind: Some("module".to_string()),
+            language: None,
+            is_test: Some(module.tests),
+            size: None,
+        },
+        files: Vec::new(),
+    }
+}
+
+/// The Mill selector running `task` in the named modules and their submodules (`core.__.compile`,
+/// `{core,util.jvm}.__.compile`), or in every module (`__.compile`).
+fn selector(names: &[&str], everything: bool, task: &str) -> String {
+    match names {
+        _ if everything => format!("__.{task}"),
+        [name] => format!("{name}.__.{task}"),
+        names => format!("{{{}}}.__.{task}", names.join(",")),
+    }
+}
+
+/// The modules the build file defines, nested objects extending a `*Module` named by their path.
+/// Test modules aren't listed; they mark the module they are in as having tests.
+fn parse_modules(build: &str) -> Vec<Module> {
+    let text = COMMENT.replace_all(build, "");
+    let mut modules: Vec<(Module, bool)> = Vec::new();
+    // For each open brace, the module whose body it opens, if any, and where the body starts.
+    let mut open: Vec<Option<(usize, usize)>> = Vec::new();
+    let mut objects = OBJECT.captures_iter(&text).peekable();
+    let mut pending = None;
+    // Bodies of the modules seen so far, for leaving them out of the body enclosing them.
+    let mut closed: Vec<(usize, usize)> = Vec::new();
+    for (i, c) in text.char_indices() {
+        if let Some(object) = objects.next_if(|o| o.get(0).unwrap().start() == i) {
+            let clause = &object[2];
+            pending = None;
+            if !clause.contains("Module") && !TESTS.is_match(clause) {
+                continue;
+            }
+            let parent = open.iter().rev().find_map(|o| o.map(|(m, _)| m));
+            let segment = object[1].trim_matches('`');
+            let name = match parent {
+                Some(p) => format!("{}.{segment}", modules[p].0.name),
+                None => segment.to_string(),
+            };
+            let test = TESTS.is_match(clause);
+            if test && let Some(p) = parent {
+                modules[p].0.tests = true;
+            }
+            modules.push((
+                Module {
+                    dir: name.split('.').collect(),
+                    name,
+                    depends: BTreeSet::new(),
+                    tests: false,
+                    scalafmt: clause.contains("ScalafmtModule"),
+                },
+                test,
+            ));
+            let end = object.get(0).unwrap().end();
+            if text[end..].trim_start().starts_with('{') {
+                pending = Some(modules.len() - 1);
+            }
+        }
+        match c {
+            '{' => open.push(pending.take().map(|m| (m, i))),
+            '}' => {
+                if let Some(Some((m, start))) = open.pop() {
+                    // The body without those of the modules nested in it, which have their own deps.
+                    let mut body = String::new();
+                    let mut from = start;
+                    let mut nested: Vec<(usize, usize)> = closed.iter().filter(|(s, _)| *s > start).copied().collect();
+                    nested.sort();
+                    for (nested_start, nested_end) in nested {
+                        if nested_start >= from {
+                            body.push_str(&text[from..nested_start]);
+                            from = nested_end;
+                        }
+                    }
+                    body.push_str(&text[from..i]);
+                    let deps = MODULE_DEPS
+                        .captures_iter(&body)
+                        .flat_map(|c| c[1].split(',').map(|d| d.trim().to_string()).collect::<Vec<_>>())
+                        .filter(|d| !d.is_empty());
+                    modules[m].0.depends.extend(deps);
+                    closed.push((start, i));
+                }
+            }
+            _ => {}
+        }
+    }
+    let names: Vec<String> = modules
+        .iter()
+        .filter(|(_, test)| !test)
+        .map(|(m, _)| m.name.clone())
+        .collect();
+    modules
+        .into_iter()
+        .filter(|(_, test)| !test)
+        .map(|(mut module, _)| {
+            module.depends = module
+                .depends
+                .iter()
+                .filter_map(|d| resolve(&module.name, d.trim_start_matches("build."), &names))
+                .filter(|d| *d != module.name)
+                .collect();
+            module
+        })
+        .collect()
+}
+
+/// The module a name in `moduleDeps` refers to, looked up from the enclosing object outwards as
+/// Scala does.
+fn resolve(from: &str, dep: &str, names: &[String]) -> Option<String> {
+    let mut scope = from;
+    loop {
+        scope = scope.rsplit_once('.').map_or("", |(parent, _)| parent);
+        let candidate = if scope.is_empty() {
+            dep.to_string()
+        } else {
+            format!("{scope}.{dep}")
+        };
+        if names.contains(&candidate) {
+            return Some(candidate);
+        }
+        if scope.is_empty() {
+            return None;
+        }
+    }
+}
+
+fn owning_module<'a>(modules: &'a [Module], path: &Path) -> Option<&'a Module> {
+    modules
+        .iter()
+        .filter(|m| path.starts_with(&m.dir))
+        .max_by_key(|m| m.dir.components().count())
+}
+
+/// Modules containing a changed file, mapped to the deepest one, plus those depending on an
+/// affected module, directly or not. A change to the build file affects every module.
+fn affected_modules(modules: &[Module], changed_files: &[PathBuf]) -> BTreeSet<String> {
+    if changed_files
+        .iter()
+        .any(|f| BUILD_FILES.iter().any(|name| f == Path::new(name)))
+    {
+        return modules.iter().map(|m| m.name.clone()).collect();
+    }
+    let mut affected: BTreeSet<String> = changed_files
+        .iter()
+        .filter_map(|f| owning_module(modules, f))
+        .map(|m| m.name.clone())
+        .collect();
+    loop {
+        let dependents: Vec<String> = modules
+            .iter()
+            .filter(|m| !affected.contains(&m.name) && m.depends.iter().any(|d| affected.contains(d)))
+            .map(|m| m.name.clone())
+            .collect();
+        if dependents.is_empty() {
+            return affected;
+        }
+        affected.extend(dependents);
+    }
+}
+
+#[cfg(test)]
+#[path = "mill_test.rs"]
+mod tests;
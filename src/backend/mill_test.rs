@@ -0,0 +1,69 @@
+use super::*;
+
+const BUILD: &str = "\
+import mill._, scalalib._, scalafmt._
+
+object core extends ScalaModule with ScalafmtModule {
+  def scalaVersion = \"3.3.1\"
+  object test extends ScalaTests with TestModule.Munit
+}
+
+object util extends Module {
+  object jvm extends ScalaModule {
+    def moduleDeps = Seq(core)
+  }
+}
+
+// object legacy extends ScalaModule
+object app extends ScalaModule {
+  def moduleDeps = Seq(util.jvm, build.core)
+  object deps {
+    val cats = ivy\"org.typelevel::cats-core:2.10.0\"
+  }
+}
+
+object docs
+  extends ScalaModule
+  with ScalafmtModule
+";
+
+#[test]
+fn modules_are_named_by_path_with_their_dependencies() {
+    let modules = parse_modules(BUILD);
+    let names: Vec<&str> = modules.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, ["core", "util", "util.jvm", "app", "docs"]);
+    let core = &modules[0];
+    assert!(core.tests && core.scalafmt);
+    let jvm = &modules[2];
+    assert_eq!(jvm.dir, Path::new("util/jvm"));
+    assert_eq!(jvm.depends.iter().collect::<Vec<_>>(), ["core"]);
+    assert!(!jvm.tests && !jvm.scalafmt);
+    assert_eq!(modules[3].depends.iter().collect::<Vec<_>>(), ["core", "util.jvm"]);
+    assert!(modules[4].scalafmt);
+}
+
+#[test]
+fn affected_modules_include_their_dependents() {
+    let modules = parse_modules(BUILD);
+    let affected = |files: &[&str]| -> Vec<String> {
+        let files: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+        affected_modules(&modules, &files).into_iter().collect()
+    };
+    assert_eq!(
+        affected(&["core/test/src/CoreSuite.scala"]),
+        ["app", "core", "util.jvm"]
+    );
+    assert_eq!(affected(&["util/jvm/src/Io.scala"]), ["app", "util.jvm"]);
+    assert!(affected(&["README.md"]).is_empty());
+    assert_eq!(affected(&["build.sc"]).len(), 5);
+}
+
+#[test]
+fn selectors_cover_the_named_modules() {
+    assert_eq!(selector(&["core"], false, "compile"), "core.__.compile");
+    assert_eq!(
+        selector(&["core", "util.jvm"], false, "test"),
+        "{core,util.jvm}.__.test"
+    );
+    assert_eq!(selector(&["core", "app"], true, "compile"), "__.compile");
+}
@@ -1,47 +1,371 @@
 mod bazel;
+mod buf;
+mod cmake;
+mod custom;
+mod docker;
+mod dotnet;
+mod dune;
+mod earthly;
+mod gleam;
 mod go;
+mod gradle;
+mod haskell;
+mod helm;
 mod js;
+mod julia;
+mod just;
+mod kustomize;
+mod maven;
+mod mill;
+mod pants;
+mod pixi;
+mod please;
+mod pnpm_lock;
+mod poetry;
+mod python;
+mod terraform;
+mod uv;
+mod xcode;
 
 use anyhow::Result;
+use serde::Serialize;
+use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+use crate::config::BackendConfig;
+use crate::coverage::Coverage;
+use crate::exec::Invocation;
+use crate::failures::Failure;
+use crate::report::{Skip, TestCache};
+use crate::security::Vulnerability;
 
 pub use bazel::BazelBackend;
+pub use buf::BufBackend;
+pub use cmake::CmakeBackend;
+pub use custom::CustomBackend;
+pub use docker::DockerBackend;
+pub use dotnet::DotnetBackend;
+pub use dune::DuneBackend;
+pub use earthly::EarthlyBackend;
+pub use gleam::GleamBackend;
 pub use go::GoBackend;
+pub use gradle::GradleBackend;
+pub use haskell::HaskellBackend;
+pub use helm::HelmBackend;
+pub use js::workspace_graph;
+pub use julia::JuliaBackend;
+pub use just::JustBackend;
+pub use kustomize::KustomizeBackend;
+pub use maven::MavenBackend;
+pub use mill::MillBackend;
+pub use pants::PantsBackend;
+pub use pixi::PixiBackend;
+pub use please::PleaseBackend;
+pub use poetry::PoetryBackend;
+pub use python::PythonBackend;
+pub use terraform::TerraformBackend;
+pub use uv::UvBackend;
+pub use xcode::XcodeBackend;
 
 /// A build target identified by a backend.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct Target {
     /// Human-readable label (e.g. "./internal/db/...")
     pub label: String,
     /// The directory this target lives in.
+    #[serde(skip)]
+    pub dir: PathBuf,
+    #[serde(flatten)]
+    pub meta: TargetMeta,
+    /// Files within the target named on the command line, relative to the repo root. Backends
+    /// that can run the tests in specific files limit `test` to them; empty means the whole target.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<PathBuf>,
+}
+
+/// Facts about a target that backends fill in when they can determine them cheaply.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TargetMeta {
+    /// Rule or project kind (e.g. "go_test", "package").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// Main source language, lowercase (e.g. "go", "typescript").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Whether the target contains tests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_test: Option<bool>,
+    /// Number of source files or rules, as a rough estimate of how long the target takes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<usize>,
+}
+
+/// A node of a backend's dependency graph: a package or target in the repo and those in the repo it
+/// depends on, for `kit graph`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphNode {
+    /// Label in the backend's terms (e.g. "//pkg/api:all", "./pkg/api", "@acme/ui").
+    pub label: String,
+    /// Directory relative to the repo root.
+    pub dir: PathBuf,
+    /// Labels of the nodes it depends on.
+    pub deps: BTreeSet<String>,
+}
+
+/// A dependency with a newer release than the version the repo uses, for `kit outdated`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Outdated {
+    /// Name in the ecosystem's terms (e.g. "golang.org/x/net", "react", "rules_go").
+    pub name: String,
+    /// The version the repo resolves to.
+    pub current: String,
+    /// The newest version released.
+    pub latest: String,
+    /// Directory of the module or package that depends on it, relative to the repo root.
     pub dir: PathBuf,
 }
 
+/// Narrows targets by their metadata (`--only-tests`, `--lang`). Targets whose backend could not
+/// determine a fact are kept.
+#[derive(Debug, Clone, Default)]
+pub struct TargetFilter {
+    pub only_tests: bool,
+    /// Lowercase language name.
+    pub language: Option<String>,
+}
+
+impl TargetFilter {
+    pub fn matches(&self, target: &Target) -> bool {
+        let meta = &target.meta;
+        if self.only_tests && meta.is_test == Some(false) {
+            return false;
+        }
+        match (&self.language, &meta.language) {
+            (Some(want), Some(have)) => want == have,
+            _ => true,
+        }
+    }
+}
+
 /// Trait implemented by each build system backend.
+///
+/// Operations return the commands to run rather than running them; the runner executes them.
 pub trait Backend {
     fn name(&self) -> &str;
 
     /// Returns true if this backend owns the given directory.
     fn detect(&self, dir: &Path) -> bool;
 
-    /// Given a set of changed files, return the targets that need to be operated on.
-    fn affected_targets(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Vec<Target>;
+    /// Given the files changed since `base`, return the targets that need to be operated on.
+    fn affected_targets(&self, repo_root: &Path, base: &str, changed_files: &[PathBuf]) -> Vec<Target>;
 
     /// Format a directory path as a backend-specific target label.
     fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target;
 
-    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<()>;
-    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<()>;
-    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<()>;
-    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<()>;
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>>;
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>>;
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>>;
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>>;
+
+    /// Commands that type-check or compile the targets without producing artifacts, the cheapest
+    /// correctness pass the toolchain has, for `kit check`.
+    fn check(&self, _repo_root: &Path, _targets: &[Target]) -> Result<Vec<Invocation>> {
+        Err(Skip::NotConfigured {
+            detail: format!("{} has no compile-only check", self.name()),
+        }
+        .into())
+    }
+
+    /// Commands that apply the linters' automatic fixes to the targets, for `kit fix`. Unlike
+    /// [`Backend::fmt`], these may change what code does, such as replacing a deprecated call.
+    fn fix(&self, _repo_root: &Path, _targets: &[Target]) -> Result<Vec<Invocation>> {
+        Err(Skip::NotConfigured {
+            detail: format!("{} has no automatic lint fixes", self.name()),
+        }
+        .into())
+    }
+
+    /// Commands that regenerate code in the targets with the ecosystem's generator, for `kit
+    /// generate`.
+    fn generate(&self, _repo_root: &Path, _targets: &[Target]) -> Result<Vec<Invocation>> {
+        Err(Skip::NotConfigured {
+            detail: format!("{} has no code generator", self.name()),
+        }
+        .into())
+    }
+
+    /// Commands that build the targets' documentation, for `kit docs`.
+    fn docs(&self, _repo_root: &Path, _targets: &[Target]) -> Result<Vec<Invocation>> {
+        Err(Skip::NotConfigured {
+            detail: format!("{} has no documentation builder", self.name()),
+        }
+        .into())
+    }
+
+    /// Commands that tidy the dependency declarations and lock files of the targets' modules, for
+    /// `kit tidy`.
+    fn tidy(&self, _repo_root: &Path, _targets: &[Target]) -> Result<Vec<Invocation>> {
+        Err(Skip::NotConfigured {
+            detail: format!("{} has no dependency tidier", self.name()),
+        }
+        .into())
+    }
+
+    /// Commands that report which of the changed files [`Backend::fmt`] would rewrite, without
+    /// writing them, for `kit fmt --check`. They fail, listing the files, when any isn't formatted.
+    fn fmt_check(&self, _repo_root: &Path, _changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        Err(Skip::NotConfigured {
+            detail: format!("{} can't check formatting without rewriting files", self.name()),
+        }
+        .into())
+    }
+
+    /// Commands that run the repo's own `task` (e.g. `e2e`, `deploy-preview`) for the targets, for
+    /// `kit run`. Skipped unless the backend has a kind of task to look it up in.
+    fn task(&self, _repo_root: &Path, task: &str, _targets: &[Target]) -> Result<Vec<Invocation>> {
+        Err(Skip::NotConfigured {
+            detail: format!("{} has no tasks to run {task} with", self.name()),
+        }
+        .into())
+    }
+
+    /// Commands that run tests with coverage enabled. `out_dir` is a scratch directory for
+    /// intermediate coverage files.
+    fn coverage(&self, _repo_root: &Path, _targets: &[Target], _out_dir: &Path) -> Result<Vec<Invocation>> {
+        anyhow::bail!("the {} backend does not support coverage", self.name())
+    }
+
+    /// Read the line coverage produced by the [`Backend::coverage`] commands, which started at `since`.
+    fn read_coverage(&self, _repo_root: &Path, _out_dir: &Path, _since: SystemTime) -> Result<Coverage> {
+        Ok(Coverage::default())
+    }
+
+    /// Arguments that make the [`Backend::test`] commands run tests in an order randomized by
+    /// `seed`, or None if the test runner cannot do that.
+    fn shuffle_args(&self, _seed: u64) -> Option<Vec<String>> {
+        None
+    }
+
+    /// Arguments that make the [`Backend::test`] commands rerun tests whose results the test runner
+    /// has cached, or None if it does not cache them.
+    fn no_cache_args(&self) -> Option<Vec<String>> {
+        None
+    }
+
+    /// How many of the test results in the output of this backend's test or coverage commands
+    /// came from the test runner's cache, if it reports that.
+    fn test_cache(&self, _output: &str) -> Option<TestCache> {
+        None
+    }
+
+    /// Failing tests in the output of this backend's test or coverage commands.
+    fn parse_failures(&self, _repo_root: &Path, _output: &str) -> Vec<Failure> {
+        vec![]
+    }
+
+    /// The packages or targets in the repo and their dependencies on each other, for `kit graph`.
+    /// None if this backend can't tell.
+    fn graph(&self, _repo_root: &Path) -> Result<Option<Vec<GraphNode>>> {
+        Ok(None)
+    }
+
+    /// Third-party dependencies with newer releases than the versions the repo uses, for `kit
+    /// outdated`. None if this backend can't tell.
+    fn outdated(&self, _repo_root: &Path) -> Result<Option<Vec<Outdated>>> {
+        Ok(None)
+    }
+
+    /// Known vulnerabilities in the dependencies of the targets, from the ecosystem's own scanner, for
+    /// `kit audit`. None if it has none, in which case OSV-Scanner scans the targets' directories.
+    fn audit(&self, _repo_root: &Path, _targets: &[Target]) -> Result<Option<Vec<Vulnerability>>> {
+        Ok(None)
+    }
+
+    /// Targets nothing else in the repo depends on that are not entry points (binaries, tests, or
+    /// published packages) either, for `kit lint --orphans`. None if this backend can't tell.
+    fn orphans(&self, _repo_root: &Path) -> Result<Option<Vec<String>>> {
+        Ok(None)
+    }
+
+    /// Commands printing the versions of the tools this backend runs, recorded as part of the
+    /// environment of a run.
+    fn toolchain(&self, _repo_root: &Path) -> Vec<Invocation> {
+        vec![]
+    }
+
+    /// Commands printing the versions of tools only some commands run, such as linters and
+    /// formatters, which `kit doctor` checks along with the [`Backend::toolchain`].
+    fn extra_tools(&self, _repo_root: &Path) -> Vec<Invocation> {
+        vec![]
+    }
+
+    /// Quick commands that exercise this backend's tools end to end in the repo (e.g. a trivial
+    /// query), for `kit doctor --deep`. Defaults to the [`Backend::toolchain`] commands.
+    fn smoke(&self, repo_root: &Path) -> Vec<Invocation> {
+        self.toolchain(repo_root)
+    }
+
+    /// Setup problems that make runs fail or behave unexpectedly, found without running anything,
+    /// for `kit doctor`.
+    fn misconfigurations(&self, _repo_root: &Path) -> Vec<String> {
+        vec![]
+    }
 }
 
-/// Returns all registered backends.
-pub fn all_backends() -> Vec<Box<dyn Backend>> {
-    vec![
+/// Returns all registered backends, after those defined in kit.toml, which take priority.
+pub fn all_backends(custom: &[BackendConfig]) -> Vec<Box<dyn Backend>> {
+    let builtin: Vec<Box<dyn Backend>> = vec![
         Box::new(BazelBackend),
+        Box::new(PantsBackend),
+        Box::new(PleaseBackend),
         Box::new(js::PNPM),
         Box::new(js::YARN),
+        Box::new(js::NPM),
         Box::new(GoBackend),
-    ]
+        Box::new(MavenBackend),
+        Box::new(GradleBackend),
+        Box::new(MillBackend),
+        Box::new(DotnetBackend),
+        Box::new(CmakeBackend),
+        Box::new(HaskellBackend),
+        Box::new(GleamBackend),
+        Box::new(DuneBackend),
+        Box::new(JuliaBackend),
+        Box::new(XcodeBackend),
+        Box::new(UvBackend),
+        Box::new(PoetryBackend),
+        Box::new(PythonBackend),
+        Box::new(PixiBackend),
+        Box::new(EarthlyBackend),
+        Box::new(TerraformBackend),
+        Box::new(DockerBackend),
+        Box::new(BufBackend),
+        Box::new(HelmBackend),
+        Box::new(KustomizeBackend),
+        Box::new(JustBackend),
+    ];
+    custom
+        .iter()
+        .map(|c| Box::new(CustomBackend::new(c.clone())) as Box<dyn Backend>)
+        .chain(builtin)
+        .collect()
+}
+
+/// Returns true if the command is on PATH.
+pub fn which_exists(cmd: &str) -> bool {
+    Command::new("which")
+        .arg(cmd)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Returns true if the file exists and was modified at or after `since`, so reports left over from
+/// earlier runs are not mistaken for fresh ones.
+pub fn written_since(path: &Path, since: SystemTime) -> bool {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .is_ok_and(|modified| modified >= since)
 }
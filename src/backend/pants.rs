@@ -0,0 +1,216 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+
+use super::uv::pytest_failures;
+use super::{Backend, Target, TargetMeta};
+use crate::error::KitError;
+use crate::exec::Invocation;
+use crate::failures::Failure;
+use crate::git;
+use crate::report::TestCache;
+
+pub struct PantsBackend;
+
+impl PantsBackend {
+    /// The repo's `./pants` script if it has one (older Pants versions), otherwise `pants` from PATH.
+    fn pants(repo_root: &Path) -> String {
+        let script = repo_root.join("pants");
+        if script.is_file() {
+            script.to_string_lossy().into_owned()
+        } else {
+            "pants".to_string()
+        }
+    }
+
+    /// Targets owning a file changed since the merge base with `base`, plus everything that depends
+    /// on them, from `pants --changed-since=<merge-base> --changed-dependents=transitive list`. Pants
+    /// includes uncommitted changes itself.
+    fn changed_since(repo_root: &Path, base: &str) -> Result<Vec<Target>> {
+        let merge_base = git::merge_base(repo_root, base)?;
+        let program = Self::pants(repo_root);
+        let output = Command::new(&program)
+            .arg(format!("--changed-since={merge_base}"))
+            .args(["--changed-dependents=transitive", "list"])
+            .current_dir(repo_root)
+            .output()
+            .map_err(|e| KitError::spawn(&program, e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("pants list failed: {}", stderr.trim());
+        }
+        let stdout = String::from_utf8(output.stdout).context("invalid utf-8 from pants list")?;
+        Ok(parse_addresses(repo_root, &stdout))
+    }
+
+    /// `pants <goal>` on the targets' addresses, or on the named files where there are any.
+    fn goal(repo_root: &Path, goal: &str, targets: &[Target]) -> Vec<Invocation> {
+        if targets.is_empty() {
+            return vec![];
+        }
+        let specs = targets.iter().flat_map(|t| {
+            if t.files.is_empty() {
+                vec![t.label.clone()]
+            } else {
+                t.files.iter().map(|f| f.to_string_lossy().replace('\\', "/")).collect()
+            }
+        });
+        vec![Invocation::new(Self::pants(repo_root), repo_root).arg(goal).args(specs)]
+    }
+}
+
+impl Backend for PantsBackend {
+    fn name(&self) -> &str {
+        "pants"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join("pants.toml").exists()
+    }
+
+    fn affected_targets(&self, repo_root: &Path, base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        // Pants works out the changed files on its own; this only skips asking it when every change
+        // was filtered out (e.g. by .kitignore).
+        if changed_files.is_empty() {
+            return vec![];
+        }
+        match Self::changed_since(repo_root, base) {
+            Ok(targets) => targets,
+            Err(e) => {
+                eprintln!("kit: failed to list changed Pants targets ({e:#})");
+                vec![]
+            }
+        }
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_string_lossy();
+        let rel = rel.replace('\\', "/");
+        // `dir::` is every target in the directory and below it.
+        Target {
+            label: format!("{rel}::"),
+            dir,
+            meta: TargetMeta::default(),
+            files: Vec::new(),
+        }
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Ok(Self::goal(repo_root, "check", targets))
+    }
+
+    fn check(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Ok(Self::goal(repo_root, "check", targets))
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Ok(Self::goal(repo_root, "test", targets))
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Ok(Self::goal(repo_root, "lint", targets))
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        // Pants accepts file paths as specs and runs each formatter on the files it applies to.
+        let files: Vec<String> = changed_files
+            .iter()
+            .filter(|f| repo_root.join(f).is_file())
+            .map(|f| f.to_string_lossy().replace('\\', "/"))
+            .collect();
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![
+            Invocation::new(Self::pants(repo_root), repo_root)
+                .arg("fmt")
+                .args(files),
+        ])
+    }
+
+    fn no_cache_args(&self) -> Option<Vec<String>> {
+        Some(vec!["--test-force".to_string()])
+    }
+
+    fn test_cache(&self, output: &str) -> Option<TestCache> {
+        test_cache(output)
+    }
+
+    fn parse_failures(&self, _repo_root: &Path, output: &str) -> Vec<Failure> {
+        test_failures(output)
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new(Self::pants(repo_root), repo_root).arg("--version")]
+    }
+}
+
+/// Targets from `pants list`, one address per line: "src/app:lib", "//:root" at the build root, or
+/// "src/app/main.py:lib" for a target generated for one file.
+fn parse_addresses(repo_root: &Path, stdout: &str) -> Vec<Target> {
+    stdout
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|address| {
+            let path = address.trim_start_matches("//").split(':').next().unwrap_or_default();
+            let path = Path::new(path.split('#').next().unwrap_or_default());
+            let dir = if path.extension().is_some() {
+                path.parent().unwrap_or(path)
+            } else {
+                path
+            };
+            Target {
+                label: address.to_string(),
+                dir: repo_root.join(dir),
+                meta: TargetMeta::default(),
+                files: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// Failing tests in `pants test` output. Pants relays pytest's output for failed test files, so
+/// those failures come with a location; other test runners only get their summary line:
+/// "✕ src/app/util_test.go:tests failed in 0.4s.".
+fn test_failures(output: &str) -> Vec<Failure> {
+    let failures = pytest_failures(output);
+    if !failures.is_empty() {
+        return failures;
+    }
+    output
+        .lines()
+        .filter_map(|line| {
+            let rest = line.trim().strip_prefix('✕')?.trim_start();
+            let (address, _) = rest.split_once(" failed")?;
+            let file = address.split(':').next().map(PathBuf::from);
+            Some(Failure {
+                test: address.to_string(),
+                file: file.filter(|f| f.extension().is_some()),
+                line: None,
+                message: "failed".to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Test results in the `pants test` summary, cached when Pants reused them ("✓ src/app:tests
+/// succeeded in 0.5s (memoized)." or "(cached locally)").
+fn test_cache(output: &str) -> Option<TestCache> {
+    let mut cache = TestCache { cached: 0, total: 0 };
+    for line in output.lines().map(str::trim) {
+        if !(line.starts_with('✓') || line.starts_with('✕'))
+            || !(line.contains(" succeeded") || line.contains(" failed"))
+        {
+            continue;
+        }
+        cache.total += 1;
+        cache.cached += usize::from(line.contains("(memoized)") || line.contains("(cached"));
+    }
+    (cache.total > 0).then_some(cache)
+}
+
+#[cfg(test)]
+#[path = "pants_test.rs"]
+mod tests;
@@ -0,0 +1,47 @@
+use super::*;
+
+#[test]
+fn parse_addresses_map_targets_to_their_directory() {
+    let root = Path::new("/repo");
+    let stdout = "src/app:lib\nsrc/app/main.py:lib\n//:root\nsrc/app/util_test.go#tests\n\n";
+    let targets = parse_addresses(root, stdout);
+    let dirs: Vec<&Path> = targets.iter().map(|t| t.dir.as_path()).collect();
+    assert_eq!(
+        dirs,
+        [
+            root.join("src/app"),
+            root.join("src/app"),
+            root.to_path_buf(),
+            root.join("src/app")
+        ]
+    );
+    assert_eq!(targets[1].label, "src/app/main.py:lib");
+}
+
+#[test]
+fn goals_run_on_addresses_or_named_files() {
+    let root = Path::new("/repo");
+    let lib = PantsBackend.resolve_target(root, root.join("src/lib"));
+    let mut app = PantsBackend.resolve_target(root, root.join("src/app"));
+    app.files = vec![PathBuf::from("src/app/test_main.py")];
+    assert_eq!(lib.label, "src/lib::");
+    assert_eq!(PantsBackend.resolve_target(root, root.to_path_buf()).label, "::");
+    assert_eq!(
+        PantsBackend.test(root, &[lib, app]).unwrap(),
+        [Invocation::new("pants", root).args(["test", "src/lib::", "src/app/test_main.py"])]
+    );
+}
+
+#[test]
+fn test_summary_gives_failures_and_cache_counts() {
+    let output = "\
+✓ src/app:tests succeeded in 0.51s (memoized).
+✓ src/lib:tests succeeded in 1.02s (cached locally).
+✕ src/app/util_test.go:tests failed in 0.40s.
+";
+    let failures = test_failures(output);
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].test, "src/app/util_test.go:tests");
+    assert_eq!(failures[0].file.as_deref(), Some(Path::new("src/app/util_test.go")));
+    assert_eq!(test_cache(output), Some(TestCache { cached: 2, total: 3 }));
+}
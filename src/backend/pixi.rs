@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use super::{Backend, Target, TargetMeta};
+use crate::exec::Invocation;
+use crate::report::Skip;
+
+/// The tasks kit runs, named after its commands.
+const TASKS: &[&str] = &["build", "test", "lint", "fmt"];
+
+/// Files whose change affects every environment.
+const MANIFEST_FILES: &[&str] = &["pixi.toml", "pixi.lock"];
+
+/// The environment every Pixi project has, made of the default feature alone unless redefined.
+const DEFAULT: &str = "default";
+
+/// Where a task looks for its files: its `inputs` globs, or else its `cwd`. A task with neither may
+/// read anything.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct TaskScope {
+    inputs: Vec<String>,
+    cwd: Option<PathBuf>,
+}
+
+/// A Pixi environment and the kit tasks it can run.
+#[derive(Debug, Clone, PartialEq)]
+struct Environment {
+    name: String,
+    tasks: BTreeMap<String, TaskScope>,
+}
+
+pub struct PixiBackend;
+
+impl PixiBackend {
+    fn environments(repo_root: &Path) -> Result<Vec<Environment>> {
+        let path = repo_root.join("pixi.toml");
+        let text = std::fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        let manifest: toml::Table = toml::from_str(&text).with_context(|| format!("invalid {}", path.display()))?;
+        Ok(parse_environments(&manifest))
+    }
+
+    /// `pixi run -e <env> <task>` for each target environment that has the task, or a [`Skip`] when
+    /// none does.
+    fn run(repo_root: &Path, task: &str, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let environments = Self::environments(repo_root)?;
+        let plan: Vec<Invocation> = targets
+            .iter()
+            .filter(|t| {
+                environments
+                    .iter()
+                    .any(|e| e.name == t.label && e.tasks.contains_key(task))
+            })
+            .map(|t| Invocation::new("pixi", repo_root).args(["run", "-e", &t.label, task]))
+            .collect();
+        if plan.is_empty() {
+            return Err(Skip::NotConfigured {
+                detail: format!("no {task} task in pixi.toml for the affected environments"),
+            }
+            .into());
+        }
+        Ok(plan)
+    }
+}
+
+impl Backend for PixiBackend {
+    fn name(&self) -> &str {
+        "pixi"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join("pixi.toml").is_file()
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let environments = match Self::environments(repo_root) {
+            Ok(environments) => environments,
+            Err(e) => {
+                eprintln!("kit: failed to read Pixi environments ({e:#})");
+                return vec![];
+            }
+        };
+        let mut targets = Vec::new();
+        for environment in &environments {
+            match affected(repo_root, environment, changed_files) {
+                Ok(true) => targets.push(target(repo_root, &environment.name)),
+                Ok(false) => {}
+                Err(e) => eprintln!("kit: invalid task inputs in pixi.toml ({e:#})"),
+            }
+        }
+        targets
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        // Environments aren't tied to directories; a named path runs the default one.
+        Target {
+            dir,
+            ..target(repo_root, DEFAULT)
+        }
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Self::run(repo_root, "build", targets)
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Self::run(repo_root, "test", targets)
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Self::run(repo_root, "lint", targets)
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        if changed_files.is_empty() {
+            return Ok(vec![]);
+        }
+        // Formatters rewrite files in place the same way in any environment, so the task runs once,
+        // in the first environment that has it.
+        let environments = Self::environments(repo_root)?;
+        let environment = environments
+            .iter()
+            .find(|e| e.name == DEFAULT && e.tasks.contains_key("fmt"))
+            .or_else(|| environments.iter().find(|e| e.tasks.contains_key("fmt")));
+        match environment {
+            Some(environment) => Ok(vec![Invocation::new("pixi", repo_root).args([
+                "run",
+                "-e",
+                &environment.name,
+                "fmt",
+            ])]),
+            None => Err(Skip::NotConfigured {
+                detail: "no fmt task in pixi.toml".to_string(),
+            }
+            .into()),
+        }
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new("pixi", repo_root).arg("--version")]
+    }
+}
+
+fn target(repo_root: &Path, environment: &str) -> Target {
+    Target {
+        label: environment.to_string(),
+        dir: repo_root.to_path_buf(),
+        meta: TargetMeta {
+            kind: Some("environment".to_string()),
+            ..TargetMeta::default()
+        },
+        files: Vec::new(),
+    }
+}
+
+/// The project's environments with the kit tasks of their features: the default feature's (the
+/// top-level `[tasks]`) unless `no-default-feature` is set, then each listed feature's. Without an
+/// `[environments]` table only the default environment exists.
+fn parse_environments(manifest: &toml::Table) -> Vec<Environment> {
+    let default_tasks = tasks(manifest.get("tasks"));
+    let features = manifest.get("feature").and_then(|f| f.as_table());
+    let mut environments = vec![Environment {
+        name: DEFAULT.to_string(),
+        tasks: default_tasks.clone(),
+    }];
+    let declared = manifest.get("environments").and_then(|e| e.as_table());
+    for (name, spec) in declared.into_iter().flatten() {
+        let (listed, no_default) = match spec {
+            toml::Value::Array(listed) => (Some(listed), false),
+            toml::Value::Table(spec) => (
+                spec.get("features").and_then(|f| f.as_array()),
+                spec.get("no-default-feature")
+                    .and_then(|n| n.as_bool())
+                    .unwrap_or(false),
+            ),
+            _ => (None, false),
+        };
+        let mut environment = Environment {
+            name: name.clone(),
+            tasks: if no_default {
+                BTreeMap::new()
+            } else {
+                default_tasks.clone()
+            },
+        };
+        for feature in listed.into_iter().flatten().filter_map(|f| f.as_str()) {
+            let feature = features.and_then(|f| f.get(feature));
+            environment.tasks.extend(tasks(feature.and_then(|f| f.get("tasks"))));
+        }
+        match environments.iter_mut().find(|e| e.name == *name) {
+            Some(existing) => *existing = environment,
+            None => environments.push(environment),
+        }
+    }
+    environments
+}
+
+/// The kit tasks in a `tasks` table. A task is a command string or a table with `cmd`, `cwd`,
+/// `inputs`, and `depends-on`.
+fn tasks(table: Option<&toml::Value>) -> BTreeMap<String, TaskScope> {
+    let Some(table) = table.and_then(|t| t.as_table()) else {
+        return BTreeMap::new();
+    };
+    TASKS
+        .iter()
+        .filter_map(|name| {
+            let task = table.get(*name)?;
+            let inputs = task
+                .get("inputs")
+                .and_then(|i| i.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|i| i.as_str().map(String::from))
+                .collect();
+            let cwd = task.get("cwd").and_then(|c| c.as_str()).map(PathBuf::from);
+            Some((name.to_string(), TaskScope { inputs, cwd }))
+        })
+        .collect()
+}
+
+/// Whether a change affects the environment: the manifest or lockfile changed, or a changed file is
+/// in scope of one of its kit tasks.
+fn affected(repo_root: &Path, environment: &Environment, changed_files: &[PathBuf]) -> Result<bool> {
+    if environment.tasks.is_empty() || changed_files.is_empty() {
+        return Ok(false);
+    }
+    if changed_files
+        .iter()
+        .any(|f| MANIFEST_FILES.iter().any(|m| f == Path::new(m)))
+    {
+        return Ok(true);
+    }
+    for scope in environment.tasks.values() {
+        if !scope.inputs.is_empty() {
+            let matcher = globs(repo_root, &scope.inputs)?;
+            if changed_files.iter().any(|f| {
+                matcher
+                    .matched_path_or_any_parents(repo_root.join(f), false)
+                    .is_ignore()
+            }) {
+                return Ok(true);
+            }
+        } else if let Some(cwd) = &scope.cwd {
+            if changed_files.iter().any(|f| f.starts_with(cwd)) {
+                return Ok(true);
+            }
+        } else {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// A matcher for task input globs, which are relative to the project root.
+fn globs(repo_root: &Path, patterns: &[String]) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(repo_root);
+    for pattern in patterns {
+        let pattern = pattern.trim_start_matches("./");
+        builder
+            .add_line(None, &format!("/{pattern}"))
+            .with_context(|| format!("invalid task input `{pattern}`"))?;
+    }
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+#[path = "pixi_test.rs"]
+mod tests;
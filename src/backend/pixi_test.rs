@@ -0,0 +1,79 @@
+use super::*;
+
+const MANIFEST: &str = r#"
+[workspace]
+name = "analysis"
+channels = ["conda-forge"]
+platforms = ["linux-64"]
+
+[tasks]
+lint = "ruff check ."
+fmt = "ruff format ."
+
+[feature.py311.dependencies]
+python = "3.11.*"
+
+[feature.test.tasks]
+test = { cmd = "pytest", inputs = ["src/**/*.py", "tests/**"] }
+
+[feature.docs.tasks]
+build = { cmd = "mkdocs build", cwd = "docs" }
+
+[environments]
+test = ["py311", "test"]
+docs = { features = ["docs"], no-default-feature = true }
+"#;
+
+fn environments() -> Vec<Environment> {
+    parse_environments(&toml::from_str(MANIFEST).unwrap())
+}
+
+#[test]
+fn environments_combine_the_tasks_of_their_features() {
+    let names: Vec<(String, Vec<String>)> = environments()
+        .into_iter()
+        .map(|e| (e.name, e.tasks.into_keys().collect()))
+        .collect();
+    assert_eq!(
+        names,
+        [
+            ("default".to_string(), vec!["fmt".to_string(), "lint".to_string()]),
+            ("docs".to_string(), vec!["build".to_string()]),
+            (
+                "test".to_string(),
+                vec!["fmt".to_string(), "lint".to_string(), "test".to_string()]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn only_environments_whose_task_files_changed_are_affected() {
+    let repo = Path::new("/repo");
+    let affected_by = |file: &str| -> Vec<String> {
+        environments()
+            .into_iter()
+            .filter(|e| affected(repo, e, &[PathBuf::from(file)]).unwrap())
+            .map(|e| e.name)
+            .collect()
+    };
+    // The default feature's tasks declare no inputs, so any change reaches environments using it.
+    assert_eq!(affected_by("src/model.py"), ["default", "test"]);
+    assert_eq!(affected_by("docs/index.md"), ["default", "docs", "test"]);
+    assert_eq!(affected_by("pixi.lock"), ["default", "docs", "test"]);
+
+    let docs = environments().into_iter().find(|e| e.name == "docs").unwrap();
+    assert!(!affected(repo, &docs, &[PathBuf::from("src/model.py")]).unwrap());
+}
+
+#[test]
+fn tasks_run_only_in_environments_that_have_them() {
+    let repo = tempfile::tempdir().unwrap();
+    std::fs::write(repo.path().join("pixi.toml"), MANIFEST).unwrap();
+    let targets = [target(repo.path(), "default"), target(repo.path(), "test")];
+    let plan = PixiBackend.test(repo.path(), &targets).unwrap();
+    assert_eq!(plan.len(), 1);
+    assert_eq!(plan[0].args, ["run", "-e", "test", "test"]);
+    let err = PixiBackend.build(repo.path(), &targets).unwrap_err();
+    assert!(matches!(Skip::find(&err), Some(Skip::NotConfigured { .. })));
+}
@@ -0,0 +1,182 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+
+use super::{Backend, Target, TargetMeta};
+use crate::error::KitError;
+use crate::exec::Invocation;
+use crate::git;
+
+/// Names Please reads BUILD files from unless `.plzconfig` sets `buildfilename`.
+const BUILD_FILES: &[&str] = &["BUILD", "BUILD.plz"];
+
+pub struct PleaseBackend;
+
+impl PleaseBackend {
+    /// The repo's `pleasew` wrapper if it has one, otherwise `plz` from PATH.
+    fn plz(repo_root: &Path) -> String {
+        let wrapper = repo_root.join("pleasew");
+        if wrapper.is_file() {
+            wrapper.to_string_lossy().into_owned()
+        } else {
+            "plz".to_string()
+        }
+    }
+
+    /// Targets affected by changes since the merge base with `base`, from
+    /// `plz query changes --since <merge-base>`.
+    fn changes(repo_root: &Path, base: &str) -> Result<Vec<Target>> {
+        let merge_base = git::merge_base(repo_root, base)?;
+        let program = Self::plz(repo_root);
+        let output = Command::new(&program)
+            .args(["query", "changes", "--since", &merge_base])
+            .current_dir(repo_root)
+            .output()
+            .map_err(|e| KitError::spawn(&program, e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("plz query changes failed: {}", stderr.trim());
+        }
+        let stdout = String::from_utf8(output.stdout).context("invalid utf-8 from plz query changes")?;
+        Ok(parse_labels(repo_root, &stdout))
+    }
+
+    fn invocation(repo_root: &Path, command: &str, targets: &[Target]) -> Vec<Invocation> {
+        if targets.is_empty() {
+            return vec![];
+        }
+        let labels = targets.iter().map(|t| t.label.clone());
+        vec![
+            Invocation::new(Self::plz(repo_root), repo_root)
+                .arg(command)
+                .args(labels),
+        ]
+    }
+}
+
+impl Backend for PleaseBackend {
+    fn name(&self) -> &str {
+        "please"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join(".plzconfig").exists()
+    }
+
+    fn affected_targets(&self, repo_root: &Path, base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        if changed_files.is_empty() {
+            return vec![];
+        }
+        match Self::changes(repo_root, base) {
+            Ok(targets) => targets,
+            Err(e) => {
+                eprintln!("kit: failed to query changed Please targets ({e:#})");
+                vec![]
+            }
+        }
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_string_lossy();
+        let rel = rel.replace('\\', "/");
+        let label = if rel.is_empty() {
+            "//...".to_string()
+        } else {
+            format!("//{rel}/...")
+        };
+        Target {
+            label,
+            dir,
+            meta: TargetMeta::default(),
+            files: Vec::new(),
+        }
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Ok(Self::invocation(repo_root, "build", targets))
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Ok(Self::invocation(repo_root, "test", targets))
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        // Please has no lint command of its own; check that the targets' BUILD files are formatted.
+        let mut files: Vec<String> = targets
+            .iter()
+            .flat_map(|t| BUILD_FILES.iter().map(|name| t.dir.join(name)))
+            .filter(|f| f.is_file())
+            .map(|f| {
+                f.strip_prefix(repo_root)
+                    .unwrap_or(&f)
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+        files.sort();
+        files.dedup();
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![
+            Invocation::new(Self::plz(repo_root), repo_root)
+                .args(["fmt", "--quiet"])
+                .args(files),
+        ])
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let files: Vec<String> = changed_files
+            .iter()
+            .filter(|f| is_build_file(f) && repo_root.join(f).is_file())
+            .map(|f| f.to_string_lossy().replace('\\', "/"))
+            .collect();
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![
+            Invocation::new(Self::plz(repo_root), repo_root)
+                .args(["fmt", "-w"])
+                .args(files),
+        ])
+    }
+
+    fn no_cache_args(&self) -> Option<Vec<String>> {
+        Some(vec!["--rerun".to_string()])
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new(Self::plz(repo_root), repo_root).arg("--version")]
+    }
+}
+
+fn is_build_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| BUILD_FILES.contains(&n))
+}
+
+/// Targets from `plz query` output, one label per line.
+fn parse_labels(repo_root: &Path, stdout: &str) -> Vec<Target> {
+    stdout
+        .lines()
+        .map(str::trim)
+        .filter(|l| l.starts_with("//"))
+        .map(|label| Target {
+            label: label.to_string(),
+            dir: label_to_dir(repo_root, label),
+            meta: TargetMeta::default(),
+            files: Vec::new(),
+        })
+        .collect()
+}
+
+fn label_to_dir(repo_root: &Path, label: &str) -> PathBuf {
+    let pkg = label.trim_start_matches("//").split(':').next().unwrap_or("");
+    repo_root.join(pkg)
+}
+
+#[cfg(test)]
+#[path = "please_test.rs"]
+mod tests;
@@ -0,0 +1,41 @@
+use super::*;
+
+#[test]
+fn parse_labels_map_targets_to_their_package() {
+    let root = Path::new("/repo");
+    let targets = parse_labels(
+        root,
+        "//src/core:core\n//src/core:core_test\n//:all_tools\nBuilding...\n",
+    );
+    let labels: Vec<&str> = targets.iter().map(|t| t.label.as_str()).collect();
+    assert_eq!(labels, ["//src/core:core", "//src/core:core_test", "//:all_tools"]);
+    assert_eq!(targets[0].dir, root.join("src/core"));
+    assert_eq!(
+        PleaseBackend.resolve_target(root, root.join("src/core")).label,
+        "//src/core/..."
+    );
+    assert_eq!(PleaseBackend.resolve_target(root, root.to_path_buf()).label, "//...");
+}
+
+#[test]
+fn fmt_rewrites_changed_build_files_and_lint_checks_them() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    std::fs::create_dir_all(root.join("src/core")).unwrap();
+    std::fs::write(root.join("src/core/BUILD"), "").unwrap();
+    std::fs::write(root.join("src/core/core.go"), "").unwrap();
+    let changed = [
+        PathBuf::from("src/core/BUILD"),
+        PathBuf::from("src/core/core.go"),
+        PathBuf::from("src/gone/BUILD.plz"),
+    ];
+    assert_eq!(
+        PleaseBackend.fmt(root, &changed).unwrap(),
+        [Invocation::new("plz", root).args(["fmt", "-w", "src/core/BUILD"])]
+    );
+    let targets = parse_labels(root, "//src/core:core\n//src/core:core_test\n");
+    assert_eq!(
+        PleaseBackend.lint(root, &targets).unwrap(),
+        [Invocation::new("plz", root).args(["fmt", "--quiet", "src/core/BUILD"])]
+    );
+}
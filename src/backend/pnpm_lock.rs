@@ -0,0 +1,194 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+/// Top-level sections of pnpm-lock.yaml whose changes can be narrowed to the importers using them.
+/// v9 lockfiles resolve packages in `packages` and their dependencies in `snapshots`; earlier ones
+/// keep both in `packages`.
+const NARROWED: &[&str] = &["importers", "packages", "snapshots"];
+
+/// Sections of an importer or package listing the dependencies it resolves.
+const DEPENDENCY_SECTIONS: &[&str] = &["dependencies", "devDependencies", "optionalDependencies"];
+
+/// A mapping entry of the YAML subset pnpm writes: its key, the value on the key's line (empty for
+/// a nested mapping), and the lines nested under it.
+struct Entry<'a> {
+    key: String,
+    value: &'a str,
+    body: Vec<&'a str>,
+}
+
+/// Directories of the importers (workspace packages, `.` for the root) that a change between two
+/// versions of pnpm-lock.yaml affects: those whose own dependencies changed, and those depending,
+/// directly or not, on a package whose resolution changed. None if the lockfile changed in a way
+/// that can't be narrowed (its version, settings, overrides, ...) or has no importers.
+pub fn affected_importers(old: &str, new: &str) -> Option<BTreeSet<PathBuf>> {
+    let (old_sections, new_sections) = (sections(old), sections(new));
+    let others = |sections: &BTreeMap<String, Entry>| -> BTreeMap<String, (String, String)> {
+        sections
+            .iter()
+            .filter(|(key, _)| !NARROWED.contains(&key.as_str()))
+            .map(|(key, e)| (key.clone(), (e.value.to_string(), e.body.join("\n"))))
+            .collect()
+    };
+    if others(&old_sections) != others(&new_sections) {
+        return None;
+    }
+    let (old_importers, new_importers) = (
+        importers(old_sections.get("importers")?),
+        importers(new_sections.get("importers")?),
+    );
+
+    let mut changed = BTreeSet::new();
+    let mut graph: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for section in ["packages", "snapshots"] {
+        let old_packages = packages(old_sections.get(section));
+        let new_packages = packages(new_sections.get(section));
+        for (key, (body, _)) in &old_packages {
+            if new_packages.get(key).is_none_or(|(new_body, _)| new_body != body) {
+                changed.insert(package_name(key));
+            }
+        }
+        for (key, (_, deps)) in &new_packages {
+            if !old_packages.contains_key(key) {
+                changed.insert(package_name(key));
+            }
+            for dep in deps {
+                graph.entry(dep.clone()).or_default().insert(package_name(key));
+            }
+        }
+    }
+    let changed = dependents(&graph, changed);
+
+    let mut affected = BTreeSet::new();
+    for (dir, (body, deps)) in &new_importers {
+        let own = old_importers.get(dir).is_none_or(|(old_body, _)| old_body != body);
+        if own || deps.iter().any(|d| changed.contains(d)) {
+            affected.insert(importer_dir(dir));
+        }
+    }
+    // A removed importer affects the repo as a whole, like a removed workspace package.
+    if old_importers.keys().any(|dir| !new_importers.contains_key(dir)) {
+        affected.insert(PathBuf::new());
+    }
+    Some(affected)
+}
+
+/// The top-level entries of the lockfile, by key.
+fn sections(text: &str) -> BTreeMap<String, Entry<'_>> {
+    let lines: Vec<&str> = text.lines().collect();
+    entries(&lines).into_iter().map(|e| (e.key.clone(), e)).collect()
+}
+
+/// Importer directories with their entry's text and the names of the dependencies they resolve.
+fn importers(section: &Entry) -> BTreeMap<String, (String, BTreeSet<String>)> {
+    entries(&section.body)
+        .into_iter()
+        .map(|e| (e.key.clone(), (e.body.join("\n"), dependency_names(&e))))
+        .collect()
+}
+
+/// Package keys (e.g. `lodash@4.17.21`) with their entry's text and the names of their dependencies.
+fn packages(section: Option<&Entry>) -> BTreeMap<String, (String, BTreeSet<String>)> {
+    let Some(section) = section else {
+        return BTreeMap::new();
+    };
+    entries(&section.body)
+        .into_iter()
+        .map(|e| {
+            (
+                e.key.clone(),
+                (format!("{}\n{}", e.value, e.body.join("\n")), dependency_names(&e)),
+            )
+        })
+        .collect()
+}
+
+fn dependency_names(entry: &Entry) -> BTreeSet<String> {
+    entries(&entry.body)
+        .into_iter()
+        .filter(|e| DEPENDENCY_SECTIONS.contains(&e.key.as_str()))
+        .flat_map(|e| entries(&e.body).into_iter().map(|d| d.key))
+        .collect()
+}
+
+/// `changed` plus every package that depends on one of them, given each package's dependents.
+fn dependents(graph: &BTreeMap<String, BTreeSet<String>>, changed: BTreeSet<String>) -> BTreeSet<String> {
+    let mut seen = changed.clone();
+    let mut queue: Vec<String> = changed.into_iter().collect();
+    while let Some(package) = queue.pop() {
+        for parent in graph.get(&package).into_iter().flatten() {
+            if seen.insert(parent.clone()) {
+                queue.push(parent.clone());
+            }
+        }
+    }
+    seen
+}
+
+/// The package a lockfile key is for: `lodash` for `lodash@4.17.21`, `/lodash@4.17.21` (v6),
+/// `/lodash/4.17.21` (v5), or `@babel/core@7.24.0(supports-color@8.1.1)` (with peers resolved).
+fn package_name(key: &str) -> String {
+    let key = key.trim_start_matches('/');
+    let key = key.split('(').next().unwrap_or(key);
+    match key[1.min(key.len())..].find('@') {
+        Some(at) => key[..at + 1].to_string(),
+        None => key.rsplit_once('/').map_or(key, |(name, _)| name).to_string(),
+    }
+}
+
+/// The repo-relative directory of an importer, empty for the root (`.`).
+fn importer_dir(key: &str) -> PathBuf {
+    if key == "." {
+        PathBuf::new()
+    } else {
+        PathBuf::from(key.trim_start_matches("./"))
+    }
+}
+
+/// The mapping entries at the indentation of the first line in `lines`, each with the lines nested
+/// under it. Comments and blank lines are skipped.
+fn entries<'a>(lines: &[&'a str]) -> Vec<Entry<'a>> {
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let lines: Vec<&'a str> = lines
+        .iter()
+        .copied()
+        .filter(|l| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .collect();
+    let Some(depth) = lines.first().map(|l| indent(l)) else {
+        return vec![];
+    };
+    let mut entries: Vec<Entry> = Vec::new();
+    for line in lines {
+        if indent(line) > depth {
+            if let Some(entry) = entries.last_mut() {
+                entry.body.push(line);
+            }
+        } else if indent(line) == depth
+            && let Some((key, value)) = split_key(line.trim())
+        {
+            entries.push(Entry {
+                key,
+                value,
+                body: Vec::new(),
+            });
+        }
+    }
+    entries
+}
+
+/// A `key: value` line's key, unquoted, and value.
+fn split_key(line: &str) -> Option<(String, &str)> {
+    if let Some(quote) = line.chars().next().filter(|c| *c == '\'' || *c == '"') {
+        let end = line[1..].find(quote)? + 1;
+        let rest = line[end + 1..].strip_prefix(':')?;
+        return Some((line[1..end].to_string(), rest.trim()));
+    }
+    let (key, value) = line
+        .split_once(": ")
+        .or_else(|| line.strip_suffix(':').map(|k| (k, "")))?;
+    Some((key.to_string(), value.trim()))
+}
+
+#[cfg(test)]
+#[path = "pnpm_lock_test.rs"]
+mod tests;
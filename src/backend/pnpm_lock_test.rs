@@ -0,0 +1,91 @@
+use super::*;
+
+const LOCK: &str = "lockfileVersion: '9.0'
+
+settings:
+  autoInstallPeers: true
+
+importers:
+
+  .:
+    devDependencies:
+      typescript:
+        specifier: ^5.4.0
+        version: 5.4.5
+
+  packages/api:
+    dependencies:
+      express:
+        specifier: ^4.19.0
+        version: 4.19.2
+
+  packages/web:
+    dependencies:
+      '@acme/ui':
+        specifier: ^1.0.0
+        version: 1.0.0(react@18.3.1)
+
+packages:
+
+  '@acme/ui@1.0.0':
+    resolution: {integrity: sha512-ui}
+
+  express@4.19.2:
+    resolution: {integrity: sha512-express}
+
+  react@18.3.1:
+    resolution: {integrity: sha512-react18}
+
+  typescript@5.4.5:
+    resolution: {integrity: sha512-ts}
+
+snapshots:
+
+  '@acme/ui@1.0.0(react@18.3.1)':
+    dependencies:
+      react: 18.3.1
+
+  express@4.19.2: {}
+
+  react@18.3.1: {}
+
+  typescript@5.4.5: {}
+";
+
+fn affected(old: &str, new: &str) -> Option<Vec<PathBuf>> {
+    affected_importers(old, new).map(|dirs| dirs.into_iter().collect())
+}
+
+#[test]
+fn a_bumped_direct_dependency_affects_its_importers() {
+    let new = LOCK
+        .replace("version: 4.19.2", "version: 4.21.0")
+        .replace("express@4.19.2", "express@4.21.0")
+        .replace("sha512-express", "sha512-express2");
+    assert_eq!(affected(LOCK, &new), Some(vec![PathBuf::from("packages/api")]));
+}
+
+#[test]
+fn a_bumped_transitive_dependency_affects_importers_using_it() {
+    let new = LOCK.replace("sha512-react18", "sha512-react18-rebuilt");
+    assert_eq!(affected(LOCK, &new), Some(vec![PathBuf::from("packages/web")]));
+}
+
+#[test]
+fn root_and_unnarrowed_changes_are_reported() {
+    let new = LOCK.replace("version: 5.4.5", "version: 5.5.0");
+    assert_eq!(affected(LOCK, &new), Some(vec![PathBuf::new()]));
+    let new = LOCK.replace("autoInstallPeers: true", "autoInstallPeers: false");
+    assert_eq!(affected(LOCK, &new), None);
+    assert_eq!(affected("lockfileVersion: '9.0'\n", "lockfileVersion: '9.0'\n"), None);
+    assert_eq!(affected(LOCK, LOCK), Some(vec![]));
+}
+
+#[test]
+fn package_names_come_from_every_lockfile_key_format() {
+    assert_eq!(package_name("lodash@4.17.21"), "lodash");
+    assert_eq!(package_name("/lodash@4.17.21"), "lodash");
+    assert_eq!(package_name("/lodash/4.17.21"), "lodash");
+    assert_eq!(package_name("@babel/core@7.24.0(supports-color@8.1.1)"), "@babel/core");
+    assert_eq!(package_name("/@babel/core/7.24.0"), "@babel/core");
+}
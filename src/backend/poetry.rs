@@ -0,0 +1,208 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use super::uv::{pytest_failures, pytest_shuffle_args, python_meta};
+use super::{Backend, Target};
+use crate::exec::Invocation;
+use crate::failures::Failure;
+use crate::git;
+
+pub struct PoetryBackend;
+
+impl PoetryBackend {
+    /// Directories of the Poetry projects in the repo, relative to the repo root.
+    fn projects(repo_root: &Path) -> Result<Vec<PathBuf>> {
+        Ok(git::find_files(repo_root, "pyproject.toml")?
+            .into_iter()
+            .filter(|m| is_poetry_project(&std::fs::read_to_string(repo_root.join(m)).unwrap_or_default()))
+            .map(|m| m.parent().map(Path::to_path_buf).unwrap_or_default())
+            .collect())
+    }
+
+    /// The project's pyproject.toml, parsed.
+    fn manifest(dir: &Path) -> toml::Table {
+        let text = std::fs::read_to_string(dir.join("pyproject.toml")).unwrap_or_default();
+        toml::from_str(&text).unwrap_or_default()
+    }
+
+    /// `poetry run <tool>` in the target's project, with `paths` relative to it.
+    fn run(dir: &Path, tool: &[&str], paths: Vec<String>) -> Invocation {
+        Invocation::new("poetry", dir)
+            .arg("run")
+            .args(tool.iter().copied())
+            .args(paths)
+    }
+}
+
+impl Backend for PoetryBackend {
+    fn name(&self) -> &str {
+        "poetry"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join("poetry.lock").exists()
+            || std::fs::read_to_string(dir.join("pyproject.toml")).is_ok_and(|t| is_poetry_project(&t))
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let projects = match Self::projects(repo_root) {
+            Ok(projects) => projects,
+            Err(e) => {
+                eprintln!("kit: failed to find Poetry projects ({e:#})");
+                return vec![];
+            }
+        };
+        affected_projects(&projects, changed_files)
+            .into_iter()
+            .map(|dir| self.resolve_target(repo_root, repo_root.join(dir)))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_string_lossy();
+        let rel = rel.replace('\\', "/");
+        let label = if rel.is_empty() { ".".to_string() } else { rel };
+        let meta = python_meta(&dir);
+        Target {
+            label,
+            dir,
+            meta,
+            files: Vec::new(),
+        }
+    }
+
+    fn build(&self, _repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Ok(targets
+            .iter()
+            .map(|t| Invocation::new("poetry", &t.dir).arg("build"))
+            .collect())
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        // Each project has its own environment, so pytest runs once per project, on the named
+        // files if there are any.
+        Ok(targets
+            .iter()
+            .map(|t| {
+                let rel = t.dir.strip_prefix(repo_root).unwrap_or(&t.dir);
+                let files = t
+                    .files
+                    .iter()
+                    .map(|f| f.strip_prefix(rel).unwrap_or(f).to_string_lossy().replace('\\', "/"))
+                    .collect();
+                Self::run(&t.dir, &["pytest"], files)
+            })
+            .collect())
+    }
+
+    fn lint(&self, _repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Ok(targets
+            .iter()
+            .map(|t| Self::run(&t.dir, tools(&Self::manifest(&t.dir)).lint, vec![".".to_string()]))
+            .collect())
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let projects = Self::projects(repo_root)?;
+        let mut files: BTreeMap<&Path, Vec<String>> = BTreeMap::new();
+        for file in changed_files {
+            if !file.extension().is_some_and(|e| e == "py" || e == "pyi") || !repo_root.join(file).exists() {
+                continue;
+            }
+            if let Some(project) = owning_project(&projects, file) {
+                let rel = file.strip_prefix(project).unwrap_or(file);
+                files
+                    .entry(project)
+                    .or_default()
+                    .push(rel.to_string_lossy().replace('\\', "/"));
+            }
+        }
+        Ok(files
+            .into_iter()
+            .map(|(project, files)| {
+                let dir = repo_root.join(project);
+                Self::run(&dir, tools(&Self::manifest(&dir)).fmt, files)
+            })
+            .collect())
+    }
+
+    fn shuffle_args(&self, seed: u64) -> Option<Vec<String>> {
+        Some(pytest_shuffle_args(seed))
+    }
+
+    fn parse_failures(&self, _repo_root: &Path, output: &str) -> Vec<Failure> {
+        pytest_failures(output)
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new("poetry", repo_root).arg("--version")]
+    }
+}
+
+/// Whether a pyproject.toml is managed by Poetry.
+fn is_poetry_project(pyproject: &str) -> bool {
+    pyproject.contains("[tool.poetry")
+}
+
+/// Projects containing a changed Python file, pyproject.toml, or poetry.lock.
+fn affected_projects(projects: &[PathBuf], changed_files: &[PathBuf]) -> BTreeSet<PathBuf> {
+    changed_files
+        .iter()
+        .filter(|f| {
+            let name = f.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            name.ends_with(".py") || name == "pyproject.toml" || name == "poetry.lock"
+        })
+        .filter_map(|f| owning_project(projects, f))
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+/// The deepest project containing a file.
+fn owning_project<'a>(projects: &'a [PathBuf], file: &Path) -> Option<&'a Path> {
+    projects
+        .iter()
+        .filter(|p| file.starts_with(p))
+        .max_by_key(|p| p.components().count())
+        .map(PathBuf::as_path)
+}
+
+/// The commands a project lints and formats with.
+struct Tools {
+    lint: &'static [&'static str],
+    fmt: &'static [&'static str],
+}
+
+/// Ruff if the project configures it or depends on it, otherwise flake8 and black.
+fn tools(manifest: &toml::Table) -> Tools {
+    let tool = manifest.get("tool");
+    let poetry = tool.and_then(|t| t.get("poetry"));
+    let groups = poetry
+        .and_then(|p| p.get("group"))
+        .and_then(|g| g.as_table())
+        .into_iter()
+        .flat_map(|g| g.values())
+        .filter_map(|g| g.get("dependencies"));
+    let mut dependencies = ["dependencies", "dev-dependencies"]
+        .iter()
+        .filter_map(|key| poetry.and_then(|p| p.get(*key)))
+        .chain(groups)
+        .filter_map(|d| d.as_table());
+    let ruff = tool.is_some_and(|t| t.get("ruff").is_some()) || dependencies.any(|d| d.contains_key("ruff"));
+    if ruff {
+        Tools {
+            lint: &["ruff", "check"],
+            fmt: &["ruff", "format"],
+        }
+    } else {
+        Tools {
+            lint: &["flake8"],
+            fmt: &["black"],
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "poetry_test.rs"]
+mod tests;
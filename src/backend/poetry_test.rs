@@ -0,0 +1,49 @@
+use super::*;
+
+fn manifest(text: &str) -> toml::Table {
+    toml::from_str(text).unwrap()
+}
+
+#[test]
+fn affected_projects_map_files_to_the_deepest_project() {
+    let projects = [PathBuf::new(), PathBuf::from("libs/core")];
+    let changed = [
+        PathBuf::from("libs/core/core/models.py"),
+        PathBuf::from("app/main.py"),
+        PathBuf::from("libs/core/README.md"),
+        PathBuf::from("docs/index.md"),
+    ];
+    assert_eq!(
+        affected_projects(&projects, &changed).into_iter().collect::<Vec<_>>(),
+        [PathBuf::new(), PathBuf::from("libs/core")]
+    );
+    assert!(affected_projects(&projects[1..], &[PathBuf::from("poetry.lock")]).is_empty());
+}
+
+#[test]
+fn tools_prefer_ruff_when_configured() {
+    let plain = manifest("[tool.poetry]\nname = \"app\"\n");
+    assert_eq!(tools(&plain).lint, ["flake8"]);
+    assert_eq!(tools(&plain).fmt, ["black"]);
+
+    let group = manifest("[tool.poetry.group.dev.dependencies]\nruff = \"^0.5\"\n");
+    assert_eq!(tools(&group).lint, ["ruff", "check"]);
+
+    let configured = manifest("[tool.ruff]\nline-length = 100\n");
+    assert_eq!(tools(&configured).fmt, ["ruff", "format"]);
+}
+
+#[test]
+fn test_runs_pytest_in_each_project_on_named_files() {
+    let repo = Path::new("/repo");
+    let app = PoetryBackend.resolve_target(repo, repo.to_path_buf());
+    let mut core = PoetryBackend.resolve_target(repo, repo.join("libs/core"));
+    core.files = vec![PathBuf::from("libs/core/tests/test_models.py")];
+    assert_eq!(
+        PoetryBackend.test(repo, &[app, core]).unwrap(),
+        [
+            Invocation::new("poetry", repo).args(["run", "pytest"]),
+            Invocation::new("poetry", &repo.join("libs/core")).args(["run", "pytest", "tests/test_models.py"]),
+        ]
+    );
+}
@@ -0,0 +1,278 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use super::uv::{pip_list_outdated, pytest_failures, pytest_paths, pytest_shuffle_args, python_files, python_meta};
+use super::{Backend, Outdated, Target};
+use crate::exec::Invocation;
+use crate::failures::Failure;
+use crate::git;
+use crate::report::Skip;
+
+/// Build backends of the projects this backend handles, by the package providing them.
+const BUILD_BACKENDS: &[&str] = &["setuptools", "hatchling"];
+
+/// Files whose change affects the project they are in.
+const PROJECT_FILES: &[&str] = &["pyproject.toml", "setup.py", "setup.cfg"];
+
+pub struct PythonBackend;
+
+impl PythonBackend {
+    /// Directories of the setuptools and Hatch projects in the repo, relative to the repo root.
+    fn projects(repo_root: &Path) -> Result<Vec<PathBuf>> {
+        let mut dirs: BTreeSet<PathBuf> = BTreeSet::new();
+        for name in ["pyproject.toml", "setup.py"] {
+            for file in git::find_files(repo_root, name)? {
+                dirs.insert(file.parent().map(Path::to_path_buf).unwrap_or_default());
+            }
+        }
+        Ok(dirs.into_iter().filter(|d| is_project(&repo_root.join(d))).collect())
+    }
+
+    /// Whether the repo lints and formats with ruff rather than flake8 and black.
+    fn uses_ruff(repo_root: &Path) -> bool {
+        if repo_root.join("ruff.toml").is_file() || repo_root.join(".ruff.toml").is_file() {
+            return true;
+        }
+        Self::projects(repo_root).unwrap_or_default().iter().any(|dir| {
+            let text = std::fs::read_to_string(repo_root.join(dir).join("pyproject.toml")).unwrap_or_default();
+            configures_ruff(&toml::from_str(&text).unwrap_or_default())
+        })
+    }
+}
+
+impl Backend for PythonBackend {
+    fn name(&self) -> &str {
+        "python"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        is_project(dir)
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let projects = match Self::projects(repo_root) {
+            Ok(projects) => projects,
+            Err(e) => {
+                eprintln!("kit: failed to find Python projects ({e:#})");
+                return vec![];
+            }
+        };
+        affected_projects(&projects, changed_files)
+            .into_iter()
+            .map(|dir| self.resolve_target(repo_root, repo_root.join(dir)))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_string_lossy();
+        let rel = rel.replace('\\', "/");
+        let label = if rel.is_empty() { ".".to_string() } else { rel };
+        let meta = python_meta(&dir);
+        Target {
+            label,
+            dir,
+            meta,
+            files: Vec::new(),
+        }
+    }
+
+    fn build(&self, _repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        // `python -m build` makes an sdist and a wheel in the project's dist/, each in a fresh
+        // environment with the build backend the project asks for.
+        Ok(targets
+            .iter()
+            .map(|t| Invocation::new("python", &t.dir).args(["-m", "build"]))
+            .collect())
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let invocation = Invocation::new("python", repo_root).args(["-m", "pytest"]);
+        Ok(vec![invocation.args(pytest_paths(targets))])
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let labels = targets.iter().map(|t| t.label.clone());
+        let invocation = if Self::uses_ruff(repo_root) {
+            Invocation::new("ruff", repo_root).arg("check")
+        } else {
+            Invocation::new("flake8", repo_root)
+        };
+        Ok(vec![invocation.args(labels)])
+    }
+
+    fn fix(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        // flake8 only reports.
+        if !Self::uses_ruff(repo_root) {
+            return Err(Skip::NotConfigured {
+                detail: "flake8 has no automatic fixes; ruff does".to_string(),
+            }
+            .into());
+        }
+        let labels = targets.iter().map(|t| t.label.clone());
+        Ok(vec![
+            Invocation::new("ruff", repo_root).args(["check", "--fix"]).args(labels),
+        ])
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let files = python_files(repo_root, changed_files);
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        let invocation = if Self::uses_ruff(repo_root) {
+            Invocation::new("ruff", repo_root).arg("format")
+        } else {
+            Invocation::new("black", repo_root)
+        };
+        Ok(vec![invocation.args(files)])
+    }
+
+    fn fmt_check(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let files = python_files(repo_root, changed_files);
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        let invocation = if Self::uses_ruff(repo_root) {
+            Invocation::new("ruff", repo_root).args(["format", "--check"])
+        } else {
+            Invocation::new("black", repo_root).arg("--check")
+        };
+        Ok(vec![invocation.args(files)])
+    }
+
+    fn extra_tools(&self, repo_root: &Path) -> Vec<Invocation> {
+        let tools: &[&str] = if Self::uses_ruff(repo_root) {
+            &["ruff"]
+        } else {
+            &["flake8", "black"]
+        };
+        tools
+            .iter()
+            .map(|tool| Invocation::new(*tool, repo_root).arg("--version"))
+            .collect()
+    }
+
+    fn outdated(&self, repo_root: &Path) -> Result<Option<Vec<Outdated>>> {
+        Ok(Some(pip_list_outdated(repo_root, "python", &["-m", "pip", "list"])?))
+    }
+
+    fn shuffle_args(&self, seed: u64) -> Option<Vec<String>> {
+        Some(pytest_shuffle_args(seed))
+    }
+
+    fn parse_failures(&self, _repo_root: &Path, output: &str) -> Vec<Failure> {
+        pytest_failures(output)
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new("python", repo_root).arg("--version")]
+    }
+}
+
+/// Whether `dir` holds a setuptools or Hatch project that neither uv nor Poetry manages: a
+/// pyproject.toml building with one of [`BUILD_BACKENDS`], or a bare setup.py.
+fn is_project(dir: &Path) -> bool {
+    if dir.join("uv.lock").exists() || dir.join("poetry.lock").exists() {
+        return false;
+    }
+    match std::fs::read_to_string(dir.join("pyproject.toml")) {
+        Ok(text) => {
+            let manifest: toml::Table = toml::from_str(&text).unwrap_or_default();
+            let managed = manifest
+                .get("tool")
+                .is_some_and(|t| t.get("uv").is_some() || t.get("poetry").is_some());
+            !managed && (builds_with_known_backend(&manifest) || dir.join("setup.py").is_file())
+        }
+        Err(_) => dir.join("setup.py").is_file(),
+    }
+}
+
+/// Whether a pyproject.toml's `[build-system]` names setuptools or hatchling, as its
+/// `build-backend` or, without one, among its `requires`.
+fn builds_with_known_backend(manifest: &toml::Table) -> bool {
+    let Some(system) = manifest.get("build-system") else {
+        return false;
+    };
+    if let Some(backend) = system.get("build-backend").and_then(|b| b.as_str()) {
+        let package = backend.split(['.', ':']).next().unwrap_or_default();
+        return BUILD_BACKENDS.contains(&package);
+    }
+    system
+        .get("requires")
+        .and_then(|r| r.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|r| r.as_str())
+        .any(|r| BUILD_BACKENDS.contains(&requirement_name(r)))
+}
+
+/// Whether a pyproject.toml configures ruff or lists it among its optional dependencies,
+/// dependency groups, or Hatch environment dependencies.
+fn configures_ruff(manifest: &toml::Table) -> bool {
+    let tool = manifest.get("tool");
+    if tool.is_some_and(|t| t.get("ruff").is_some()) {
+        return true;
+    }
+    let lists = |table: Option<&toml::Value>| -> Vec<toml::Value> {
+        table
+            .and_then(|t| t.as_table())
+            .into_iter()
+            .flat_map(|t| t.values().cloned())
+            .collect()
+    };
+    let optional = lists(manifest.get("project").and_then(|p| p.get("optional-dependencies")));
+    let groups = lists(manifest.get("dependency-groups"));
+    let hatch = lists(tool.and_then(|t| t.get("hatch")).and_then(|h| h.get("envs")))
+        .into_iter()
+        .filter_map(|env| env.get("dependencies").cloned());
+    optional
+        .into_iter()
+        .chain(groups)
+        .chain(hatch)
+        .filter_map(|list| list.as_array().cloned())
+        .flatten()
+        .filter_map(|r| r.as_str().map(String::from))
+        .any(|r| requirement_name(&r) == "ruff")
+}
+
+/// The distribution name a requirement such as `setuptools>=61` or `ruff[all] ; python_version > "3"`
+/// is for.
+fn requirement_name(requirement: &str) -> &str {
+    let end = requirement
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.'))
+        .unwrap_or(requirement.len());
+    requirement[..end].trim()
+}
+
+/// Projects containing a changed Python file or packaging file, mapped to the deepest project.
+fn affected_projects(projects: &[PathBuf], changed_files: &[PathBuf]) -> BTreeSet<PathBuf> {
+    changed_files
+        .iter()
+        .filter(|f| {
+            let name = f.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            name.ends_with(".py") || PROJECT_FILES.contains(&name)
+        })
+        .filter_map(|f| {
+            projects
+                .iter()
+                .filter(|p| f.starts_with(p))
+                .max_by_key(|p| p.components().count())
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "python_test.rs"]
+mod tests;
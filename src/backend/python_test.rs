@@ -0,0 +1,64 @@
+use super::*;
+
+fn manifest(text: &str) -> toml::Table {
+    toml::from_str(text).unwrap()
+}
+
+#[test]
+fn setuptools_and_hatch_projects_are_detected_unless_uv_or_poetry_manage_them() {
+    let dir = tempfile::tempdir().unwrap();
+    let write = |name: &str, text: &str| std::fs::write(dir.path().join(name), text).unwrap();
+    write(
+        "pyproject.toml",
+        "[build-system]\nbuild-backend = \"hatchling.build\"\n",
+    );
+    assert!(PythonBackend.detect(dir.path()));
+    write("pyproject.toml", "[tool.ruff]\nline-length = 100\n");
+    assert!(!PythonBackend.detect(dir.path()));
+    write("setup.py", "from setuptools import setup\nsetup()\n");
+    assert!(PythonBackend.detect(dir.path()));
+    write("pyproject.toml", "[tool.poetry]\nname = \"app\"\n");
+    assert!(!PythonBackend.detect(dir.path()));
+}
+
+#[test]
+fn build_backends_come_from_the_backend_or_the_requirements() {
+    assert!(builds_with_known_backend(&manifest(
+        "[build-system]\nbuild-backend = \"setuptools.build_meta:__legacy__\"\n"
+    )));
+    assert!(builds_with_known_backend(&manifest(
+        "[build-system]\nrequires = [\"setuptools>=61\", \"wheel\"]\n"
+    )));
+    assert!(!builds_with_known_backend(&manifest(
+        "[build-system]\nbuild-backend = \"flit_core.buildapi\"\n"
+    )));
+    assert!(!builds_with_known_backend(&manifest("[project]\nname = \"app\"\n")));
+}
+
+#[test]
+fn ruff_is_used_when_configured_or_depended_on() {
+    assert!(configures_ruff(&manifest("[tool.ruff]\n")));
+    assert!(configures_ruff(&manifest(
+        "[project.optional-dependencies]\ndev = [\"pytest\", \"ruff>=0.5\"]\n"
+    )));
+    assert!(configures_ruff(&manifest(
+        "[tool.hatch.envs.lint]\ndependencies = [\"ruff\"]\n"
+    )));
+    assert!(!configures_ruff(&manifest(
+        "[dependency-groups]\ndev = [\"black\", \"flake8\", \"ruff-lsp\"]\n"
+    )));
+}
+
+#[test]
+fn affected_projects_map_files_to_the_deepest_project() {
+    let projects = [PathBuf::new(), PathBuf::from("libs/core")];
+    let changed = [
+        PathBuf::from("libs/core/setup.cfg"),
+        PathBuf::from("docs/conf.md"),
+        PathBuf::from("app/main.py"),
+    ];
+    assert_eq!(
+        affected_projects(&projects, &changed).into_iter().collect::<Vec<_>>(),
+        [PathBuf::new(), PathBuf::from("libs/core")]
+    );
+}
@@ -0,0 +1,307 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use regex::Regex;
+
+use super::{Backend, Target, TargetMeta, which_exists};
+use crate::exec::Invocation;
+use crate::{git, paths};
+
+/// A module call's local source: `source = "../modules/network"`. Registry and git sources are
+/// outside the repo.
+static LOCAL_SOURCE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"(?m)^\s*source\s*=\s*"(\.\.?/[^"]*)""#).unwrap());
+
+/// Files at the repo root that mark a Terraform repo whose modules all live in subdirectories: the
+/// provider lock file, tfenv's and tofuenv's version pins, and the TFLint config.
+const ROOT_MARKERS: &[&str] = &[
+    ".terraform.lock.hcl",
+    ".terraform-version",
+    ".opentofu-version",
+    ".tflint.hcl",
+];
+
+/// Extensions of the files `terraform fmt` formats.
+const FORMATTED: &[&str] = &["tf", "tfvars", "hcl"];
+
+/// A directory of .tf files.
+#[derive(Debug, Clone, PartialEq)]
+struct Module {
+    /// Directory relative to the repo root.
+    dir: PathBuf,
+    /// Directories of the local modules it calls.
+    calls: BTreeSet<PathBuf>,
+    /// Whether it has `terraform test` files (`*.tftest.hcl`, next to it or in `tests/`).
+    tftest: bool,
+    /// Directories below it with Terratest tests.
+    terratest: Vec<PathBuf>,
+}
+
+pub struct TerraformBackend;
+
+impl TerraformBackend {
+    /// `terraform`, or OpenTofu's `tofu` when only that is installed.
+    fn tool() -> &'static str {
+        if !which_exists("terraform") && which_exists("tofu") {
+            "tofu"
+        } else {
+            "terraform"
+        }
+    }
+
+    fn modules(repo_root: &Path) -> Result<Vec<Module>> {
+        let mut sources: BTreeMap<PathBuf, String> = BTreeMap::new();
+        for file in git::find_files(repo_root, "*.tf")? {
+            let text = std::fs::read_to_string(repo_root.join(&file)).unwrap_or_default();
+            let dir = file.parent().unwrap_or(Path::new("")).to_path_buf();
+            let entry = sources.entry(dir).or_default();
+            entry.push_str(&text);
+            entry.push('\n');
+        }
+        let tftests = git::find_files(repo_root, "*.tftest.hcl")?;
+        let mut terratest = Vec::new();
+        for file in git::find_files(repo_root, "*_test.go")? {
+            let text = std::fs::read_to_string(repo_root.join(&file)).unwrap_or_default();
+            if text.contains("github.com/gruntwork-io/terratest") {
+                terratest.push(file.parent().unwrap_or(Path::new("")).to_path_buf());
+            }
+        }
+        terratest.sort();
+        terratest.dedup();
+        let dirs: Vec<PathBuf> = sources.keys().cloned().collect();
+        Ok(sources
+            .into_iter()
+            .map(|(dir, text)| Module {
+                calls: local_sources(&dir, &text),
+                tftest: tftests.iter().any(|f| {
+                    let parent = f.parent().unwrap_or(Path::new(""));
+                    parent == dir || parent == dir.join("tests")
+                }),
+                terratest: terratest
+                    .iter()
+                    .filter(|t| owning_module(&dirs, t) == Some(&dir))
+                    .cloned()
+                    .collect(),
+                dir,
+            })
+            .collect())
+    }
+
+    /// `init` in a module, which installs its providers and modules. Only `plan` needs the state
+    /// backend configured.
+    fn init(repo_root: &Path, dir: &Path, backend: bool) -> Invocation {
+        let invocation = Invocation::new(Self::tool(), &repo_root.join(dir)).args(["init", "-input=false"]);
+        if backend {
+            invocation
+        } else {
+            invocation.arg("-backend=false")
+        }
+    }
+}
+
+impl Backend for TerraformBackend {
+    fn name(&self) -> &str {
+        "terraform"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        ROOT_MARKERS.iter().any(|m| dir.join(m).is_file())
+            || std::fs::read_dir(dir).is_ok_and(|entries| {
+                entries
+                    .flatten()
+                    .any(|e| e.path().extension().is_some_and(|ext| ext == "tf"))
+            })
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let modules = match Self::modules(repo_root) {
+            Ok(modules) => modules,
+            Err(e) => {
+                eprintln!("kit: failed to find Terraform modules ({e:#})");
+                return vec![];
+            }
+        };
+        affected_modules(&modules, changed_files)
+            .into_iter()
+            .filter_map(|dir| modules.iter().find(|m| m.dir == dir))
+            .map(|m| module_target(repo_root, &modules, m))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_path_buf();
+        let modules = Self::modules(repo_root).unwrap_or_default();
+        if let Some(module) = modules.iter().find(|m| m.dir == rel) {
+            return module_target(repo_root, &modules, module);
+        }
+        Target {
+            label: label(&rel),
+            dir,
+            meta: TargetMeta::default(),
+            files: Vec::new(),
+        }
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let tool = Self::tool();
+        let mut plan = Vec::new();
+        for target in targets {
+            plan.push(Self::init(repo_root, &rel_dir(repo_root, target), false));
+            plan.push(Invocation::new(tool, &target.dir).arg("validate"));
+        }
+        Ok(plan)
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let modules = Self::modules(repo_root)?;
+        let tool = Self::tool();
+        let mut plan = Vec::new();
+        for target in targets {
+            let dir = rel_dir(repo_root, target);
+            let Some(module) = modules.iter().find(|m| m.dir == dir) else {
+                continue;
+            };
+            if module.tftest {
+                plan.push(Self::init(repo_root, &dir, false));
+                plan.push(Invocation::new(tool, &target.dir).arg("test"));
+            }
+            for tests in &module.terratest {
+                plan.push(Invocation::new("go", &repo_root.join(tests)).args(["test", "-timeout", "60m", "./..."]));
+            }
+            // A root configuration without tests of its own is checked by planning it.
+            if !module.tftest && module.terratest.is_empty() && is_root(&modules, module) {
+                plan.push(Self::init(repo_root, &dir, true));
+                plan.push(Invocation::new(tool, &target.dir).args(["plan", "-input=false", "-lock=false"]));
+            }
+        }
+        Ok(plan)
+    }
+
+    fn lint(&self, _repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        Ok(targets.iter().map(|t| Invocation::new("tflint", &t.dir)).collect())
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let files = formatted_files(repo_root, changed_files);
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![Invocation::new(Self::tool(), repo_root).arg("fmt").args(files)])
+    }
+
+    fn fmt_check(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let files = formatted_files(repo_root, changed_files);
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![
+            Invocation::new(Self::tool(), repo_root)
+                .args(["fmt", "-check"])
+                .args(files),
+        ])
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new(Self::tool(), repo_root).arg("version")]
+    }
+}
+
+fn label(dir: &Path) -> String {
+    let rel = dir.to_string_lossy().replace('\\', "/");
+    if rel.is_empty() { ".".to_string() } else { rel }
+}
+
+fn rel_dir(repo_root: &Path, target: &Target) -> PathBuf {
+    target.dir.strip_prefix(repo_root).unwrap_or(&target.dir).to_path_buf()
+}
+
+fn module_target(repo_root: &Path, modules: &[Module], module: &Module) -> Target {
+    let kind = if is_root(modules, module) {
+        "root_module"
+    } else {
+        "module"
+    };
+    let meta = TargetMeta {
+        kind: Some(kind.to_string()),
+        language: Some("terraform".to_string()),
+        is_test: Some(module.tftest || !module.terratest.is_empty()),
+        size: None,
+    };
+    Target {
+        label: label(&module.dir),
+        dir: repo_root.join(&module.dir),
+        meta,
+        files: Vec::new(),
+    }
+}
+
+/// Whether no other module in the repo calls this one, so it is applied on its own.
+fn is_root(modules: &[Module], module: &Module) -> bool {
+    !modules.iter().any(|m| m.calls.contains(&module.dir))
+}
+
+/// Directories of the local modules the configuration in `dir` calls, relative to the repo root.
+fn local_sources(dir: &Path, text: &str) -> BTreeSet<PathBuf> {
+    LOCAL_SOURCE
+        .captures_iter(text)
+        .filter_map(|c| paths::normalize(&dir.join(&c[1])))
+        .collect()
+}
+
+/// The deepest module directory at or above `path`.
+fn owning_module<'a>(dirs: &'a [PathBuf], path: &Path) -> Option<&'a PathBuf> {
+    dirs.iter()
+        .filter(|d| path.starts_with(d))
+        .max_by_key(|d| d.components().count())
+}
+
+/// Modules containing a changed file, plus every module calling an affected one. Besides
+/// Terraform files, a module's templates, scripts, and Terratest tests count, except for a module
+/// at the repo root, which would otherwise own every file in the repo. Documentation never counts.
+fn affected_modules(modules: &[Module], changed_files: &[PathBuf]) -> BTreeSet<PathBuf> {
+    let dirs: Vec<PathBuf> = modules.iter().map(|m| m.dir.clone()).collect();
+    let mut affected: BTreeSet<PathBuf> = BTreeSet::new();
+    for file in changed_files {
+        let ext = file.extension().and_then(|e| e.to_str()).unwrap_or("");
+        if ext == "md" {
+            continue;
+        }
+        let Some(dir) = owning_module(&dirs, file) else {
+            continue;
+        };
+        if FORMATTED.contains(&ext) || !dir.as_os_str().is_empty() {
+            affected.insert(dir.clone());
+        }
+    }
+    loop {
+        let callers: Vec<PathBuf> = modules
+            .iter()
+            .filter(|m| !affected.contains(&m.dir) && m.calls.iter().any(|c| affected.contains(c)))
+            .map(|m| m.dir.clone())
+            .collect();
+        if callers.is_empty() {
+            return affected;
+        }
+        affected.extend(callers);
+    }
+}
+
+/// The changed files `fmt` formats that still exist. Lock files are generated, so they are left
+/// alone.
+fn formatted_files(repo_root: &Path, changed_files: &[PathBuf]) -> Vec<String> {
+    changed_files
+        .iter()
+        .filter(|f| {
+            f.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| FORMATTED.contains(&e))
+        })
+        .filter(|f| !f.ends_with(".terraform.lock.hcl") && repo_root.join(f).is_file())
+        .map(|f| f.to_string_lossy().replace('\\', "/"))
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "terraform_test.rs"]
+mod tests;
@@ -0,0 +1,106 @@
+use super::*;
+
+const STAGING: &str = r#"
+module "network" {
+  source = "../../modules/network"
+  cidr   = "10.0.0.0/16"
+}
+
+module "dns" {
+  source  = "terraform-aws-modules/route53/aws"
+  version = "~> 2.0"
+}
+
+module "app" {
+  source = "./app"
+}
+"#;
+
+fn module(dir: &str, calls: &[&str]) -> Module {
+    Module {
+        dir: PathBuf::from(dir),
+        calls: calls.iter().map(PathBuf::from).collect(),
+        tftest: false,
+        terratest: vec![],
+    }
+}
+
+fn modules() -> Vec<Module> {
+    vec![
+        module("", &[]),
+        module("envs/staging", &["modules/network", "envs/staging/app"]),
+        module("envs/staging/app", &[]),
+        module("envs/prod", &["modules/network"]),
+        module("modules/network", &["modules/subnets"]),
+        module("modules/subnets", &[]),
+    ]
+}
+
+#[test]
+fn local_module_sources_are_resolved_from_the_calling_directory() {
+    let sources: Vec<PathBuf> = local_sources(Path::new("envs/staging"), STAGING).into_iter().collect();
+    assert_eq!(
+        sources,
+        [PathBuf::from("envs/staging/app"), PathBuf::from("modules/network")]
+    );
+}
+
+#[test]
+fn changes_affect_the_module_and_every_caller() {
+    let affected: Vec<PathBuf> = affected_modules(&modules(), &[PathBuf::from("modules/subnets/main.tf")])
+        .into_iter()
+        .collect();
+    assert_eq!(
+        affected,
+        [
+            PathBuf::from("envs/prod"),
+            PathBuf::from("envs/staging"),
+            PathBuf::from("modules/network"),
+            PathBuf::from("modules/subnets"),
+        ]
+    );
+}
+
+#[test]
+fn templates_count_but_documentation_and_unrelated_root_files_do_not() {
+    let changed = [
+        PathBuf::from("envs/staging/app/templates/user_data.sh"),
+        PathBuf::from("modules/network/README.md"),
+        PathBuf::from("services/api/main.go"),
+    ];
+    let affected: Vec<PathBuf> = affected_modules(&modules(), &changed).into_iter().collect();
+    assert_eq!(
+        affected,
+        [PathBuf::from("envs/staging"), PathBuf::from("envs/staging/app")]
+    );
+
+    let affected = affected_modules(&modules(), &[PathBuf::from("terraform.tfvars")]);
+    assert!(affected.contains(Path::new("")));
+}
+
+#[test]
+fn modules_nothing_calls_are_roots() {
+    let modules = modules();
+    let roots: Vec<&Path> = modules
+        .iter()
+        .filter(|m| is_root(&modules, m))
+        .map(|m| m.dir.as_path())
+        .collect();
+    assert_eq!(
+        roots,
+        [Path::new(""), Path::new("envs/staging"), Path::new("envs/prod")]
+    );
+}
+
+#[test]
+fn detection_needs_tf_files_or_a_marker_at_the_root() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("infra")).unwrap();
+    std::fs::write(dir.path().join("infra/main.tf"), "").unwrap();
+    assert!(!TerraformBackend.detect(dir.path()));
+    std::fs::write(dir.path().join(".tflint.hcl"), "").unwrap();
+    assert!(TerraformBackend.detect(dir.path()));
+    std::fs::remove_file(dir.path().join(".tflint.hcl")).unwrap();
+    std::fs::write(dir.path().join("main.tf"), "").unwrap();
+    assert!(TerraformBackend.detect(dir.path()));
+}
@@ -0,0 +1,358 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result};
+use ignore::WalkBuilder;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use regex::Regex;
+use serde::Deserialize;
+
+use super::{Backend, Outdated, Target, TargetMeta};
+use crate::error::KitError;
+use crate::exec::Invocation;
+use crate::failures::Failure;
+use crate::git;
+
+static PYTEST_SECTION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^_{3,} (.+?) _{3,}$").unwrap());
+static PYTEST_LOCATION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\S+\.py):(\d+): ").unwrap());
+static PYTEST_SUMMARY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:FAILED|ERROR) (\S+?)(?: - (.*))?$").unwrap());
+
+pub struct UvBackend;
+
+impl UvBackend {
+    /// The root pyproject.toml, parsed.
+    fn root_project(repo_root: &Path) -> Option<toml::Table> {
+        let text = std::fs::read_to_string(repo_root.join("pyproject.toml")).ok()?;
+        toml::from_str(&text).ok()
+    }
+
+    /// Directories of the workspace members, relative to the repo root.
+    fn members(repo_root: &Path) -> Result<Vec<PathBuf>> {
+        let root = Self::root_project(repo_root).unwrap_or_default();
+        let manifests = git::find_files(repo_root, "pyproject.toml")?;
+        workspace_members(repo_root, &root, &manifests)
+    }
+
+    /// The `[project] name` of the member in `dir`.
+    fn package_name(dir: &Path) -> Result<String> {
+        let path = dir.join("pyproject.toml");
+        let text = std::fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        let manifest: toml::Table = toml::from_str(&text).with_context(|| format!("invalid {}", path.display()))?;
+        manifest
+            .get("project")
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+            .map(String::from)
+            .with_context(|| format!("{} has no [project] name", path.display()))
+    }
+}
+
+impl Backend for UvBackend {
+    fn name(&self) -> &str {
+        "uv"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join("uv.lock").exists()
+            || std::fs::read_to_string(dir.join("pyproject.toml")).is_ok_and(|t| t.contains("[tool.uv"))
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let members = match Self::members(repo_root) {
+            Ok(members) => members,
+            Err(e) => {
+                eprintln!("kit: failed to read uv workspace members ({e:#})");
+                return vec![];
+            }
+        };
+        let mut affected: BTreeSet<PathBuf> = BTreeSet::new();
+        for file in changed_files {
+            let name = file.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if name == "uv.lock" && file.parent().is_some_and(|p| p.as_os_str().is_empty()) {
+                // The lockfile pins every member's dependencies, so everything is affected.
+                affected.insert(PathBuf::new());
+                continue;
+            }
+            if !(name.ends_with(".py") || name == "pyproject.toml") {
+                continue;
+            }
+            let member = members
+                .iter()
+                .filter(|m| file.starts_with(m))
+                .max_by_key(|m| m.components().count());
+            if let Some(member) = member {
+                affected.insert(member.clone());
+            }
+        }
+        affected
+            .into_iter()
+            .map(|dir| self.resolve_target(repo_root, repo_root.join(dir)))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_string_lossy();
+        let rel = rel.replace('\\', "/");
+        let label = if rel.is_empty() { ".".to_string() } else { rel };
+        let meta = python_meta(&dir);
+        Target {
+            label,
+            dir,
+            meta,
+            files: Vec::new(),
+        }
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.iter().any(|t| t.label == ".") {
+            return Ok(vec![Invocation::new("uv", repo_root).args(["build", "--all-packages"])]);
+        }
+        targets
+            .iter()
+            .map(|t| {
+                let name = Self::package_name(&t.dir)?;
+                Ok(Invocation::new("uv", repo_root).args(["build", "--package", &name]))
+            })
+            .collect()
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let invocation = Invocation::new("uv", repo_root).args(["run", "pytest"]);
+        Ok(vec![invocation.args(pytest_paths(targets))])
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let labels = targets.iter().map(|t| t.label.as_str());
+        Ok(vec![Invocation::new("ruff", repo_root).arg("check").args(labels)])
+    }
+
+    fn fix(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let labels = targets.iter().map(|t| t.label.as_str());
+        Ok(vec![
+            Invocation::new("ruff", repo_root).args(["check", "--fix"]).args(labels),
+        ])
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let files: Vec<String> = python_files(repo_root, changed_files);
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![Invocation::new("ruff", repo_root).arg("format").args(files)])
+    }
+
+    fn fmt_check(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let files: Vec<String> = python_files(repo_root, changed_files);
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![
+            Invocation::new("ruff", repo_root)
+                .args(["format", "--check"])
+                .args(files),
+        ])
+    }
+
+    fn shuffle_args(&self, seed: u64) -> Option<Vec<String>> {
+        Some(pytest_shuffle_args(seed))
+    }
+
+    fn parse_failures(&self, _repo_root: &Path, output: &str) -> Vec<Failure> {
+        pytest_failures(output)
+    }
+
+    fn outdated(&self, repo_root: &Path) -> Result<Option<Vec<Outdated>>> {
+        Ok(Some(pip_list_outdated(repo_root, "uv", &["pip", "list"])?))
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new("uv", repo_root).arg("--version")]
+    }
+
+    fn extra_tools(&self, repo_root: &Path) -> Vec<Invocation> {
+        vec![Invocation::new("ruff", repo_root).arg("--version")]
+    }
+}
+
+/// Directories of the members of the workspace defined by the root pyproject.toml, among the
+/// directories of `manifests`. The root is included (as an empty path) when it is a project itself
+/// rather than a virtual workspace.
+fn workspace_members(repo_root: &Path, root: &toml::Table, manifests: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut members = Vec::new();
+    if root.contains_key("project") {
+        members.push(PathBuf::new());
+    }
+    let workspace = root
+        .get("tool")
+        .and_then(|t| t.get("uv"))
+        .and_then(|u| u.get("workspace"));
+    let Some(workspace) = workspace else {
+        return Ok(members);
+    };
+    let include = globs(repo_root, workspace.get("members"))?;
+    let exclude = globs(repo_root, workspace.get("exclude"))?;
+    for manifest in manifests {
+        let Some(dir) = manifest.parent().filter(|d| !d.as_os_str().is_empty()) else {
+            continue;
+        };
+        let path = repo_root.join(dir);
+        if include.matched(&path, true).is_ignore() && !exclude.matched(&path, true).is_ignore() {
+            members.push(dir.to_path_buf());
+        }
+    }
+    Ok(members)
+}
+
+/// Paths to pass to pytest: files named on the command line, or the target directories. Empty if
+/// the whole workspace is a target, so pytest uses its configured test paths.
+pub fn pytest_paths(targets: &[Target]) -> Vec<String> {
+    if targets.iter().any(|t| t.label == "." && t.files.is_empty()) {
+        return vec![];
+    }
+    targets
+        .iter()
+        .flat_map(|t| {
+            if t.files.is_empty() {
+                vec![t.label.clone()]
+            } else {
+                t.files.iter().map(|f| f.to_string_lossy().replace('\\', "/")).collect()
+            }
+        })
+        .collect()
+}
+
+/// Failing tests in pytest output, from the `FAILED` and `ERROR` lines of its short test summary.
+/// The line is the last traceback entry in the test's own file.
+pub fn pytest_failures(output: &str) -> Vec<Failure> {
+    let mut locations: BTreeMap<String, Vec<(String, u32)>> = BTreeMap::new();
+    let mut section: Option<String> = None;
+    let mut failures = Vec::new();
+    for line in output.lines() {
+        if let Some(c) = PYTEST_SECTION.captures(line) {
+            section = Some(c[1].to_string());
+        } else if let Some(c) = PYTEST_LOCATION.captures(line)
+            && let Some(section) = &section
+            && let Ok(n) = c[2].parse()
+        {
+            locations
+                .entry(section.clone())
+                .or_default()
+                .push((c[1].to_string(), n));
+        } else if let Some(c) = PYTEST_SUMMARY.captures(line) {
+            let id = &c[1];
+            let (file, name) = id.split_once("::").unwrap_or((id, ""));
+            let line = locations
+                .get(&name.replace("::", "."))
+                .and_then(|l| l.iter().rev().find(|(f, _)| f == file))
+                .map(|(_, n)| *n);
+            failures.push(Failure {
+                test: id.to_string(),
+                file: Some(PathBuf::from(file)),
+                line,
+                message: c.get(2).map_or("", |m| m.as_str()).to_string(),
+            });
+        }
+    }
+    failures
+}
+
+/// pytest-randomly's option fixing the seed it shuffles tests with.
+pub fn pytest_shuffle_args(seed: u64) -> Vec<String> {
+    vec![format!("--randomly-seed={seed}")]
+}
+
+/// A package in `pip list --outdated --format json` output.
+#[derive(Deserialize)]
+struct ListedPackage {
+    name: String,
+    version: String,
+    latest_version: String,
+}
+
+/// The packages installed in the repo's environment that have newer releases, from `<program>
+/// <list...> --outdated --format json`, which pip and uv both understand.
+pub fn pip_list_outdated(repo_root: &Path, program: &str, list: &[&str]) -> Result<Vec<Outdated>> {
+    let output = Command::new(program)
+        .args(list)
+        .args(["--outdated", "--format", "json"])
+        .current_dir(repo_root)
+        .output()
+        .map_err(|e| KitError::spawn(program, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("{program} {} failed: {}", list.join(" "), stderr.trim());
+    }
+    pip_outdated(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn pip_outdated(json: &str) -> Result<Vec<Outdated>> {
+    let packages: Vec<ListedPackage> = serde_json::from_str(json).context("invalid JSON from pip list")?;
+    Ok(packages
+        .into_iter()
+        .map(|p| Outdated {
+            name: p.name,
+            current: p.version,
+            latest: p.latest_version,
+            dir: PathBuf::new(),
+        })
+        .collect())
+}
+
+/// Changed Python files that still exist.
+pub fn python_files(repo_root: &Path, changed_files: &[PathBuf]) -> Vec<String> {
+    changed_files
+        .iter()
+        .filter(|f| f.extension().is_some_and(|e| e == "py" || e == "pyi"))
+        .filter(|f| repo_root.join(f).exists())
+        .map(|f| f.to_string_lossy().replace('\\', "/"))
+        .collect()
+}
+
+/// Count the Python files under a directory and note whether any are pytest test modules.
+pub fn python_meta(dir: &Path) -> TargetMeta {
+    let mut files = 0;
+    let mut tests = false;
+    for entry in WalkBuilder::new(dir).build().flatten() {
+        let name = entry.file_name().to_string_lossy();
+        if name.ends_with(".py") {
+            files += 1;
+            tests |= name.starts_with("test_") || name.ends_with("_test.py");
+        }
+    }
+    TargetMeta {
+        kind: Some("package".to_string()),
+        language: Some("python".to_string()),
+        is_test: Some(tests),
+        size: Some(files),
+    }
+}
+
+/// A matcher for a list of workspace globs, anchored at the repo root.
+fn globs(repo_root: &Path, patterns: Option<&toml::Value>) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(repo_root);
+    for pattern in patterns.and_then(|p| p.as_array()).into_iter().flatten() {
+        if let Some(pattern) = pattern.as_str() {
+            let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+            builder
+                .add_line(None, &format!("/{pattern}"))
+                .with_context(|| format!("invalid workspace pattern `{pattern}`"))?;
+        }
+    }
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+#[path = "uv_test.rs"]
+mod tests;
@@ -0,0 +1,108 @@
+use super::*;
+
+fn root(text: &str) -> toml::Table {
+    toml::from_str(text).unwrap()
+}
+
+#[test]
+fn workspace_members_follow_globs_and_excludes() {
+    let repo = Path::new("/repo");
+    let manifests = [
+        PathBuf::from("pyproject.toml"),
+        PathBuf::from("packages/api/pyproject.toml"),
+        PathBuf::from("packages/legacy/pyproject.toml"),
+        PathBuf::from("tools/pyproject.toml"),
+    ];
+    let virtual_root = root("[tool.uv.workspace]\nmembers = [\"packages/*\"]\nexclude = [\"packages/legacy\"]\n");
+    assert_eq!(
+        workspace_members(repo, &virtual_root, &manifests).unwrap(),
+        [PathBuf::from("packages/api")]
+    );
+
+    let project_root = root("[project]\nname = \"app\"\n");
+    assert_eq!(
+        workspace_members(repo, &project_root, &manifests).unwrap(),
+        [PathBuf::new()]
+    );
+}
+
+#[test]
+fn pytest_paths_prefer_named_files() {
+    let repo = Path::new("/repo");
+    let mut api = UvBackend.resolve_target(repo, repo.join("packages/api"));
+    let cli = UvBackend.resolve_target(repo, repo.join("packages/cli"));
+    assert_eq!(
+        pytest_paths(&[api.clone(), cli.clone()]),
+        ["packages/api", "packages/cli"]
+    );
+
+    api.files = vec![PathBuf::from("packages/api/tests/test_routes.py")];
+    assert_eq!(
+        pytest_paths(&[api, cli]),
+        ["packages/api/tests/test_routes.py", "packages/cli"]
+    );
+    assert!(pytest_paths(&[UvBackend.resolve_target(repo, repo.to_path_buf())]).is_empty());
+}
+
+#[test]
+fn pytest_failures_reads_the_summary_and_traceback_lines() {
+    let output = "\
+=================================== FAILURES ===================================
+___________________________________ test_add ___________________________________
+
+    def test_add():
+>       assert add(1, 2) == 4
+E       assert 3 == 4
+
+tests/test_math.py:4: AssertionError
+____________________________ TestThing.test_method _____________________________
+
+tests/test_math.py:12: in test_method
+    helper()
+src/pkg/mod.py:10: in helper
+    raise ValueError(\"x\")
+E   ValueError: x
+=========================== short test summary info ============================
+FAILED tests/test_math.py::test_add - assert 3 == 4
+FAILED tests/test_math.py::TestThing::test_method - ValueError: x
+ERROR tests/test_other.py
+";
+    let failure = |test: &str, file: &str, line, message: &str| Failure {
+        test: test.to_string(),
+        file: Some(PathBuf::from(file)),
+        line,
+        message: message.to_string(),
+    };
+    assert_eq!(
+        pytest_failures(output),
+        [
+            failure(
+                "tests/test_math.py::test_add",
+                "tests/test_math.py",
+                Some(4),
+                "assert 3 == 4"
+            ),
+            failure(
+                "tests/test_math.py::TestThing::test_method",
+                "tests/test_math.py",
+                Some(12),
+                "ValueError: x"
+            ),
+            failure("tests/test_other.py", "tests/test_other.py", None, ""),
+        ]
+    );
+}
+
+#[test]
+fn pip_outdated_reads_list_json() {
+    let json = r#"[{"name": "requests", "version": "2.31.0", "latest_version": "2.32.3", "latest_filetype": "wheel"}]"#;
+    assert_eq!(
+        pip_outdated(json).unwrap(),
+        [Outdated {
+            name: "requests".to_string(),
+            current: "2.31.0".to_string(),
+            latest: "2.32.3".to_string(),
+            dir: PathBuf::new(),
+        }]
+    );
+}
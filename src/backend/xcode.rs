@@ -0,0 +1,314 @@
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use regex::Regex;
+
+use super::{Backend, Target, TargetMeta};
+use crate::config::Config;
+use crate::exec::Invocation;
+use crate::failures::Failure;
+use crate::git;
+
+/// A target a scheme builds or tests, in its `<BuildableReference>`.
+static BLUEPRINT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"BlueprintName\s*=\s*"([^"]+)""#).unwrap());
+/// A failed XCTest assertion: `/src/AppTests/CartTests.swift:42: error: -[AppTests.CartTests
+/// testTotal] : XCTAssertEqual failed: ("1") is not equal to ("2")`.
+static XCTEST_FAILURE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(.+?):(\d+): error: -\[(\S+) (\w+)\] : (.*)$").unwrap());
+
+/// Files whose change affects every scheme of the project next to them.
+const PROJECT_FILES: &[&str] = &["Podfile", "Podfile.lock", "Package.swift", "Package.resolved"];
+
+/// Files at the repo root that mark an Apple project, besides a project or workspace.
+const ROOT_MARKERS: &[&str] = &["Podfile", "Package.swift"];
+
+/// A shared scheme of an Xcode project or workspace.
+#[derive(Debug, Clone, PartialEq)]
+struct Scheme {
+    name: String,
+    /// Directory of the project, relative to the repo root.
+    dir: PathBuf,
+    /// The workspace or project to build the scheme from, relative to the repo root.
+    container: PathBuf,
+    /// Names of the targets it builds and tests, which are also the names of their source folders.
+    blueprints: BTreeSet<String>,
+    has_tests: bool,
+}
+
+pub struct XcodeBackend;
+
+impl XcodeBackend {
+    /// Shared schemes, from the `xcshareddata/xcschemes` of every project and workspace. A project
+    /// with a workspace next to it, as CocoaPods sets up, is built through the workspace.
+    fn schemes(repo_root: &Path) -> Result<Vec<Scheme>> {
+        let mut schemes: Vec<Scheme> = Vec::new();
+        for file in git::find_files(repo_root, "*.xcscheme")? {
+            let Some(container) = file.ancestors().find(|a| is_container(a)) else {
+                continue;
+            };
+            let dir = container.parent().unwrap_or(Path::new("")).to_path_buf();
+            let workspace = std::fs::read_dir(repo_root.join(&dir)).ok().and_then(|entries| {
+                entries
+                    .flatten()
+                    .map(|e| e.path())
+                    .filter(|p| p.extension().is_some_and(|e| e == "xcworkspace"))
+                    .min()
+            });
+            let container = match workspace {
+                Some(workspace) => dir.join(workspace.file_name().unwrap_or_default()),
+                None => container.to_path_buf(),
+            };
+            let name = file.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+            let text = std::fs::read_to_string(repo_root.join(&file)).unwrap_or_default();
+            let scheme = parse_scheme(&name, dir, container, &text);
+            if !schemes.iter().any(|s| s.name == scheme.name && s.dir == scheme.dir) {
+                schemes.push(scheme);
+            }
+        }
+        Ok(schemes)
+    }
+
+    /// `xcodebuild <action>` for each target's scheme.
+    fn xcodebuild(repo_root: &Path, action: &str, targets: &[&Target]) -> Result<Vec<Invocation>> {
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+        let schemes = Self::schemes(repo_root)?;
+        let destination = Config::load(repo_root)?.xcode.destination;
+        let mut plan = Vec::new();
+        for target in targets {
+            let Some(scheme) = schemes.iter().find(|s| s.name == target.label) else {
+                continue;
+            };
+            let flag = if scheme.container.extension().is_some_and(|e| e == "xcworkspace") {
+                "-workspace"
+            } else {
+                "-project"
+            };
+            let mut invocation = Invocation::new("xcodebuild", repo_root).arg(action).args([
+                flag,
+                &scheme.container.to_string_lossy(),
+                "-scheme",
+                &scheme.name,
+            ]);
+            if let Some(destination) = &destination {
+                invocation = invocation.args(["-destination", destination]);
+            }
+            plan.push(invocation);
+        }
+        Ok(plan)
+    }
+
+    /// `pod install` in each target's project whose Pods are out of date with its Podfile.lock,
+    /// which is how CocoaPods itself tells.
+    fn pod_install(targets: &[&Target]) -> Vec<Invocation> {
+        let dirs: BTreeSet<&PathBuf> = targets.iter().map(|t| &t.dir).collect();
+        dirs.into_iter()
+            .filter(|dir| dir.join("Podfile").is_file())
+            .filter(|dir| {
+                let lock = std::fs::read(dir.join("Podfile.lock")).ok();
+                let manifest = std::fs::read(dir.join("Pods").join("Manifest.lock")).ok();
+                lock.is_none() || lock != manifest
+            })
+            .map(|dir| Invocation::new("pod", dir).arg("install"))
+            .collect()
+    }
+}
+
+impl Backend for XcodeBackend {
+    fn name(&self) -> &str {
+        "xcode"
+    }
+
+    fn detect(&self, dir: &Path) -> bool {
+        ROOT_MARKERS.iter().any(|m| dir.join(m).is_file())
+            || std::fs::read_dir(dir).is_ok_and(|entries| entries.flatten().any(|e| is_container(&e.path())))
+    }
+
+    fn affected_targets(&self, repo_root: &Path, _base: &str, changed_files: &[PathBuf]) -> Vec<Target> {
+        let schemes = match Self::schemes(repo_root) {
+            Ok(schemes) => schemes,
+            Err(e) => {
+                eprintln!("kit: failed to find Xcode schemes ({e:#})");
+                return vec![];
+            }
+        };
+        affected_schemes(&schemes, changed_files)
+            .into_iter()
+            .map(|s| scheme_target(repo_root, s))
+            .collect()
+    }
+
+    fn resolve_target(&self, repo_root: &Path, dir: PathBuf) -> Target {
+        let rel = dir.strip_prefix(repo_root).unwrap_or(&dir).to_path_buf();
+        let schemes = Self::schemes(repo_root).unwrap_or_default();
+        // The scheme building the target the directory is the source folder of, or else the first
+        // scheme of the project containing it.
+        let scheme = schemes
+            .iter()
+            .find(|s| rel.starts_with(&s.dir) && blueprint_in(s, &rel))
+            .or_else(|| {
+                schemes
+                    .iter()
+                    .filter(|s| rel.starts_with(&s.dir))
+                    .max_by_key(|s| s.dir.components().count())
+            });
+        match scheme {
+            Some(scheme) => scheme_target(repo_root, scheme),
+            None => Target {
+                label: rel.to_string_lossy().replace('\\', "/"),
+                dir,
+                meta: TargetMeta::default(),
+                files: Vec::new(),
+            },
+        }
+    }
+
+    fn build(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let targets: Vec<&Target> = targets.iter().collect();
+        let mut plan = Self::pod_install(&targets);
+        plan.extend(Self::xcodebuild(repo_root, "build", &targets)?);
+        Ok(plan)
+    }
+
+    fn test(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let targets: Vec<&Target> = targets.iter().filter(|t| t.meta.is_test != Some(false)).collect();
+        let mut plan = Self::pod_install(&targets);
+        plan.extend(Self::xcodebuild(repo_root, "test", &targets)?);
+        Ok(plan)
+    }
+
+    fn lint(&self, repo_root: &Path, targets: &[Target]) -> Result<Vec<Invocation>> {
+        let schemes = Self::schemes(repo_root)?;
+        let folders: BTreeSet<String> = targets
+            .iter()
+            .filter_map(|t| schemes.iter().find(|s| s.name == t.label))
+            .flat_map(|s| s.blueprints.iter().map(|b| s.dir.join(b)))
+            .filter(|folder| repo_root.join(folder).is_dir())
+            .map(|folder| folder.to_string_lossy().replace('\\', "/"))
+            .collect();
+        if folders.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![
+            Invocation::new("swiftlint", repo_root)
+                .args(["lint", "--strict"])
+                .args(folders),
+        ])
+    }
+
+    fn fmt(&self, repo_root: &Path, changed_files: &[PathBuf]) -> Result<Vec<Invocation>> {
+        let files: Vec<String> = changed_files
+            .iter()
+            .filter(|f| f.extension().is_some_and(|e| e == "swift") && repo_root.join(f).is_file())
+            .filter(|f| !f.components().any(|c| c.as_os_str() == "Pods"))
+            .map(|f| f.to_string_lossy().replace('\\', "/"))
+            .collect();
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![
+            Invocation::new("swift-format", repo_root)
+                .args(["format", "--in-place"])
+                .args(files),
+        ])
+    }
+
+    fn parse_failures(&self, repo_root: &Path, output: &str) -> Vec<Failure> {
+        test_failures(repo_root, output)
+    }
+
+    fn toolchain(&self, repo_root: &Path) -> Vec<Invocation> {
+        let mut tools = vec![Invocation::new("xcodebuild", repo_root).arg("-version")];
+        if repo_root.join("Podfile").is_file() {
+            tools.push(Invocation::new("pod", repo_root).arg("--version"));
+        }
+        tools
+    }
+}
+
+fn is_container(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "xcodeproj" || e == "xcworkspace")
+}
+
+fn scheme_target(repo_root: &Path, scheme: &Scheme) -> Target {
+    let meta = TargetMeta {
+        kind: Some("scheme".to_string()),
+        language: Some("swift".to_string()),
+        is_test: Some(scheme.has_tests),
+        size: None,
+    };
+    Target {
+        label: scheme.name.clone(),
+        dir: repo_root.join(&scheme.dir),
+        meta,
+        files: Vec::new(),
+    }
+}
+
+fn parse_scheme(name: &str, dir: PathBuf, container: PathBuf, text: &str) -> Scheme {
+    Scheme {
+        name: name.to_string(),
+        dir,
+        container,
+        blueprints: BLUEPRINT.captures_iter(text).map(|c| c[1].to_string()).collect(),
+        has_tests: text.contains("<TestableReference"),
+    }
+}
+
+/// Whether a path in the scheme's project is in the source folder of one of its targets: a folder
+/// named after the target anywhere below the project directory, as Xcode creates them.
+fn blueprint_in(scheme: &Scheme, path: &Path) -> bool {
+    let Ok(rel) = path.strip_prefix(&scheme.dir) else {
+        return false;
+    };
+    rel.components().any(|c| match c {
+        Component::Normal(name) => scheme.blueprints.iter().any(|b| name == b.as_str()),
+        _ => false,
+    })
+}
+
+/// Schemes building a changed file's target folder, and every scheme of a project whose project
+/// file, workspace, Podfile, or Swift package manifest changed.
+fn affected_schemes<'a>(schemes: &'a [Scheme], changed_files: &[PathBuf]) -> Vec<&'a Scheme> {
+    schemes
+        .iter()
+        .filter(|scheme| {
+            changed_files.iter().any(|file| {
+                let Ok(rel) = file.strip_prefix(&scheme.dir) else {
+                    return false;
+                };
+                let name = rel.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                let project_file = (PROJECT_FILES.contains(&name) && rel.parent() == Some(Path::new("")))
+                    || rel
+                        .components()
+                        .next()
+                        .is_some_and(|c| is_container(Path::new(c.as_os_str())));
+                project_file || (!rel.starts_with("Pods") && blueprint_in(scheme, file))
+            })
+        })
+        .collect()
+}
+
+/// Failed XCTest assertions in `xcodebuild test` output.
+fn test_failures(repo_root: &Path, output: &str) -> Vec<Failure> {
+    output
+        .lines()
+        .filter_map(|line| XCTEST_FAILURE.captures(line.trim()))
+        .map(|c| {
+            let file = PathBuf::from(&c[1]);
+            Failure {
+                test: format!("{}/{}", &c[3], &c[4]),
+                file: Some(file.strip_prefix(repo_root).map(Path::to_path_buf).unwrap_or(file)),
+                line: c[2].parse().ok(),
+                message: c[5].to_string(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "xcode_test.rs"]
+mod tests;
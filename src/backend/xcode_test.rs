@@ -0,0 +1,104 @@
+use super::*;
+
+const APP_SCHEME: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Scheme LastUpgradeVersion = "1500" version = "1.7">
+   <BuildAction parallelizeBuildables = "YES">
+      <BuildActionEntries>
+         <BuildActionEntry buildForTesting = "YES" buildForRunning = "YES">
+            <BuildableReference
+               BuildableIdentifier = "primary"
+               BlueprintIdentifier = "A1B2C3"
+               BuildableName = "Shop.app"
+               BlueprintName = "Shop"
+               ReferencedContainer = "container:Shop.xcodeproj">
+            </BuildableReference>
+         </BuildActionEntry>
+      </BuildActionEntries>
+   </BuildAction>
+   <TestAction buildConfiguration = "Debug">
+      <Testables>
+         <TestableReference skipped = "NO">
+            <BuildableReference
+               BuildableIdentifier = "primary"
+               BlueprintName = "ShopTests"
+               ReferencedContainer = "container:Shop.xcodeproj">
+            </BuildableReference>
+         </TestableReference>
+      </Testables>
+   </TestAction>
+</Scheme>
+"#;
+
+fn schemes() -> Vec<Scheme> {
+    vec![
+        parse_scheme(
+            "Shop",
+            PathBuf::from("ios"),
+            PathBuf::from("ios/Shop.xcworkspace"),
+            APP_SCHEME,
+        ),
+        parse_scheme(
+            "Widgets",
+            PathBuf::from("ios"),
+            PathBuf::from("ios/Shop.xcworkspace"),
+            r#"<BuildableReference BlueprintName = "Widgets">"#,
+        ),
+    ]
+}
+
+#[test]
+fn schemes_list_their_targets_and_tests() {
+    let schemes = schemes();
+    assert_eq!(schemes[0].blueprints.iter().collect::<Vec<_>>(), ["Shop", "ShopTests"]);
+    assert!(schemes[0].has_tests);
+    assert!(!schemes[1].has_tests);
+}
+
+#[test]
+fn changed_files_affect_the_schemes_building_their_folder() {
+    let schemes = schemes();
+    let affected = |files: &[&str]| -> Vec<String> {
+        let files: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+        affected_schemes(&schemes, &files)
+            .iter()
+            .map(|s| s.name.clone())
+            .collect()
+    };
+    assert_eq!(affected(&["ios/ShopTests/CartTests.swift"]), ["Shop"]);
+    assert_eq!(affected(&["ios/Widgets/Clock/ClockView.swift"]), ["Widgets"]);
+    assert_eq!(affected(&["ios/Podfile.lock"]), ["Shop", "Widgets"]);
+    assert_eq!(affected(&["ios/Shop.xcodeproj/project.pbxproj"]), ["Shop", "Widgets"]);
+    assert!(affected(&["ios/Pods/Shop/Shop.swift", "android/Shop/Main.kt", "ios/README.md"]).is_empty());
+}
+
+#[test]
+fn xctest_failures_are_parsed() {
+    let output = "\
+Test Case '-[ShopTests.CartTests testTotal]' started.
+/Users/ci/repo/ios/ShopTests/CartTests.swift:42: error: -[ShopTests.CartTests testTotal] : XCTAssertEqual failed: (\"1\") is not equal to (\"2\")
+Test Case '-[ShopTests.CartTests testTotal]' failed (0.004 seconds).
+";
+    assert_eq!(
+        test_failures(Path::new("/Users/ci/repo"), output),
+        [Failure {
+            test: "ShopTests.CartTests/testTotal".to_string(),
+            file: Some(PathBuf::from("ios/ShopTests/CartTests.swift")),
+            line: Some(42),
+            message: "XCTAssertEqual failed: (\"1\") is not equal to (\"2\")".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn detection_needs_a_project_or_marker_at_the_root() {
+    let dir = tempfile::tempdir().unwrap();
+    let schemes = dir.path().join("ios/App.xcodeproj/xcshareddata/xcschemes");
+    std::fs::create_dir_all(&schemes).unwrap();
+    std::fs::write(schemes.join("App.xcscheme"), "").unwrap();
+    assert!(!XcodeBackend.detect(dir.path()));
+    std::fs::write(dir.path().join("Package.swift"), "").unwrap();
+    assert!(XcodeBackend.detect(dir.path()));
+    std::fs::remove_file(dir.path().join("Package.swift")).unwrap();
+    std::fs::create_dir(dir.path().join("App.xcworkspace")).unwrap();
+    assert!(XcodeBackend.detect(dir.path()));
+}
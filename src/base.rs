@@ -0,0 +1,70 @@
+use std::path::Path;
+
+use anyhow::Result;
+use regex::Regex;
+
+use crate::git;
+
+/// The base branch a run diffs against when several are possible, as with long-lived release
+/// branches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Nearest {
+    pub branch: String,
+    /// Commits on HEAD since its merge base with the branch.
+    pub distance: usize,
+}
+
+/// The candidate whose merge base with HEAD is the nearest, so a branch cut from `release/2.1`
+/// diffs against it rather than against `main`. Candidates are branch names or patterns where `*`
+/// matches anything, including `/`; ties go to the candidate listed first. None if no branch
+/// other than the current one matches.
+pub fn nearest(repo_root: &Path, candidates: &[String]) -> Result<Option<Nearest>> {
+    let current = git::current_branch(repo_root)?;
+    let branches = matching(candidates, &git::branches(repo_root)?, current.as_deref());
+    let mut nearest: Option<Nearest> = None;
+    for branch in branches {
+        let Ok(merge_base) = git::merge_base(repo_root, &branch) else {
+            continue;
+        };
+        let distance = git::commits_since(repo_root, &merge_base)?;
+        if nearest.as_ref().is_none_or(|n| distance < n.distance) {
+            nearest = Some(Nearest { branch, distance });
+        }
+    }
+    Ok(nearest)
+}
+
+/// The branches matching each candidate in turn, by the short names git diffs against, other than
+/// the current branch. A remote-tracking branch matches by its name without the remote
+/// (`origin/release/2.1` as `release/2.1`) and is only used when there is no local branch of that
+/// name, as in CI checkouts.
+fn matching(candidates: &[String], refs: &[String], current: Option<&str>) -> Vec<String> {
+    let local: Vec<&str> = refs.iter().filter_map(|r| r.strip_prefix("refs/heads/")).collect();
+    // (short name, name matched against the candidates)
+    let branches: Vec<(String, &str)> = refs
+        .iter()
+        .filter_map(|r| match r.strip_prefix("refs/heads/") {
+            Some(name) => Some((name.to_string(), name)),
+            None => {
+                let short = r.strip_prefix("refs/remotes/")?;
+                let (_, name) = short.split_once('/')?;
+                (name != "HEAD" && !local.contains(&name)).then(|| (short.to_string(), name))
+            }
+        })
+        .collect();
+    let mut found: Vec<String> = Vec::new();
+    for candidate in candidates {
+        let parts: Vec<String> = candidate.trim().split('*').map(regex::escape).collect();
+        let pattern = Regex::new(&format!("^{}$", parts.join(".*"))).expect("escaped pattern is valid");
+        for (short, name) in &branches {
+            if pattern.is_match(name) && Some(*name) != current && !found.contains(short) {
+                found.push(short.clone());
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+#[path = "base_test.rs"]
+mod tests;
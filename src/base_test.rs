@@ -0,0 +1,28 @@
+use super::*;
+
+fn refs(names: &[&str]) -> Vec<String> {
+    names.iter().map(|n| n.to_string()).collect()
+}
+
+#[test]
+fn candidates_match_local_then_remote_branches_in_order() {
+    let refs = refs(&[
+        "refs/heads/main",
+        "refs/heads/release/2.1",
+        "refs/heads/feat/login",
+        "refs/remotes/origin/HEAD",
+        "refs/remotes/origin/main",
+        "refs/remotes/origin/release/2.0",
+        "refs/remotes/origin/release/2.1",
+    ]);
+    let candidates = vec!["main".to_string(), "release/*".to_string()];
+    assert_eq!(
+        matching(&candidates, &refs, Some("feat/login")),
+        ["main", "release/2.1", "origin/release/2.0"]
+    );
+    assert_eq!(
+        matching(&candidates, &refs, Some("release/2.1")),
+        ["main", "origin/release/2.0"]
+    );
+    assert!(matching(&["hotfix/*".to_string()], &refs, None).is_empty());
+}
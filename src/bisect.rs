@@ -0,0 +1,86 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result};
+
+use crate::error::KitError;
+use crate::git;
+
+/// Exit code telling `git bisect run` the commit can't be tested and should be skipped.
+pub const SKIP: u8 = 125;
+
+/// Find the commit between `good` and `bad` that broke the target in `target` with `git bisect
+/// run`, which re-invokes kit at each step (`kit bisect-step`) to build and test just that target,
+/// or to build it and run `command`. The bisection is always reset afterwards, leaving HEAD where
+/// it was.
+pub fn run(repo_root: &Path, target: &Path, good: &str, bad: &str, command: &[String]) -> Result<()> {
+    if !git::is_clean(repo_root)? {
+        return Err(KitError::ConfigError(
+            "kit bisect checks out other commits; commit or stash your changes first".to_string(),
+        )
+        .into());
+    }
+    let exe = std::env::current_exe().context("failed to find the kit executable")?;
+    bisect(repo_root, &["start", bad, good])?;
+    let mut run = Command::new("git");
+    run.args(["bisect", "run"])
+        .arg(exe)
+        .arg("--repo")
+        .arg(repo_root)
+        .arg("bisect-step")
+        .arg(target)
+        .current_dir(repo_root);
+    if !command.is_empty() {
+        run.arg("--").args(command);
+    }
+    let status = run.status().context("failed to run git bisect run");
+    let reset = bisect(repo_root, &["reset"]);
+    KitError::check("git bisect run", status?)?;
+    reset
+}
+
+/// What one bisect step tells `git bisect run`: skip the commit when the target didn't build (or
+/// kit couldn't run its tests there at all), otherwise good or bad depending on the tests. A custom
+/// test command can ask for a skip itself by exiting with 125, as with plain `git bisect run`.
+pub fn verdict(built: &Result<()>, tested: &Result<()>) -> u8 {
+    if built.is_err() {
+        return SKIP;
+    }
+    let Err(e) = tested else {
+        return 0;
+    };
+    match KitError::find(e) {
+        Some(KitError::ToolFailed { exit: Some(125), .. }) => SKIP,
+        Some(KitError::ToolFailed { .. }) => 1,
+        _ => SKIP,
+    }
+}
+
+/// Run `command` (program and arguments) in the repo root as a bisect step's test.
+pub fn run_command(repo_root: &Path, command: &[String]) -> Result<()> {
+    let (program, args) = command.split_first().context("no test command given")?;
+    let status = Command::new(program)
+        .args(args)
+        .current_dir(repo_root)
+        .status()
+        .map_err(|e| KitError::spawn(program, e))?;
+    KitError::check(program, status)
+}
+
+fn bisect(repo_root: &Path, args: &[&str]) -> Result<()> {
+    let output = Command::new("git")
+        .arg("bisect")
+        .args(args)
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git bisect")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(KitError::GitError(format!("git bisect {} failed: {}", args[0], stderr.trim())).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "bisect_test.rs"]
+mod tests;
@@ -0,0 +1,24 @@
+use super::*;
+
+fn failed(exit: Option<i32>) -> Result<()> {
+    Err(KitError::ToolFailed {
+        tool: "go".to_string(),
+        exit,
+    }
+    .into())
+}
+
+#[test]
+fn test_passing_and_failing_tests_mark_the_commit_good_or_bad() {
+    assert_eq!(verdict(&Ok(()), &Ok(())), 0);
+    assert_eq!(verdict(&Ok(()), &failed(Some(1))), 1);
+    assert_eq!(verdict(&Ok(()), &failed(None)), 1);
+}
+
+#[test]
+fn test_untestable_commits_are_skipped() {
+    assert_eq!(verdict(&failed(Some(2)), &Ok(())), SKIP);
+    assert_eq!(verdict(&Ok(()), &failed(Some(125))), SKIP);
+    let missing: Result<()> = Err(KitError::ToolMissing { tool: "go".to_string() }.into());
+    assert_eq!(verdict(&Ok(()), &missing), SKIP);
+}
@@ -0,0 +1,199 @@
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use ignore::WalkBuilder;
+
+use crate::config::{CodegenConfig, format_duration};
+use crate::error::KitError;
+use crate::{codegen, history, state};
+
+/// What kit keeps in each directory of `.kit/`.
+const ENTRIES: &[(&str, &str)] = &[
+    ("bazel-query", "memoized Bazel queries for affected targets"),
+    ("codegen", "input hashes of code generators' last runs"),
+    ("history", "last-green markers, step durations, coverage, and audits"),
+    ("last-run", "test and coverage output of the last run"),
+    ("coverage", "coverage reports"),
+    ("upload", "report bodies sent with --upload"),
+    ("cmake", "CMake build directory"),
+    ("helm", "rendered Helm charts"),
+    ("kustomize", "rendered Kustomize overlays"),
+];
+
+/// Directories of memoized results, safe to drop once old, since kit recomputes them.
+const EXPIRING: &[&str] = &["bazel-query", "upload"];
+
+/// A directory of `.kit/` and the disk it uses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub name: String,
+    pub description: String,
+    pub bytes: u64,
+    pub files: usize,
+}
+
+/// The directories of `.kit/`, those kit knows first, in [`ENTRIES`] order.
+pub fn status(repo_root: &Path) -> Result<Vec<Entry>> {
+    let root = repo_root.join(state::DIR);
+    let Ok(dirs) = std::fs::read_dir(&root) else {
+        return Ok(vec![]);
+    };
+    let mut names: Vec<String> = dirs
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort_by_key(|n| {
+        (
+            ENTRIES
+                .iter()
+                .position(|(known, _)| known == n)
+                .unwrap_or(ENTRIES.len()),
+            n.clone(),
+        )
+    });
+    Ok(names
+        .into_iter()
+        .map(|name| {
+            let (bytes, files) = usage(&root.join(&name));
+            let description = ENTRIES
+                .iter()
+                .find(|(known, _)| *known == name)
+                .map_or("", |(_, d)| d)
+                .to_string();
+            Entry {
+                name,
+                description,
+                bytes,
+                files,
+            }
+        })
+        .collect())
+}
+
+/// The entries as a table with aligned columns under a header.
+pub fn table(entries: &[Entry]) -> String {
+    let mut lines = vec![[
+        "NAME".to_string(),
+        "SIZE".to_string(),
+        "FILES".to_string(),
+        "CONTENTS".to_string(),
+    ]];
+    for entry in entries {
+        lines.push([
+            entry.name.clone(),
+            size(entry.bytes),
+            entry.files.to_string(),
+            entry.description.clone(),
+        ]);
+    }
+    let mut widths = [0; 4];
+    for line in &lines {
+        for (width, cell) in widths.iter_mut().zip(line) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let mut out = String::new();
+    for line in &lines {
+        let cells: Vec<String> = line
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Delete the named directories of `.kit/`, or all of them, returning the bytes freed. Names must be
+/// ones kit uses or ones present.
+pub fn clear(repo_root: &Path, names: &[String]) -> Result<u64> {
+    let present = status(repo_root)?;
+    for name in names {
+        if !present.iter().any(|e| e.name == *name) && !ENTRIES.iter().any(|(known, _)| known == name) {
+            let known: Vec<&str> = ENTRIES.iter().map(|(known, _)| *known).collect();
+            return Err(KitError::ConfigError(format!("no cache named {name} (expected {})", known.join(", "))).into());
+        }
+    }
+    let mut freed = 0;
+    for entry in present.iter().filter(|e| names.is_empty() || names.contains(&e.name)) {
+        let path = repo_root.join(state::DIR).join(&entry.name);
+        std::fs::remove_dir_all(&path).with_context(|| format!("failed to remove {}", path.display()))?;
+        freed += entry.bytes;
+    }
+    Ok(freed)
+}
+
+/// Drop what kit no longer needs: memoized results older than `max_age`, the input hashes of
+/// generators no longer in kit.toml, and last-green markers of deleted branches. Returns a line
+/// describing each kind of thing dropped, and the bytes freed.
+pub fn gc(repo_root: &Path, max_age: Duration, generators: &[CodegenConfig]) -> Result<(Vec<String>, u64)> {
+    let before: u64 = status(repo_root)?.iter().map(|e| e.bytes).sum();
+    let mut removed = Vec::new();
+    let cutoff = SystemTime::now().checked_sub(max_age).unwrap_or(SystemTime::UNIX_EPOCH);
+    for name in EXPIRING {
+        let n = expire(&repo_root.join(state::DIR).join(name), cutoff)?;
+        if n > 0 {
+            removed.push(format!("{n} {name} file(s) older than {}", format_duration(max_age)));
+        }
+    }
+    let n = codegen::prune(repo_root, generators)?;
+    if n > 0 {
+        removed.push(format!("{n} input hash(es) of removed code generators"));
+    }
+    let n = history::prune(repo_root)?;
+    if n > 0 {
+        removed.push(format!("{n} last-green marker(s) of deleted branches"));
+    }
+    let after: u64 = status(repo_root)?.iter().map(|e| e.bytes).sum();
+    Ok((removed, before.saturating_sub(after)))
+}
+
+/// Remove the files below `dir` last modified before `cutoff`, returning how many were removed.
+fn expire(dir: &Path, cutoff: SystemTime) -> Result<usize> {
+    if !dir.is_dir() {
+        return Ok(0);
+    }
+    let mut removed = 0;
+    let walk = WalkBuilder::new(dir).standard_filters(false).build();
+    for entry in walk.flatten().filter(|e| e.file_type().is_some_and(|t| t.is_file())) {
+        let modified = entry.metadata().ok().and_then(|m| m.modified().ok());
+        if modified.is_some_and(|m| m < cutoff) {
+            std::fs::remove_file(entry.path())
+                .with_context(|| format!("failed to remove {}", entry.path().display()))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Total size and number of the files below `dir`.
+fn usage(dir: &Path) -> (u64, usize) {
+    let walk = WalkBuilder::new(dir).standard_filters(false).build();
+    walk.flatten()
+        .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+        .fold((0, 0), |(bytes, files), e| {
+            (bytes + e.metadata().map_or(0, |m| m.len()), files + 1)
+        })
+}
+
+/// A byte count in the largest unit it makes at least one of, e.g. "512 B" or "3.4 MB".
+pub fn size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KB", "MB", "GB", "TB"];
+    if bytes < 1000 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1000.0;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+#[path = "cache_test.rs"]
+mod tests;
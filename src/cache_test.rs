@@ -0,0 +1,54 @@
+use super::*;
+
+#[test]
+fn status_lists_known_directories_first_with_their_usage() {
+    let repo = tempfile::tempdir().unwrap();
+    let root = repo.path();
+    std::fs::write(state::dir(root, "zz-other").unwrap().join("a"), "12345").unwrap();
+    let history = state::dir(root, "history").unwrap();
+    std::fs::write(history.join("green.jsonl"), "123").unwrap();
+    std::fs::write(history.join("durations.jsonl"), "1234").unwrap();
+    std::fs::write(state::dir(root, "bazel-query").unwrap().join("k"), "1").unwrap();
+
+    let entries = status(root).unwrap();
+    let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["bazel-query", "history", "zz-other"]);
+    assert_eq!((entries[1].bytes, entries[1].files), (7, 2));
+    assert_eq!(entries[2].description, "");
+}
+
+#[test]
+fn clear_removes_named_directories_or_all_and_rejects_unknown_names() {
+    let repo = tempfile::tempdir().unwrap();
+    let root = repo.path();
+    std::fs::write(state::dir(root, "history").unwrap().join("green.jsonl"), "123").unwrap();
+    std::fs::write(state::dir(root, "bazel-query").unwrap().join("k"), "1").unwrap();
+
+    assert!(clear(root, &["nope".to_string()]).is_err());
+    assert_eq!(
+        clear(root, &["bazel-query".to_string(), "codegen".to_string()]).unwrap(),
+        1
+    );
+    assert!(root.join(".kit/history").is_dir());
+    assert!(!root.join(".kit/bazel-query").exists());
+    assert_eq!(clear(root, &[]).unwrap(), 3);
+    assert!(status(root).unwrap().is_empty());
+    assert!(root.join(".kit/.gitignore").is_file());
+}
+
+#[test]
+fn expire_removes_files_older_than_the_cutoff() {
+    let repo = tempfile::tempdir().unwrap();
+    let dir = state::dir(repo.path(), "bazel-query").unwrap();
+    std::fs::write(dir.join("k"), "1").unwrap();
+    assert_eq!(expire(&dir, SystemTime::UNIX_EPOCH).unwrap(), 0);
+    assert_eq!(expire(&dir, SystemTime::now() + Duration::from_secs(60)).unwrap(), 1);
+    assert!(!dir.join("k").exists());
+}
+
+#[test]
+fn sizes_use_the_largest_whole_unit() {
+    assert_eq!(size(512), "512 B");
+    assert_eq!(size(1500), "1.5 KB");
+    assert_eq!(size(3_400_000), "3.4 MB");
+}
@@ -0,0 +1,215 @@
+use std::cell::OnceCell;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+
+use crate::error::KitError;
+use crate::git;
+
+/// A source of the files changed on a branch. kit asks git by default; code-review systems and CI
+/// setups that know the change set already can supply it instead.
+pub trait ChangeProvider {
+    /// Files changed relative to `base`, as paths relative to the repo root.
+    fn changed_files(&self, repo_root: &Path, base: &str) -> Result<Vec<PathBuf>>;
+}
+
+/// Build a change provider from a `--changes` spec: `git`, `staged`, `hg`, `list=PATH`,
+/// `webhook=PATH`, or `command=CMD`. `-` as the PATH reads standard input, once: later calls get
+/// the files it listed.
+pub fn from_spec(spec: &str) -> Result<Box<dyn ChangeProvider>> {
+    let (kind, arg) = match spec.split_once('=') {
+        Some((kind, arg)) => (kind, Some(arg)),
+        None => (spec, None),
+    };
+    let required = |what: &str| {
+        arg.map(String::from)
+            .ok_or_else(|| KitError::ConfigError(format!("--changes {kind} requires {what} ({kind}={what})")))
+    };
+    Ok(match kind {
+        "git" => Box::new(Git),
+        "staged" => Box::new(Staged),
+        "hg" => Box::new(Mercurial),
+        "list" | "webhook" if arg == Some("-") => match kind {
+            "list" => Box::new(Memoized::new(FileList("-".to_string()))),
+            _ => Box::new(Memoized::new(Webhook("-".to_string()))),
+        },
+        "list" => Box::new(FileList(required("PATH")?)),
+        "webhook" => Box::new(Webhook(required("PATH")?)),
+        "command" => Box::new(External(required("CMD")?)),
+        _ => {
+            return Err(KitError::ConfigError(format!(
+                "unknown change provider `{kind}` (expected git, staged, hg, list, webhook, or command)"
+            ))
+            .into());
+        }
+    })
+}
+
+/// Committed, staged, unstaged, and untracked changes since the merge base with `base`.
+pub struct Git;
+
+impl ChangeProvider for Git {
+    fn changed_files(&self, repo_root: &Path, base: &str) -> Result<Vec<PathBuf>> {
+        git::changed_files(repo_root, base)
+    }
+}
+
+/// Changes staged for the next commit, whatever the base; what a pre-commit hook checks.
+pub struct Staged;
+
+impl ChangeProvider for Staged {
+    fn changed_files(&self, repo_root: &Path, _base: &str) -> Result<Vec<PathBuf>> {
+        git::staged_files(repo_root)
+    }
+}
+
+/// Changes in a Mercurial working copy since its common ancestor with `base`, including unknown
+/// files.
+pub struct Mercurial;
+
+impl ChangeProvider for Mercurial {
+    fn changed_files(&self, repo_root: &Path, base: &str) -> Result<Vec<PathBuf>> {
+        let output = Command::new("hg")
+            .args(["status", "--rev", &format!("ancestor({base}, .)"), "-marun"])
+            .current_dir(repo_root)
+            .output()
+            .map_err(|e| KitError::spawn("hg", e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("hg status failed: {}", stderr.trim());
+        }
+        Ok(lines(
+            &String::from_utf8(output.stdout).context("invalid utf-8 from hg status")?,
+        ))
+    }
+}
+
+/// Paths listed one per line in the file at the path it holds (`-` for standard input), e.g. exported
+/// from a code-review system's diff API.
+pub struct FileList(pub String);
+
+impl ChangeProvider for FileList {
+    fn changed_files(&self, _repo_root: &Path, _base: &str) -> Result<Vec<PathBuf>> {
+        Ok(lines(&read_input(&self.0)?))
+    }
+}
+
+/// Paths in a CI webhook payload read from the file at the path it holds (`-` for standard input):
+/// the files of a push event's commits, or a list of files from a pull or merge request.
+pub struct Webhook(pub String);
+
+impl ChangeProvider for Webhook {
+    fn changed_files(&self, _repo_root: &Path, _base: &str) -> Result<Vec<PathBuf>> {
+        let text = read_input(&self.0)?;
+        let payload: serde_json::Value =
+            serde_json::from_str(&text).with_context(|| format!("invalid webhook payload in {}", self.0))?;
+        Ok(payload_files(&payload))
+    }
+}
+
+/// A provider asked only once, for providers whose input can't be read again (standard input).
+/// Later calls get the files of the first, whatever the base.
+pub struct Memoized<P> {
+    provider: P,
+    files: OnceCell<Vec<PathBuf>>,
+}
+
+impl<P: ChangeProvider> Memoized<P> {
+    pub fn new(provider: P) -> Self {
+        Memoized {
+            provider,
+            files: OnceCell::new(),
+        }
+    }
+}
+
+impl<P: ChangeProvider> ChangeProvider for Memoized<P> {
+    fn changed_files(&self, repo_root: &Path, base: &str) -> Result<Vec<PathBuf>> {
+        if let Some(files) = self.files.get() {
+            return Ok(files.clone());
+        }
+        let files = self.provider.changed_files(repo_root, base)?;
+        Ok(self.files.get_or_init(|| files).clone())
+    }
+}
+
+/// Paths printed one per line by a shell command, which gets the base in `KIT_BASE`.
+pub struct External(pub String);
+
+impl ChangeProvider for External {
+    fn changed_files(&self, repo_root: &Path, base: &str) -> Result<Vec<PathBuf>> {
+        let output = Command::new("sh")
+            .args(["-c", &self.0])
+            .env("KIT_BASE", base)
+            .current_dir(repo_root)
+            .output()
+            .map_err(|e| KitError::spawn("sh", e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("change provider command `{}` failed: {}", self.0, stderr.trim());
+        }
+        Ok(lines(
+            &String::from_utf8(output.stdout).context("invalid utf-8 from change provider command")?,
+        ))
+    }
+}
+
+fn read_input(path: &str) -> Result<String> {
+    if path == "-" {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .context("failed to read standard input")?;
+        return Ok(text);
+    }
+    std::fs::read_to_string(path).with_context(|| format!("failed to read {path}"))
+}
+
+/// Non-empty lines as sorted, deduplicated paths.
+fn lines(text: &str) -> Vec<PathBuf> {
+    let paths: std::collections::BTreeSet<PathBuf> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(|l| PathBuf::from(l.trim_start_matches("./")))
+        .collect();
+    paths.into_iter().collect()
+}
+
+/// Changed paths in a webhook payload: the `added`, `modified`, and `removed` lists of each of its
+/// `commits` (GitHub and GitLab push events), or a `files` list of paths or of objects with a
+/// `filename` (GitHub's pull request files API), `new_path` and `old_path` (GitLab's merge request
+/// changes), or `path`.
+fn payload_files(payload: &serde_json::Value) -> Vec<PathBuf> {
+    let mut paths = String::new();
+    let mut add = |value: &serde_json::Value| {
+        if let Some(path) = value.as_str() {
+            paths.push_str(path);
+            paths.push('\n');
+        }
+    };
+    for commit in payload.get("commits").and_then(|c| c.as_array()).into_iter().flatten() {
+        for list in ["added", "modified", "removed"] {
+            commit
+                .get(list)
+                .and_then(|l| l.as_array())
+                .into_iter()
+                .flatten()
+                .for_each(&mut add);
+        }
+    }
+    let files = payload.get("files").or(payload.get("changes")).or(Some(payload));
+    for file in files.and_then(|f| f.as_array()).into_iter().flatten() {
+        add(file);
+        for key in ["filename", "new_path", "old_path", "path"] {
+            file.get(key).into_iter().for_each(&mut add);
+        }
+    }
+    lines(&paths)
+}
+
+#[cfg(test)]
+#[path = "changes_test.rs"]
+mod tests;
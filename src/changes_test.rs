@@ -0,0 +1,65 @@
+use super::*;
+
+#[test]
+fn specs_select_providers() {
+    assert!(from_spec("git").is_ok());
+    assert!(from_spec("staged").is_ok());
+    assert!(from_spec("command=arc paths").is_ok());
+    assert!(from_spec("list").is_err());
+    assert!(from_spec("svn").is_err());
+}
+
+#[test]
+fn lists_are_trimmed_and_deduplicated() {
+    assert_eq!(
+        lines("./pkg/a/a.go\n\n  pkg/b/b.go \npkg/a/a.go\n"),
+        [PathBuf::from("pkg/a/a.go"), PathBuf::from("pkg/b/b.go")]
+    );
+}
+
+#[test]
+fn payload_files_read_push_events_and_file_lists() {
+    let push = serde_json::json!({
+        "commits": [
+            { "added": ["new.go"], "modified": ["pkg/a.go"], "removed": [] },
+            { "added": [], "modified": ["pkg/a.go"], "removed": ["old.go"] },
+        ]
+    });
+    assert_eq!(
+        payload_files(&push),
+        [
+            PathBuf::from("new.go"),
+            PathBuf::from("old.go"),
+            PathBuf::from("pkg/a.go")
+        ]
+    );
+    let pull_files = serde_json::json!([{ "filename": "src/x.ts", "status": "modified" }]);
+    assert_eq!(payload_files(&pull_files), [PathBuf::from("src/x.ts")]);
+    let merge_request = serde_json::json!({ "changes": [{ "old_path": "a/old.py", "new_path": "a/new.py" }] });
+    assert_eq!(
+        payload_files(&merge_request),
+        [PathBuf::from("a/new.py"), PathBuf::from("a/old.py")]
+    );
+}
+
+#[test]
+fn command_providers_get_the_base() {
+    let tmp = tempfile::tempdir().unwrap();
+    let files = External("echo \"$KIT_BASE/x.go\"; echo y.go".to_string())
+        .changed_files(tmp.path(), "main")
+        .unwrap();
+    assert_eq!(files, [PathBuf::from("main/x.go"), PathBuf::from("y.go")]);
+}
+
+#[test]
+fn memoized_providers_are_asked_once() {
+    let tmp = tempfile::tempdir().unwrap();
+    let provider = Memoized::new(External("echo run >> runs; cat runs".to_string()));
+    for _ in 0..2 {
+        assert_eq!(
+            provider.changed_files(tmp.path(), "main").unwrap(),
+            [PathBuf::from("run")]
+        );
+    }
+    assert_eq!(std::fs::read_to_string(tmp.path().join("runs")).unwrap(), "run\n");
+}
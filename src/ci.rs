@@ -0,0 +1,57 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+
+use crate::config::CiStage;
+
+/// How a stage of `kit ci` ended.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StageResult {
+    Passed(Duration),
+    Failed(Duration),
+    /// An earlier stage failed and `keep_going` is off.
+    NotRun,
+}
+
+/// The contents of files before a step that may rewrite them, such as formatting, to tell which
+/// ones it did. Missing files have no hash.
+pub fn snapshot(repo_root: &Path, files: &[PathBuf]) -> BTreeMap<PathBuf, Option<Vec<u8>>> {
+    files
+        .iter()
+        .map(|f| {
+            let hash = std::fs::read(repo_root.join(f))
+                .ok()
+                .map(|c| Sha256::digest(c).to_vec());
+            (f.clone(), hash)
+        })
+        .collect()
+}
+
+/// The files whose contents differ from the snapshot.
+pub fn rewritten(repo_root: &Path, before: &BTreeMap<PathBuf, Option<Vec<u8>>>) -> Vec<PathBuf> {
+    let files: Vec<PathBuf> = before.keys().cloned().collect();
+    let after = snapshot(repo_root, &files);
+    files.into_iter().filter(|f| before[f] != after[f]).collect()
+}
+
+/// One line per stage, with how it ended and how long it took.
+pub fn summary(results: &[(CiStage, StageResult)]) -> Vec<String> {
+    let width = results.iter().map(|(s, _)| s.name().len()).max().unwrap_or_default();
+    results
+        .iter()
+        .map(|(stage, result)| {
+            let name = stage.name();
+            match result {
+                StageResult::Passed(d) => format!("{name:<width$}  passed  {:.1}s", d.as_secs_f64()),
+                StageResult::Failed(d) => format!("{name:<width$}  FAILED  {:.1}s", d.as_secs_f64()),
+                StageResult::NotRun => format!("{name:<width$}  not run"),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "ci_test.rs"]
+mod tests;
@@ -0,0 +1,26 @@
+use super::*;
+
+#[test]
+fn rewritten_lists_files_formatting_changed() {
+    let repo = tempfile::tempdir().unwrap();
+    let root = repo.path();
+    std::fs::write(root.join("a.go"), "package a\n").unwrap();
+    std::fs::write(root.join("b.go"), "package  b\n").unwrap();
+    let files = ["a.go", "b.go", "gone.go"].map(PathBuf::from);
+    let before = snapshot(root, &files);
+    std::fs::write(root.join("b.go"), "package b\n").unwrap();
+    assert_eq!(rewritten(root, &before), [PathBuf::from("b.go")]);
+}
+
+#[test]
+fn summary_lines_are_aligned() {
+    let results = [
+        (CiStage::Fmt, StageResult::Passed(Duration::from_millis(1200))),
+        (CiStage::Build, StageResult::Failed(Duration::from_secs(42))),
+        (CiStage::Test, StageResult::NotRun),
+    ];
+    assert_eq!(
+        summary(&results),
+        ["fmt    passed  1.2s", "build  FAILED  42.0s", "test   not run"]
+    );
+}
@@ -0,0 +1,228 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use ignore::WalkBuilder;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use sha2::{Digest, Sha256};
+
+use crate::backend::{Target, TargetMeta};
+use crate::config::CodegenConfig;
+use crate::exec::Invocation;
+use crate::report::{Skip, Step};
+use crate::{git, state};
+
+/// The backend name generator steps and targets are reported under.
+pub const BACKEND: &str = "codegen";
+
+/// Which generators a run needs.
+pub enum Scope {
+    /// Those whose inputs changed on the branch.
+    Changed(Vec<PathBuf>),
+    /// Those generating code in, or around, the named repo-relative paths.
+    Paths(Vec<PathBuf>),
+}
+
+/// A generator step ready to run, with the hash of the inputs it generates from.
+pub struct Generation {
+    pub step: Step,
+    pub plan: Vec<Invocation>,
+    name: String,
+    hash: String,
+}
+
+/// A matcher for a generator's inputs, anchored at the repo root. Each input is a file, a directory
+/// (everything below it), or a glob such as `proto/**/*.proto`.
+fn inputs(repo_root: &Path, generator: &CodegenConfig) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(repo_root);
+    for input in &generator.inputs {
+        let input = input.trim_start_matches("./").trim_end_matches('/');
+        builder
+            .add_line(None, &format!("/{input}"))
+            .with_context(|| format!("invalid input `{input}` for code generator {}", generator.name))?;
+    }
+    Ok(builder.build()?)
+}
+
+fn matches(repo_root: &Path, matcher: &Gitignore, file: &Path) -> bool {
+    matcher
+        .matched_path_or_any_parents(repo_root.join(file), false)
+        .is_ignore()
+}
+
+/// Whether any changed file is one of the generator's inputs.
+fn affected(repo_root: &Path, generator: &CodegenConfig, changed_files: &[PathBuf]) -> Result<bool> {
+    let matcher = inputs(repo_root, generator)?;
+    Ok(changed_files.iter().any(|f| matches(repo_root, &matcher, f)))
+}
+
+/// Whether the generator writes into one of `paths` or below one of them.
+fn overlaps(generator: &CodegenConfig, paths: &[PathBuf]) -> bool {
+    generator
+        .outputs
+        .iter()
+        .any(|output| paths.iter().any(|p| p.starts_with(output) || output.starts_with(p)))
+}
+
+/// Generators whose inputs changed on the branch, as targets.
+pub fn targets(repo_root: &Path, generators: &[CodegenConfig], changed_files: &[PathBuf]) -> Result<Vec<Target>> {
+    let mut targets = Vec::new();
+    for generator in generators {
+        if affected(repo_root, generator, changed_files)? {
+            targets.push(target(repo_root, generator));
+        }
+    }
+    Ok(targets)
+}
+
+fn target(repo_root: &Path, generator: &CodegenConfig) -> Target {
+    Target {
+        label: generator.name.clone(),
+        dir: repo_root.to_path_buf(),
+        meta: TargetMeta {
+            kind: Some("codegen".to_string()),
+            ..TargetMeta::default()
+        },
+        files: Vec::new(),
+    }
+}
+
+/// The changed files plus every file the generators whose inputs changed write, so the code built
+/// from generated files is affected along with the generators. Outputs that don't exist yet add
+/// nothing.
+pub fn with_outputs(
+    repo_root: &Path,
+    generators: &[CodegenConfig],
+    mut changed_files: Vec<PathBuf>,
+) -> Result<Vec<PathBuf>> {
+    let mut generated = Vec::new();
+    for generator in generators {
+        if !affected(repo_root, generator, &changed_files)? {
+            continue;
+        }
+        for output in &generator.outputs {
+            // Generated code is often gitignored, so nothing is filtered out.
+            let walk = WalkBuilder::new(repo_root.join(output)).standard_filters(false).build();
+            for entry in walk.flatten().filter(|e| e.file_type().is_some_and(|t| t.is_file())) {
+                if let Ok(rel) = entry.path().strip_prefix(repo_root) {
+                    generated.push(rel.to_path_buf());
+                }
+            }
+        }
+    }
+    for file in generated {
+        if !changed_files.contains(&file) {
+            changed_files.push(file);
+        }
+    }
+    Ok(changed_files)
+}
+
+/// A step for each generator the scope needs, or whose outputs are missing. A generator whose
+/// inputs hash the same as when it last ran successfully, and whose outputs all exist, is skipped as
+/// up to date.
+pub fn plan(repo_root: &Path, generators: &[CodegenConfig], scope: &Scope) -> Result<Vec<Generation>> {
+    let mut planned = Vec::new();
+    for generator in generators {
+        let missing = generator.outputs.iter().any(|o| !repo_root.join(o).exists());
+        let needed = match scope {
+            Scope::Changed(changed_files) => affected(repo_root, generator, changed_files)?,
+            Scope::Paths(paths) => overlaps(generator, paths),
+        };
+        if !needed && !missing {
+            continue;
+        }
+        let hash = hash(repo_root, generator)?;
+        let mut step = Step {
+            command: "generate".to_string(),
+            backend: BACKEND.to_string(),
+            targets: vec![target(repo_root, generator)],
+            seed: None,
+            variant: None,
+            skip: None,
+        };
+        let stamp = std::fs::read_to_string(stamp_path(repo_root, &generator.name)?).unwrap_or_default();
+        let plan = if !missing && stamp.trim() == hash {
+            step.skip = Some(Skip::UpToDate);
+            vec![]
+        } else {
+            vec![Invocation::new("sh", repo_root).args(["-c", &generator.command])]
+        };
+        planned.push(Generation {
+            step,
+            plan,
+            name: generator.name.clone(),
+            hash,
+        });
+    }
+    Ok(planned)
+}
+
+/// Remember the inputs a generator ran on successfully, so it is skipped until they change.
+pub fn record(repo_root: &Path, generation: &Generation) -> Result<()> {
+    if generation.step.skip.is_some() {
+        return Ok(());
+    }
+    let path = stamp_path(repo_root, &generation.name)?;
+    std::fs::write(&path, format!("{}\n", generation.hash))
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+fn stamp_path(repo_root: &Path, name: &str) -> Result<PathBuf> {
+    Ok(state::dir(repo_root, "codegen")?.join(stamp_file(name)))
+}
+
+fn stamp_file(name: &str) -> String {
+    let file: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{file}.sha256")
+}
+
+/// Remove the stamps of generators no longer in kit.toml, returning how many were removed.
+pub fn prune(repo_root: &Path, generators: &[CodegenConfig]) -> Result<usize> {
+    let dir = repo_root.join(state::DIR).join("codegen");
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Ok(0);
+    };
+    let current: Vec<String> = generators.iter().map(|g| stamp_file(&g.name)).collect();
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.ends_with(".sha256") && !current.contains(&name) {
+            std::fs::remove_file(entry.path())
+                .with_context(|| format!("failed to remove {}", entry.path().display()))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// SHA-256 over the generator's command and the paths and contents of its input files.
+fn hash(repo_root: &Path, generator: &CodegenConfig) -> Result<String> {
+    let matcher = inputs(repo_root, generator)?;
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}\0", generator.command));
+    for file in git::find_files(repo_root, "*")? {
+        if !matches(repo_root, &matcher, &file) {
+            continue;
+        }
+        let path = repo_root.join(&file);
+        let Ok(contents) = std::fs::read(&path) else {
+            continue;
+        };
+        hasher.update(format!("{}\0", file.to_string_lossy()));
+        hasher.update(Sha256::digest(&contents));
+    }
+    Ok(hasher.finalize().iter().map(|b| format!("{b:02x}")).collect())
+}
+
+#[cfg(test)]
+#[path = "codegen_test.rs"]
+mod tests;
@@ -0,0 +1,52 @@
+use super::*;
+
+fn generator() -> CodegenConfig {
+    CodegenConfig {
+        name: "protos".to_string(),
+        command: "buf generate".to_string(),
+        inputs: vec!["proto/**/*.proto".to_string(), "buf.gen.yaml".to_string()],
+        outputs: vec![PathBuf::from("gen/go"), PathBuf::from("web/src/gen")],
+    }
+}
+
+#[test]
+fn inputs_match_globs_and_files() {
+    let repo = Path::new("/repo");
+    let generator = generator();
+    assert!(affected(repo, &generator, &[PathBuf::from("proto/api/v1/user.proto")]).unwrap());
+    assert!(affected(repo, &generator, &[PathBuf::from("buf.gen.yaml")]).unwrap());
+    assert!(!affected(repo, &generator, &[PathBuf::from("proto/README.md")]).unwrap());
+    assert!(!affected(repo, &generator, &[PathBuf::from("api/buf.gen.yaml")]).unwrap());
+}
+
+#[test]
+fn named_paths_select_generators_writing_near_them() {
+    let generator = generator();
+    assert!(overlaps(&generator, &[PathBuf::from("gen/go/userpb")]));
+    assert!(overlaps(&generator, &[PathBuf::from("web")]));
+    assert!(!overlaps(&generator, &[PathBuf::from("services/api")]));
+}
+
+#[test]
+fn outputs_of_affected_generators_count_as_changed() {
+    let repo = tempfile::tempdir().unwrap();
+    let out = repo.path().join("gen/go/userpb");
+    std::fs::create_dir_all(&out).unwrap();
+    std::fs::write(out.join("user.pb.go"), "package userpb\n").unwrap();
+    let generators = [generator()];
+
+    let changed = with_outputs(repo.path(), &generators, vec![PathBuf::from("proto/user.proto")]).unwrap();
+    assert_eq!(
+        changed,
+        [
+            PathBuf::from("proto/user.proto"),
+            PathBuf::from("gen/go/userpb/user.pb.go")
+        ]
+    );
+    let changed = with_outputs(repo.path(), &generators, vec![PathBuf::from("main.go")]).unwrap();
+    assert_eq!(changed, [PathBuf::from("main.go")]);
+    assert_eq!(
+        targets(repo.path(), &generators, &[PathBuf::from("buf.gen.yaml")]).unwrap()[0].label,
+        "protos"
+    );
+}
@@ -0,0 +1,117 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use crate::config::OwnerRules;
+
+/// Locations GitHub searches for a CODEOWNERS file, in priority order.
+const LOCATIONS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+struct Rule {
+    matcher: Gitignore,
+    owners: Vec<String>,
+}
+
+/// A parsed CODEOWNERS file. As on GitHub, the last matching rule wins.
+pub struct CodeOwners {
+    /// Path of the CODEOWNERS file relative to the repo root.
+    pub path: PathBuf,
+    rules: Vec<Rule>,
+}
+
+impl CodeOwners {
+    /// Load the repository's CODEOWNERS file, if it has one.
+    pub fn load(repo_root: &Path) -> Result<Option<Self>> {
+        for location in LOCATIONS {
+            let path = repo_root.join(location);
+            if path.is_file() {
+                let text =
+                    std::fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+                return Self::parse(repo_root, PathBuf::from(location), &text).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    fn parse(repo_root: &Path, path: PathBuf, text: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else {
+                continue;
+            };
+            let mut builder = GitignoreBuilder::new(repo_root);
+            builder
+                .add_line(None, pattern)
+                .with_context(|| format!("{}:{}: invalid pattern `{pattern}`", path.display(), n + 1))?;
+            let matcher = builder
+                .build()
+                .with_context(|| format!("{}:{}: invalid pattern `{pattern}`", path.display(), n + 1))?;
+            rules.push(Rule {
+                matcher,
+                owners: fields.map(String::from).collect(),
+            });
+        }
+        Ok(Self { path, rules })
+    }
+
+    /// Owners of a repo-relative path, or None if no rule covers it.
+    ///
+    /// A rule with no owners explicitly un-owns its paths, so the result may be an empty slice.
+    pub fn owners(&self, repo_root: &Path, rel: &Path) -> Option<&[String]> {
+        self.find(repo_root, rel, false)
+    }
+
+    /// Owners of a repo-relative directory, as for [`CodeOwners::owners`].
+    pub fn dir_owners(&self, repo_root: &Path, rel: &Path) -> Option<&[String]> {
+        self.find(repo_root, rel, true)
+    }
+
+    fn find(&self, repo_root: &Path, rel: &Path, is_dir: bool) -> Option<&[String]> {
+        let path = repo_root.join(rel);
+        self.rules
+            .iter()
+            .rev()
+            .find(|r| r.matcher.matched_path_or_any_parents(&path, is_dir).is_ignore())
+            .map(|r| r.owners.as_slice())
+    }
+}
+
+/// Fail if any changed file has no owner or is owned by a forbidden owner.
+pub fn lint(repo_root: &Path, changed_files: &[PathBuf], rules: &OwnerRules) -> Result<()> {
+    let Some(codeowners) = CodeOwners::load(repo_root)? else {
+        anyhow::bail!("no CODEOWNERS file found (looked in {})", LOCATIONS.join(", "));
+    };
+    eprintln!(
+        "kit: checking ownership of {} changed file(s) against {}",
+        changed_files.len(),
+        codeowners.path.display()
+    );
+
+    let mut failures = 0;
+    for file in changed_files {
+        match codeowners.owners(repo_root, file) {
+            None | Some([]) => {
+                eprintln!("kit: {} has no owner", file.display());
+                failures += 1;
+            }
+            Some(owners) => {
+                for owner in owners.iter().filter(|o| rules.forbidden.contains(o)) {
+                    eprintln!("kit: {} is owned by forbidden owner {owner}", file.display());
+                    failures += 1;
+                }
+            }
+        }
+    }
+
+    if failures > 0 {
+        anyhow::bail!("{failures} ownership violation(s)");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "codeowners_test.rs"]
+mod tests;
@@ -0,0 +1,41 @@
+use super::*;
+
+fn parse(text: &str) -> CodeOwners {
+    CodeOwners::parse(Path::new("/repo"), PathBuf::from("CODEOWNERS"), text).unwrap()
+}
+
+fn owners<'a>(co: &'a CodeOwners, path: &str) -> Option<&'a [String]> {
+    co.owners(Path::new("/repo"), Path::new(path))
+}
+
+#[test]
+fn last_matching_rule_wins() {
+    let co = parse("* @org/everyone\n/pkg/db/ @org/db\n");
+    assert_eq!(owners(&co, "main.go").unwrap(), ["@org/everyone"]);
+    assert_eq!(owners(&co, "pkg/db/conn.go").unwrap(), ["@org/db"]);
+}
+
+#[test]
+fn unmatched_paths_have_no_rule() {
+    let co = parse("# comment\n\n*.go @org/go # trailing comment\n");
+    assert_eq!(owners(&co, "pkg/foo.go").unwrap(), ["@org/go"]);
+    assert!(owners(&co, "README.md").is_none());
+}
+
+#[test]
+fn rules_without_owners_unown_paths() {
+    let co = parse("* @org/everyone\n/generated/\n");
+    assert_eq!(owners(&co, "generated/api.go"), Some(&[][..]));
+}
+
+#[test]
+fn directory_patterns_match_directories() {
+    let co = parse("* @org/everyone\n/pkg/db/ @org/db\n");
+    let root = Path::new("/repo");
+    assert_eq!(co.dir_owners(root, Path::new("pkg/db")).unwrap(), ["@org/db"]);
+    assert_eq!(
+        co.dir_owners(root, Path::new("pkg/db/migrations")).unwrap(),
+        ["@org/db"]
+    );
+    assert_eq!(co.dir_owners(root, Path::new("pkg")).unwrap(), ["@org/everyone"]);
+}
@@ -0,0 +1,112 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use crate::config::CommitRules;
+use crate::git::{self, Commit};
+
+/// Compiled form of the `[lint.commits]` rules.
+pub struct CommitPolicy {
+    conventional: Option<Regex>,
+    ticket: Option<Regex>,
+    max_subject_length: Option<usize>,
+    branch: Option<Regex>,
+}
+
+impl CommitPolicy {
+    pub fn new(rules: &CommitRules) -> Result<Self> {
+        let conventional = if rules.conventional {
+            let types: Vec<String> = rules.types.iter().map(|t| regex::escape(t)).collect();
+            let pattern = format!(r"^({})(\([^()]+\))?!?: \S", types.join("|"));
+            Some(Regex::new(&pattern).context("invalid commit types")?)
+        } else {
+            None
+        };
+        let ticket = rules
+            .ticket_pattern
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .context("invalid ticket_pattern")?;
+        let branch = rules
+            .branch_pattern
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .context("invalid branch_pattern")?;
+        Ok(Self {
+            conventional,
+            ticket,
+            max_subject_length: rules.max_subject_length,
+            branch,
+        })
+    }
+
+    /// Return a description of every rule the commit violates.
+    pub fn check_commit(&self, commit: &Commit) -> Vec<String> {
+        let subject = commit.subject();
+        let mut problems = Vec::new();
+        if let Some(re) = &self.conventional
+            && !re.is_match(subject)
+        {
+            problems.push("subject is not a conventional commit (`type(scope): description`)".to_string());
+        }
+        if let Some(re) = &self.ticket
+            && !re.is_match(&commit.message)
+        {
+            problems.push(format!(
+                "message does not reference a ticket matching `{}`",
+                re.as_str()
+            ));
+        }
+        if let Some(max) = self.max_subject_length {
+            let len = subject.chars().count();
+            if len > max {
+                problems.push(format!("subject is {len} characters, limit is {max}"));
+            }
+        }
+        problems
+    }
+
+    /// Return a description of the problem if the branch name violates the branch pattern.
+    pub fn check_branch(&self, branch: &str) -> Option<String> {
+        let re = self.branch.as_ref()?;
+        if re.is_match(branch) {
+            None
+        } else {
+            Some(format!("branch `{branch}` does not match `{}`", re.as_str()))
+        }
+    }
+}
+
+/// Validate the branch name and every commit between the merge base and HEAD.
+pub fn lint(repo_root: &Path, base: &str, rules: &CommitRules) -> Result<()> {
+    let policy = CommitPolicy::new(rules)?;
+    let commits = git::commits(repo_root, base)?;
+    eprintln!("kit: checking {} commit(s) on branch", commits.len());
+
+    let mut failures = 0;
+    if let Some(branch) = git::current_branch(repo_root)?
+        && let Some(problem) = policy.check_branch(&branch)
+    {
+        eprintln!("kit: {problem}");
+        failures += 1;
+    }
+    for commit in &commits {
+        for problem in policy.check_commit(commit) {
+            let short = &commit.sha[..commit.sha.len().min(8)];
+            eprintln!("kit: {short} {}: {problem}", commit.subject());
+            failures += 1;
+        }
+    }
+
+    if failures > 0 {
+        anyhow::bail!("{failures} commit policy violation(s)");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "commits_test.rs"]
+mod tests;
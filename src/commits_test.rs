@@ -0,0 +1,62 @@
+use super::*;
+
+fn commit(message: &str) -> Commit {
+    Commit {
+        sha: "0123456789abcdef".to_string(),
+        message: message.to_string(),
+    }
+}
+
+#[test]
+fn conventional_subjects_pass() {
+    let policy = CommitPolicy::new(&CommitRules::default()).unwrap();
+    assert!(policy.check_commit(&commit("feat: add kit lint --commits")).is_empty());
+    assert!(policy.check_commit(&commit("fix(go)!: drop go.work.sum")).is_empty());
+}
+
+#[test]
+fn non_conventional_subjects_fail() {
+    let policy = CommitPolicy::new(&CommitRules::default()).unwrap();
+    assert_eq!(policy.check_commit(&commit("Add a thing")).len(), 1);
+    assert_eq!(policy.check_commit(&commit("feature: add a thing")).len(), 1);
+    assert_eq!(policy.check_commit(&commit("feat:missing space")).len(), 1);
+}
+
+#[test]
+fn ticket_pattern_matches_body() {
+    let rules = CommitRules {
+        conventional: false,
+        ticket_pattern: Some("[A-Z]+-[0-9]+".to_string()),
+        ..CommitRules::default()
+    };
+    let policy = CommitPolicy::new(&rules).unwrap();
+    assert!(policy.check_commit(&commit("Fix it\n\nRefs: KIT-123")).is_empty());
+    assert_eq!(policy.check_commit(&commit("Fix it")).len(), 1);
+}
+
+#[test]
+fn max_subject_length() {
+    let rules = CommitRules {
+        conventional: false,
+        max_subject_length: Some(10),
+        ..CommitRules::default()
+    };
+    let policy = CommitPolicy::new(&rules).unwrap();
+    assert!(
+        policy
+            .check_commit(&commit("short\n\na much longer body line"))
+            .is_empty()
+    );
+    assert_eq!(policy.check_commit(&commit("this subject is too long")).len(), 1);
+}
+
+#[test]
+fn branch_pattern() {
+    let rules = CommitRules {
+        branch_pattern: Some("^(feat|fix)/".to_string()),
+        ..CommitRules::default()
+    };
+    let policy = CommitPolicy::new(&rules).unwrap();
+    assert!(policy.check_branch("feat/commit-lint").is_none());
+    assert!(policy.check_branch("wip").is_some());
+}
@@ -0,0 +1,634 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::error::KitError;
+use crate::release::Bump;
+
+const FILE_NAME: &str = "kit.toml";
+
+/// Tables `kit.toml` may not set, because a cloned repo must not decide them for the user.
+const USER_ONLY: &[&str] = &["update"];
+
+/// Configuration read from `kit.toml` at the repo root, layered over the user's own
+/// [`user_path`] file: tables merge key by key, and any other value in `kit.toml` replaces the
+/// user's.
+///
+/// Every section is optional; a missing file is equivalent to an empty one.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Branch to diff against when `--base` isn't given, instead of `main`.
+    pub base: Option<String>,
+    /// Reporters added to every run, as with `--report` (e.g. a personal `webhook=URL`).
+    pub report: Vec<String>,
+    /// Maximum number of steps running at once, of any kind. Unlimited by default.
+    pub jobs: Option<usize>,
+    pub lint: LintConfig,
+    pub release: ReleaseConfig,
+    pub services: Vec<ServiceConfig>,
+    pub image: ImageConfig,
+    /// Default deployment command template per environment, used by services without their own.
+    pub deploy: BTreeMap<String, String>,
+    pub budget: BudgetConfig,
+    pub tools: ToolsConfig,
+    /// Targets whose tests run once per combination of environment values.
+    pub matrix: Vec<MatrixConfig>,
+    /// Merge requirements checked by `kit gate`.
+    pub gate: Vec<GatePolicy>,
+    /// Code generators run before building and testing the code they generate.
+    pub codegen: Vec<CodegenConfig>,
+    /// Backends defined by their commands, for build systems kit has no built-in support for.
+    pub backends: Vec<BackendConfig>,
+    pub unchanged: UnchangedConfig,
+    pub ci: CiConfig,
+    pub gradle: GradleConfig,
+    pub xcode: XcodeConfig,
+    pub new: NewConfig,
+    pub update: UpdateConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintConfig {
+    pub commits: CommitRules,
+    pub owners: OwnerRules,
+}
+
+/// Rules applied by `kit lint --owners`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OwnerRules {
+    /// Owners (e.g. `@org/legacy-team`) that changed files must not be assigned to.
+    pub forbidden: Vec<String>,
+}
+
+/// Rules applied by `kit lint --commits`.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CommitRules {
+    /// Require Conventional Commits subjects (`type(scope)!: description`).
+    pub conventional: bool,
+    /// Allowed Conventional Commits types.
+    pub types: Vec<String>,
+    /// Regex that must match somewhere in each commit message (e.g. a ticket ID).
+    pub ticket_pattern: Option<String>,
+    /// Maximum length of the subject line.
+    pub max_subject_length: Option<usize>,
+    /// Regex the current branch name must match.
+    pub branch_pattern: Option<String>,
+}
+
+impl Default for CommitRules {
+    fn default() -> Self {
+        Self {
+            conventional: true,
+            types: [
+                "build", "chore", "ci", "docs", "feat", "fix", "perf", "refactor", "revert", "style", "test",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+            ticket_pattern: None,
+            max_subject_length: None,
+            branch_pattern: None,
+        }
+    }
+}
+
+/// Settings for `kit release`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReleaseConfig {
+    /// Fixed version bump for every release. If unset, the bump is derived from Conventional Commits.
+    pub bump: Option<Bump>,
+}
+
+/// An environment matrix for `kit test`: the listed targets' tests run once per combination of the
+/// values of `env`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MatrixConfig {
+    /// Target labels as `kit affected` prints them (e.g. `./pkg/db/...`).
+    pub targets: Vec<String>,
+    /// Values per environment variable (e.g. `POSTGRES_VERSION = ["14", "16"]`).
+    pub env: BTreeMap<String, Vec<String>>,
+}
+
+/// A code generation step, such as protobuf to Go and TypeScript. `kit build` and `kit test` run it
+/// first when its inputs changed, and code built from its outputs is affected by those inputs.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CodegenConfig {
+    pub name: String,
+    /// Shell command generating the outputs, run from the repo root (e.g. `buf generate`).
+    pub command: String,
+    /// Repo-relative files, directories, or globs the outputs are generated from (e.g.
+    /// `proto/**/*.proto`).
+    pub inputs: Vec<String>,
+    /// Repo-relative files or directories the command writes.
+    pub outputs: Vec<PathBuf>,
+}
+
+/// A backend defined in kit.toml, such as asset validation for a game engine. Its projects are the
+/// directories holding its marker file, and each kit command runs a shell command template from the
+/// repo root, with `{targets}` replaced by the affected projects and `{files}` by the changed files
+/// in them, shell-quoted and separated by spaces. Commands without a template are skipped.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BackendConfig {
+    pub name: String,
+    /// File marking a project, relative to the project directory: a name or glob, possibly under a
+    /// subdirectory (e.g. `*.uproject` or `ProjectSettings/ProjectVersion.txt`).
+    pub detect: String,
+    /// Repo-relative globs of the files whose change affects their project (e.g. `**/*.prefab`);
+    /// empty means any file.
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    pub build: Option<String>,
+    pub test: Option<String>,
+    pub lint: Option<String>,
+    /// A compile-only check, for `kit check`.
+    pub check: Option<String>,
+    /// Applies the linters' automatic fixes, for `kit fix`.
+    pub fix: Option<String>,
+    /// Regenerates code, for `kit generate`.
+    pub generate: Option<String>,
+    /// Builds documentation, for `kit docs`.
+    pub docs: Option<String>,
+    /// Runs on the changed files matching `inputs`, through `{files}`.
+    pub fmt: Option<String>,
+    /// Commands for `kit run <task>`, keyed by task name (e.g. `e2e = "make -C {targets} e2e"`).
+    #[serde(default)]
+    pub tasks: BTreeMap<String, String>,
+}
+
+/// A merge requirement checked by `kit gate`: the listed commands must have passed at HEAD.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GatePolicy {
+    pub name: String,
+    /// Repo-relative directories the policy covers; it applies when an affected target is in one of
+    /// them. Without paths it always applies.
+    #[serde(default)]
+    pub paths: Vec<PathBuf>,
+    /// kit commands that must have passed: build, check, test, lint, coverage, fmt, or ci.
+    pub require: Vec<String>,
+}
+
+/// Settings for `kit new`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NewConfig {
+    /// Directory of the repo's own templates, one subdirectory per template, relative to the repo
+    /// root. A template named like a built-in one replaces it.
+    pub templates: Option<PathBuf>,
+}
+
+/// Limits on a single kit run, so scheduled jobs have predictable durations.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BudgetConfig {
+    /// Wall-clock time the run may take (e.g. `"30m"`, `"1h30m"`). Steps still running when it is
+    /// used up are stopped and steps not started yet are skipped.
+    pub max_wall_time: Option<String>,
+    /// Maximum number of build, check, test, and coverage steps running at once.
+    pub max_concurrent_heavy: Option<usize>,
+    /// Expected duration per target label pattern, where `*` matches anything (e.g.
+    /// `"./services/payments/*" = "5m"`). Steps that take longer than their targets' budget are
+    /// reported.
+    pub targets: BTreeMap<String, String>,
+    /// Fail steps that run past their targets' budget instead of warning.
+    pub fail_over_budget: bool,
+}
+
+/// The pipeline `kit ci` runs.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CiConfig {
+    /// Stages in the order they run.
+    pub stages: Vec<CiStage>,
+    /// Run the remaining stages after one fails, rather than stopping.
+    pub keep_going: bool,
+}
+
+impl Default for CiConfig {
+    fn default() -> Self {
+        Self {
+            stages: vec![CiStage::Fmt, CiStage::Lint, CiStage::Build, CiStage::Test],
+            keep_going: false,
+        }
+    }
+}
+
+/// A stage of `kit ci`, each the kit command of the same name on the affected targets.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum CiStage {
+    /// Format the changed files, failing if that changed any of them.
+    Fmt,
+    Lint,
+    Check,
+    Build,
+    Test,
+}
+
+impl CiStage {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Fmt => "fmt",
+            Self::Lint => "lint",
+            Self::Check => "check",
+            Self::Build => "build",
+            Self::Test => "test",
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GradleConfig {
+    /// Android build variant the `assemble`, unit test, and lint tasks of Android modules run for
+    /// (e.g. `freeRelease`). Defaults to `debug`.
+    pub variant: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct XcodeConfig {
+    /// `xcodebuild -destination` for builds and tests (e.g. `platform=iOS Simulator,name=iPhone 15`).
+    /// Without one, xcodebuild picks the scheme's default, which iOS tests don't have.
+    pub destination: Option<String>,
+}
+
+/// What `kit build`, `test`, `lint`, and `coverage` run when nothing changed on the branch, such
+/// as on the default branch right after a merge.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UnchangedConfig {
+    pub build: UnchangedScope,
+    pub test: UnchangedScope,
+    pub lint: UnchangedScope,
+    pub coverage: UnchangedScope,
+    /// Repo-relative directories or files the `smoke` scope runs on.
+    pub smoke: Vec<PathBuf>,
+}
+
+/// The targets a command runs on when nothing changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum UnchangedScope {
+    /// Nothing, as when changes affect no targets.
+    #[default]
+    None,
+    /// The targets under the `smoke` paths.
+    Smoke,
+    /// Every target in the repo.
+    All,
+}
+
+impl UnchangedConfig {
+    /// The scope of `command` when nothing changed.
+    pub fn scope(&self, command: &str) -> UnchangedScope {
+        match command {
+            "build" => self.build,
+            "test" => self.test,
+            "lint" => self.lint,
+            "coverage" => self.coverage,
+            _ => UnchangedScope::None,
+        }
+    }
+
+    /// The repo-relative paths a scope runs on: none, the `smoke` paths, or the repo root.
+    pub fn paths(&self, scope: UnchangedScope) -> Result<Vec<PathBuf>> {
+        match scope {
+            UnchangedScope::None => Ok(vec![]),
+            UnchangedScope::Smoke if self.smoke.is_empty() => {
+                Err(KitError::ConfigError("the smoke scope needs paths in unchanged.smoke".to_string()).into())
+            }
+            UnchangedScope::Smoke => Ok(self.smoke.clone()),
+            UnchangedScope::All => Ok(vec![PathBuf::new()]),
+        }
+    }
+}
+
+/// Where `kit self-update` gets new releases. Only the user config may set it, since it decides
+/// what replaces the kit executable.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UpdateConfig {
+    /// URL of the release manifest: a JSON object with the latest `version` and, per platform (e.g.
+    /// `linux-x86_64`), an asset with the binary's `url`, `sha256`, and minisign `signature` URL.
+    pub url: Option<String>,
+    /// minisign public key release binaries must be signed with, in place of the one built into kit.
+    pub public_key: Option<String>,
+}
+
+/// How kit treats the external tools steps run.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolsConfig {
+    pub missing: MissingTools,
+}
+
+/// What happens to a step whose tool is not installed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MissingTools {
+    /// The step fails.
+    Strict,
+    /// Lint and fmt steps are skipped, since their linters and formatters are often installed only
+    /// in CI; build, test, and other steps fail.
+    #[default]
+    Degrade,
+    /// The step is skipped, whatever its command.
+    Skip,
+}
+
+impl MissingTools {
+    /// Whether a `command` step whose tool is missing is skipped rather than failed.
+    pub fn skips(self, command: &str) -> bool {
+        match self {
+            Self::Strict => false,
+            Self::Degrade => matches!(command, "lint" | "fmt" | "fmt --check"),
+            Self::Skip => true,
+        }
+    }
+}
+
+impl BudgetConfig {
+    /// `max_wall_time`, parsed.
+    pub fn max_wall_time(&self) -> Result<Option<Duration>> {
+        let Some(text) = &self.max_wall_time else {
+            return Ok(None);
+        };
+        parse_duration(text).map(Some).ok_or_else(|| {
+            KitError::ConfigError(format!(
+                "invalid budget.max_wall_time {text:?}; use e.g. \"30m\" or \"1h30m\""
+            ))
+            .into()
+        })
+    }
+
+    /// `max_concurrent_heavy`, which must be at least 1.
+    pub fn max_concurrent_heavy(&self) -> Result<Option<usize>> {
+        match self.max_concurrent_heavy {
+            Some(0) => Err(KitError::ConfigError("budget.max_concurrent_heavy must be at least 1".to_string()).into()),
+            limit => Ok(limit),
+        }
+    }
+
+    /// `targets`, with each budget parsed.
+    pub fn target_budgets(&self) -> Result<Vec<(String, Duration)>> {
+        self.targets
+            .iter()
+            .map(|(pattern, text)| {
+                let budget = parse_duration(text).ok_or_else(|| {
+                    KitError::ConfigError(format!(
+                        "invalid budget for {pattern:?} in budget.targets: {text:?}; use e.g. \"5m\""
+                    ))
+                })?;
+                Ok((pattern.clone(), budget))
+            })
+            .collect()
+    }
+}
+
+/// Parse a duration made of whole hours, minutes, and seconds, such as "90s", "30m", or "1h30m".
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let mut total = 0;
+    let mut digits = String::new();
+    for c in text.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        let n: u64 = std::mem::take(&mut digits).parse().ok()?;
+        total += n * unit;
+    }
+    (digits.is_empty() && total > 0).then(|| Duration::from_secs(total))
+}
+
+/// Format a duration the way [`parse_duration`] reads it, e.g. "1h30m".
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    let mut out = String::new();
+    for (n, unit) in [(h, 'h'), (m, 'm'), (s, 's')] {
+        if n > 0 {
+            out.push_str(&format!("{n}{unit}"));
+        }
+    }
+    if out.is_empty() { "0s".to_string() } else { out }
+}
+
+/// A deployable service, used by `kit image` and `kit deploy`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServiceConfig {
+    pub name: String,
+    /// Service directory, relative to the repo root.
+    pub dir: PathBuf,
+    /// Additional paths (e.g. shared libraries) whose changes also affect this service.
+    #[serde(default)]
+    pub inputs: Vec<PathBuf>,
+    /// Image name without a tag (e.g. `ghcr.io/acme/api`).
+    pub image: Option<String>,
+    /// Dockerfile relative to the repo root (default: `<dir>/Dockerfile`).
+    pub dockerfile: Option<PathBuf>,
+    /// Build context relative to the repo root (default: `<dir>`).
+    pub context: Option<PathBuf>,
+    /// Deployment command template per environment (e.g. `staging = "helm upgrade ..."`).
+    #[serde(default)]
+    pub deploy: BTreeMap<String, String>,
+}
+
+/// Settings for `kit image`. `{image}` in cache settings is replaced with the service's image name.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ImageConfig {
+    /// Value for `docker buildx build --cache-from` (e.g. `type=registry,ref={image}:buildcache`).
+    pub cache_from: Option<String>,
+    /// Value for `docker buildx build --cache-to` (e.g. `type=registry,ref={image}:buildcache,mode=max`).
+    pub cache_to: Option<String>,
+    /// Target platforms (e.g. `["linux/amd64", "linux/arm64"]`).
+    pub platforms: Vec<String>,
+    /// Smoke test the Docker backend's `kit test` runs on each image it builds, as a shell command
+    /// with `{image}` replaced by the image's tag (e.g. `docker run --rm {image} --version`).
+    pub test: Option<String>,
+}
+
+impl Config {
+    /// Load `kit.toml` from the repo root over the user's config, falling back to defaults for
+    /// whatever neither sets.
+    pub fn load(repo_root: &Path) -> Result<Self> {
+        let layers = Layers::read(user_path().as_deref(), repo_root)?;
+        parse(layers.merged(), "kit.toml and the user config")
+    }
+
+    /// Load the user's config alone, for commands that run outside a repo.
+    pub fn load_user() -> Result<Self> {
+        let layers = Layers {
+            user: user_path().as_deref().map(read_table).transpose()?.flatten(),
+            repo: None,
+        };
+        parse(layers.merged(), "the user config")
+    }
+
+    /// `jobs`, which must be at least 1.
+    pub fn jobs(&self) -> Result<Option<usize>> {
+        match self.jobs {
+            Some(0) => Err(KitError::ConfigError("jobs must be at least 1".to_string()).into()),
+            jobs => Ok(jobs),
+        }
+    }
+}
+
+/// The user's config file: `$XDG_CONFIG_HOME/kit/config.toml`, or `~/.config/kit/config.toml`.
+pub fn user_path() -> Option<PathBuf> {
+    let dir = match std::env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(dir.join("kit").join("config.toml"))
+}
+
+/// The config files, each as read and checked on its own, so errors name the file at fault.
+pub struct Layers {
+    pub user: Option<(PathBuf, toml::Table)>,
+    pub repo: Option<(PathBuf, toml::Table)>,
+}
+
+impl Layers {
+    pub fn read(user: Option<&Path>, repo_root: &Path) -> Result<Self> {
+        let repo = read_table(&repo_root.join(FILE_NAME))?;
+        if let Some((path, table)) = &repo
+            && let Some(key) = USER_ONLY.iter().find(|key| table.contains_key(**key))
+        {
+            return Err(KitError::ConfigError(format!(
+                "invalid config in {}: [{key}] can only be set in the user config",
+                path.display()
+            ))
+            .into());
+        }
+        Ok(Self {
+            user: user.map(read_table).transpose()?.flatten(),
+            repo,
+        })
+    }
+
+    /// The user's settings with the repo's on top.
+    pub fn merged(&self) -> toml::Table {
+        let mut merged = toml::Table::new();
+        for (_, table) in self.user.iter().chain(&self.repo) {
+            merge(&mut merged, table.clone());
+        }
+        merged
+    }
+
+    /// A setting's effective value and the file it comes from (kit.toml if both set it), for a
+    /// dotted key such as `budget.max_wall_time`.
+    pub fn get(&self, key: &str) -> Option<(toml::Value, &Path)> {
+        let path = [&self.repo, &self.user]
+            .into_iter()
+            .flatten()
+            .find(|(_, table)| lookup(table, key).is_some())
+            .map(|(path, _)| path.as_path())?;
+        Some((lookup(&self.merged(), key)?.clone(), path))
+    }
+}
+
+/// Set a dotted key in the user's config file, creating it if needed. The value is read as TOML
+/// (`2`, `true`, `["a", "b"]`), or taken as a string when it isn't valid TOML.
+pub fn set_user(key: &str, value: &str) -> Result<PathBuf> {
+    let path = user_path().ok_or_else(|| KitError::ConfigError("HOME is not set".to_string()))?;
+    set(&path, key, value)?;
+    Ok(path)
+}
+
+fn set(path: &Path, key: &str, value: &str) -> Result<()> {
+    let mut table = read_table(path)?.map(|(_, table)| table).unwrap_or_default();
+    insert(&mut table, key, parse_value(value))?;
+    parse(table.clone(), &path.display().to_string())?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    let text = toml::to_string_pretty(&table).context("failed to serialize config")?;
+    std::fs::write(path, text).with_context(|| format!("failed to write {}", path.display()))
+}
+
+fn read_table(path: &Path) -> Result<Option<(PathBuf, toml::Table)>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = std::fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let table: toml::Table = toml::from_str(&text)
+        .map_err(|e| KitError::ConfigError(format!("invalid config in {}: {e}", path.display())))?;
+    parse(table.clone(), &path.display().to_string())?;
+    Ok(Some((path.to_path_buf(), table)))
+}
+
+fn parse(table: toml::Table, source: &str) -> Result<Config> {
+    toml::Value::Table(table)
+        .try_into()
+        .map_err(|e| KitError::ConfigError(format!("invalid config in {source}: {e}")).into())
+}
+
+/// Merge `overlay` into `base`: tables key by key, anything else replaced.
+fn merge(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => merge(base, overlay),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn lookup<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+    let (head, rest) = key.split_once('.').map_or((key, None), |(h, r)| (h, Some(r)));
+    let value = table.get(head)?;
+    match rest {
+        None => Some(value),
+        Some(rest) => lookup(value.as_table()?, rest),
+    }
+}
+
+fn insert(table: &mut toml::Table, key: &str, value: toml::Value) -> Result<()> {
+    match key.split_once('.') {
+        None => {
+            table.insert(key.to_string(), value);
+            Ok(())
+        }
+        Some((head, rest)) => {
+            let entry = table
+                .entry(head)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            let toml::Value::Table(inner) = entry else {
+                return Err(KitError::ConfigError(format!("{head} is not a table")).into());
+            };
+            insert(inner, rest, value)
+        }
+    }
+}
+
+fn parse_value(text: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {text}"))
+        .ok()
+        .and_then(|mut t| t.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(text.to_string()))
+}
+
+#[cfg(test)]
+#[path = "config_test.rs"]
+mod tests;
@@ -0,0 +1,132 @@
+use super::*;
+
+#[test]
+fn parse_duration_reads_hours_minutes_and_seconds() {
+    assert_eq!(parse_duration("30m"), Some(Duration::from_secs(1800)));
+    assert_eq!(parse_duration("1h30m"), Some(Duration::from_secs(5400)));
+    assert_eq!(parse_duration("90s"), Some(Duration::from_secs(90)));
+    for invalid in ["", "30", "m", "1d", "0s", "1.5h"] {
+        assert_eq!(parse_duration(invalid), None, "{invalid}");
+    }
+    assert_eq!(format_duration(Duration::from_secs(5400)), "1h30m");
+    assert_eq!(format_duration(Duration::from_secs(61)), "1m1s");
+}
+
+#[test]
+fn budget_rejects_invalid_limits() {
+    let config: Config = toml::from_str("[budget]\nmax_wall_time = \"30m\"\nmax_concurrent_heavy = 2\n").unwrap();
+    assert_eq!(config.budget.max_wall_time().unwrap(), Some(Duration::from_secs(1800)));
+    assert_eq!(config.budget.max_concurrent_heavy().unwrap(), Some(2));
+
+    let config: Config = toml::from_str("[budget]\nmax_wall_time = \"soon\"\nmax_concurrent_heavy = 0\n").unwrap();
+    assert!(config.budget.max_wall_time().is_err());
+    assert!(config.budget.max_concurrent_heavy().is_err());
+}
+
+#[test]
+fn jobs_must_be_positive() {
+    assert_eq!(toml::from_str::<Config>("jobs = 4\n").unwrap().jobs().unwrap(), Some(4));
+    assert!(toml::from_str::<Config>("jobs = 0\n").unwrap().jobs().is_err());
+}
+
+#[test]
+fn target_budgets_are_parsed() {
+    let config: Config = toml::from_str("[budget.targets]\n\"./services/*\" = \"5m\"\n").unwrap();
+    assert_eq!(
+        config.budget.target_budgets().unwrap(),
+        [("./services/*".to_string(), Duration::from_secs(300))]
+    );
+    let config: Config = toml::from_str("[budget.targets]\n\"./services/*\" = \"fast\"\n").unwrap();
+    assert!(config.budget.target_budgets().is_err());
+}
+
+#[test]
+fn unchanged_scopes_are_per_command() {
+    let config: Config =
+        toml::from_str("[unchanged]\ntest = \"smoke\"\nbuild = \"all\"\nsmoke = [\"services/api\"]\n").unwrap();
+    assert_eq!(config.unchanged.scope("test"), UnchangedScope::Smoke);
+    assert_eq!(config.unchanged.scope("lint"), UnchangedScope::None);
+    assert_eq!(
+        config.unchanged.paths(UnchangedScope::Smoke).unwrap(),
+        [PathBuf::from("services/api")]
+    );
+    assert_eq!(config.unchanged.paths(UnchangedScope::All).unwrap(), [PathBuf::new()]);
+    assert!(UnchangedConfig::default().paths(UnchangedScope::Smoke).is_err());
+}
+
+#[test]
+fn missing_tools_policy_decides_which_steps_are_skipped() {
+    assert_eq!(Config::default().tools.missing, MissingTools::Degrade);
+    let config: Config = toml::from_str("[tools]\nmissing = \"strict\"\n").unwrap();
+    assert_eq!(config.tools.missing, MissingTools::Strict);
+    assert!(toml::from_str::<Config>("[tools]\nmissing = \"sometimes\"\n").is_err());
+
+    assert!(MissingTools::Degrade.skips("lint"));
+    assert!(!MissingTools::Degrade.skips("test"));
+    assert!(!MissingTools::Strict.skips("fmt"));
+    assert!(MissingTools::Skip.skips("build"));
+}
+
+#[test]
+fn repo_config_is_layered_over_the_user_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let user = dir.path().join("user.toml");
+    std::fs::write(
+        &user,
+        "report = [\"webhook=https://example.com/me\"]\n[budget]\nmax_wall_time = \"1h\"\nmax_concurrent_heavy = 2\n",
+    )
+    .unwrap();
+    std::fs::write(dir.path().join("kit.toml"), "[budget]\nmax_wall_time = \"30m\"\n").unwrap();
+    let layers = Layers::read(Some(&user), dir.path()).unwrap();
+    let config = parse(layers.merged(), "test").unwrap();
+    assert_eq!(config.budget.max_wall_time.as_deref(), Some("30m"));
+    assert_eq!(config.budget.max_concurrent_heavy, Some(2));
+    assert_eq!(config.report, ["webhook=https://example.com/me"]);
+
+    let (value, path) = layers.get("budget.max_wall_time").unwrap();
+    assert_eq!(value.as_str(), Some("30m"));
+    assert_eq!(path, dir.path().join("kit.toml"));
+    assert_eq!(layers.get("budget.max_concurrent_heavy").unwrap().1, user);
+    assert!(layers.get("budget.missing").is_none());
+}
+
+#[test]
+fn invalid_user_config_names_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let user = dir.path().join("user.toml");
+    std::fs::write(&user, "colour = true\n").unwrap();
+    let err = Layers::read(Some(&user), dir.path()).err().unwrap();
+    assert!(format!("{err:#}").contains("user.toml"), "{err:#}");
+}
+
+#[test]
+fn update_settings_are_rejected_in_kit_toml() {
+    let dir = tempfile::tempdir().unwrap();
+    let user = dir.path().join("user.toml");
+    std::fs::write(&user, "[update]\nurl = \"https://example.com/kit.json\"\n").unwrap();
+    assert!(Layers::read(Some(&user), dir.path()).is_ok());
+    std::fs::write(
+        dir.path().join("kit.toml"),
+        "[update]\nurl = \"https://evil.example.com\"\n",
+    )
+    .unwrap();
+    let err = Layers::read(Some(&user), dir.path()).err().unwrap();
+    assert!(
+        format!("{err:#}").contains("[update] can only be set in the user config"),
+        "{err:#}"
+    );
+}
+
+#[test]
+fn set_writes_typed_values_and_rejects_unknown_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("kit/config.toml");
+    set(&path, "budget.max_concurrent_heavy", "4").unwrap();
+    set(&path, "tools.missing", "skip").unwrap();
+    let config: Config = toml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(config.budget.max_concurrent_heavy, Some(4));
+    assert_eq!(config.tools.missing, MissingTools::Skip);
+
+    assert!(set(&path, "budget.jobs", "4").is_err());
+    assert!(set(&path, "tools.missing.policy", "skip").is_err());
+}
@@ -0,0 +1,377 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::backend::Target;
+use crate::{git, state};
+
+/// Per-target coverage records kept per repo; older ones are dropped when new ones are recorded.
+const KEEP: usize = 5000;
+
+/// Line hit counts per source file, keyed by path relative to the repo root.
+#[derive(Debug, Default, PartialEq)]
+pub struct Coverage {
+    pub files: BTreeMap<PathBuf, BTreeMap<u32, u64>>,
+}
+
+impl Coverage {
+    /// Combine hit counts from another run.
+    pub fn merge(&mut self, other: Coverage) {
+        for (file, lines) in other.files {
+            let entry = self.files.entry(file).or_default();
+            for (line, hits) in lines {
+                *entry.entry(line).or_default() += hits;
+            }
+        }
+    }
+
+    /// Parse an lcov tracefile. Relative `SF:` paths are resolved against `base`; paths outside the
+    /// repo root are dropped.
+    pub fn parse_lcov(text: &str, base: &Path, repo_root: &Path) -> Self {
+        let mut cov = Self::default();
+        let mut current: Option<PathBuf> = None;
+        for line in text.lines() {
+            let line = line.trim();
+            if let Some(sf) = line.strip_prefix("SF:") {
+                let path = base.join(sf);
+                current = path.strip_prefix(repo_root).ok().map(Path::to_path_buf);
+            } else if let Some(da) = line.strip_prefix("DA:") {
+                let Some(file) = &current else { continue };
+                let mut fields = da.split(',');
+                let (Some(Ok(n)), Some(Ok(hits))) = (
+                    fields.next().map(str::parse::<u32>),
+                    fields.next().map(str::parse::<u64>),
+                ) else {
+                    continue;
+                };
+                *cov.files.entry(file.clone()).or_default().entry(n).or_default() += hits;
+            } else if line == "end_of_record" {
+                current = None;
+            }
+        }
+        cov
+    }
+
+    /// Parse a Go coverprofile. File names are import paths, mapped to repo-relative paths through
+    /// `modules` (module path → module directory relative to the repo root).
+    pub fn parse_go(text: &str, modules: &[(String, PathBuf)]) -> Self {
+        let mut cov = Self::default();
+        for line in text.lines().filter(|l| !l.starts_with("mode:")) {
+            // name.go:startLine.startCol,endLine.endCol numStmts count
+            let Some((name, rest)) = line.rsplit_once(':') else {
+                continue;
+            };
+            let mut fields = rest.split_whitespace();
+            let (Some(range), Some(_), Some(Ok(count))) =
+                (fields.next(), fields.next(), fields.next().map(str::parse::<u64>))
+            else {
+                continue;
+            };
+            let Some((start, end)) = range.split_once(',') else {
+                continue;
+            };
+            let line_of = |pos: &str| pos.split('.').next().and_then(|l| l.parse::<u32>().ok());
+            let (Some(start), Some(end)) = (line_of(start), line_of(end)) else {
+                continue;
+            };
+            let Some(file) = go_import_to_path(name, modules) else {
+                continue;
+            };
+            let lines = cov.files.entry(file).or_default();
+            for n in start..=end {
+                let hits = lines.entry(n).or_default();
+                *hits = (*hits).max(count);
+            }
+        }
+        cov
+    }
+
+    /// Instrumented and covered line counts for one file.
+    fn counts(lines: &BTreeMap<u32, u64>) -> (usize, usize) {
+        (lines.len(), lines.values().filter(|hits| **hits > 0).count())
+    }
+
+    /// Render as an lcov tracefile with paths relative to the repo root.
+    pub fn to_lcov(&self) -> String {
+        let mut out = String::new();
+        for (file, lines) in &self.files {
+            let _ = writeln!(out, "SF:{}", file.to_string_lossy().replace('\\', "/"));
+            for (line, hits) in lines {
+                let _ = writeln!(out, "DA:{line},{hits}");
+            }
+            let (found, hit) = Self::counts(lines);
+            let _ = writeln!(out, "LF:{found}\nLH:{hit}\nend_of_record");
+        }
+        out
+    }
+
+    /// Render as a self-contained HTML page: a summary table, then the source of each file (read
+    /// from `repo_root`) with covered and uncovered lines highlighted.
+    pub fn to_html(&self, repo_root: &Path) -> String {
+        let mut out = String::from(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>kit coverage</title><style>\n\
+             body{font-family:sans-serif} pre{line-height:1.3} td{padding:0 1em}\n\
+             .hit{background:#dfd} .miss{background:#fdd}\n\
+             </style></head><body>\n<h1>Coverage</h1>\n<table>\n",
+        );
+        let (mut total_found, mut total_hit) = (0, 0);
+        for (i, (file, lines)) in self.files.iter().enumerate() {
+            let (found, hit) = Self::counts(lines);
+            total_found += found;
+            total_hit += hit;
+            let _ = writeln!(
+                out,
+                "<tr><td><a href=\"#f{i}\">{}</a></td><td>{hit}/{found}</td><td>{:.1}%</td></tr>",
+                escape(&file.to_string_lossy()),
+                percent(hit, found),
+            );
+        }
+        let _ = writeln!(
+            out,
+            "<tr><th>total</th><th>{total_hit}/{total_found}</th><th>{:.1}%</th></tr>\n</table>",
+            percent(total_hit, total_found),
+        );
+        for (i, (file, lines)) in self.files.iter().enumerate() {
+            let _ = writeln!(out, "<h2 id=\"f{i}\">{}</h2>", escape(&file.to_string_lossy()));
+            let Ok(source) = std::fs::read_to_string(repo_root.join(file)) else {
+                out.push_str("<p>Source not found.</p>\n");
+                continue;
+            };
+            out.push_str("<pre>");
+            for (n, text) in (1..).zip(source.lines()) {
+                let class = match lines.get(&n) {
+                    Some(0) => " class=\"miss\"",
+                    Some(_) => " class=\"hit\"",
+                    None => "",
+                };
+                let _ = writeln!(out, "<span{class}>{n:5}  {}</span>", escape(text));
+            }
+            out.push_str("</pre>\n");
+        }
+        out.push_str("</body></html>\n");
+        out
+    }
+
+    /// Write the coverage to `lcov.info` and `index.html` in `dir`, returning the HTML report's path.
+    pub fn write_reports(&self, repo_root: &Path, dir: &Path) -> Result<PathBuf> {
+        let lcov = dir.join("lcov.info");
+        std::fs::write(&lcov, self.to_lcov()).with_context(|| format!("failed to write {}", lcov.display()))?;
+        let html = dir.join("index.html");
+        std::fs::write(&html, self.to_html(repo_root))
+            .with_context(|| format!("failed to write {}", html.display()))?;
+        Ok(html)
+    }
+}
+
+fn percent(covered: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 100.0;
+    }
+    100.0 * covered as f64 / total as f64
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Map a Go import-path file name to a repo-relative path using the longest matching module path.
+pub fn go_import_to_path(name: &str, modules: &[(String, PathBuf)]) -> Option<PathBuf> {
+    modules
+        .iter()
+        .filter_map(|(module, dir)| {
+            let rest = name.strip_prefix(module.as_str())?.strip_prefix('/')?;
+            Some((module.len(), dir.join(rest)))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, path)| path)
+}
+
+/// Coverage of changed lines in one file. Only instrumented lines are counted.
+#[derive(Debug, PartialEq)]
+pub struct FileDiffCoverage {
+    pub file: PathBuf,
+    pub covered: usize,
+    pub total: usize,
+    pub missing: Vec<u32>,
+}
+
+/// Intersect coverage data with the lines changed on the branch.
+pub fn diff_coverage(cov: &Coverage, changed_lines: &BTreeMap<PathBuf, BTreeSet<u32>>) -> Vec<FileDiffCoverage> {
+    let mut result = Vec::new();
+    for (file, lines) in changed_lines {
+        let Some(hits) = cov.files.get(file) else { continue };
+        let mut covered = 0;
+        let mut missing = Vec::new();
+        for line in lines {
+            match hits.get(line) {
+                Some(0) => missing.push(*line),
+                Some(_) => covered += 1,
+                None => {}
+            }
+        }
+        let total = covered + missing.len();
+        if total > 0 {
+            result.push(FileDiffCoverage {
+                file: file.clone(),
+                covered,
+                total,
+                missing,
+            });
+        }
+    }
+    result
+}
+
+/// Collapse sorted line numbers into ranges like `3-5, 9`.
+pub fn format_ranges(lines: &[u32]) -> String {
+    let mut ranges: Vec<String> = Vec::new();
+    let mut iter = lines.iter().copied().peekable();
+    while let Some(start) = iter.next() {
+        let mut end = start;
+        while iter.peek() == Some(&(end + 1)) {
+            end = iter.next().unwrap_or(end);
+        }
+        ranges.push(if start == end {
+            start.to_string()
+        } else {
+            format!("{start}-{end}")
+        });
+    }
+    ranges.join(", ")
+}
+
+/// Print per-file diff coverage and return the overall percentage, or None if no changed line is instrumented.
+pub fn report(files: &[FileDiffCoverage]) -> Option<f64> {
+    let covered: usize = files.iter().map(|f| f.covered).sum();
+    let total: usize = files.iter().map(|f| f.total).sum();
+    for f in files {
+        let pct = 100.0 * f.covered as f64 / f.total as f64;
+        print!("{pct:6.1}%  {} ({}/{})", f.file.display(), f.covered, f.total);
+        if f.missing.is_empty() {
+            println!();
+        } else {
+            println!("  missing: {}", format_ranges(&f.missing));
+        }
+    }
+    if total == 0 {
+        return None;
+    }
+    let pct = 100.0 * covered as f64 / total as f64;
+    println!("{pct:6.1}%  total ({covered}/{total} changed lines covered)");
+    Some(pct)
+}
+
+/// Line coverage of one target, over every instrumented line in its directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TargetCoverage {
+    pub label: String,
+    pub covered: usize,
+    pub total: usize,
+}
+
+/// A target's coverage at a commit, as kept in `.kit/history/coverage.jsonl`.
+#[derive(Debug, Serialize, Deserialize)]
+struct Recorded {
+    commit: String,
+    #[serde(flatten)]
+    coverage: TargetCoverage,
+}
+
+/// Coverage of each target with instrumented lines.
+pub fn by_target(cov: &Coverage, repo_root: &Path, targets: &[Target]) -> Vec<TargetCoverage> {
+    targets
+        .iter()
+        .filter_map(|target| {
+            let (total, covered) = cov
+                .files
+                .iter()
+                .filter(|(file, _)| repo_root.join(file).starts_with(&target.dir))
+                .map(|(_, lines)| Coverage::counts(lines))
+                .fold((0, 0), |(total, covered), (t, c)| (total + t, covered + c));
+            (total > 0).then(|| TargetCoverage {
+                label: target.label.clone(),
+                covered,
+                total,
+            })
+        })
+        .collect()
+}
+
+fn history_path(repo_root: &Path) -> Result<PathBuf> {
+    Ok(state::dir(repo_root, "history")?.join("coverage.jsonl"))
+}
+
+/// Record the targets' coverage at HEAD, to compare branches forked from it against. Runs with
+/// uncommitted changes are not recorded.
+pub fn record(repo_root: &Path, targets: &[TargetCoverage]) -> Result<()> {
+    if targets.is_empty() || !git::is_clean(repo_root)? {
+        return Ok(());
+    }
+    let commit = git::head(repo_root)?;
+    let path = history_path(repo_root)?;
+    let text = std::fs::read_to_string(&path).unwrap_or_default();
+    let mut lines: Vec<String> = text.lines().map(String::from).collect();
+    for coverage in targets {
+        let recorded = Recorded {
+            commit: commit.clone(),
+            coverage: coverage.clone(),
+        };
+        lines.push(serde_json::to_string(&recorded)?);
+    }
+    let kept = &lines[lines.len().saturating_sub(KEEP)..];
+    let mut out = String::new();
+    for line in kept {
+        let _ = writeln!(out, "{line}");
+    }
+    std::fs::write(&path, out).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Each target's coverage as last recorded at `commit`.
+pub fn baseline(repo_root: &Path, commit: &str) -> Result<BTreeMap<String, TargetCoverage>> {
+    let text = std::fs::read_to_string(history_path(repo_root)?).unwrap_or_default();
+    Ok(baseline_in(&text, commit))
+}
+
+fn baseline_in(history: &str, commit: &str) -> BTreeMap<String, TargetCoverage> {
+    history
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Recorded>(line).ok())
+        .filter(|r| r.commit == commit)
+        .map(|r| (r.coverage.label.clone(), r.coverage))
+        .collect()
+}
+
+/// How a target's coverage moved from the baseline: "+1.8 vs main", "-0.4 vs main", "±0.0 vs main",
+/// or "new" without a baseline.
+fn delta(coverage: &TargetCoverage, baseline: Option<&TargetCoverage>, base: &str) -> String {
+    let Some(baseline) = baseline else {
+        return "new".to_string();
+    };
+    let change = percent(coverage.covered, coverage.total) - percent(baseline.covered, baseline.total);
+    match change {
+        c if c >= 0.05 => format!("+{c:.1} vs {base}"),
+        c if c <= -0.05 => format!("{c:.1} vs {base}"),
+        _ => format!("±0.0 vs {base}"),
+    }
+}
+
+/// Print each target's coverage and its change from the baseline on `base`.
+pub fn report_targets(targets: &[TargetCoverage], baseline: &BTreeMap<String, TargetCoverage>, base: &str) {
+    for target in targets {
+        let pct = percent(target.covered, target.total);
+        println!(
+            "{pct:6.1}%  {} ({}/{} lines, {})",
+            target.label,
+            target.covered,
+            target.total,
+            delta(target, baseline.get(&target.label), base)
+        );
+    }
+}
+
+#[cfg(test)]
+#[path = "coverage_test.rs"]
+mod tests;
@@ -0,0 +1,152 @@
+use super::*;
+
+fn lines(pairs: &[(u32, u64)]) -> BTreeMap<u32, u64> {
+    pairs.iter().copied().collect()
+}
+
+#[test]
+fn parse_lcov_resolves_paths() {
+    let text = "TN:\nSF:src/a.ts\nDA:1,1\nDA:2,0\nend_of_record\nSF:/elsewhere/b.ts\nDA:1,1\nend_of_record\n";
+    let root = Path::new("/repo");
+    let cov = Coverage::parse_lcov(text, &root.join("web"), root);
+    assert_eq!(cov.files.len(), 1);
+    assert_eq!(cov.files[Path::new("web/src/a.ts")], lines(&[(1, 1), (2, 0)]));
+}
+
+#[test]
+fn parse_go_maps_import_paths() {
+    let text = "mode: set\n\
+                example.com/m/pkg/db/db.go:3.10,5.2 2 1\n\
+                example.com/m/pkg/db/db.go:7.1,7.20 1 0\n\
+                example.com/m/tools/gen/main.go:1.1,2.1 1 1\n\
+                github.com/other/x.go:1.1,1.2 1 1\n";
+    let modules = vec![
+        ("example.com/m".to_string(), PathBuf::new()),
+        ("example.com/m/tools".to_string(), PathBuf::from("tools")),
+    ];
+    let cov = Coverage::parse_go(text, &modules);
+    assert_eq!(
+        cov.files[Path::new("pkg/db/db.go")],
+        lines(&[(3, 1), (4, 1), (5, 1), (7, 0)])
+    );
+    assert_eq!(cov.files[Path::new("tools/gen/main.go")], lines(&[(1, 1), (2, 1)]));
+    assert_eq!(cov.files.len(), 2);
+}
+
+#[test]
+fn diff_coverage_counts_instrumented_changed_lines() {
+    let mut cov = Coverage::default();
+    cov.files
+        .insert(PathBuf::from("a.go"), lines(&[(1, 1), (2, 0), (3, 4), (4, 0)]));
+    let changed: BTreeMap<PathBuf, BTreeSet<u32>> = [
+        (PathBuf::from("a.go"), [2, 3, 4, 10].into_iter().collect()),
+        (PathBuf::from("README.md"), [1].into_iter().collect()),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(
+        diff_coverage(&cov, &changed),
+        vec![FileDiffCoverage {
+            file: PathBuf::from("a.go"),
+            covered: 1,
+            total: 3,
+            missing: vec![2, 4],
+        }]
+    );
+}
+
+#[test]
+fn merge_sums_hits() {
+    let mut a = Coverage::default();
+    a.files.insert(PathBuf::from("a.go"), lines(&[(1, 1), (2, 0)]));
+    let mut b = Coverage::default();
+    b.files.insert(PathBuf::from("a.go"), lines(&[(2, 3)]));
+    a.merge(b);
+    assert_eq!(a.files[Path::new("a.go")], lines(&[(1, 1), (2, 3)]));
+}
+
+#[test]
+fn format_ranges_collapses_runs() {
+    assert_eq!(format_ranges(&[1, 2, 3, 5, 7, 8]), "1-3, 5, 7-8");
+    assert_eq!(format_ranges(&[]), "");
+}
+
+#[test]
+fn to_lcov_writes_a_record_per_file() {
+    let mut cov = Coverage::default();
+    cov.files.insert(PathBuf::from("pkg/a.go"), lines(&[(3, 2), (4, 0)]));
+    cov.files.insert(PathBuf::from("tools/b.go"), lines(&[(1, 1)]));
+    assert_eq!(
+        cov.to_lcov(),
+        "SF:pkg/a.go\nDA:3,2\nDA:4,0\nLF:2\nLH:1\nend_of_record\n\
+         SF:tools/b.go\nDA:1,1\nLF:1\nLH:1\nend_of_record\n"
+    );
+    assert_eq!(
+        Coverage::parse_lcov(&cov.to_lcov(), Path::new("/repo"), Path::new("/repo")),
+        cov
+    );
+}
+
+#[test]
+fn to_html_marks_hit_and_missed_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.go"), "package a\nif x < y {\n}\n").unwrap();
+    let mut cov = Coverage::default();
+    cov.files.insert(PathBuf::from("a.go"), lines(&[(2, 1), (3, 0)]));
+    cov.files.insert(PathBuf::from("gone.go"), lines(&[(1, 1)]));
+    let html = cov.to_html(dir.path());
+    assert!(html.contains("<span>    1  package a</span>"));
+    assert!(html.contains("<span class=\"hit\">    2  if x &lt; y {</span>"));
+    assert!(html.contains("<span class=\"miss\">    3  }</span>"));
+    assert!(html.contains("<th>2/3</th><th>66.7%</th>"));
+    assert!(html.contains("Source not found."));
+}
+
+#[test]
+fn target_coverage_counts_the_files_in_its_directory() {
+    let repo = tempfile::tempdir().unwrap();
+    let mut cov = Coverage::default();
+    cov.files
+        .insert(PathBuf::from("pkg/api/handler.go"), lines(&[(1, 1), (2, 0)]));
+    cov.files
+        .insert(PathBuf::from("pkg/api/v2/routes.go"), lines(&[(1, 3), (4, 1)]));
+    cov.files.insert(PathBuf::from("pkg/db/db.go"), lines(&[(1, 0)]));
+    let target = |label: &str, dir: &str| Target {
+        label: label.to_string(),
+        dir: repo.path().join(dir),
+        ..Default::default()
+    };
+    let found = by_target(
+        &cov,
+        repo.path(),
+        &[target("./pkg/api/...", "pkg/api"), target("./cmd/...", "cmd")],
+    );
+    assert_eq!(
+        found,
+        [TargetCoverage {
+            label: "./pkg/api/...".to_string(),
+            covered: 3,
+            total: 4,
+        }]
+    );
+}
+
+#[test]
+fn deltas_compare_against_the_baseline_commit() {
+    let history = "\
+{\"commit\":\"abc\",\"label\":\"./pkg/api/...\",\"covered\":50,\"total\":100}
+{\"commit\":\"def\",\"label\":\"./pkg/api/...\",\"covered\":90,\"total\":100}
+not json
+";
+    let baseline = baseline_in(history, "abc");
+    let now = |covered| TargetCoverage {
+        label: "./pkg/api/...".to_string(),
+        covered,
+        total: 100,
+    };
+    let base = baseline.get("./pkg/api/...");
+    assert_eq!(delta(&now(62), base, "main"), "+12.0 vs main");
+    assert_eq!(delta(&now(45), base, "main"), "-5.0 vs main");
+    assert_eq!(delta(&now(50), base, "main"), "±0.0 vs main");
+    assert_eq!(delta(&now(50), None, "main"), "new");
+}
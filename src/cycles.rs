@@ -0,0 +1,120 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use regex::Regex;
+
+use crate::backend::workspace_graph;
+use crate::git;
+
+static PROTO_IMPORT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?m)^\s*import\s+(?:public\s+|weak\s+)?"([^"]+)"\s*;"#).unwrap());
+
+/// Nodes and the nodes each one depends on.
+pub type Graph = BTreeMap<String, BTreeSet<String>>;
+
+/// Fail if workspace packages or proto files depend on each other in a cycle. Package managers
+/// install cyclic workspaces without complaint and protoc only reports the first cycle it meets,
+/// so kit checks the whole graph and prints each cycle.
+pub fn lint(repo_root: &Path) -> Result<()> {
+    let mut graphs: Vec<(&str, Graph)> = Vec::new();
+    if repo_root.join("package.json").is_file() {
+        graphs.push(("workspace package", workspace_graph(repo_root)?));
+    }
+    let protos = git::find_files(repo_root, "*.proto")?;
+    if !protos.is_empty() {
+        let files: Vec<(PathBuf, String)> = protos
+            .into_iter()
+            .map(|f| {
+                let text = std::fs::read_to_string(repo_root.join(&f)).unwrap_or_default();
+                (f, text)
+            })
+            .collect();
+        graphs.push(("proto file", proto_graph(&files)));
+    }
+    if graphs.is_empty() {
+        eprintln!("kit: no workspace packages or proto files to check for cycles");
+        return Ok(());
+    }
+    let mut count = 0;
+    for (kind, graph) in &graphs {
+        eprintln!("kit: checking {} {kind}(s) for dependency cycles", graph.len());
+        for cycle in find_cycles(graph) {
+            eprintln!("kit: {kind} cycle: {}", cycle.join(" -> "));
+            count += 1;
+        }
+    }
+    if count > 0 {
+        anyhow::bail!("{count} dependency cycle(s)");
+    }
+    Ok(())
+}
+
+/// Proto files, by repo-relative path, and the files they import. An import resolves to the file
+/// whose path ends with it (proto include roots are not known), the shortest such path if there
+/// are several; imports of files outside the repo are left out.
+fn proto_graph(files: &[(PathBuf, String)]) -> Graph {
+    let paths: Vec<String> = files
+        .iter()
+        .map(|(f, _)| f.to_string_lossy().replace('\\', "/"))
+        .collect();
+    let resolve = |import: &str| {
+        paths
+            .iter()
+            .filter(|p| *p == import || p.ends_with(&format!("/{import}")))
+            .min_by_key(|p| p.len())
+            .cloned()
+    };
+    files
+        .iter()
+        .zip(&paths)
+        .map(|((_, text), path)| {
+            let imports = PROTO_IMPORT
+                .captures_iter(text)
+                .filter_map(|c| resolve(&c[1]))
+                .collect();
+            (path.clone(), imports)
+        })
+        .collect()
+}
+
+/// Cycles in the graph, each starting and ending at its smallest node. Every strongly connected
+/// group of nodes yields at least one cycle, though not necessarily every cycle through it.
+pub fn find_cycles(graph: &Graph) -> Vec<Vec<String>> {
+    fn visit<'a>(
+        node: &'a str,
+        graph: &'a Graph,
+        stack: &mut Vec<&'a str>,
+        done: &mut BTreeSet<&'a str>,
+        cycles: &mut BTreeSet<Vec<String>>,
+    ) {
+        stack.push(node);
+        for next in graph.get(node).into_iter().flatten() {
+            if let Some(start) = stack.iter().position(|n| n == next) {
+                let mut cycle: Vec<String> = stack[start..].iter().map(|n| n.to_string()).collect();
+                let smallest = (0..cycle.len()).min_by_key(|&i| &cycle[i]).unwrap_or(0);
+                cycle.rotate_left(smallest);
+                cycle.push(cycle[0].clone());
+                cycles.insert(cycle);
+            } else if !done.contains(next.as_str()) {
+                visit(next, graph, stack, done, cycles);
+            }
+        }
+        stack.pop();
+        done.insert(node);
+    }
+
+    let mut cycles = BTreeSet::new();
+    let mut done = BTreeSet::new();
+    for node in graph.keys() {
+        if !done.contains(node.as_str()) {
+            visit(node, graph, &mut Vec::new(), &mut done, &mut cycles);
+        }
+    }
+    cycles.into_iter().collect()
+}
+
+#[cfg(test)]
+#[path = "cycles_test.rs"]
+mod tests;
@@ -0,0 +1,55 @@
+use super::*;
+
+fn graph(edges: &[(&str, &[&str])]) -> Graph {
+    edges
+        .iter()
+        .map(|(node, deps)| (node.to_string(), deps.iter().map(|d| d.to_string()).collect()))
+        .collect()
+}
+
+#[test]
+fn cycles_are_reported_from_their_smallest_node() {
+    let g = graph(&[
+        ("@acme/web", &["@acme/ui"]),
+        ("@acme/ui", &["@acme/theme"]),
+        ("@acme/theme", &["@acme/ui"]),
+        ("@acme/api", &["@acme/api"]),
+        ("@acme/util", &[]),
+    ]);
+    assert_eq!(
+        find_cycles(&g),
+        [
+            vec!["@acme/api", "@acme/api"],
+            vec!["@acme/theme", "@acme/ui", "@acme/theme"],
+        ]
+    );
+    assert!(find_cycles(&graph(&[("a", &["b"]), ("b", &["c"]), ("c", &[])])).is_empty());
+}
+
+#[test]
+fn proto_imports_resolve_by_path_suffix() {
+    let files = [
+        (
+            PathBuf::from("proto/acme/order.proto"),
+            r#"import "acme/user.proto";"#.to_string(),
+        ),
+        (
+            PathBuf::from("proto/acme/user.proto"),
+            "syntax = \"proto3\";\nimport public \"acme/order.proto\";\nimport \"google/protobuf/timestamp.proto\";\n"
+                .to_string(),
+        ),
+    ];
+    let g = proto_graph(&files);
+    assert_eq!(
+        g["proto/acme/user.proto"],
+        BTreeSet::from(["proto/acme/order.proto".to_string()])
+    );
+    assert_eq!(
+        find_cycles(&g),
+        [vec![
+            "proto/acme/order.proto",
+            "proto/acme/user.proto",
+            "proto/acme/order.proto"
+        ]]
+    );
+}
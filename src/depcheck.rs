@@ -0,0 +1,331 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use crate::backend::BazelBackend;
+use crate::error::KitError;
+
+/// A dependency imported by a source file but not declared in the manifest that owns it.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Drift {
+    pub manifest: PathBuf,
+    pub dependency: String,
+    pub file: PathBuf,
+}
+
+const NODE_BUILTINS: &[&str] = &[
+    "assert",
+    "async_hooks",
+    "buffer",
+    "child_process",
+    "cluster",
+    "crypto",
+    "dgram",
+    "dns",
+    "events",
+    "fs",
+    "http",
+    "http2",
+    "https",
+    "inspector",
+    "module",
+    "net",
+    "os",
+    "path",
+    "perf_hooks",
+    "process",
+    "querystring",
+    "readline",
+    "stream",
+    "string_decoder",
+    "timers",
+    "tls",
+    "tty",
+    "url",
+    "util",
+    "v8",
+    "vm",
+    "worker_threads",
+    "zlib",
+];
+
+const RUST_BUILTINS: &[&str] = &[
+    "std",
+    "core",
+    "alloc",
+    "proc_macro",
+    "test",
+    "crate",
+    "self",
+    "super",
+    "Self",
+];
+
+static JS_IMPORT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?m)(?:^\s*(?:import|export)\b[^'"`;]*?\bfrom\s*|^\s*import\s*|\brequire\(\s*|\bimport\(\s*)['"]([^'"]+)['"]"#,
+    )
+    .unwrap()
+});
+static RUST_USE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?(?:use\s+(?:::)?|extern\s+crate\s+)([A-Za-z_][A-Za-z0-9_]*)").unwrap()
+});
+static RUST_MOD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+([A-Za-z_][A-Za-z0-9_]*)").unwrap());
+
+/// Check changed files for undeclared dependencies and stale generated build metadata, without
+/// modifying anything.
+pub fn lint(repo_root: &Path, changed_files: &[PathBuf]) -> Result<()> {
+    let existing: Vec<&PathBuf> = changed_files.iter().filter(|f| repo_root.join(f).is_file()).collect();
+    let has_ext = |exts: &[&str]| {
+        existing.iter().any(|f| {
+            f.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| exts.contains(&e))
+        })
+    };
+
+    let mut drift = Vec::new();
+    drift.extend(check_manifests(
+        repo_root,
+        &existing,
+        JS_EXTENSIONS,
+        "package.json",
+        js_imports,
+        js_declared,
+    )?);
+    drift.extend(check_manifests(
+        repo_root,
+        &existing,
+        &["rs"],
+        "Cargo.toml",
+        rust_imports,
+        rust_declared,
+    )?);
+    drift.sort();
+    for d in &drift {
+        eprintln!(
+            "kit: {} imports {} which is not declared in {}",
+            d.file.display(),
+            d.dependency,
+            d.manifest.display()
+        );
+    }
+
+    let mut failures = drift.len();
+    if has_ext(&["go"]) {
+        failures += go_mod_drift(repo_root, &existing)?;
+    }
+    let bazel = ["WORKSPACE", "WORKSPACE.bazel", "MODULE.bazel"]
+        .iter()
+        .any(|f| repo_root.join(f).exists());
+    if bazel && !existing.is_empty() {
+        failures += gazelle_drift(repo_root, &existing)?;
+    }
+
+    if failures > 0 {
+        anyhow::bail!("{failures} build metadata problem(s)");
+    }
+    Ok(())
+}
+
+const JS_EXTENSIONS: &[&str] = &["js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts"];
+
+/// Extracts imported dependency names from a source file, given its path and contents.
+type Imports = fn(&Path, &str) -> BTreeSet<String>;
+type Declared = fn(&str) -> Result<BTreeSet<String>>;
+
+/// Compare imports in changed source files with the dependencies declared in their nearest manifest.
+fn check_manifests(
+    repo_root: &Path,
+    files: &[&PathBuf],
+    extensions: &[&str],
+    manifest_name: &str,
+    imports: Imports,
+    declared: Declared,
+) -> Result<Vec<Drift>> {
+    let mut manifests: BTreeMap<PathBuf, BTreeSet<String>> = BTreeMap::new();
+    let mut drift = Vec::new();
+    for file in files {
+        if !file
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| extensions.contains(&e))
+        {
+            continue;
+        }
+        let Some(manifest) = nearest(repo_root, file, manifest_name) else {
+            continue;
+        };
+        if !manifests.contains_key(&manifest) {
+            let path = repo_root.join(&manifest);
+            let text = std::fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+            let deps = declared(&text).with_context(|| format!("invalid {}", path.display()))?;
+            manifests.insert(manifest.clone(), deps);
+        }
+        let deps = &manifests[&manifest];
+        let path = repo_root.join(file);
+        let source = std::fs::read_to_string(&path).unwrap_or_default();
+        for dependency in imports(&path, &source) {
+            if !deps.contains(&dependency) {
+                drift.push(Drift {
+                    manifest: manifest.clone(),
+                    dependency,
+                    file: file.to_path_buf(),
+                });
+            }
+        }
+    }
+    Ok(drift)
+}
+
+/// The closest manifest with the given name in the file's directory or an ancestor, relative to the repo root.
+fn nearest(repo_root: &Path, file: &Path, name: &str) -> Option<PathBuf> {
+    let mut dir = file.parent();
+    while let Some(d) = dir {
+        let candidate = d.join(name);
+        if repo_root.join(&candidate).is_file() {
+            return Some(candidate);
+        }
+        dir = d.parent();
+    }
+    None
+}
+
+/// Bare npm package names imported by a JS/TS source file.
+fn js_imports(_path: &Path, source: &str) -> BTreeSet<String> {
+    JS_IMPORT
+        .captures_iter(source)
+        .filter_map(|c| {
+            let spec = c.get(1)?.as_str();
+            if spec.starts_with(['.', '/', '~', '#']) || spec.starts_with("@/") || spec.contains(':') {
+                return None;
+            }
+            let mut parts = spec.split('/');
+            let name = match parts.next()? {
+                scope if scope.starts_with('@') => format!("{scope}/{}", parts.next()?),
+                name => name.to_string(),
+            };
+            (!NODE_BUILTINS.contains(&name.as_str())).then_some(name)
+        })
+        .collect()
+}
+
+/// Package names a package.json declares, including its own name.
+fn js_declared(text: &str) -> Result<BTreeSet<String>> {
+    let json: serde_json::Value = serde_json::from_str(text)?;
+    let mut names: BTreeSet<String> = [
+        "dependencies",
+        "devDependencies",
+        "peerDependencies",
+        "optionalDependencies",
+    ]
+    .iter()
+    .filter_map(|key| json.get(key).and_then(|d| d.as_object()))
+    .flat_map(|deps| deps.keys().cloned())
+    .collect();
+    names.extend(json.get("name").and_then(|n| n.as_str()).map(String::from));
+    Ok(names)
+}
+
+/// External crate names brought in by `use` or `extern crate`, excluding modules declared in the file
+/// and sibling modules that may be in scope through a glob import.
+fn rust_imports(path: &Path, source: &str) -> BTreeSet<String> {
+    let local: BTreeSet<&str> = RUST_MOD
+        .captures_iter(source)
+        .filter_map(|c| c.get(1).map(|m| m.as_str()))
+        .collect();
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let sibling = |name: &str| dir.join(format!("{name}.rs")).is_file() || dir.join(name).join("mod.rs").is_file();
+    RUST_USE
+        .captures_iter(source)
+        .filter_map(|c| c.get(1).map(|m| m.as_str()))
+        .filter(|name| !RUST_BUILTINS.contains(name) && !local.contains(name) && !sibling(name))
+        .map(String::from)
+        .collect()
+}
+
+/// Crate names (as imported, with `-` replaced by `_`) a Cargo.toml declares, including its own.
+fn rust_declared(text: &str) -> Result<BTreeSet<String>> {
+    let manifest: toml::Table = toml::from_str(text)?;
+    let mut names: BTreeSet<String> = ["dependencies", "dev-dependencies", "build-dependencies"]
+        .iter()
+        .filter_map(|key| manifest.get(*key).and_then(|d| d.as_table()))
+        .flat_map(|deps| deps.keys().cloned())
+        .collect();
+    if let Some(name) = manifest
+        .get("package")
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str())
+    {
+        names.insert(name.to_string());
+    }
+    Ok(names.into_iter().map(|n| n.replace('-', "_")).collect())
+}
+
+/// Run `go mod tidy -diff` in every module with changed Go files. Returns the number of modules that drifted.
+fn go_mod_drift(repo_root: &Path, files: &[&PathBuf]) -> Result<usize> {
+    let modules: BTreeSet<PathBuf> = files
+        .iter()
+        .filter(|f| f.extension().is_some_and(|e| e == "go"))
+        .filter_map(|f| nearest(repo_root, f, "go.mod"))
+        .filter_map(|m| m.parent().map(Path::to_path_buf))
+        .collect();
+    let mut drifted = 0;
+    for module in modules {
+        let output = Command::new("go")
+            .args(["mod", "tidy", "-diff"])
+            .current_dir(repo_root.join(&module))
+            .output()
+            .map_err(|e| KitError::spawn("go", e))?;
+        if !output.status.success() {
+            let module = if module.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                &module
+            };
+            eprintln!("kit: go.mod in {} is out of date:", module.display());
+            eprint!("{}", String::from_utf8_lossy(&output.stdout));
+            eprint!("{}", String::from_utf8_lossy(&output.stderr));
+            drifted += 1;
+        }
+    }
+    Ok(drifted)
+}
+
+/// Run gazelle in diff mode over the packages containing changed files. Returns 1 if BUILD files drifted.
+fn gazelle_drift(repo_root: &Path, files: &[&PathBuf]) -> Result<usize> {
+    let dirs: BTreeSet<String> = files
+        .iter()
+        .filter_map(|f| f.parent())
+        .map(|d| {
+            let d = d.to_string_lossy().replace('\\', "/");
+            if d.is_empty() { ".".to_string() } else { d }
+        })
+        .collect();
+    let output = Command::new(BazelBackend::bazel_cmd())
+        .args(["run", "//:gazelle", "--", "-mode=diff"])
+        .args(&dirs)
+        .current_dir(repo_root)
+        .output()
+        .map_err(|e| KitError::spawn(BazelBackend::bazel_cmd(), e))?;
+    if output.status.success() {
+        return Ok(0);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("no such target") || stderr.contains("no such package") {
+        eprintln!("kit: //:gazelle target not found, skipping BUILD file check");
+        return Ok(0);
+    }
+    eprintln!("kit: BUILD files are out of date:");
+    eprint!("{}", String::from_utf8_lossy(&output.stdout));
+    Ok(1)
+}
+
+#[cfg(test)]
+#[path = "depcheck_test.rs"]
+mod tests;
@@ -0,0 +1,92 @@
+use super::*;
+use tempfile::TempDir;
+
+fn set(items: &[&str]) -> BTreeSet<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn js_imports_bare_packages_only() {
+    let source = r#"
+import React from "react";
+import { x } from '@org/ui/button';
+import type { T } from "zod";
+import "./styles.css";
+export * from "lodash/fp";
+const fs = require("fs");
+const y = require('left-pad');
+const z = await import("chalk");
+import a from "node:path";
+import b from "@/components/a";
+"#;
+    assert_eq!(
+        js_imports(Path::new("app.ts"), source),
+        set(&["@org/ui", "chalk", "left-pad", "lodash", "react", "zod"])
+    );
+}
+
+#[test]
+fn rust_imports_skip_std_and_local_modules() {
+    let source = r#"
+mod parser;
+use std::path::Path;
+use crate::config::Config;
+use anyhow::{Context, Result};
+pub(crate) use serde_json::json;
+use parser::parse;
+use ::regex::Regex;
+extern crate libc;
+"#;
+    assert_eq!(
+        rust_imports(Path::new("lib.rs"), source),
+        set(&["anyhow", "libc", "regex", "serde_json"])
+    );
+}
+
+#[test]
+fn rust_imports_skip_sibling_modules() {
+    let tmp = TempDir::new().unwrap();
+    std::fs::write(tmp.path().join("package.rs"), "").unwrap();
+    let source = "use super::*;\nuse package::Package;\nuse tempfile::TempDir;\n";
+    assert_eq!(
+        rust_imports(&tmp.path().join("lib_test.rs"), source),
+        set(&["tempfile"])
+    );
+}
+
+#[test]
+fn rust_declared_normalizes_names() {
+    let text = "[package]\nname = \"my-crate\"\n\n[dependencies]\nserde-json = \"1\"\n\n[dev-dependencies]\ntempfile = \"3\"\n";
+    assert_eq!(
+        rust_declared(text).unwrap(),
+        set(&["my_crate", "serde_json", "tempfile"])
+    );
+}
+
+#[test]
+fn check_manifests_reports_undeclared_imports() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    std::fs::create_dir_all(root.join("web/src")).unwrap();
+    std::fs::write(
+        root.join("web/package.json"),
+        r#"{"name": "web", "dependencies": {"react": "^18"}}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("web/src/app.tsx"),
+        "import React from 'react';\nimport axios from 'axios';\nimport self from 'web/util';\n",
+    )
+    .unwrap();
+
+    let file = PathBuf::from("web/src/app.tsx");
+    let drift = check_manifests(root, &[&file], JS_EXTENSIONS, "package.json", js_imports, js_declared).unwrap();
+    assert_eq!(
+        drift,
+        vec![Drift {
+            manifest: PathBuf::from("web/package.json"),
+            dependency: "axios".to_string(),
+            file,
+        }]
+    );
+}
@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::Result;
+
+use crate::config::ServiceConfig;
+use crate::error::KitError;
+
+/// Run the deployment command of each service for the given environment.
+///
+/// Commands are templates run with `sh -c`; `{service}`, `{dir}`, `{image}`, `{tag}`, and `{env}`
+/// are substituted. Unless `yes` is set, the plan is printed and confirmed interactively first.
+pub fn deploy(
+    repo_root: &Path,
+    services: &[&ServiceConfig],
+    defaults: &BTreeMap<String, String>,
+    env: &str,
+    tag: &str,
+    yes: bool,
+) -> Result<()> {
+    let mut plan = Vec::new();
+    for service in services {
+        let Some(template) = service.deploy.get(env).or_else(|| defaults.get(env)) else {
+            return Err(KitError::ConfigError(format!(
+                "service {} has no deploy command for environment `{env}`",
+                service.name
+            ))
+            .into());
+        };
+        plan.push((service, render(template, service, env, tag)));
+    }
+    if plan.is_empty() {
+        eprintln!("kit: no services to deploy");
+        return Ok(());
+    }
+
+    eprintln!("kit: deploying {} service(s) to {env}:", plan.len());
+    for (service, command) in &plan {
+        eprintln!("kit:   {}: {command}", service.name);
+    }
+    if !yes && !confirm(&format!("Deploy {} service(s) to {env}?", plan.len()), "deploy")? {
+        anyhow::bail!("deployment cancelled");
+    }
+
+    for (service, command) in &plan {
+        eprintln!("kit: deploying {}", service.name);
+        let status = Command::new("sh")
+            .args(["-c", command])
+            .current_dir(repo_root)
+            .status()
+            .map_err(|e| KitError::spawn("sh", e))?;
+        KitError::check(&format!("deploy of {}", service.name), status)?;
+    }
+    Ok(())
+}
+
+fn render(template: &str, service: &ServiceConfig, env: &str, tag: &str) -> String {
+    template
+        .replace("{service}", &service.name)
+        .replace("{dir}", &service.dir.to_string_lossy())
+        .replace("{image}", service.image.as_deref().unwrap_or(""))
+        .replace("{tag}", tag)
+        .replace("{env}", env)
+}
+
+/// Ask a yes/no question on the terminal before doing `what`. Refuses to guess when stdin is not
+/// interactive.
+pub fn confirm(question: &str, what: &str) -> Result<bool> {
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() {
+        anyhow::bail!("refusing to {what} without confirmation; pass --yes in non-interactive environments");
+    }
+    eprint!("{question} [y/N] ");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    stdin.lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+#[cfg(test)]
+#[path = "deploy_test.rs"]
+mod tests;
@@ -0,0 +1,21 @@
+use super::*;
+use std::path::PathBuf;
+
+#[test]
+fn render_substitutes_placeholders() {
+    let service = ServiceConfig {
+        name: "api".to_string(),
+        dir: PathBuf::from("services/api"),
+        inputs: vec![],
+        image: Some("ghcr.io/acme/api".to_string()),
+        dockerfile: None,
+        context: None,
+        deploy: BTreeMap::new(),
+    };
+    let template = "helm upgrade --install {service} {dir}/chart -f {dir}/values-{env}.yaml --set image={image}:{tag}";
+    assert_eq!(
+        render(template, &service, "staging", "abc123"),
+        "helm upgrade --install api services/api/chart -f services/api/values-staging.yaml \
+         --set image=ghcr.io/acme/api:abc123"
+    );
+}
@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::git;
+
+/// A dependency version requirement declared in a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requirement {
+    pub name: String,
+    pub version: String,
+}
+
+/// A dependency required at more than one version across the repository.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Skew {
+    /// "go", "npm", or "cargo".
+    pub ecosystem: &'static str,
+    pub name: String,
+    /// Each version and the manifests (relative to the repo root) that require it.
+    pub versions: BTreeMap<String, Vec<PathBuf>>,
+}
+
+type Parse = fn(&str) -> Result<Vec<Requirement>>;
+
+const MANIFESTS: &[(&str, &str, Parse)] = &[
+    ("go", "go.mod", go_requirements),
+    ("npm", "package.json", npm_requirements),
+    ("cargo", "Cargo.toml", cargo_requirements),
+];
+
+/// Report dependencies required at different versions by different modules, packages, or crates.
+pub fn doctor(repo_root: &Path) -> Result<()> {
+    let mut manifests = 0;
+    let mut requirements = Vec::new();
+    for (ecosystem, name, parse) in MANIFESTS {
+        for manifest in git::find_files(repo_root, name)? {
+            let path = repo_root.join(&manifest);
+            let text = std::fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+            let reqs = parse(&text).with_context(|| format!("invalid {}", path.display()))?;
+            manifests += 1;
+            requirements.extend(reqs.into_iter().map(|r| (*ecosystem, manifest.clone(), r)));
+        }
+    }
+    eprintln!(
+        "kit: checked {} dependencies in {manifests} manifests",
+        requirements.len()
+    );
+
+    let skews = skew(requirements);
+    for s in &skews {
+        println!("{} {}", s.ecosystem, s.name);
+        for (version, manifests) in &s.versions {
+            let paths: Vec<String> = manifests.iter().map(|m| m.display().to_string()).collect();
+            println!("  {version}  {}", paths.join(", "));
+        }
+    }
+    if !skews.is_empty() {
+        anyhow::bail!("{} dependency(s) required at more than one version", skews.len());
+    }
+    eprintln!("kit: no version skew");
+    Ok(())
+}
+
+/// Group requirements by dependency and keep those with more than one version.
+pub fn skew(requirements: Vec<(&'static str, PathBuf, Requirement)>) -> Vec<Skew> {
+    let mut by_dep: BTreeMap<(&'static str, String), BTreeMap<String, Vec<PathBuf>>> = BTreeMap::new();
+    for (ecosystem, manifest, req) in requirements {
+        let manifests = by_dep
+            .entry((ecosystem, req.name))
+            .or_default()
+            .entry(req.version)
+            .or_default();
+        if !manifests.contains(&manifest) {
+            manifests.push(manifest);
+        }
+    }
+    by_dep
+        .into_iter()
+        .filter(|(_, versions)| versions.len() > 1)
+        .map(|((ecosystem, name), versions)| Skew {
+            ecosystem,
+            name,
+            versions,
+        })
+        .collect()
+}
+
+/// Required modules in a go.mod. Major version suffixes (`/v2`) are folded into the version so that
+/// two majors of the same module show up as skew.
+fn go_requirements(text: &str) -> Result<Vec<Requirement>> {
+    let mut requirements = Vec::new();
+    let mut in_block = false;
+    for line in text.lines() {
+        let line = line.split("//").next().unwrap_or("").trim();
+        let spec = if in_block {
+            if line == ")" {
+                in_block = false;
+                continue;
+            }
+            line
+        } else if line == "require (" {
+            in_block = true;
+            continue;
+        } else if let Some(spec) = line.strip_prefix("require ") {
+            spec
+        } else {
+            continue;
+        };
+        let mut fields = spec.split_whitespace();
+        if let (Some(module), Some(version)) = (fields.next(), fields.next()) {
+            let name = match module.rsplit_once("/v") {
+                Some((base, major)) if major.parse::<u32>().is_ok() => base,
+                _ => module,
+            };
+            requirements.push(Requirement {
+                name: name.to_string(),
+                version: version.to_string(),
+            });
+        }
+    }
+    Ok(requirements)
+}
+
+/// Registry dependencies in a package.json. Workspace, file, and link references are skipped.
+fn npm_requirements(text: &str) -> Result<Vec<Requirement>> {
+    let json: serde_json::Value = serde_json::from_str(text)?;
+    Ok([
+        "dependencies",
+        "devDependencies",
+        "peerDependencies",
+        "optionalDependencies",
+    ]
+    .iter()
+    .filter_map(|key| json.get(key).and_then(|d| d.as_object()))
+    .flat_map(|deps| deps.iter())
+    .filter_map(|(name, spec)| {
+        let version = spec.as_str()?;
+        let local = ["workspace:", "file:", "link:", "portal:"]
+            .iter()
+            .any(|p| version.starts_with(p));
+        (!local).then(|| Requirement {
+            name: name.clone(),
+            version: version.to_string(),
+        })
+    })
+    .collect())
+}
+
+/// Registry dependencies in a Cargo.toml, including `[workspace.dependencies]`. Path-only and
+/// `workspace = true` dependencies are skipped.
+fn cargo_requirements(text: &str) -> Result<Vec<Requirement>> {
+    let manifest: toml::Table = toml::from_str(text)?;
+    let workspace = manifest.get("workspace").and_then(|w| w.get("dependencies"));
+    let tables = ["dependencies", "dev-dependencies", "build-dependencies"]
+        .iter()
+        .filter_map(|key| manifest.get(*key))
+        .chain(workspace);
+    Ok(tables
+        .filter_map(|t| t.as_table())
+        .flat_map(|deps| deps.iter())
+        .filter_map(|(key, spec)| {
+            let version = match spec {
+                toml::Value::String(v) => v.as_str(),
+                spec => spec.get("version")?.as_str()?,
+            };
+            // A renamed dependency names the real crate in its `package` field.
+            let name = spec.get("package").and_then(|p| p.as_str()).unwrap_or(key);
+            Some(Requirement {
+                name: name.to_string(),
+                version: version.to_string(),
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+#[path = "deps_test.rs"]
+mod tests;
@@ -0,0 +1,69 @@
+use super::*;
+
+fn req(name: &str, version: &str) -> Requirement {
+    Requirement {
+        name: name.to_string(),
+        version: version.to_string(),
+    }
+}
+
+#[test]
+fn go_requirements_fold_major_versions() {
+    let text = "module example.com/m\n\nrequire github.com/a/x v1.0.0\n\nrequire (\n\tgithub.com/a/x/v2 v2.1.0\n\tgithub.com/b/y v0.3.0 // indirect\n)\n";
+    assert_eq!(
+        go_requirements(text).unwrap(),
+        [
+            req("github.com/a/x", "v1.0.0"),
+            req("github.com/a/x", "v2.1.0"),
+            req("github.com/b/y", "v0.3.0")
+        ]
+    );
+}
+
+#[test]
+fn npm_and_cargo_requirements_skip_local_dependencies() {
+    let json =
+        r#"{"dependencies": {"react": "^18.2.0", "@org/ui": "workspace:*"}, "devDependencies": {"zod": "3.22.0"}}"#;
+    assert_eq!(
+        npm_requirements(json).unwrap(),
+        [req("react", "^18.2.0"), req("zod", "3.22.0")]
+    );
+
+    let toml = r#"
+[dependencies]
+serde = "1.0"
+local = { path = "../local" }
+shared = { workspace = true }
+json = { package = "serde_json", version = "1" }
+
+[workspace.dependencies]
+anyhow = "1"
+"#;
+    assert_eq!(
+        cargo_requirements(toml).unwrap(),
+        [req("serde_json", "1"), req("serde", "1.0"), req("anyhow", "1")]
+    );
+}
+
+#[test]
+fn skew_lists_every_version_and_manifest() {
+    let skews = skew(vec![
+        ("go", PathBuf::from("a/go.mod"), req("github.com/a/x", "v1.0.0")),
+        ("go", PathBuf::from("b/go.mod"), req("github.com/a/x", "v1.2.0")),
+        ("go", PathBuf::from("c/go.mod"), req("github.com/a/x", "v1.2.0")),
+        ("go", PathBuf::from("c/go.mod"), req("github.com/b/y", "v0.3.0")),
+        ("npm", PathBuf::from("package.json"), req("github.com/a/x", "1.0.0")),
+    ]);
+    assert_eq!(skews.len(), 1);
+    assert_eq!(skews[0].name, "github.com/a/x");
+    assert_eq!(
+        skews[0].versions,
+        BTreeMap::from([
+            ("v1.0.0".to_string(), vec![PathBuf::from("a/go.mod")]),
+            (
+                "v1.2.0".to_string(),
+                vec![PathBuf::from("b/go.mod"), PathBuf::from("c/go.mod")]
+            ),
+        ])
+    );
+}
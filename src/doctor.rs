@@ -0,0 +1,171 @@
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use crate::backend::{Backend, which_exists};
+use crate::exec::Invocation;
+
+/// The outcome of one check of a backend.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Ok,
+    /// The program is not installed.
+    Missing,
+    /// The command ran and failed, with the first line it printed.
+    Failed(String),
+}
+
+/// How to install the tools backends run, by program name.
+const INSTALL: &[(&str, &str)] = &[
+    (
+        "bazel",
+        "install Bazelisk: https://github.com/bazelbuild/bazelisk#installation",
+    ),
+    ("bazelisk", "https://github.com/bazelbuild/bazelisk#installation"),
+    ("black", "pip install black"),
+    ("buf", "https://buf.build/docs/installation"),
+    (
+        "buildifier",
+        "go install github.com/bazelbuild/buildtools/buildifier@latest",
+    ),
+    ("cmake", "https://cmake.org/download/"),
+    ("docker", "https://docs.docker.com/get-docker/"),
+    ("dotnet", "https://dotnet.microsoft.com/download"),
+    ("earthly", "https://earthly.dev/get-earthly"),
+    ("flake8", "pip install flake8"),
+    ("go", "https://go.dev/doc/install"),
+    ("golangci-lint", "https://golangci-lint.run/welcome/install/"),
+    ("just", "https://just.systems/man/en/packages.html"),
+    ("node", "https://nodejs.org/en/download"),
+    ("nx", "npm install --global nx"),
+    ("pixi", "https://pixi.sh/latest/#installation"),
+    ("pnpm", "corepack enable pnpm"),
+    ("poetry", "pipx install poetry"),
+    ("ruff", "pipx install ruff, or: uv tool install ruff"),
+    ("turbo", "npm install --global turbo"),
+    ("uv", "https://docs.astral.sh/uv/getting-started/installation/"),
+    ("yarn", "corepack enable yarn"),
+];
+
+/// Check every detected backend: that its tools are installed, with the versions they report, and
+/// that its setup has no known problems. With `deep`, run each backend's smoke commands instead of
+/// the version commands of its main tools, and report how long they took.
+pub fn run(repo_root: &Path, backends: &[&dyn Backend], deep: bool) -> Result<()> {
+    let mut problems = 0;
+    for backend in backends {
+        println!("{}", backend.name());
+        let mut invocations = if deep {
+            backend.smoke(repo_root)
+        } else {
+            backend.toolchain(repo_root)
+        };
+        invocations.extend(backend.extra_tools(repo_root));
+        for invocation in &invocations {
+            let start = Instant::now();
+            let (outcome, output) = if installed(&invocation.program) {
+                probe(invocation)
+            } else {
+                (Outcome::Missing, None)
+            };
+            problems += usize::from(outcome != Outcome::Ok);
+            if deep {
+                println!("  {}", line(invocation, &outcome, Some(start.elapsed())));
+            } else {
+                println!("  {}", version_line(invocation, &outcome, output.as_deref()));
+            }
+            if let Some(hint) = install_hint(&invocation.program).filter(|_| outcome == Outcome::Missing) {
+                println!("           to install: {hint}");
+            }
+        }
+        for problem in backend.misconfigurations(repo_root) {
+            problems += 1;
+            println!("  warning  {problem}");
+        }
+    }
+    if problems > 0 {
+        anyhow::bail!("{problems} problem(s) found");
+    }
+    eprintln!("kit: no problems found");
+    Ok(())
+}
+
+/// Whether a program is on PATH, or, for a wrapper in the repo (`./mvnw`), exists.
+fn installed(program: &str) -> bool {
+    if program.contains('/') {
+        return Path::new(program).is_file();
+    }
+    which_exists(program)
+}
+
+/// How to install a program, if kit knows.
+fn install_hint(program: &str) -> Option<&'static str> {
+    let name = Path::new(program).file_name()?.to_str()?;
+    INSTALL.iter().find(|(tool, _)| *tool == name).map(|(_, hint)| *hint)
+}
+
+/// Run a command, with the first line it printed: on stdout if it passed, as a version command does,
+/// and on stderr if it failed. A failure's line is part of the outcome.
+fn probe(invocation: &Invocation) -> (Outcome, Option<String>) {
+    let output = Command::new(&invocation.program)
+        .args(&invocation.args)
+        .envs(invocation.env.iter().map(|(k, v)| (k, v)))
+        .current_dir(&invocation.dir)
+        .stdin(std::process::Stdio::null())
+        .output();
+    let first_line = |primary: &[u8], secondary: &[u8]| {
+        let text = String::from_utf8_lossy(primary);
+        let text = if text.trim().is_empty() {
+            String::from_utf8_lossy(secondary)
+        } else {
+            text
+        };
+        text.lines().map(str::trim).find(|l| !l.is_empty()).map(String::from)
+    };
+    match output {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => (Outcome::Missing, None),
+        Err(e) => (Outcome::Failed(e.to_string()), None),
+        Ok(o) if o.status.success() => (Outcome::Ok, first_line(&o.stdout, &o.stderr)),
+        Ok(o) => {
+            let first = first_line(&o.stderr, &o.stdout);
+            (Outcome::Failed(first.unwrap_or_else(|| o.status.to_string())), None)
+        }
+    }
+}
+
+/// One line of the report: the outcome, how long the command took, and the command.
+fn line(invocation: &Invocation, outcome: &Outcome, elapsed: Option<Duration>) -> String {
+    let program = Path::new(&invocation.program)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| invocation.program.clone());
+    let command = std::iter::once(program)
+        .chain(invocation.args.iter().cloned())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let latency = elapsed.map(|e| format!("  {:>6}ms", e.as_millis())).unwrap_or_default();
+    match outcome {
+        Outcome::Ok => format!("ok{latency}  {command}"),
+        Outcome::Missing => format!("missing  {command}: {} is not installed", invocation.program),
+        Outcome::Failed(detail) => format!("failed{latency}  {command}: {detail}"),
+    }
+}
+
+/// One line of the version report: the outcome, the program, and the version it printed.
+fn version_line(invocation: &Invocation, outcome: &Outcome, version: Option<&str>) -> String {
+    let program = Path::new(&invocation.program)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| invocation.program.clone());
+    match (outcome, version) {
+        (Outcome::Ok, Some(version)) => format!("ok  {program}: {version}"),
+        (Outcome::Ok, None) => format!("ok  {program}"),
+        (Outcome::Missing, _) => format!("missing  {program}: {} is not installed", invocation.program),
+        (Outcome::Failed(detail), _) => format!("failed  {program}: {detail}"),
+    }
+}
+
+#[cfg(test)]
+#[path = "doctor_test.rs"]
+mod tests;
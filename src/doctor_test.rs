@@ -0,0 +1,59 @@
+use super::*;
+
+#[test]
+fn lines_show_outcome_latency_and_command() {
+    let invocation = Invocation::new("/usr/bin/go", Path::new("/repo")).args(["list", "-m"]);
+    assert_eq!(
+        line(&invocation, &Outcome::Ok, Some(Duration::from_millis(42))),
+        "ok      42ms  go list -m"
+    );
+    assert_eq!(line(&invocation, &Outcome::Ok, None), "ok  go list -m");
+    assert_eq!(
+        line(
+            &invocation,
+            &Outcome::Failed("go: no modules".to_string()),
+            Some(Duration::from_millis(7))
+        ),
+        "failed       7ms  go list -m: go: no modules"
+    );
+    assert_eq!(
+        line(&invocation, &Outcome::Missing, None),
+        "missing  go list -m: /usr/bin/go is not installed"
+    );
+}
+
+#[test]
+fn probe_reports_the_first_line_a_failing_command_printed() {
+    let repo = tempfile::tempdir().unwrap();
+    let failing = Invocation::new("sh", repo.path()).args(["-c", "echo; echo 'bad config' >&2; exit 1"]);
+    assert_eq!(probe(&failing).0, Outcome::Failed("bad config".to_string()));
+    assert_eq!(probe(&Invocation::new("true", repo.path())).0, Outcome::Ok);
+    assert_eq!(
+        probe(&Invocation::new("kit-no-such-tool", repo.path())).0,
+        Outcome::Missing
+    );
+}
+
+#[test]
+fn version_lines_show_what_the_tool_printed_and_how_to_install_it() {
+    let repo = tempfile::tempdir().unwrap();
+    let version = Invocation::new("sh", repo.path()).args(["-c", "echo; echo 'tool 1.2.3'; echo more"]);
+    let (outcome, output) = probe(&version);
+    assert_eq!(outcome, Outcome::Ok);
+    assert_eq!(output.as_deref(), Some("tool 1.2.3"));
+    assert_eq!(
+        version_line(&version, &outcome, output.as_deref()),
+        "ok  sh: tool 1.2.3"
+    );
+
+    let missing = Invocation::new("golangci-lint", repo.path()).arg("--version");
+    assert_eq!(
+        version_line(&missing, &Outcome::Missing, None),
+        "missing  golangci-lint: golangci-lint is not installed"
+    );
+    assert_eq!(
+        install_hint("golangci-lint"),
+        Some("https://golangci-lint.run/welcome/install/")
+    );
+    assert_eq!(install_hint("./mvnw"), None);
+}
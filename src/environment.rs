@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::backend::Backend;
+
+static IMAGE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#""image"\s*:\s*"([^"]+)""#).unwrap());
+
+/// Devcontainer configuration files, in the order the devcontainer CLI looks for them.
+const DEVCONTAINER: &[&str] = &[".devcontainer/devcontainer.json", ".devcontainer.json"];
+
+/// The toolchain a run used, so results from different toolchains are not mistaken for each other.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Environment {
+    /// First line each version command printed, keyed by the command (e.g. "go version").
+    pub tools: BTreeMap<String, String>,
+    /// The devcontainer's image, with its digest when the configuration pins one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_image: Option<String>,
+    /// SHA-256 of `flake.lock`, which pins every input of a Nix dev shell.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flake_lock: Option<String>,
+    /// SHA-256 over all of the above. Two runs had the same environment if this matches.
+    pub fingerprint: String,
+}
+
+impl Environment {
+    pub fn new(tools: BTreeMap<String, String>, container_image: Option<String>, flake_lock: Option<String>) -> Self {
+        let mut hasher = Sha256::new();
+        for (tool, version) in &tools {
+            hasher.update(format!("{tool}\0{version}\0"));
+        }
+        hasher.update(format!("image\0{}\0", container_image.as_deref().unwrap_or_default()));
+        hasher.update(format!("flake\0{}\0", flake_lock.as_deref().unwrap_or_default()));
+        Self {
+            tools,
+            container_image,
+            flake_lock,
+            fingerprint: hasher.finalize().iter().map(|b| format!("{b:02x}")).collect(),
+        }
+    }
+}
+
+/// Capture the environment: the tool versions of every backend detected at the repo root, the
+/// devcontainer image, and the Nix flake lock.
+pub fn capture(repo_root: &Path, backends: &[Box<dyn Backend>]) -> Environment {
+    let mut tools = BTreeMap::new();
+    for invocation in backends
+        .iter()
+        .filter(|b| b.detect(repo_root))
+        .flat_map(|b| b.toolchain(repo_root))
+    {
+        let program = Path::new(&invocation.program)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| invocation.program.clone());
+        let key = std::iter::once(program)
+            .chain(invocation.args.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let version = Command::new(&invocation.program)
+            .args(&invocation.args)
+            .current_dir(&invocation.dir)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .and_then(|o| first_line(&o.stdout).or_else(|| first_line(&o.stderr)))
+            .unwrap_or_else(|| "unavailable".to_string());
+        tools.insert(key, version);
+    }
+    let container_image = DEVCONTAINER
+        .iter()
+        .find_map(|f| std::fs::read_to_string(repo_root.join(f)).ok())
+        .and_then(|config| devcontainer_image(&config));
+    let flake_lock = std::fs::read(repo_root.join("flake.lock"))
+        .ok()
+        .map(|lock| sha256(&lock));
+    Environment::new(tools, container_image, flake_lock)
+}
+
+/// The `image` a devcontainer.json (JSON with comments) runs in, if it names one rather than
+/// building a Dockerfile.
+fn devcontainer_image(config: &str) -> Option<String> {
+    let config: String = config
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    IMAGE.captures(&config).map(|c| c[1].to_string())
+}
+
+fn first_line(output: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(output);
+    text.lines().map(str::trim).find(|l| !l.is_empty()).map(String::from)
+}
+
+fn sha256(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+#[path = "environment_test.rs"]
+mod tests;
@@ -0,0 +1,29 @@
+use super::*;
+
+#[test]
+fn devcontainer_image_skips_comments() {
+    let config = r#"{
+  // "image": "mcr.microsoft.com/devcontainers/base:old",
+  "name": "dev",
+  "image": "ghcr.io/acme/dev@sha256:0123abcd"
+}"#;
+    assert_eq!(
+        devcontainer_image(config).as_deref(),
+        Some("ghcr.io/acme/dev@sha256:0123abcd")
+    );
+    assert_eq!(devcontainer_image(r#"{"build": {"dockerfile": "Dockerfile"}}"#), None);
+}
+
+#[test]
+fn fingerprint_changes_with_any_part_of_the_environment() {
+    let tools = |go: &str| BTreeMap::from([("go version".to_string(), go.to_string())]);
+    let base = Environment::new(tools("go1.22.0"), None, Some("abc".to_string()));
+    assert_eq!(base, Environment::new(tools("go1.22.0"), None, Some("abc".to_string())));
+    for other in [
+        Environment::new(tools("go1.23.0"), None, Some("abc".to_string())),
+        Environment::new(tools("go1.22.0"), Some("img".to_string()), Some("abc".to_string())),
+        Environment::new(tools("go1.22.0"), None, None),
+    ] {
+        assert_ne!(base.fingerprint, other.fingerprint);
+    }
+}
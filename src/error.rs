@@ -0,0 +1,100 @@
+use std::fmt;
+use std::process::ExitStatus;
+
+/// Failures callers need to tell apart, e.g. a missing tool versus a failing test.
+///
+/// Errors are still passed around as `anyhow::Error`; a `KitError` sits at the root of the chain
+/// and can be recovered with [`KitError::find`].
+#[derive(Debug)]
+pub enum KitError {
+    /// A tool kit needs to run is not installed.
+    ToolMissing { tool: String },
+    /// A tool ran and reported failure. `exit` is None if it was killed by a signal.
+    ToolFailed { tool: String, exit: Option<i32> },
+    /// A git command failed.
+    GitError(String),
+    /// No supported build system was found.
+    DetectionError(String),
+    /// kit.toml or a command-line option is invalid.
+    ConfigError(String),
+    /// No targets were affected and the caller asked for that to be reported (`--exit-empty`).
+    NothingAffected,
+    /// Ctrl-C stopped the run.
+    Interrupted,
+}
+
+impl KitError {
+    /// The first `KitError` in an error's chain.
+    pub fn find(err: &anyhow::Error) -> Option<&KitError> {
+        err.chain().find_map(|e| e.downcast_ref::<KitError>())
+    }
+
+    /// Short machine-readable name for reports.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ToolMissing { .. } => "tool_missing",
+            Self::ToolFailed { .. } => "tool_failed",
+            Self::GitError(_) => "git",
+            Self::DetectionError(_) => "detection",
+            Self::ConfigError(_) => "config",
+            Self::NothingAffected => "nothing_affected",
+            Self::Interrupted => "interrupted",
+        }
+    }
+
+    /// Process exit code for this failure; see [`exit_code`].
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::ToolFailed { .. } | Self::Interrupted => 1,
+            Self::ConfigError(_) => 2,
+            Self::ToolMissing { .. } | Self::GitError(_) | Self::DetectionError(_) => 3,
+            Self::NothingAffected => 4,
+        }
+    }
+
+    /// Turn a failure to start a tool into ToolMissing when it isn't installed.
+    pub fn spawn(tool: &str, err: std::io::Error) -> anyhow::Error {
+        if err.kind() == std::io::ErrorKind::NotFound {
+            Self::ToolMissing { tool: tool.to_string() }.into()
+        } else {
+            anyhow::Error::new(err).context(format!("failed to run {tool}"))
+        }
+    }
+
+    /// Fail with ToolFailed unless the tool exited successfully.
+    pub fn check(tool: &str, status: ExitStatus) -> anyhow::Result<()> {
+        if status.success() {
+            return Ok(());
+        }
+        Err(Self::ToolFailed {
+            tool: tool.to_string(),
+            exit: status.code(),
+        }
+        .into())
+    }
+}
+
+impl fmt::Display for KitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ToolMissing { tool } => write!(f, "failed to run {tool} — is it installed?"),
+            Self::ToolFailed { tool, exit: Some(code) } => write!(f, "{tool} exited with code {code}"),
+            Self::ToolFailed { tool, exit: None } => write!(f, "{tool} was terminated by a signal"),
+            Self::GitError(msg) | Self::DetectionError(msg) | Self::ConfigError(msg) => f.write_str(msg),
+            Self::NothingAffected => f.write_str("no targets are affected by changes on the branch"),
+            Self::Interrupted => f.write_str("interrupted"),
+        }
+    }
+}
+
+impl std::error::Error for KitError {}
+
+/// Exit code for an error: 1 for target failures and anything unclassified, 2 for usage and config
+/// errors, 3 for environment problems such as a missing tool, 4 when nothing was affected.
+pub fn exit_code(err: &anyhow::Error) -> u8 {
+    KitError::find(err).map_or(1, KitError::exit_code)
+}
+
+#[cfg(test)]
+#[path = "error_test.rs"]
+mod tests;
@@ -0,0 +1,37 @@
+use super::*;
+use anyhow::Context;
+
+#[test]
+fn find_sees_through_context() {
+    let err = Err::<(), _>(KitError::GitError("git log failed".to_string()))
+        .context("failed to plan release")
+        .unwrap_err();
+    let found = KitError::find(&err).unwrap();
+    assert_eq!(found.kind(), "git");
+    assert!(KitError::find(&anyhow::anyhow!("plain")).is_none());
+}
+
+#[test]
+fn exit_codes_follow_the_convention() {
+    let tool_failed = KitError::ToolFailed {
+        tool: "go".to_string(),
+        exit: Some(1),
+    };
+    assert_eq!(exit_code(&tool_failed.into()), 1);
+    assert_eq!(exit_code(&anyhow::anyhow!("2 checks failed")), 1);
+    assert_eq!(exit_code(&KitError::ConfigError("bad".to_string()).into()), 2);
+    let missing = KitError::ToolMissing { tool: "go".to_string() };
+    assert_eq!(exit_code(&anyhow::Error::from(missing).context("while testing")), 3);
+    assert_eq!(exit_code(&KitError::NothingAffected.into()), 4);
+    assert_eq!(exit_code(&KitError::Interrupted.into()), 1);
+}
+
+#[test]
+fn spawn_distinguishes_missing_tools() {
+    let missing = KitError::spawn("nx", std::io::Error::from(std::io::ErrorKind::NotFound));
+    assert!(matches!(KitError::find(&missing), Some(KitError::ToolMissing { .. })));
+    assert_eq!(missing.to_string(), "failed to run nx — is it installed?");
+
+    let denied = KitError::spawn("nx", std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+    assert!(KitError::find(&denied).is_none());
+}
@@ -0,0 +1,175 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+
+use crate::backend::Target;
+use crate::error::KitError;
+use crate::hooks::shell_quote;
+
+/// A command a backend wants run. Backends describe commands instead of running them so the
+/// runner can schedule, stream, and cancel them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invocation {
+    pub program: String,
+    pub args: Vec<String>,
+    pub dir: PathBuf,
+    /// Environment variables set for the command, on top of kit's own environment.
+    pub env: Vec<(String, String)>,
+}
+
+impl Invocation {
+    pub fn new(program: impl Into<String>, dir: &Path) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            dir: dir.to_path_buf(),
+            env: Vec::new(),
+        }
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+}
+
+/// A `kit exec` command for one target, with `{dir}` replaced by the target's absolute directory
+/// and `{label}` by its label, each shell-quoted.
+pub fn render(template: &str, target: &Target) -> String {
+    template
+        .replace("{dir}", &shell_quote(&target.dir.to_string_lossy()))
+        .replace("{label}", &shell_quote(&target.label))
+}
+
+/// A `KEY=VALUE` assignment from `--env`.
+pub fn parse_env(assignment: &str) -> Result<(String, String)> {
+    match assignment.split_once('=') {
+        Some((key, value)) if valid_key(key) => Ok((key.trim().to_string(), value.to_string())),
+        _ => Err(KitError::ConfigError(format!("invalid environment variable {assignment:?}; use KEY=VALUE")).into()),
+    }
+}
+
+/// The variables in a dotenv file: `KEY=VALUE` lines, optionally prefixed with `export`, with the
+/// value optionally in single or double quotes. Blank lines and `#` comments are skipped.
+pub fn read_env_file(path: &Path) -> Result<Vec<(String, String)>> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let mut vars = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = parse_env(line)
+            .map_err(|_| KitError::ConfigError(format!("{}:{}: expected KEY=VALUE", path.display(), n + 1)))?;
+        let value = value.trim();
+        let unquoted = ['"', '\'']
+            .iter()
+            .find_map(|q| value.strip_prefix(*q).and_then(|v| v.strip_suffix(*q)))
+            .unwrap_or(value);
+        vars.push((key, unquoted.to_string()));
+    }
+    Ok(vars)
+}
+
+fn valid_key(key: &str) -> bool {
+    let key = key.trim();
+    !key.is_empty()
+        && !key.starts_with(|c: char| c.is_ascii_digit())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Run invocations in order, stopping at the first failure.
+///
+/// Without a prefix or log the commands inherit kit's stdout and stderr. With a prefix, their output
+/// is streamed line by line with `[prefix]` in front, so concurrent steps stay readable. With a log,
+/// every line is also appended to that file.
+pub async fn run_all(plan: Vec<Invocation>, prefix: Option<String>, log: Option<PathBuf>) -> Result<()> {
+    let log = match log {
+        Some(path) => {
+            let file = std::fs::File::create(&path).with_context(|| format!("failed to create {}", path.display()))?;
+            Some(Mutex::new(file))
+        }
+        None => None,
+    };
+    for invocation in plan {
+        run(&invocation, prefix.as_deref(), log.as_ref()).await?;
+    }
+    Ok(())
+}
+
+async fn run(invocation: &Invocation, prefix: Option<&str>, log: Option<&Mutex<std::fs::File>>) -> Result<()> {
+    let program = &invocation.program;
+    let mut cmd = Command::new(program);
+    cmd.args(&invocation.args)
+        .envs(invocation.env.iter().map(|(k, v)| (k, v)))
+        .current_dir(&invocation.dir)
+        .kill_on_drop(true);
+    let status = if prefix.is_none() && log.is_none() {
+        cmd.status().await
+    } else {
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+        match cmd.spawn() {
+            Ok(mut child) => {
+                let stdout = forward(child.stdout.take(), prefix, log, false);
+                let stderr = forward(child.stderr.take(), prefix, log, true);
+                let (status, ..) = tokio::join!(child.wait(), stdout, stderr);
+                status
+            }
+            Err(e) => Err(e),
+        }
+    };
+    let status = status.map_err(|e| KitError::spawn(program, e))?;
+    KitError::check(program, status)
+}
+
+async fn forward<R: AsyncRead + Unpin>(
+    reader: Option<R>,
+    prefix: Option<&str>,
+    log: Option<&Mutex<std::fs::File>>,
+    stderr: bool,
+) {
+    let Some(reader) = reader else { return };
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    // Lines are read as bytes so that output that isn't UTF-8 is still forwarded, lossily.
+    while reader.read_until(b'\n', &mut buf).await.is_ok_and(|n| n > 0) {
+        let line = String::from_utf8_lossy(&buf);
+        let line = line.trim_end_matches('\n').trim_end_matches('\r');
+        if let Some(log) = log
+            && let Ok(mut file) = log.lock()
+        {
+            let _ = writeln!(file, "{line}");
+        }
+        match (prefix, stderr) {
+            (Some(prefix), true) => eprintln!("[{prefix}] {line}"),
+            (Some(prefix), false) => println!("[{prefix}] {line}"),
+            (None, true) => eprintln!("{line}"),
+            (None, false) => println!("{line}"),
+        }
+        buf.clear();
+    }
+}
+
+#[cfg(test)]
+#[path = "exec_test.rs"]
+mod tests;
@@ -0,0 +1,96 @@
+use super::*;
+
+fn block_on<F: std::future::Future>(f: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(f)
+}
+
+#[test]
+fn run_all_stops_at_first_failure() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let dir = tmp.path();
+    let plan = vec![
+        Invocation::new("sh", dir).args(["-c", "touch first"]),
+        Invocation::new("sh", dir).args(["-c", "exit 3"]),
+        Invocation::new("sh", dir).args(["-c", "touch third"]),
+    ];
+    let err = block_on(run_all(plan, Some("test".to_string()), None)).unwrap_err();
+    assert!(err.to_string().starts_with("sh exited with"), "{err}");
+    assert!(dir.join("first").exists());
+    assert!(!dir.join("third").exists());
+}
+
+#[test]
+fn missing_programs_are_reported() {
+    let plan = vec![Invocation::new("kit-no-such-tool", Path::new("."))];
+    let err = block_on(run_all(plan, None, None)).unwrap_err();
+    assert_eq!(err.to_string(), "failed to run kit-no-such-tool — is it installed?");
+}
+
+#[test]
+fn run_all_writes_output_to_the_log() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let dir = tmp.path();
+    let log = dir.join("out.log");
+    let plan = vec![Invocation::new("sh", dir).args(["-c", "echo out; echo err >&2"])];
+    block_on(run_all(plan, None, Some(log.clone()))).unwrap();
+    let text = std::fs::read_to_string(log).unwrap();
+    assert!(text.contains("out\n") && text.contains("err\n"), "{text}");
+}
+
+#[test]
+fn env_assignments_need_a_valid_key() {
+    assert_eq!(parse_env("FLAG=a=b").unwrap(), ("FLAG".to_string(), "a=b".to_string()));
+    assert_eq!(parse_env("EMPTY=").unwrap(), ("EMPTY".to_string(), String::new()));
+    assert!(parse_env("FLAG").is_err());
+    assert!(parse_env("1X=1").is_err());
+    assert!(parse_env("MY-FLAG=1").is_err());
+}
+
+#[test]
+fn env_files_are_read_like_dotenv() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let path = tmp.path().join(".env");
+    std::fs::write(
+        &path,
+        "# sandbox\nAPI_URL=https://sandbox.example.com\n\nexport TOKEN = 'abc def'\nNAME=\"kit\"\n",
+    )
+    .unwrap();
+    assert_eq!(
+        read_env_file(&path).unwrap(),
+        [
+            ("API_URL".to_string(), "https://sandbox.example.com".to_string()),
+            ("TOKEN".to_string(), "abc def".to_string()),
+            ("NAME".to_string(), "kit".to_string()),
+        ]
+    );
+    std::fs::write(&path, "OK=1\nnot an assignment\n").unwrap();
+    let err = read_env_file(&path).unwrap_err();
+    assert!(err.to_string().ends_with(":2: expected KEY=VALUE"), "{err}");
+}
+
+#[test]
+fn output_after_invalid_utf8_is_still_logged() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let dir = tmp.path();
+    let log = dir.join("out.log");
+    let plan = vec![Invocation::new("sh", dir).args(["-c", r"printf 'bad \377\r\nafter\n'"])];
+    block_on(run_all(plan, None, Some(log.clone()))).unwrap();
+    assert_eq!(std::fs::read_to_string(log).unwrap(), "bad \u{fffd}\nafter\n");
+}
+
+#[test]
+fn render_fills_in_dir_and_label_quoted() {
+    let target = Target {
+        label: "//services/api:all".to_string(),
+        dir: PathBuf::from("/repo/services/it's"),
+        ..Default::default()
+    };
+    assert_eq!(
+        render("echo {label} > {dir}/LABEL", &target),
+        r"echo '//services/api:all' > '/repo/services/it'\''s'/LABEL"
+    );
+}
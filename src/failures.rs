@@ -0,0 +1,154 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::backend::Backend;
+use crate::error::KitError;
+use crate::runner::{LAST_RUN, MANIFEST};
+use crate::state;
+
+/// A failing test found in test output.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Failure {
+    /// Test name as the test runner reports it (e.g. "TestParse/empty", "tests/test_x.py::test_y").
+    pub test: String,
+    /// File the failure points at, relative to the repo root when the runner reports it that way.
+    pub file: Option<PathBuf>,
+    pub line: Option<u32>,
+    /// First line of the failure message.
+    pub message: String,
+}
+
+impl Failure {
+    /// `file:line`, or just the file if the line is unknown.
+    pub fn location(&self) -> Option<String> {
+        let file = self.file.as_ref()?.to_string_lossy().replace('\\', "/");
+        Some(match self.line {
+            Some(line) => format!("{file}:{line}"),
+            None => file,
+        })
+    }
+}
+
+/// Print the failing tests of the last `kit test` or `kit coverage` run as `file:line: test: message`,
+/// and with `open`, open each located failure in `$VISUAL` or `$EDITOR`.
+pub fn run(repo_root: &Path, backends: &[Box<dyn Backend>], open: bool) -> Result<()> {
+    let failures = read(repo_root, backends)?;
+    for f in &failures {
+        match f.location() {
+            Some(location) => println!("{location}: {}: {}", f.test, f.message),
+            None => println!("{}: {}", f.test, f.message),
+        }
+    }
+    if failures.is_empty() {
+        eprintln!("kit: no failing tests in the last run");
+        return Ok(());
+    }
+    eprintln!("kit: {} failing test(s) in the last run", failures.len());
+    for seed in seeds(repo_root) {
+        eprintln!("kit: tests ran in shuffled order; kit test --shuffle={seed} repeats it");
+    }
+    if !open {
+        return Ok(());
+    }
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .map_err(|_| KitError::ConfigError("set $EDITOR to open failures".to_string()))?;
+    for f in &failures {
+        let Some(file) = &f.file else { continue };
+        let args = editor_command(&editor, file, f.line);
+        let Some((program, args)) = args.split_first() else {
+            return Err(KitError::ConfigError("$EDITOR is empty".to_string()).into());
+        };
+        let status = Command::new(program)
+            .args(args)
+            .current_dir(repo_root)
+            .status()
+            .map_err(|e| KitError::spawn(program, e))?;
+        KitError::check(program, status)?;
+    }
+    Ok(())
+}
+
+/// Failures in the logs of the last run, parsed by the backend that wrote each log.
+fn read(repo_root: &Path, backends: &[Box<dyn Backend>]) -> Result<Vec<Failure>> {
+    Ok(dedup(read_logs(repo_root, backends)?.into_iter().flatten().collect()))
+}
+
+/// Failures in each log of the last run, one list per log.
+pub fn read_logs(repo_root: &Path, backends: &[Box<dyn Backend>]) -> Result<Vec<Vec<Failure>>> {
+    let dir = repo_root.join(state::DIR).join(LAST_RUN);
+    if !dir.is_dir() {
+        return Err(KitError::ConfigError("no test output recorded yet; run kit test first".to_string()).into());
+    }
+    let mut logs: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .with_context(|| format!("failed to read {}", dir.display()))?
+        .filter_map(|e| Some(e.ok()?.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "log"))
+        .collect();
+    logs.sort();
+    let mut failures = Vec::new();
+    for log in logs {
+        // Logs are named after the backend, numbered if it ran more than once: "go.log", "go.2.log".
+        let stem = log.file_stem().unwrap_or_default().to_string_lossy();
+        let name = stem.split('.').next().unwrap_or_default();
+        let Some(backend) = backends.iter().find(|b| b.name() == name) else {
+            continue;
+        };
+        let output = std::fs::read_to_string(&log).with_context(|| format!("failed to read {}", log.display()))?;
+        failures.push(dedup(backend.parse_failures(repo_root, &output)));
+    }
+    Ok(failures)
+}
+
+/// The distinct shuffle seeds recorded in the last run's manifest.
+fn seeds(repo_root: &Path) -> BTreeSet<u64> {
+    let path = repo_root.join(state::DIR).join(LAST_RUN).join(MANIFEST);
+    let manifest: serde_json::Value = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default();
+    manifest["steps"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|step| step["seed"].as_u64())
+        .collect()
+}
+
+/// Drop repeated failures, which runners print again in their summaries, keeping the first.
+pub fn dedup(failures: Vec<Failure>) -> Vec<Failure> {
+    let mut seen = BTreeSet::new();
+    failures
+        .into_iter()
+        .filter(|f| seen.insert((f.test.clone(), f.file.clone())))
+        .collect()
+}
+
+/// The command opening `file` at `line` in `editor`. VS Code takes `--goto file:line`, Sublime Text
+/// and Zed take `file:line`, and terminal editors (vim, emacs, nano) take `+line file`.
+pub fn editor_command(editor: &str, file: &Path, line: Option<u32>) -> Vec<String> {
+    let mut args: Vec<String> = editor.split_whitespace().map(String::from).collect();
+    let file = file.to_string_lossy().into_owned();
+    let program = args
+        .first()
+        .and_then(|p| Path::new(p).file_name())
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    match (program.as_str(), line) {
+        ("code" | "code-insiders" | "codium", Some(line)) => {
+            args.extend(["--goto".to_string(), format!("{file}:{line}")])
+        }
+        ("subl" | "zed", Some(line)) => args.push(format!("{file}:{line}")),
+        (_, Some(line)) => args.extend([format!("+{line}"), file]),
+        (_, None) => args.push(file),
+    }
+    args
+}
+
+#[cfg(test)]
+#[path = "failures_test.rs"]
+mod tests;
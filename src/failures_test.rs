@@ -0,0 +1,45 @@
+use super::*;
+
+fn failure(test: &str, file: Option<&str>, line: Option<u32>) -> Failure {
+    Failure {
+        test: test.to_string(),
+        file: file.map(PathBuf::from),
+        line,
+        message: "boom".to_string(),
+    }
+}
+
+#[test]
+fn location_includes_the_line_when_known() {
+    assert_eq!(
+        failure("t", Some("a/b.go"), Some(3)).location().as_deref(),
+        Some("a/b.go:3")
+    );
+    assert_eq!(failure("t", Some("a/b.go"), None).location().as_deref(), Some("a/b.go"));
+    assert_eq!(failure("t", None, None).location(), None);
+}
+
+#[test]
+fn dedup_keeps_the_first_report_of_each_test() {
+    let failures = vec![
+        failure("t", Some("a.py"), Some(3)),
+        failure("u", Some("a.py"), None),
+        failure("t", Some("a.py"), None),
+    ];
+    assert_eq!(
+        dedup(failures),
+        [failure("t", Some("a.py"), Some(3)), failure("u", Some("a.py"), None)]
+    );
+}
+
+#[test]
+fn editor_command_uses_each_editors_line_syntax() {
+    let file = Path::new("src/a.rs");
+    assert_eq!(editor_command("vim", file, Some(7)), ["vim", "+7", "src/a.rs"]);
+    assert_eq!(
+        editor_command("/usr/bin/code --wait", file, Some(7)),
+        ["/usr/bin/code", "--wait", "--goto", "src/a.rs:7"]
+    );
+    assert_eq!(editor_command("subl", file, Some(7)), ["subl", "src/a.rs:7"]);
+    assert_eq!(editor_command("nano", file, None), ["nano", "src/a.rs"]);
+}
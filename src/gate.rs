@@ -0,0 +1,100 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::config::GatePolicy;
+use crate::error::KitError;
+use crate::{git, history};
+
+/// Commands whose passing runs kit records, and so the ones a policy can require.
+const COMMANDS: &[&str] = &["build", "check", "test", "lint", "coverage", "fmt", "ci"];
+
+/// What a policy says about the current commit.
+#[derive(Debug, PartialEq)]
+pub enum Verdict<'a> {
+    /// No affected target is in the policy's paths.
+    NotApplicable,
+    Met,
+    /// The required commands that have not passed.
+    Unmet(Vec<&'a str>),
+}
+
+/// Check every policy against HEAD: a policy that applies is met when each command it requires has
+/// passed at HEAD on a clean tree (as recorded for `--since-last-green`). Prints a line per policy
+/// and fails if any is not met.
+pub fn run(repo_root: &Path, policies: &[GatePolicy], affected_dirs: &[PathBuf]) -> Result<()> {
+    if policies.is_empty() {
+        return Err(KitError::ConfigError("no [[gate]] policies in kit.toml".to_string()).into());
+    }
+    validate(policies)?;
+    let head = git::head(repo_root)?;
+    let short = &head[..head.len().min(12)];
+    let passed = if git::is_clean(repo_root)? {
+        history::passed_at(repo_root, &head)?
+    } else {
+        eprintln!("kit: the working tree has uncommitted changes, which no recorded run covers");
+        BTreeSet::new()
+    };
+    let mut unmet = 0;
+    for policy in policies {
+        match check(policy, affected_dirs, &passed) {
+            Verdict::NotApplicable => eprintln!("kit: gate: {}: no affected targets in its paths", policy.name),
+            Verdict::Met => eprintln!("kit: gate: {}: met", policy.name),
+            Verdict::Unmet(missing) => {
+                eprintln!(
+                    "kit: gate: {}: not met, {} has not passed at {short}",
+                    policy.name,
+                    missing.join(", ")
+                );
+                unmet += 1;
+            }
+        }
+    }
+    if unmet > 0 {
+        anyhow::bail!("gate failed: {unmet} of {} policies not met", policies.len());
+    }
+    eprintln!("kit: gate passed");
+    Ok(())
+}
+
+fn validate(policies: &[GatePolicy]) -> Result<()> {
+    for policy in policies {
+        if let Some(command) = policy.require.iter().find(|c| !COMMANDS.contains(&c.as_str())) {
+            return Err(KitError::ConfigError(format!(
+                "gate policy `{}` requires `{command}`, expected one of {}",
+                policy.name,
+                COMMANDS.join(", ")
+            ))
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// Check one policy, given the repo-relative directories of the affected targets and the commands
+/// that passed.
+pub fn check<'a>(policy: &'a GatePolicy, affected_dirs: &[PathBuf], passed: &BTreeSet<String>) -> Verdict<'a> {
+    let applies = policy.paths.is_empty()
+        || affected_dirs
+            .iter()
+            .any(|dir| policy.paths.iter().any(|path| dir.starts_with(path)));
+    if !applies {
+        return Verdict::NotApplicable;
+    }
+    let missing: Vec<&str> = policy
+        .require
+        .iter()
+        .map(String::as_str)
+        .filter(|c| !passed.contains(*c))
+        .collect();
+    if missing.is_empty() {
+        Verdict::Met
+    } else {
+        Verdict::Unmet(missing)
+    }
+}
+
+#[cfg(test)]
+#[path = "gate_test.rs"]
+mod tests;
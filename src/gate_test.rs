@@ -0,0 +1,37 @@
+use super::*;
+
+fn policy(paths: &[&str], require: &[&str]) -> GatePolicy {
+    GatePolicy {
+        name: "policy".to_string(),
+        paths: paths.iter().map(PathBuf::from).collect(),
+        require: require.iter().map(|c| c.to_string()).collect(),
+    }
+}
+
+fn passed(commands: &[&str]) -> BTreeSet<String> {
+    commands.iter().map(|c| c.to_string()).collect()
+}
+
+#[test]
+fn policies_apply_to_affected_targets_in_their_paths() {
+    let payments = policy(&["payments"], &["test"]);
+    let affected = [PathBuf::from("payments/ledger"), PathBuf::from("web")];
+    assert_eq!(check(&payments, &affected, &passed(&["test"])), Verdict::Met);
+    assert_eq!(check(&payments, &affected[1..], &passed(&[])), Verdict::NotApplicable);
+    assert_eq!(
+        check(&payments, &[PathBuf::from("payments-legacy")], &passed(&[])),
+        Verdict::NotApplicable
+    );
+}
+
+#[test]
+fn policies_without_paths_always_apply() {
+    let fmt = policy(&[], &["fmt", "lint"]);
+    assert_eq!(check(&fmt, &[], &passed(&["lint"])), Verdict::Unmet(vec!["fmt"]));
+}
+
+#[test]
+fn unknown_commands_are_rejected() {
+    assert!(validate(&[policy(&[], &["test", "deploy"])]).is_err());
+    assert!(validate(&[policy(&[], &["build", "fmt"])]).is_ok());
+}
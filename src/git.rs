@@ -1,34 +1,72 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use anyhow::{Context, Result};
 
-/// Find the root of the current git repository.
-pub fn repo_root() -> Result<PathBuf> {
+use crate::error::KitError;
+
+/// Find the root of the current git repository, and the current directory relative to it as git
+/// sees it (empty at the root).
+pub fn repo_root() -> Result<(PathBuf, PathBuf)> {
     let output = Command::new("git")
-        .args(["rev-parse", "--show-toplevel"])
+        .args(["rev-parse", "--show-toplevel", "--show-prefix"])
         .output()
         .context("failed to run git")?;
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("not in a git repository: {}", stderr.trim());
+        return Err(KitError::GitError(format!("not in a git repository: {}", stderr.trim())).into());
+    }
+    let stdout = String::from_utf8(output.stdout).context("invalid utf-8 from git")?;
+    let mut lines = stdout.lines();
+    let root = lines.next().unwrap_or_default();
+    let prefix = lines.next().unwrap_or_default();
+    Ok((PathBuf::from(root), PathBuf::from(prefix)))
+}
+
+/// Return the full commit hash of HEAD.
+pub fn head(repo_root: &Path) -> Result<String> {
+    rev_parse(repo_root, &["HEAD"])
+}
+
+/// Return the abbreviated commit hash of HEAD.
+pub fn short_head(repo_root: &Path) -> Result<String> {
+    rev_parse(repo_root, &["--short", "HEAD"])
+}
+
+/// Return the directory git runs hooks from, honoring `core.hooksPath`.
+pub fn hooks_dir(repo_root: &Path) -> Result<PathBuf> {
+    let path = rev_parse(repo_root, &["--git-path", "hooks"])?;
+    Ok(repo_root.join(path))
+}
+
+fn rev_parse(repo_root: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("rev-parse")
+        .args(args)
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git rev-parse")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(KitError::GitError(format!("git rev-parse {} failed: {}", args.join(" "), stderr.trim())).into());
     }
-    let path = String::from_utf8(output.stdout)
-        .context("invalid utf-8 from git")?
+    Ok(String::from_utf8(output.stdout)
+        .context("invalid utf-8")?
         .trim()
-        .to_string();
-    Ok(PathBuf::from(path))
+        .to_string())
 }
 
 /// Find the merge base between HEAD and the given base branch.
-fn merge_base(repo_root: &Path, base: &str) -> Result<String> {
+pub fn merge_base(repo_root: &Path, base: &str) -> Result<String> {
     let output = Command::new("git")
         .args(["merge-base", base, "HEAD"])
         .current_dir(repo_root)
         .output()
         .context("failed to run git merge-base")?;
     if !output.status.success() {
-        anyhow::bail!("git merge-base failed — is '{base}' a valid ref?");
+        return Err(KitError::GitError(format!("git merge-base failed — is '{base}' a valid ref?")).into());
     }
     Ok(String::from_utf8(output.stdout)
         .context("invalid utf-8")?
@@ -40,21 +78,36 @@ fn merge_base(repo_root: &Path, base: &str) -> Result<String> {
 /// Paths are relative to the repo root.
 pub fn changed_files(repo_root: &Path, base: &str) -> Result<Vec<PathBuf>> {
     let base_commit = merge_base(repo_root, base)?;
+    // Rename detection compares file contents, which a blobless clone would download one blob at a
+    // time. Without it a renamed file lists as its old and its new path, both of which matter.
+    let renames = if is_partial_clone(repo_root)? {
+        "--no-renames"
+    } else {
+        "--find-renames"
+    };
 
     let branch_diff = Command::new("git")
-        .args(["diff", "--name-only", "-z", "--diff-filter=ACMRD", &base_commit, "HEAD"])
+        .args([
+            "diff",
+            "--name-only",
+            "-z",
+            "--diff-filter=ACMRD",
+            renames,
+            &base_commit,
+            "HEAD",
+        ])
         .current_dir(repo_root)
         .output()
         .context("failed to run git diff")?;
 
     let unstaged = Command::new("git")
-        .args(["diff", "--name-only", "-z", "--diff-filter=ACMRD"])
+        .args(["diff", "--name-only", "-z", "--diff-filter=ACMRD", renames])
         .current_dir(repo_root)
         .output()
         .context("failed to run git diff (unstaged)")?;
 
     let staged = Command::new("git")
-        .args(["diff", "--name-only", "-z", "--diff-filter=ACMRD", "--cached"])
+        .args(["diff", "--name-only", "-z", "--diff-filter=ACMRD", renames, "--cached"])
         .current_dir(repo_root)
         .output()
         .context("failed to run git diff (staged)")?;
@@ -75,3 +128,469 @@ pub fn changed_files(repo_root: &Path, base: &str) -> Result<Vec<PathBuf>> {
 
     Ok(all.into_iter().collect())
 }
+
+/// Files staged for the next commit, relative to the repo root.
+pub fn staged_files(repo_root: &Path) -> Result<Vec<PathBuf>> {
+    let output = Command::new("git")
+        .args(["diff", "--name-only", "-z", "--diff-filter=ACMRD", "--cached"])
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git diff (staged)")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(KitError::GitError(format!("git diff --cached failed: {}", stderr.trim())).into());
+    }
+    let text = String::from_utf8(output.stdout).context("invalid utf-8")?;
+    Ok(text.split('\0').filter(|s| !s.is_empty()).map(PathBuf::from).collect())
+}
+
+/// Return a file's contents at the given revision, or None if it didn't exist there.
+pub fn show(repo_root: &Path, rev: &str, path: &Path) -> Result<Option<String>> {
+    let spec = format!("{rev}:{}", path.to_string_lossy().replace('\\', "/"));
+    let output = Command::new("git")
+        .args(["show", &spec])
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git show")?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8(output.stdout).context("invalid utf-8")?))
+}
+
+/// Return the contents of the blob a revision or ref names, or None if it doesn't exist.
+pub fn cat_blob(repo_root: &Path, rev: &str) -> Result<Option<String>> {
+    let output = Command::new("git")
+        .args(["cat-file", "blob", rev])
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git cat-file")?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8(output.stdout).context("invalid utf-8")?))
+}
+
+/// Return the value of a git config key, or None if it is unset.
+pub fn config_value(repo_root: &Path, key: &str) -> Result<Option<String>> {
+    let output = Command::new("git")
+        .args(["config", "--get", key])
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git config")?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let value = String::from_utf8(output.stdout).context("invalid utf-8")?;
+    Ok(Some(value.trim().to_string()).filter(|v| !v.is_empty()))
+}
+
+/// Move a file or directory with `git mv`, so the index records the move.
+pub fn mv(repo_root: &Path, from: &Path, to: &Path) -> Result<()> {
+    let output = Command::new("git")
+        .arg("mv")
+        .args([from, to])
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git mv")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(KitError::GitError(format!("git mv failed: {}", stderr.trim())).into());
+    }
+    Ok(())
+}
+
+/// Commit the given files, relative to the repo root, with `message`.
+pub fn commit(repo_root: &Path, message: &str, paths: &[PathBuf]) -> Result<()> {
+    for args in [vec!["add", "--"], vec!["commit", "-q", "-m", message, "--"]] {
+        let output = Command::new("git")
+            .args(&args)
+            .args(paths)
+            .current_dir(repo_root)
+            .output()
+            .with_context(|| format!("failed to run git {}", args[0]))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(KitError::GitError(format!("git {} failed: {}", args[0], stderr.trim())).into());
+        }
+    }
+    Ok(())
+}
+
+/// Returns true if the clone is partial (e.g. `--filter=blob:none`), so file contents missing
+/// locally are fetched on demand.
+pub fn is_partial_clone(repo_root: &Path) -> Result<bool> {
+    if config_value(repo_root, "extensions.partialclone")?.is_some() {
+        return Ok(true);
+    }
+    // Newer git marks the remote the clone was filtered from instead.
+    let output = Command::new("git")
+        .args(["config", "--get-regexp", r"^remote\..*\.promisor$"])
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git config")?;
+    let values = String::from_utf8_lossy(&output.stdout);
+    Ok(values.lines().any(|line| line.ends_with(" true")))
+}
+
+/// Returns true if a local branch with the given name exists.
+pub fn branch_exists(repo_root: &Path, branch: &str) -> Result<bool> {
+    let status = Command::new("git")
+        .args(["rev-parse", "--quiet", "--verify"])
+        .arg(format!("refs/heads/{branch}"))
+        .current_dir(repo_root)
+        .stdout(std::process::Stdio::null())
+        .status()
+        .context("failed to run git rev-parse")?;
+    Ok(status.success())
+}
+
+/// Return the full ref names of local branches and remote-tracking branches
+/// (`refs/remotes/origin/main`).
+pub fn branches(repo_root: &Path) -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .args(["for-each-ref", "--format=%(refname)", "refs/heads", "refs/remotes"])
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git for-each-ref")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(KitError::GitError(format!("git for-each-ref failed: {}", stderr.trim())).into());
+    }
+    let text = String::from_utf8(output.stdout).context("invalid utf-8")?;
+    Ok(text
+        .lines()
+        .filter(|l| !l.is_empty() && !l.ends_with("/HEAD"))
+        .map(String::from)
+        .collect())
+}
+
+/// Returns the number of commits in HEAD's history that are not in `commit`'s.
+pub fn commits_since(repo_root: &Path, commit: &str) -> Result<usize> {
+    let output = Command::new("git")
+        .args(["rev-list", "--count"])
+        .arg(format!("{commit}..HEAD"))
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git rev-list")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(KitError::GitError(format!("git rev-list failed: {}", stderr.trim())).into());
+    }
+    let text = String::from_utf8(output.stdout).context("invalid utf-8")?;
+    text.trim()
+        .parse()
+        .with_context(|| format!("unexpected git rev-list output: {text}"))
+}
+
+/// Return tracked and untracked (but not git-ignored) files with the given name anywhere in the repo.
+/// Paths are relative to the repo root.
+pub fn find_files(repo_root: &Path, name: &str) -> Result<Vec<PathBuf>> {
+    find_files_named(repo_root, &[name])
+}
+
+/// [`find_files`] for any of several names, with one `git ls-files`.
+pub fn find_files_named(repo_root: &Path, names: &[&str]) -> Result<Vec<PathBuf>> {
+    let output = Command::new("git")
+        .args(["ls-files", "--cached", "--others", "--exclude-standard", "-z", "--"])
+        .args(names.iter().map(|name| format!(":(glob)**/{name}")))
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git ls-files")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(KitError::GitError(format!("git ls-files failed: {}", stderr.trim())).into());
+    }
+    let text = String::from_utf8(output.stdout).context("invalid utf-8")?;
+    let files: std::collections::BTreeSet<PathBuf> =
+        text.split('\0').filter(|s| !s.is_empty()).map(PathBuf::from).collect();
+    Ok(files.into_iter().collect())
+}
+
+/// Those of the repo-relative paths git ignores, per `.gitignore` files and `.git/info/exclude`.
+pub fn ignored(repo_root: &Path, paths: &[PathBuf]) -> Result<BTreeSet<PathBuf>> {
+    if paths.is_empty() {
+        return Ok(BTreeSet::new());
+    }
+    let mut child = Command::new("git")
+        .args(["check-ignore", "-z", "--stdin"])
+        .current_dir(repo_root)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .context("failed to run git check-ignore")?;
+    let mut input = Vec::new();
+    for path in paths {
+        input.extend_from_slice(path.to_string_lossy().as_bytes());
+        input.push(0);
+    }
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(&input).context("failed to write to git check-ignore")?;
+    }
+    let output = child.wait_with_output().context("failed to run git check-ignore")?;
+    // Exit code 1 means none of the paths is ignored.
+    if !output.status.success() && output.status.code() != Some(1) {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(KitError::GitError(format!("git check-ignore failed: {}", stderr.trim())).into());
+    }
+    let text = String::from_utf8(output.stdout).context("invalid utf-8")?;
+    Ok(text.split('\0').filter(|s| !s.is_empty()).map(PathBuf::from).collect())
+}
+
+/// A commit on the current branch.
+#[derive(Debug, Clone)]
+pub struct Commit {
+    pub sha: String,
+    /// Full commit message (subject, blank line, body).
+    pub message: String,
+}
+
+impl Commit {
+    /// The first line of the commit message.
+    pub fn subject(&self) -> &str {
+        self.message.lines().next().unwrap_or("")
+    }
+}
+
+/// Return the non-merge commits on the current branch since its merge base with the given base branch,
+/// oldest first.
+pub fn commits(repo_root: &Path, base: &str) -> Result<Vec<Commit>> {
+    let base_commit = merge_base(repo_root, base)?;
+    log(repo_root, &format!("{base_commit}..HEAD"), &[])
+}
+
+/// Return the non-merge commits in a revision range that touch the given pathspecs, oldest first.
+/// An empty pathspec list matches every commit.
+pub fn log(repo_root: &Path, range: &str, pathspecs: &[String]) -> Result<Vec<Commit>> {
+    let output = Command::new("git")
+        .args(["log", "--no-merges", "--reverse", "-z", "--format=%H%n%B", range, "--"])
+        .args(pathspecs)
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git log")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(KitError::GitError(format!("git log failed: {}", stderr.trim())).into());
+    }
+    let text = String::from_utf8(output.stdout).context("invalid utf-8")?;
+    Ok(text
+        .split('\0')
+        .filter(|s| !s.trim().is_empty())
+        .filter_map(|entry| {
+            let (sha, message) = entry.trim_start().split_once('\n')?;
+            Some(Commit {
+                sha: sha.to_string(),
+                message: message.trim().to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Return the oldest commit that added the file, or None if it was never committed.
+pub fn added_in(repo_root: &Path, path: &Path) -> Result<Option<String>> {
+    let output = Command::new("git")
+        .args(["log", "--diff-filter=A", "--format=%H", "--"])
+        .arg(path)
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git log")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(KitError::GitError(format!("git log failed: {}", stderr.trim())).into());
+    }
+    let text = String::from_utf8(output.stdout).context("invalid utf-8")?;
+    Ok(text.lines().rfind(|l| !l.is_empty()).map(String::from))
+}
+
+/// Return tags starting with the given prefix, highest version first.
+pub fn tags(repo_root: &Path, prefix: &str) -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .args(["tag", "--list", "--sort=-v:refname"])
+        .arg(format!("{prefix}*"))
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git tag")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(KitError::GitError(format!("git tag failed: {}", stderr.trim())).into());
+    }
+    let text = String::from_utf8(output.stdout).context("invalid utf-8")?;
+    Ok(text.lines().filter(|l| !l.is_empty()).map(String::from).collect())
+}
+
+/// Returns true if the tag exists locally.
+pub fn tag_exists(repo_root: &Path, tag: &str) -> Result<bool> {
+    let status = Command::new("git")
+        .args(["rev-parse", "--quiet", "--verify"])
+        .arg(format!("refs/tags/{tag}"))
+        .current_dir(repo_root)
+        .stdout(std::process::Stdio::null())
+        .status()
+        .context("failed to run git rev-parse")?;
+    Ok(status.success())
+}
+
+/// Returns true if `commit` is HEAD or one of its ancestors.
+pub fn is_ancestor(repo_root: &Path, commit: &str) -> Result<bool> {
+    let status = Command::new("git")
+        .args(["merge-base", "--is-ancestor", commit, "HEAD"])
+        .current_dir(repo_root)
+        .stderr(std::process::Stdio::null())
+        .status()
+        .context("failed to run git merge-base")?;
+    Ok(status.success())
+}
+
+/// Returns true if the working tree has no uncommitted changes or untracked files.
+pub fn is_clean(repo_root: &Path) -> Result<bool> {
+    let output = Command::new("git")
+        .args(["status", "--porcelain"])
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git status")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(KitError::GitError(format!("git status failed: {}", stderr.trim())).into());
+    }
+    Ok(output.stdout.is_empty())
+}
+
+/// Repo-relative paths of the files with uncommitted changes, and of untracked files.
+pub fn dirty_files(repo_root: &Path) -> Result<Vec<PathBuf>> {
+    let output = Command::new("git")
+        .args(["status", "--porcelain", "-z", "--untracked-files=all"])
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git status")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(KitError::GitError(format!("git status failed: {}", stderr.trim())).into());
+    }
+    Ok(porcelain_paths(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// The paths in `git status --porcelain -z` output. Renames and copies are followed by their
+/// source path, which isn't dirty.
+fn porcelain_paths(status: &str) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    let mut entries = status.split('\0').filter(|e| !e.is_empty());
+    while let Some(entry) = entries.next() {
+        let Some(path) = entry.get(3..) else {
+            continue;
+        };
+        paths.push(PathBuf::from(path));
+        if entry.starts_with('R') || entry.starts_with('C') {
+            entries.next();
+        }
+    }
+    paths
+}
+
+/// The branch changes usually merge into: the one `origin/HEAD` points at, or else `main` or `master`
+/// if it exists.
+pub fn default_branch(repo_root: &Path) -> Result<Option<String>> {
+    let output = Command::new("git")
+        .args(["symbolic-ref", "--quiet", "--short", "refs/remotes/origin/HEAD"])
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git symbolic-ref")?;
+    if output.status.success() {
+        let name = String::from_utf8(output.stdout).context("invalid utf-8")?;
+        let name = name.trim();
+        return Ok(Some(name.strip_prefix("origin/").unwrap_or(name).to_string()));
+    }
+    for name in ["main", "master"] {
+        if branch_exists(repo_root, name)? {
+            return Ok(Some(name.to_string()));
+        }
+    }
+    Ok(None)
+}
+
+/// Return the name of the currently checked-out branch, or None when HEAD is detached.
+pub fn current_branch(repo_root: &Path) -> Result<Option<String>> {
+    let output = Command::new("git")
+        .args(["symbolic-ref", "--quiet", "--short", "HEAD"])
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git symbolic-ref")?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let name = String::from_utf8(output.stdout).context("invalid utf-8")?;
+    Ok(Some(name.trim().to_string()))
+}
+
+/// Return the git blob hash of every tracked file matching the pathspecs, as recorded in the index.
+/// Paths are relative to the repo root.
+pub fn blob_hashes(repo_root: &Path, pathspecs: &[String]) -> Result<Vec<(PathBuf, String)>> {
+    let output = Command::new("git")
+        .args(["ls-files", "--stage", "-z", "--"])
+        .args(pathspecs)
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git ls-files")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(KitError::GitError(format!("git ls-files failed: {}", stderr.trim())).into());
+    }
+    let text = String::from_utf8(output.stdout).context("invalid utf-8")?;
+    // Each entry is "<mode> <object> <stage>\t<path>".
+    Ok(text
+        .split('\0')
+        .filter_map(|entry| {
+            let (meta, path) = entry.split_once('\t')?;
+            let object = meta.split_whitespace().nth(1)?;
+            Some((PathBuf::from(path), object.to_string()))
+        })
+        .collect())
+}
+
+/// Return the lines added or modified since the merge base with the given base branch, including
+/// uncommitted changes, keyed by path relative to the repo root.
+pub fn changed_lines(repo_root: &Path, base: &str) -> Result<BTreeMap<PathBuf, BTreeSet<u32>>> {
+    let base_commit = merge_base(repo_root, base)?;
+    let output = Command::new("git")
+        .args(["diff", "-U0", "--no-color", "--no-ext-diff", &base_commit])
+        .current_dir(repo_root)
+        .output()
+        .context("failed to run git diff")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(KitError::GitError(format!("git diff failed: {}", stderr.trim())).into());
+    }
+    Ok(parse_changed_lines(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Collect the new-side line numbers of every hunk in a zero-context unified diff.
+fn parse_changed_lines(diff: &str) -> BTreeMap<PathBuf, BTreeSet<u32>> {
+    let mut changed: BTreeMap<PathBuf, BTreeSet<u32>> = BTreeMap::new();
+    let mut current: Option<PathBuf> = None;
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            current = path.strip_prefix("b/").map(PathBuf::from);
+        } else if let Some(hunk) = line.strip_prefix("@@ ")
+            && let Some(file) = &current
+        {
+            // @@ -old[,count] +new[,count] @@
+            let Some(new) = hunk.split_whitespace().nth(1).and_then(|r| r.strip_prefix('+')) else {
+                continue;
+            };
+            let (start, count) = match new.split_once(',') {
+                Some((start, count)) => (start.parse::<u32>(), count.parse::<u32>()),
+                None => (new.parse::<u32>(), Ok(1)),
+            };
+            if let (Ok(start), Ok(count)) = (start, count)
+                && count > 0
+            {
+                changed.entry(file.clone()).or_default().extend(start..start + count);
+            }
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+#[path = "git_test.rs"]
+mod tests;
@@ -0,0 +1,42 @@
+use super::*;
+
+#[test]
+fn parse_changed_lines_reads_new_side_of_hunks() {
+    let diff = "\
+diff --git a/a.go b/a.go
+index 1111111..2222222 100644
+--- a/a.go
++++ b/a.go
+@@ -3 +3 @@ func a() {
+-\told()
++\tnew()
+@@ -10,0 +11,2 @@ func b() {
++\tx()
++\ty()
+@@ -20,2 +22,0 @@ func c() {
+-\tgone()
+-\tgone()
+diff --git a/old.go b/old.go
+deleted file mode 100644
+--- a/old.go
++++ /dev/null
+@@ -1 +0,0 @@
+-package old
+";
+    let changed = parse_changed_lines(diff);
+    assert_eq!(changed.len(), 1);
+    assert_eq!(changed[Path::new("a.go")], BTreeSet::from([3, 11, 12]));
+}
+
+#[test]
+fn porcelain_paths_skip_rename_sources() {
+    let status = " M gen/api.pb.go\0R  gen/new.go\0gen/old.go\0?? gen/extra.go\0";
+    assert_eq!(
+        porcelain_paths(status),
+        [
+            PathBuf::from("gen/api.pb.go"),
+            PathBuf::from("gen/new.go"),
+            PathBuf::from("gen/extra.go"),
+        ]
+    );
+}
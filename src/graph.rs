@@ -0,0 +1,193 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::path::Path;
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::backend::{Backend, GraphNode, Target};
+
+/// Output formats of `kit graph`.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Format {
+    /// Graphviz, for `dot -Tsvg`.
+    Dot,
+    /// A Mermaid flowchart, which GitHub renders in Markdown.
+    Mermaid,
+    /// An array of nodes with their dependencies.
+    Json,
+}
+
+/// A node of the printed graph.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Node {
+    backend: String,
+    label: String,
+    /// Whether a change on the branch affects it, rather than it only being depended on.
+    affected: bool,
+    /// Labels of the printed nodes it depends on.
+    deps: BTreeSet<String>,
+}
+
+/// The dependency graph of every backend that can tell, rendered in `format`: the affected
+/// targets and what they depend on, or with `all` every package in the repo.
+pub fn render(
+    repo_root: &Path,
+    backends: &[&dyn Backend],
+    affected: &[(&str, Target)],
+    all: bool,
+    format: Format,
+) -> Result<String> {
+    let mut nodes = Vec::new();
+    for backend in backends {
+        let Some(graph) = backend.graph(repo_root)? else {
+            eprintln!("kit: {} can't list dependencies, skipping it", backend.name());
+            continue;
+        };
+        let targets: Vec<&Target> = affected
+            .iter()
+            .filter(|(name, _)| *name == backend.name())
+            .map(|(_, t)| t)
+            .collect();
+        nodes.extend(select(repo_root, backend.name(), &graph, &targets, all));
+    }
+    Ok(match format {
+        Format::Dot => to_dot(&nodes),
+        Format::Mermaid => to_mermaid(&nodes),
+        Format::Json => format!("{}\n", serde_json::to_string_pretty(&nodes)?),
+    })
+}
+
+/// Whether a graph node is in a target: the target's own package, or any below a recursive target
+/// (`./pkg/...`, `//...:all`, or the whole repo `.`).
+fn in_target(repo_root: &Path, node: &GraphNode, target: &Target) -> bool {
+    let dir = target.dir.strip_prefix(repo_root).unwrap_or(&target.dir);
+    let recursive = target.label == "." || target.label.contains("...");
+    node.dir == dir || (recursive && node.dir.starts_with(dir))
+}
+
+/// The affected nodes and everything they depend on, directly or not, or every node with `all`.
+fn select(repo_root: &Path, backend: &str, graph: &[GraphNode], targets: &[&Target], all: bool) -> Vec<Node> {
+    let affected: BTreeSet<&str> = graph
+        .iter()
+        .filter(|n| targets.iter().any(|t| in_target(repo_root, n, t)))
+        .map(|n| n.label.as_str())
+        .collect();
+    let mut selected: BTreeSet<&str> = if all {
+        graph.iter().map(|n| n.label.as_str()).collect()
+    } else {
+        affected.clone()
+    };
+    loop {
+        let deps: Vec<&str> = graph
+            .iter()
+            .filter(|n| selected.contains(n.label.as_str()))
+            .flat_map(|n| n.deps.iter().map(String::as_str))
+            .filter(|d| !selected.contains(d))
+            .collect();
+        if deps.is_empty() {
+            break;
+        }
+        selected.extend(deps);
+    }
+    graph
+        .iter()
+        .filter(|n| selected.contains(n.label.as_str()))
+        .map(|n| Node {
+            backend: backend.to_string(),
+            label: n.label.clone(),
+            affected: affected.contains(n.label.as_str()),
+            deps: n
+                .deps
+                .iter()
+                .filter(|d| selected.contains(d.as_str()))
+                .cloned()
+                .collect(),
+        })
+        .collect()
+}
+
+fn backends(nodes: &[Node]) -> BTreeSet<&str> {
+    nodes.iter().map(|n| n.backend.as_str()).collect()
+}
+
+/// A Graphviz digraph with the affected nodes filled, and a cluster per backend when there are
+/// several.
+fn to_dot(nodes: &[Node]) -> String {
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+    let backends = backends(nodes);
+    let mut out = String::from("digraph kit {\n  rankdir=LR;\n  node [shape=box];\n");
+    for backend in &backends {
+        let indent = if backends.len() > 1 {
+            let _ = writeln!(
+                out,
+                "  subgraph {} {{\n    label={};",
+                quote(&format!("cluster_{backend}")),
+                quote(backend)
+            );
+            "    "
+        } else {
+            "  "
+        };
+        for node in nodes.iter().filter(|n| n.backend == *backend) {
+            let style = if node.affected {
+                " [style=filled, fillcolor=lightyellow]"
+            } else {
+                ""
+            };
+            let _ = writeln!(out, "{indent}{}{style};", quote(&node.label));
+            for dep in &node.deps {
+                let _ = writeln!(out, "{indent}{} -> {};", quote(&node.label), quote(dep));
+            }
+        }
+        if backends.len() > 1 {
+            out.push_str("  }\n");
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// A Mermaid flowchart with the affected nodes highlighted, and a subgraph per backend when there
+/// are several. Node ids are generated, since labels like `//pkg:all` aren't valid ids.
+fn to_mermaid(nodes: &[Node]) -> String {
+    let id = |backend: &str, label: &str| {
+        let index = nodes
+            .iter()
+            .position(|n| n.backend == backend && n.label == label)
+            .unwrap_or_default();
+        format!("n{index}")
+    };
+    let backends = backends(nodes);
+    let mut out = String::from("flowchart LR\n");
+    for backend in &backends {
+        if backends.len() > 1 {
+            let _ = writeln!(out, "  subgraph {backend}");
+        }
+        for node in nodes.iter().filter(|n| n.backend == *backend) {
+            let class = if node.affected { ":::affected" } else { "" };
+            let label = node.label.replace('"', "#quot;");
+            let _ = writeln!(out, "  {}[\"{label}\"]{class}", id(backend, &node.label));
+        }
+        if backends.len() > 1 {
+            out.push_str("  end\n");
+        }
+    }
+    for node in nodes {
+        for dep in &node.deps {
+            let _ = writeln!(
+                out,
+                "  {} --> {}",
+                id(&node.backend, &node.label),
+                id(&node.backend, dep)
+            );
+        }
+    }
+    out.push_str("  classDef affected fill:#fff3b0,stroke:#b58900\n");
+    out
+}
+
+#[cfg(test)]
+#[path = "graph_test.rs"]
+mod tests;
@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+
+use super::*;
+use crate::backend::TargetMeta;
+
+fn node(label: &str, dir: &str, deps: &[&str]) -> GraphNode {
+    GraphNode {
+        label: label.to_string(),
+        dir: PathBuf::from(dir),
+        deps: deps.iter().map(|d| d.to_string()).collect(),
+    }
+}
+
+fn graph() -> Vec<GraphNode> {
+    vec![
+        node("./cmd/app", "cmd/app", &["./pkg/api"]),
+        node("./pkg/api", "pkg/api", &["./pkg/db"]),
+        node("./pkg/api/v2", "pkg/api/v2", &[]),
+        node("./pkg/db", "pkg/db", &[]),
+        node("./tools", "tools", &[]),
+    ]
+}
+
+#[test]
+fn selection_is_the_affected_nodes_and_their_dependencies() {
+    let repo = Path::new("/repo");
+    let target = Target {
+        label: "./pkg/api/...".to_string(),
+        dir: repo.join("pkg/api"),
+        meta: TargetMeta::default(),
+        files: Vec::new(),
+    };
+    let nodes = select(repo, "go", &graph(), &[&target], false);
+    let selected: Vec<(&str, bool)> = nodes.iter().map(|n| (n.label.as_str(), n.affected)).collect();
+    assert_eq!(
+        selected,
+        [("./pkg/api", true), ("./pkg/api/v2", true), ("./pkg/db", false)]
+    );
+    assert_eq!(select(repo, "go", &graph(), &[&target], true).len(), 5);
+}
+
+#[test]
+fn dot_and_mermaid_mark_affected_nodes() {
+    let nodes = vec![
+        Node {
+            backend: "bazel".to_string(),
+            label: "//pkg/api:all".to_string(),
+            affected: true,
+            deps: ["//pkg/db:all".to_string()].into(),
+        },
+        Node {
+            backend: "bazel".to_string(),
+            label: "//pkg/db:all".to_string(),
+            affected: false,
+            deps: BTreeSet::new(),
+        },
+    ];
+    assert_eq!(
+        to_dot(&nodes),
+        "digraph kit {
+  rankdir=LR;
+  node [shape=box];
+  \"//pkg/api:all\" [style=filled, fillcolor=lightyellow];
+  \"//pkg/api:all\" -> \"//pkg/db:all\";
+  \"//pkg/db:all\";
+}
+"
+    );
+    assert_eq!(
+        to_mermaid(&nodes),
+        "flowchart LR
+  n0[\"//pkg/api:all\"]:::affected
+  n1[\"//pkg/db:all\"]
+  n0 --> n1
+  classDef affected fill:#fff3b0,stroke:#b58900
+"
+    );
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{git, state};
+
+/// Green runs kept per repo; older ones are dropped when a new one is recorded.
+const KEEP: usize = 200;
+
+/// A command that passed on every affected target at a commit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Green {
+    pub branch: String,
+    pub commit: String,
+    /// The kit command that passed (e.g. "test").
+    pub command: String,
+    /// The [`Environment`](crate::environment::Environment) fingerprint of the run.
+    #[serde(default)]
+    pub environment: Option<String>,
+}
+
+fn path(repo_root: &Path) -> Result<PathBuf> {
+    Ok(state::dir(repo_root, "history")?.join("green.jsonl"))
+}
+
+/// Record that `command` passed at HEAD on the current branch, in the environment with the given
+/// fingerprint. Runs with uncommitted changes or on a detached HEAD are not recorded, since they don't
+/// vouch for a commit on a branch.
+pub fn record_green(repo_root: &Path, command: &str, environment: &str) -> Result<()> {
+    let Some(branch) = git::current_branch(repo_root)? else {
+        return Ok(());
+    };
+    if !git::is_clean(repo_root)? {
+        return Ok(());
+    }
+    let green = Green {
+        branch,
+        commit: git::head(repo_root)?,
+        command: command.to_string(),
+        environment: Some(environment.to_string()),
+    };
+    let path = path(repo_root)?;
+    let text = std::fs::read_to_string(&path).unwrap_or_default();
+    let mut lines: Vec<&str> = text.lines().collect();
+    let line = serde_json::to_string(&green)?;
+    lines.push(&line);
+    let kept = &lines[lines.len().saturating_sub(KEEP)..];
+    let mut file = std::fs::File::create(&path).with_context(|| format!("failed to write {}", path.display()))?;
+    for line in kept {
+        writeln!(file, "{line}").with_context(|| format!("failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// The newest commit on the current branch, and still in HEAD's history, at which `command` passed
+/// in the same environment. Without a command, a pass of any command counts. Passes with another
+/// toolchain don't count: the upgrade may break what they tested.
+pub fn last_green(repo_root: &Path, command: Option<&str>, environment: &str) -> Result<Option<String>> {
+    let Some(branch) = git::current_branch(repo_root)? else {
+        return Ok(None);
+    };
+    let text = std::fs::read_to_string(path(repo_root)?).unwrap_or_default();
+    for commit in candidates(&text, &branch, command, environment) {
+        if git::is_ancestor(repo_root, &commit)? {
+            return Ok(Some(commit));
+        }
+    }
+    Ok(None)
+}
+
+/// The commands recorded as passing at `commit`, on any branch and in any environment.
+pub fn passed_at(repo_root: &Path, commit: &str) -> Result<BTreeSet<String>> {
+    let text = std::fs::read_to_string(path(repo_root)?).unwrap_or_default();
+    Ok(commands_at(&text, commit))
+}
+
+/// Drop the green runs recorded on branches that no longer exist locally, returning how many were
+/// dropped.
+pub fn prune(repo_root: &Path) -> Result<usize> {
+    let path = path(repo_root)?;
+    let Ok(text) = std::fs::read_to_string(&path) else {
+        return Ok(0);
+    };
+    let branches: BTreeSet<String> = git::branches(repo_root)?
+        .iter()
+        .filter_map(|b| b.strip_prefix("refs/heads/").map(String::from))
+        .collect();
+    let kept = on_branches(&text, &branches);
+    let dropped = text.lines().count() - kept.len();
+    if dropped > 0 {
+        let mut file = std::fs::File::create(&path).with_context(|| format!("failed to write {}", path.display()))?;
+        for line in kept {
+            writeln!(file, "{line}").with_context(|| format!("failed to write {}", path.display()))?;
+        }
+    }
+    Ok(dropped)
+}
+
+/// The lines of green runs recorded on one of `branches`. Unreadable lines are dropped.
+fn on_branches<'a>(history: &'a str, branches: &BTreeSet<String>) -> Vec<&'a str> {
+    history
+        .lines()
+        .filter(|line| serde_json::from_str::<Green>(line).is_ok_and(|g| branches.contains(&g.branch)))
+        .collect()
+}
+
+fn commands_at(history: &str, commit: &str) -> BTreeSet<String> {
+    history
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Green>(line).ok())
+        .filter(|g| g.commit == commit)
+        .map(|g| g.command)
+        .collect()
+}
+
+/// Commits `command` passed at on `branch` in `environment`, newest first. Unreadable lines are
+/// skipped.
+fn candidates(history: &str, branch: &str, command: Option<&str>, environment: &str) -> Vec<String> {
+    history
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<Green>(line).ok())
+        .filter(|g| g.branch == branch && command.is_none_or(|c| g.command == c))
+        .filter(|g| g.environment.as_deref() == Some(environment))
+        .map(|g| g.commit)
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "history_test.rs"]
+mod tests;
@@ -0,0 +1,60 @@
+use super::*;
+
+fn line(branch: &str, commit: &str, command: &str) -> String {
+    let green = Green {
+        branch: branch.to_string(),
+        commit: commit.to_string(),
+        command: command.to_string(),
+        environment: Some("env1".to_string()),
+    };
+    serde_json::to_string(&green).unwrap()
+}
+
+#[test]
+fn candidates_are_the_branch_greens_in_the_same_environment_newest_first() {
+    let history = [
+        line("feat/a", "c1", "test"),
+        line("main", "c2", "test"),
+        line("feat/a", "c3", "lint"),
+        "not json".to_string(),
+        line("feat/a", "c4", "test"),
+        line("feat/a", "c5", "test").replace("env1", "env2"),
+        r#"{"branch":"feat/a","commit":"c6","command":"test"}"#.to_string(),
+    ]
+    .join("\n");
+    assert_eq!(candidates(&history, "feat/a", Some("test"), "env1"), ["c4", "c1"]);
+    assert_eq!(candidates(&history, "feat/a", None, "env1"), ["c4", "c3", "c1"]);
+    assert_eq!(candidates(&history, "feat/a", None, "env2"), ["c5"]);
+    assert!(candidates(&history, "feat/b", None, "env1").is_empty());
+}
+
+#[test]
+fn commands_at_a_commit_count_every_branch_and_environment() {
+    let history = [
+        line("feat/a", "c1", "test"),
+        line("main", "c1", "lint").replace("env1", "env2"),
+        line("feat/a", "c2", "build"),
+    ]
+    .join("\n");
+    assert_eq!(
+        commands_at(&history, "c1"),
+        BTreeSet::from(["lint".to_string(), "test".to_string()])
+    );
+    assert!(commands_at(&history, "c3").is_empty());
+}
+
+#[test]
+fn pruning_keeps_greens_of_existing_branches() {
+    let history = [
+        line("feat/a", "c1", "test"),
+        line("feat/gone", "c2", "test"),
+        "not json".to_string(),
+        line("main", "c3", "lint"),
+    ]
+    .join("\n");
+    let branches = BTreeSet::from(["feat/a".to_string(), "main".to_string()]);
+    assert_eq!(
+        on_branches(&history, &branches),
+        [line("feat/a", "c1", "test"), line("main", "c3", "lint")]
+    );
+}
@@ -0,0 +1,153 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::error::KitError;
+use crate::git;
+
+/// The kit blocks a hook script may hold, each between its own markers.
+const PRECOMPUTE: &str = "precompute";
+const CHECKS: &str = "checks";
+
+/// Every hook kit installs into.
+const HOOKS: &[&str] = &["pre-commit", "pre-push", "post-checkout", "post-commit"];
+
+/// Install git hooks that run `kit affected` in the background after commits and branch switches,
+/// so caches such as the Bazel query cache are warm by the time targets are built or tested.
+pub fn install(repo_root: &Path, base: &str, precompute: bool) -> Result<()> {
+    if !precompute {
+        return Err(KitError::ConfigError("no hooks selected (pass --precompute)".to_string()).into());
+    }
+    let kit = std::env::current_exe().context("failed to locate the kit executable")?;
+    let command = format!(
+        "{} --base {} affected >/dev/null 2>&1 &",
+        shell_quote(&kit.to_string_lossy()),
+        shell_quote(base)
+    );
+    // post-checkout's third argument is 1 for branch switches and 0 for file checkouts.
+    let hooks = [
+        ("post-checkout", format!("if [ \"$3\" = 1 ]; then\n  {command}\nfi")),
+        ("post-commit", command.clone()),
+    ];
+    write_hooks(repo_root, PRECOMPUTE, &hooks)
+}
+
+/// Install git hooks that run `kit fmt --check` and `kit lint`: a pre-commit hook on the staged
+/// files, and a pre-push hook on the branch's changes. Neither flag installs both. A failing check
+/// stops the commit or push; `--no-verify` bypasses it.
+pub fn install_checks(repo_root: &Path, pre_commit: bool, pre_push: bool) -> Result<()> {
+    let kit = std::env::current_exe().context("failed to locate the kit executable")?;
+    let kit = shell_quote(&kit.to_string_lossy());
+    let checks = |changes: &str| format!("{kit} {changes}fmt --check || exit 1\n{kit} {changes}lint || exit 1");
+    let mut hooks = Vec::new();
+    if pre_commit || !pre_push {
+        hooks.push(("pre-commit", checks("--changes staged ")));
+    }
+    if pre_push || !pre_commit {
+        hooks.push(("pre-push", checks("")));
+    }
+    write_hooks(repo_root, CHECKS, &hooks)
+}
+
+/// Remove kit's blocks from the repo's hooks, deleting hook scripts left with nothing else in them.
+pub fn uninstall(repo_root: &Path) -> Result<()> {
+    let dir = git::hooks_dir(repo_root)?;
+    let mut removed = 0;
+    for name in HOOKS {
+        let path = dir.join(name);
+        let Ok(existing) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let script = [PRECOMPUTE, CHECKS]
+            .iter()
+            .fold(existing.clone(), |script, block| without_block(&script, block));
+        if script == existing {
+            continue;
+        }
+        if script.lines().all(|l| l.trim().is_empty() || l.starts_with("#!")) {
+            std::fs::remove_file(&path).with_context(|| format!("failed to remove {}", path.display()))?;
+        } else {
+            std::fs::write(&path, script).with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        eprintln!("kit: uninstalled {}", path.display());
+        removed += 1;
+    }
+    if removed == 0 {
+        eprintln!("kit: no kit hooks installed in {}", dir.display());
+    }
+    Ok(())
+}
+
+/// Write kit's `block` with each hook's body into the hooks directory, which git's `core.hooksPath`
+/// may move.
+fn write_hooks(repo_root: &Path, block: &str, hooks: &[(&str, String)]) -> Result<()> {
+    let dir = git::hooks_dir(repo_root)?;
+    std::fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    for (name, body) in hooks {
+        let path = dir.join(name);
+        let existing = std::fs::read_to_string(&path).ok();
+        let script = with_block(existing.as_deref(), block, body);
+        std::fs::write(&path, script).with_context(|| format!("failed to write {}", path.display()))?;
+        make_executable(&path)?;
+        eprintln!("kit: installed {}", path.display());
+    }
+    Ok(())
+}
+
+/// The markers around kit's `block` in a hook script.
+fn markers(block: &str) -> (String, String) {
+    (format!("# >>> kit {block} >>>"), format!("# <<< kit {block} <<<"))
+}
+
+/// A hook script containing kit's `block` with `body`, replacing an earlier one and keeping
+/// anything else already in the script.
+fn with_block(existing: Option<&str>, block: &str, body: &str) -> String {
+    let (begin, end) = markers(block);
+    let lines = format!("{begin}\n{body}\n{end}\n");
+    let Some(existing) = existing else {
+        return format!("#!/bin/sh\n{lines}");
+    };
+    if let (Some(start), Some(stop)) = (existing.find(&begin), existing.find(&end)) {
+        let rest = existing[stop + end.len()..].trim_start_matches('\n');
+        return format!("{}{lines}{rest}", &existing[..start]);
+    }
+    let sep = if existing.is_empty() || existing.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    format!("{existing}{sep}{lines}")
+}
+
+/// A hook script without kit's `block`, or unchanged when it has none.
+fn without_block(existing: &str, block: &str) -> String {
+    let (begin, end) = markers(block);
+    match (existing.find(&begin), existing.find(&end)) {
+        (Some(start), Some(stop)) => {
+            let rest = existing[stop + end.len()..].trim_start_matches('\n');
+            format!("{}{rest}", &existing[..start])
+        }
+        _ => existing.to_string(),
+    }
+}
+
+/// Quote a string for a POSIX shell.
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(unix)]
+pub fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+        .with_context(|| format!("failed to make {} executable", path.display()))
+}
+
+#[cfg(not(unix))]
+pub fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "hooks_test.rs"]
+mod tests;
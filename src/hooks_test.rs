@@ -0,0 +1,34 @@
+use super::*;
+
+#[test]
+fn with_block_creates_appends_and_replaces() {
+    let (begin, end) = markers(PRECOMPUTE);
+    assert_eq!(begin, "# >>> kit precompute >>>");
+
+    let fresh = with_block(None, PRECOMPUTE, "echo one");
+    assert_eq!(fresh, format!("#!/bin/sh\n{begin}\necho one\n{end}\n"));
+
+    let appended = with_block(Some("#!/bin/sh\nlint-staged"), PRECOMPUTE, "echo one");
+    assert_eq!(appended, format!("#!/bin/sh\nlint-staged\n{begin}\necho one\n{end}\n"));
+
+    let replaced = with_block(Some(&format!("{appended}echo after\n")), PRECOMPUTE, "echo two");
+    assert_eq!(
+        replaced,
+        format!("#!/bin/sh\nlint-staged\n{begin}\necho two\n{end}\necho after\n")
+    );
+}
+
+#[test]
+fn without_block_keeps_other_blocks_and_lines() {
+    let script = with_block(Some("#!/bin/sh\nlint-staged\n"), CHECKS, "kit lint || exit 1");
+    let script = with_block(Some(&script), PRECOMPUTE, "kit affected &");
+    let (begin, end) = markers(PRECOMPUTE);
+    assert_eq!(
+        without_block(&script, CHECKS),
+        format!("#!/bin/sh\nlint-staged\n{begin}\nkit affected &\n{end}\n")
+    );
+    assert_eq!(
+        without_block("#!/bin/sh\nlint-staged\n", CHECKS),
+        "#!/bin/sh\nlint-staged\n"
+    );
+}
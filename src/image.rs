@@ -0,0 +1,48 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::Result;
+
+use crate::config::{ImageConfig, ServiceConfig};
+use crate::error::KitError;
+
+/// Build (and optionally push) the image of each service with `docker buildx build`.
+pub fn build(repo_root: &Path, services: &[&ServiceConfig], config: &ImageConfig, tag: &str, push: bool) -> Result<()> {
+    for service in services {
+        let Some(image) = &service.image else {
+            eprintln!("kit: service {} has no image configured, skipping", service.name);
+            continue;
+        };
+        eprintln!("kit: building image {image}:{tag} for {}", service.name);
+        let args = buildx_args(service, image, config, tag, push);
+        let status = Command::new("docker")
+            .args(&args)
+            .current_dir(repo_root)
+            .status()
+            .map_err(|e| KitError::spawn("docker", e))?;
+        KitError::check(&format!("docker buildx build for {}", service.name), status)?;
+    }
+    Ok(())
+}
+
+fn buildx_args(service: &ServiceConfig, image: &str, config: &ImageConfig, tag: &str, push: bool) -> Vec<String> {
+    let mut args: Vec<String> = vec!["buildx".into(), "build".into()];
+    args.extend(["--file".into(), service.dockerfile().to_string_lossy().into_owned()]);
+    args.extend(["--tag".into(), format!("{image}:{tag}")]);
+    if let Some(from) = &config.cache_from {
+        args.extend(["--cache-from".into(), from.replace("{image}", image)]);
+    }
+    if let Some(to) = &config.cache_to {
+        args.extend(["--cache-to".into(), to.replace("{image}", image)]);
+    }
+    if !config.platforms.is_empty() {
+        args.extend(["--platform".into(), config.platforms.join(",")]);
+    }
+    args.push(if push { "--push" } else { "--load" }.into());
+    args.push(service.context().to_string_lossy().into_owned());
+    args
+}
+
+#[cfg(test)]
+#[path = "image_test.rs"]
+mod tests;
@@ -0,0 +1,53 @@
+use super::*;
+use std::path::PathBuf;
+
+fn service() -> ServiceConfig {
+    ServiceConfig {
+        name: "api".to_string(),
+        dir: PathBuf::from("services/api"),
+        inputs: vec![],
+        image: Some("ghcr.io/acme/api".to_string()),
+        dockerfile: None,
+        context: None,
+        deploy: Default::default(),
+    }
+}
+
+#[test]
+fn buildx_args_defaults() {
+    let args = buildx_args(&service(), "ghcr.io/acme/api", &ImageConfig::default(), "abc123", false);
+    assert_eq!(
+        args,
+        vec![
+            "buildx",
+            "build",
+            "--file",
+            "services/api/Dockerfile",
+            "--tag",
+            "ghcr.io/acme/api:abc123",
+            "--load",
+            "services/api",
+        ]
+    );
+}
+
+#[test]
+fn buildx_args_cache_and_push() {
+    let config = ImageConfig {
+        cache_from: Some("type=registry,ref={image}:buildcache".to_string()),
+        cache_to: Some("type=registry,ref={image}:buildcache,mode=max".to_string()),
+        platforms: vec!["linux/amd64".to_string(), "linux/arm64".to_string()],
+        test: None,
+    };
+    let args = buildx_args(&service(), "ghcr.io/acme/api", &config, "v1", true);
+    assert!(
+        args.windows(2)
+            .any(|w| w == ["--cache-from", "type=registry,ref=ghcr.io/acme/api:buildcache"])
+    );
+    assert!(
+        args.windows(2)
+            .any(|w| w == ["--cache-to", "type=registry,ref=ghcr.io/acme/api:buildcache,mode=max"])
+    );
+    assert!(args.windows(2).any(|w| w == ["--platform", "linux/amd64,linux/arm64"]));
+    assert!(args.contains(&"--push".to_string()));
+}
@@ -0,0 +1,83 @@
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::backend::Backend;
+use crate::error::KitError;
+use crate::git;
+
+const FILE_NAME: &str = "kit.toml";
+
+/// A detected backend and the tools it runs, as the paths it runs them by (e.g. `./gradlew`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detected {
+    pub backend: String,
+    pub tools: Vec<String>,
+}
+
+/// Write a kit.toml for the repo with the settings kit detects, and return its path and the base
+/// branch it names. An existing kit.toml is only replaced with `force`.
+pub fn run(repo_root: &Path, backends: &[&dyn Backend], force: bool) -> Result<(PathBuf, String)> {
+    let path = repo_root.join(FILE_NAME);
+    if path.exists() && !force {
+        return Err(KitError::ConfigError(format!(
+            "{} already exists (pass --force to replace it)",
+            path.display()
+        ))
+        .into());
+    }
+    let base = git::default_branch(repo_root)?.unwrap_or_else(|| "main".to_string());
+    let detected: Vec<Detected> = backends
+        .iter()
+        .filter(|b| b.detect(repo_root))
+        .map(|b| {
+            let mut tools: Vec<String> = b
+                .toolchain(repo_root)
+                .into_iter()
+                .chain(b.extra_tools(repo_root))
+                .map(|i| i.program)
+                .collect();
+            tools.dedup();
+            Detected {
+                backend: b.name().to_string(),
+                tools,
+            }
+        })
+        .collect();
+    std::fs::write(&path, render(&base, &detected)).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok((path, base))
+}
+
+/// The contents of a new kit.toml: the base branch and the defaults worth knowing about, with the
+/// detected backends noted in a comment since kit finds them itself on every run.
+fn render(base: &str, detected: &[Detected]) -> String {
+    let mut out = String::from("# kit configuration, generated by `kit init`. Every setting is optional.\n#\n");
+    if detected.is_empty() {
+        out.push_str("# No supported build system was detected; define one under [[backends]].\n");
+    } else {
+        out.push_str("# Detected backends and the tools they run:\n");
+        for d in detected {
+            let _ = writeln!(out, "#   {}: {}", d.backend, d.tools.join(", "));
+        }
+    }
+    let _ = writeln!(
+        out,
+        r#"
+# Branch to diff against to find what changed; --base overrides it.
+base = "{base}"
+
+[tools]
+# When a tool is missing: strict (fail), degrade (skip lint and fmt), or skip.
+missing = "degrade"
+
+[ci]
+# Stages `kit ci` runs, in order: fmt, lint, check, build, test.
+stages = ["fmt", "lint", "build", "test"]"#
+    );
+    out
+}
+
+#[cfg(test)]
+#[path = "init_test.rs"]
+mod tests;
@@ -0,0 +1,32 @@
+use super::*;
+use crate::config::{CiStage, Config};
+
+#[test]
+fn rendered_config_lists_backends_and_parses() {
+    let detected = [
+        Detected {
+            backend: "go".to_string(),
+            tools: vec!["go".to_string(), "golangci-lint".to_string()],
+        },
+        Detected {
+            backend: "gradle".to_string(),
+            tools: vec!["./gradlew".to_string()],
+        },
+    ];
+    let text = render("master", &detected);
+    assert!(
+        text.contains("#   go: go, golangci-lint\n#   gradle: ./gradlew\n"),
+        "{text}"
+    );
+    let config: Config = toml::from_str(&text).unwrap();
+    assert_eq!(config.base.as_deref(), Some("master"));
+    assert_eq!(config.ci.stages[0], CiStage::Fmt);
+}
+
+#[test]
+fn an_existing_config_is_kept_without_force() {
+    let repo = tempfile::tempdir().unwrap();
+    std::fs::write(repo.path().join("kit.toml"), "base = \"main\"\n").unwrap();
+    let err = run(repo.path(), &[], false).unwrap_err();
+    assert!(matches!(KitError::find(&err), Some(KitError::ConfigError(_))));
+}
@@ -0,0 +1,123 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::exec::Invocation;
+use crate::git;
+
+/// A dependency installer for one package manager.
+pub struct Installer {
+    pub name: &'static str,
+    /// File marking the directory the installer runs in.
+    pub root: &'static str,
+    /// Files whose changes mean dependencies must be reinstalled.
+    pub triggers: &'static [&'static str],
+    pub program: &'static str,
+    pub args: &'static [&'static str],
+}
+
+impl Installer {
+    fn invocation(&self, dir: &Path) -> Invocation {
+        Invocation::new(self.program, dir).args(self.args.iter().copied())
+    }
+}
+
+pub const INSTALLERS: &[Installer] = &[
+    Installer {
+        name: "go",
+        root: "go.mod",
+        triggers: &["go.mod", "go.sum"],
+        program: "go",
+        args: &["mod", "download"],
+    },
+    Installer {
+        name: "pnpm",
+        root: "pnpm-lock.yaml",
+        triggers: &["pnpm-lock.yaml", "pnpm-workspace.yaml", "package.json"],
+        program: "pnpm",
+        args: &["install", "--frozen-lockfile"],
+    },
+    Installer {
+        name: "yarn",
+        root: "yarn.lock",
+        triggers: &["yarn.lock", "package.json"],
+        program: "yarn",
+        args: &["install", "--frozen-lockfile"],
+    },
+    Installer {
+        name: "npm",
+        root: "package-lock.json",
+        triggers: &["package-lock.json", "package.json"],
+        program: "npm",
+        args: &["ci"],
+    },
+    Installer {
+        name: "bundler",
+        root: "Gemfile",
+        triggers: &["Gemfile", "Gemfile.lock"],
+        program: "bundle",
+        args: &["install"],
+    },
+    Installer {
+        name: "pip",
+        root: "requirements.txt",
+        triggers: &["requirements.txt"],
+        program: "pip",
+        args: &["install", "-r", "requirements.txt"],
+    },
+];
+
+/// Directories (relative to the repo root) each installer needs to run in: those whose dependency
+/// files changed, or with `all`, every directory with the installer's root file.
+pub fn plan(repo_root: &Path, changed_files: &[PathBuf], all: bool) -> Result<Vec<(&'static Installer, Vec<PathBuf>)>> {
+    let mut dirs: BTreeMap<&'static str, BTreeSet<PathBuf>> = BTreeMap::new();
+    for installer in INSTALLERS {
+        if all {
+            let roots = git::find_files(repo_root, installer.root)?;
+            dirs.entry(installer.name).or_default().extend(
+                roots
+                    .iter()
+                    .map(|r| r.parent().map(Path::to_path_buf).unwrap_or_default()),
+            );
+            continue;
+        }
+        for file in changed_files {
+            let name = file.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if !installer.triggers.contains(&name) {
+                continue;
+            }
+            if let Some(dir) = nearest_root(repo_root, file, installer.root) {
+                dirs.entry(installer.name).or_default().insert(dir);
+            }
+        }
+    }
+    Ok(INSTALLERS
+        .iter()
+        .filter_map(|i| {
+            let dirs = dirs.remove(i.name)?;
+            (!dirs.is_empty()).then(|| (i, dirs.into_iter().collect()))
+        })
+        .collect())
+}
+
+/// Commands installing dependencies in each directory, in order.
+pub fn invocations(repo_root: &Path, installer: &Installer, dirs: &[PathBuf]) -> Vec<Invocation> {
+    dirs.iter().map(|d| installer.invocation(&repo_root.join(d))).collect()
+}
+
+/// The closest directory at or above the file's containing the given root file.
+fn nearest_root(repo_root: &Path, file: &Path, root: &str) -> Option<PathBuf> {
+    let mut dir = file.parent();
+    while let Some(d) = dir {
+        if repo_root.join(d).join(root).is_file() {
+            return Some(d.to_path_buf());
+        }
+        dir = d.parent();
+    }
+    None
+}
+
+#[cfg(test)]
+#[path = "install_test.rs"]
+mod tests;
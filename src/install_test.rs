@@ -0,0 +1,38 @@
+use super::*;
+use tempfile::TempDir;
+
+fn touch(root: &Path, file: &str) {
+    let path = root.join(file);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, "").unwrap();
+}
+
+#[test]
+fn plan_runs_installers_where_dependency_files_changed() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    for file in [
+        "pnpm-lock.yaml",
+        "web/app/package.json",
+        "svc/go.mod",
+        "tools/requirements.txt",
+    ] {
+        touch(root, file);
+    }
+    let changed = [
+        PathBuf::from("web/app/package.json"),
+        PathBuf::from("svc/go.sum"),
+        PathBuf::from("svc/main.go"),
+        PathBuf::from("tools/README.md"),
+    ];
+    let plan = plan(root, &changed, false).unwrap();
+    let names: Vec<(&str, &[PathBuf])> = plan.iter().map(|(i, d)| (i.name, d.as_slice())).collect();
+    assert_eq!(
+        names,
+        [("go", &[PathBuf::from("svc")][..]), ("pnpm", &[PathBuf::from("")][..])]
+    );
+    assert_eq!(
+        invocations(root, plan[1].0, &plan[1].1),
+        [Invocation::new("pnpm", root).args(["install", "--frozen-lockfile"])]
+    );
+}
@@ -0,0 +1,71 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use ignore::Match;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use crate::git;
+
+const FILE_NAME: &str = ".kitignore";
+
+/// Exclusion patterns loaded from `.kitignore` files (gitignore syntax).
+///
+/// Each `.kitignore` applies to paths beneath the directory it lives in. Files deeper in the
+/// tree are consulted last, so their patterns (including `!` negations) take precedence.
+pub struct KitIgnore {
+    /// One matcher per `.kitignore`, ordered from shallowest to deepest.
+    matchers: Vec<Gitignore>,
+}
+
+impl KitIgnore {
+    /// Load every `.kitignore` in the repository.
+    pub fn load(repo_root: &Path) -> Result<Self> {
+        let files = git::find_files(repo_root, FILE_NAME)?;
+        Self::from_files(repo_root, files)
+    }
+
+    /// Build matchers from `.kitignore` files given relative to the repo root.
+    fn from_files(repo_root: &Path, mut files: Vec<PathBuf>) -> Result<Self> {
+        files.sort_by_key(|f| f.components().count());
+        let mut matchers = Vec::new();
+        for rel in files {
+            let path = repo_root.join(&rel);
+            let dir = path.parent().unwrap_or(repo_root);
+            let mut builder = GitignoreBuilder::new(dir);
+            if let Some(err) = builder.add(&path) {
+                return Err(err).with_context(|| format!("failed to read {}", rel.display()));
+            }
+            let matcher = builder
+                .build()
+                .with_context(|| format!("invalid pattern in {}", rel.display()))?;
+            matchers.push(matcher);
+        }
+        Ok(Self { matchers })
+    }
+
+    /// Returns true if the given repo-relative path is excluded by a `.kitignore`.
+    pub fn is_ignored(&self, repo_root: &Path, rel: &Path) -> bool {
+        let path = repo_root.join(rel);
+        let mut ignored = false;
+        for matcher in &self.matchers {
+            if !path.starts_with(matcher.path()) {
+                continue;
+            }
+            match matcher.matched_path_or_any_parents(&path, false) {
+                Match::Ignore(_) => ignored = true,
+                Match::Whitelist(_) => ignored = false,
+                Match::None => {}
+            }
+        }
+        ignored
+    }
+
+    /// Drop repo-relative paths that are excluded by a `.kitignore`.
+    pub fn filter(&self, repo_root: &Path, files: Vec<PathBuf>) -> Vec<PathBuf> {
+        files.into_iter().filter(|f| !self.is_ignored(repo_root, f)).collect()
+    }
+}
+
+#[cfg(test)]
+#[path = "kitignore_test.rs"]
+mod tests;
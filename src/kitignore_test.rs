@@ -0,0 +1,58 @@
+use super::*;
+use tempfile::TempDir;
+
+fn write(root: &Path, rel: &str, contents: &str) -> PathBuf {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, contents).unwrap();
+    PathBuf::from(rel)
+}
+
+#[test]
+fn root_patterns_apply_everywhere() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    let files = vec![write(root, ".kitignore", "*.generated.go\nvendor/\n")];
+    let ignore = KitIgnore::from_files(root, files).unwrap();
+
+    assert!(ignore.is_ignored(root, Path::new("pkg/foo/api.generated.go")));
+    assert!(ignore.is_ignored(root, Path::new("vendor/github.com/x/y.go")));
+    assert!(!ignore.is_ignored(root, Path::new("pkg/foo/api.go")));
+}
+
+#[test]
+fn nested_patterns_are_relative_to_their_directory() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    let files = vec![write(root, "web/.kitignore", "/dist\n")];
+    let ignore = KitIgnore::from_files(root, files).unwrap();
+
+    assert!(ignore.is_ignored(root, Path::new("web/dist/index.js")));
+    assert!(!ignore.is_ignored(root, Path::new("dist/index.js")));
+    assert!(!ignore.is_ignored(root, Path::new("web/src/dist/index.js")));
+}
+
+#[test]
+fn deeper_files_override_shallower_ones() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    let files = vec![
+        write(root, "pkg/keep/.kitignore", "!*.md\n"),
+        write(root, ".kitignore", "*.md\n"),
+    ];
+    let ignore = KitIgnore::from_files(root, files).unwrap();
+
+    assert!(ignore.is_ignored(root, Path::new("README.md")));
+    assert!(!ignore.is_ignored(root, Path::new("pkg/keep/README.md")));
+}
+
+#[test]
+fn filter_drops_ignored_paths() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    let files = vec![write(root, ".kitignore", "docs/\n")];
+    let ignore = KitIgnore::from_files(root, files).unwrap();
+
+    let changed = vec![PathBuf::from("docs/index.md"), PathBuf::from("main.go")];
+    assert_eq!(ignore.filter(root, changed), vec![PathBuf::from("main.go")]);
+}
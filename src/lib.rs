@@ -0,0 +1,111 @@
+//! kit's change providers, for embedding kit's notion of "what changed" in other tools, and the
+//! modules the `kit` binary is built from.
+//!
+//! A [`ChangeProvider`] tells kit which files a branch changed. The built-in ones ask git
+//! ([`Git`], [`Staged`]) or Mercurial ([`Mercurial`]), read a list of paths ([`FileList`]) or a CI
+//! webhook payload ([`Webhook`]), or run a command ([`External`]); tools embedding kit implement
+//! the trait for their own source of changes.
+
+pub mod changes;
+
+pub use changes::{ChangeProvider, External, FileList, Git, Memoized, Mercurial, Staged, Webhook};
+
+// The rest is the binary's own code, public for main.rs and with no stability promise.
+#[doc(hidden)]
+pub mod affected;
+#[doc(hidden)]
+pub mod audit;
+#[doc(hidden)]
+pub mod backend;
+#[doc(hidden)]
+pub mod base;
+#[doc(hidden)]
+pub mod bisect;
+#[doc(hidden)]
+pub mod cache;
+#[doc(hidden)]
+pub mod ci;
+#[doc(hidden)]
+pub mod codegen;
+#[doc(hidden)]
+pub mod codeowners;
+#[doc(hidden)]
+pub mod commits;
+#[doc(hidden)]
+pub mod config;
+#[doc(hidden)]
+pub mod coverage;
+#[doc(hidden)]
+pub mod cycles;
+#[doc(hidden)]
+pub mod depcheck;
+#[doc(hidden)]
+pub mod deploy;
+#[doc(hidden)]
+pub mod deps;
+#[doc(hidden)]
+pub mod doctor;
+#[doc(hidden)]
+pub mod environment;
+#[doc(hidden)]
+pub mod error;
+#[doc(hidden)]
+pub mod exec;
+#[doc(hidden)]
+pub mod failures;
+#[doc(hidden)]
+pub mod gate;
+#[doc(hidden)]
+pub mod git;
+#[doc(hidden)]
+pub mod graph;
+#[doc(hidden)]
+pub mod history;
+#[doc(hidden)]
+pub mod hooks;
+#[doc(hidden)]
+pub mod image;
+#[doc(hidden)]
+pub mod init;
+#[doc(hidden)]
+pub mod install;
+#[doc(hidden)]
+pub mod kitignore;
+#[doc(hidden)]
+pub mod matrix;
+#[doc(hidden)]
+pub mod orphans;
+#[doc(hidden)]
+pub mod outdated;
+#[doc(hidden)]
+pub mod paths;
+#[doc(hidden)]
+pub mod prefetch;
+#[doc(hidden)]
+pub mod provenance;
+#[doc(hidden)]
+pub mod release;
+#[doc(hidden)]
+pub mod relocate;
+#[doc(hidden)]
+pub mod report;
+#[doc(hidden)]
+pub mod runner;
+#[doc(hidden)]
+pub mod scaffold;
+#[doc(hidden)]
+pub mod security;
+#[doc(hidden)]
+pub mod self_update;
+#[doc(hidden)]
+pub mod services;
+#[doc(hidden)]
+pub mod stack;
+#[doc(hidden)]
+pub mod state;
+#[doc(hidden)]
+pub mod stress;
+#[doc(hidden)]
+pub mod timing;
+#[doc(hidden)]
+pub mod watch;
@@ -1,26 +1,170 @@
-mod backend;
-mod git;
-
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Instant;
+
+use kit::{
+    affected, audit, backend, base, bisect, cache, changes, ci, codegen, codeowners, commits, config, coverage, cycles,
+    depcheck, deploy, deps, doctor, error, exec, failures, gate, git, graph, history, hooks, image, init, install,
+    kitignore, matrix, orphans, outdated, paths, prefetch, provenance, release, relocate, report, runner, scaffold,
+    security, self_update, services, stack, state, stress, timing, watch,
+};
+
+use backend::{Backend, Target, TargetFilter, all_backends};
+use changes::ChangeProvider;
+use config::{CiStage, Config, UnchangedScope};
+use error::KitError;
+use exec::Invocation;
+use kitignore::KitIgnore;
+use report::{ConsoleReporter, Reporter, Skip, Step, upload};
+use runner::{Budget, Runner};
 
-use backend::{Backend, all_backends};
+const EXIT_CODES: &str = "\
+Exit codes:
+  0  success
+  1  a build, test, lint, or other check failed
+  2  usage or configuration error
+  3  environment problem: a required tool is missing, git failed, or no supported build system was found
+  4  no targets were affected (only with --exit-empty)";
 
 #[derive(Parser)]
-#[command(name = "kit", about = "Universal build tool", version)]
+#[command(name = "kit", about = "Universal build tool", version, after_help = EXIT_CODES)]
 struct Cli {
     #[command(subcommand)]
     command: Cmd,
 
-    /// Base branch to diff against (default: main).
-    #[arg(long, default_value = "main", global = true)]
-    base: String,
+    /// Base branch to diff against (default: the parent branch of a stacked branch, or `base` in
+    /// kit.toml, or main).
+    #[arg(long, global = true)]
+    base: Option<String>,
+
+    /// Branches to diff against when --base is not set, as a comma-separated list of names or
+    /// patterns (e.g. main,release/*): the one whose merge base with HEAD is nearest wins.
+    #[arg(long, global = true, value_name = "BRANCHES", value_delimiter = ',')]
+    base_candidates: Vec<String>,
 
     /// Repository root (auto-detected if not set).
     #[arg(long, global = true)]
     repo: Option<PathBuf>,
+
+    /// Also report results as json[=PATH], junit[=PATH], github (annotations and step summary), or
+    /// webhook=URL. May be repeated.
+    #[arg(long, global = true, value_name = "REPORTER")]
+    report: Vec<String>,
+
+    /// Upload the run's results to a central results service at this URL, authenticating with the
+    /// token in KIT_UPLOAD_TOKEN if it is set.
+    #[arg(long, global = true, value_name = "URL")]
+    upload: Option<String>,
+
+    /// Run build, test, lint, fmt, and coverage for every detected backend concurrently, instead of
+    /// only the first.
+    #[arg(long, global = true)]
+    all_backends: bool,
+
+    /// Exit with code 4 when no targets (or files, or services) are affected by the branch.
+    #[arg(long, global = true)]
+    exit_empty: bool,
+
+    /// Diff against the last commit on this branch at which the same command passed on every
+    /// affected target, instead of the merge base with --base. Falls back to --base when there is none.
+    #[arg(long, global = true)]
+    since_last_green: bool,
+
+    /// Where changed files come from: git (default), hg, list=PATH (one path per line), webhook=PATH
+    /// (a CI or code-review webhook payload), or command=CMD (a command printing one path per line,
+    /// with the base in $KIT_BASE). PATH may be - for standard input.
+    #[arg(long, global = true, value_name = "PROVIDER")]
+    changes: Option<String>,
+
+    /// What build, test, lint, and coverage run when nothing changed on the branch: none, the
+    /// `unchanged.smoke` paths from kit.toml, or all targets. Overrides the `[unchanged]` settings.
+    #[arg(long, global = true, value_name = "SCOPE")]
+    when_unchanged: Option<UnchangedScope>,
+
+    /// Only operate on targets that contain tests.
+    #[arg(long, global = true)]
+    only_tests: bool,
+
+    /// Only operate on targets written in this language (e.g. go, python, typescript).
+    #[arg(long, global = true, value_name = "LANG")]
+    lang: Option<String>,
+}
+
+/// A path on the command line of `kit resolve` and the target it belongs to.
+#[derive(serde::Serialize)]
+struct Resolved<'a> {
+    path: PathBuf,
+    backend: &'a str,
+    #[serde(flatten)]
+    target: Target,
+}
+
+/// A target `kit list --json` prints, with the changed files in it.
+#[derive(serde::Serialize)]
+struct Listed<'a> {
+    backend: &'a str,
+    #[serde(flatten)]
+    target: Target,
+    /// Repo-relative; `.` at the root.
+    dir: PathBuf,
+    changed_files: Vec<PathBuf>,
+}
+
+/// Options shared by every command.
+struct Globals {
+    base: String,
+    changes: Box<dyn ChangeProvider>,
+    all_backends: bool,
+    exit_empty: bool,
+    when_unchanged: Option<UnchangedScope>,
+    filter: TargetFilter,
+    /// The current directory relative to the repo root, which paths on the command line are
+    /// relative to. `None` if it is outside the repo.
+    prefix: Option<PathBuf>,
+}
+
+impl Globals {
+    /// Fail with [`KitError::NothingAffected`] if `--exit-empty` is set and there is nothing to do.
+    fn check_empty(&self, empty: bool) -> Result<()> {
+        if self.exit_empty && empty {
+            return Err(KitError::NothingAffected.into());
+        }
+        Ok(())
+    }
+}
+
+/// Environment variables `--env` and `--env-file` add to every command a run spawns.
+#[derive(clap::Args)]
+struct EnvArgs {
+    /// Set an environment variable for every command kit runs, e.g. to toggle a feature flag
+    /// (repeatable).
+    #[arg(long = "env", value_name = "KEY=VALUE")]
+    vars: Vec<String>,
+    /// Read environment variables from a dotenv file of KEY=VALUE lines (repeatable). --env takes
+    /// precedence.
+    #[arg(long, value_name = "PATH")]
+    env_file: Vec<PathBuf>,
+}
+
+impl EnvArgs {
+    fn is_empty(&self) -> bool {
+        self.vars.is_empty() && self.env_file.is_empty()
+    }
+
+    /// The variables, files first and in order, so later settings override earlier ones.
+    fn resolve(&self) -> Result<Vec<(String, String)>> {
+        let mut vars = Vec::new();
+        for path in &self.env_file {
+            vars.extend(exec::read_env_file(path)?);
+        }
+        for var in &self.vars {
+            vars.push(exec::parse_env(var)?);
+        }
+        Ok(vars)
+    }
 }
 
 #[derive(Subcommand)]
@@ -29,141 +173,1878 @@ enum Cmd {
     Build {
         /// Directories to build. If empty, builds targets affected by changes on the current branch.
         dirs: Vec<PathBuf>,
+        /// Directory the build writes its release artifacts to.
+        #[arg(long)]
+        artifacts_dir: Option<PathBuf>,
+        /// After building, write SLSA provenance for the artifacts to <ARTIFACTS_DIR>/kit.intoto.jsonl.
+        #[arg(long, requires = "artifacts_dir")]
+        provenance: bool,
+        #[command(flatten)]
+        env: EnvArgs,
+    },
+    /// Type-check or compile changed targets (or specific directories) without producing artifacts,
+    /// for fast signal: `go build -o /dev/null` and `go vet`, `tsc --noEmit`, `bazel build --nobuild`.
+    Check {
+        /// Directories to check. If empty, checks targets affected by changes on the current branch.
+        dirs: Vec<PathBuf>,
+        #[command(flatten)]
+        env: EnvArgs,
     },
     /// Test changed targets (or specific directories).
     Test {
         /// Directories to test. If empty, tests targets affected by changes on the current branch.
         dirs: Vec<PathBuf>,
+        /// Run the tests N times and report how often each failing test failed, to find flaky tests.
+        #[arg(long, value_name = "N")]
+        stress: Option<usize>,
+        /// With --stress, how many runs to execute at the same time.
+        #[arg(long, value_name = "N", default_value_t = 1, requires = "stress")]
+        parallel: usize,
+        /// Rerun tests whose results the test runner cached (bazel --nocache_test_results, go test
+        /// -count=1, pants --test-force).
+        #[arg(long)]
+        no_cache_results: bool,
+        /// Run tests in random order, using SEED (random if not given) where the test runner
+        /// supports it. With --stress and no SEED, every run gets its own seed.
+        #[arg(long, value_name = "SEED", num_args = 0..=1, require_equals = true)]
+        shuffle: Option<Option<u64>>,
+        #[command(flatten)]
+        env: EnvArgs,
+    },
+    /// Test changed targets with coverage and report how many changed lines the tests cover.
+    Coverage {
+        /// Directories to test. If empty, tests targets affected by changes on the current branch.
+        dirs: Vec<PathBuf>,
+        /// Fail if less than this percentage of changed, instrumented lines is covered.
+        #[arg(long, value_name = "PERCENT")]
+        diff_threshold: Option<f64>,
+    },
+    /// List the failing tests of the last test or coverage run as file:line locations.
+    Failures {
+        /// Open each failure in $VISUAL or $EDITOR.
+        #[arg(long)]
+        open: bool,
     },
     /// Lint changed targets (or specific directories).
     Lint {
         /// Directories to lint. If empty, lints targets affected by changes on the current branch.
         dirs: Vec<PathBuf>,
+        /// Check commit messages on the branch against the `[lint.commits]` rules instead of linting targets.
+        #[arg(long)]
+        commits: bool,
+        /// Check that every changed file has a CODEOWNERS entry and no forbidden owner.
+        #[arg(long)]
+        owners: bool,
+        /// Check that dependencies imported by changed files are declared (package.json, Cargo.toml,
+        /// go.mod) and that generated BUILD files are up to date, without rewriting anything.
+        #[arg(long)]
+        build_metadata: bool,
+        /// Report targets with no dependents in the repo that are not binaries, tests, or published
+        /// packages, to find dead code.
+        #[arg(long)]
+        orphans: bool,
+        /// Check for dependency cycles among workspace packages and among proto files, which the
+        /// tools themselves tolerate.
+        #[arg(long)]
+        cycles: bool,
+        #[command(flatten)]
+        env: EnvArgs,
+    },
+    /// Build the documentation of changed targets (or specific directories): `go doc`, TypeDoc, the
+    /// Bazel targets under //docs that depend on them, or a custom backend's `docs`.
+    Docs {
+        /// Directories to document. If empty, documents targets affected by changes on the current
+        /// branch.
+        dirs: Vec<PathBuf>,
+        #[command(flatten)]
+        env: EnvArgs,
+    },
+    /// Regenerate code for changed targets (or specific directories): the `[[codegen]]` generators in
+    /// kit.toml, then `go generate`, `buf generate`, Gazelle, or a custom backend's `generate`.
+    Generate {
+        /// Directories to generate code for. If empty, generates for targets affected by changes on the
+        /// current branch.
+        dirs: Vec<PathBuf>,
+        /// Fail, listing them, if generating changed any files, so CI catches generated code that
+        /// wasn't committed.
+        #[arg(long)]
+        check: bool,
+        #[command(flatten)]
+        env: EnvArgs,
+    },
+    /// Tidy the dependencies of changed targets (or specific directories): `go mod tidy` in their
+    /// modules, `pnpm dedupe`, `npm dedupe`, or `yarn dedupe`, and `cargo update --workspace` when
+    /// Rust code changed.
+    Tidy {
+        /// Directories to tidy. If empty, tidies targets affected by changes on the current branch.
+        dirs: Vec<PathBuf>,
+        /// Fail, listing them, if tidying changed any files, so CI catches untidy dependencies.
+        #[arg(long)]
+        check: bool,
+    },
+    /// Apply the linters' automatic fixes to changed targets (or specific directories), such as
+    /// `golangci-lint run --fix` or `eslint --fix`. Unlike fmt, fixes may change what code does.
+    Fix {
+        /// Directories to fix. If empty, fixes targets affected by changes on the current branch.
+        dirs: Vec<PathBuf>,
+        #[command(flatten)]
+        env: EnvArgs,
     },
     /// Format changed files (or specific directories/files).
     Fmt {
         /// Files or directories to format. If empty, formats files changed on the current branch.
         dirs: Vec<PathBuf>,
+        /// Only check that the files are formatted, failing and listing those that aren't, without
+        /// rewriting them.
+        #[arg(long)]
+        check: bool,
+    },
+    /// Run one of the repo's own tasks (e.g. `e2e`, `deploy-preview`) on changed targets (or specific
+    /// directories): a Bazel target in their package, a Go `cmd/<task>` package, a package.json
+    /// script, a just recipe, an Earthfile target, or a `[[backends]]` task from kit.toml.
+    Run {
+        /// Task name.
+        task: String,
+        /// Directories to run the task for. If empty, runs it for targets affected by changes on the
+        /// current branch.
+        dirs: Vec<PathBuf>,
+        #[command(flatten)]
+        env: EnvArgs,
+    },
+    /// Run a shell command in the directory of each affected target, with `{dir}` (its absolute
+    /// path) and `{label}` replaced, e.g. `kit exec -- sed -i s/v1/v2/ {dir}/config.yaml`.
+    /// Directories shared by several targets or backends run it once.
+    Exec {
+        /// Directories to run it in. If empty, runs it for targets affected by changes on the
+        /// current branch.
+        dirs: Vec<PathBuf>,
+        /// The command, run with `sh -c`.
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+        #[command(flatten)]
+        env: EnvArgs,
+    },
+    /// Install dependencies for modules whose dependency files changed on the current branch.
+    Install {
+        /// Install dependencies for every module in the repository, not only changed ones.
+        #[arg(long)]
+        all: bool,
+    },
+    /// Write the dependency files and download commands of the modules with affected targets, for
+    /// Docker builds to download dependencies in a layer of their own.
+    Prefetch {
+        /// Directory to write the context to.
+        #[arg(long, value_name = "DIR")]
+        docker_context: PathBuf,
+    },
+    /// Install git hooks for this clone.
+    InstallHooks {
+        /// After commits and branch switches, compute affected targets in the background so caches
+        /// are warm for the next kit command.
+        #[arg(long)]
+        precompute: bool,
+    },
+    /// Install or remove git hooks that check formatting and lint before commits and pushes.
+    Hook {
+        #[command(subcommand)]
+        command: HookCmd,
+    },
+    /// Write a kit.toml with the settings kit detects in the repo: the base branch, and the backends
+    /// and tools it found, noted in comments.
+    Init {
+        /// Also install the git hooks that precompute affected targets (see install-hooks).
+        #[arg(long)]
+        hooks: bool,
+        /// Replace an existing kit.toml.
+        #[arg(long)]
+        force: bool,
     },
     /// Detect the build system(s) in the repository.
     Detect,
+    /// Check that the tools of every detected backend are installed and set up correctly.
+    Doctor {
+        /// Also run a quick end-to-end check per backend (e.g. `bazel info`, `go list -m`) and report
+        /// how long each took.
+        #[arg(long)]
+        deep: bool,
+    },
+    /// Print the label of the target each path belongs to, as kit's other commands would use it
+    /// (e.g. `./pkg/auth/...`, `//pkg/auth:all`). With --all-backends, lines are `<backend>\t<label>`.
+    Resolve {
+        /// Directories or files.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Print a JSON array with the path, backend, label, and target metadata for each.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print targets affected by changes on the current branch, across every detected backend.
+    Affected {
+        /// Print the number of affected targets per owner, backend, or top-level directory instead.
+        #[arg(long, value_enum)]
+        group_by: Option<affected::GroupBy>,
+    },
+    /// Run commands on the affected targets, then watch the repo and rerun them on the targets each
+    /// change affects, until interrupted.
+    Watch {
+        /// Commands to run in order, stopping at the first that fails (default: test).
+        #[arg(value_enum)]
+        commands: Vec<audit::AuditCommand>,
+        #[command(flatten)]
+        env: EnvArgs,
+    },
+    /// Run the CI pipeline on the affected targets: fmt (failing if it changed any files), lint,
+    /// build, and test by default, or the `[ci]` stages in kit.toml. Prints a summary of the stages
+    /// and exits with a single code.
+    Ci {
+        /// Stages to run, in order, instead of those in kit.toml (e.g. `lint,test`).
+        #[arg(long, value_enum, value_delimiter = ',')]
+        stages: Vec<CiStage>,
+        /// Run the remaining stages after one fails.
+        #[arg(long)]
+        keep_going: bool,
+        #[command(flatten)]
+        env: EnvArgs,
+    },
+    /// Print the targets a command would run on, without running anything. With --all-backends, lines
+    /// are `<backend>\t<label>`.
+    List {
+        #[arg(value_enum, default_value = "build")]
+        command: audit::AuditCommand,
+        /// Print a JSON array with the backend, label, directory, target metadata, and the changed
+        /// files in the target for each.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the dependency graph of the affected targets and everything they depend on, from `bazel
+    /// query`, `go list`, or the JS workspace packages.
+    Graph {
+        /// Print every package in the repo, with the affected ones marked.
+        #[arg(long)]
+        all: bool,
+        #[arg(long, value_enum, default_value = "dot")]
+        format: graph::Format,
+    },
+    /// Scan the dependencies of changed targets (or specific directories) for known vulnerabilities
+    /// with govulncheck, npm audit, pnpm audit, cargo audit, or OSV-Scanner, and fail if any are
+    /// found.
+    Audit {
+        /// Directories to scan. If empty, scans targets affected by changes on the current branch.
+        dirs: Vec<PathBuf>,
+        /// Print a JSON array with the backend, advisory, package, versions, and severity of each.
+        #[arg(long)]
+        json: bool,
+    },
+    /// List third-party dependencies with newer releases, from `go list -u -m`, `npm outdated`, `pnpm
+    /// outdated`, `pip list --outdated`, or the registry of MODULE.bazel's bazel_deps.
+    Outdated {
+        /// Print a JSON array with the backend, name, current and latest versions, and directory of
+        /// each.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Run a command on every target, one target at a time, and report the failures an affected-only
+    /// run of the branch would have missed. The comparison is appended to .kit/history/audit.jsonl.
+    AuditAffected {
+        #[arg(long, value_enum, default_value = "test")]
+        command: audit::AuditCommand,
+    },
+    /// Check the `[[gate]]` policies in kit.toml against the commands that passed at HEAD, and fail
+    /// with the reasons if any is not met.
+    Gate,
+    /// Create a package from a template: go, bazel (a Go package plus its BUILD file from gazelle),
+    /// npm, pnpm, yarn, or one of the repo's own templates.
+    New {
+        /// Template name.
+        template: String,
+        /// Directory of the new package.
+        path: PathBuf,
+    },
+    /// Find the commit that broke a target with `git bisect`, building and testing just that target
+    /// at each step.
+    Bisect {
+        /// Directory (or file) of the target to test, as for `kit test`.
+        #[arg(long)]
+        target: PathBuf,
+        /// A commit where the target passed.
+        #[arg(long)]
+        good: String,
+        /// A commit where it fails.
+        #[arg(long, default_value = "HEAD")]
+        bad: String,
+        /// Command to run instead of the target's tests once it is built, e.g. `-- ./scripts/check.sh`.
+        #[arg(last = true)]
+        command: Vec<String>,
+    },
+    /// One step of `kit bisect`, run by `git bisect run` at each commit it checks out.
+    #[command(hide = true)]
+    BisectStep {
+        target: PathBuf,
+        #[arg(last = true)]
+        command: Vec<String>,
+    },
+    /// Move a package directory and update the Go imports, Bazel labels, and TypeScript path aliases
+    /// that refer to it, then print the targets to re-test.
+    Mv {
+        /// Directory to move.
+        old: PathBuf,
+        /// Where to move it.
+        new: PathBuf,
+    },
+    /// Build container images for services affected by changes (or the named services).
+    Image {
+        /// Services (from kit.toml `[[services]]`) to build. If empty, builds affected services.
+        services: Vec<String>,
+        /// Image tag (default: the short commit hash of HEAD).
+        #[arg(long)]
+        tag: Option<String>,
+        /// Push images to their registry instead of loading them into the local docker daemon.
+        #[arg(long)]
+        push: bool,
+    },
+    /// Deploy services affected by changes (or the named services) to an environment.
+    Deploy {
+        /// Services (from kit.toml `[[services]]`) to deploy. If empty, deploys affected services.
+        services: Vec<String>,
+        /// Environment to deploy to; selects the deploy command template.
+        #[arg(long)]
+        env: String,
+        /// Value for the `{tag}` placeholder (default: the short commit hash of HEAD).
+        #[arg(long)]
+        tag: Option<String>,
+        /// Deploy without asking for confirmation.
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Inspect dependencies declared across the repository.
+    Deps {
+        #[command(subcommand)]
+        command: DepsCmd,
+    },
+    /// Release the packages affected by changes on the current branch (or under the named
+    /// directories) in one go: bump their versions and changelogs, commit that, then publish with
+    /// `npm publish`, `pnpm publish`, or `cargo publish` and tag, dependencies first. Go modules are
+    /// only tagged.
+    Publish {
+        /// Directories whose packages to publish. If empty, publishes those with changes on the
+        /// current branch.
+        dirs: Vec<PathBuf>,
+        /// Version bump for packages released before, instead of `release.bump` or one derived from
+        /// Conventional Commits.
+        #[arg(long, value_enum)]
+        bump: Option<release::Bump>,
+        /// Print the plan without changing, publishing, or tagging anything.
+        #[arg(long)]
+        dry_run: bool,
+        /// Publish without asking for confirmation.
+        #[arg(long)]
+        yes: bool,
+        /// Push the created tags to origin.
+        #[arg(long)]
+        push: bool,
+    },
+    /// Prepare and publish releases of packages changed since their last release.
+    Release {
+        #[command(subcommand)]
+        command: ReleaseCmd,
+    },
+    /// Read settings from kit.toml and the user config, or set them in the user config.
+    Config {
+        #[command(subcommand)]
+        command: ConfigCmd,
+    },
+    /// Show, clear, or garbage-collect kit's own state in .kit/: memoized queries, run history,
+    /// last-green markers, and the last run's logs.
+    Cache {
+        #[command(subcommand)]
+        command: CacheCmd,
+    },
+    /// Replace this kit with the latest release from the manifest at `update.url`, after verifying
+    /// its checksum and its minisign signature against `update.public_key`.
+    SelfUpdate {
+        /// Only report whether a newer release is available.
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+impl Cmd {
+    /// The `--env` and `--env-file` flags of the commands that take them.
+    fn env_args(&self) -> Option<&EnvArgs> {
+        match self {
+            Self::Build { env, .. }
+            | Self::Check { env, .. }
+            | Self::Test { env, .. }
+            | Self::Lint { env, .. }
+            | Self::Run { env, .. }
+            | Self::Exec { env, .. }
+            | Self::Watch { env, .. }
+            | Self::Ci { env, .. }
+            | Self::Fix { env, .. }
+            | Self::Generate { env, .. }
+            | Self::Docs { env, .. } => Some(env),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum ConfigCmd {
+    /// Print a setting's effective value (e.g. `budget.max_wall_time`); kit.toml takes precedence
+    /// over the user config.
+    Get { key: String },
+    /// Set a setting in the user config (`~/.config/kit/config.toml`), for personal preferences
+    /// that don't belong in the repo.
+    Set { key: String, value: String },
 }
 
-fn detect_backend<'a>(backends: &'a [Box<dyn Backend>], repo_root: &std::path::Path) -> Option<&'a dyn Backend> {
-    backends
+#[derive(Subcommand)]
+enum CacheCmd {
+    /// Print the size and file count of each directory of .kit/ and what it holds.
+    Status,
+    /// Delete the named directories of .kit/ (e.g. bazel-query, history), or all of them.
+    Clear { names: Vec<String> },
+    /// Delete memoized results older than --max-age, input hashes of generators no longer in
+    /// kit.toml, and last-green markers of deleted branches.
+    Gc {
+        /// Age past which memoized results are deleted, e.g. 24h.
+        #[arg(long, default_value = "168h", value_name = "DURATION")]
+        max_age: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum HookCmd {
+    /// Install pre-commit and pre-push hooks running `kit fmt --check` and `kit lint`, on the staged
+    /// files before a commit and on the branch's changes before a push. Honors `core.hooksPath`.
+    Install {
+        /// Only install the pre-commit hook.
+        #[arg(long)]
+        pre_commit: bool,
+        /// Only install the pre-push hook.
+        #[arg(long)]
+        pre_push: bool,
+    },
+    /// Remove kit's lines from the git hooks, including the precompute hooks, deleting hooks left
+    /// empty.
+    Uninstall,
+}
+
+#[derive(Subcommand)]
+enum DepsCmd {
+    /// Report dependencies required at different versions by different go.mod, package.json, or
+    /// Cargo.toml files.
+    Doctor,
+}
+
+#[derive(Subcommand)]
+enum ReleaseCmd {
+    /// Bump versions of changed Go modules, npm packages, and crates, and draft changelog sections.
+    Prepare {
+        /// Print the plan and changelog draft without modifying any files.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Publish and tag packages whose current version has not been released, in dependency order.
+    Publish {
+        /// Print what would be published and tagged without doing it.
+        #[arg(long)]
+        dry_run: bool,
+        /// Push the created tags to origin.
+        #[arg(long)]
+        push: bool,
+    },
+}
+
+/// All backends that own the repository root, in priority order.
+fn detect_backends<'a>(backends: &'a [Box<dyn Backend>], repo_root: &Path) -> Result<Vec<&'a dyn Backend>> {
+    let detected: Vec<&dyn Backend> = backends
         .iter()
-        .find_map(|b| if b.detect(repo_root) { Some(b.as_ref()) } else { None })
+        .filter(|b| b.detect(repo_root))
+        .map(|b| b.as_ref())
+        .collect();
+    if detected.is_empty() {
+        let supported: Vec<&str> = backends.iter().map(|b| b.name()).collect();
+        return Err(KitError::DetectionError(format!(
+            "kit does not support the build system in {}. \
+             kit cannot be used to build, test, lint, or format this project.\n\
+             Supported backends: {}",
+            repo_root.display(),
+            supported.join(", "),
+        ))
+        .into());
+    }
+    Ok(detected)
+}
+
+/// Every detected backend with `--all-backends`, otherwise only the first.
+fn select_backends<'a>(backends: &'a [Box<dyn Backend>], repo_root: &Path, all: bool) -> Result<Vec<&'a dyn Backend>> {
+    if !all {
+        return Ok(vec![detect_backend(backends, repo_root)?]);
+    }
+    let detected = detect_backends(backends, repo_root)?;
+    let names: Vec<&str> = detected.iter().map(|b| b.name()).collect();
+    eprintln!("kit: detected {} backend(s)", names.join(", "));
+    Ok(detected)
 }
 
-fn main() -> Result<()> {
+fn detect_backend<'a>(backends: &'a [Box<dyn Backend>], repo_root: &Path) -> Result<&'a dyn Backend> {
+    let backend = detect_backends(backends, repo_root)?[0];
+    eprintln!("kit: detected {} backend", backend.name());
+    Ok(backend)
+}
+
+fn main() -> ExitCode {
+    match try_main() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e:?}");
+            ExitCode::from(error::exit_code(&e))
+        }
+    }
+}
+
+fn try_main() -> Result<()> {
     let cli = Cli::parse();
-    let repo_root = match cli.repo {
-        Some(p) => p
-            .canonicalize()
-            .with_context(|| format!("could not canonicalize repo root: {}", p.display()))?,
+    // The user config is not tied to a repo, so it can be set from anywhere.
+    if let Cmd::Config {
+        command: ConfigCmd::Set { key, value },
+    } = &cli.command
+    {
+        let path = config::set_user(key, value)?;
+        eprintln!("kit: set {key} in {}", path.display());
+        return Ok(());
+    }
+    // Updating kit doesn't need a repo either, and only trusts the user's settings, never a repo's.
+    if let Cmd::SelfUpdate { check } = &cli.command {
+        return self_update::run(&Config::load_user()?.update, *check);
+    }
+    let (repo_root, prefix) = match cli.repo {
+        Some(p) => {
+            let root = p
+                .canonicalize()
+                .map_err(|e| KitError::ConfigError(format!("could not canonicalize repo root {}: {e}", p.display())))?;
+            let cwd = env::current_dir().context("failed to get current directory")?;
+            let prefix = [Some(cwd.clone()), cwd.canonicalize().ok()]
+                .into_iter()
+                .flatten()
+                .find_map(|dir| dir.strip_prefix(&root).ok().map(Path::to_path_buf));
+            (root, prefix)
+        }
         None => {
-            let root = git::repo_root().context("could not detect repo root")?;
-            root.canonicalize()
-                .with_context(|| format!("could not canonicalize repo root: {}", root.display()))?
+            let (root, prefix) = git::repo_root().context("could not detect repo root")?;
+            let root = root
+                .canonicalize()
+                .with_context(|| format!("could not canonicalize repo root: {}", root.display()))?;
+            (root, Some(prefix))
         }
     };
-    let backends = all_backends();
-
-    let backend = match detect_backend(&backends, &repo_root) {
-        Some(b) => b,
-        None => {
-            let supported: Vec<&str> = backends.iter().map(|b| b.name()).collect();
-            anyhow::bail!(
-                "kit does not support the build system in {}. \
-                 kit cannot be used to build, test, lint, or format this project.\n\
-                 Supported backends: {}",
-                repo_root.display(),
-                supported.join(", "),
-            );
+    let config = Config::load(&repo_root)?;
+    let mut reporters: Vec<Box<dyn Reporter>> = vec![Box::new(ConsoleReporter)];
+    for spec in config.report.iter().chain(&cli.report) {
+        reporters.push(report::from_spec(spec)?);
+    }
+    let budget = Budget {
+        max_wall_time: config.budget.max_wall_time()?,
+        max_concurrent_heavy: config.budget.max_concurrent_heavy()?,
+    };
+    let mut runner = Runner::new(reporters, repo_root.clone(), all_backends(&config.backends))?
+        .with_budget(budget)
+        .with_jobs(config.jobs()?)
+        .with_missing_tools(config.tools.missing)
+        .with_target_budgets(timing::TargetBudgets::new(
+            config.budget.target_budgets()?,
+            config.budget.fail_over_budget,
+        ));
+    if let Some(url) = cli.upload {
+        let run = upload::Run {
+            repo: git::config_value(&repo_root, "remote.origin.url")?,
+            branch: git::current_branch(&repo_root)?,
+            commit: git::head(&repo_root)?,
+            environment: runner.environment().clone(),
+        };
+        let token = env::var(upload::TOKEN_VAR).ok().filter(|t| !t.is_empty());
+        let body_path = state::dir(&repo_root, "upload")?.join("body.json");
+        runner.add_reporter(Box::new(upload::UploadReporter::new(url, token, run, body_path)));
+    }
+    let green = green_command(&cli.command);
+    let default_base = config.base.clone().unwrap_or_else(|| "main".to_string());
+    let mut base = match cli.base {
+        Some(base) => base,
+        None => match stack::parent_branch(&repo_root)? {
+            Some((parent, source)) if parent != default_base => {
+                eprintln!(
+                    "kit: diffing against {parent}, the parent branch in the stack ({})",
+                    source.name()
+                );
+                parent
+            }
+            _ if cli.base_candidates.is_empty() => default_base,
+            _ => match base::nearest(&repo_root, &cli.base_candidates)? {
+                Some(nearest) => {
+                    eprintln!(
+                        "kit: diffing against {}, the nearest base candidate ({} commit(s) since the merge base)",
+                        nearest.branch, nearest.distance
+                    );
+                    nearest.branch
+                }
+                None => {
+                    eprintln!("kit: no branch matches --base-candidates, diffing against {default_base}");
+                    default_base
+                }
+            },
+        },
+    };
+    if cli.since_last_green {
+        let environment = &runner.environment().fingerprint;
+        match history::last_green(&repo_root, green, environment)? {
+            Some(commit) => {
+                eprintln!("kit: diffing against {commit}, the last green run on this branch");
+                base = commit;
+            }
+            None => eprintln!("kit: no green run recorded on this branch with this toolchain, diffing against {base}"),
         }
+    }
+    let filtered = cli.only_tests || cli.lang.is_some();
+    let globals = Globals {
+        base,
+        changes: changes::from_spec(cli.changes.as_deref().unwrap_or("git"))?,
+        all_backends: cli.all_backends,
+        exit_empty: cli.exit_empty,
+        when_unchanged: cli.when_unchanged,
+        filter: TargetFilter {
+            only_tests: cli.only_tests,
+            language: cli.lang.map(|l| l.to_lowercase()),
+        },
+        prefix,
     };
+    let result = run(cli.command, &globals, &repo_root, &mut runner);
+    if let Some(command) = green.filter(|_| result.is_ok() && !filtered) {
+        let environment = runner.environment().fingerprint.clone();
+        if let Err(e) = history::record_green(&repo_root, command, &environment) {
+            eprintln!("kit: warning: failed to record the green run: {e:#}");
+        }
+    }
+    runner.finish();
+    result
+}
 
-    eprintln!("kit: detected {} backend", backend.name());
+/// The name a command's passing runs are recorded under for `--since-last-green` and `kit gate`:
+/// build, check, test, lint, coverage, and fmt of the affected targets, and ci with the stages in
+/// kit.toml. Runs on named directories don't vouch for the whole branch, nor do runs with `--env` or `--env-file`, whose variables may change
+/// what passes. fmt only counts when it left the tree clean.
+fn green_command(command: &Cmd) -> Option<&'static str> {
+    if command.env_args().is_some_and(|env| !env.is_empty()) {
+        return None;
+    }
+    match command {
+        Cmd::Build { dirs, .. } if dirs.is_empty() => Some("build"),
+        Cmd::Check { dirs, .. } if dirs.is_empty() => Some("check"),
+        Cmd::Test { dirs, stress: None, .. } if dirs.is_empty() => Some("test"),
+        Cmd::Coverage { dirs, .. } if dirs.is_empty() => Some("coverage"),
+        Cmd::Lint {
+            dirs,
+            commits: false,
+            owners: false,
+            build_metadata: false,
+            orphans: false,
+            cycles: false,
+            ..
+        } if dirs.is_empty() => Some("lint"),
+        Cmd::Fmt { dirs, .. } if dirs.is_empty() => Some("fmt"),
+        Cmd::Ci { stages, .. } if stages.is_empty() => Some("ci"),
+        _ => None,
+    }
+}
 
-    match cli.command {
-        Cmd::Build { dirs } => {
-            let targets = resolve_targets(backend, &repo_root, &cli.base, dirs)?;
-            eprintln!("kit: building {} target(s)", targets.len());
-            backend.build(&repo_root, &targets)
+fn run(command: Cmd, globals: &Globals, repo_root: &Path, runner: &mut Runner) -> Result<()> {
+    let base = globals.base.as_str();
+    let backends = all_backends(&Config::load(repo_root)?.backends);
+
+    if let Some(env) = command.env_args() {
+        runner.add_env(env.resolve()?);
+    }
+    match command {
+        Cmd::Build {
+            dirs,
+            artifacts_dir,
+            provenance,
+            ..
+        } => {
+            let selected = select_backends(&backends, repo_root, globals.all_backends)?;
+            let dirs = unchanged_dirs(repo_root, globals, "build", dirs)?;
+            let targets = resolve_targets(&selected, repo_root, globals, &dirs)?;
+            globals.check_empty(targets.iter().all(|(_, t)| t.is_empty()))?;
+            generate(runner, repo_root, globals, &dirs)?;
+            execute(runner, "build", &targets, |b, t| b.build(repo_root, t))?;
+            if let Some(dir) = artifacts_dir.filter(|_| provenance) {
+                let dir = env::current_dir().context("failed to get current directory")?.join(dir);
+                let names: Vec<&str> = targets.iter().map(|(b, _)| b.name()).collect();
+                let built: Vec<Target> = targets.into_iter().flat_map(|(_, t)| t).collect();
+                let path = provenance::write(repo_root, &dir, &names.join(","), &built)?;
+                eprintln!("kit: wrote provenance to {}", path.display());
+            }
+            Ok(())
+        }
+        Cmd::Check { dirs, .. } => {
+            let selected = select_backends(&backends, repo_root, globals.all_backends)?;
+            let targets = resolve_targets(&selected, repo_root, globals, &dirs)?;
+            globals.check_empty(targets.iter().all(|(_, t)| t.is_empty()))?;
+            generate(runner, repo_root, globals, &dirs)?;
+            execute(runner, "check", &targets, |b, t| b.check(repo_root, t))
+        }
+        Cmd::Test {
+            dirs,
+            stress,
+            parallel,
+            shuffle,
+            no_cache_results,
+            ..
+        } => {
+            let selected = select_backends(&backends, repo_root, globals.all_backends)?;
+            let dirs = unchanged_dirs(repo_root, globals, "test", dirs)?;
+            let targets = resolve_targets(&selected, repo_root, globals, &dirs)?;
+            globals.check_empty(targets.iter().all(|(_, t)| t.is_empty()))?;
+            generate(runner, repo_root, globals, &dirs)?;
+            let matrices = Config::load(repo_root)?.matrix;
+            let mut steps = matrix_steps("test", &targets, &matrices, |b, t| b.test(repo_root, t))?;
+            if no_cache_results {
+                steps = uncached(&selected, steps);
+            }
+            let plan = || match shuffle {
+                Some(seed) => shuffled(&selected, steps.clone(), seed.unwrap_or_else(random_seed)),
+                None => steps.clone(),
+            };
+            match stress {
+                Some(0) => Err(KitError::ConfigError("--stress needs at least one run".to_string()).into()),
+                Some(runs) => stress::run(runner, repo_root, &backends, plan, runs, parallel),
+                None => runner.execute(plan()),
+            }
+        }
+        Cmd::Coverage { dirs, diff_threshold } => {
+            let selected = select_backends(&backends, repo_root, globals.all_backends)?;
+            let dirs = unchanged_dirs(repo_root, globals, "coverage", dirs)?;
+            let targets = resolve_targets(&selected, repo_root, globals, &dirs)?;
+            globals.check_empty(targets.iter().all(|(_, t)| t.is_empty()))?;
+            generate(runner, repo_root, globals, &dirs)?;
+            let out_dir = state::dir(repo_root, "coverage")?;
+            let since = state::fs_now(&out_dir)?;
+            execute(runner, "coverage", &targets, |b, t| b.coverage(repo_root, t, &out_dir))?;
+            let mut cov = coverage::Coverage::default();
+            for (backend, _) in &targets {
+                cov.merge(backend.read_coverage(repo_root, &out_dir, since)?);
+            }
+            let report = cov.write_reports(repo_root, &out_dir)?;
+            eprintln!("kit: wrote coverage report to {}", report.display());
+            let covered: Vec<Target> = targets.iter().flat_map(|(_, t)| t.iter().cloned()).collect();
+            let per_target = coverage::by_target(&cov, repo_root, &covered);
+            let baseline = match git::merge_base(repo_root, base) {
+                Ok(commit) => coverage::baseline(repo_root, &commit)?,
+                Err(_) => Default::default(),
+            };
+            coverage::report_targets(&per_target, &baseline, base);
+            coverage::record(repo_root, &per_target)?;
+            let ignore = KitIgnore::load(repo_root)?;
+            let mut changed = git::changed_lines(repo_root, base)?;
+            changed.retain(|file, _| !ignore.is_ignored(repo_root, file));
+            match (
+                coverage::report(&coverage::diff_coverage(&cov, &changed)),
+                diff_threshold,
+            ) {
+                (None, _) => eprintln!("kit: no changed lines are instrumented for coverage"),
+                (Some(pct), Some(threshold)) if pct < threshold => {
+                    anyhow::bail!("diff coverage {pct:.1}% is below the threshold of {threshold}%")
+                }
+                _ => {}
+            }
+            Ok(())
+        }
+        Cmd::Lint {
+            commits,
+            owners,
+            build_metadata,
+            orphans,
+            cycles,
+            ..
+        } if commits || owners || build_metadata || orphans || cycles => {
+            let config = Config::load(repo_root)?;
+            let mut results = Vec::new();
+            if commits {
+                results.push(commits::lint(repo_root, base, &config.lint.commits));
+            }
+            if owners {
+                results.push(
+                    changed_files(repo_root, globals)
+                        .and_then(|changed| codeowners::lint(repo_root, &changed, &config.lint.owners)),
+                );
+            }
+            if build_metadata {
+                results.push(changed_files(repo_root, globals).and_then(|changed| depcheck::lint(repo_root, &changed)));
+            }
+            if orphans {
+                results.push(
+                    select_backends(&backends, repo_root, globals.all_backends)
+                        .and_then(|selected| orphans::lint(repo_root, &selected)),
+                );
+            }
+            if cycles {
+                results.push(cycles::lint(repo_root));
+            }
+            combine_results(results)
+        }
+        Cmd::Lint { dirs, .. } => {
+            let selected = select_backends(&backends, repo_root, globals.all_backends)?;
+            let dirs = unchanged_dirs(repo_root, globals, "lint", dirs)?;
+            let targets = resolve_targets(&selected, repo_root, globals, &dirs)?;
+            globals.check_empty(targets.iter().all(|(_, t)| t.is_empty()))?;
+            execute(runner, "lint", &targets, |b, t| b.lint(repo_root, t))
+        }
+        Cmd::Docs { dirs, .. } => {
+            let selected = select_backends(&backends, repo_root, globals.all_backends)?;
+            let dirs = unchanged_dirs(repo_root, globals, "docs", dirs)?;
+            let targets = resolve_targets(&selected, repo_root, globals, &dirs)?;
+            globals.check_empty(targets.iter().all(|(_, t)| t.is_empty()))?;
+            generate(runner, repo_root, globals, &dirs)?;
+            execute(runner, "docs", &targets, |b, t| b.docs(repo_root, t))
         }
-        Cmd::Test { dirs } => {
-            let targets = resolve_targets(backend, &repo_root, &cli.base, dirs)?;
-            eprintln!("kit: testing {} target(s)", targets.len());
-            backend.test(&repo_root, &targets)
+        Cmd::Generate { dirs, check, .. } => {
+            let selected = select_backends(&backends, repo_root, globals.all_backends)?;
+            let dirs = unchanged_dirs(repo_root, globals, "generate", dirs)?;
+            let targets = resolve_targets(&selected, repo_root, globals, &dirs)?;
+            let changed = files_changed_by(repo_root, check, || {
+                generate(runner, repo_root, globals, &dirs)?;
+                if targets.iter().all(|(_, t)| t.is_empty()) {
+                    return Ok(());
+                }
+                execute(runner, "generate", &targets, |b, t| b.generate(repo_root, t))
+            })?;
+            if !changed.is_empty() {
+                let names: Vec<String> = changed.iter().map(|f| f.display().to_string()).collect();
+                anyhow::bail!("generating code changed {} files: {}", names.len(), names.join(", "));
+            }
+            Ok(())
         }
-        Cmd::Lint { dirs } => {
-            let targets = resolve_targets(backend, &repo_root, &cli.base, dirs)?;
-            eprintln!("kit: linting {} target(s)", targets.len());
-            backend.lint(&repo_root, &targets)
+        Cmd::Tidy { dirs, check } => {
+            let selected = select_backends(&backends, repo_root, globals.all_backends)?;
+            let targets = resolve_targets(&selected, repo_root, globals, &dirs)?;
+            let paths = if dirs.is_empty() {
+                changed_files(repo_root, globals)?
+            } else {
+                resolve_file_args(repo_root, globals, dirs)?
+            };
+            // Cargo isn't a backend, so its lock file is tidied on its own when Rust code changed.
+            let cargo = repo_root.join("Cargo.lock").is_file() && security::touches_rust(repo_root, &paths);
+            globals.check_empty(!cargo && targets.iter().all(|(_, t)| t.is_empty()))?;
+            let changed = files_changed_by(repo_root, check, || {
+                let mut steps = plan_steps("tidy", &targets, |b, t| b.tidy(repo_root, t))?;
+                if cargo {
+                    let step = Step {
+                        command: "tidy".to_string(),
+                        backend: "cargo".to_string(),
+                        targets: vec![Target {
+                            label: ".".to_string(),
+                            dir: repo_root.to_path_buf(),
+                            ..Default::default()
+                        }],
+                        seed: None,
+                        variant: None,
+                        skip: None,
+                    };
+                    let plan = vec![Invocation::new("cargo", repo_root).args(["update", "--workspace"])];
+                    steps.push((step, plan));
+                }
+                runner.execute(steps)
+            })?;
+            if !changed.is_empty() {
+                let names: Vec<String> = changed.iter().map(|f| f.display().to_string()).collect();
+                anyhow::bail!(
+                    "tidying dependencies changed {} files: {}",
+                    names.len(),
+                    names.join(", ")
+                );
+            }
+            Ok(())
         }
-        Cmd::Fmt { dirs } => {
+        Cmd::Fix { dirs, .. } => {
+            let selected = select_backends(&backends, repo_root, globals.all_backends)?;
+            let targets = resolve_targets(&selected, repo_root, globals, &dirs)?;
+            globals.check_empty(targets.iter().all(|(_, t)| t.is_empty()))?;
+            execute(runner, "fix", &targets, |b, t| b.fix(repo_root, t))
+        }
+        Cmd::Fmt { dirs, check } => {
+            let selected = select_backends(&backends, repo_root, globals.all_backends)?;
             let files = if dirs.is_empty() {
-                git::changed_files(&repo_root, &cli.base)?
+                changed_files(repo_root, globals)?
             } else {
-                resolve_file_args(&repo_root, dirs)?
+                let files = resolve_file_args(repo_root, globals, dirs)?;
+                KitIgnore::load(repo_root)?.filter(repo_root, files)
             };
-            eprintln!("kit: formatting {} file(s)", files.len());
-            backend.fmt(&repo_root, &files)
+            globals.check_empty(files.is_empty())?;
+            runner.execute(fmt_steps(&selected, repo_root, &files, check)?)
+        }
+        Cmd::Ci { stages, keep_going, .. } => {
+            let config = Config::load(repo_root)?.ci;
+            let stages = if stages.is_empty() { config.stages } else { stages };
+            let keep_going = keep_going || config.keep_going;
+            let selected = select_backends(&backends, repo_root, globals.all_backends)?;
+            let mut results = Vec::new();
+            let mut failures = Vec::new();
+            for stage in stages {
+                if !failures.is_empty() && !keep_going {
+                    results.push((stage, ci::StageResult::NotRun));
+                    continue;
+                }
+                eprintln!("kit: ci: {}", stage.name());
+                let started = Instant::now();
+                match ci_stage(runner, repo_root, globals, &selected, stage) {
+                    Ok(()) => results.push((stage, ci::StageResult::Passed(started.elapsed()))),
+                    Err(e) if matches!(KitError::find(&e), Some(KitError::Interrupted)) => return Err(e),
+                    Err(e) => {
+                        results.push((stage, ci::StageResult::Failed(started.elapsed())));
+                        failures.push(Err(e));
+                    }
+                }
+            }
+            for line in ci::summary(&results) {
+                eprintln!("kit: ci: {line}");
+            }
+            combine_results(failures)
+        }
+        Cmd::Run { task, dirs, .. } => {
+            let selected = select_backends(&backends, repo_root, globals.all_backends)?;
+            let targets = resolve_targets(&selected, repo_root, globals, &dirs)?;
+            globals.check_empty(targets.iter().all(|(_, t)| t.is_empty()))?;
+            execute(runner, &format!("run {task}"), &targets, |b, t| {
+                b.task(repo_root, &task, t)
+            })
+        }
+        Cmd::Exec { dirs, command, .. } => {
+            let selected = select_backends(&backends, repo_root, globals.all_backends)?;
+            let targets = resolve_targets(&selected, repo_root, globals, &dirs)?;
+            globals.check_empty(targets.iter().all(|(_, t)| t.is_empty()))?;
+            let template = command.join(" ");
+            let mut seen = std::collections::BTreeSet::new();
+            let mut steps = Vec::new();
+            for (backend, targets) in &targets {
+                let targets: Vec<Target> = targets.iter().filter(|t| seen.insert(t.dir.clone())).cloned().collect();
+                if targets.is_empty() {
+                    continue;
+                }
+                let plan = targets
+                    .iter()
+                    .map(|t| Invocation::new("sh", &t.dir).args(["-c", &exec::render(&template, t)]))
+                    .collect();
+                let step = Step {
+                    command: "exec".to_string(),
+                    backend: backend.name().to_string(),
+                    targets,
+                    seed: None,
+                    variant: None,
+                    skip: None,
+                };
+                steps.push((step, plan));
+            }
+            runner.execute(steps)
+        }
+        Cmd::Install { all } => {
+            let changed = if all {
+                Vec::new()
+            } else {
+                changed_files(repo_root, globals)?
+            };
+            let plan = install::plan(repo_root, &changed, all)?;
+            globals.check_empty(plan.is_empty())?;
+            let mut steps = Vec::new();
+            for (installer, dirs) in plan {
+                let step = Step {
+                    command: "install".to_string(),
+                    backend: installer.name.to_string(),
+                    targets: dirs
+                        .iter()
+                        .map(|d| Target {
+                            label: if d.as_os_str().is_empty() {
+                                ".".to_string()
+                            } else {
+                                format!("./{}", d.display())
+                            },
+                            dir: repo_root.join(d),
+                            ..Default::default()
+                        })
+                        .collect(),
+                    seed: None,
+                    variant: None,
+                    skip: None,
+                };
+                steps.push((step, install::invocations(repo_root, installer, &dirs)));
+            }
+            runner.execute(steps)
+        }
+        Cmd::Prefetch { docker_context } => {
+            let detected = detect_backends(&backends, repo_root)?;
+            let changed = changed_files(repo_root, globals)?;
+            let target_dirs: Vec<PathBuf> = affected::collect(&detected, repo_root, base, &changed)
+                .into_iter()
+                .map(|(_, t)| t.dir.strip_prefix(repo_root).unwrap_or(&t.dir).to_path_buf())
+                .collect();
+            let modules = prefetch::modules(repo_root, &target_dirs)?;
+            globals.check_empty(modules.is_empty())?;
+            let out = env::current_dir()
+                .context("failed to get current directory")?
+                .join(docker_context);
+            prefetch::write(repo_root, &out, &modules)?;
+            eprintln!("kit: wrote {} module(s) to {}", modules.len(), out.display());
+            Ok(())
+        }
+        Cmd::InstallHooks { precompute } => hooks::install(repo_root, base, precompute),
+        Cmd::Hook {
+            command: HookCmd::Install { pre_commit, pre_push },
+        } => hooks::install_checks(repo_root, pre_commit, pre_push),
+        Cmd::Hook {
+            command: HookCmd::Uninstall,
+        } => hooks::uninstall(repo_root),
+        Cmd::Failures { open } => failures::run(repo_root, &backends, open),
+        Cmd::Init {
+            hooks: install_hooks,
+            force,
+        } => {
+            let all: Vec<&dyn Backend> = backends.iter().map(|b| b.as_ref()).collect();
+            let (path, base) = init::run(repo_root, &all, force)?;
+            eprintln!("kit: wrote {} (base branch {base})", path.display());
+            if install_hooks {
+                hooks::install(repo_root, &base, true)?;
+            }
+            Ok(())
         }
         Cmd::Detect => {
+            let backend = detect_backend(&backends, repo_root)?;
             println!("{}", backend.name());
             Ok(())
         }
+        Cmd::Doctor { deep } => {
+            let detected = detect_backends(&backends, repo_root)?;
+            doctor::run(repo_root, &detected, deep)
+        }
+        Cmd::Image { services, tag, push } => {
+            let config = Config::load(repo_root)?;
+            let selected = services::select(&config.services, &services, || changed_files(repo_root, globals))?;
+            globals.check_empty(selected.is_empty())?;
+            let tag = match tag {
+                Some(t) => t,
+                None => git::short_head(repo_root)?,
+            };
+            eprintln!("kit: building {} image(s)", selected.len());
+            image::build(repo_root, &selected, &config.image, &tag, push)
+        }
+        Cmd::Deploy {
+            services,
+            env,
+            tag,
+            yes,
+        } => {
+            let config = Config::load(repo_root)?;
+            let selected = services::select(&config.services, &services, || changed_files(repo_root, globals))?;
+            globals.check_empty(selected.is_empty())?;
+            let tag = match tag {
+                Some(t) => t,
+                None => git::short_head(repo_root)?,
+            };
+            deploy::deploy(repo_root, &selected, &config.deploy, &env, &tag, yes)
+        }
+        Cmd::New { template, path } => {
+            let config = Config::load(repo_root)?;
+            let path = resolve_file_args(repo_root, globals, vec![path])?.remove(0);
+            scaffold::new(repo_root, &config.new, &template, &path)
+        }
+        Cmd::Mv { old, new } => {
+            let mut paths = resolve_file_args(repo_root, globals, vec![old, new])?;
+            let new = paths.pop().unwrap();
+            let old = paths.pop().unwrap();
+            let touched = relocate::relocate(repo_root, &old, &new)?;
+            let detected = detect_backends(&backends, repo_root)?;
+            let targets = affected::collect(&detected, repo_root, base, &touched);
+            eprintln!(
+                "kit: {} target(s) affected by the move; run kit test to re-test them",
+                targets.len()
+            );
+            for (backend, target) in &targets {
+                println!("{backend}\t{}", target.label);
+            }
+            Ok(())
+        }
+        Cmd::Bisect {
+            target,
+            good,
+            bad,
+            command,
+        } => {
+            let target = resolve_file_args(repo_root, globals, vec![target])?.remove(0);
+            bisect::run(repo_root, &target, &good, &bad, &command)
+        }
+        Cmd::BisectStep { target, command } => {
+            let verdict = match detect_backend(&backends, repo_root) {
+                Ok(backend) => {
+                    let targets = resolve_targets(&[backend], repo_root, globals, &[target])?;
+                    let built = execute(runner, "build", &targets, |b, t| b.build(repo_root, t));
+                    let tested = match &built {
+                        Err(_) => Ok(()),
+                        Ok(()) if command.is_empty() => execute(runner, "test", &targets, |b, t| b.test(repo_root, t)),
+                        Ok(()) => bisect::run_command(repo_root, &command),
+                    };
+                    bisect::verdict(&built, &tested)
+                }
+                Err(e) => {
+                    eprintln!("kit: {e:#}, skipping this commit");
+                    bisect::SKIP
+                }
+            };
+            std::process::exit(verdict.into())
+        }
+        Cmd::Gate => {
+            let config = Config::load(repo_root)?;
+            let detected = detect_backends(&backends, repo_root)?;
+            let changed = changed_files(repo_root, globals)?;
+            let affected_dirs: Vec<PathBuf> = affected::collect(&detected, repo_root, base, &changed)
+                .into_iter()
+                .map(|(_, t)| t.dir.strip_prefix(repo_root).unwrap_or(&t.dir).to_path_buf())
+                .collect();
+            gate::run(repo_root, &config.gate, &affected_dirs)
+        }
+        Cmd::Resolve { paths, json } => {
+            let selected = select_backends(&backends, repo_root, globals.all_backends)?;
+            let mut resolved = Vec::new();
+            for backend in &selected {
+                for path in &paths {
+                    let (mut target, file) = resolve_path(*backend, repo_root, globals, path)?;
+                    target.files.extend(file);
+                    resolved.push(Resolved {
+                        path: path.clone(),
+                        backend: backend.name(),
+                        target,
+                    });
+                }
+            }
+            if json {
+                println!("{}", serde_json::to_string_pretty(&resolved)?);
+            } else {
+                for r in &resolved {
+                    if globals.all_backends {
+                        println!("{}\t{}", r.backend, r.target.label);
+                    } else {
+                        println!("{}", r.target.label);
+                    }
+                }
+            }
+            Ok(())
+        }
+        Cmd::Watch { mut commands, .. } => {
+            if commands.is_empty() {
+                commands.push(audit::AuditCommand::Test);
+            }
+            let selected = select_backends(&backends, repo_root, globals.all_backends)?;
+            let mut watcher = watch::Watcher::new(repo_root)?;
+            // The first run covers the branch, later ones only what changed since.
+            let mut targets = resolve_targets(&selected, repo_root, globals, &[])?;
+            loop {
+                if targets.iter().all(|(_, t)| t.is_empty()) {
+                    eprintln!("kit: no targets affected");
+                } else if let Err(e) = watch_run(runner, repo_root, globals, &commands, &targets) {
+                    if matches!(KitError::find(&e), Some(KitError::Interrupted)) {
+                        return Err(e);
+                    }
+                    eprintln!("kit: {e:#}");
+                }
+                eprintln!("kit: watching for changes (Ctrl-C to stop)");
+                let Some(changed) = runner.block_on(watcher.next())? else {
+                    return Ok(());
+                };
+                eprintln!("kit: {} changed files", changed.len());
+                let changed = codegen::with_outputs(repo_root, &Config::load(repo_root)?.codegen, changed)?;
+                targets = selected
+                    .iter()
+                    .map(|b| {
+                        let mut affected = b.affected_targets(repo_root, base, &changed);
+                        affected.retain(|t| globals.filter.matches(t));
+                        (*b, affected)
+                    })
+                    .collect();
+            }
+        }
+        Cmd::List { command, json } => {
+            let selected = select_backends(&backends, repo_root, globals.all_backends)?;
+            let dirs = unchanged_dirs(repo_root, globals, command.name(), vec![])?;
+            let targets = resolve_targets(&selected, repo_root, globals, &dirs)?;
+            globals.check_empty(targets.iter().all(|(_, t)| t.is_empty()))?;
+            if json {
+                let changed = changed_files(repo_root, globals)?;
+                let changed = codegen::with_outputs(repo_root, &Config::load(repo_root)?.codegen, changed)?;
+                let mut listed = Vec::new();
+                for (backend, targets) in targets {
+                    for target in targets {
+                        let dir = target.dir.strip_prefix(repo_root).unwrap_or(&target.dir);
+                        listed.push(Listed {
+                            backend: backend.name(),
+                            dir: if dir.as_os_str().is_empty() {
+                                PathBuf::from(".")
+                            } else {
+                                dir.to_path_buf()
+                            },
+                            changed_files: affected::triggers(repo_root, &target, &changed),
+                            target,
+                        });
+                    }
+                }
+                println!("{}", serde_json::to_string_pretty(&listed)?);
+            } else {
+                for (backend, targets) in &targets {
+                    for target in targets {
+                        if globals.all_backends {
+                            println!("{}\t{}", backend.name(), target.label);
+                        } else {
+                            println!("{}", target.label);
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+        Cmd::Graph { all, format } => {
+            let detected = detect_backends(&backends, repo_root)?;
+            let changed = changed_files(repo_root, globals)?;
+            let mut affected = affected::collect(&detected, repo_root, base, &changed);
+            affected.retain(|(_, t)| globals.filter.matches(t));
+            globals.check_empty(!all && affected.is_empty())?;
+            print!("{}", graph::render(repo_root, &detected, &affected, all, format)?);
+            Ok(())
+        }
+        Cmd::Audit { dirs, json } => {
+            let selected = select_backends(&backends, repo_root, globals.all_backends)?;
+            let dirs = unchanged_dirs(repo_root, globals, "audit", dirs)?;
+            let targets = resolve_targets(&selected, repo_root, globals, &dirs)?;
+            globals.check_empty(targets.iter().all(|(_, t)| t.is_empty()))?;
+            let rows = security::scan(repo_root, &targets, &audit_paths(repo_root, globals, dirs)?)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&rows)?);
+            } else if !rows.is_empty() {
+                print!("{}", security::table(&rows));
+            }
+            if !rows.is_empty() {
+                anyhow::bail!("{} known vulnerabilities", rows.len());
+            }
+            eprintln!("kit: no known vulnerabilities");
+            Ok(())
+        }
+        Cmd::Outdated { json } => {
+            let detected = detect_backends(&backends, repo_root)?;
+            let rows = outdated::collect(repo_root, &detected)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&rows)?);
+            } else if rows.is_empty() {
+                eprintln!("kit: all dependencies are up to date");
+            } else {
+                print!("{}", outdated::table(&rows));
+            }
+            Ok(())
+        }
+        Cmd::Affected { group_by } => {
+            let detected = detect_backends(&backends, repo_root)?;
+            let changed = changed_files(repo_root, globals)?;
+            eprintln!("kit: {} changed files on branch", changed.len());
+            let generators = Config::load(repo_root)?.codegen;
+            let changed = codegen::with_outputs(repo_root, &generators, changed)?;
+            let mut targets: Vec<(&str, Target)> = codegen::targets(repo_root, &generators, &changed)?
+                .into_iter()
+                .map(|t| (codegen::BACKEND, t))
+                .collect();
+            targets.extend(affected::collect(&detected, repo_root, base, &changed));
+            targets.retain(|(_, t)| globals.filter.matches(t));
+            globals.check_empty(targets.is_empty())?;
+            match group_by {
+                Some(group_by) => {
+                    for (group, count) in affected::group(repo_root, &targets, group_by)? {
+                        println!("{count:>6}  {group}");
+                    }
+                }
+                None => {
+                    for (backend, target) in &targets {
+                        println!("{backend}\t{}", target.label);
+                    }
+                }
+            }
+            Ok(())
+        }
+        Cmd::AuditAffected { command } => {
+            let selected = select_backends(&backends, repo_root, globals.all_backends)?;
+            let changed = changed_files(repo_root, globals)?;
+            let changed = codegen::with_outputs(repo_root, &Config::load(repo_root)?.codegen, changed)?;
+            // With every file changed, each backend's affected targets are all of its targets.
+            let all = KitIgnore::load(repo_root)?.filter(repo_root, git::find_files(repo_root, "*")?);
+            let mut affected = std::collections::BTreeSet::new();
+            let mut ran = Vec::new();
+            let mut steps = Vec::new();
+            for backend in &selected {
+                let targets = |files: &[PathBuf]| {
+                    let mut targets = backend.affected_targets(repo_root, base, files);
+                    targets.retain(|t| globals.filter.matches(t));
+                    targets
+                };
+                for target in targets(&changed) {
+                    affected.insert((backend.name().to_string(), target.label));
+                }
+                for target in targets(&all) {
+                    ran.push((backend.name().to_string(), target.label.clone()));
+                    steps.extend(plan_steps(
+                        command.name(),
+                        &[(*backend, vec![target])],
+                        |b, t| match command {
+                            audit::AuditCommand::Build => b.build(repo_root, t),
+                            audit::AuditCommand::Test => b.test(repo_root, t),
+                            audit::AuditCommand::Lint => b.lint(repo_root, t),
+                        },
+                    )?);
+                }
+            }
+            eprintln!(
+                "kit: running {} on all {} target(s), {} of them affected",
+                command.name(),
+                ran.len(),
+                affected.len()
+            );
+            let results = runner.execute_each(steps)?;
+            let ran: Vec<audit::Ran> = ran
+                .into_iter()
+                .zip(&results)
+                .map(|((backend, label), result)| audit::Ran {
+                    backend,
+                    label,
+                    passed: result.is_ok(),
+                })
+                .collect();
+            let (count, caught, missed) = audit::compare(&ran, &affected);
+            let report = audit::Audit {
+                commit: git::head(repo_root)?,
+                base: base.to_string(),
+                command: command.name().to_string(),
+                targets: ran.len(),
+                affected: count,
+                caught,
+                missed,
+            };
+            audit::record(repo_root, &report)?;
+            eprintln!(
+                "kit: {} failing target(s), {} of them affected by the branch",
+                report.caught.len() + report.missed.len(),
+                report.caught.len()
+            );
+            if report.missed.is_empty() {
+                return Ok(());
+            }
+            for target in &report.missed {
+                eprintln!("kit:   missed: {target}");
+            }
+            anyhow::bail!(
+                "an affected-only {} would have missed {} failing target(s)",
+                command.name(),
+                report.missed.len()
+            )
+        }
+        Cmd::Deps {
+            command: DepsCmd::Doctor,
+        } => deps::doctor(repo_root),
+        Cmd::Release {
+            command: ReleaseCmd::Prepare { dry_run },
+        } => {
+            let config = Config::load(repo_root)?;
+            release::prepare(repo_root, &config.release, dry_run)
+        }
+        Cmd::Release {
+            command: ReleaseCmd::Publish { dry_run, push },
+        } => {
+            let config = Config::load(repo_root)?;
+            release::publish(repo_root, &config.release, dry_run, push)
+        }
+        Cmd::Publish {
+            dirs,
+            bump,
+            dry_run,
+            yes,
+            push,
+        } => {
+            let paths = if dirs.is_empty() {
+                changed_files(repo_root, globals)?
+            } else {
+                resolve_file_args(repo_root, globals, dirs)?
+            };
+            globals.check_empty(paths.is_empty())?;
+            let mut config = Config::load(repo_root)?.release;
+            config.bump = bump.or(config.bump);
+            release::publish_affected(repo_root, &config, &paths, dry_run, yes, push)
+        }
+        Cmd::Config {
+            command: ConfigCmd::Get { key },
+        } => {
+            let layers = config::Layers::read(config::user_path().as_deref(), repo_root)?;
+            let Some((value, path)) = layers.get(&key) else {
+                anyhow::bail!("{key} is not set");
+            };
+            match value.as_str() {
+                Some(text) => println!("{text}"),
+                None => println!("{value}"),
+            }
+            eprintln!("kit: from {}", path.display());
+            Ok(())
+        }
+        Cmd::Config {
+            command: ConfigCmd::Set { .. },
+        } => unreachable!("kit config set runs before the repo is detected"),
+        Cmd::Cache {
+            command: CacheCmd::Status,
+        } => {
+            let entries = cache::status(repo_root)?;
+            if entries.is_empty() {
+                eprintln!("kit: nothing in {}", repo_root.join(state::DIR).display());
+                return Ok(());
+            }
+            print!("{}", cache::table(&entries));
+            let total: u64 = entries.iter().map(|e| e.bytes).sum();
+            eprintln!(
+                "kit: {} in {}",
+                cache::size(total),
+                repo_root.join(state::DIR).display()
+            );
+            Ok(())
+        }
+        Cmd::Cache {
+            command: CacheCmd::Clear { names },
+        } => {
+            let freed = cache::clear(repo_root, &names)?;
+            eprintln!("kit: freed {}", cache::size(freed));
+            Ok(())
+        }
+        Cmd::Cache {
+            command: CacheCmd::Gc { max_age },
+        } => {
+            let max_age = config::parse_duration(&max_age)
+                .ok_or_else(|| KitError::ConfigError(format!("invalid --max-age {max_age} (e.g. 24h)")))?;
+            let (removed, freed) = cache::gc(repo_root, max_age, &Config::load(repo_root)?.codegen)?;
+            if removed.is_empty() {
+                eprintln!("kit: nothing to collect");
+            }
+            for line in removed {
+                eprintln!("kit: removed {line}");
+            }
+            if freed > 0 {
+                eprintln!("kit: freed {}", cache::size(freed));
+            }
+            Ok(())
+        }
+        Cmd::SelfUpdate { .. } => unreachable!("kit self-update runs before the repo is detected"),
     }
 }
 
-fn canonical_cwd() -> Result<PathBuf> {
-    env::current_dir()
-        .context("failed to get current directory")?
-        .canonicalize()
-        .context("failed to canonicalize current directory")
+/// Run the code generators the targets may be built from before anything else, and stop if one
+/// fails. With directories named, those generating code in or around them run; otherwise those whose
+/// inputs changed. Either way, generators with missing outputs run too.
+fn generate(runner: &mut Runner, repo_root: &Path, globals: &Globals, dirs: &[PathBuf]) -> Result<()> {
+    let generators = Config::load(repo_root)?.codegen;
+    if generators.is_empty() {
+        return Ok(());
+    }
+    let scope = if dirs.is_empty() {
+        codegen::Scope::Changed(changed_files(repo_root, globals)?)
+    } else {
+        codegen::Scope::Paths(resolve_file_args(repo_root, globals, dirs.to_vec())?)
+    };
+    let generations = codegen::plan(repo_root, &generators, &scope)?;
+    if generations.is_empty() {
+        return Ok(());
+    }
+    let steps = generations.iter().map(|g| (g.step.clone(), g.plan.clone())).collect();
+    let results = runner.execute_each(steps)?;
+    for (generation, result) in generations.iter().zip(&results) {
+        if result.is_ok() {
+            codegen::record(repo_root, generation)?;
+        }
+    }
+    combine_results(results)
 }
 
-fn resolve_targets(
-    backend: &dyn Backend,
-    repo_root: &std::path::Path,
-    base: &str,
-    dirs: Vec<PathBuf>,
-) -> Result<Vec<backend::Target>> {
+/// The fmt step of each backend, on the given repo-relative files: `fmt --check` when `check` is set.
+fn fmt_steps(
+    backends: &[&dyn Backend],
+    repo_root: &Path,
+    files: &[PathBuf],
+    check: bool,
+) -> Result<Vec<(Step, Vec<Invocation>)>> {
+    let mut steps = Vec::new();
+    for backend in backends {
+        let mut step = Step {
+            command: if check { "fmt --check" } else { "fmt" }.to_string(),
+            backend: backend.name().to_string(),
+            targets: files
+                .iter()
+                .map(|f| Target {
+                    label: f.display().to_string(),
+                    dir: f.parent().map(Path::to_path_buf).unwrap_or_default(),
+                    ..Default::default()
+                })
+                .collect(),
+            seed: None,
+            variant: None,
+            skip: None,
+        };
+        let plan = planned(&mut step, || {
+            if check {
+                backend.fmt_check(repo_root, files)
+            } else {
+                backend.fmt(repo_root, files)
+            }
+        })?;
+        steps.push((step, plan));
+    }
+    Ok(steps)
+}
+
+/// One stage of `kit ci`, run as its command would on the affected targets. The fmt stage fails if
+/// formatting changed any of the changed files, leaving the changes in place.
+fn ci_stage(
+    runner: &mut Runner,
+    repo_root: &Path,
+    globals: &Globals,
+    backends: &[&dyn Backend],
+    stage: CiStage,
+) -> Result<()> {
+    if stage == CiStage::Fmt {
+        let files = changed_files(repo_root, globals)?;
+        let before = ci::snapshot(repo_root, &files);
+        runner.execute(fmt_steps(backends, repo_root, &files, false)?)?;
+        let rewritten = ci::rewritten(repo_root, &before);
+        if !rewritten.is_empty() {
+            let names: Vec<String> = rewritten.iter().map(|f| f.display().to_string()).collect();
+            anyhow::bail!("{} files are not formatted: {}", names.len(), names.join(", "));
+        }
+        return Ok(());
+    }
+    let dirs = unchanged_dirs(repo_root, globals, stage.name(), vec![])?;
+    let targets = resolve_targets(backends, repo_root, globals, &dirs)?;
+    if stage != CiStage::Lint {
+        generate(runner, repo_root, globals, &dirs)?;
+    }
+    execute(runner, stage.name(), &targets, |b, t| match stage {
+        CiStage::Lint => b.lint(repo_root, t),
+        CiStage::Check => b.check(repo_root, t),
+        CiStage::Build => b.build(repo_root, t),
+        CiStage::Test => b.test(repo_root, t),
+        CiStage::Fmt => unreachable!("fmt runs on the changed files"),
+    })
+}
+
+/// One round of `kit watch`: generate code, then run each command on the targets, stopping at the
+/// first that fails.
+fn watch_run(
+    runner: &mut Runner,
+    repo_root: &Path,
+    globals: &Globals,
+    commands: &[audit::AuditCommand],
+    targets: &[(&dyn Backend, Vec<Target>)],
+) -> Result<()> {
+    generate(runner, repo_root, globals, &[])?;
+    for command in commands {
+        execute(runner, command.name(), targets, |b, t| match command {
+            audit::AuditCommand::Build => b.build(repo_root, t),
+            audit::AuditCommand::Test => b.test(repo_root, t),
+            audit::AuditCommand::Lint => b.lint(repo_root, t),
+        })?;
+    }
+    Ok(())
+}
+
+/// Plan one step per backend and run them all.
+fn execute(
+    runner: &mut Runner,
+    command: &str,
+    targets: &[(&dyn Backend, Vec<Target>)],
+    plan: impl Fn(&dyn Backend, &[Target]) -> Result<Vec<Invocation>>,
+) -> Result<()> {
+    runner.execute(plan_steps(command, targets, plan)?)
+}
+
+/// The files `run` changes when `check` is set: dirty files it rewrites and files it makes dirty,
+/// sorted. Without `check`, `run` runs alone and nothing is reported.
+fn files_changed_by(repo_root: &Path, check: bool, run: impl FnOnce() -> Result<()>) -> Result<Vec<PathBuf>> {
+    if !check {
+        run()?;
+        return Ok(vec![]);
+    }
+    let dirty = git::dirty_files(repo_root)?;
+    let snapshot = ci::snapshot(repo_root, &dirty);
+    run()?;
+    let mut changed = ci::rewritten(repo_root, &snapshot);
+    changed.extend(git::dirty_files(repo_root)?.into_iter().filter(|f| !dirty.contains(f)));
+    changed.sort();
+    Ok(changed)
+}
+
+/// One step per backend, with the commands `plan` returns for its targets.
+fn plan_steps(
+    command: &str,
+    targets: &[(&dyn Backend, Vec<Target>)],
+    plan: impl Fn(&dyn Backend, &[Target]) -> Result<Vec<Invocation>>,
+) -> Result<Vec<(Step, Vec<Invocation>)>> {
+    let mut steps = Vec::new();
+    for (backend, targets) in targets {
+        let mut step = Step {
+            command: command.to_string(),
+            backend: backend.name().to_string(),
+            targets: targets.to_vec(),
+            seed: None,
+            variant: None,
+            skip: None,
+        };
+        let plan = planned(&mut step, || plan(*backend, targets))?;
+        steps.push((step, plan));
+    }
+    Ok(steps)
+}
+
+/// The commands `plan` returns for the step, or none if the step has no targets or the backend
+/// skipped it, with the [`Skip`] recorded on the step.
+fn planned(step: &mut Step, plan: impl FnOnce() -> Result<Vec<Invocation>>) -> Result<Vec<Invocation>> {
+    if step.targets.is_empty() {
+        step.skip = Some(Skip::NoTargets);
+        return Ok(vec![]);
+    }
+    match plan() {
+        Ok(plan) => Ok(plan),
+        Err(e) => match Skip::find(&e) {
+            Some(skip) => {
+                step.skip = Some(skip.clone());
+                Ok(vec![])
+            }
+            None => Err(e),
+        },
+    }
+}
+
+/// Like [`plan_steps`], but targets an environment matrix lists get a step per combination of its
+/// values, with the variables set on every command and the combination as the step's variant.
+fn matrix_steps(
+    command: &str,
+    targets: &[(&dyn Backend, Vec<Target>)],
+    matrices: &[config::MatrixConfig],
+    plan: impl Fn(&dyn Backend, &[Target]) -> Result<Vec<Invocation>>,
+) -> Result<Vec<(Step, Vec<Invocation>)>> {
+    let mut steps = Vec::new();
+    for (backend, targets) in targets {
+        let (plain, expanded) = matrix::split(targets, matrices);
+        if !plain.is_empty() || expanded.is_empty() {
+            steps.extend(plan_steps(command, &[(*backend, plain)], &plan)?);
+        }
+        for (matrix, targets) in expanded {
+            for variant in matrix::variants(matrix) {
+                let mut step = Step {
+                    command: command.to_string(),
+                    backend: backend.name().to_string(),
+                    targets: targets.clone(),
+                    seed: None,
+                    variant: Some(matrix::label(&variant)),
+                    skip: None,
+                };
+                let invocations = planned(&mut step, || plan(*backend, &targets))?
+                    .into_iter()
+                    .map(|i| variant.iter().fold(i, |i, (k, v)| i.env(k, v)))
+                    .collect();
+                steps.push((step, invocations));
+            }
+        }
+    }
+    Ok(steps)
+}
+
+/// Add each backend's shuffle arguments for `seed` to its test commands and record the seed on its
+/// step. Backends whose test runner cannot shuffle run tests in the usual order.
+fn shuffled(backends: &[&dyn Backend], steps: Vec<(Step, Vec<Invocation>)>, seed: u64) -> Vec<(Step, Vec<Invocation>)> {
+    steps
+        .into_iter()
+        .map(|(mut step, plan)| {
+            let args = backends
+                .iter()
+                .find(|b| b.name() == step.backend)
+                .and_then(|b| b.shuffle_args(seed));
+            let Some(args) = args else {
+                eprintln!(
+                    "kit: the {} backend cannot shuffle tests, running them in order",
+                    step.backend
+                );
+                return (step, plan);
+            };
+            eprintln!("kit: shuffling {} tests with seed {seed}", step.backend);
+            step.seed = Some(seed);
+            (step, plan.into_iter().map(|i| i.args(args.clone())).collect())
+        })
+        .collect()
+}
+
+/// Add each backend's arguments for rerunning cached test results to its test commands.
+fn uncached(backends: &[&dyn Backend], steps: Vec<(Step, Vec<Invocation>)>) -> Vec<(Step, Vec<Invocation>)> {
+    steps
+        .into_iter()
+        .map(|(step, plan)| {
+            let args = backends
+                .iter()
+                .find(|b| b.name() == step.backend)
+                .and_then(|b| b.no_cache_args())
+                .unwrap_or_default();
+            let plan = plan.into_iter().map(|i| i.args(args.clone())).collect();
+            (step, plan)
+        })
+        .collect()
+}
+
+/// A seed for `--shuffle` without a value, small enough for every test runner to accept.
+fn random_seed() -> u64 {
+    use std::hash::{BuildHasher, RandomState};
+    RandomState::new().hash_one(std::time::SystemTime::now()) % 1_000_000_000
+}
+
+/// Report every failure from a set of independent checks, not just the first.
+fn combine_results(results: Vec<Result<()>>) -> Result<()> {
+    let mut errors: Vec<anyhow::Error> = results.into_iter().filter_map(Result::err).collect();
+    match errors.len() {
+        0 => Ok(()),
+        1 => Err(errors.remove(0)),
+        n => {
+            for e in &errors {
+                eprintln!("kit: {e:#}");
+            }
+            anyhow::bail!("{n} checks failed")
+        }
+    }
+}
+
+/// Files changed on the branch, minus anything excluded by `.kitignore`.
+fn changed_files(repo_root: &Path, globals: &Globals) -> Result<Vec<PathBuf>> {
+    let changed = globals.changes.changed_files(repo_root, &globals.base)?;
+    Ok(KitIgnore::load(repo_root)?.filter(repo_root, changed))
+}
+
+/// The directories a command runs on: those named on the command line, or, when none are and
+/// nothing changed on the branch, those of the command's `[unchanged]` scope. Empty means the
+/// targets affected by the changes.
+fn unchanged_dirs(repo_root: &Path, globals: &Globals, command: &str, dirs: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
+    if !dirs.is_empty() {
+        return Ok(dirs);
+    }
+    let config = Config::load(repo_root)?.unchanged;
+    let scope = globals.when_unchanged.unwrap_or_else(|| config.scope(command));
+    if scope == UnchangedScope::None || !changed_files(repo_root, globals)?.is_empty() {
+        return Ok(dirs);
+    }
+    let paths = config.paths(scope)?;
+    let names: Vec<String> = paths
+        .iter()
+        .map(|p| match p.to_string_lossy() {
+            name if name.is_empty() => "all targets".to_string(),
+            name => name.into_owned(),
+        })
+        .collect();
+    eprintln!(
+        "kit: nothing changed on branch, running {command} on {}",
+        names.join(", ")
+    );
+    // Absolute, so the paths are relative to the repo root wherever kit runs.
+    Ok(paths.into_iter().map(|p| repo_root.join(p)).collect())
+}
+
+/// Targets for each backend: those under the given directories, or those affected by changes on the branch.
+///
+/// Targets that don't match `--only-tests` or `--lang` are dropped.
+fn resolve_targets<'a>(
+    backends: &[&'a dyn Backend],
+    repo_root: &Path,
+    globals: &Globals,
+    dirs: &[PathBuf],
+) -> Result<Vec<(&'a dyn Backend, Vec<Target>)>> {
+    let mut resolved = resolve_unfiltered(backends, repo_root, globals, dirs)?;
+    for (_, targets) in &mut resolved {
+        targets.retain(|t| globals.filter.matches(t));
+    }
+    Ok(resolved)
+}
+
+fn resolve_unfiltered<'a>(
+    backends: &[&'a dyn Backend],
+    repo_root: &Path,
+    globals: &Globals,
+    dirs: &[PathBuf],
+) -> Result<Vec<(&'a dyn Backend, Vec<Target>)>> {
     if dirs.is_empty() {
-        let changed = git::changed_files(repo_root, base)?;
+        let changed = changed_files(repo_root, globals)?;
         eprintln!("kit: {} changed files on branch", changed.len());
-        Ok(backend.affected_targets(repo_root, &changed))
-    } else {
-        let cwd = canonical_cwd()?;
+        let changed = codegen::with_outputs(repo_root, &Config::load(repo_root)?.codegen, changed)?;
+        return Ok(backends
+            .iter()
+            .map(|b| (*b, b.affected_targets(repo_root, &globals.base, &changed)))
+            .collect());
+    }
+    let mut resolved = Vec::new();
+    for backend in backends {
         let mut targets = Vec::new();
         for d in dirs {
-            let mut full = cwd.join(&d);
-            if full.strip_prefix(repo_root).is_err() {
-                anyhow::bail!("path {} is outside repository root", full.display());
-            }
-            if full.is_file() {
-                full = full
-                    .parent()
-                    .with_context(|| format!("{} has no parent directory", d.display()))?
-                    .to_path_buf();
+            let (mut target, file) = resolve_path(*backend, repo_root, globals, d)?;
+            // Files in the same target are merged; naming the target's directory as well selects all of it.
+            match targets.iter_mut().find(|t: &&mut Target| t.label == target.label) {
+                Some(existing) => match file {
+                    Some(file) if !existing.files.is_empty() => existing.files.push(file),
+                    _ => existing.files.clear(),
+                },
+                None => {
+                    target.files.extend(file);
+                    targets.push(target);
+                }
             }
-            targets.push(backend.resolve_target(repo_root, full));
         }
-        Ok(targets)
+        resolved.push((*backend, targets));
     }
+    Ok(resolved)
 }
 
-fn resolve_file_args(repo_root: &std::path::Path, dirs: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
-    let cwd = canonical_cwd()?;
-    let mut files = Vec::new();
-    for d in dirs {
-        let full = cwd.join(&d);
-        if full.strip_prefix(repo_root).is_err() {
-            anyhow::bail!("path {} is outside repository root", full.display());
-        }
-        let rel = full.strip_prefix(repo_root).unwrap().to_path_buf();
-        files.push(rel);
+/// The target a path on the command line names, and the file it names within the target if it is a
+/// file rather than a directory.
+fn resolve_path(
+    backend: &dyn Backend,
+    repo_root: &Path,
+    globals: &Globals,
+    path: &Path,
+) -> Result<(Target, Option<PathBuf>)> {
+    let rel = paths::repo_relative(repo_root, globals.prefix.as_deref(), path)?;
+    let full = repo_root.join(&rel);
+    if !full.is_file() {
+        return Ok((backend.resolve_target(repo_root, full), None));
+    }
+    let dir = full
+        .parent()
+        .with_context(|| format!("{} has no parent directory", path.display()))?
+        .to_path_buf();
+    Ok((backend.resolve_target(repo_root, dir), Some(rel)))
+}
+
+/// Paths given on the command line, relative to the repo root.
+fn resolve_file_args(repo_root: &Path, globals: &Globals, dirs: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
+    dirs.iter()
+        .map(|d| paths::repo_relative(repo_root, globals.prefix.as_deref(), d))
+        .collect()
+}
+
+/// The paths `kit audit` checks for Rust changes: the changed files, or the named directories
+/// relative to the repo root.
+fn audit_paths(repo_root: &Path, globals: &Globals, dirs: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
+    if dirs.is_empty() {
+        changed_files(repo_root, globals)
+    } else {
+        resolve_file_args(repo_root, globals, dirs)
     }
-    Ok(files)
 }
+
+#[cfg(test)]
+#[path = "main_test.rs"]
+mod tests;
@@ -0,0 +1,28 @@
+use super::*;
+
+fn globals(prefix: &str) -> Globals {
+    Globals {
+        base: "main".to_string(),
+        changes: Box::new(changes::Git),
+        all_backends: false,
+        exit_empty: false,
+        when_unchanged: None,
+        filter: TargetFilter::default(),
+        prefix: Some(PathBuf::from(prefix)),
+    }
+}
+
+#[test]
+fn audit_resolves_directories_from_a_subdirectory() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    std::fs::create_dir_all(root.join("crates/core")).unwrap();
+    std::fs::write(root.join("crates/core/Cargo.toml"), "").unwrap();
+    let globals = globals("crates");
+    // Named on the command line from crates/, and as `[unchanged]` paths give them.
+    for dir in [PathBuf::from("core"), root.join("crates/core")] {
+        let paths = audit_paths(root, &globals, vec![dir]).unwrap();
+        assert_eq!(paths, [PathBuf::from("crates/core")]);
+        assert!(security::touches_rust(root, &paths));
+    }
+}
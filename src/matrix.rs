@@ -0,0 +1,52 @@
+use crate::backend::Target;
+use crate::config::MatrixConfig;
+
+/// One combination of matrix values, in variable order.
+pub type Variant = Vec<(String, String)>;
+
+/// Every combination of the matrix's values, varying the last variable fastest. A variable with no
+/// values is left out.
+pub fn variants(matrix: &MatrixConfig) -> Vec<Variant> {
+    let mut variants: Vec<Variant> = vec![vec![]];
+    for (key, values) in matrix.env.iter().filter(|(_, values)| !values.is_empty()) {
+        variants = variants
+            .into_iter()
+            .flat_map(|variant| {
+                values.iter().map(move |value| {
+                    let mut variant = variant.clone();
+                    variant.push((key.clone(), value.clone()));
+                    variant
+                })
+            })
+            .collect();
+    }
+    variants
+}
+
+/// How a variant is labelled in output and reports: "POSTGRES_VERSION=14, REDIS_VERSION=7".
+pub fn label(variant: &Variant) -> String {
+    let pairs: Vec<String> = variant.iter().map(|(k, v)| format!("{k}={v}")).collect();
+    pairs.join(", ")
+}
+
+/// Split targets into those no matrix lists and, per matrix, the targets it lists. A target listed
+/// by several matrices belongs to the first.
+pub fn split<'a>(
+    targets: &[Target],
+    matrices: &'a [MatrixConfig],
+) -> (Vec<Target>, Vec<(&'a MatrixConfig, Vec<Target>)>) {
+    let mut plain = Vec::new();
+    let mut expanded: Vec<(&MatrixConfig, Vec<Target>)> = matrices.iter().map(|m| (m, Vec::new())).collect();
+    for target in targets {
+        match expanded.iter_mut().find(|(m, _)| m.targets.contains(&target.label)) {
+            Some((_, listed)) => listed.push(target.clone()),
+            None => plain.push(target.clone()),
+        }
+    }
+    expanded.retain(|(_, listed)| !listed.is_empty());
+    (plain, expanded)
+}
+
+#[cfg(test)]
+#[path = "matrix_test.rs"]
+mod tests;
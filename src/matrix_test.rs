@@ -0,0 +1,46 @@
+use super::*;
+
+fn matrix(targets: &[&str], env: &[(&str, &[&str])]) -> MatrixConfig {
+    MatrixConfig {
+        targets: targets.iter().map(|t| t.to_string()).collect(),
+        env: env
+            .iter()
+            .map(|(k, values)| (k.to_string(), values.iter().map(|v| v.to_string()).collect()))
+            .collect(),
+    }
+}
+
+fn target(label: &str) -> Target {
+    Target {
+        label: label.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn variants_are_every_combination() {
+    let m = matrix(&[], &[("PG", &["14", "16"]), ("REDIS", &["7"]), ("EMPTY", &[])]);
+    let labels: Vec<String> = variants(&m).iter().map(label).collect();
+    assert_eq!(labels, ["PG=14, REDIS=7", "PG=16, REDIS=7"]);
+}
+
+#[test]
+fn split_assigns_targets_to_the_first_matrix_listing_them() {
+    let matrices = [
+        matrix(&["./pkg/db/..."], &[("PG", &["14", "16"])]),
+        matrix(&["./pkg/db/...", "./pkg/cache/..."], &[("REDIS", &["7"])]),
+        matrix(&["./pkg/unaffected/..."], &[("X", &["1"])]),
+    ];
+    let targets = [
+        target("./pkg/db/..."),
+        target("./pkg/api/..."),
+        target("./pkg/cache/..."),
+    ];
+    let (plain, expanded) = split(&targets, &matrices);
+    assert_eq!(plain.len(), 1);
+    assert_eq!(plain[0].label, "./pkg/api/...");
+    assert_eq!(expanded.len(), 2);
+    assert_eq!(expanded[0].1[0].label, "./pkg/db/...");
+    assert_eq!(expanded[1].1[0].label, "./pkg/cache/...");
+    assert!(std::ptr::eq(expanded[1].0, &matrices[1]));
+}
@@ -0,0 +1,30 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::backend::Backend;
+
+/// Fail if any backend finds targets that nothing depends on and that are not entry points.
+pub fn lint(repo_root: &Path, backends: &[&dyn Backend]) -> Result<()> {
+    let mut orphans = 0;
+    for backend in backends {
+        let Some(found) = backend.orphans(repo_root)? else {
+            eprintln!(
+                "kit: the {} backend cannot find orphaned targets, skipping it",
+                backend.name()
+            );
+            continue;
+        };
+        for label in &found {
+            eprintln!(
+                "kit: {label} ({}) has no dependents and is not a binary or test",
+                backend.name()
+            );
+        }
+        orphans += found.len();
+    }
+    if orphans > 0 {
+        anyhow::bail!("{orphans} orphaned target(s)");
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::backend::{Backend, Outdated};
+
+/// An outdated dependency and the backend that reported it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Row {
+    pub backend: String,
+    #[serde(flatten)]
+    pub dependency: Outdated,
+}
+
+/// The outdated dependencies of every backend that can tell, sorted by backend, directory, and name.
+pub fn collect(repo_root: &Path, backends: &[&dyn Backend]) -> Result<Vec<Row>> {
+    let mut rows = Vec::new();
+    for backend in backends {
+        let found = backend
+            .outdated(repo_root)
+            .with_context(|| format!("failed to list outdated {} dependencies", backend.name()))?;
+        let Some(found) = found else {
+            eprintln!("kit: {} can't list outdated dependencies, skipping it", backend.name());
+            continue;
+        };
+        rows.extend(found.into_iter().map(|dependency| Row {
+            backend: backend.name().to_string(),
+            dependency,
+        }));
+    }
+    rows.sort_by(|a, b| {
+        (&a.backend, &a.dependency.dir, &a.dependency.name).cmp(&(&b.backend, &b.dependency.dir, &b.dependency.name))
+    });
+    Ok(rows)
+}
+
+/// The rows as a table with aligned columns under a header. The repo root's directory is `.`.
+pub fn table(rows: &[Row]) -> String {
+    let mut lines = vec![[
+        "BACKEND".to_string(),
+        "DIR".to_string(),
+        "NAME".to_string(),
+        "CURRENT".to_string(),
+        "LATEST".to_string(),
+    ]];
+    for row in rows {
+        let dir = row.dependency.dir.to_string_lossy().replace('\\', "/");
+        lines.push([
+            row.backend.clone(),
+            if dir.is_empty() { ".".to_string() } else { dir },
+            row.dependency.name.clone(),
+            row.dependency.current.clone(),
+            row.dependency.latest.clone(),
+        ]);
+    }
+    let mut widths = [0; 5];
+    for line in &lines {
+        for (width, cell) in widths.iter_mut().zip(line) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let mut out = String::new();
+    for line in &lines {
+        let cells: Vec<String> = line
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+#[path = "outdated_test.rs"]
+mod tests;
@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+use super::*;
+
+fn row(backend: &str, dir: &str, name: &str, current: &str, latest: &str) -> Row {
+    Row {
+        backend: backend.to_string(),
+        dependency: Outdated {
+            name: name.to_string(),
+            current: current.to_string(),
+            latest: latest.to_string(),
+            dir: PathBuf::from(dir),
+        },
+    }
+}
+
+#[test]
+fn table_aligns_columns_and_names_the_root_dot() {
+    let rows = [
+        row("go", "", "golang.org/x/net", "v0.20.0", "v0.24.0"),
+        row("pnpm", "packages/ui", "react", "18.2.0", "19.0.0"),
+    ];
+    assert_eq!(
+        table(&rows),
+        "BACKEND  DIR          NAME              CURRENT  LATEST\n\
+         go       .            golang.org/x/net  v0.20.0  v0.24.0\n\
+         pnpm     packages/ui  react             18.2.0   19.0.0\n"
+    );
+}
+
+#[test]
+fn json_rows_are_flat() {
+    let json = serde_json::to_value(row("go", "tools", "golang.org/x/tools", "v0.1.0", "v0.2.0")).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "backend": "go",
+            "name": "golang.org/x/tools",
+            "current": "v0.1.0",
+            "latest": "v0.2.0",
+            "dir": "tools",
+        })
+    );
+}
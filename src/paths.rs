@@ -0,0 +1,47 @@
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::Result;
+
+/// A path given on the command line, relative to the repo root.
+///
+/// Relative paths are resolved against `prefix`, the current directory relative to the repo root
+/// as git reports it, rather than by comparing absolute paths: inside a container or through a
+/// symlink, the current directory's absolute path may not start with the repo root's even though
+/// it is inside the repo. `prefix` is `None` when the current directory is outside the repo.
+pub fn repo_relative(repo_root: &Path, prefix: Option<&Path>, arg: &Path) -> Result<PathBuf> {
+    if arg.is_absolute() {
+        let rel = normalize(arg)
+            .and_then(|p| p.strip_prefix(repo_root).ok().map(Path::to_path_buf))
+            .or_else(|| {
+                let canonical = arg.canonicalize().ok()?;
+                canonical.strip_prefix(repo_root).ok().map(Path::to_path_buf)
+            });
+        return rel.ok_or_else(|| anyhow::anyhow!("path {} is outside repository root", arg.display()));
+    }
+    prefix
+        .and_then(|prefix| normalize(&prefix.join(arg)))
+        .ok_or_else(|| anyhow::anyhow!("path {} is outside repository root", arg.display()))
+}
+
+/// `path` with `.` and `..` components resolved without touching the filesystem, or `None` if it
+/// climbs above its start.
+pub fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !matches!(normalized.components().next_back(), Some(Component::Normal(_))) {
+                    return None;
+                }
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    Some(normalized)
+}
+
+#[cfg(test)]
+#[path = "paths_test.rs"]
+mod tests;
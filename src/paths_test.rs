@@ -0,0 +1,55 @@
+use super::*;
+
+#[test]
+fn test_relative_paths_resolve_against_the_prefix() {
+    let root = Path::new("/repo");
+    let prefix = Path::new("services/api");
+    assert_eq!(
+        repo_relative(root, Some(prefix), Path::new("handlers")).unwrap(),
+        PathBuf::from("services/api/handlers")
+    );
+    assert_eq!(
+        repo_relative(root, Some(prefix), Path::new("../web/./src")).unwrap(),
+        PathBuf::from("services/web/src")
+    );
+    assert_eq!(
+        repo_relative(root, Some(prefix), Path::new(".")).unwrap(),
+        PathBuf::from("services/api")
+    );
+    assert_eq!(
+        repo_relative(root, Some(Path::new("")), Path::new(".")).unwrap(),
+        PathBuf::new()
+    );
+}
+
+#[test]
+fn test_paths_outside_the_repo_are_rejected() {
+    let root = Path::new("/repo");
+    assert!(repo_relative(root, Some(Path::new("services")), Path::new("../../etc")).is_err());
+    assert!(repo_relative(root, None, Path::new("src")).is_err());
+    assert!(repo_relative(root, Some(Path::new("")), Path::new("/elsewhere/src")).is_err());
+}
+
+#[test]
+fn test_absolute_paths_are_made_relative_to_the_root() {
+    let root = Path::new("/repo");
+    assert_eq!(
+        repo_relative(root, None, Path::new("/repo/services/../lib")).unwrap(),
+        PathBuf::from("lib")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_absolute_paths_through_a_symlink_are_resolved() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("repo");
+    std::fs::create_dir_all(root.join("lib")).unwrap();
+    let root = root.canonicalize().unwrap();
+    let link = dir.path().join("link");
+    std::os::unix::fs::symlink(&root, &link).unwrap();
+    assert_eq!(
+        repo_relative(&root, None, &link.join("lib")).unwrap(),
+        PathBuf::from("lib")
+    );
+}
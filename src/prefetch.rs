@@ -0,0 +1,130 @@
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::error::KitError;
+use crate::git;
+use crate::hooks::shell_quote;
+use crate::install::{INSTALLERS, Installer};
+
+/// Manifest listing the modules in a prefetch context; also marks a directory as kit's to replace.
+pub const MANIFEST: &str = "kit-prefetch.json";
+
+/// Script downloading every module's dependencies, run from the context's root.
+pub const SCRIPT: &str = "kit-prefetch.sh";
+
+/// A module whose dependencies a Docker build can download ahead of copying in the source.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Module {
+    /// The installer that downloads them (e.g. "go", "pnpm").
+    pub installer: &'static str,
+    /// Directory of the module, relative to the repo root.
+    pub dir: PathBuf,
+    /// Dependency files the download needs, relative to the repo root.
+    pub files: Vec<PathBuf>,
+    pub command: Vec<String>,
+}
+
+/// The modules owning the given target directories (relative to the repo root): for each
+/// installer, the deepest directory with its root file (go.mod, pnpm-lock.yaml, ...) at or above a
+/// target.
+pub fn modules(repo_root: &Path, target_dirs: &[PathBuf]) -> Result<Vec<Module>> {
+    let mut modules = Vec::new();
+    for installer in INSTALLERS {
+        let roots: Vec<PathBuf> = git::find_files(repo_root, installer.root)?
+            .iter()
+            .map(|f| f.parent().map(Path::to_path_buf).unwrap_or_default())
+            .collect();
+        let owning = owning_roots(&roots, target_dirs);
+        if owning.is_empty() {
+            continue;
+        }
+        let mut triggers = Vec::new();
+        for trigger in installer.triggers {
+            triggers.extend(git::find_files(repo_root, trigger)?);
+        }
+        for dir in owning {
+            let mut files: Vec<PathBuf> = triggers.iter().filter(|f| f.starts_with(&dir)).cloned().collect();
+            files.sort();
+            modules.push(Module {
+                installer: installer.name,
+                command: command(installer),
+                dir,
+                files,
+            });
+        }
+    }
+    Ok(modules)
+}
+
+/// Write the prefetch context to `out`: the modules' dependency files at their repo paths,
+/// [`SCRIPT`] to download their dependencies, and [`MANIFEST`]. A directory holding a previous
+/// context is replaced; any other non-empty directory is refused.
+pub fn write(repo_root: &Path, out: &Path, modules: &[Module]) -> Result<()> {
+    if out.exists() {
+        let empty = std::fs::read_dir(out)
+            .with_context(|| format!("failed to read {}", out.display()))?
+            .next()
+            .is_none();
+        if !empty && !out.join(MANIFEST).is_file() {
+            return Err(KitError::ConfigError(format!(
+                "{} is not empty and is not a kit prefetch context",
+                out.display()
+            ))
+            .into());
+        }
+        std::fs::remove_dir_all(out).with_context(|| format!("failed to clear {}", out.display()))?;
+    }
+    std::fs::create_dir_all(out).with_context(|| format!("failed to create {}", out.display()))?;
+    for file in modules.iter().flat_map(|m| &m.files) {
+        let dest = out.join(file);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        std::fs::copy(repo_root.join(file), &dest).with_context(|| format!("failed to copy {}", file.display()))?;
+    }
+    let path = out.join(SCRIPT);
+    std::fs::write(&path, script(modules)).with_context(|| format!("failed to write {}", path.display()))?;
+    let path = out.join(MANIFEST);
+    let text = serde_json::to_string_pretty(&serde_json::json!({ "modules": modules }))?;
+    std::fs::write(&path, text + "\n").with_context(|| format!("failed to write {}", path.display()))
+}
+
+fn command(installer: &Installer) -> Vec<String> {
+    std::iter::once(installer.program)
+        .chain(installer.args.iter().copied())
+        .map(String::from)
+        .collect()
+}
+
+/// The deepest root at or above each directory.
+fn owning_roots(roots: &[PathBuf], dirs: &[PathBuf]) -> BTreeSet<PathBuf> {
+    dirs.iter()
+        .filter_map(|dir| {
+            roots
+                .iter()
+                .filter(|root| dir.starts_with(root))
+                .max_by_key(|root| root.components().count())
+        })
+        .cloned()
+        .collect()
+}
+
+fn script(modules: &[Module]) -> String {
+    let mut script =
+        String::from("#!/bin/sh\n# Written by kit prefetch: downloads the dependencies of affected modules.\nset -e\n");
+    for module in modules {
+        let dir = module.dir.to_string_lossy().replace('\\', "/");
+        let dir = if dir.is_empty() { ".".to_string() } else { dir };
+        let command: Vec<String> = module.command.iter().map(|arg| shell_quote(arg)).collect();
+        let _ = writeln!(script, "(cd {} && {})", shell_quote(&dir), command.join(" "));
+    }
+    script
+}
+
+#[cfg(test)]
+#[path = "prefetch_test.rs"]
+mod tests;
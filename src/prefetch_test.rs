@@ -0,0 +1,54 @@
+use super::*;
+
+#[test]
+fn test_targets_map_to_the_deepest_module_above_them() {
+    let roots = [PathBuf::new(), PathBuf::from("services/api"), PathBuf::from("tools")];
+    let dirs = [
+        PathBuf::from("services/api/handlers"),
+        PathBuf::from("services/api"),
+        PathBuf::from("libs/auth"),
+    ];
+    let owning: Vec<PathBuf> = owning_roots(&roots, &dirs).into_iter().collect();
+    assert_eq!(owning, [PathBuf::new(), PathBuf::from("services/api")]);
+    assert!(owning_roots(&roots[1..], &[PathBuf::from("libs/auth")]).is_empty());
+}
+
+#[test]
+fn test_script_downloads_in_each_module_directory() {
+    let modules = [
+        Module {
+            installer: "go",
+            dir: PathBuf::from("services/api"),
+            files: vec![PathBuf::from("services/api/go.mod")],
+            command: vec!["go".to_string(), "mod".to_string(), "download".to_string()],
+        },
+        Module {
+            installer: "npm",
+            dir: PathBuf::new(),
+            files: vec![PathBuf::from("package-lock.json")],
+            command: vec!["npm".to_string(), "ci".to_string()],
+        },
+    ];
+    let script = script(&modules);
+    assert!(script.starts_with("#!/bin/sh\n"));
+    assert!(script.contains("(cd 'services/api' && 'go' 'mod' 'download')\n"));
+    assert!(script.ends_with("(cd '.' && 'npm' 'ci')\n"));
+}
+
+#[test]
+fn test_write_refuses_directories_it_did_not_write() {
+    let tmp = tempfile::tempdir().unwrap();
+    let out = tmp.path().join("context");
+    std::fs::create_dir_all(&out).unwrap();
+    std::fs::write(out.join("Dockerfile"), "FROM scratch\n").unwrap();
+    assert!(write(tmp.path(), &out, &[]).is_err());
+    assert!(out.join("Dockerfile").is_file());
+
+    std::fs::remove_file(out.join("Dockerfile")).unwrap();
+    write(tmp.path(), &out, &[]).unwrap();
+    std::fs::write(out.join("stale"), "").unwrap();
+    write(tmp.path(), &out, &[]).unwrap();
+    assert!(!out.join("stale").exists());
+    assert!(out.join(MANIFEST).is_file());
+    assert!(out.join(SCRIPT).is_file());
+}
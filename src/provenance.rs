@@ -0,0 +1,123 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+use crate::backend::Target;
+use crate::git;
+
+/// File written into the artifacts directory. It is excluded from the subjects it describes.
+pub const FILE_NAME: &str = "kit.intoto.jsonl";
+
+const BUILD_TYPE: &str = "https://github.com/block/devkit/kit-build@v1";
+const BUILDER_ID: &str = "https://github.com/block/devkit";
+
+/// Write an in-toto statement with SLSA v1 provenance for every file in the artifacts directory.
+///
+/// Subjects are the artifacts' SHA-256 digests; resolved dependencies are the source commit and
+/// the git blob hashes of every tracked file in the built targets' directories.
+pub fn write(repo_root: &Path, artifacts_dir: &Path, backend: &str, targets: &[Target]) -> Result<PathBuf> {
+    let subjects = subjects(artifacts_dir)?;
+    if subjects.is_empty() {
+        eprintln!("kit: warning: {} contains no artifacts", artifacts_dir.display());
+    }
+
+    let pathspecs: Vec<String> = targets
+        .iter()
+        .map(|t| {
+            let rel = t
+                .dir
+                .strip_prefix(repo_root)
+                .unwrap_or(&t.dir)
+                .to_string_lossy()
+                .replace('\\', "/");
+            if rel.is_empty() { ".".to_string() } else { rel }
+        })
+        .collect();
+    let materials = git::blob_hashes(repo_root, &pathspecs)?;
+    let commit = git::head(repo_root)?;
+
+    let labels: Vec<&str> = targets.iter().map(|t| t.label.as_str()).collect();
+    let stmt = statement(&subjects, &commit, &materials, backend, &labels);
+
+    let path = artifacts_dir.join(FILE_NAME);
+    let line = serde_json::to_string(&stmt).context("failed to serialize provenance")?;
+    std::fs::write(&path, format!("{line}\n")).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+fn statement(
+    subjects: &[(String, String)],
+    commit: &str,
+    materials: &[(PathBuf, String)],
+    backend: &str,
+    targets: &[&str],
+) -> Value {
+    let subject: Vec<Value> = subjects
+        .iter()
+        .map(|(name, sha256)| json!({ "name": name, "digest": { "sha256": sha256 } }))
+        .collect();
+    let mut dependencies = vec![json!({ "uri": "git+HEAD", "digest": { "gitCommit": commit } })];
+    dependencies.extend(materials.iter().map(
+        |(path, blob)| json!({ "uri": path.to_string_lossy().replace('\\', "/"), "digest": { "gitBlob": blob } }),
+    ));
+
+    json!({
+        "_type": "https://in-toto.io/Statement/v1",
+        "subject": subject,
+        "predicateType": "https://slsa.dev/provenance/v1",
+        "predicate": {
+            "buildDefinition": {
+                "buildType": BUILD_TYPE,
+                "externalParameters": {
+                    "command": "build",
+                    "backend": backend,
+                    "targets": targets,
+                },
+                "resolvedDependencies": dependencies,
+            },
+            "runDetails": {
+                "builder": {
+                    "id": BUILDER_ID,
+                    "version": { "kit": env!("CARGO_PKG_VERSION") },
+                },
+            },
+        },
+    })
+}
+
+/// SHA-256 digests of every file under `dir`, keyed by path relative to `dir`, sorted by path.
+fn subjects(dir: &Path) -> Result<Vec<(String, String)>> {
+    let mut files = Vec::new();
+    collect_files(dir, &mut files)?;
+    files.sort();
+
+    let mut subjects = Vec::new();
+    for file in files {
+        let rel = file.strip_prefix(dir).unwrap_or(&file);
+        if rel == Path::new(FILE_NAME) {
+            continue;
+        }
+        let bytes = std::fs::read(&file).with_context(|| format!("failed to read {}", file.display()))?;
+        let digest: String = Sha256::digest(&bytes).iter().map(|b| format!("{b:02x}")).collect();
+        subjects.push((rel.to_string_lossy().replace('\\', "/"), digest));
+    }
+    Ok(subjects)
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "provenance_test.rs"]
+mod tests;
@@ -0,0 +1,49 @@
+use super::*;
+use tempfile::TempDir;
+
+#[test]
+fn subjects_hash_files_recursively() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path();
+    std::fs::create_dir_all(dir.join("bin")).unwrap();
+    std::fs::write(dir.join("bin/app"), "hello").unwrap();
+    std::fs::write(dir.join("app.tar"), "").unwrap();
+    std::fs::write(dir.join(FILE_NAME), "{}").unwrap();
+
+    let subjects = subjects(dir).unwrap();
+    assert_eq!(
+        subjects,
+        vec![
+            (
+                "app.tar".to_string(),
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string()
+            ),
+            (
+                "bin/app".to_string(),
+                "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn statement_shape() {
+    let stmt = statement(
+        &[("app".to_string(), "abc".to_string())],
+        "deadbeef",
+        &[(PathBuf::from("pkg/main.go"), "0123".to_string())],
+        "go",
+        &["./pkg/..."],
+    );
+    assert_eq!(stmt["_type"], "https://in-toto.io/Statement/v1");
+    assert_eq!(stmt["predicateType"], "https://slsa.dev/provenance/v1");
+    assert_eq!(stmt["subject"][0]["digest"]["sha256"], "abc");
+    let deps = &stmt["predicate"]["buildDefinition"]["resolvedDependencies"];
+    assert_eq!(deps[0]["digest"]["gitCommit"], "deadbeef");
+    assert_eq!(deps[1]["uri"], "pkg/main.go");
+    assert_eq!(deps[1]["digest"]["gitBlob"], "0123");
+    assert_eq!(
+        stmt["predicate"]["runDetails"]["builder"]["version"]["kit"],
+        env!("CARGO_PKG_VERSION")
+    );
+}
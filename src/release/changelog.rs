@@ -0,0 +1,70 @@
+use crate::git::Commit;
+
+use super::version::Version;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Category {
+    Breaking,
+    Feature,
+    Fix,
+    Other,
+}
+
+impl Category {
+    fn heading(self) -> &'static str {
+        match self {
+            Self::Breaking => "Breaking changes",
+            Self::Feature => "Features",
+            Self::Fix => "Fixes",
+            Self::Other => "Other changes",
+        }
+    }
+}
+
+/// Split a commit into its changelog category and a description with any conventional prefix removed.
+fn describe(commit: &Commit) -> (Category, String) {
+    let subject = commit.subject();
+    let Some((header, description)) = subject.split_once(": ") else {
+        return (Category::Other, subject.to_string());
+    };
+    let breaking = header.ends_with('!') || commit.message.contains("BREAKING CHANGE");
+    let kind = header.trim_end_matches('!').split('(').next().unwrap_or("");
+    let category = match kind {
+        _ if breaking => Category::Breaking,
+        "feat" => Category::Feature,
+        "fix" => Category::Fix,
+        "build" | "chore" | "ci" | "docs" | "perf" | "refactor" | "revert" | "style" | "test" => Category::Other,
+        _ => return (Category::Other, subject.to_string()),
+    };
+    (category, description.to_string())
+}
+
+/// Draft a markdown changelog section for a release from its commits.
+pub fn section(name: &str, version: &Version, commits: &[Commit]) -> String {
+    let mut entries: Vec<(Category, String)> = commits.iter().map(describe).collect();
+    entries.sort_by_key(|(category, _)| *category);
+
+    let mut out = format!("## {name} {version}\n");
+    let mut current = None;
+    for (category, description) in entries {
+        if current != Some(category) {
+            out.push_str(&format!("\n### {}\n\n", category.heading()));
+            current = Some(category);
+        }
+        out.push_str(&format!("- {description}\n"));
+    }
+    out
+}
+
+/// Insert a section at the top of an existing changelog, below its `# ` title if it has one.
+pub fn prepend(existing: &str, section: &str) -> String {
+    if existing.trim().is_empty() {
+        return format!("# Changelog\n\n{section}");
+    }
+    match existing.split_once('\n') {
+        Some((title, rest)) if title.starts_with("# ") => {
+            format!("{title}\n\n{section}\n{}", rest.trim_start_matches('\n'))
+        }
+        _ => format!("{section}\n{existing}"),
+    }
+}
@@ -0,0 +1,177 @@
+mod changelog;
+mod package;
+mod publish;
+mod version;
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::config::ReleaseConfig;
+use crate::git::{self, Commit};
+
+pub use package::Package;
+pub use publish::{publish, publish_affected};
+pub use version::{Bump, Version};
+
+/// Version a Go module gets on its first tagged release.
+const INITIAL_GO_VERSION: Version = Version::new(0, 1, 0);
+
+const CHANGELOG: &str = "CHANGELOG.md";
+
+/// A package with unreleased commits and the version it will be released as.
+pub struct PlannedRelease {
+    pub package: Package,
+    /// Tag of the previous release, if there was one.
+    pub last_tag: Option<String>,
+    pub next: Version,
+    /// Commits touching the package since the previous release, oldest first.
+    pub commits: Vec<Commit>,
+}
+
+impl PlannedRelease {
+    pub fn tag(&self) -> String {
+        self.package.tag(&self.next)
+    }
+}
+
+/// Work out which packages changed since their last release and what version each should be released as.
+pub fn plan(repo_root: &Path, config: &ReleaseConfig) -> Result<Vec<PlannedRelease>> {
+    plan_packages(repo_root, config, &package::discover(repo_root)?)
+}
+
+/// [`plan`] for packages already discovered.
+pub fn plan_packages(repo_root: &Path, config: &ReleaseConfig, packages: &[Package]) -> Result<Vec<PlannedRelease>> {
+    let mut plans = Vec::new();
+    for pkg in packages {
+        let (current, last_tag) = last_release(repo_root, pkg)?;
+        // A manifest version that was never tagged is released as-is, with notes since the
+        // package's previous release; a package never released at all, since its manifest was added.
+        let since = match &last_tag {
+            Some(tag) => Some(tag.clone()),
+            None => latest_tag(repo_root, pkg)?.map(|(_, tag)| tag),
+        };
+        let range = match &since {
+            Some(tag) => format!("{tag}..HEAD"),
+            None => "HEAD".to_string(),
+        };
+        let mut commits = git::log(repo_root, &range, &pathspecs(pkg, packages))?;
+        if since.is_none()
+            && let Some(added) = git::added_in(repo_root, &pkg.manifest())?
+            && let Some(start) = commits.iter().position(|c| c.sha == added)
+        {
+            commits.drain(..start);
+        }
+        if commits.is_empty() {
+            continue;
+        }
+        let next = match (current, &last_tag) {
+            (Some(v), Some(_)) => v.bump(config.bump.unwrap_or_else(|| version::bump_for_commits(&commits))),
+            // The manifest version has never been tagged, so it is released as-is.
+            (Some(v), None) => v,
+            (None, _) => INITIAL_GO_VERSION,
+        };
+        plans.push(PlannedRelease {
+            package: pkg.clone(),
+            last_tag,
+            next,
+            commits,
+        });
+    }
+    Ok(plans)
+}
+
+/// The package's current version and the tag it was released under, if it has been released.
+fn last_release(repo_root: &Path, pkg: &Package) -> Result<(Option<Version>, Option<String>)> {
+    if let Some(version) = pkg.version {
+        let tag = pkg.tag(&version);
+        let released = git::tag_exists(repo_root, &tag)?;
+        return Ok((Some(version), released.then_some(tag)));
+    }
+    Ok(latest_tag(repo_root, pkg)?.map_or((None, None), |(version, tag)| (Some(version), Some(tag))))
+}
+
+/// The highest version the package was tagged with, and its tag.
+fn latest_tag(repo_root: &Path, pkg: &Package) -> Result<Option<(Version, String)>> {
+    let prefix = pkg.tag_prefix();
+    for tag in git::tags(repo_root, &prefix)? {
+        if let Ok(version) = tag[prefix.len()..].parse() {
+            return Ok(Some((version, tag)));
+        }
+    }
+    Ok(None)
+}
+
+/// Pathspecs covering a package's directory, excluding packages nested inside it.
+fn pathspecs(pkg: &Package, all: &[Package]) -> Vec<String> {
+    let spec = |dir: &Path| {
+        let s = dir.to_string_lossy().replace('\\', "/");
+        if s.is_empty() { ".".to_string() } else { s }
+    };
+    let mut specs = vec![spec(&pkg.dir)];
+    for other in all {
+        if other.dir != pkg.dir && other.dir.starts_with(&pkg.dir) {
+            specs.push(format!(":(exclude){}", spec(&other.dir)));
+        }
+    }
+    specs
+}
+
+/// Bump versions of changed packages and draft their changelog sections.
+///
+/// With `dry_run`, only print the plan and the drafted changelog.
+pub fn prepare(repo_root: &Path, config: &ReleaseConfig, dry_run: bool) -> Result<()> {
+    let plans = plan(repo_root, config)?;
+    if plans.is_empty() {
+        eprintln!("kit: no packages changed since their last release");
+        return Ok(());
+    }
+
+    for p in &plans {
+        eprintln!(
+            "kit: {} {} ({}): {} -> {} ({} commit(s))",
+            p.package.kind.name(),
+            p.package.name,
+            p.package.dir.display(),
+            p.last_tag.as_deref().unwrap_or("unreleased"),
+            p.tag(),
+            p.commits.len(),
+        );
+    }
+
+    for p in &plans {
+        let section = changelog::section(&p.package.name, &p.next, &p.commits);
+        println!("{section}");
+        if !dry_run {
+            write_release(repo_root, p, &section)?;
+        }
+    }
+
+    if !dry_run {
+        eprintln!(
+            "kit: prepared {} release(s); review and commit the changes",
+            plans.len()
+        );
+    }
+    Ok(())
+}
+
+/// Write a release's version into its manifest and its changelog `section` into its CHANGELOG.md,
+/// returning the files written, relative to the repo root.
+fn write_release(repo_root: &Path, p: &PlannedRelease, section: &str) -> Result<Vec<PathBuf>> {
+    p.package.set_version(repo_root, &p.next)?;
+    let changelog = p.package.dir.join(CHANGELOG);
+    let path = repo_root.join(&changelog);
+    let existing = std::fs::read_to_string(&path).unwrap_or_default();
+    std::fs::write(&path, changelog::prepend(&existing, section))
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    let mut written = vec![changelog];
+    if p.package.version.is_some() {
+        written.push(p.package.manifest());
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+#[path = "release_test.rs"]
+mod tests;
@@ -0,0 +1,242 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use super::version::Version;
+use crate::git;
+
+/// The ecosystem a releasable package belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PackageKind {
+    Go,
+    Npm,
+    Cargo,
+}
+
+impl PackageKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Go => "go",
+            Self::Npm => "npm",
+            Self::Cargo => "cargo",
+        }
+    }
+
+    fn manifest(self) -> &'static str {
+        match self {
+            Self::Go => "go.mod",
+            Self::Npm => "package.json",
+            Self::Cargo => "Cargo.toml",
+        }
+    }
+}
+
+/// A releasable unit: a Go module, an npm package, or a crate.
+#[derive(Debug, Clone)]
+pub struct Package {
+    pub kind: PackageKind,
+    pub name: String,
+    /// Directory containing the manifest, relative to the repo root.
+    pub dir: PathBuf,
+    /// Version declared in the manifest. Go modules are versioned by tag only.
+    pub version: Option<Version>,
+    /// Names of the packages this one depends on, as declared in its manifest.
+    pub dependencies: Vec<String>,
+}
+
+impl Package {
+    /// Prefix shared by every release tag of this package.
+    ///
+    /// Go modules follow the Go toolchain's `<dir>/vX.Y.Z` convention; other packages use `<name>@X.Y.Z`.
+    pub fn tag_prefix(&self) -> String {
+        match self.kind {
+            PackageKind::Go if self.dir.as_os_str().is_empty() => "v".to_string(),
+            PackageKind::Go => format!("{}/v", self.dir.to_string_lossy().replace('\\', "/")),
+            PackageKind::Npm | PackageKind::Cargo => format!("{}@", self.name),
+        }
+    }
+
+    pub fn tag(&self, version: &Version) -> String {
+        format!("{}{version}", self.tag_prefix())
+    }
+
+    /// The package's manifest, relative to the repo root.
+    pub fn manifest(&self) -> PathBuf {
+        self.dir.join(self.kind.manifest())
+    }
+
+    /// Rewrite the version in the package manifest, preserving the rest of the file.
+    pub fn set_version(&self, repo_root: &Path, version: &Version) -> Result<()> {
+        let (Some(old), PackageKind::Npm | PackageKind::Cargo) = (self.version, self.kind) else {
+            return Ok(());
+        };
+        let path = repo_root.join(self.manifest());
+        let text = std::fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        let updated = match self.kind {
+            PackageKind::Npm => replace_json_version(&text, &old, version),
+            _ => replace_cargo_version(&text, &old, version),
+        }
+        .with_context(|| format!("could not find version {old} in {}", path.display()))?;
+        std::fs::write(&path, updated).with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+/// Find every Go module, public npm package, and crate in the repository.
+pub fn discover(repo_root: &Path) -> Result<Vec<Package>> {
+    let mut packages = Vec::new();
+    for kind in [PackageKind::Go, PackageKind::Npm, PackageKind::Cargo] {
+        for manifest in git::find_files(repo_root, kind.manifest())? {
+            let path = repo_root.join(&manifest);
+            let Ok(text) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            if kind == PackageKind::Cargo && inherits_version(&text) {
+                eprintln!(
+                    "kit: skipping {} in releases: its version is inherited from the workspace",
+                    manifest.display()
+                );
+                continue;
+            }
+            let parsed = match kind {
+                PackageKind::Go => parse_go_mod(&text).map(|(name, deps)| (name, None, deps)),
+                PackageKind::Npm => parse_package_json(&text).with_context(|| format!("invalid {}", path.display()))?,
+                PackageKind::Cargo => parse_cargo_toml(&text).with_context(|| format!("invalid {}", path.display()))?,
+            };
+            if let Some((name, version, dependencies)) = parsed {
+                // A placeholder or pre-release version (`0.0.0-development`, `1.2.0-rc.1`) can't be
+                // bumped, so that package is left out rather than failing the whole repo.
+                let version = match version.as_deref().map(str::parse::<Version>).transpose() {
+                    Ok(version) => version,
+                    Err(_) => {
+                        eprintln!(
+                            "kit: skipping {} in releases: version `{}` isn't major.minor.patch",
+                            manifest.display(),
+                            version.unwrap_or_default()
+                        );
+                        continue;
+                    }
+                };
+                let dir = manifest.parent().map(Path::to_path_buf).unwrap_or_default();
+                packages.push(Package {
+                    kind,
+                    name,
+                    dir,
+                    version,
+                    dependencies,
+                });
+            }
+        }
+    }
+    Ok(packages)
+}
+
+/// Module path and required modules from a go.mod.
+fn parse_go_mod(text: &str) -> Option<(String, Vec<String>)> {
+    let mut name = None;
+    let mut requires = Vec::new();
+    let mut in_block = false;
+    for line in text.lines() {
+        let line = line.split("//").next().unwrap_or("").trim();
+        if in_block {
+            if line == ")" {
+                in_block = false;
+            } else if let Some(module) = line.split_whitespace().next() {
+                requires.push(module.to_string());
+            }
+        } else if let Some(module) = line.strip_prefix("module ") {
+            name = Some(module.trim().trim_matches('"').to_string());
+        } else if line == "require (" {
+            in_block = true;
+        } else if let Some(module) = line.strip_prefix("require ").and_then(|r| r.split_whitespace().next()) {
+            requires.push(module.to_string());
+        }
+    }
+    name.map(|n| (n, requires))
+}
+
+/// Name, declared version as written, and dependencies.
+type Manifest = (String, Option<String>, Vec<String>);
+
+/// Returns None for private packages and manifests without a name.
+fn parse_package_json(text: &str) -> Result<Option<Manifest>> {
+    let json: serde_json::Value = serde_json::from_str(text)?;
+    if json.get("private").and_then(|p| p.as_bool()) == Some(true) {
+        return Ok(None);
+    }
+    let Some(name) = json.get("name").and_then(|n| n.as_str()) else {
+        return Ok(None);
+    };
+    let version = json.get("version").and_then(|v| v.as_str()).map(String::from);
+    let dependencies = ["dependencies", "peerDependencies", "optionalDependencies"]
+        .iter()
+        .filter_map(|key| json.get(key).and_then(|d| d.as_object()))
+        .flat_map(|deps| deps.keys().cloned())
+        .collect();
+    Ok(Some((name.to_string(), version, dependencies)))
+}
+
+/// Returns None for virtual workspace manifests and crates with `publish = false`.
+fn parse_cargo_toml(text: &str) -> Result<Option<Manifest>> {
+    let manifest: toml::Table = toml::from_str(text)?;
+    let Some(package) = manifest.get("package").and_then(|p| p.as_table()) else {
+        return Ok(None);
+    };
+    if package.get("publish").and_then(|p| p.as_bool()) == Some(false) {
+        return Ok(None);
+    }
+    let Some(name) = package.get("name").and_then(|n| n.as_str()) else {
+        return Ok(None);
+    };
+    let version = package.get("version").and_then(|v| v.as_str()).map(String::from);
+    let dependencies = ["dependencies", "build-dependencies"]
+        .iter()
+        .filter_map(|key| manifest.get(*key).and_then(|d| d.as_table()))
+        .flat_map(|deps| deps.iter())
+        .map(|(key, spec)| {
+            // A renamed dependency names the real crate in its `package` field.
+            spec.get("package").and_then(|p| p.as_str()).unwrap_or(key).to_string()
+        })
+        .collect();
+    Ok(Some((name.to_string(), version, dependencies)))
+}
+
+/// Whether a crate takes its version from `[workspace.package]` (`version.workspace = true`), which
+/// kit can't bump per crate.
+fn inherits_version(text: &str) -> bool {
+    toml::from_str::<toml::Table>(text).is_ok_and(|manifest| {
+        manifest
+            .get("package")
+            .and_then(|p| p.get("version"))
+            .and_then(|v| v.get("workspace"))
+            .and_then(|w| w.as_bool())
+            == Some(true)
+    })
+}
+
+fn replace_json_version(text: &str, old: &Version, new: &Version) -> Option<String> {
+    let re = Regex::new(&format!(r#""version"(\s*):(\s*)"{}""#, regex::escape(&old.to_string()))).ok()?;
+    re.is_match(text).then(|| {
+        re.replacen(text, 1, format!(r#""version"${{1}}:${{2}}"{new}""#))
+            .into_owned()
+    })
+}
+
+fn replace_cargo_version(text: &str, old: &Version, new: &Version) -> Option<String> {
+    let (head, tail) = text.split_at(text.find("[package]")?);
+    let re = Regex::new(&format!(
+        r#"(?m)^version(\s*)=(\s*)"{}""#,
+        regex::escape(&old.to_string())
+    ))
+    .ok()?;
+    re.is_match(tail).then(|| {
+        format!(
+            "{head}{}",
+            re.replacen(tail, 1, format!(r#"version${{1}}=${{2}}"{new}""#))
+        )
+    })
+}
+
+#[cfg(test)]
+#[path = "package_test.rs"]
+mod tests;
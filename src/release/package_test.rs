@@ -0,0 +1,75 @@
+use super::*;
+
+#[test]
+fn go_mod_module_path_and_requires() {
+    let text = "// comment\nmodule example.com/m/sub\n\ngo 1.22\n\nrequire example.com/m v0.1.0\n\n\
+                require (\n\tgithub.com/a/b v1.0.0 // indirect\n\tgithub.com/c/d v2.0.0\n)\n";
+    let (name, requires) = parse_go_mod(text).unwrap();
+    assert_eq!(name, "example.com/m/sub");
+    assert_eq!(requires, vec!["example.com/m", "github.com/a/b", "github.com/c/d"]);
+    assert!(parse_go_mod("go 1.22\n").is_none());
+}
+
+#[test]
+fn package_json_skips_private_packages() {
+    let public =
+        parse_package_json(r#"{"name": "@org/ui", "version": "1.2.3", "dependencies": {"@org/core": "^1"}}"#).unwrap();
+    assert_eq!(
+        public,
+        Some((
+            "@org/ui".to_string(),
+            Some("1.2.3".to_string()),
+            vec!["@org/core".to_string()]
+        ))
+    );
+    let development = parse_package_json(r#"{"name": "@org/app", "version": "0.0.0-development"}"#).unwrap();
+    assert_eq!(development.unwrap().1.as_deref(), Some("0.0.0-development"));
+    let private = parse_package_json(r#"{"name": "root", "private": true}"#).unwrap();
+    assert!(private.is_none());
+}
+
+#[test]
+fn cargo_toml_skips_virtual_and_unpublished_manifests() {
+    let krate = parse_cargo_toml(
+        "[package]\nname = \"kit\"\nversion = \"0.1.0\"\n\n[dependencies]\nanyhow = \"1\"\ncore = { package = \"kit-core\", path = \"../core\" }\n",
+    )
+    .unwrap();
+    assert_eq!(
+        krate,
+        Some((
+            "kit".to_string(),
+            Some("0.1.0".to_string()),
+            vec!["anyhow".to_string(), "kit-core".to_string()]
+        ))
+    );
+    assert!(parse_cargo_toml("[workspace]\nmembers = [\"a\"]\n").unwrap().is_none());
+    assert!(
+        parse_cargo_toml("[package]\nname = \"x\"\nversion = \"0.1.0\"\npublish = false\n")
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+fn inherited_crate_versions_are_recognized() {
+    assert!(inherits_version("[package]\nname = \"x\"\nversion.workspace = true\n"));
+    assert!(inherits_version(
+        "[package]\nname = \"x\"\nversion = { workspace = true }\n"
+    ));
+    assert!(!inherits_version("[package]\nname = \"x\"\nversion = \"0.1.0\"\n"));
+}
+
+#[test]
+fn version_replacement_preserves_formatting() {
+    let json = "{\n  \"name\": \"ui\",\n  \"version\" : \"1.2.3\",\n  \"dependencies\": {}\n}\n";
+    assert_eq!(
+        replace_json_version(json, &Version::new(1, 2, 3), &Version::new(1, 3, 0)).unwrap(),
+        "{\n  \"name\": \"ui\",\n  \"version\" : \"1.3.0\",\n  \"dependencies\": {}\n}\n"
+    );
+
+    let toml = "[workspace]\nversion = \"0.1.0\"\n\n[package]\nname = \"kit\"\nversion = \"0.1.0\"\n";
+    assert_eq!(
+        replace_cargo_version(toml, &Version::new(0, 1, 0), &Version::new(0, 2, 0)).unwrap(),
+        "[workspace]\nversion = \"0.1.0\"\n\n[package]\nname = \"kit\"\nversion = \"0.2.0\"\n"
+    );
+}
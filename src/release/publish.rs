@@ -0,0 +1,244 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::Result;
+
+use super::package::{Package, PackageKind, discover};
+use super::{PlannedRelease, changelog, plan, plan_packages, write_release};
+use crate::config::ReleaseConfig;
+use crate::deploy::confirm;
+use crate::error::KitError;
+use crate::git;
+
+/// Publish every package whose version has not been released yet, dependencies first, tagging each one.
+///
+/// npm packages and crates are published when their manifest version has no release tag (i.e. after
+/// `kit release prepare` and a commit); Go modules are published by tagging the next version.
+pub fn publish(repo_root: &Path, config: &ReleaseConfig, dry_run: bool, push: bool) -> Result<()> {
+    let pending: Vec<PlannedRelease> = plan(repo_root, config)?
+        .into_iter()
+        .filter(|p| p.package.kind == PackageKind::Go || p.last_tag.is_none())
+        .collect();
+    if pending.is_empty() {
+        eprintln!("kit: nothing to publish");
+        return Ok(());
+    }
+
+    let ordered = dependency_order(pending)?;
+    eprintln!("kit: publishing {} package(s):", ordered.len());
+    for p in &ordered {
+        eprintln!("kit:   {} {}", p.package.kind.name(), p.tag());
+    }
+    release(repo_root, &ordered, dry_run, push)
+}
+
+/// Release the packages `paths` (changed files or named directories) fall in, in one go. Packages
+/// released before get their next version written to the manifest and a drafted changelog section,
+/// committed together, as do Go modules; then each package is published and tagged as by
+/// [`publish`], dependencies first. The plan is printed and, unless `yes`, confirmed before anything
+/// changes.
+pub fn publish_affected(
+    repo_root: &Path,
+    config: &ReleaseConfig,
+    paths: &[PathBuf],
+    dry_run: bool,
+    yes: bool,
+    push: bool,
+) -> Result<()> {
+    let packages = discover(repo_root)?;
+    let affected = affected_packages(&packages, paths);
+    let pending: Vec<PlannedRelease> = plan_packages(repo_root, config, &packages)?
+        .into_iter()
+        .filter(|p| affected.contains(&p.package.dir))
+        .collect();
+    if pending.is_empty() {
+        eprintln!("kit: no affected packages have unreleased commits");
+        return Ok(());
+    }
+
+    let ordered = dependency_order(pending)?;
+    let pnpm = repo_root.join("pnpm-lock.yaml").exists();
+    eprintln!("kit: publishing {} affected package(s):", ordered.len());
+    for p in &ordered {
+        let upload = match upload_command(p.package.kind, pnpm) {
+            Some((cmd, args)) => format!("{cmd} {}", args.join(" ")),
+            None => "tag only".to_string(),
+        };
+        eprintln!(
+            "kit:   {} {} ({}): {} -> {} ({} commit(s), {upload})",
+            p.package.kind.name(),
+            p.package.name,
+            p.package.dir.display(),
+            p.last_tag.as_deref().unwrap_or("unreleased"),
+            p.tag(),
+            p.commits.len(),
+        );
+    }
+    if dry_run {
+        return Ok(());
+    }
+    if !git::is_clean(repo_root)? {
+        anyhow::bail!("the working tree has uncommitted changes; commit or stash them before publishing");
+    }
+    if !yes && !confirm(&format!("Publish {} package(s)?", ordered.len()), "publish")? {
+        anyhow::bail!("publish cancelled");
+    }
+
+    let mut written = Vec::new();
+    let mut bumped = Vec::new();
+    for p in ordered.iter().filter(|p| is_unprepared(p)) {
+        let section = changelog::section(&p.package.name, &p.next, &p.commits);
+        written.extend(write_release(repo_root, p, &section)?);
+        bumped.push(p.tag());
+    }
+    if !written.is_empty() {
+        git::commit(repo_root, &format!("chore(release): {}", bumped.join(", ")), &written)?;
+        eprintln!(
+            "kit: committed the version bumps and changelogs of {}",
+            bumped.join(", ")
+        );
+    }
+    release(repo_root, &ordered, false, push)
+}
+
+/// Whether a release still needs its version and changelog written. A manifest version without a
+/// release tag was written by `kit release prepare`, or is the package's first version.
+fn is_unprepared(release: &PlannedRelease) -> bool {
+    release.package.kind == PackageKind::Go || release.last_tag.is_some()
+}
+
+/// Directories of the packages `paths` fall in, each path counting for the deepest package holding
+/// it, and of the packages below the paths that are directories.
+fn affected_packages(packages: &[Package], paths: &[PathBuf]) -> BTreeSet<PathBuf> {
+    let mut dirs = BTreeSet::new();
+    for path in paths {
+        if let Some(holding) = packages
+            .iter()
+            .filter(|p| path.starts_with(&p.dir))
+            .max_by_key(|p| p.dir.components().count())
+        {
+            dirs.insert(holding.dir.clone());
+        }
+        dirs.extend(
+            packages
+                .iter()
+                .filter(|p| p.dir.starts_with(path))
+                .map(|p| p.dir.clone()),
+        );
+    }
+    dirs
+}
+
+/// The command uploading a package to its registry; Go modules are only tagged.
+fn upload_command(kind: PackageKind, pnpm: bool) -> Option<(&'static str, &'static [&'static str])> {
+    match kind {
+        PackageKind::Go => None,
+        PackageKind::Npm if pnpm => Some(("pnpm", &["publish", "--no-git-checks"])),
+        PackageKind::Npm => Some(("npm", &["publish"])),
+        PackageKind::Cargo => Some(("cargo", &["publish"])),
+    }
+}
+
+/// Upload each release in order, unless already on its registry, then tag it.
+fn release(repo_root: &Path, ordered: &[PlannedRelease], dry_run: bool, push: bool) -> Result<()> {
+    let pnpm = repo_root.join("pnpm-lock.yaml").exists();
+    for p in ordered {
+        let dir = repo_root.join(&p.package.dir);
+        let tag = p.tag();
+        if let Some((cmd, args)) = upload_command(p.package.kind, pnpm) {
+            if is_published(&dir, p) {
+                eprintln!("kit: {tag} is already published, skipping upload");
+            } else if dry_run {
+                eprintln!("kit: would run `{cmd} {}` in {}", args.join(" "), dir.display());
+            } else {
+                run(cmd, args, &dir)?;
+            }
+        }
+
+        if dry_run {
+            eprintln!("kit: would tag {tag}");
+            continue;
+        }
+        if !git::tag_exists(repo_root, &tag)? {
+            run("git", &["tag", "-a", &tag, "-m", &tag], repo_root)?;
+        }
+        if push {
+            run("git", &["push", "origin", &format!("refs/tags/{tag}")], repo_root)?;
+        }
+    }
+    Ok(())
+}
+
+/// Order releases so that every package comes after the packages it depends on.
+fn dependency_order(releases: Vec<PlannedRelease>) -> Result<Vec<PlannedRelease>> {
+    let index: BTreeMap<&str, usize> = releases
+        .iter()
+        .enumerate()
+        .map(|(i, r)| (r.package.name.as_str(), i))
+        .collect();
+    let deps: Vec<Vec<usize>> = releases
+        .iter()
+        .map(|r| {
+            r.package
+                .dependencies
+                .iter()
+                .filter_map(|d| index.get(d.as_str()).copied())
+                .collect()
+        })
+        .collect();
+
+    let mut order = Vec::with_capacity(releases.len());
+    let mut done = vec![false; releases.len()];
+    while order.len() < releases.len() {
+        let ready: Vec<usize> = (0..releases.len())
+            .filter(|&i| !done[i] && deps[i].iter().all(|&d| done[d]))
+            .collect();
+        if ready.is_empty() {
+            let stuck: Vec<&str> = (0..releases.len())
+                .filter(|&i| !done[i])
+                .map(|i| releases[i].package.name.as_str())
+                .collect();
+            anyhow::bail!("dependency cycle between packages: {}", stuck.join(", "));
+        }
+        for i in ready {
+            done[i] = true;
+            order.push(i);
+        }
+    }
+
+    let mut slots: Vec<Option<PlannedRelease>> = releases.into_iter().map(Some).collect();
+    Ok(order.into_iter().filter_map(|i| slots[i].take()).collect())
+}
+
+/// Ask the package registry whether this version already exists, so a re-run after a partial
+/// failure does not try to upload it twice.
+fn is_published(dir: &Path, release: &PlannedRelease) -> bool {
+    let spec = format!("{}@{}", release.package.name, release.next);
+    let output = match release.package.kind {
+        PackageKind::Go => return false,
+        PackageKind::Npm => Command::new("npm")
+            .args(["view", &spec, "version"])
+            .current_dir(dir)
+            .output(),
+        PackageKind::Cargo => Command::new("cargo")
+            .args(["info", "--quiet", &spec])
+            .current_dir(dir)
+            .output(),
+    };
+    // If the registry can't be queried, let the publish command itself report the problem.
+    output.is_ok_and(|o| o.status.success() && !o.stdout.trim_ascii().is_empty())
+}
+
+fn run(cmd: &str, args: &[&str], dir: &Path) -> Result<()> {
+    let status = Command::new(cmd)
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .map_err(|e| KitError::spawn(cmd, e))?;
+    KitError::check(cmd, status)
+}
+
+#[cfg(test)]
+#[path = "publish_test.rs"]
+mod tests;
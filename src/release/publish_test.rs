@@ -0,0 +1,71 @@
+use super::*;
+use crate::release::{Package, Version};
+use std::path::PathBuf;
+
+fn release(name: &str, deps: &[&str]) -> PlannedRelease {
+    PlannedRelease {
+        package: Package {
+            kind: PackageKind::Npm,
+            name: name.to_string(),
+            dir: PathBuf::from(name),
+            version: Some(Version::new(1, 0, 0)),
+            dependencies: deps.iter().map(|d| d.to_string()).collect(),
+        },
+        last_tag: None,
+        next: Version::new(1, 0, 0),
+        commits: vec![],
+    }
+}
+
+fn names(releases: &[PlannedRelease]) -> Vec<&str> {
+    releases.iter().map(|r| r.package.name.as_str()).collect()
+}
+
+#[test]
+fn dependencies_publish_first() {
+    let releases = vec![
+        release("app", &["ui", "react"]),
+        release("ui", &["core"]),
+        release("core", &[]),
+        release("cli", &["core"]),
+    ];
+    let ordered = dependency_order(releases).unwrap();
+    assert_eq!(names(&ordered), vec!["core", "ui", "cli", "app"]);
+}
+
+#[test]
+fn cycles_are_reported() {
+    let releases = vec![release("a", &["b"]), release("b", &["a"]), release("c", &[])];
+    let err = dependency_order(releases).err().unwrap();
+    assert!(err.to_string().contains("a, b"), "{err}");
+}
+
+#[test]
+fn affected_packages_hold_the_paths_or_sit_below_them() {
+    let package = |dir: &str| Package {
+        kind: PackageKind::Npm,
+        name: dir.to_string(),
+        dir: PathBuf::from(dir),
+        version: None,
+        dependencies: vec![],
+    };
+    let packages = [
+        package(""),
+        package("web/ui"),
+        package("web/ui/icons"),
+        package("web/app"),
+    ];
+    let dirs = |paths: &[&str]| {
+        let paths: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+        affected_packages(&packages, &paths)
+            .into_iter()
+            .map(|d| d.to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        dirs(&["web/ui/icons/star.svg", "web/ui/index.ts"]),
+        ["web/ui", "web/ui/icons"]
+    );
+    assert_eq!(dirs(&["README.md"]), [""]);
+    assert_eq!(dirs(&["web"]), ["", "web/app", "web/ui", "web/ui/icons"]);
+}
@@ -0,0 +1,97 @@
+use super::*;
+use package::PackageKind;
+use std::path::PathBuf;
+
+fn commit(message: &str) -> Commit {
+    Commit {
+        sha: "0123456789abcdef".to_string(),
+        message: message.to_string(),
+    }
+}
+
+fn package(kind: PackageKind, name: &str, dir: &str) -> Package {
+    Package {
+        kind,
+        name: name.to_string(),
+        dir: PathBuf::from(dir),
+        version: None,
+        dependencies: vec![],
+    }
+}
+
+#[test]
+fn version_bumps() {
+    let v: Version = "1.2.3".parse().unwrap();
+    assert_eq!(v.bump(Bump::Patch), Version::new(1, 2, 4));
+    assert_eq!(v.bump(Bump::Minor), Version::new(1, 3, 0));
+    assert_eq!(v.bump(Bump::Major), Version::new(2, 0, 0));
+    assert_eq!(Version::new(0, 4, 1).bump(Bump::Major), Version::new(0, 5, 0));
+    assert!("1.2".parse::<Version>().is_err());
+}
+
+#[test]
+fn bump_follows_conventional_commits() {
+    assert_eq!(
+        version::bump_for_commits(&[commit("fix: a"), commit("chore: b")]),
+        Bump::Patch
+    );
+    assert_eq!(
+        version::bump_for_commits(&[commit("fix: a"), commit("feat(x): b")]),
+        Bump::Minor
+    );
+    assert_eq!(version::bump_for_commits(&[commit("feat!: a")]), Bump::Major);
+    assert_eq!(
+        version::bump_for_commits(&[commit("fix: a\n\nBREAKING CHANGE: gone")]),
+        Bump::Major
+    );
+}
+
+#[test]
+fn tag_prefixes() {
+    assert_eq!(package(PackageKind::Go, "example.com/m", "").tag_prefix(), "v");
+    assert_eq!(
+        package(PackageKind::Go, "example.com/m/sub", "sub").tag_prefix(),
+        "sub/v"
+    );
+    assert_eq!(package(PackageKind::Npm, "@org/ui", "web/ui").tag_prefix(), "@org/ui@");
+}
+
+#[test]
+fn pathspecs_exclude_nested_packages() {
+    let all = vec![
+        package(PackageKind::Go, "example.com/m", ""),
+        package(PackageKind::Go, "example.com/m/sub", "sub"),
+        package(PackageKind::Npm, "ui", "web"),
+    ];
+    assert_eq!(pathspecs(&all[0], &all), vec![".", ":(exclude)sub", ":(exclude)web"]);
+    assert_eq!(pathspecs(&all[1], &all), vec!["sub"]);
+}
+
+#[test]
+fn changelog_groups_by_category() {
+    let commits = vec![
+        commit("fix(db): close connections"),
+        commit("feat: add pooling"),
+        commit("Update README"),
+        commit("feat!: drop v1 API"),
+    ];
+    let section = changelog::section("db", &Version::new(2, 0, 0), &commits);
+    assert_eq!(
+        section,
+        "## db 2.0.0\n\n\
+         ### Breaking changes\n\n- drop v1 API\n\n\
+         ### Features\n\n- add pooling\n\n\
+         ### Fixes\n\n- close connections\n\n\
+         ### Other changes\n\n- Update README\n"
+    );
+}
+
+#[test]
+fn changelog_prepend_keeps_title() {
+    let section = "## db 1.1.0\n\n### Fixes\n\n- a\n";
+    assert_eq!(changelog::prepend("", section), format!("# Changelog\n\n{section}"));
+    assert_eq!(
+        changelog::prepend("# Changelog\n\n## db 1.0.0\n", section),
+        format!("# Changelog\n\n{section}\n## db 1.0.0\n")
+    );
+}
@@ -0,0 +1,78 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::git::Commit;
+
+/// A `major.minor.patch` release version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+/// Which version component a release increments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Version {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self { major, minor, patch }
+    }
+
+    /// Increment the version. Before 1.0.0 a major bump only increments the minor component.
+    pub fn bump(self, bump: Bump) -> Self {
+        match bump {
+            Bump::Major if self.major > 0 => Self::new(self.major + 1, 0, 0),
+            Bump::Major | Bump::Minor => Self::new(self.major, self.minor + 1, 0),
+            Bump::Patch => Self::new(self.major, self.minor, self.patch + 1),
+        }
+    }
+}
+
+impl FromStr for Version {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim().trim_start_matches('v');
+        let parts: Vec<&str> = s.split('.').collect();
+        let [major, minor, patch] = parts.as_slice() else {
+            anyhow::bail!("invalid version `{s}`: expected major.minor.patch");
+        };
+        let parse = |p: &str| p.parse::<u64>().with_context(|| format!("invalid version `{s}`"));
+        Ok(Self::new(parse(major)?, parse(minor)?, parse(patch)?))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Derive a bump from Conventional Commits: breaking changes are major, features minor, anything else patch.
+pub fn bump_for_commits(commits: &[Commit]) -> Bump {
+    commits
+        .iter()
+        .map(|c| {
+            let subject = c.subject();
+            let header = subject.split(':').next().unwrap_or("");
+            if header.ends_with('!') || c.message.contains("BREAKING CHANGE") {
+                Bump::Major
+            } else if header == "feat" || header.starts_with("feat(") {
+                Bump::Minor
+            } else {
+                Bump::Patch
+            }
+        })
+        .max()
+        .unwrap_or(Bump::Patch)
+}
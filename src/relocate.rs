@@ -0,0 +1,148 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use crate::backend::{Backend, BazelBackend, GoBackend, which_exists};
+use crate::error::KitError;
+use crate::git;
+
+/// Move the package directory `old` to `new` (both relative to the repo root) with `git mv`, then
+/// update the references to it that kit knows how to find: Go import paths, Bazel labels, and
+/// TypeScript path aliases. Returns the files the move touched, for working out what to re-test.
+pub fn relocate(repo_root: &Path, old: &Path, new: &Path) -> Result<Vec<PathBuf>> {
+    if !repo_root.join(old).is_dir() {
+        return Err(KitError::ConfigError(format!("{} is not a directory", old.display())).into());
+    }
+    if repo_root.join(new).exists() {
+        return Err(KitError::ConfigError(format!("{} already exists", new.display())).into());
+    }
+    if let Some(parent) = repo_root.join(new).parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let moved = files_under(&repo_root.join(old))?;
+    git::mv(repo_root, old, new)?;
+    eprintln!("kit: moved {} to {}", old.display(), new.display());
+
+    let mut touched: Vec<PathBuf> = Vec::new();
+    for file in &moved {
+        let rel = file.strip_prefix(repo_root.join(old)).unwrap_or(file);
+        touched.push(old.join(rel));
+        touched.push(new.join(rel));
+    }
+    if let Some((old_import, new_import)) = import_paths(repo_root, old, new)? {
+        let files = git::find_files(repo_root, "*.go")?;
+        let rewritten = rewrite_files(repo_root, &files, |text| rewrite(text, "", &old_import, &new_import))?;
+        report("Go import path", &old_import, &rewritten);
+        if !rewritten.is_empty() && which_exists("gofmt") {
+            // Renamed imports can sort differently within their group.
+            let status = std::process::Command::new("gofmt")
+                .arg("-w")
+                .args(&rewritten)
+                .current_dir(repo_root)
+                .status()
+                .map_err(|e| KitError::spawn("gofmt", e))?;
+            KitError::check("gofmt", status)?;
+        }
+        touched.extend(rewritten);
+    }
+    if BazelBackend.detect(repo_root) {
+        let (old_pkg, new_pkg) = (slash(old), slash(new));
+        let mut files = Vec::new();
+        for name in ["BUILD", "BUILD.bazel", "*.bzl"] {
+            files.extend(git::find_files(repo_root, name)?);
+        }
+        let rewritten = rewrite_files(repo_root, &files, |text| rewrite(text, "//", &old_pkg, &new_pkg))?;
+        report("Bazel label", &format!("//{old_pkg}"), &rewritten);
+        touched.extend(rewritten);
+    }
+    let mut rewritten = Vec::new();
+    for tsconfig in git::find_files(repo_root, "tsconfig*.json")? {
+        let dir = tsconfig.parent().unwrap_or(Path::new(""));
+        let (Ok(old_rel), Ok(new_rel)) = (old.strip_prefix(dir), new.strip_prefix(dir)) else {
+            continue;
+        };
+        let (old_rel, new_rel) = (slash(old_rel), slash(new_rel));
+        rewritten.extend(rewrite_files(repo_root, std::slice::from_ref(&tsconfig), |text| {
+            rewrite(text, "(?:\\./)?", &old_rel, &new_rel)
+        })?);
+    }
+    report("TypeScript path alias", &slash(old), &rewritten);
+    touched.extend(rewritten);
+    touched.sort();
+    touched.dedup();
+    Ok(touched)
+}
+
+/// The Go import paths of `old` and `new`, if `old` is a package inside a Go module (and not the
+/// module's root, whose import path is in its go.mod and moves with it).
+fn import_paths(repo_root: &Path, old: &Path, new: &Path) -> Result<Option<(String, String)>> {
+    let modules = GoBackend::modules(repo_root)?;
+    let import_path = |dir: &Path| {
+        modules
+            .iter()
+            .filter(|(_, root)| dir.starts_with(root) && dir != root)
+            .max_by_key(|(_, root)| root.components().count())
+            .map(|(module, root)| format!("{module}/{}", slash(dir.strip_prefix(root).unwrap_or(dir))))
+    };
+    Ok(import_path(old).zip(import_path(new)))
+}
+
+/// `text` with quoted references to `old` rewritten to `new`: strings that, after `prefix` (a
+/// regex), are `old` or continue it with `/` or `:`. None if nothing changed.
+fn rewrite(text: &str, prefix: &str, old: &str, new: &str) -> Option<String> {
+    let re = Regex::new(&format!(r#""({prefix}){}(["/:])"#, regex::escape(old))).unwrap();
+    let out = re.replace_all(text, |c: &regex::Captures| format!("\"{}{new}{}", &c[1], &c[2]));
+    (out != text).then(|| out.into_owned())
+}
+
+/// Apply `rewrite` to each file, writing back those it changed. Returns the changed files.
+fn rewrite_files(
+    repo_root: &Path,
+    files: &[PathBuf],
+    rewrite: impl Fn(&str) -> Option<String>,
+) -> Result<Vec<PathBuf>> {
+    let mut changed = Vec::new();
+    for file in files {
+        let path = repo_root.join(file);
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Some(text) = rewrite(&text) {
+            std::fs::write(&path, text).with_context(|| format!("failed to write {}", path.display()))?;
+            changed.push(file.clone());
+        }
+    }
+    Ok(changed)
+}
+
+fn report(kind: &str, old: &str, files: &[PathBuf]) {
+    if !files.is_empty() {
+        eprintln!("kit: updated {kind} {old} in {} file(s)", files.len());
+    }
+}
+
+fn slash(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Files under `dir`, recursively.
+fn files_under(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(next) = pending.pop() {
+        for entry in std::fs::read_dir(&next).with_context(|| format!("failed to read {}", next.display()))? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+#[path = "relocate_test.rs"]
+mod tests;
@@ -0,0 +1,34 @@
+use super::*;
+
+#[test]
+fn go_imports_are_rewritten_with_their_subpackages() {
+    let source = r#"import (
+	"example.com/m/pkg/auth"
+	"example.com/m/pkg/auth/token"
+	"example.com/m/pkg/authz"
+)"#;
+    let out = rewrite(source, "", "example.com/m/pkg/auth", "example.com/m/internal/auth").unwrap();
+    assert_eq!(
+        out,
+        r#"import (
+	"example.com/m/internal/auth"
+	"example.com/m/internal/auth/token"
+	"example.com/m/pkg/authz"
+)"#
+    );
+    assert!(rewrite(source, "", "example.com/m/pkg/db", "example.com/m/db").is_none());
+}
+
+#[test]
+fn bazel_labels_and_ts_aliases_are_rewritten() {
+    let build = r#"deps = ["//pkg/auth", "//pkg/auth:token", "//pkg/authz", "@x//pkg/auth"]"#;
+    assert_eq!(
+        rewrite(build, "//", "pkg/auth", "lib/auth").unwrap(),
+        r#"deps = ["//lib/auth", "//lib/auth:token", "//pkg/authz", "@x//pkg/auth"]"#
+    );
+    let tsconfig = r#""paths": { "@acme/auth/*": ["./packages/auth/src/*"], "@acme/ui": ["packages/auth-ui"] }"#;
+    assert_eq!(
+        rewrite(tsconfig, "(?:\\./)?", "packages/auth", "libs/auth").unwrap(),
+        r#""paths": { "@acme/auth/*": ["./libs/auth/src/*"], "@acme/ui": ["packages/auth-ui"] }"#
+    );
+}
@@ -0,0 +1,60 @@
+use anyhow::Result;
+
+use super::{Event, Reporter};
+use crate::codegen;
+
+/// Progress messages on stderr. Always enabled.
+pub struct ConsoleReporter;
+
+impl Reporter for ConsoleReporter {
+    fn event(&mut self, event: &Event) -> Result<()> {
+        match event {
+            Event::Started(step) => {
+                let running = step
+                    .command
+                    .strip_prefix("run ")
+                    .map(|task| format!("running {task} for"));
+                let (verb, unit) = match step.command.as_str() {
+                    "build" => ("building", "target"),
+                    "check" => ("checking", "target"),
+                    "test" => ("testing", "target"),
+                    "lint" => ("linting", "target"),
+                    "fix" => ("fixing", "target"),
+                    "docs" => ("building docs for", "target"),
+                    "fmt" => ("formatting", "file"),
+                    "fmt --check" => ("checking formatting of", "file"),
+                    "coverage" => ("measuring coverage of", "target"),
+                    "install" => ("installing dependencies for", "module"),
+                    "exec" => ("running the command for", "target"),
+                    "tidy" => ("tidying dependencies of", "target"),
+                    "generate" if step.backend == codegen::BACKEND => ("generating code for", "generator"),
+                    "generate" => ("generating code for", "target"),
+                    other => (running.as_deref().unwrap_or(other), "target"),
+                };
+                let variant = step.variant.as_ref().map(|v| format!(" [{v}]")).unwrap_or_default();
+                eprintln!(
+                    "kit: {verb} {} {unit}(s) with {}{variant}",
+                    step.targets.len(),
+                    step.backend
+                );
+            }
+            Event::Finished(step, _) if let Some(skip) = &step.skip => {
+                eprintln!("kit: {} {} skipped: {skip}", step.backend, step.command_label());
+            }
+            Event::Finished(step, outcome) if outcome.error.is_none() => {
+                let cached = outcome
+                    .cache
+                    .map(|c| format!(" ({} of {} test results cached)", c.cached, c.total))
+                    .unwrap_or_default();
+                eprintln!(
+                    "kit: {} {} finished in {:.1}s{cached}",
+                    step.backend,
+                    step.command_label(),
+                    outcome.duration.as_secs_f64()
+                );
+            }
+            Event::Finished(..) => {}
+        }
+        Ok(())
+    }
+}
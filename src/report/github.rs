@@ -0,0 +1,61 @@
+use std::io::Write;
+
+use anyhow::{Context, Result};
+
+use super::{Event, Finished, Reporter};
+
+/// Emits GitHub Actions error annotations for failed steps and appends a summary table to
+/// `$GITHUB_STEP_SUMMARY` when the run ends.
+#[derive(Default)]
+pub struct GithubReporter {
+    finished: Finished,
+}
+
+impl Reporter for GithubReporter {
+    fn event(&mut self, event: &Event) -> Result<()> {
+        self.finished.record(event);
+        if let Event::Finished(step, outcome) = event
+            && let Some(error) = &outcome.error
+        {
+            // Workflow commands end at a newline, so multi-line messages are percent-encoded.
+            let message = error.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A");
+            println!(
+                "::error title=kit {} ({})::{message}",
+                step.command_label(),
+                step.backend
+            );
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let Some(path) = std::env::var_os("GITHUB_STEP_SUMMARY") else {
+            return Ok(());
+        };
+        let mut summary = String::from("| Step | Backend | Targets | Result | Time |\n|---|---|---|---|---|\n");
+        for (step, outcome) in &self.finished.0 {
+            let cached = outcome
+                .cache
+                .map(|c| format!(" ({}/{} cached)", c.cached, c.total))
+                .unwrap_or_default();
+            summary.push_str(&format!(
+                "| {} | {} | {} | {}{cached} | {:.1}s |\n",
+                step.command_label(),
+                step.backend,
+                step.targets.len(),
+                match (&outcome.error, &step.skip) {
+                    (Some(_), _) => "❌ failed".to_string(),
+                    (None, Some(skip)) => format!("⏭️ skipped: {skip}"),
+                    (None, None) => "✅ passed".to_string(),
+                },
+                outcome.duration.as_secs_f64()
+            ));
+        }
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut f| f.write_all(summary.as_bytes()))
+            .context("failed to write GITHUB_STEP_SUMMARY")
+    }
+}
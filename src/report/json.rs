@@ -0,0 +1,79 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+
+use super::{Event, Finished, Outcome, Reporter, Step};
+
+/// Writes a JSON summary of every step to a file when the run ends.
+pub struct JsonReporter {
+    path: PathBuf,
+    finished: Finished,
+}
+
+impl JsonReporter {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            finished: Finished::default(),
+        }
+    }
+}
+
+impl Reporter for JsonReporter {
+    fn event(&mut self, event: &Event) -> Result<()> {
+        self.finished.record(event);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let text = serde_json::to_string_pretty(&summary(&self.finished.0))?;
+        std::fs::write(&self.path, text + "\n").with_context(|| format!("failed to write {}", self.path.display()))
+    }
+}
+
+/// The JSON document describing a run, shared with the webhook reporter.
+pub fn summary(steps: &[(Step, Outcome)]) -> Value {
+    let steps: Vec<Value> = steps
+        .iter()
+        .map(|(step, outcome)| {
+            let mut value = json!({
+                "command": step.command,
+                "backend": step.backend,
+                "targets": step.targets,
+                "duration_ms": outcome.duration.as_millis() as u64,
+                "status": status(step, outcome),
+                "error": outcome.error,
+                "error_kind": outcome.error_kind,
+            });
+            if let Some(seed) = step.seed {
+                value["seed"] = json!(seed);
+            }
+            if let Some(variant) = &step.variant {
+                value["variant"] = json!(variant);
+            }
+            if let Some(skip) = &step.skip {
+                value["skip"] = json!(skip);
+            }
+            if let Some(cache) = outcome.cache {
+                value["cached_tests"] = json!(cache.cached);
+                value["tests"] = json!(cache.total);
+            }
+            value
+        })
+        .collect();
+    let passed = steps.iter().all(|s| s["status"] != "failed");
+    let skipped = steps.iter().filter(|s| s["status"] == "skipped").count();
+    json!({ "passed": passed, "skipped": skipped, "steps": steps })
+}
+
+/// "passed", "failed", or "skipped".
+pub fn status(step: &Step, outcome: &Outcome) -> &'static str {
+    if outcome.error.is_some() {
+        "failed"
+    } else if step.skip.is_some() {
+        "skipped"
+    } else {
+        "passed"
+    }
+}
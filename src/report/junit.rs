@@ -0,0 +1,80 @@
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use super::{Event, Finished, Outcome, Reporter, Step};
+
+/// Writes a JUnit XML file with one test case per step when the run ends.
+pub struct JunitReporter {
+    path: PathBuf,
+    finished: Finished,
+}
+
+impl JunitReporter {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            finished: Finished::default(),
+        }
+    }
+}
+
+impl Reporter for JunitReporter {
+    fn event(&mut self, event: &Event) -> Result<()> {
+        self.finished.record(event);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        std::fs::write(&self.path, render(&self.finished.0))
+            .with_context(|| format!("failed to write {}", self.path.display()))
+    }
+}
+
+pub fn render(steps: &[(Step, Outcome)]) -> String {
+    let failures = steps.iter().filter(|(_, o)| o.error.is_some()).count();
+    let skipped = steps.iter().filter(|(s, _)| s.skip.is_some()).count();
+    let time: f64 = steps.iter().map(|(_, o)| o.duration.as_secs_f64()).sum();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuite name=\"kit\" tests=\"{}\" failures=\"{failures}\" skipped=\"{skipped}\" time=\"{time:.3}\">",
+        steps.len()
+    );
+    for (step, outcome) in steps {
+        let _ = write!(
+            xml,
+            "  <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\">",
+            escape(&step.backend),
+            escape(&step.command_label()),
+            outcome.duration.as_secs_f64()
+        );
+        if let Some(error) = &outcome.error {
+            let _ = write!(xml, "<failure message=\"{}\"/>", escape(error));
+        } else if let Some(skip) = &step.skip {
+            let _ = write!(xml, "<skipped message=\"{}\"/>", escape(&skip.to_string()));
+        }
+        let _ = writeln!(
+            xml,
+            "<system-out>{}</system-out></testcase>",
+            escape(
+                &step
+                    .targets
+                    .iter()
+                    .map(|t| t.label.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )
+        );
+    }
+    xml.push_str("</testsuite>\n");
+    xml
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
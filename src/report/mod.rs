@@ -0,0 +1,169 @@
+mod console;
+mod github;
+mod json;
+mod junit;
+pub mod upload;
+mod webhook;
+
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::backend::Target;
+use crate::error::KitError;
+
+pub use console::ConsoleReporter;
+
+/// A backend operation the runner executes, such as building a set of targets.
+#[derive(Debug, Clone)]
+pub struct Step {
+    /// The kit command being run (e.g. "build", "fmt").
+    pub command: String,
+    pub backend: String,
+    /// Targets, or files for `fmt` (labelled with their path).
+    pub targets: Vec<Target>,
+    /// The seed tests were shuffled with (`--shuffle`), if the backend shuffled them.
+    pub seed: Option<u64>,
+    /// The environment matrix entry the step ran with (e.g. "POSTGRES_VERSION=14"), if any.
+    pub variant: Option<String>,
+    /// Why the step's targets were not built, tested, etc., if kit skipped the step.
+    pub skip: Option<Skip>,
+}
+
+/// Why kit skipped work it would otherwise have done. Backends return one as the error from
+/// planning a step; the step is then reported as skipped rather than failed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum Skip {
+    /// A tool the step runs is not installed.
+    ToolMissing { tool: String },
+    /// The repo doesn't set up what the step runs, e.g. no `.clang-tidy` for lint.
+    NotConfigured { detail: String },
+    /// The backend had no targets to run the step on.
+    NoTargets,
+    /// What the step would produce is already current, e.g. generated code whose inputs are
+    /// unchanged.
+    UpToDate,
+}
+
+impl Skip {
+    /// The `Skip` in an error's chain, if planning was skipped rather than failed.
+    pub fn find(err: &anyhow::Error) -> Option<&Skip> {
+        err.chain().find_map(|e| e.downcast_ref::<Skip>())
+    }
+}
+
+impl fmt::Display for Skip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ToolMissing { tool } => write!(f, "{tool} is not installed"),
+            Self::NotConfigured { detail } => f.write_str(detail),
+            Self::NoTargets => f.write_str("no targets"),
+            Self::UpToDate => f.write_str("up to date"),
+        }
+    }
+}
+
+impl std::error::Error for Skip {}
+
+impl Step {
+    /// The command, followed by the matrix variant in brackets if there is one: "test [PG=14]".
+    pub fn command_label(&self) -> String {
+        match &self.variant {
+            Some(variant) => format!("{} [{variant}]", self.command),
+            None => self.command.clone(),
+        }
+    }
+}
+
+/// How a step ended.
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub duration: Duration,
+    /// The failure message, if the step failed.
+    pub error: Option<String>,
+    /// The [`KitError::kind`] of the failure, when it is a known kind.
+    pub error_kind: Option<&'static str>,
+    /// How many test results the backend reused from its cache, if it reports that.
+    pub cache: Option<TestCache>,
+}
+
+/// Cached test results out of all the test results a step reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TestCache {
+    pub cached: usize,
+    pub total: usize,
+}
+
+impl Outcome {
+    pub fn new(duration: Duration, result: &Result<()>) -> Self {
+        let err = result.as_ref().err();
+        Self {
+            duration,
+            error: err.map(|e| format!("{e:#}")),
+            error_kind: err.and_then(KitError::find).map(KitError::kind),
+            cache: None,
+        }
+    }
+}
+
+/// A lifecycle event emitted by the runner.
+pub enum Event<'a> {
+    Started(&'a Step),
+    Finished(&'a Step, &'a Outcome),
+}
+
+/// Receives runner events and turns them into output somewhere.
+pub trait Reporter {
+    fn event(&mut self, event: &Event) -> Result<()>;
+
+    /// Called once after the last event, to write out anything the reporter buffered.
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Build a reporter from a `--report` value: `json[=PATH]`, `junit[=PATH]`, `github`, or `webhook=URL`.
+pub fn from_spec(spec: &str) -> Result<Box<dyn Reporter>> {
+    let (kind, arg) = match spec.split_once('=') {
+        Some((kind, arg)) => (kind, Some(arg)),
+        None => (spec, None),
+    };
+    let path = |default: &str| PathBuf::from(arg.unwrap_or(default));
+    Ok(match kind {
+        "json" => Box::new(json::JsonReporter::new(path("kit-report.json"))),
+        "junit" => Box::new(junit::JunitReporter::new(path("kit-junit.xml"))),
+        "github" => Box::new(github::GithubReporter::default()),
+        "webhook" => match arg {
+            Some(url) => Box::new(webhook::WebhookReporter::new(url.to_string())),
+            None => {
+                return Err(KitError::ConfigError("--report webhook requires a URL (webhook=URL)".to_string()).into());
+            }
+        },
+        _ => {
+            return Err(KitError::ConfigError(format!(
+                "unknown reporter `{kind}` (expected json, junit, github, or webhook)"
+            ))
+            .into());
+        }
+    })
+}
+
+/// Collects finished steps for reporters that write a summary at the end of the run.
+#[derive(Default)]
+struct Finished(Vec<(Step, Outcome)>);
+
+impl Finished {
+    fn record(&mut self, event: &Event) {
+        if let Event::Finished(step, outcome) = event {
+            self.0.push(((*step).clone(), (*outcome).clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "report_test.rs"]
+mod tests;
@@ -0,0 +1,123 @@
+use super::*;
+use crate::backend::TargetMeta;
+
+fn finished(command: &str, error: Option<&str>) -> (Step, Outcome) {
+    (
+        Step {
+            command: command.to_string(),
+            backend: "go".to_string(),
+            targets: vec![Target {
+                label: "./pkg/...".to_string(),
+                meta: TargetMeta {
+                    language: Some("go".to_string()),
+                    is_test: Some(true),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            seed: None,
+            variant: None,
+            skip: None,
+        },
+        Outcome::new(
+            Duration::from_millis(1500),
+            &match error {
+                Some(e) => Err(KitError::ToolFailed {
+                    tool: e.to_string(),
+                    exit: Some(1),
+                }
+                .into()),
+                None => Ok(()),
+            },
+        ),
+    )
+}
+
+#[test]
+fn from_spec_rejects_unknown_reporters() {
+    assert!(from_spec("json").is_ok());
+    assert!(from_spec("junit=out/report.xml").is_ok());
+    assert!(from_spec("webhook").is_err());
+    assert!(from_spec("slack").is_err());
+}
+
+#[test]
+fn json_summary_marks_failed_steps() {
+    let summary = json::summary(&[finished("build", None), finished("test", Some("go"))]);
+    assert_eq!(summary["passed"], false);
+    assert_eq!(summary["steps"][0]["status"], "passed");
+    assert_eq!(summary["steps"][0]["duration_ms"], 1500);
+    assert_eq!(
+        summary["steps"][0]["targets"],
+        serde_json::json!([{ "label": "./pkg/...", "language": "go", "is_test": true }])
+    );
+    assert_eq!(summary["steps"][1]["status"], "failed");
+    assert_eq!(summary["steps"][1]["error"], "go exited with code 1");
+    assert_eq!(summary["steps"][1]["error_kind"], "tool_failed");
+}
+
+#[test]
+fn junit_escapes_failure_messages() {
+    let xml = junit::render(&[finished("test", Some("<a> & \"b\""))]);
+    assert!(xml.contains(r#"<testsuite name="kit" tests="1" failures="1" skipped="0" time="1.500">"#));
+    assert!(xml.contains(r#"<failure message="&lt;a&gt; &amp; &quot;b&quot; exited with code 1"/>"#));
+}
+
+#[test]
+fn json_summary_records_shuffle_seeds() {
+    let (mut step, outcome) = finished("test", None);
+    assert!(
+        json::summary(&[(step.clone(), outcome.clone())])["steps"][0]
+            .get("seed")
+            .is_none()
+    );
+    step.seed = Some(42);
+    assert_eq!(json::summary(&[(step, outcome)])["steps"][0]["seed"], 42);
+}
+
+#[test]
+fn json_summary_counts_cached_tests() {
+    let (step, mut outcome) = finished("test", None);
+    outcome.cache = Some(TestCache { cached: 3, total: 5 });
+    let summary = json::summary(&[(step, outcome)]);
+    assert_eq!(summary["steps"][0]["cached_tests"], 3);
+    assert_eq!(summary["steps"][0]["tests"], 5);
+}
+
+#[test]
+fn upload_body_adds_the_run_to_the_summary() {
+    let run = upload::Run {
+        repo: Some("git@github.com:block/devkit.git".to_string()),
+        branch: Some("feature".to_string()),
+        commit: "abc123".to_string(),
+        environment: crate::environment::Environment::new(Default::default(), None, None),
+    };
+    let body = upload::body(&run, &[finished("test", None)]).unwrap();
+    assert_eq!(body["passed"], true);
+    assert_eq!(body["steps"][0]["command"], "test");
+    assert_eq!(body["run"]["repo"], "git@github.com:block/devkit.git");
+    assert_eq!(body["run"]["commit"], "abc123");
+    assert_eq!(body["run"]["environment"]["fingerprint"], run.environment.fingerprint);
+}
+
+#[test]
+fn skipped_steps_are_reported_with_their_reason() {
+    let (mut step, outcome) = finished("lint", None);
+    step.skip = Some(Skip::ToolMissing {
+        tool: "buildifier".to_string(),
+    });
+    let steps = [(step, outcome), finished("test", None)];
+    let summary = json::summary(&steps);
+    assert_eq!(summary["passed"], true);
+    assert_eq!(summary["skipped"], 1);
+    assert_eq!(summary["steps"][0]["status"], "skipped");
+    assert_eq!(
+        summary["steps"][0]["skip"],
+        serde_json::json!({ "reason": "tool_missing", "tool": "buildifier" })
+    );
+    assert_eq!(summary["steps"][1]["status"], "passed");
+
+    let xml = junit::render(&steps);
+    assert!(xml.contains(r#"skipped="1""#));
+    assert!(xml.contains(r#"<skipped message="buildifier is not installed"/>"#));
+}
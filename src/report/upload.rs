@@ -0,0 +1,92 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{Event, Finished, Outcome, Reporter, Step, json};
+use crate::environment::Environment;
+use crate::error::KitError;
+
+/// Environment variable holding the bearer token sent with `--upload`.
+pub const TOKEN_VAR: &str = "KIT_UPLOAD_TOKEN";
+
+/// What a run was of, sent with its results so a results service can tell runs apart.
+#[derive(Debug, Clone, Serialize)]
+pub struct Run {
+    /// The `origin` remote's URL, if the repo has one.
+    pub repo: Option<String>,
+    pub branch: Option<String>,
+    pub commit: String,
+    pub environment: Environment,
+}
+
+/// POSTs the run and its JSON summary to a central results service when the run ends, using curl.
+pub struct UploadReporter {
+    url: String,
+    token: Option<String>,
+    run: Run,
+    /// Where the body is written for curl to read; stdin carries curl's configuration.
+    body_path: PathBuf,
+    finished: Finished,
+}
+
+impl UploadReporter {
+    pub fn new(url: String, token: Option<String>, run: Run, body_path: PathBuf) -> Self {
+        Self {
+            url,
+            token,
+            run,
+            body_path,
+            finished: Finished::default(),
+        }
+    }
+}
+
+impl Reporter for UploadReporter {
+    fn event(&mut self, event: &Event) -> Result<()> {
+        self.finished.record(event);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        // Commands that run no steps, such as `kit affected`, have nothing to report.
+        if self.finished.0.is_empty() {
+            return Ok(());
+        }
+        let body = body(&self.run, &self.finished.0)?.to_string();
+        std::fs::write(&self.body_path, body)
+            .with_context(|| format!("failed to write {}", self.body_path.display()))?;
+        // The token goes through curl's config on stdin so it doesn't show up in the process list.
+        let config = match &self.token {
+            Some(token) => format!("header = \"Authorization: Bearer {token}\"\n"),
+            None => String::new(),
+        };
+        let mut child = Command::new("curl")
+            .args(["-fsS", "-X", "POST", "-H", "Content-Type: application/json", "-K", "-"])
+            .arg("--data-binary")
+            .arg(format!("@{}", self.body_path.display()))
+            .arg(&self.url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .map_err(|e| KitError::spawn("curl", e))?;
+        child
+            .stdin
+            .take()
+            .context("failed to open curl stdin")?
+            .write_all(config.as_bytes())
+            .context("failed to send configuration to curl")?;
+        let status = child.wait().context("failed to wait for curl")?;
+        KitError::check(&format!("upload to {}", self.url), status)
+    }
+}
+
+/// The uploaded document: the JSON summary with the run's details added under `run`.
+pub fn body(run: &Run, steps: &[(Step, Outcome)]) -> Result<Value> {
+    let mut body = json::summary(steps);
+    body["run"] = serde_json::to_value(run)?;
+    Ok(body)
+}
@@ -0,0 +1,56 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result};
+
+use super::{Event, Finished, Reporter, json};
+use crate::error::KitError;
+
+/// POSTs the JSON run summary to a URL when the run ends, using curl.
+pub struct WebhookReporter {
+    url: String,
+    finished: Finished,
+}
+
+impl WebhookReporter {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            finished: Finished::default(),
+        }
+    }
+}
+
+impl Reporter for WebhookReporter {
+    fn event(&mut self, event: &Event) -> Result<()> {
+        self.finished.record(event);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let body = json::summary(&self.finished.0).to_string();
+        let mut child = Command::new("curl")
+            .args([
+                "-fsS",
+                "-X",
+                "POST",
+                "-H",
+                "Content-Type: application/json",
+                "--data-binary",
+                "@-",
+            ])
+            .arg(&self.url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .map_err(|e| KitError::spawn("curl", e))?;
+        child
+            .stdin
+            .take()
+            .context("failed to open curl stdin")?
+            .write_all(body.as_bytes())
+            .context("failed to send webhook body to curl")?;
+        let status = child.wait().context("failed to wait for curl")?;
+        KitError::check(&format!("webhook POST to {}", self.url), status)
+    }
+}
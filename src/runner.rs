@@ -0,0 +1,469 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use tokio::task::JoinSet;
+
+use crate::backend::Backend;
+use crate::config::{MissingTools, format_duration};
+use crate::environment::{self, Environment};
+use crate::error::KitError;
+use crate::exec::{self, Invocation};
+use crate::report::{Event, Outcome, Reporter, Skip, Step, TestCache};
+use crate::state;
+use crate::timing::{self, TargetBudgets};
+
+/// Directory under `.kit` holding the output of the most recent test run, one log per backend.
+pub const LAST_RUN: &str = "last-run";
+
+/// File in [`LAST_RUN`] describing the steps whose output is logged there.
+pub const MANIFEST: &str = "manifest.json";
+
+/// Executes the commands backends plan and tells every reporter when each step starts and finishes.
+pub struct Runner {
+    reporters: Vec<Box<dyn Reporter>>,
+    runtime: tokio::runtime::Runtime,
+    repo_root: PathBuf,
+    /// Backends by name, to read test results out of the logs of their steps.
+    backends: Vec<Box<dyn Backend>>,
+    /// When the run started, which the wall-time budget counts from.
+    started: Instant,
+    budget: Budget,
+    /// Maximum number of steps of any kind running at once.
+    jobs: Option<usize>,
+    missing_tools: MissingTools,
+    target_budgets: TargetBudgets,
+    /// Variables from `--env` and `--env-file`, set for every command after the step's own.
+    env: Vec<(String, String)>,
+    /// Passing steps and how long they took, recorded for the regressions summary at the end.
+    timings: Vec<(Step, Duration)>,
+    /// Captured on first use; the toolchain doesn't change during a run.
+    environment: Option<Environment>,
+}
+
+/// Limits on the whole run, from the `[budget]` section of kit.toml.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Budget {
+    /// Wall-clock time all steps together may take.
+    pub max_wall_time: Option<Duration>,
+    /// Maximum number of heavy steps (build, check, test, coverage) running at once.
+    pub max_concurrent_heavy: Option<usize>,
+}
+
+/// How a batch of steps stopped.
+enum Stop {
+    Done,
+    Interrupted,
+    OutOfTime,
+}
+
+impl Runner {
+    pub fn new(reporters: Vec<Box<dyn Reporter>>, repo_root: PathBuf, backends: Vec<Box<dyn Backend>>) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .context("failed to start async runtime")?;
+        Ok(Self {
+            reporters,
+            runtime,
+            repo_root,
+            backends,
+            started: Instant::now(),
+            budget: Budget::default(),
+            jobs: None,
+            missing_tools: MissingTools::default(),
+            target_budgets: TargetBudgets::default(),
+            env: Vec::new(),
+            timings: Vec::new(),
+            environment: None,
+        })
+    }
+
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// At most `jobs` steps run at once, from `jobs` in kit.toml or the user config.
+    pub fn with_jobs(mut self, jobs: Option<usize>) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    /// Whether steps whose tool is missing are skipped or failed, from `[tools] missing` in kit.toml.
+    pub fn with_missing_tools(mut self, policy: MissingTools) -> Self {
+        self.missing_tools = policy;
+        self
+    }
+
+    /// Expected durations of targets, from `[budget.targets]` in kit.toml.
+    pub fn with_target_budgets(mut self, budgets: TargetBudgets) -> Self {
+        self.target_budgets = budgets;
+        self
+    }
+
+    /// Add a reporter that needs something only known once the runner exists, such as the environment.
+    pub fn add_reporter(&mut self, reporter: Box<dyn Reporter>) {
+        self.reporters.push(reporter);
+    }
+
+    /// Set environment variables for every command the runner spawns from now on, overriding those
+    /// a backend or matrix sets.
+    pub fn add_env(&mut self, vars: Vec<(String, String)>) {
+        self.env.extend(vars);
+    }
+
+    /// The environment of this run: tool versions, devcontainer image, and Nix flake lock.
+    pub fn environment(&mut self) -> &Environment {
+        self.environment
+            .get_or_insert_with(|| environment::capture(&self.repo_root, &self.backends))
+    }
+
+    /// Run a future on the runner's runtime, such as waiting for file changes between runs.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// Whether the wall-time budget is used up, so no more steps will run.
+    pub fn out_of_time(&self) -> bool {
+        self.budget
+            .max_wall_time
+            .is_some_and(|budget| self.started.elapsed() >= budget)
+    }
+
+    /// Run steps concurrently; each step's commands run in order. When more than one step runs,
+    /// output is prefixed with the step's backend. Ctrl-C cancels every unfinished step.
+    ///
+    /// The output of test and coverage steps is also saved to `.kit/last-run/<backend>.log`,
+    /// replacing the previous run's, for `kit failures`. When a backend has several steps, they are
+    /// told apart as `<backend>.1`, `<backend>.2`, and so on.
+    ///
+    /// At most `jobs` steps run at once when set. With a [`Budget`], at most `max_concurrent_heavy`
+    /// build, check, test, and coverage steps run at once. Steps over either limit wait in order.
+    /// Once `max_wall_time` has passed since the runner was created, running steps are stopped and
+    /// the rest skipped, each failing with a message saying so.
+    ///
+    /// A step whose tool is missing, whether a backend found that while planning it or the tool
+    /// failed to start, is skipped or fails according to the [`MissingTools`] policy.
+    ///
+    /// A step that passes but takes longer than its [`TargetBudgets`] allow produces a warning, or
+    /// fails if the budgets say so.
+    ///
+    /// A reporter that fails only produces a warning; it never changes a step's result.
+    pub fn execute(&mut self, steps: Vec<(Step, Vec<Invocation>)>) -> Result<()> {
+        let mut errors: Vec<anyhow::Error> = self.execute_each(steps)?.into_iter().filter_map(Result::err).collect();
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            n => {
+                for e in &errors {
+                    eprintln!("kit: {e:#}");
+                }
+                anyhow::bail!("{n} steps failed")
+            }
+        }
+    }
+
+    /// Like [`Runner::execute`], but returns each step's result, in order, instead of combining
+    /// them. Fails only if interrupted.
+    pub fn execute_each(&mut self, mut steps: Vec<(Step, Vec<Invocation>)>) -> Result<Vec<Result<()>>> {
+        for invocation in steps.iter_mut().flat_map(|(_, plan)| plan) {
+            invocation.env.extend(self.env.iter().cloned());
+        }
+        let repo_root = &self.repo_root;
+        let logged = |step: &Step| step.command == "test" || step.command == "coverage";
+        let log_dir = if steps.iter().any(|(step, _)| logged(step)) {
+            let dir = repo_root.join(state::DIR).join(LAST_RUN);
+            if dir.exists() {
+                std::fs::remove_dir_all(&dir).with_context(|| format!("failed to clear {}", dir.display()))?;
+            }
+            Some(state::dir(repo_root, LAST_RUN)?)
+        } else {
+            None
+        };
+        let names = step_names(&steps);
+        if let Some(dir) = &log_dir {
+            let environment = self.environment();
+            write_manifest(dir, &steps, &names, environment)?;
+        }
+        let Self {
+            reporters,
+            runtime,
+            backends,
+            started,
+            budget,
+            jobs,
+            missing_tools,
+            target_budgets,
+            timings,
+            ..
+        } = self;
+        let start = Instant::now();
+        let mut results: Vec<Option<Result<()>>> = steps.iter().map(|_| None).collect();
+        for (i, (step, _)) in steps.iter_mut().enumerate() {
+            if let Some(Skip::ToolMissing { tool }) = &step.skip
+                && !missing_tools.skips(&step.command)
+            {
+                let result = Err(KitError::ToolMissing { tool: tool.clone() }.into());
+                step.skip = None;
+                emit(
+                    reporters,
+                    &Event::Finished(step, &Outcome::new(Duration::ZERO, &result)),
+                );
+                results[i] = Some(result);
+            } else if step.skip.is_some() {
+                emit(
+                    reporters,
+                    &Event::Finished(step, &Outcome::new(Duration::ZERO, &Ok(()))),
+                );
+                results[i] = Some(Ok(()));
+            } else {
+                emit(reporters, &Event::Started(step));
+            }
+        }
+        let concurrent = steps.iter().filter(|(step, _)| step.skip.is_none()).count() > 1;
+        let logs: Vec<Option<PathBuf>> = steps
+            .iter()
+            .zip(&names)
+            .map(|((step, _), name)| {
+                let dir = log_dir.as_ref().filter(|_| logged(step))?;
+                Some(dir.join(format!("{name}.log")))
+            })
+            .collect();
+        let heavy: Vec<bool> = steps.iter().map(|(step, _)| is_heavy(step)).collect();
+        let mut spawned = vec![false; steps.len()];
+        let deadline = budget.max_wall_time.map(|budget| *started + budget);
+        let stop = runtime.block_on(async {
+            let mut tasks = JoinSet::new();
+            let mut pending: VecDeque<usize> = (0..steps.len()).filter(|&i| results[i].is_none()).collect();
+            let (mut running, mut running_heavy) = (0, 0);
+            loop {
+                // Start every waiting step the job and heavy-step limits allow, in order.
+                pending.retain(|&i| {
+                    if jobs.is_some_and(|max| running >= max)
+                        || heavy[i] && budget.max_concurrent_heavy.is_some_and(|max| running_heavy >= max)
+                    {
+                        return true;
+                    }
+                    running += 1;
+                    running_heavy += usize::from(heavy[i]);
+                    spawned[i] = true;
+                    let prefix = concurrent.then(|| names[i].clone());
+                    let log = logs[i].clone();
+                    let plan = steps[i].1.clone();
+                    tasks.spawn(async move {
+                        let start = Instant::now();
+                        let result = exec::run_all(plan, prefix, log).await;
+                        (i, start.elapsed(), result)
+                    });
+                    false
+                });
+                let out_of_time = async {
+                    match deadline {
+                        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+                        None => std::future::pending().await,
+                    }
+                };
+                tokio::select! {
+                    next = tasks.join_next() => match next {
+                        Some(Ok((i, duration, mut result))) => {
+                            running -= 1;
+                            running_heavy -= usize::from(heavy[i]);
+                            let step = &mut steps[i].0;
+                            if let Some(tool) = missing_tool(&result)
+                                && missing_tools.skips(&step.command)
+                            {
+                                step.skip = Some(Skip::ToolMissing { tool });
+                                result = Ok(());
+                            }
+                            if result.is_ok() && step.skip.is_none() {
+                                if let Err(e) = over_budget(target_budgets, step, duration) {
+                                    result = Err(e);
+                                } else {
+                                    timings.push((step.clone(), duration));
+                                }
+                            }
+                            let mut outcome = Outcome::new(duration, &result);
+                            outcome.cache = test_cache(backends, &steps[i].0, logs[i].as_deref());
+                            emit(reporters, &Event::Finished(&steps[i].0, &outcome));
+                            results[i] = Some(result);
+                        }
+                        // A panicked step has no result and is reported as failed below. Its slot
+                        // stays taken, which only matters if another one panics.
+                        Some(Err(_)) => {}
+                        None => return Stop::Done,
+                    },
+                    _ = out_of_time => {
+                        tasks.abort_all();
+                        return Stop::OutOfTime;
+                    }
+                    _ = tokio::signal::ctrl_c() => {
+                        tasks.abort_all();
+                        for (i, _) in results.iter().enumerate().filter(|(_, r)| r.is_none()) {
+                            let outcome = Outcome::new(start.elapsed(), &Err(anyhow::anyhow!("cancelled")));
+                            emit(reporters, &Event::Finished(&steps[i].0, &outcome));
+                        }
+                        return Stop::Interrupted;
+                    }
+                }
+            }
+        });
+
+        match stop {
+            Stop::Done => {}
+            Stop::Interrupted => return Err(KitError::Interrupted.into()),
+            Stop::OutOfTime => {
+                let budget = format_duration(budget.max_wall_time.unwrap_or_default());
+                let (mut stopped, mut skipped) = (Vec::new(), Vec::new());
+                for (i, slot) in results.iter_mut().enumerate().filter(|(_, r)| r.is_none()) {
+                    let (list, message) = if spawned[i] {
+                        (&mut stopped, "stopped: the wall-time budget")
+                    } else {
+                        (&mut skipped, "not started: the wall-time budget")
+                    };
+                    list.push(format!("{} {}", steps[i].0.backend, steps[i].0.command));
+                    let result = Err(anyhow::anyhow!("{message} of {budget} is used up"));
+                    emit(
+                        reporters,
+                        &Event::Finished(&steps[i].0, &Outcome::new(start.elapsed(), &result)),
+                    );
+                    *slot = Some(result);
+                }
+                eprintln!("kit: the wall-time budget of {budget} is used up");
+                if !stopped.is_empty() {
+                    eprintln!("kit: stopped: {}", stopped.join(", "));
+                }
+                if !skipped.is_empty() {
+                    eprintln!("kit: did not run: {}", skipped.join(", "));
+                }
+            }
+        }
+        Ok(results
+            .into_iter()
+            .map(|r| r.unwrap_or_else(|| Err(anyhow::anyhow!("step panicked"))))
+            .collect())
+    }
+
+    /// Record how long the passing steps took and list the largest slowdowns against their
+    /// previous runs, then let reporters write out anything they buffered. Call once, after the last
+    /// step.
+    pub fn finish(mut self) {
+        if !self.timings.is_empty() {
+            match timing::record(&self.repo_root, &self.timings) {
+                Ok(regressions) if !regressions.is_empty() => {
+                    eprintln!("kit: slower than usual:");
+                    for r in regressions {
+                        eprintln!(
+                            "kit:   {} took {:.1}s, {:.1}s on average",
+                            r.step,
+                            r.duration.as_secs_f64(),
+                            r.average.as_secs_f64()
+                        );
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("kit: warning: failed to record step durations: {e:#}"),
+            }
+        }
+        for reporter in &mut self.reporters {
+            if let Err(e) = reporter.finish() {
+                eprintln!("kit: warning: reporter failed: {e:#}");
+            }
+        }
+    }
+}
+
+/// Whether a step counts against `max_concurrent_heavy`.
+fn is_heavy(step: &Step) -> bool {
+    matches!(step.command.as_str(), "build" | "check" | "test" | "coverage")
+}
+
+/// The cached test results the step's backend finds in its log.
+fn test_cache(backends: &[Box<dyn Backend>], step: &Step, log: Option<&Path>) -> Option<TestCache> {
+    let output = std::fs::read_to_string(log?).ok()?;
+    backends.iter().find(|b| b.name() == step.backend)?.test_cache(&output)
+}
+
+/// Record what the logged run executed in `manifest.json` next to its logs: each step's name (as in
+/// the log file name), command, backend, targets, and shuffle seed, and the environment it ran in.
+fn write_manifest(
+    dir: &Path,
+    steps: &[(Step, Vec<Invocation>)],
+    names: &[String],
+    environment: &Environment,
+) -> Result<()> {
+    let steps: Vec<serde_json::Value> = steps
+        .iter()
+        .zip(names)
+        .map(|((step, _), name)| {
+            serde_json::json!({
+                "name": name,
+                "command": step.command,
+                "backend": step.backend,
+                "targets": step.targets,
+                "seed": step.seed,
+                "variant": step.variant,
+            })
+        })
+        .collect();
+    let path = dir.join(MANIFEST);
+    let text = serde_json::to_string_pretty(&serde_json::json!({ "steps": steps, "environment": environment }))?;
+    std::fs::write(&path, text + "\n").with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Warn about a step that took longer than its targets' budget, or fail it if the budgets say so.
+fn over_budget(budgets: &TargetBudgets, step: &Step, duration: Duration) -> Result<()> {
+    let Some(budget) = budgets.for_step(step).filter(|&budget| duration > budget) else {
+        return Ok(());
+    };
+    let message = format!(
+        "{} took {:.1}s, over its budget of {}",
+        timing::describe(step),
+        duration.as_secs_f64(),
+        format_duration(budget)
+    );
+    if budgets.fail {
+        anyhow::bail!(message);
+    }
+    eprintln!("kit: warning: {message}");
+    Ok(())
+}
+
+/// The tool a failed step could not start because it isn't installed.
+fn missing_tool(result: &Result<()>) -> Option<String> {
+    match KitError::find(result.as_ref().err()?)? {
+        KitError::ToolMissing { tool } => Some(tool.clone()),
+        _ => None,
+    }
+}
+
+/// The name each step's output is prefixed and logged with: its backend, numbered when the backend
+/// has more than one step.
+fn step_names(steps: &[(Step, Vec<Invocation>)]) -> Vec<String> {
+    let mut seen: BTreeMap<&str, usize> = BTreeMap::new();
+    steps
+        .iter()
+        .map(|(step, _)| {
+            let backend = step.backend.as_str();
+            if steps.iter().filter(|(s, _)| s.backend == backend).count() == 1 {
+                return backend.to_string();
+            }
+            let n = seen.entry(backend).or_default();
+            *n += 1;
+            format!("{backend}.{n}")
+        })
+        .collect()
+}
+
+fn emit(reporters: &mut [Box<dyn Reporter>], event: &Event) {
+    for reporter in reporters {
+        if let Err(e) = reporter.event(event) {
+            eprintln!("kit: warning: reporter failed: {e:#}");
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "runner_test.rs"]
+mod tests;
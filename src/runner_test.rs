@@ -0,0 +1,178 @@
+use super::*;
+use crate::backend::Target;
+use crate::report::Skip;
+
+fn step(command: &str, backend: &str) -> Step {
+    Step {
+        command: command.to_string(),
+        backend: backend.to_string(),
+        targets: vec![],
+        seed: None,
+        variant: None,
+        skip: None,
+    }
+}
+
+fn sh(dir: &Path, script: &str) -> Vec<Invocation> {
+    vec![Invocation::new("sh", dir).args(["-c", script])]
+}
+
+#[test]
+fn heavy_steps_wait_for_a_free_slot() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path();
+    let budget = Budget {
+        max_wall_time: None,
+        max_concurrent_heavy: Some(1),
+    };
+    let mut runner = Runner::new(vec![], dir.to_path_buf(), vec![])
+        .unwrap()
+        .with_budget(budget);
+    // The second build only starts once the first is done, so it sees the first's file; the lint
+    // step is not heavy and runs alongside.
+    let results = runner
+        .execute_each(vec![
+            (step("build", "a"), sh(dir, "sleep 0.2; touch first")),
+            (step("build", "b"), sh(dir, "test -e first")),
+            (step("lint", "c"), sh(dir, "test ! -e first")),
+        ])
+        .unwrap();
+    assert!(results.iter().all(Result::is_ok), "{results:?}");
+}
+
+#[test]
+fn steps_beyond_the_job_limit_wait() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path();
+    let mut runner = Runner::new(vec![], dir.to_path_buf(), vec![])
+        .unwrap()
+        .with_jobs(Some(1));
+    let results = runner
+        .execute_each(vec![
+            (step("lint", "a"), sh(dir, "sleep 0.2; touch first")),
+            (step("lint", "b"), sh(dir, "test -e first")),
+        ])
+        .unwrap();
+    assert!(results.iter().all(Result::is_ok), "{results:?}");
+}
+
+#[test]
+fn wall_time_budget_stops_running_steps_and_skips_the_rest() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path();
+    let budget = Budget {
+        max_wall_time: Some(Duration::from_millis(300)),
+        max_concurrent_heavy: Some(1),
+    };
+    let mut runner = Runner::new(vec![], dir.to_path_buf(), vec![])
+        .unwrap()
+        .with_budget(budget);
+    let results = runner
+        .execute_each(vec![
+            (step("build", "a"), sh(dir, "sleep 5")),
+            (step("build", "b"), sh(dir, "true")),
+        ])
+        .unwrap();
+    let errors: Vec<String> = results
+        .iter()
+        .map(|r| format!("{:#}", r.as_ref().unwrap_err()))
+        .collect();
+    assert!(errors[0].starts_with("stopped:"), "{}", errors[0]);
+    assert!(errors[1].starts_with("not started:"), "{}", errors[1]);
+    assert!(runner.out_of_time());
+}
+
+#[test]
+fn steps_over_their_target_budget_fail_when_configured() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path();
+    let budgets = TargetBudgets::new(vec![("./slow/*".to_string(), Duration::from_secs(1))], true);
+    let mut runner = Runner::new(vec![], dir.to_path_buf(), vec![])
+        .unwrap()
+        .with_target_budgets(budgets);
+    let mut slow = step("test", "a");
+    slow.targets = vec![Target {
+        label: "./slow/...".to_string(),
+        dir: dir.join("slow"),
+        meta: Default::default(),
+        files: Vec::new(),
+    }];
+    let results = runner
+        .execute_each(vec![
+            (slow, sh(dir, "sleep 1.2")),
+            (step("test", "b"), sh(dir, "sleep 1.2")),
+        ])
+        .unwrap();
+    let error = format!("{:#}", results[0].as_ref().unwrap_err());
+    assert!(error.contains("over its budget of 1s"), "{error}");
+    assert!(results[1].is_ok());
+    assert_eq!(runner.timings.len(), 1);
+}
+
+#[test]
+fn run_env_overrides_the_steps_own() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path();
+    let mut runner = Runner::new(vec![], dir.to_path_buf(), vec![]).unwrap();
+    runner.add_env(vec![("FLAG".to_string(), "on".to_string())]);
+    let plan = sh(dir, "test \"$FLAG\" = on")
+        .into_iter()
+        .map(|i| i.env("FLAG", "off"))
+        .collect();
+    let results = runner.execute_each(vec![(step("test", "a"), plan)]).unwrap();
+    assert!(results[0].is_ok(), "{results:?}");
+}
+
+#[test]
+fn skipped_steps_do_not_run() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path();
+    let mut runner = Runner::new(vec![], dir.to_path_buf(), vec![]).unwrap();
+    let mut skipped = step("lint", "a");
+    skipped.skip = Some(Skip::NoTargets);
+    let results = runner
+        .execute_each(vec![
+            (skipped, sh(dir, "touch ran")),
+            (step("lint", "b"), sh(dir, "true")),
+        ])
+        .unwrap();
+    assert!(results.iter().all(Result::is_ok), "{results:?}");
+    assert!(!dir.join("ran").exists());
+}
+
+#[test]
+fn missing_tools_skip_or_fail_steps_by_policy() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path();
+    let missing = || vec![Invocation::new("kit-no-such-linter", dir)];
+    let mut planned = step("lint", "c");
+    planned.skip = Some(Skip::ToolMissing {
+        tool: "buildifier".to_string(),
+    });
+    let steps = || {
+        vec![
+            (step("lint", "a"), missing()),
+            (step("test", "b"), missing()),
+            (planned.clone(), vec![]),
+        ]
+    };
+    let ok = |results: &[Result<()>]| results.iter().map(Result::is_ok).collect::<Vec<_>>();
+
+    let mut runner = Runner::new(vec![], dir.to_path_buf(), vec![]).unwrap();
+    assert_eq!(ok(&runner.execute_each(steps()).unwrap()), [true, false, true]);
+
+    let mut runner = Runner::new(vec![], dir.to_path_buf(), vec![])
+        .unwrap()
+        .with_missing_tools(MissingTools::Strict);
+    let results = runner.execute_each(steps()).unwrap();
+    assert_eq!(ok(&results), [false, false, false]);
+    assert!(matches!(
+        KitError::find(results[2].as_ref().unwrap_err()),
+        Some(KitError::ToolMissing { tool }) if tool == "buildifier"
+    ));
+
+    let mut runner = Runner::new(vec![], dir.to_path_buf(), vec![])
+        .unwrap()
+        .with_missing_tools(MissingTools::Skip);
+    assert_eq!(ok(&runner.execute_each(steps()).unwrap()), [true, true, true]);
+}
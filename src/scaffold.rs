@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+
+use crate::backend::BazelBackend;
+use crate::config::NewConfig;
+use crate::error::KitError;
+
+const GO_SOURCE: &str = "package {{package}}\n";
+
+const GO_TEST: &str = "package {{package}}
+
+import \"testing\"
+
+func TestPlaceholder(t *testing.T) {
+\tt.Skip(\"no tests yet\")
+}
+";
+
+const PACKAGE_JSON: &str = r#"{
+  "name": "{{npm_name}}",
+  "version": "0.0.0",
+  "private": true,
+  "main": "src/index.js",
+  "scripts": {
+    "test": "node --test"
+  }
+}
+"#;
+
+const INDEX_JS: &str = "module.exports = {};\n";
+
+const INDEX_TEST_JS: &str = "const test = require(\"node:test\");
+
+test.skip(\"no tests yet\");
+";
+
+/// Built-in templates: files to create, relative to the new package, before placeholders are
+/// filled in.
+fn builtin(name: &str) -> Option<Vec<(&'static str, &'static str)>> {
+    match name {
+        "go" | "bazel" => Some(vec![("{{package}}.go", GO_SOURCE), ("{{package}}_test.go", GO_TEST)]),
+        "npm" | "pnpm" | "yarn" => Some(vec![
+            ("package.json", PACKAGE_JSON),
+            ("src/index.js", INDEX_JS),
+            ("test/index.test.js", INDEX_TEST_JS),
+        ]),
+        _ => None,
+    }
+}
+
+/// Create a package at `path` (relative to the repo root) from the template `name`: the repo's own
+/// template of that name if it has one, otherwise a built-in one. `bazel` also runs gazelle to
+/// write the package's BUILD file.
+pub fn new(repo_root: &Path, config: &NewConfig, name: &str, path: &Path) -> Result<()> {
+    if path.as_os_str().is_empty() || path.components().any(|c| !matches!(c, Component::Normal(_))) {
+        return Err(
+            KitError::ConfigError(format!("{} is not a new directory in the repository", path.display())).into(),
+        );
+    }
+    let dir = repo_root.join(path);
+    if dir.read_dir().is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(KitError::ConfigError(format!("{} already exists and is not empty", path.display())).into());
+    }
+    let files = match repo_template(repo_root, config, name)? {
+        Some(files) => files,
+        None => match builtin(name) {
+            Some(files) => files.into_iter().map(|(p, c)| (p.to_string(), c.to_string())).collect(),
+            None => return Err(unknown(repo_root, config, name).into()),
+        },
+    };
+    let values = placeholders(repo_root, path);
+    for (file, contents) in files {
+        let file = path.join(render(&file, &values));
+        let full = repo_root.join(&file);
+        if let Some(parent) = full.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        std::fs::write(&full, render(&contents, &values))
+            .with_context(|| format!("failed to write {}", full.display()))?;
+        eprintln!("kit: created {}", file.display());
+    }
+    if name == "bazel" {
+        gazelle(repo_root, path)?;
+    }
+    Ok(())
+}
+
+/// The files of the repo's template `name`, with their paths relative to the template directory.
+fn repo_template(repo_root: &Path, config: &NewConfig, name: &str) -> Result<Option<Vec<(String, String)>>> {
+    let Some(templates) = &config.templates else {
+        return Ok(None);
+    };
+    let dir = repo_root.join(templates).join(name);
+    if !dir.is_dir() {
+        return Ok(None);
+    }
+    let mut files = Vec::new();
+    let mut pending = vec![dir.clone()];
+    while let Some(next) = pending.pop() {
+        for entry in std::fs::read_dir(&next).with_context(|| format!("failed to read {}", next.display()))? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let contents =
+                std::fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+            let rel = path.strip_prefix(&dir).unwrap_or(&path);
+            files.push((rel.to_string_lossy().replace('\\', "/"), contents));
+        }
+    }
+    files.sort();
+    Ok(Some(files))
+}
+
+fn unknown(repo_root: &Path, config: &NewConfig, name: &str) -> KitError {
+    let mut names = vec!["go", "bazel", "npm", "pnpm", "yarn"]
+        .into_iter()
+        .map(String::from)
+        .collect::<Vec<_>>();
+    if let Some(templates) = &config.templates
+        && let Ok(entries) = std::fs::read_dir(repo_root.join(templates))
+    {
+        names.extend(
+            entries
+                .flatten()
+                .filter(|e| e.path().is_dir())
+                .map(|e| e.file_name().to_string_lossy().into_owned()),
+        );
+    }
+    names.sort();
+    names.dedup();
+    KitError::ConfigError(format!(
+        "unknown template `{name}`, expected one of {}",
+        names.join(", ")
+    ))
+}
+
+/// Values for the placeholders in template paths and contents:
+/// - `{{path}}`: the package's path relative to the repo root
+/// - `{{name}}`: its directory name
+/// - `{{package}}`: the name as a Go package name (lowercase letters and digits)
+/// - `{{npm_name}}`: the name under the root package.json's scope, if it has one
+fn placeholders(repo_root: &Path, path: &Path) -> BTreeMap<&'static str, String> {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let package: String = name
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect();
+    let scope = std::fs::read_to_string(repo_root.join("package.json"))
+        .ok()
+        .and_then(|text| serde_json::from_str::<serde_json::Value>(&text).ok())
+        .and_then(|root| root.get("name")?.as_str().map(String::from))
+        .and_then(|root| Some(root.strip_prefix('@')?.split('/').next()?.to_string()));
+    let npm_name = match scope {
+        Some(scope) => format!("@{scope}/{name}"),
+        None => name.clone(),
+    };
+    BTreeMap::from([
+        ("path", path.to_string_lossy().replace('\\', "/")),
+        ("name", name),
+        ("package", package),
+        ("npm_name", npm_name),
+    ])
+}
+
+/// `text` with each `{{key}}` replaced by its value.
+fn render(text: &str, values: &BTreeMap<&'static str, String>) -> String {
+    values.iter().fold(text.to_string(), |text, (key, value)| {
+        text.replace(&format!("{{{{{key}}}}}"), value)
+    })
+}
+
+/// Write the new package's BUILD file with the repo's gazelle target.
+fn gazelle(repo_root: &Path, path: &Path) -> Result<()> {
+    let program = BazelBackend::bazel_cmd();
+    eprintln!("kit: running gazelle on {}", path.display());
+    let status = Command::new(program)
+        .args(["run", "//:gazelle", "--"])
+        .arg(path)
+        .current_dir(repo_root)
+        .status()
+        .map_err(|e| KitError::spawn(program, e))?;
+    KitError::check(program, status)
+}
+
+#[cfg(test)]
+#[path = "scaffold_test.rs"]
+mod tests;
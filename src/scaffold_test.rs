@@ -0,0 +1,56 @@
+use super::*;
+use std::path::PathBuf;
+
+#[test]
+fn placeholders_derive_names_from_the_path() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("package.json"), r#"{"name": "@acme/monorepo"}"#).unwrap();
+    let values = placeholders(tmp.path(), Path::new("pkg/rate-limit"));
+    assert_eq!(values["path"], "pkg/rate-limit");
+    assert_eq!(values["package"], "ratelimit");
+    assert_eq!(values["npm_name"], "@acme/rate-limit");
+    assert_eq!(
+        render("{{package}}_test.go in {{path}}, {not} {{unknown}}", &values),
+        "ratelimit_test.go in pkg/rate-limit, {not} {{unknown}}"
+    );
+}
+
+#[test]
+fn go_packages_get_a_source_and_a_test_stub() {
+    let tmp = tempfile::tempdir().unwrap();
+    new(tmp.path(), &NewConfig::default(), "go", Path::new("pkg/auth")).unwrap();
+    let test = std::fs::read_to_string(tmp.path().join("pkg/auth/auth_test.go")).unwrap();
+    assert!(test.starts_with("package auth\n"));
+    assert!(tmp.path().join("pkg/auth/auth.go").is_file());
+}
+
+#[test]
+fn repo_templates_replace_built_in_ones() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    std::fs::create_dir_all(root.join("tools/templates/go/internal")).unwrap();
+    std::fs::write(
+        root.join("tools/templates/go/internal/{{package}}.go"),
+        "package {{package}} // {{path}}\n",
+    )
+    .unwrap();
+    let config = NewConfig {
+        templates: Some(PathBuf::from("tools/templates")),
+    };
+    new(root, &config, "go", Path::new("svc/billing")).unwrap();
+    let source = std::fs::read_to_string(root.join("svc/billing/internal/billing.go")).unwrap();
+    assert_eq!(source, "package billing // svc/billing\n");
+    assert!(!root.join("svc/billing/billing_test.go").exists());
+}
+
+#[test]
+fn existing_directories_and_unknown_templates_are_refused() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    std::fs::create_dir_all(root.join("pkg/auth")).unwrap();
+    std::fs::write(root.join("pkg/auth/auth.go"), "package auth\n").unwrap();
+    assert!(new(root, &NewConfig::default(), "go", Path::new("pkg/auth")).is_err());
+    assert!(new(root, &NewConfig::default(), "go", Path::new("../outside")).is_err());
+    let err = new(root, &NewConfig::default(), "rust", Path::new("crates/x")).unwrap_err();
+    assert!(err.to_string().contains("expected one of bazel, go, npm, pnpm, yarn"));
+}
@@ -0,0 +1,368 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::backend::{Backend, Target, which_exists};
+use crate::error::KitError;
+
+/// How serious a vulnerability is, most serious first, so sorting puts it at the top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Critical,
+    High,
+    Medium,
+    Low,
+    /// The scanner doesn't rate it, as with the Go vulnerability database.
+    Unknown,
+}
+
+impl Severity {
+    /// A severity as scanners name it: "critical", "high", "moderate" or "medium", "low".
+    pub fn parse(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "critical" => Self::Critical,
+            "high" => Self::High,
+            "moderate" | "medium" => Self::Medium,
+            "low" => Self::Low,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// The CVSS rating of a base score.
+    pub fn from_score(score: f64) -> Self {
+        match score {
+            s if s >= 9.0 => Self::Critical,
+            s if s >= 7.0 => Self::High,
+            s if s >= 4.0 => Self::Medium,
+            s if s > 0.0 => Self::Low,
+            _ => Self::Unknown,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Critical => "critical",
+            Self::High => "high",
+            Self::Medium => "medium",
+            Self::Low => "low",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// A known vulnerability in a dependency of the repo, for `kit audit`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Vulnerability {
+    /// Advisory ID (e.g. "GO-2024-2687", "GHSA-xxxx-xxxx-xxxx", "RUSTSEC-2023-0071").
+    pub id: String,
+    pub package: String,
+    /// The version in use, or the affected range when the scanner doesn't say.
+    pub version: String,
+    pub severity: Severity,
+    pub summary: String,
+    /// The version, or range, that fixes it, when there is one.
+    pub fixed: Option<String>,
+    /// Directory of the module or package that depends on it, relative to the repo root.
+    pub dir: PathBuf,
+}
+
+/// A vulnerability and the scanner's backend, or `cargo` or `osv-scanner`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Row {
+    pub backend: String,
+    #[serde(flatten)]
+    pub vulnerability: Vulnerability,
+}
+
+/// The output of a scanner that exits non-zero when it finds something. Only a run without output
+/// is a failure.
+pub fn scanner_output(program: &str, args: &[String], dir: &Path) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| KitError::spawn(program, e))?;
+    let stdout = String::from_utf8(output.stdout).with_context(|| format!("invalid utf-8 from {program}"))?;
+    if !output.status.success() && stdout.trim().is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("{program} failed: {}", stderr.trim());
+    }
+    Ok(stdout)
+}
+
+/// Scan the targets of each backend with its own scanner, or with OSV-Scanner for backends without
+/// one, and the repo's Rust crates with `cargo audit` when `paths` (changed files or named
+/// directories) touch them. Sorted by severity, most serious first. Scanners that aren't installed
+/// are skipped with a message.
+pub fn scan(repo_root: &Path, targets: &[(&dyn Backend, Vec<Target>)], paths: &[PathBuf]) -> Result<Vec<Row>> {
+    let mut rows = Vec::new();
+    let mut add = |backend: &str, found: Result<Vec<Vulnerability>>| -> Result<()> {
+        match found {
+            Ok(found) => rows.extend(found.into_iter().map(|vulnerability| Row {
+                backend: backend.to_string(),
+                vulnerability,
+            })),
+            Err(e) => match KitError::find(&e) {
+                Some(KitError::ToolMissing { tool }) => eprintln!("kit: {tool} isn't installed, skipping {backend}"),
+                _ => return Err(e.context(format!("failed to audit {backend} dependencies"))),
+            },
+        }
+        Ok(())
+    };
+    for (backend, targets) in targets {
+        if targets.is_empty() {
+            continue;
+        }
+        match backend.audit(repo_root, targets) {
+            Ok(Some(found)) => add(backend.name(), Ok(found))?,
+            Ok(None) if which_exists("osv-scanner") => add(backend.name(), osv_scan(repo_root, targets))?,
+            Ok(None) => eprintln!(
+                "kit: {} has no vulnerability scanner and osv-scanner isn't installed, skipping it",
+                backend.name()
+            ),
+            Err(e) => add(backend.name(), Err(e))?,
+        }
+    }
+    if repo_root.join("Cargo.lock").is_file() && touches_rust(repo_root, paths) {
+        add("cargo", cargo_audit(repo_root))?;
+    }
+    rows.sort_by(|a, b| {
+        let key = |r: &Row| {
+            (
+                r.vulnerability.severity,
+                r.vulnerability.package.clone(),
+                r.vulnerability.id.clone(),
+            )
+        };
+        key(a).cmp(&key(b))
+    });
+    Ok(rows)
+}
+
+/// The rows as a table with aligned columns under a header, the advisory's summary last.
+pub fn table(rows: &[Row]) -> String {
+    let mut lines = vec![[
+        "SEVERITY".to_string(),
+        "ID".to_string(),
+        "PACKAGE".to_string(),
+        "VERSION".to_string(),
+        "FIXED".to_string(),
+        "BACKEND".to_string(),
+        "SUMMARY".to_string(),
+    ]];
+    for row in rows {
+        let v = &row.vulnerability;
+        lines.push([
+            v.severity.name().to_string(),
+            v.id.clone(),
+            v.package.clone(),
+            v.version.clone(),
+            v.fixed.clone().unwrap_or_else(|| "-".to_string()),
+            row.backend.clone(),
+            v.summary.clone(),
+        ]);
+    }
+    let mut widths = [0; 7];
+    for line in &lines {
+        for (width, cell) in widths.iter_mut().zip(line) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let mut out = String::new();
+    for line in &lines {
+        let cells: Vec<String> = line
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Whether any of `paths` is a Rust source, a Cargo manifest or lock file, or a directory with a
+/// Cargo.toml.
+pub fn touches_rust(repo_root: &Path, paths: &[PathBuf]) -> bool {
+    paths.iter().any(|p| {
+        let name = p.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        name.ends_with(".rs")
+            || name == "Cargo.toml"
+            || name == "Cargo.lock"
+            || repo_root.join(p).join("Cargo.toml").is_file()
+    })
+}
+
+fn osv_scan(repo_root: &Path, targets: &[Target]) -> Result<Vec<Vulnerability>> {
+    let mut args = vec!["--format".to_string(), "json".to_string(), "--recursive".to_string()];
+    args.extend(targets.iter().map(|t| t.dir.to_string_lossy().into_owned()));
+    osv_vulnerabilities(repo_root, &scanner_output("osv-scanner", &args, repo_root)?)
+}
+
+#[derive(Deserialize)]
+struct OsvReport {
+    #[serde(default)]
+    results: Vec<OsvResult>,
+}
+
+#[derive(Deserialize)]
+struct OsvResult {
+    source: OsvSource,
+    #[serde(default)]
+    packages: Vec<OsvPackage>,
+}
+
+#[derive(Deserialize)]
+struct OsvSource {
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct OsvPackage {
+    package: OsvPackageInfo,
+    #[serde(default)]
+    vulnerabilities: Vec<OsvEntry>,
+    #[serde(default)]
+    groups: Vec<OsvGroup>,
+}
+
+#[derive(Deserialize)]
+struct OsvPackageInfo {
+    name: String,
+    #[serde(default)]
+    version: String,
+}
+
+#[derive(Deserialize)]
+struct OsvEntry {
+    id: String,
+    #[serde(default)]
+    summary: String,
+}
+
+/// Advisories OSV-Scanner considers the same vulnerability, with the highest CVSS score among them.
+#[derive(Deserialize)]
+struct OsvGroup {
+    ids: Vec<String>,
+    #[serde(default)]
+    max_severity: String,
+}
+
+/// One vulnerability per advisory group in `osv-scanner --format json` output, named by the first
+/// ID of the group, in the directory of the lock file it was found through.
+fn osv_vulnerabilities(repo_root: &Path, json: &str) -> Result<Vec<Vulnerability>> {
+    if json.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    let report: OsvReport = serde_json::from_str(json).context("invalid JSON from osv-scanner")?;
+    let mut found = Vec::new();
+    for result in report.results {
+        let dir = result
+            .source
+            .path
+            .parent()
+            .map(|d| d.strip_prefix(repo_root).unwrap_or(d).to_path_buf())
+            .unwrap_or_default();
+        for package in result.packages {
+            let summaries: BTreeMap<&str, &str> = package
+                .vulnerabilities
+                .iter()
+                .map(|v| (v.id.as_str(), v.summary.as_str()))
+                .collect();
+            for group in &package.groups {
+                let Some(id) = group.ids.first() else {
+                    continue;
+                };
+                found.push(Vulnerability {
+                    id: id.clone(),
+                    package: package.package.name.clone(),
+                    version: package.package.version.clone(),
+                    severity: group
+                        .max_severity
+                        .parse()
+                        .map_or(Severity::Unknown, Severity::from_score),
+                    summary: group
+                        .ids
+                        .iter()
+                        .find_map(|id| summaries.get(id.as_str()).filter(|s| !s.is_empty()))
+                        .unwrap_or(&"")
+                        .to_string(),
+                    fixed: None,
+                    dir: dir.clone(),
+                });
+            }
+        }
+    }
+    Ok(found)
+}
+
+fn cargo_audit(repo_root: &Path) -> Result<Vec<Vulnerability>> {
+    let args = vec!["audit".to_string(), "--json".to_string()];
+    cargo_vulnerabilities(&scanner_output("cargo", &args, repo_root)?)
+}
+
+#[derive(Deserialize)]
+struct CargoAudit {
+    vulnerabilities: CargoFound,
+}
+
+#[derive(Deserialize)]
+struct CargoFound {
+    #[serde(default)]
+    list: Vec<CargoVulnerability>,
+}
+
+#[derive(Deserialize)]
+struct CargoVulnerability {
+    advisory: CargoAdvisory,
+    package: CargoPackage,
+    #[serde(default)]
+    versions: CargoVersions,
+}
+
+#[derive(Deserialize)]
+struct CargoAdvisory {
+    id: String,
+    #[serde(default)]
+    title: String,
+}
+
+#[derive(Deserialize)]
+struct CargoPackage {
+    name: String,
+    version: String,
+}
+
+#[derive(Deserialize, Default)]
+struct CargoVersions {
+    #[serde(default)]
+    patched: Vec<String>,
+}
+
+/// The vulnerabilities in `cargo audit --json` output. RustSec gives a CVSS vector rather than a
+/// rating, so their severity is unknown.
+fn cargo_vulnerabilities(json: &str) -> Result<Vec<Vulnerability>> {
+    let report: CargoAudit = serde_json::from_str(json).context("invalid JSON from cargo audit")?;
+    Ok(report
+        .vulnerabilities
+        .list
+        .into_iter()
+        .map(|v| Vulnerability {
+            id: v.advisory.id,
+            package: v.package.name,
+            version: v.package.version,
+            severity: Severity::Unknown,
+            summary: v.advisory.title,
+            fixed: (!v.versions.patched.is_empty()).then(|| v.versions.patched.join(", ")),
+            dir: PathBuf::new(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+#[path = "security_test.rs"]
+mod tests;
@@ -0,0 +1,91 @@
+use super::*;
+
+#[test]
+fn severities_sort_most_serious_first() {
+    assert_eq!(Severity::parse("MODERATE"), Severity::Medium);
+    assert_eq!(Severity::parse("info"), Severity::Unknown);
+    assert_eq!(Severity::from_score(9.8), Severity::Critical);
+    assert_eq!(Severity::from_score(7.5), Severity::High);
+    assert_eq!(Severity::from_score(5.3), Severity::Medium);
+    assert_eq!(Severity::from_score(0.0), Severity::Unknown);
+    let mut severities = vec![Severity::Low, Severity::Unknown, Severity::Critical, Severity::Medium];
+    severities.sort();
+    assert_eq!(
+        severities,
+        [Severity::Critical, Severity::Medium, Severity::Low, Severity::Unknown]
+    );
+}
+
+#[test]
+fn osv_scanner_groups_become_one_vulnerability_each() {
+    let json = r#"{"results": [{
+        "source": {"path": "/repo/services/api/requirements.txt", "type": "lockfile"},
+        "packages": [{
+            "package": {"name": "jinja2", "version": "3.1.2", "ecosystem": "PyPI"},
+            "vulnerabilities": [
+                {"id": "GHSA-h5c8-rqwp-cp95", "summary": "Jinja vulnerable to HTML attribute injection"},
+                {"id": "PYSEC-2024-1", "summary": ""}
+            ],
+            "groups": [{"ids": ["PYSEC-2024-1", "GHSA-h5c8-rqwp-cp95"], "max_severity": "5.4"}]
+        }]
+    }]}"#;
+    assert_eq!(
+        osv_vulnerabilities(Path::new("/repo"), json).unwrap(),
+        [Vulnerability {
+            id: "PYSEC-2024-1".to_string(),
+            package: "jinja2".to_string(),
+            version: "3.1.2".to_string(),
+            severity: Severity::Medium,
+            summary: "Jinja vulnerable to HTML attribute injection".to_string(),
+            fixed: None,
+            dir: PathBuf::from("services/api"),
+        }]
+    );
+    assert!(osv_vulnerabilities(Path::new("/repo"), "").unwrap().is_empty());
+}
+
+#[test]
+fn cargo_audit_lists_advisories_with_patched_versions() {
+    let json = r#"{"vulnerabilities": {"found": true, "count": 1, "list": [{
+        "advisory": {"id": "RUSTSEC-2023-0071", "package": "rsa", "title": "Marvin Attack", "cvss": null},
+        "versions": {"patched": [], "unaffected": []},
+        "package": {"name": "rsa", "version": "0.9.6"}
+    }]}}"#;
+    let found = cargo_vulnerabilities(json).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].id, "RUSTSEC-2023-0071");
+    assert_eq!(found[0].version, "0.9.6");
+    assert_eq!(found[0].fixed, None);
+}
+
+#[test]
+fn rust_changes_are_sources_manifests_and_crate_dirs() {
+    let repo = tempfile::tempdir().unwrap();
+    std::fs::create_dir(repo.path().join("cli")).unwrap();
+    std::fs::write(repo.path().join("cli/Cargo.toml"), "").unwrap();
+    assert!(touches_rust(repo.path(), &[PathBuf::from("src/main.rs")]));
+    assert!(touches_rust(repo.path(), &[PathBuf::from("Cargo.lock")]));
+    assert!(touches_rust(repo.path(), &[repo.path().join("cli")]));
+    assert!(!touches_rust(repo.path(), &[PathBuf::from("web/app.ts")]));
+}
+
+#[test]
+fn table_lists_fixes_and_summaries() {
+    let row = Row {
+        backend: "go".to_string(),
+        vulnerability: Vulnerability {
+            id: "GO-2024-2687".to_string(),
+            package: "golang.org/x/net".to_string(),
+            version: "v0.17.0".to_string(),
+            severity: Severity::Unknown,
+            summary: "HTTP/2 CONTINUATION flood".to_string(),
+            fixed: None,
+            dir: PathBuf::new(),
+        },
+    };
+    assert_eq!(
+        table(&[row]),
+        "SEVERITY  ID            PACKAGE           VERSION  FIXED  BACKEND  SUMMARY\n\
+         unknown   GO-2024-2687  golang.org/x/net  v0.17.0  -      go       HTTP/2 CONTINUATION flood\n"
+    );
+}
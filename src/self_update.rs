@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::config::UpdateConfig;
+use crate::error::KitError;
+use crate::hooks;
+
+/// The release manifest and the minisign key its binaries are signed with, when built into kit with
+/// `KIT_UPDATE_URL` and `KIT_UPDATE_PUBLIC_KEY` set at compile time. The user config overrides them.
+const URL: Option<&str> = option_env!("KIT_UPDATE_URL");
+const PUBLIC_KEY: Option<&str> = option_env!("KIT_UPDATE_PUBLIC_KEY");
+
+/// The release manifest at `update.url`.
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    version: String,
+    /// Release binaries by [`platform`].
+    #[serde(default)]
+    assets: BTreeMap<String, Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Asset {
+    url: String,
+    /// Hex SHA-256 of the binary.
+    sha256: String,
+    /// URL of the binary's minisign signature.
+    signature: String,
+}
+
+/// The name release binaries are listed under for the running platform, e.g. `linux-x86_64` or
+/// `macos-aarch64`.
+fn platform() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// Check the release manifest for a newer kit and, unless `check_only`, download the binary for
+/// this platform, verify its checksum and signature, and replace the running executable with it.
+/// `config` must come from the user config alone.
+pub fn run(config: &UpdateConfig, check_only: bool) -> Result<()> {
+    let url = config.url.as_deref().or(URL).ok_or_else(|| {
+        KitError::ConfigError("no release manifest configured (kit config set update.url URL)".to_string())
+    })?;
+    let key = config.public_key.as_deref().or(PUBLIC_KEY).ok_or_else(|| {
+        KitError::ConfigError("no release signing key configured (kit config set update.public_key KEY)".to_string())
+    })?;
+    let manifest: Manifest =
+        serde_json::from_str(&fetch(url)?).with_context(|| format!("invalid release manifest at {url}"))?;
+    let current = env!("CARGO_PKG_VERSION");
+    if !is_newer(&manifest.version, current) {
+        eprintln!("kit: {current} is the latest release");
+        return Ok(());
+    }
+    if check_only {
+        eprintln!("kit: {} is available (running {current})", manifest.version);
+        return Ok(());
+    }
+    let platform = platform();
+    let asset = manifest
+        .assets
+        .get(&platform)
+        .with_context(|| format!("release {} has no binary for {platform}", manifest.version))?;
+    let exe = std::env::current_exe().context("failed to locate the kit executable")?;
+    // Replace the file a symlink such as a Homebrew shim points to, not the link.
+    let exe = exe.canonicalize().unwrap_or(exe);
+    let download = exe.with_file_name(format!(".kit-{}.download", manifest.version));
+    let installed = fetch_verified(key, asset, &download).and_then(|()| replace(&download, &exe));
+    if installed.is_err() {
+        let _ = std::fs::remove_file(&download);
+    }
+    installed?;
+    eprintln!("kit: updated {} from {current} to {}", exe.display(), manifest.version);
+    Ok(())
+}
+
+/// Download the asset to `path` and check it against its checksum and its signature by `key`.
+fn fetch_verified(key: &str, asset: &Asset, path: &Path) -> Result<()> {
+    download(&asset.url, path)?;
+    let contents = std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    if !checksum_matches(&contents, &asset.sha256) {
+        anyhow::bail!("checksum mismatch for {}: expected {}", asset.url, asset.sha256);
+    }
+    let signature = path.with_file_name(format!(
+        "{}.minisig",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    download(&asset.signature, &signature)?;
+    let status = Command::new("minisign")
+        .args(["-V", "-q", "-P", key, "-m"])
+        .arg(path)
+        .arg("-x")
+        .arg(&signature)
+        .status()
+        .map_err(|e| KitError::spawn("minisign", e));
+    let _ = std::fs::remove_file(&signature);
+    if !status?.success() {
+        anyhow::bail!("signature verification failed for {}", asset.url);
+    }
+    Ok(())
+}
+
+/// Whether the SHA-256 of `contents` is the hex digest `expected`, in either case.
+fn checksum_matches(contents: &[u8], expected: &str) -> bool {
+    let digest: String = Sha256::digest(contents).iter().map(|b| format!("{b:02x}")).collect();
+    digest.eq_ignore_ascii_case(expected.trim())
+}
+
+/// Whether `latest` is a later version than `current`, comparing dot-separated numbers. A leading
+/// `v` and pre-release or build suffixes are ignored.
+fn is_newer(latest: &str, current: &str) -> bool {
+    fn parts(version: &str) -> Vec<u64> {
+        let version = version.trim().trim_start_matches('v');
+        let version = version.split(['-', '+']).next().unwrap_or_default();
+        version.split('.').map(|p| p.parse().unwrap_or(0)).collect()
+    }
+    let (mut latest, mut current) = (parts(latest), parts(current));
+    let len = latest.len().max(current.len());
+    latest.resize(len, 0);
+    current.resize(len, 0);
+    latest > current
+}
+
+fn fetch(url: &str) -> Result<String> {
+    let output = Command::new("curl")
+        .args(["-fsSL", url])
+        .output()
+        .map_err(|e| KitError::spawn("curl", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("failed to fetch {url}: {}", stderr.trim());
+    }
+    String::from_utf8(output.stdout).with_context(|| format!("invalid utf-8 from {url}"))
+}
+
+fn download(url: &str, path: &Path) -> Result<()> {
+    let output = Command::new("curl")
+        .args(["-fsSL", "-o"])
+        .arg(path)
+        .arg(url)
+        .output()
+        .map_err(|e| KitError::spawn("curl", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("failed to download {url}: {}", stderr.trim());
+    }
+    Ok(())
+}
+
+/// Move the new binary over the executable. The running process keeps the old file open, so this
+/// is safe mid-run.
+#[cfg(unix)]
+fn replace(new: &Path, exe: &Path) -> Result<()> {
+    hooks::make_executable(new)?;
+    std::fs::rename(new, exe).with_context(|| format!("failed to replace {}", exe.display()))
+}
+
+/// Windows can't overwrite a running executable, but can rename it out of the way first.
+#[cfg(not(unix))]
+fn replace(new: &Path, exe: &Path) -> Result<()> {
+    hooks::make_executable(new)?;
+    let old = exe.with_extension("old");
+    let _ = std::fs::remove_file(&old);
+    std::fs::rename(exe, &old).with_context(|| format!("failed to move {} aside", exe.display()))?;
+    std::fs::rename(new, exe).with_context(|| format!("failed to replace {}", exe.display()))
+}
+
+#[cfg(test)]
+#[path = "self_update_test.rs"]
+mod tests;
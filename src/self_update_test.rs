@@ -0,0 +1,54 @@
+use super::*;
+
+#[test]
+fn versions_compare_numerically() {
+    assert!(is_newer("0.10.0", "0.9.3"));
+    assert!(is_newer("v1.2.1", "1.2.0"));
+    assert!(is_newer("1.3", "1.2.9"));
+    assert!(!is_newer("1.2.0", "1.2"));
+    assert!(!is_newer("1.2.0-rc.1", "1.2.0"));
+    assert!(!is_newer("0.9.9", "1.0.0"));
+}
+
+#[test]
+fn checksums_compare_hex_digests_in_either_case() {
+    let digest = "2CF24DBA5FB0A30E26E83B2AC5B9E29E1B161E5C1FA7425E73043362938B9824";
+    assert!(checksum_matches(b"hello", digest));
+    assert!(checksum_matches(b"hello", &format!("{}\n", digest.to_lowercase())));
+    assert!(!checksum_matches(b"hello!", digest));
+}
+
+#[test]
+fn manifests_list_assets_by_platform() {
+    let manifest = serde_json::from_str::<Manifest>(
+        r#"{
+            "version": "0.2.0",
+            "assets": {
+                "linux-x86_64": {
+                    "url": "https://releases.example.com/kit/0.2.0/kit-linux-x86_64",
+                    "sha256": "abc",
+                    "signature": "https://releases.example.com/kit/0.2.0/kit-linux-x86_64.minisig"
+                },
+                "macos-aarch64": { "url": "https://releases.example.com/kit/0.2.0/kit-macos-aarch64", "sha256": "def" }
+            }
+        }"#,
+    );
+    // Every binary must be signed.
+    assert!(manifest.is_err());
+
+    let manifest: Manifest = serde_json::from_str(
+        r#"{
+            "version": "0.2.0",
+            "assets": {
+                "linux-x86_64": {
+                    "url": "https://releases.example.com/kit/0.2.0/kit-linux-x86_64",
+                    "sha256": "abc",
+                    "signature": "https://releases.example.com/kit/0.2.0/kit-linux-x86_64.minisig"
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(manifest.version, "0.2.0");
+    assert!(manifest.assets["linux-x86_64"].signature.ends_with(".minisig"));
+}
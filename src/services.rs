@@ -0,0 +1,60 @@
+use std::path::{Path, PathBuf};
+
+use crate::config::ServiceConfig;
+use crate::error::KitError;
+
+/// Services with at least one changed file in their directory or declared inputs.
+pub fn affected<'a>(services: &'a [ServiceConfig], changed_files: &[PathBuf]) -> Vec<&'a ServiceConfig> {
+    services
+        .iter()
+        .filter(|s| {
+            let mut paths = std::iter::once(&s.dir).chain(&s.inputs).chain(&s.dockerfile);
+            paths.any(|p| changed_files.iter().any(|f| f.starts_with(p)))
+        })
+        .collect()
+}
+
+/// Look up services by name, failing on unknown names.
+pub fn named<'a>(services: &'a [ServiceConfig], names: &[String]) -> anyhow::Result<Vec<&'a ServiceConfig>> {
+    names
+        .iter()
+        .map(|n| {
+            services
+                .iter()
+                .find(|s| &s.name == n)
+                .ok_or_else(|| anyhow::anyhow!("unknown service `{n}` (not in kit.toml [[services]])"))
+        })
+        .collect()
+}
+
+/// Resolve the services to operate on: those named explicitly, or those affected by the changed files.
+pub fn select<'a>(
+    services: &'a [ServiceConfig],
+    names: &[String],
+    changed_files: impl FnOnce() -> anyhow::Result<Vec<PathBuf>>,
+) -> anyhow::Result<Vec<&'a ServiceConfig>> {
+    if services.is_empty() {
+        return Err(
+            KitError::ConfigError("no services configured; add [[services]] entries to kit.toml".to_string()).into(),
+        );
+    }
+    if names.is_empty() {
+        Ok(affected(services, &changed_files()?))
+    } else {
+        named(services, names)
+    }
+}
+
+impl ServiceConfig {
+    pub fn dockerfile(&self) -> PathBuf {
+        self.dockerfile.clone().unwrap_or_else(|| self.dir.join("Dockerfile"))
+    }
+
+    pub fn context(&self) -> &Path {
+        self.context.as_deref().unwrap_or(&self.dir)
+    }
+}
+
+#[cfg(test)]
+#[path = "services_test.rs"]
+mod tests;
@@ -0,0 +1,45 @@
+use super::*;
+
+fn service(name: &str, dir: &str, inputs: &[&str]) -> ServiceConfig {
+    ServiceConfig {
+        name: name.to_string(),
+        dir: PathBuf::from(dir),
+        inputs: inputs.iter().map(PathBuf::from).collect(),
+        image: None,
+        dockerfile: None,
+        context: None,
+        deploy: Default::default(),
+    }
+}
+
+fn names(services: Vec<&ServiceConfig>) -> Vec<&str> {
+    services.iter().map(|s| s.name.as_str()).collect()
+}
+
+#[test]
+fn affected_by_dir_and_inputs() {
+    let services = vec![
+        service("api", "services/api", &["libs/common"]),
+        service("web", "services/web", &[]),
+        service("worker", "services/worker", &["libs/queue"]),
+    ];
+    let changed = vec![
+        PathBuf::from("libs/common/log.go"),
+        PathBuf::from("services/web/app.ts"),
+    ];
+    assert_eq!(names(affected(&services, &changed)), vec!["api", "web"]);
+}
+
+#[test]
+fn dir_prefix_is_component_wise() {
+    let services = vec![service("api", "services/api", &[])];
+    let changed = vec![PathBuf::from("services/api-gateway/main.go")];
+    assert!(affected(&services, &changed).is_empty());
+}
+
+#[test]
+fn named_rejects_unknown_services() {
+    let services = vec![service("api", "services/api", &[])];
+    assert_eq!(names(named(&services, &["api".to_string()]).unwrap()), vec!["api"]);
+    assert!(named(&services, &["nope".to_string()]).is_err());
+}
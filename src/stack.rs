@@ -0,0 +1,68 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::git;
+
+/// Where a stacked branch's parent was found.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    /// `git config branch.<branch>.kit-parent`.
+    Kit,
+    /// Graphite's `refs/branch-metadata/<branch>`.
+    Graphite,
+    /// git-town's `git config git-town-branch.<branch>.parent`.
+    GitTown,
+}
+
+impl Source {
+    pub fn name(self) -> &'static str {
+        match self {
+            Source::Kit => "branch.<name>.kit-parent",
+            Source::Graphite => "Graphite",
+            Source::GitTown => "git-town",
+        }
+    }
+}
+
+/// The branch the current branch is stacked on, if any stacking tool recorded one and it exists
+/// locally. kit's own setting wins over Graphite's metadata, which wins over git-town's.
+pub fn parent_branch(repo_root: &Path) -> Result<Option<(String, Source)>> {
+    let Some(branch) = git::current_branch(repo_root)? else {
+        return Ok(None);
+    };
+    let candidates = [
+        (
+            git::config_value(repo_root, &format!("branch.{branch}.kit-parent"))?,
+            Source::Kit,
+        ),
+        (
+            git::cat_blob(repo_root, &format!("refs/branch-metadata/{branch}"))?.and_then(|m| graphite_parent(&m)),
+            Source::Graphite,
+        ),
+        (
+            git::config_value(repo_root, &format!("git-town-branch.{branch}.parent"))?,
+            Source::GitTown,
+        ),
+    ];
+    for (parent, source) in candidates {
+        if let Some(parent) = parent
+            && parent != branch
+            && git::branch_exists(repo_root, &parent)?
+        {
+            return Ok(Some((parent, source)));
+        }
+    }
+    Ok(None)
+}
+
+/// The parent branch in Graphite's branch metadata, a JSON object with a `parentBranchName`.
+fn graphite_parent(metadata: &str) -> Option<String> {
+    let metadata: serde_json::Value = serde_json::from_str(metadata).ok()?;
+    let parent = metadata["parentBranchName"].as_str()?;
+    (!parent.is_empty()).then(|| parent.to_string())
+}
+
+#[cfg(test)]
+#[path = "stack_test.rs"]
+mod tests;
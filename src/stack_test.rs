@@ -0,0 +1,10 @@
+use super::*;
+
+#[test]
+fn graphite_parent_reads_branch_metadata() {
+    let metadata = r#"{"parentBranchName":"feat/base","parentBranchRevision":"0123abc"}"#;
+    assert_eq!(graphite_parent(metadata).as_deref(), Some("feat/base"));
+    assert_eq!(graphite_parent(r#"{"parentBranchName":""}"#), None);
+    assert_eq!(graphite_parent("{}"), None);
+    assert_eq!(graphite_parent("not json"), None);
+}
@@ -0,0 +1,33 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+
+/// Name of the directory under the repo root where kit keeps generated files.
+pub const DIR: &str = ".kit";
+
+/// Return `.kit/<name>` under the repo root, creating it if needed.
+///
+/// `.kit/` carries its own `.gitignore` so its contents never show up as changes on the branch.
+pub fn dir(repo_root: &Path, name: &str) -> Result<PathBuf> {
+    let root = repo_root.join(DIR);
+    let path = root.join(name);
+    std::fs::create_dir_all(&path).with_context(|| format!("failed to create {}", path.display()))?;
+    let gitignore = root.join(".gitignore");
+    if !gitignore.exists() {
+        std::fs::write(&gitignore, "*\n").with_context(|| format!("failed to write {}", gitignore.display()))?;
+    }
+    Ok(path)
+}
+
+/// The current time as the filesystem records it, taken by touching a marker file in `dir`.
+///
+/// File timestamps come from a coarser clock than `SystemTime::now()`, so a file written just after
+/// `now()` can appear older than it. Compare file times against this instead.
+pub fn fs_now(dir: &Path) -> Result<SystemTime> {
+    let marker = dir.join(".now");
+    std::fs::write(&marker, "").with_context(|| format!("failed to write {}", marker.display()))?;
+    std::fs::metadata(&marker)
+        .and_then(|m| m.modified())
+        .with_context(|| format!("failed to stat {}", marker.display()))
+}
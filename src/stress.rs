@@ -0,0 +1,99 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::backend::Backend;
+use crate::exec::Invocation;
+use crate::failures::{self, Failure};
+use crate::report::Step;
+use crate::runner::Runner;
+
+/// A test that failed in some of the stress runs.
+#[derive(Debug, PartialEq)]
+pub struct Flake {
+    pub failure: Failure,
+    /// Number of runs it failed in.
+    pub failed: usize,
+}
+
+/// Run the test steps `plan` returns `runs` times, `parallel` runs at a time, then print how often
+/// each failing test failed. `plan` is called for every run, so each can get its own shuffle seed.
+/// Fails if any run failed, or if the wall-time budget ran out before every run did.
+pub fn run(
+    runner: &mut Runner,
+    repo_root: &Path,
+    backends: &[Box<dyn Backend>],
+    plan: impl Fn() -> Vec<(Step, Vec<Invocation>)>,
+    runs: usize,
+    parallel: usize,
+) -> Result<()> {
+    let mut failed_runs = 0;
+    let mut failed_seeds = BTreeSet::new();
+    let mut logs = Vec::new();
+    let mut done = 0;
+    while done < runs {
+        if runner.out_of_time() {
+            eprintln!("kit: the wall-time budget is used up; stopping after {done} of {runs} runs");
+            break;
+        }
+        let batch = parallel.clamp(1, runs - done);
+        let batch: Vec<Vec<(Step, Vec<Invocation>)>> = (0..batch).map(|_| plan()).collect();
+        let sizes: Vec<usize> = batch.iter().map(Vec::len).collect();
+        let steps: Vec<(Step, Vec<Invocation>)> = batch.into_iter().flatten().collect();
+        let seeds: Vec<Option<u64>> = steps.iter().map(|(step, _)| step.seed).collect();
+        let mut results = runner.execute_each(steps)?.into_iter().zip(seeds);
+        for size in &sizes {
+            let mut failed = false;
+            for (result, seed) in results.by_ref().take(*size) {
+                if let Err(e) = result {
+                    eprintln!("kit: {e:#}");
+                    failed = true;
+                    failed_seeds.extend(seed);
+                }
+            }
+            failed_runs += usize::from(failed);
+        }
+        logs.extend(failures::read_logs(repo_root, backends)?);
+        done += sizes.len();
+        eprintln!("kit: {done}/{runs} stress runs done, {failed_runs} failed");
+    }
+
+    for flake in tally(logs) {
+        let f = &flake.failure;
+        let location = f.location().map(|l| format!("{l}: ")).unwrap_or_default();
+        let rate = 100.0 * flake.failed as f64 / done as f64;
+        println!("{}/{done} ({rate:.0}%)  {location}{}", flake.failed, f.test);
+    }
+    if !failed_seeds.is_empty() {
+        let seeds: Vec<String> = failed_seeds.iter().map(u64::to_string).collect();
+        eprintln!("kit: failed runs were shuffled with seeds {}", seeds.join(", "));
+    }
+    if failed_runs > 0 {
+        anyhow::bail!("{failed_runs} of {done} runs failed");
+    }
+    if done < runs {
+        anyhow::bail!("only {done} of {runs} runs fit in the wall-time budget");
+    }
+    eprintln!("kit: all {runs} runs passed");
+    Ok(())
+}
+
+/// Count the logs each test failed in, most frequent first. A test's location is taken from the
+/// first run it failed in.
+pub fn tally(logs: Vec<Vec<Failure>>) -> Vec<Flake> {
+    let mut counts: BTreeMap<(String, Option<PathBuf>), Flake> = BTreeMap::new();
+    for failure in logs.into_iter().flatten() {
+        counts
+            .entry((failure.test.clone(), failure.file.clone()))
+            .or_insert(Flake { failure, failed: 0 })
+            .failed += 1;
+    }
+    let mut flakes: Vec<Flake> = counts.into_values().collect();
+    flakes.sort_by_key(|f| std::cmp::Reverse(f.failed));
+    flakes
+}
+
+#[cfg(test)]
+#[path = "stress_test.rs"]
+mod tests;
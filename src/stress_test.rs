@@ -0,0 +1,34 @@
+use super::*;
+
+fn failure(test: &str, line: u32) -> Failure {
+    Failure {
+        test: test.to_string(),
+        file: Some(PathBuf::from("pkg/a_test.go")),
+        line: Some(line),
+        message: "boom".to_string(),
+    }
+}
+
+#[test]
+fn tally_counts_runs_per_test_most_frequent_first() {
+    let logs = vec![
+        vec![failure("TestA", 3)],
+        vec![failure("TestB", 9), failure("TestA", 4)],
+        vec![],
+        vec![failure("TestB", 9)],
+        vec![failure("TestB", 9)],
+    ];
+    assert_eq!(
+        tally(logs),
+        [
+            Flake {
+                failure: failure("TestB", 9),
+                failed: 3,
+            },
+            Flake {
+                failure: failure("TestA", 3),
+                failed: 2,
+            },
+        ]
+    );
+}
@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::report::Step;
+use crate::state;
+
+/// Step durations kept per repo; older ones are dropped when new ones are recorded.
+const KEEP: usize = 2000;
+
+/// How many of a step's most recent durations its average is taken over.
+const RECENT: usize = 10;
+
+/// Regressions listed after a run, the largest first.
+const TOP: usize = 3;
+
+/// A step counts as a regression when it took at least this much longer than its average, both as a
+/// factor and in absolute time, so sub-second jitter isn't reported.
+const SLOWER_BY: f64 = 1.25;
+const MIN_EXTRA: Duration = Duration::from_secs(1);
+
+/// Expected durations of targets, from `[budget.targets]` in kit.toml.
+#[derive(Debug, Clone, Default)]
+pub struct TargetBudgets {
+    patterns: Vec<(String, Regex, Duration)>,
+    /// Whether a step over its budget fails rather than only producing a warning.
+    pub fail: bool,
+}
+
+impl TargetBudgets {
+    /// Budgets keyed by target label pattern, where `*` matches anything, including `/`.
+    pub fn new(budgets: Vec<(String, Duration)>, fail: bool) -> Self {
+        let patterns = budgets
+            .into_iter()
+            .map(|(pattern, budget)| {
+                let parts: Vec<String> = pattern.split('*').map(regex::escape).collect();
+                let regex = Regex::new(&format!("^{}$", parts.join(".*"))).expect("escaped pattern is valid");
+                (pattern, regex, budget)
+            })
+            .collect();
+        Self { patterns, fail }
+    }
+
+    /// The time a step may take: the sum of its targets' budgets, each from the longest pattern
+    /// matching the target's label. A step with a target no pattern matches has no budget, since
+    /// kit can't tell how much of its time that target took.
+    pub fn for_step(&self, step: &Step) -> Option<Duration> {
+        if step.targets.is_empty() {
+            return None;
+        }
+        step.targets.iter().try_fold(Duration::ZERO, |total, target| {
+            let (_, _, budget) = self
+                .patterns
+                .iter()
+                .filter(|(_, regex, _)| regex.is_match(&target.label))
+                .max_by_key(|(pattern, _, _)| pattern.len())?;
+            Some(total + *budget)
+        })
+    }
+}
+
+/// How long a passing step took in one run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Timing {
+    /// The step's backend, command, matrix variant, and target labels; see [`key`].
+    key: String,
+    millis: u64,
+}
+
+/// A step that took markedly longer than it used to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    /// The step as [`describe`] names it.
+    pub step: String,
+    pub duration: Duration,
+    /// The step's average over its recent runs before this one.
+    pub average: Duration,
+}
+
+fn path(repo_root: &Path) -> Result<PathBuf> {
+    Ok(state::dir(repo_root, "history")?.join("durations.jsonl"))
+}
+
+/// What identifies a step across runs: the same command of a backend on the same targets.
+fn key(step: &Step) -> String {
+    let labels: Vec<&str> = step.targets.iter().map(|t| t.label.as_str()).collect();
+    format!("{} {} {}", step.backend, step.command_label(), labels.join(","))
+}
+
+/// How a step is named in the regressions summary: "go test of ./pkg/api/..." or "go test of 12
+/// targets".
+pub fn describe(step: &Step) -> String {
+    let of = match step.targets.as_slice() {
+        [target] => target.label.clone(),
+        targets => format!("{} targets", targets.len()),
+    };
+    format!("{} {} of {of}", step.backend, step.command_label())
+}
+
+/// Record how long each passing step of a run took, and return the steps that took markedly longer
+/// than their average over previous runs, the largest regression first.
+pub fn record(repo_root: &Path, steps: &[(Step, Duration)]) -> Result<Vec<Regression>> {
+    let path = path(repo_root)?;
+    let text = std::fs::read_to_string(&path).unwrap_or_default();
+    let found = regressions(&averages(&text), steps);
+    let mut lines: Vec<String> = text.lines().map(String::from).collect();
+    for (step, duration) in steps {
+        let timing = Timing {
+            key: key(step),
+            millis: duration.as_millis() as u64,
+        };
+        lines.push(serde_json::to_string(&timing)?);
+    }
+    let kept = &lines[lines.len().saturating_sub(KEEP)..];
+    let mut file = std::fs::File::create(&path).with_context(|| format!("failed to write {}", path.display()))?;
+    for line in kept {
+        writeln!(file, "{line}").with_context(|| format!("failed to write {}", path.display()))?;
+    }
+    Ok(found)
+}
+
+/// Each step's average duration over its [`RECENT`] most recent runs. Unreadable lines are skipped.
+fn averages(history: &str) -> BTreeMap<String, Duration> {
+    let mut recent: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+    for timing in history
+        .lines()
+        .rev()
+        .filter_map(|l| serde_json::from_str::<Timing>(l).ok())
+    {
+        let millis = recent.entry(timing.key).or_default();
+        if millis.len() < RECENT {
+            millis.push(timing.millis);
+        }
+    }
+    recent
+        .into_iter()
+        .map(|(key, millis)| {
+            let average = millis.iter().sum::<u64>() / millis.len() as u64;
+            (key, Duration::from_millis(average))
+        })
+        .collect()
+}
+
+/// The [`TOP`] steps that took the most time over their average, among those slower by both
+/// [`SLOWER_BY`] and [`MIN_EXTRA`].
+fn regressions(averages: &BTreeMap<String, Duration>, steps: &[(Step, Duration)]) -> Vec<Regression> {
+    let mut found: Vec<Regression> = steps
+        .iter()
+        .filter_map(|(step, duration)| {
+            let average = *averages.get(&key(step))?;
+            let slower = duration.as_secs_f64() >= average.as_secs_f64() * SLOWER_BY;
+            (slower && *duration >= average + MIN_EXTRA).then(|| Regression {
+                step: describe(step),
+                duration: *duration,
+                average,
+            })
+        })
+        .collect();
+    found.sort_by_key(|r| std::cmp::Reverse(r.duration - r.average));
+    found.truncate(TOP);
+    found
+}
+
+#[cfg(test)]
+#[path = "timing_test.rs"]
+mod tests;
@@ -0,0 +1,94 @@
+use super::*;
+use crate::backend::{Target, TargetMeta};
+
+fn step(labels: &[&str]) -> Step {
+    Step {
+        command: "test".to_string(),
+        backend: "go".to_string(),
+        targets: labels
+            .iter()
+            .map(|label| Target {
+                label: label.to_string(),
+                dir: PathBuf::from(label),
+                meta: TargetMeta::default(),
+                files: Vec::new(),
+            })
+            .collect(),
+        seed: None,
+        variant: None,
+        skip: None,
+    }
+}
+
+fn secs(n: u64) -> Duration {
+    Duration::from_secs(n)
+}
+
+#[test]
+fn a_step_budget_sums_its_targets_most_specific_budgets() {
+    let budgets = TargetBudgets::new(
+        vec![
+            ("./services/*".to_string(), secs(60)),
+            ("./services/payments/...".to_string(), secs(300)),
+        ],
+        false,
+    );
+    assert_eq!(budgets.for_step(&step(&["./services/payments/..."])), Some(secs(300)));
+    assert_eq!(
+        budgets.for_step(&step(&["./services/api/...", "./services/payments/..."])),
+        Some(secs(360))
+    );
+    assert_eq!(budgets.for_step(&step(&["./services/api/...", "./lib/..."])), None);
+    assert_eq!(budgets.for_step(&step(&[])), None);
+}
+
+#[test]
+fn averages_use_each_steps_recent_runs() {
+    let key = key(&step(&["./a/..."]));
+    let mut history = String::new();
+    history.push_str(&format!("{{\"key\":\"{key}\",\"millis\":100000}}\n"));
+    for _ in 0..RECENT {
+        history.push_str(&format!("{{\"key\":\"{key}\",\"millis\":2000}}\n"));
+    }
+    history.push_str("not json\n");
+    assert_eq!(averages(&history)[&key], Duration::from_millis(2000));
+}
+
+#[test]
+fn only_marked_slowdowns_are_regressions_largest_first() {
+    let averages: BTreeMap<String, Duration> = [
+        (key(&step(&["./a/..."])), secs(10)),
+        (key(&step(&["./b/..."])), secs(10)),
+        (key(&step(&["./c/..."])), secs(10)),
+        (key(&step(&["./d/..."])), Duration::from_millis(100)),
+    ]
+    .into_iter()
+    .collect();
+    let steps = vec![
+        (step(&["./a/..."]), secs(20)),
+        (step(&["./b/..."]), secs(11)),
+        (step(&["./c/..."]), secs(40)),
+        (step(&["./d/..."]), Duration::from_millis(500)),
+        (step(&["./e/..."]), secs(99)),
+    ];
+    let found = regressions(&averages, &steps);
+    let names: Vec<&str> = found.iter().map(|r| r.step.as_str()).collect();
+    assert_eq!(names, ["go test of ./c/...", "go test of ./a/..."]);
+}
+
+#[test]
+fn record_compares_against_earlier_runs() {
+    let repo = tempfile::tempdir().unwrap();
+    let steps = vec![(step(&["./a/...", "./b/..."]), secs(10))];
+    assert!(record(repo.path(), &steps).unwrap().is_empty());
+    let slower = vec![(step(&["./a/...", "./b/..."]), secs(30))];
+    let found = record(repo.path(), &slower).unwrap();
+    assert_eq!(
+        found,
+        [Regression {
+            step: "go test of 2 targets".to_string(),
+            duration: secs(30),
+            average: secs(10),
+        }]
+    );
+}
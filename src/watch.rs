@@ -0,0 +1,93 @@
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+use tokio::sync::mpsc::{UnboundedReceiver, unbounded_channel};
+
+use crate::git;
+use crate::kitignore::KitIgnore;
+
+/// How long the repo must be quiet after a change before kit reruns, so a save touching several
+/// files, or a branch switch, causes one run rather than many.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Directories whose changes are kit's and git's own bookkeeping.
+const OWN_DIRS: &[&str] = &[".git", ".kit"];
+
+/// Watches the repo for changed files.
+pub struct Watcher {
+    repo_root: PathBuf,
+    events: UnboundedReceiver<PathBuf>,
+    // Watching stops when this is dropped.
+    _watcher: RecommendedWatcher,
+}
+
+impl Watcher {
+    pub fn new(repo_root: &Path) -> Result<Self> {
+        let (tx, events) = unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            // Reads, such as a build tool opening sources, change nothing.
+            if let Ok(event) = event
+                && !matches!(event.kind, EventKind::Access(_))
+            {
+                for path in event.paths {
+                    let _ = tx.send(path);
+                }
+            }
+        })
+        .context("failed to start watching files")?;
+        watcher
+            .watch(repo_root, RecursiveMode::Recursive)
+            .with_context(|| format!("failed to watch {}", repo_root.display()))?;
+        Ok(Self {
+            repo_root: repo_root.to_path_buf(),
+            events,
+            _watcher: watcher,
+        })
+    }
+
+    /// The next batch of changed files, relative to the repo root, once the repo has been quiet for
+    /// [`DEBOUNCE`]. Files git ignores, such as build outputs, and those a `.kitignore` excludes
+    /// don't count. `None` when interrupted with Ctrl-C.
+    pub async fn next(&mut self) -> Result<Option<Vec<PathBuf>>> {
+        loop {
+            let first = tokio::select! {
+                path = self.events.recv() => path,
+                _ = tokio::signal::ctrl_c() => None,
+            };
+            let Some(first) = first else {
+                return Ok(None);
+            };
+            let mut paths = vec![first];
+            while let Ok(Some(path)) = tokio::time::timeout(DEBOUNCE, self.events.recv()).await {
+                paths.push(path);
+            }
+            let files: Vec<PathBuf> = in_repo(&self.repo_root, &paths).into_iter().collect();
+            let ignored = git::ignored(&self.repo_root, &files)?;
+            let files = files.into_iter().filter(|f| !ignored.contains(f)).collect();
+            let files = KitIgnore::load(&self.repo_root)?.filter(&self.repo_root, files);
+            if !files.is_empty() {
+                return Ok(Some(files));
+            }
+        }
+    }
+}
+
+/// The watched paths relative to the repo root, without those under [`OWN_DIRS`].
+fn in_repo(repo_root: &Path, paths: &[PathBuf]) -> BTreeSet<PathBuf> {
+    paths
+        .iter()
+        .filter_map(|p| p.strip_prefix(repo_root).ok())
+        .filter(|rel| match rel.components().next() {
+            Some(Component::Normal(first)) => !OWN_DIRS.iter().any(|d| first == *d),
+            _ => false,
+        })
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "watch_test.rs"]
+mod tests;
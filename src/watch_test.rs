@@ -0,0 +1,18 @@
+use super::*;
+
+#[test]
+fn own_directories_and_paths_outside_the_repo_are_dropped() {
+    let root = Path::new("/repo");
+    let paths = [
+        "/repo/pkg/api/handler.go",
+        "/repo/pkg/api/handler.go",
+        "/repo/.git/index.lock",
+        "/repo/.kit/last-run/go.log",
+        "/repo/web/.kit.json",
+        "/repo",
+        "/elsewhere/file.go",
+    ]
+    .map(PathBuf::from);
+    let kept: Vec<PathBuf> = in_repo(root, &paths).into_iter().collect();
+    assert_eq!(kept, ["pkg/api/handler.go", "web/.kit.json"].map(PathBuf::from));
+}